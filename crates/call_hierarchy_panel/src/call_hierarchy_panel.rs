@@ -0,0 +1,688 @@
+mod call_hierarchy_panel_settings;
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use call_hierarchy_panel_settings::{CallHierarchyPanelDockPosition, CallHierarchyPanelSettings};
+use collections::{HashMap, HashSet};
+use db::kvp::KEY_VALUE_STORE;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{
+    actions, div, px, uniform_list, Action, AppContext, AsyncWindowContext, ElementId,
+    EventEmitter, FocusHandle, FocusableView, InteractiveElement, IntoElement, KeyContext, Model,
+    MouseButton, ParentElement, Pixels, Render, SharedString, Stateful, Styled, Subscription, Task,
+    UniformListScrollHandle, View, ViewContext, VisualContext, WeakView, WindowContext,
+};
+use language::{Buffer, OffsetRangeExt, ToPoint};
+use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrev};
+use project::{CallHierarchyItem, Fs, Location, Project};
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use util::{ResultExt, TryFutureExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    ui::{
+        h_flex, v_flex, ActiveTheme, Button, Clickable, Color, FluentBuilder, IconName, Label,
+        LabelCommon, LabelSize, ListItem, Selectable, StyledTypography,
+    },
+    Workspace,
+};
+
+actions!(
+    call_hierarchy_panel,
+    [
+        ToggleFocus,
+        ShowIncomingCalls,
+        ShowOutgoingCalls,
+        ToggleCallDirection,
+        ExpandSelectedEntry,
+        CollapseSelectedEntry,
+        Open,
+    ]
+);
+
+const CALL_HIERARCHY_PANEL_KEY: &str = "CallHierarchyPanel";
+
+/// Whether the panel lists the callers or the callees of its root symbols.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallDirection {
+    Incoming,
+    Outgoing,
+}
+
+type EntryId = usize;
+
+struct Entry {
+    item: CallHierarchyItem,
+    /// The calls between this entry and its parent, always located in the caller.
+    call_sites: Vec<Location>,
+    /// `None` until the calls of this entry were fetched from the language server.
+    children: Option<Vec<EntryId>>,
+}
+
+pub struct CallHierarchyPanel {
+    fs: Arc<dyn Fs>,
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    width: Option<Pixels>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    pending_serialization: Task<Option<()>>,
+    direction: CallDirection,
+    entries: Vec<Entry>,
+    roots: Vec<EntryId>,
+    expanded_entries: HashSet<EntryId>,
+    visible_entries: Vec<(usize, EntryId)>,
+    selected_entry: Option<EntryId>,
+    prepare_task: Task<()>,
+    fetch_tasks: HashMap<EntryId, Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+#[derive(Debug)]
+pub enum Event {
+    Focus,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedCallHierarchyPanel {
+    width: Option<Pixels>,
+}
+
+pub fn init_settings(cx: &mut AppContext) {
+    CallHierarchyPanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut AppContext) {
+    init_settings(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, _: &ToggleFocus, cx| {
+                workspace.toggle_panel_focus::<CallHierarchyPanel>(cx);
+            })
+            .register_action(|workspace, _: &ShowIncomingCalls, cx| {
+                show_call_hierarchy(workspace, CallDirection::Incoming, cx);
+            })
+            .register_action(|workspace, _: &ShowOutgoingCalls, cx| {
+                show_call_hierarchy(workspace, CallDirection::Outgoing, cx);
+            });
+    })
+    .detach();
+}
+
+/// Fills the call hierarchy panel with the calls of the symbol under the newest cursor of the active editor.
+fn show_call_hierarchy(
+    workspace: &mut Workspace,
+    direction: CallDirection,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let Some((buffer, position)) = editor.update(cx, |editor, cx| {
+        let head = editor.selections.newest_anchor().head();
+        editor.buffer().read(cx).text_anchor_for_position(head, cx)
+    }) else {
+        return;
+    };
+    if let Some(panel) = workspace.focus_panel::<CallHierarchyPanel>(cx) {
+        panel.update(cx, |panel, cx| {
+            panel.show_call_hierarchy(buffer, position, direction, cx)
+        });
+    }
+}
+
+impl CallHierarchyPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(CALL_HIERARCHY_PANEL_KEY) })
+            .await
+            .context("loading call hierarchy panel")
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedCallHierarchyPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let panel = Self::new(workspace, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        let workspace_handle = workspace.weak_handle();
+        cx.new_view(|cx| {
+            let focus_handle = cx.focus_handle();
+            let focus_subscription = cx.on_focus(&focus_handle, Self::focus_in);
+
+            let mut call_hierarchy_panel_settings = *CallHierarchyPanelSettings::get_global(cx);
+            let settings_subscription = cx.observe_global::<SettingsStore>(move |_, cx| {
+                let new_settings = *CallHierarchyPanelSettings::get_global(cx);
+                if call_hierarchy_panel_settings != new_settings {
+                    call_hierarchy_panel_settings = new_settings;
+                    cx.notify();
+                }
+            });
+
+            Self {
+                fs,
+                workspace: workspace_handle,
+                project,
+                width: None,
+                focus_handle,
+                scroll_handle: UniformListScrollHandle::new(),
+                pending_serialization: Task::ready(None),
+                direction: CallDirection::Incoming,
+                entries: Vec::new(),
+                roots: Vec::new(),
+                expanded_entries: HashSet::default(),
+                visible_entries: Vec::new(),
+                selected_entry: None,
+                prepare_task: Task::ready(()),
+                fetch_tasks: HashMap::default(),
+                _subscriptions: vec![settings_subscription, focus_subscription],
+            }
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        CALL_HIERARCHY_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedCallHierarchyPanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn dispatch_context(&self, _: &ViewContext<Self>) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("CallHierarchyPanel");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    /// Replaces the panel contents with the call hierarchy of the symbol at the given position.
+    pub fn show_call_hierarchy(
+        &mut self,
+        buffer: Model<Buffer>,
+        position: language::Anchor,
+        direction: CallDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let items = self.project.update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, position, cx)
+        });
+        self.prepare_task = cx.spawn(|panel, mut cx| async move {
+            let items = items.await.log_err().unwrap_or_default();
+            panel
+                .update(&mut cx, |panel, cx| panel.set_roots(items, direction, cx))
+                .ok();
+        });
+    }
+
+    fn set_roots(
+        &mut self,
+        items: Vec<CallHierarchyItem>,
+        direction: CallDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.direction = direction;
+        self.entries.clear();
+        self.expanded_entries.clear();
+        self.fetch_tasks.clear();
+        self.roots = items
+            .into_iter()
+            .map(|item| self.push_entry(item, Vec::new()))
+            .collect();
+        self.selected_entry = self.roots.first().copied();
+        for root in self.roots.clone() {
+            self.expand_entry(root, cx);
+        }
+        self.update_visible_entries(cx);
+    }
+
+    fn push_entry(&mut self, item: CallHierarchyItem, call_sites: Vec<Location>) -> EntryId {
+        self.entries.push(Entry {
+            item,
+            call_sites,
+            children: None,
+        });
+        self.entries.len() - 1
+    }
+
+    fn toggle_call_direction(&mut self, _: &ToggleCallDirection, cx: &mut ViewContext<Self>) {
+        let direction = match self.direction {
+            CallDirection::Incoming => CallDirection::Outgoing,
+            CallDirection::Outgoing => CallDirection::Incoming,
+        };
+        self.set_direction(direction, cx);
+    }
+
+    fn set_direction(&mut self, direction: CallDirection, cx: &mut ViewContext<Self>) {
+        if self.direction == direction {
+            return;
+        }
+        let root_items = self
+            .roots
+            .iter()
+            .map(|root| self.entries[*root].item.clone())
+            .collect();
+        self.set_roots(root_items, direction, cx);
+    }
+
+    fn expand_entry(&mut self, entry_id: EntryId, cx: &mut ViewContext<Self>) {
+        self.expanded_entries.insert(entry_id);
+        if self.entries[entry_id].children.is_none() && !self.fetch_tasks.contains_key(&entry_id) {
+            let item = self.entries[entry_id].item.clone();
+            let calls = self.project.update(cx, |project, cx| match self.direction {
+                CallDirection::Incoming => project.incoming_calls(&item, cx),
+                CallDirection::Outgoing => project.outgoing_calls(&item, cx),
+            });
+            let fetch_task = cx.spawn(|panel, mut cx| async move {
+                let calls = calls.await.log_err().unwrap_or_default();
+                panel
+                    .update(&mut cx, |panel, cx| {
+                        panel.fetch_tasks.remove(&entry_id);
+                        let children = calls
+                            .into_iter()
+                            .map(|call| panel.push_entry(call.item, call.call_sites))
+                            .collect();
+                        panel.entries[entry_id].children = Some(children);
+                        panel.update_visible_entries(cx);
+                    })
+                    .ok();
+            });
+            self.fetch_tasks.insert(entry_id, fetch_task);
+        }
+        self.update_visible_entries(cx);
+    }
+
+    fn collapse_entry(&mut self, entry_id: EntryId, cx: &mut ViewContext<Self>) {
+        self.expanded_entries.remove(&entry_id);
+        self.update_visible_entries(cx);
+    }
+
+    fn toggle_expanded(&mut self, entry_id: EntryId, cx: &mut ViewContext<Self>) {
+        if self.expanded_entries.contains(&entry_id) {
+            self.collapse_entry(entry_id, cx);
+        } else {
+            self.expand_entry(entry_id, cx);
+        }
+    }
+
+    fn update_visible_entries(&mut self, cx: &mut ViewContext<Self>) {
+        self.visible_entries.clear();
+        let mut stack = self
+            .roots
+            .iter()
+            .rev()
+            .map(|root| (0, *root))
+            .collect::<Vec<_>>();
+        while let Some((depth, entry_id)) = stack.pop() {
+            self.visible_entries.push((depth, entry_id));
+            if self.expanded_entries.contains(&entry_id) {
+                if let Some(children) = &self.entries[entry_id].children {
+                    stack.extend(children.iter().rev().map(|child| (depth + 1, *child)));
+                }
+            }
+        }
+        cx.notify();
+    }
+
+    fn expand_selected_entry(&mut self, _: &ExpandSelectedEntry, cx: &mut ViewContext<Self>) {
+        if let Some(entry_id) = self.selected_entry {
+            if self.expanded_entries.contains(&entry_id) {
+                self.select_next(&SelectNext, cx);
+            } else {
+                self.expand_entry(entry_id, cx);
+            }
+        }
+    }
+
+    fn collapse_selected_entry(&mut self, _: &CollapseSelectedEntry, cx: &mut ViewContext<Self>) {
+        let Some(entry_id) = self.selected_entry else {
+            return;
+        };
+        if self.expanded_entries.contains(&entry_id) {
+            self.collapse_entry(entry_id, cx);
+        } else if let Some(parent_id) = self.parent_entry(entry_id) {
+            self.selected_entry = Some(parent_id);
+            self.collapse_entry(parent_id, cx);
+            self.autoscroll(cx);
+        }
+    }
+
+    fn parent_entry(&self, entry_id: EntryId) -> Option<EntryId> {
+        self.entries.iter().position(|entry| {
+            entry
+                .children
+                .as_ref()
+                .map_or(false, |children| children.contains(&entry_id))
+        })
+    }
+
+    fn select_next(&mut self, _: &SelectNext, cx: &mut ViewContext<Self>) {
+        let next_ix = self.selected_index().map_or(0, |ix| {
+            (ix + 1).min(self.visible_entries.len().saturating_sub(1))
+        });
+        self.select_index(next_ix, cx);
+    }
+
+    fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
+        let prev_ix = self.selected_index().map_or(0, |ix| ix.saturating_sub(1));
+        self.select_index(prev_ix, cx);
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, cx: &mut ViewContext<Self>) {
+        self.select_index(0, cx);
+    }
+
+    fn select_last(&mut self, _: &SelectLast, cx: &mut ViewContext<Self>) {
+        self.select_index(self.visible_entries.len().saturating_sub(1), cx);
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        let selected_entry = self.selected_entry?;
+        self.visible_entries
+            .iter()
+            .position(|(_, entry_id)| *entry_id == selected_entry)
+    }
+
+    fn select_index(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        if let Some((_, entry_id)) = self.visible_entries.get(ix) {
+            self.selected_entry = Some(*entry_id);
+            self.autoscroll(cx);
+        }
+    }
+
+    fn autoscroll(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.selected_index() {
+            self.scroll_handle.scroll_to_item(ix);
+            cx.notify();
+        }
+    }
+
+    fn focus_in(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.focus_handle.contains_focused(cx) {
+            cx.emit(Event::Focus);
+        }
+    }
+
+    fn open(&mut self, _: &Open, cx: &mut ViewContext<Self>) {
+        if let Some(entry_id) = self.selected_entry {
+            self.open_entry(entry_id, cx);
+        }
+    }
+
+    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        self.open(&Open, cx);
+    }
+
+    fn open_entry(&mut self, entry_id: EntryId, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        self.selected_entry = Some(entry_id);
+        let location = self.entries[entry_id].item.location.clone();
+        workspace.update(cx, |workspace, cx| {
+            let pane = workspace.active_pane().clone();
+            let editor = workspace.open_project_item::<Editor>(pane, location.buffer.clone(), cx);
+            editor.update(cx, |editor, cx| {
+                let range = location
+                    .range
+                    .to_point(&location.buffer.read(cx).snapshot());
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([range])
+                });
+            });
+        });
+        cx.notify();
+    }
+
+    fn render_entry(
+        &self,
+        entry_id: EntryId,
+        depth: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> Stateful<gpui::Div> {
+        let settings = CallHierarchyPanelSettings::get_global(cx);
+        let entry = &self.entries[entry_id];
+        let is_selected = self.selected_entry == Some(entry_id);
+        let is_expanded = self.expanded_entries.contains(&entry_id);
+        // Entries whose calls were fetched and turned out empty cannot be expanded.
+        let toggle = match &entry.children {
+            Some(children) if children.is_empty() => None,
+            _ => Some(is_expanded),
+        };
+
+        let buffer = entry.item.location.buffer.read(cx);
+        let row = entry.item.location.range.start.to_point(buffer).row + 1;
+        let file_name = buffer
+            .file()
+            .map(|file| file.file_name(cx).to_string_lossy().to_string())
+            .unwrap_or_default();
+        let call_count = entry.call_sites.len();
+        let location_label = if call_count > 1 {
+            format!("{file_name}:{row} ({call_count} calls)")
+        } else {
+            format!("{file_name}:{row}")
+        };
+
+        let item_id = ElementId::from(SharedString::from(format!(
+            "call_hierarchy_entry_{entry_id}"
+        )));
+        div()
+            .text_ui(cx)
+            .id(item_id.clone())
+            .child(
+                ListItem::new(item_id)
+                    .indent_level(depth)
+                    .indent_step_size(px(settings.indent_size))
+                    .selected(is_selected)
+                    .toggle(toggle)
+                    .on_toggle(cx.listener(move |panel, _, cx| {
+                        panel.toggle_expanded(entry_id, cx);
+                    }))
+                    .child(
+                        h_flex()
+                            .h_6()
+                            .ml_1()
+                            .gap_2()
+                            .child(Label::new(entry.item.name.clone()))
+                            .child(
+                                Label::new(location_label)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .on_click(cx.listener(move |panel, event: &gpui::ClickEvent, cx| {
+                        if event.down.button == MouseButton::Right || event.down.first_mouse {
+                            return;
+                        }
+                        panel.open_entry(entry_id, cx);
+                    })),
+            )
+            .border_1()
+            .border_r_2()
+            .rounded_none()
+            .hover(|style| {
+                if is_selected {
+                    style
+                } else {
+                    let hover_color = cx.theme().colors().ghost_element_hover;
+                    style.bg(hover_color).border_color(hover_color)
+                }
+            })
+            .when(
+                is_selected && self.focus_handle.contains_focused(cx),
+                |div| div.border_color(Color::Selected.color(cx)),
+            )
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .p_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Button::new("incoming_calls", "Incoming Calls")
+                    .selected(self.direction == CallDirection::Incoming)
+                    .on_click(
+                        cx.listener(|panel, _, cx| {
+                            panel.set_direction(CallDirection::Incoming, cx)
+                        }),
+                    ),
+            )
+            .child(
+                Button::new("outgoing_calls", "Outgoing Calls")
+                    .selected(self.direction == CallDirection::Outgoing)
+                    .on_click(
+                        cx.listener(|panel, _, cx| {
+                            panel.set_direction(CallDirection::Outgoing, cx)
+                        }),
+                    ),
+            )
+    }
+}
+
+impl Panel for CallHierarchyPanel {
+    fn persistent_name() -> &'static str {
+        "Call Hierarchy Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match CallHierarchyPanelSettings::get_global(cx).dock {
+            CallHierarchyPanelDockPosition::Left => DockPosition::Left,
+            CallHierarchyPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<CallHierarchyPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => {
+                        CallHierarchyPanelDockPosition::Left
+                    }
+                    DockPosition::Right => CallHierarchyPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| CallHierarchyPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        CallHierarchyPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::Link)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Call Hierarchy Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+impl FocusableView for CallHierarchyPanel {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<Event> for CallHierarchyPanel {}
+
+impl EventEmitter<PanelEvent> for CallHierarchyPanel {}
+
+impl Render for CallHierarchyPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .id("call-hierarchy-panel")
+            .size_full()
+            .key_context(self.dispatch_context(cx))
+            .on_action(cx.listener(Self::open))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::expand_selected_entry))
+            .on_action(cx.listener(Self::collapse_selected_entry))
+            .on_action(cx.listener(Self::toggle_call_direction))
+            .track_focus(&self.focus_handle)
+            .child(self.render_header(cx))
+            .map(|this| {
+                if self.roots.is_empty() {
+                    this.child(
+                        v_flex().p_4().child(
+                            Label::new(
+                                "Show incoming or outgoing calls of a symbol to fill this panel",
+                            )
+                            .color(Color::Muted),
+                        ),
+                    )
+                } else {
+                    this.child(
+                        uniform_list(
+                            cx.view().clone(),
+                            "entries",
+                            self.visible_entries.len(),
+                            |panel, range, cx| {
+                                panel.visible_entries[range]
+                                    .to_vec()
+                                    .into_iter()
+                                    .map(|(depth, entry_id)| {
+                                        panel.render_entry(entry_id, depth, cx)
+                                    })
+                                    .collect()
+                            },
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+    }
+}
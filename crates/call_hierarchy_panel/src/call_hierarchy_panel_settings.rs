@@ -0,0 +1,52 @@
+use gpui::Pixels;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CallHierarchyPanelDockPosition {
+    Left,
+    Right,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CallHierarchyPanelSettings {
+    pub button: bool,
+    pub default_width: Pixels,
+    pub dock: CallHierarchyPanelDockPosition,
+    pub indent_size: f32,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct CallHierarchyPanelSettingsContent {
+    /// Whether to show the call hierarchy panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Customise default width (in pixels) taken by call hierarchy panel
+    ///
+    /// Default: 300
+    pub default_width: Option<f32>,
+    /// The position of call hierarchy panel
+    ///
+    /// Default: right
+    pub dock: Option<CallHierarchyPanelDockPosition>,
+    /// Amount of indentation (in pixels) for nested calls.
+    ///
+    /// Default: 20
+    pub indent_size: Option<f32>,
+}
+
+impl Settings for CallHierarchyPanelSettings {
+    const KEY: Option<&'static str> = Some("call_hierarchy_panel");
+
+    type FileContent = CallHierarchyPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
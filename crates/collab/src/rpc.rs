@@ -464,6 +464,51 @@ impl Server {
             .add_request_handler(user_handler(
                 forward_read_only_project_request::<proto::GetReferences>,
             ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSignatureHelp>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::PrepareCallHierarchy>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetIncomingCalls>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetOutgoingCalls>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::PrepareTypeHierarchy>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSupertypes>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSubtypes>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSemanticTokens>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetCodeLens>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetFoldingRanges>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetDocumentColors>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetColorPresentations>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetDocumentLinks>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::OpenDocumentLink>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSelectionRanges>,
+            ))
             .add_request_handler(user_handler(
                 forward_read_only_project_request::<proto::SearchProject>,
             ))
@@ -554,6 +599,10 @@ impl Server {
             .add_message_handler(create_buffer_for_peer)
             .add_request_handler(update_buffer)
             .add_message_handler(broadcast_project_message_from_host::<proto::RefreshInlayHints>)
+            .add_message_handler(
+                broadcast_project_message_from_host::<proto::RefreshSemanticTokens>,
+            )
+            .add_message_handler(broadcast_project_message_from_host::<proto::RefreshCodeLenses>)
             .add_message_handler(broadcast_project_message_from_host::<proto::UpdateBufferFile>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferReloaded>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferSaved>)
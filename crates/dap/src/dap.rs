@@ -0,0 +1,956 @@
+mod types;
+
+pub use types::*;
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashMap;
+use futures::{
+    channel::oneshot, io::BufWriter, select, AsyncRead, AsyncWrite, Future, FutureExt as _,
+};
+use gpui::{AsyncAppContext, BackgroundExecutor, Task};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use smol::{
+    channel,
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    process::{self, Child},
+};
+
+#[cfg(target_os = "windows")]
+use smol::process::windows::CommandExt;
+
+use std::{
+    ffi::OsString,
+    fmt,
+    io::Write,
+    net::Ipv4Addr,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{
+        atomic::{AtomicU64, Ordering::SeqCst},
+        Arc,
+    },
+    time::Duration,
+};
+use util::ResultExt;
+
+const CONTENT_LEN_HEADER: &str = "Content-Length: ";
+
+const DAP_REQUEST_TIMEOUT: Duration = Duration::from_secs(60 * 2);
+const ADAPTER_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+const TCP_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const TCP_CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+type MessageHandler = Box<dyn Send + FnMut(u64, Value, AsyncAppContext)>;
+type ResponseHandler = Box<dyn Send + FnOnce(Result<Value, String>)>;
+
+/// Represents a launchable debug adapter, along with the way to talk to it once it runs.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DebugAdapterBinary {
+    pub path: PathBuf,
+    pub arguments: Vec<OsString>,
+    pub env: Option<HashMap<String, String>>,
+    pub transport: DebugAdapterTransport,
+}
+
+/// How messages are exchanged with a debug adapter.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub enum DebugAdapterTransport {
+    /// Over the standard input and output of the adapter process.
+    Stdio,
+    /// Over a TCP connection to the given port, on which the adapter process listens.
+    Tcp { host: Ipv4Addr, port: u16 },
+}
+
+/// Identifies a running debug adapter.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct DebugAdapterClientId(pub usize);
+
+/// A connection to a running debug adapter.
+pub struct DebugAdapterClient {
+    id: DebugAdapterClientId,
+    name: Arc<str>,
+    next_seq: Arc<AtomicU64>,
+    outbound_tx: channel::Sender<String>,
+    capabilities: Capabilities,
+    event_handlers: Arc<Mutex<HashMap<&'static str, MessageHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, MessageHandler>>>,
+    response_handlers: Arc<Mutex<Option<HashMap<u64, ResponseHandler>>>>,
+    executor: BackgroundExecutor,
+    #[allow(clippy::type_complexity)]
+    io_tasks: Mutex<Option<(Task<Option<()>>, Task<Option<()>>)>>,
+    process: Arc<Mutex<Option<Child>>>,
+}
+
+/// Handle to a debug adapter event or reverse request subscription.
+pub struct Subscription {
+    key: &'static str,
+    handlers: Option<Arc<Mutex<HashMap<&'static str, MessageHandler>>>>,
+}
+
+/// A message of the Debug Adapter Protocol.
+///
+/// [DAP Specification](https://microsoft.github.io/debug-adapter-protocol/specification#Base_Protocol_ProtocolMessage)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Message {
+    Request(RequestMessage),
+    Response(ResponseMessage),
+    Event(EventMessage),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RequestMessage {
+    seq: u64,
+    command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    arguments: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ResponseMessage {
+    seq: u64,
+    request_seq: u64,
+    success: bool,
+    command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EventMessage {
+    seq: u64,
+    event: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+}
+
+/// The `arguments` or `body` of a message, which the protocol omits rather than sets to `null`.
+fn optional_value(value: impl Serialize) -> Option<Value> {
+    match serde_json::to_value(value).log_err()? {
+        Value::Null => None,
+        value => Some(value),
+    }
+}
+
+impl DebugAdapterClient {
+    /// Starts a debug adapter process and connects to it.
+    pub async fn new(
+        id: DebugAdapterClientId,
+        name: Arc<str>,
+        binary: DebugAdapterBinary,
+        working_dir: &Path,
+        cx: AsyncAppContext,
+    ) -> Result<Self> {
+        log::info!(
+            "starting debug adapter. binary path: {:?}, working directory: {:?}, args: {:?}",
+            binary.path,
+            working_dir,
+            &binary.arguments
+        );
+
+        let mut command = process::Command::new(&binary.path);
+        command
+            .current_dir(working_dir)
+            .args(&binary.arguments)
+            .envs(binary.env.unwrap_or_default())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        #[cfg(windows)]
+        command.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+        let mut process = command.spawn().with_context(|| {
+            format!(
+                "failed to spawn command. path: {:?}, working directory: {:?}, args: {:?}",
+                binary.path, working_dir, &binary.arguments
+            )
+        })?;
+
+        let stderr = process.stderr.take().unwrap();
+        cx.background_executor()
+            .spawn(Self::log_process_output(stderr, name.clone()).log_err())
+            .detach();
+
+        let client = match binary.transport {
+            DebugAdapterTransport::Stdio => {
+                let stdin = process.stdin.take().unwrap();
+                let stdout = process.stdout.take().unwrap();
+                Self::new_internal(id, name, stdin, stdout, Some(process), cx)
+            }
+            DebugAdapterTransport::Tcp { host, port } => {
+                // Adapters talking over TCP may still write to their standard output.
+                let stdout = process.stdout.take().unwrap();
+                cx.background_executor()
+                    .spawn(Self::log_process_output(stdout, name.clone()).log_err())
+                    .detach();
+                let stream = Self::connect_tcp(host, port, cx.background_executor()).await?;
+                Self::new_internal(id, name, stream.clone(), stream, Some(process), cx)
+            }
+        };
+        Ok(client)
+    }
+
+    /// Connects to the given port, retrying while the adapter process starts listening on it.
+    async fn connect_tcp(
+        host: Ipv4Addr,
+        port: u16,
+        executor: &BackgroundExecutor,
+    ) -> Result<TcpStream> {
+        let mut timeout = executor.timer(TCP_CONNECT_TIMEOUT).fuse();
+        loop {
+            match TcpStream::connect((host, port)).await {
+                Ok(stream) => return Ok(stream),
+                Err(error) => {
+                    select! {
+                        _ = executor.timer(TCP_CONNECT_RETRY_INTERVAL).fuse() => {}
+                        _ = timeout => {
+                            return Err(error).with_context(|| {
+                                format!("failed to connect to debug adapter at {host}:{port}")
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn new_internal<Input, Output>(
+        id: DebugAdapterClientId,
+        name: Arc<str>,
+        input: Input,
+        output: Output,
+        process: Option<Child>,
+        cx: AsyncAppContext,
+    ) -> Self
+    where
+        Input: AsyncWrite + Unpin + Send + 'static,
+        Output: AsyncRead + Unpin + Send + 'static,
+    {
+        let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
+        let next_seq = Arc::new(AtomicU64::new(1));
+        let event_handlers = Arc::new(Mutex::new(HashMap::<_, MessageHandler>::default()));
+        let request_handlers = Arc::new(Mutex::new(HashMap::<_, MessageHandler>::default()));
+        let response_handlers =
+            Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
+
+        let input_task = cx.spawn({
+            let name = name.clone();
+            let next_seq = next_seq.clone();
+            let outbound_tx = outbound_tx.clone();
+            let event_handlers = event_handlers.clone();
+            let request_handlers = request_handlers.clone();
+            let response_handlers = response_handlers.clone();
+            move |cx| {
+                Self::handle_input(
+                    output,
+                    name,
+                    next_seq,
+                    outbound_tx,
+                    event_handlers,
+                    request_handlers,
+                    response_handlers,
+                    cx,
+                )
+                .log_err()
+            }
+        });
+        let output_task = cx
+            .background_executor()
+            .spawn(Self::handle_output(input, outbound_rx, response_handlers.clone()).log_err());
+
+        Self {
+            id,
+            name,
+            next_seq,
+            outbound_tx,
+            capabilities: Capabilities::default(),
+            event_handlers,
+            request_handlers,
+            response_handlers,
+            executor: cx.background_executor().clone(),
+            io_tasks: Mutex::new(Some((input_task, output_task))),
+            process: Arc::new(Mutex::new(process)),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_input<Output>(
+        output: Output,
+        name: Arc<str>,
+        next_seq: Arc<AtomicU64>,
+        outbound_tx: channel::Sender<String>,
+        event_handlers: Arc<Mutex<HashMap<&'static str, MessageHandler>>>,
+        request_handlers: Arc<Mutex<HashMap<&'static str, MessageHandler>>>,
+        response_handlers: Arc<Mutex<Option<HashMap<u64, ResponseHandler>>>>,
+        cx: AsyncAppContext,
+    ) -> Result<()>
+    where
+        Output: AsyncRead + Unpin + Send + 'static,
+    {
+        let _clear_response_handlers = util::defer({
+            let response_handlers = response_handlers.clone();
+            move || {
+                response_handlers.lock().take();
+            }
+        });
+        let mut output = BufReader::new(output);
+        let mut buffer = Vec::new();
+        loop {
+            read_message(&mut output, &mut buffer).await?;
+            if let Ok(message) = std::str::from_utf8(&buffer) {
+                log::trace!("incoming message: {message}");
+            }
+
+            match serde_json::from_slice::<Message>(&buffer) {
+                Ok(Message::Event(event)) => {
+                    let mut handlers = event_handlers.lock();
+                    if let Some(handler) = handlers.get_mut(event.event.as_str()) {
+                        handler(event.seq, event.body.unwrap_or(Value::Null), cx.clone());
+                    } else {
+                        log::info!("debug adapter {name} sent unhandled event {}", event.event);
+                    }
+                }
+                Ok(Message::Response(response)) => {
+                    let handler = response_handlers
+                        .lock()
+                        .as_mut()
+                        .and_then(|handlers| handlers.remove(&response.request_seq));
+                    if let Some(handler) = handler {
+                        if response.success {
+                            handler(Ok(response.body.unwrap_or(Value::Null)));
+                        } else {
+                            handler(Err(response.message.unwrap_or(response.command)));
+                        }
+                    }
+                }
+                Ok(Message::Request(request)) => {
+                    let mut handlers = request_handlers.lock();
+                    if let Some(handler) = handlers.get_mut(request.command.as_str()) {
+                        handler(
+                            request.seq,
+                            request.arguments.unwrap_or(Value::Null),
+                            cx.clone(),
+                        );
+                    } else {
+                        drop(handlers);
+                        log::info!(
+                            "debug adapter {name} sent unhandled request {}",
+                            request.command
+                        );
+                        send_response(
+                            &next_seq,
+                            &outbound_tx,
+                            request.seq,
+                            request.command,
+                            Err::<(), _>(anyhow!("unsupported request")),
+                        );
+                    }
+                }
+                Err(error) => {
+                    log::warn!(
+                        "failed to deserialize message of debug adapter {name}: {error}\n{}",
+                        String::from_utf8_lossy(&buffer)
+                    );
+                }
+            }
+
+            // Don't starve the main thread when receiving lots of messages at once.
+            smol::future::yield_now().await;
+        }
+    }
+
+    async fn log_process_output<Stream>(stream: Stream, name: Arc<str>) -> Result<()>
+    where
+        Stream: AsyncRead + Unpin + Send + 'static,
+    {
+        let mut stream = BufReader::new(stream);
+        let mut buffer = String::new();
+        loop {
+            buffer.clear();
+            if stream.read_line(&mut buffer).await? == 0 {
+                return Ok(());
+            }
+            log::debug!("debug adapter {name} output: {}", buffer.trim_end());
+        }
+    }
+
+    async fn handle_output<Input>(
+        input: Input,
+        outbound_rx: channel::Receiver<String>,
+        response_handlers: Arc<Mutex<Option<HashMap<u64, ResponseHandler>>>>,
+    ) -> Result<()>
+    where
+        Input: AsyncWrite + Unpin + Send + 'static,
+    {
+        let _clear_response_handlers = util::defer({
+            let response_handlers = response_handlers.clone();
+            move || {
+                response_handlers.lock().take();
+            }
+        });
+        let mut input = BufWriter::new(input);
+        let mut content_len_buffer = Vec::new();
+        while let Ok(message) = outbound_rx.recv().await {
+            log::trace!("outgoing message: {}", message);
+            content_len_buffer.clear();
+            write!(content_len_buffer, "{}", message.len()).unwrap();
+            input.write_all(CONTENT_LEN_HEADER.as_bytes()).await?;
+            input.write_all(&content_len_buffer).await?;
+            input.write_all("\r\n\r\n".as_bytes()).await?;
+            input.write_all(message.as_bytes()).await?;
+            input.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Initializes the debug adapter by sending the `initialize` request, storing the
+    /// capabilities it responds with.
+    ///
+    /// [DAP Specification](https://microsoft.github.io/debug-adapter-protocol/specification#Requests_Initialize)
+    pub fn initialize(
+        mut self,
+        adapter_id: String,
+        cx: &gpui::AppContext,
+    ) -> Task<Result<Arc<Self>>> {
+        let arguments = InitializeRequestArguments {
+            client_id: Some("zed".to_string()),
+            client_name: Some("Zed".to_string()),
+            adapter_id,
+            locale: None,
+            lines_start_at1: Some(true),
+            columns_start_at1: Some(true),
+            path_format: Some("path".to_string()),
+            supports_variable_type: Some(true),
+            supports_variable_paging: Some(false),
+            supports_run_in_terminal_request: Some(false),
+        };
+        cx.spawn(|_| async move {
+            self.capabilities = self.request::<requests::Initialize>(arguments).await?;
+            Ok(Arc::new(self))
+        })
+    }
+
+    /// Asks the debug adapter to end the debug session, terminating the debuggee, and prepares
+    /// the [`DebugAdapterClient`] to be dropped.
+    pub fn shutdown(&self) -> Option<impl 'static + Send + Future<Output = Option<()>>> {
+        let tasks = self.io_tasks.lock().take()?;
+        let disconnect = self.request::<requests::Disconnect>(DisconnectArguments {
+            restart: Some(false),
+            terminate_debuggee: Some(true),
+        });
+        let outbound_tx = self.outbound_tx.clone();
+        let response_handlers = self.response_handlers.clone();
+        let process = self.process.clone();
+        let name = self.name.clone();
+        let mut timer = self.executor.timer(ADAPTER_SHUTDOWN_TIMEOUT).fuse();
+        Some(
+            async move {
+                log::debug!("debug adapter shutdown started");
+                select! {
+                    result = disconnect.fuse() => {
+                        result.log_err();
+                    }
+                    _ = timer => {
+                        log::info!("timeout waiting for debug adapter {name} to disconnect");
+                    },
+                }
+                outbound_tx.close();
+                response_handlers.lock().take();
+                if let Some(mut process) = process.lock().take() {
+                    process.kill().log_err();
+                }
+                log::debug!("debug adapter shutdown finished");
+                drop(tasks);
+                anyhow::Ok(())
+            }
+            .log_err(),
+        )
+    }
+
+    /// Registers a handler for the events of the given kind.
+    ///
+    /// [DAP Specification](https://microsoft.github.io/debug-adapter-protocol/specification#Base_Protocol_Event)
+    #[must_use]
+    pub fn on_event<T, F>(&self, mut f: F) -> Subscription
+    where
+        T: 'static + Event,
+        F: 'static + Send + FnMut(T::Body, AsyncAppContext),
+    {
+        let prev_handler = self.event_handlers.lock().insert(
+            T::EVENT,
+            Box::new(move |_, body, cx| {
+                if let Some(body) = serde_json::from_value(body).log_err() {
+                    f(body, cx);
+                }
+            }),
+        );
+        assert!(
+            prev_handler.is_none(),
+            "registered multiple handlers for the same DAP event"
+        );
+        Subscription {
+            key: T::EVENT,
+            handlers: Some(self.event_handlers.clone()),
+        }
+    }
+
+    /// Registers a handler for the requests of the given kind the debug adapter sends to the
+    /// client, such as `runInTerminal`.
+    ///
+    /// [DAP Specification](https://microsoft.github.io/debug-adapter-protocol/specification#Reverse_Requests)
+    #[must_use]
+    pub fn on_request<T, F, Fut>(&self, mut f: F) -> Subscription
+    where
+        T: 'static + Request,
+        F: 'static + Send + FnMut(T::Arguments, AsyncAppContext) -> Fut,
+        Fut: 'static + Future<Output = Result<T::Response>>,
+    {
+        let next_seq = self.next_seq.clone();
+        let outbound_tx = self.outbound_tx.clone();
+        let prev_handler = self.request_handlers.lock().insert(
+            T::COMMAND,
+            Box::new(move |seq, arguments, cx| {
+                let response = match serde_json::from_value(arguments) {
+                    Ok(arguments) => f(arguments, cx.clone()).left_future(),
+                    Err(error) => {
+                        log::error!("error deserializing {} request: {:?}", T::COMMAND, error);
+                        futures::future::ready(Err(error.into())).right_future()
+                    }
+                };
+                let next_seq = next_seq.clone();
+                let outbound_tx = outbound_tx.clone();
+                cx.foreground_executor()
+                    .spawn(async move {
+                        let response = response.await;
+                        send_response(
+                            &next_seq,
+                            &outbound_tx,
+                            seq,
+                            T::COMMAND.to_string(),
+                            response,
+                        );
+                    })
+                    .detach();
+            }),
+        );
+        assert!(
+            prev_handler.is_none(),
+            "registered multiple handlers for the same DAP request"
+        );
+        Subscription {
+            key: T::COMMAND,
+            handlers: Some(self.request_handlers.clone()),
+        }
+    }
+
+    /// Removes a request handler registered via [`Self::on_request`].
+    pub fn remove_request_handler<T: Request>(&self) {
+        self.request_handlers.lock().remove(T::COMMAND);
+    }
+
+    /// Get the id of the running debug adapter.
+    pub fn id(&self) -> DebugAdapterClientId {
+        self.id
+    }
+
+    /// Get the name of the running debug adapter.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the capabilities the debug adapter reported when initialized.
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Sends a request to the debug adapter.
+    ///
+    /// [DAP Specification](https://microsoft.github.io/debug-adapter-protocol/specification#Base_Protocol_Request)
+    pub fn request<T: Request>(
+        &self,
+        arguments: T::Arguments,
+    ) -> impl 'static + Send + Future<Output = Result<T::Response>> {
+        let seq = self.next_seq.fetch_add(1, SeqCst);
+        let message = serde_json::to_string(&Message::Request(RequestMessage {
+            seq,
+            command: T::COMMAND.to_string(),
+            arguments: optional_value(arguments),
+        }))
+        .unwrap();
+
+        let (tx, rx) = oneshot::channel();
+        let handle_response = self
+            .response_handlers
+            .lock()
+            .as_mut()
+            .ok_or_else(|| anyhow!("debug adapter shut down"))
+            .map(|handlers| {
+                handlers.insert(
+                    seq,
+                    Box::new(move |result| {
+                        let response = match result {
+                            Ok(body) => serde_json::from_value::<T::Response>(body)
+                                .context("failed to deserialize response"),
+                            Err(message) => Err(anyhow!("{message}")),
+                        };
+                        tx.send(response).ok();
+                    }),
+                );
+            });
+        let send = self
+            .outbound_tx
+            .try_send(message)
+            .context("failed to write to debug adapter");
+
+        let mut timeout = self.executor.timer(DAP_REQUEST_TIMEOUT).fuse();
+        async move {
+            handle_response?;
+            send?;
+            let command = T::COMMAND;
+            select! {
+                response = rx.fuse() => response.context("debug adapter shut down")?,
+                _ = timeout => {
+                    log::error!("cancelled DAP request {command:?} seq {seq} which took over {DAP_REQUEST_TIMEOUT:?}");
+                    anyhow::bail!("DAP request timeout");
+                }
+            }
+        }
+    }
+
+    /// Sends an event to the other end of the connection, which only debug adapters do.
+    #[cfg(any(test, feature = "test-support"))]
+    fn send_event<T: Event>(&self, body: T::Body) -> Result<()> {
+        let message = serde_json::to_string(&Message::Event(EventMessage {
+            seq: self.next_seq.fetch_add(1, SeqCst),
+            event: T::EVENT.to_string(),
+            body: optional_value(body),
+        }))
+        .unwrap();
+        self.outbound_tx.try_send(message)?;
+        Ok(())
+    }
+}
+
+fn send_response<T: Serialize>(
+    next_seq: &AtomicU64,
+    outbound_tx: &channel::Sender<String>,
+    request_seq: u64,
+    command: String,
+    response: Result<T>,
+) {
+    let (success, message, body) = match response {
+        Ok(body) => (true, None, optional_value(body)),
+        Err(error) => (false, Some(error.to_string()), None),
+    };
+    let message = serde_json::to_string(&Message::Response(ResponseMessage {
+        seq: next_seq.fetch_add(1, SeqCst),
+        request_seq,
+        success,
+        command,
+        message,
+        body,
+    }))
+    .unwrap();
+    outbound_tx.try_send(message).ok();
+}
+
+/// Reads the next message into the buffer, replacing its contents.
+async fn read_message<Output>(output: &mut BufReader<Output>, buffer: &mut Vec<u8>) -> Result<()>
+where
+    Output: AsyncRead + Unpin + Send + 'static,
+{
+    let mut content_length = None;
+    loop {
+        buffer.clear();
+        if output.read_until(b'\n', buffer).await? == 0 {
+            return Err(anyhow!("cannot read DAP message headers"));
+        }
+        let header = std::str::from_utf8(buffer)?.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix(CONTENT_LEN_HEADER) {
+            content_length = Some(length.parse::<usize>()?);
+        }
+    }
+    let content_length =
+        content_length.ok_or_else(|| anyhow!("missing DAP message content length"))?;
+    buffer.resize(content_length, 0);
+    output.read_exact(buffer).await?;
+    Ok(())
+}
+
+impl Drop for DebugAdapterClient {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown() {
+            self.executor.spawn(shutdown).detach();
+        }
+    }
+}
+
+impl fmt::Debug for DebugAdapterClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebugAdapterClient")
+            .field("id", &self.id.0)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for DebugAdapterClientId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Subscription {
+    /// Detaching a subscription handle prevents it from unsubscribing on drop.
+    pub fn detach(&mut self) {
+        self.handlers = None;
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(handlers) = self.handlers.as_ref() {
+            handlers.lock().remove(self.key);
+        }
+    }
+}
+
+/// Mock debug adapter for use in tests.
+#[cfg(any(test, feature = "test-support"))]
+pub struct FakeDebugAdapter {
+    pub adapter: Arc<DebugAdapterClient>,
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl FakeDebugAdapter {
+    /// Construct a fake debug adapter, along with a client connected to it.
+    pub fn new(
+        id: DebugAdapterClientId,
+        name: &str,
+        capabilities: Capabilities,
+        cx: AsyncAppContext,
+    ) -> (DebugAdapterClient, FakeDebugAdapter) {
+        let (input_writer, input_reader) = async_pipe::pipe();
+        let (output_writer, output_reader) = async_pipe::pipe();
+
+        let client = DebugAdapterClient::new_internal(
+            id,
+            name.into(),
+            input_writer,
+            output_reader,
+            None,
+            cx.clone(),
+        );
+        let fake = FakeDebugAdapter {
+            adapter: Arc::new(DebugAdapterClient::new_internal(
+                id,
+                name.into(),
+                output_writer,
+                input_reader,
+                None,
+                cx,
+            )),
+        };
+        fake.handle_request::<requests::Initialize, _, _>(move |_, _| {
+            let capabilities = capabilities.clone();
+            async move { Ok(capabilities) }
+        });
+        fake.handle_request::<requests::Disconnect, _, _>(|_, _| async move { Ok(()) });
+        (client, fake)
+    }
+
+    /// Registers a handler for a specific kind of request. Removes any existing handler for
+    /// that kind of request.
+    pub fn handle_request<T, F, Fut>(
+        &self,
+        mut handler: F,
+    ) -> futures::channel::mpsc::UnboundedReceiver<()>
+    where
+        T: 'static + Request,
+        F: 'static + Send + FnMut(T::Arguments, AsyncAppContext) -> Fut,
+        Fut: 'static + Send + Future<Output = Result<T::Response>>,
+    {
+        let (responded_tx, responded_rx) = futures::channel::mpsc::unbounded();
+        self.adapter.remove_request_handler::<T>();
+        self.adapter
+            .on_request::<T, _, _>(move |arguments, cx| {
+                let result = handler(arguments, cx.clone());
+                let responded_tx = responded_tx.clone();
+                let executor = cx.background_executor().clone();
+                async move {
+                    executor.simulate_random_delay().await;
+                    let result = result.await;
+                    responded_tx.unbounded_send(()).ok();
+                    result
+                }
+            })
+            .detach();
+        responded_rx
+    }
+
+    /// Sends an event to the client.
+    pub fn send_event<T: Event>(&self, body: T::Body) {
+        self.adapter.send_event::<T>(body).ok();
+    }
+
+    /// Sends a reverse request to the client.
+    pub async fn request<T: Request>(&self, arguments: T::Arguments) -> Result<T::Response> {
+        self.adapter.executor.start_waiting();
+        self.adapter.request::<T>(arguments).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    #[ctor::ctor]
+    fn init_logger() {
+        if std::env::var("RUST_LOG").is_ok() {
+            env_logger::init();
+        }
+    }
+
+    #[gpui::test]
+    async fn test_fake(cx: &mut TestAppContext) {
+        let (client, fake) = FakeDebugAdapter::new(
+            DebugAdapterClientId(0),
+            "the-adapter",
+            Capabilities {
+                supports_configuration_done_request: Some(true),
+                ..Default::default()
+            },
+            cx.to_async(),
+        );
+
+        let (stopped_tx, stopped_rx) = channel::unbounded();
+        let (output_tx, output_rx) = channel::unbounded();
+        client
+            .on_event::<events::Stopped, _>(move |body, _| stopped_tx.try_send(body).unwrap())
+            .detach();
+        client
+            .on_event::<events::Output, _>(move |body, _| output_tx.try_send(body).unwrap())
+            .detach();
+        client
+            .on_request::<requests::RunInTerminal, _, _>(|arguments, _| async move {
+                assert_eq!(arguments.args, vec!["a.out".to_string()]);
+                Ok(RunInTerminalResponse {
+                    process_id: Some(42),
+                    shell_process_id: None,
+                })
+            })
+            .detach();
+
+        let client = cx
+            .update(|cx| client.initialize("fake".to_string(), cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            client.capabilities().supports_configuration_done_request,
+            Some(true)
+        );
+
+        fake.handle_request::<requests::StackTrace, _, _>(|arguments, _| async move {
+            assert_eq!(arguments.thread_id, 1);
+            Ok(StackTraceResponse {
+                stack_frames: vec![StackFrame {
+                    id: 7,
+                    name: "main".to_string(),
+                    line: 3,
+                    column: 1,
+                    ..Default::default()
+                }],
+                total_frames: Some(1),
+            })
+        });
+        let stack_trace = client
+            .request::<requests::StackTrace>(StackTraceArguments {
+                thread_id: 1,
+                start_frame: None,
+                levels: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(stack_trace.stack_frames[0].name, "main");
+
+        fake.handle_request::<requests::Evaluate, _, _>(|_, _| async move {
+            Err(anyhow!("not available"))
+        });
+        let error = client
+            .request::<requests::Evaluate>(EvaluateArguments {
+                expression: "x".to_string(),
+                frame_id: Some(7),
+                context: Some(EvaluateContext::Repl),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "not available");
+
+        fake.send_event::<events::Output>(OutputEventBody {
+            category: Some(OutputCategory::Stdout),
+            output: "hello\n".to_string(),
+        });
+        fake.send_event::<events::Stopped>(StoppedEventBody {
+            reason: "breakpoint".to_string(),
+            thread_id: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(output_rx.recv().await.unwrap().output, "hello\n");
+        assert_eq!(stopped_rx.recv().await.unwrap().thread_id, Some(1));
+
+        let response = fake
+            .request::<requests::RunInTerminal>(RunInTerminalRequestArguments {
+                cwd: "/".to_string(),
+                args: vec!["a.out".to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.process_id, Some(42));
+    }
+
+    #[gpui::test]
+    async fn test_read_message() {
+        let mut buffer = Vec::new();
+        let mut reader = BufReader::new(
+            b"Content-Length: 11\r\n\r\n{\"seq\":1}\r\nContent-Type: json\r\nContent-Length: 2\r\n\r\n{}"
+                as &[u8],
+        );
+        read_message(&mut reader, &mut buffer).await.unwrap();
+        assert_eq!(buffer, b"{\"seq\":1}\r\n");
+        read_message(&mut reader, &mut buffer).await.unwrap();
+        assert_eq!(buffer, b"{}");
+        assert!(read_message(&mut reader, &mut buffer).await.is_err());
+    }
+
+    #[test]
+    fn test_message_serialization() {
+        let message = serde_json::to_string(&Message::Request(RequestMessage {
+            seq: 1,
+            command: requests::Threads::COMMAND.to_string(),
+            arguments: optional_value(()),
+        }))
+        .unwrap();
+        assert_eq!(message, r#"{"type":"request","seq":1,"command":"threads"}"#);
+
+        let message = serde_json::from_str::<Message>(
+            r#"{"type":"response","seq":2,"request_seq":1,"success":false,"command":"threads","message":"no threads"}"#,
+        )
+        .unwrap();
+        let Message::Response(response) = message else {
+            panic!("expected a response, got {message:?}");
+        };
+        assert_eq!(response.request_seq, 1);
+        assert_eq!(response.message.as_deref(), Some("no threads"));
+    }
+}
@@ -0,0 +1,491 @@
+//! The subset of the Debug Adapter Protocol used by Zed.
+//!
+//! [DAP Specification](https://microsoft.github.io/debug-adapter-protocol/specification)
+
+use collections::HashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+/// A request sent from the client to the debug adapter, or from the debug adapter to the client
+/// in the case of reverse requests.
+pub trait Request {
+    type Arguments: Serialize + DeserializeOwned + Send + 'static;
+    type Response: Serialize + DeserializeOwned + Send + 'static;
+    const COMMAND: &'static str;
+}
+
+/// An event sent from the debug adapter to the client.
+pub trait Event {
+    type Body: Serialize + DeserializeOwned + Send + 'static;
+    const EVENT: &'static str;
+}
+
+/// The features a debug adapter reported to support in response to the `initialize` request.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_configuration_done_request: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_function_breakpoints: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_conditional_breakpoints: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_hit_conditional_breakpoints: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_evaluate_for_hovers: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_set_variable: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_restart_request: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_terminate_request: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_log_points: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_single_thread_execution_requests: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeRequestArguments {
+    #[serde(rename = "clientID", default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_name: Option<String>,
+    #[serde(rename = "adapterID")]
+    pub adapter_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines_start_at1: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns_start_at1: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_variable_type: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_variable_paging: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_run_in_terminal_request: Option<bool>,
+}
+
+/// A source file, identified either by its path or by a reference to be used with the `source`
+/// request.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Source {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_reference: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceBreakpoint {
+    pub line: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_message: Option<String>,
+}
+
+/// A breakpoint as set by the debug adapter, which may differ from the one requested.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Breakpoint {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    pub verified: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetBreakpointsArguments {
+    pub source: Source,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breakpoints: Option<Vec<SourceBreakpoint>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_modified: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetBreakpointsResponse {
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Thread {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadsResponse {
+    pub threads: Vec<Thread>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackTraceArguments {
+    pub thread_id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_frame: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub levels: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackFrame {
+    pub id: u64,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    pub line: u64,
+    pub column: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackTraceResponse {
+    pub stack_frames: Vec<StackFrame>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_frames: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopesArguments {
+    pub frame_id: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scope {
+    pub name: String,
+    /// The reference to pass to the `variables` request to list the variables of this scope.
+    pub variables_reference: u64,
+    #[serde(default)]
+    pub expensive: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScopesResponse {
+    pub scopes: Vec<Scope>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariablesArguments {
+    pub variables_reference: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    /// If greater than zero, the reference to pass to the `variables` request to list the
+    /// children of this variable.
+    #[serde(default)]
+    pub variables_reference: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evaluate_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariablesResponse {
+    pub variables: Vec<Variable>,
+}
+
+/// The context in which an expression is evaluated, which adapters may use to format the result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EvaluateContext {
+    Watch,
+    Repl,
+    Hover,
+    Clipboard,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateArguments {
+    pub expression: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<EvaluateContext>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateResponse {
+    pub result: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    #[serde(default)]
+    pub variables_reference: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinueArguments {
+    pub thread_id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub single_thread: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinueResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub all_threads_continued: Option<bool>,
+}
+
+/// The arguments of the `next`, `stepIn` and `stepOut` requests.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepArguments {
+    pub thread_id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub single_thread: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PauseArguments {
+    pub thread_id: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisconnectArguments {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminate_debuggee: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminateArguments {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunInTerminalRequestArguments {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub cwd: String,
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<HashMap<String, Option<String>>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunInTerminalResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell_process_id: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoppedEventBody {
+    /// Why the execution stopped, e.g. `step`, `breakpoint`, `exception` or `pause`.
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub all_threads_stopped: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinuedEventBody {
+    pub thread_id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub all_threads_continued: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitedEventBody {
+    pub exit_code: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminatedEventBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart: Option<Value>,
+}
+
+/// Where the output of an `output` event belongs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputCategory {
+    Console,
+    Important,
+    Stdout,
+    Stderr,
+    Telemetry,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputEventBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<OutputCategory>,
+    pub output: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadEventBody {
+    /// Either `started` or `exited`.
+    pub reason: String,
+    pub thread_id: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakpointEventBody {
+    /// Either `changed`, `new` or `removed`.
+    pub reason: String,
+    pub breakpoint: Breakpoint,
+}
+
+pub mod requests {
+    use serde_json::Value;
+
+    use super::*;
+
+    macro_rules! request {
+        ($name:ident, $command:literal, $arguments:ty, $response:ty) => {
+            #[derive(Debug)]
+            pub enum $name {}
+
+            impl Request for $name {
+                type Arguments = $arguments;
+                type Response = $response;
+                const COMMAND: &'static str = $command;
+            }
+        };
+    }
+
+    request!(
+        Initialize,
+        "initialize",
+        InitializeRequestArguments,
+        Capabilities
+    );
+    // The arguments of `launch` and `attach` are specific to each debug adapter.
+    request!(Launch, "launch", Value, ());
+    request!(Attach, "attach", Value, ());
+    request!(ConfigurationDone, "configurationDone", (), ());
+    request!(
+        SetBreakpoints,
+        "setBreakpoints",
+        SetBreakpointsArguments,
+        SetBreakpointsResponse
+    );
+    request!(Threads, "threads", (), ThreadsResponse);
+    request!(
+        StackTrace,
+        "stackTrace",
+        StackTraceArguments,
+        StackTraceResponse
+    );
+    request!(Scopes, "scopes", ScopesArguments, ScopesResponse);
+    request!(
+        Variables,
+        "variables",
+        VariablesArguments,
+        VariablesResponse
+    );
+    request!(Evaluate, "evaluate", EvaluateArguments, EvaluateResponse);
+    request!(Continue, "continue", ContinueArguments, ContinueResponse);
+    request!(Next, "next", StepArguments, ());
+    request!(StepIn, "stepIn", StepArguments, ());
+    request!(StepOut, "stepOut", StepArguments, ());
+    request!(Pause, "pause", PauseArguments, ());
+    request!(Disconnect, "disconnect", DisconnectArguments, ());
+    request!(Terminate, "terminate", TerminateArguments, ());
+    request!(
+        RunInTerminal,
+        "runInTerminal",
+        RunInTerminalRequestArguments,
+        RunInTerminalResponse
+    );
+}
+
+pub mod events {
+    use super::*;
+
+    macro_rules! event {
+        ($name:ident, $event:literal, $body:ty) => {
+            #[derive(Debug)]
+            pub enum $name {}
+
+            impl Event for $name {
+                type Body = $body;
+                const EVENT: &'static str = $event;
+            }
+        };
+    }
+
+    event!(Initialized, "initialized", ());
+    event!(Stopped, "stopped", StoppedEventBody);
+    event!(Continued, "continued", ContinuedEventBody);
+    event!(Exited, "exited", ExitedEventBody);
+    event!(Terminated, "terminated", Option<TerminatedEventBody>);
+    event!(Output, "output", OutputEventBody);
+    event!(Thread, "thread", ThreadEventBody);
+    event!(Breakpoint, "breakpoint", BreakpointEventBody);
+}
@@ -0,0 +1,1347 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::Context;
+use dap::{
+    requests, ContinueArguments, DebugAdapterClientId, EvaluateArguments, EvaluateContext,
+    OutputCategory, PauseArguments, ScopesArguments, StackFrame, StackTraceArguments,
+    StepArguments, VariablesArguments,
+};
+use db::kvp::KEY_VALUE_STORE;
+use editor::{scroll::Autoscroll, Editor};
+use futures::{future::join_all, FutureExt as _, StreamExt as _};
+use gpui::{
+    div, px, Action, AppContext, AsyncWindowContext, ElementId, EventEmitter, FocusHandle,
+    FocusableView, InteractiveElement, IntoElement, KeyContext, Model, ParentElement, Pixels,
+    Render, SharedString, StatefulInteractiveElement, Styled, Subscription, Task, View,
+    ViewContext, VisualContext, WeakView, WindowContext,
+};
+use language::Point;
+use menu::Confirm;
+use project::{Fs, Project, ProjectPath};
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use task::{DebugTemplate, DebugTemplates};
+use util::{paths::HOME, post_inc, ResultExt, TryFutureExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    ui::{
+        h_flex, v_flex, ActiveTheme, ButtonCommon, Clickable, Color, Disableable, FluentBuilder,
+        IconButton, IconName, IconSize, Label, LabelCommon, LabelSize, ListItem, Selectable,
+        StyledTypography, Tooltip,
+    },
+    Workspace,
+};
+
+use crate::{
+    debug_panel_settings::{DebugPanelDockPosition, DebugPanelSettings},
+    session::{ConsoleEntry, ConsoleEntryKind, Session, SessionEvent, SessionStatus, Watch},
+    Continue, Pause, StepInto, StepOut, StepOver, Stop, ToggleFocus,
+};
+
+const DEBUG_PANEL_KEY: &str = "DebugPanel";
+
+/// The row highlight of the line on which the debuggee stopped.
+enum DebugStackFrameLine {}
+
+pub struct DebugPanel {
+    fs: Arc<dyn Fs>,
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    width: Option<Pixels>,
+    height: Option<Pixels>,
+    focus_handle: FocusHandle,
+    pending_serialization: Task<Option<()>>,
+    configurations: Vec<DebugTemplate>,
+    load_configurations_task: Task<()>,
+    /// The configuration of the latest session, started again by [`crate::Start`].
+    last_configuration: Option<DebugTemplate>,
+    session: Option<Session>,
+    session_task: Task<Option<()>>,
+    next_session_id: usize,
+    console: Vec<ConsoleEntry>,
+    console_input: View<Editor>,
+    watches: Vec<Watch>,
+    watch_input: View<Editor>,
+    /// The editor showing the line on which the debuggee stopped.
+    stopped_editor: Option<WeakView<Editor>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+#[derive(Debug)]
+pub enum Event {
+    Focus,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedDebugPanel {
+    width: Option<Pixels>,
+    height: Option<Pixels>,
+}
+
+impl DebugPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(DEBUG_PANEL_KEY) })
+            .await
+            .context("loading debug panel")
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedDebugPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let panel = Self::new(workspace, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    panel.height = serialized_panel.height.map(|px| px.round());
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        let workspace_handle = workspace.weak_handle();
+        cx.new_view(|cx| {
+            let focus_handle = cx.focus_handle();
+            let focus_subscription = cx.on_focus(&focus_handle, Self::focus_in);
+
+            let mut debug_panel_settings = *DebugPanelSettings::get_global(cx);
+            let settings_subscription = cx.observe_global::<SettingsStore>(move |_, cx| {
+                let new_settings = *DebugPanelSettings::get_global(cx);
+                if debug_panel_settings != new_settings {
+                    debug_panel_settings = new_settings;
+                    cx.notify();
+                }
+            });
+            let project_subscription = cx.subscribe(&project, |panel, _, event, cx| match event {
+                project::Event::BreakpointsChanged(project_path) => {
+                    panel.send_breakpoints(project_path, cx);
+                }
+                project::Event::WorktreeAdded | project::Event::WorktreeRemoved(_) => {
+                    panel.load_configurations(cx);
+                }
+                _ => {}
+            });
+
+            let console_input = cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("Evaluate an expression", cx);
+                editor
+            });
+            let watch_input = cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("Add a watch expression", cx);
+                editor
+            });
+
+            let mut panel = Self {
+                fs,
+                workspace: workspace_handle,
+                project,
+                width: None,
+                height: None,
+                focus_handle,
+                pending_serialization: Task::ready(None),
+                configurations: Vec::new(),
+                load_configurations_task: Task::ready(()),
+                last_configuration: None,
+                session: None,
+                session_task: Task::ready(None),
+                next_session_id: 0,
+                console: Vec::new(),
+                console_input,
+                watches: Vec::new(),
+                watch_input,
+                stopped_editor: None,
+                _subscriptions: vec![
+                    settings_subscription,
+                    focus_subscription,
+                    project_subscription,
+                ],
+            };
+            panel.load_configurations(cx);
+            panel
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        let height = self.height;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        DEBUG_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedDebugPanel { width, height })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn dispatch_context(&self, _: &ViewContext<Self>) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("DebugPanel");
+        dispatch_context
+    }
+
+    fn focus_in(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.focus_handle.contains_focused(cx) {
+            cx.emit(Event::Focus);
+        }
+    }
+
+    /// Reads the debug configurations of the `.zed/debug.json` files of the local worktrees,
+    /// followed by the global ones.
+    fn load_configurations(&mut self, cx: &mut ViewContext<Self>) {
+        let fs = self.fs.clone();
+        let mut paths = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter_map(|worktree| {
+                let worktree = worktree.read(cx);
+                worktree
+                    .is_local()
+                    .then(|| worktree.abs_path().join(*paths::LOCAL_DEBUG_RELATIVE_PATH))
+            })
+            .collect::<Vec<_>>();
+        paths.push(paths::DEBUG.clone());
+
+        self.load_configurations_task = cx.spawn(|panel, mut cx| async move {
+            let mut configurations = Vec::new();
+            for path in paths {
+                // Most projects have no debug configurations.
+                let Ok(contents) = fs.load(&path).await else {
+                    continue;
+                };
+                match serde_json_lenient::from_str::<DebugTemplates>(&contents) {
+                    Ok(templates) => configurations.extend(templates.0),
+                    Err(error) => {
+                        log::error!("failed to parse debug configurations at {path:?}: {error}")
+                    }
+                }
+            }
+            panel
+                .update(&mut cx, |panel, cx| {
+                    panel.configurations = configurations;
+                    cx.notify();
+                })
+                .ok();
+        });
+    }
+
+    /// Starts the configuration of the latest session again, or the only configuration there
+    /// is. Otherwise the configurations are reloaded to pick one from.
+    pub(crate) fn start(&mut self, cx: &mut ViewContext<Self>) {
+        let configuration =
+            self.last_configuration
+                .clone()
+                .or_else(|| match self.configurations.as_slice() {
+                    [configuration] => Some(configuration.clone()),
+                    _ => None,
+                });
+        match configuration {
+            Some(configuration) => self.start_session(configuration, cx),
+            None => self.load_configurations(cx),
+        }
+    }
+
+    fn start_session(&mut self, configuration: DebugTemplate, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        self.stop(cx);
+
+        let task_context = workspace.update(cx, |workspace, cx| {
+            editor::tasks::task_context(workspace, cx)
+        });
+        let default_cwd = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .unwrap_or_else(|| HOME.clone());
+        let id = DebugAdapterClientId(post_inc(&mut self.next_session_id));
+        self.last_configuration = Some(configuration.clone());
+        self.console.clear();
+        self.push_console(
+            ConsoleEntryKind::Output,
+            format!("Starting {}", configuration.label),
+            cx,
+        );
+
+        self.session_task = cx.spawn(|panel, mut cx| async move {
+            let task_context = task_context.await;
+            let Some(config) = configuration.resolve(&task_context) else {
+                panel
+                    .update(&mut cx, |panel, cx| {
+                        let message = format!(
+                            "Failed to resolve the variables of {}",
+                            configuration.label
+                        );
+                        panel.push_console(ConsoleEntryKind::Error, message, cx);
+                    })
+                    .ok();
+                return None;
+            };
+
+            let (session, mut events) =
+                match Session::start(id, &config, &default_cwd, (*cx).clone()).await {
+                    Ok(session) => session,
+                    Err(error) => {
+                        panel
+                            .update(&mut cx, |panel, cx| {
+                                let message = format!("Failed to start {}: {error:#}", config.label);
+                                panel.push_console(ConsoleEntryKind::Error, message, cx);
+                            })
+                            .ok();
+                        return None;
+                    }
+                };
+            let mut launch = session.launch(&config).fuse();
+            panel
+                .update(&mut cx, |panel, cx| {
+                    panel.session = Some(session);
+                    cx.notify();
+                })
+                .ok()?;
+
+            loop {
+                futures::select_biased! {
+                    result = launch => {
+                        if let Err(error) = result {
+                            panel
+                                .update(&mut cx, |panel, cx| {
+                                    let message = format!("Failed to start the debuggee: {error:#}");
+                                    panel.push_console(ConsoleEntryKind::Error, message, cx);
+                                    panel.end_session(cx);
+                                })
+                                .ok();
+                            return None;
+                        }
+                    }
+                    event = events.next() => {
+                        let event = event?;
+                        panel
+                            .update(&mut cx, |panel, cx| panel.handle_session_event(id, event, cx))
+                            .ok()?;
+                    }
+                }
+            }
+        });
+        cx.notify();
+    }
+
+    fn handle_session_event(
+        &mut self,
+        id: DebugAdapterClientId,
+        event: SessionEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(session) = self.session_mut(id) else {
+            return;
+        };
+        match event {
+            SessionEvent::Initialized => {
+                session.status = SessionStatus::Running;
+                let breakpoints = self.project.read(cx).all_breakpoints(cx);
+                let Some(session) = self.session.as_ref() else {
+                    return;
+                };
+                let requests = breakpoints
+                    .iter()
+                    .map(|(abs_path, rows)| session.set_breakpoints(abs_path, rows))
+                    .collect::<Vec<_>>();
+                let configuration_done = session
+                    .client
+                    .capabilities()
+                    .supports_configuration_done_request
+                    .unwrap_or(false)
+                    .then(|| session.client.request::<requests::ConfigurationDone>(()));
+                cx.background_executor()
+                    .spawn(async move {
+                        for result in join_all(requests).await {
+                            result.log_err();
+                        }
+                        if let Some(configuration_done) = configuration_done {
+                            configuration_done.await.log_err();
+                        }
+                    })
+                    .detach();
+            }
+            SessionEvent::Stopped { thread_id, reason } => {
+                session.status = SessionStatus::Stopped;
+                session.clear_stopped_state();
+                if thread_id.is_some() {
+                    session.active_thread_id = thread_id;
+                }
+                log::debug!("debuggee stopped: {reason}");
+                self.fetch_threads(id, cx);
+                if let Some(thread_id) = thread_id {
+                    self.fetch_stack_trace(id, thread_id, cx);
+                }
+            }
+            SessionEvent::Continued => {
+                session.status = SessionStatus::Running;
+                session.clear_stopped_state();
+                self.clear_stopped_line(cx);
+            }
+            SessionEvent::Output { category, output } => {
+                let kind = match category {
+                    Some(OutputCategory::Telemetry) => return,
+                    Some(OutputCategory::Stderr) => ConsoleEntryKind::Error,
+                    _ => ConsoleEntryKind::Output,
+                };
+                self.push_console(kind, output.trim_end().to_string(), cx);
+            }
+            SessionEvent::ThreadsChanged => self.fetch_threads(id, cx),
+            SessionEvent::Exited { exit_code } => {
+                session.status = SessionStatus::Exited;
+                let message = format!("Process exited with code {exit_code}");
+                self.push_console(ConsoleEntryKind::Output, message, cx);
+            }
+            SessionEvent::Terminated => self.end_session(cx),
+        }
+        cx.notify();
+    }
+
+    fn session_mut(&mut self, id: DebugAdapterClientId) -> Option<&mut Session> {
+        self.session
+            .as_mut()
+            .filter(|session| session.client.id() == id)
+    }
+
+    /// Shuts the debug adapter of the current session down.
+    fn end_session(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(session) = self.session.take() {
+            if let Some(shutdown) = session.client.shutdown() {
+                cx.background_executor().spawn(shutdown).detach();
+            }
+        }
+        self.clear_stopped_line(cx);
+        cx.notify();
+    }
+
+    /// Sends the breakpoints of a file to the debug adapter, after they were changed.
+    fn send_breakpoints(&mut self, project_path: &ProjectPath, cx: &mut ViewContext<Self>) {
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+        if session.status == SessionStatus::Starting {
+            return;
+        }
+        let project = self.project.read(cx);
+        let Some(abs_path) = project.absolute_path(project_path, cx) else {
+            return;
+        };
+        let rows = project
+            .all_breakpoints(cx)
+            .remove(&abs_path)
+            .unwrap_or_default();
+        cx.background_executor()
+            .spawn(session.set_breakpoints(&abs_path, &rows).log_err())
+            .detach();
+    }
+
+    fn fetch_threads(&mut self, id: DebugAdapterClientId, cx: &mut ViewContext<Self>) {
+        let Some(session) = self.session_mut(id) else {
+            return;
+        };
+        let request = session.client.request::<requests::Threads>(());
+        cx.spawn(|panel, mut cx| async move {
+            let threads = request.await?.threads;
+            panel.update(&mut cx, |panel, cx| {
+                if let Some(session) = panel.session_mut(id) {
+                    if session.active_thread_id.is_none() {
+                        session.active_thread_id = threads.first().map(|thread| thread.id);
+                    }
+                    session.threads = threads;
+                    cx.notify();
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn fetch_stack_trace(
+        &mut self,
+        id: DebugAdapterClientId,
+        thread_id: u64,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(session) = self.session_mut(id) else {
+            return;
+        };
+        let request = session
+            .client
+            .request::<requests::StackTrace>(StackTraceArguments {
+                thread_id,
+                ..Default::default()
+            });
+        cx.spawn(|panel, mut cx| async move {
+            let stack_frames = request.await?.stack_frames;
+            panel.update(&mut cx, |panel, cx| {
+                let Some(session) = panel.session_mut(id) else {
+                    return;
+                };
+                let top_frame = stack_frames.first().cloned();
+                session.stack_frames = stack_frames;
+                if let Some(frame) = top_frame {
+                    panel.select_frame(id, frame, cx);
+                }
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn select_thread(&mut self, thread_id: u64, cx: &mut ViewContext<Self>) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        session.active_thread_id = Some(thread_id);
+        if session.is_stopped() {
+            session.clear_stopped_state();
+            let id = session.client.id();
+            self.fetch_stack_trace(id, thread_id, cx);
+        }
+        cx.notify();
+    }
+
+    /// Shows the variables of the stack frame and the line it is at.
+    fn select_frame(
+        &mut self,
+        id: DebugAdapterClientId,
+        frame: StackFrame,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(session) = self.session_mut(id) else {
+            return;
+        };
+        session.active_frame_id = Some(frame.id);
+        session.scopes.clear();
+        session.variables.clear();
+        let request = session
+            .client
+            .request::<requests::Scopes>(ScopesArguments { frame_id: frame.id });
+        cx.spawn(|panel, mut cx| async move {
+            let scopes = request.await?.scopes;
+            panel.update(&mut cx, |panel, cx| {
+                let Some(session) = panel.session_mut(id) else {
+                    return;
+                };
+                let references = scopes
+                    .iter()
+                    .filter(|scope| !scope.expensive)
+                    .map(|scope| scope.variables_reference)
+                    .collect::<Vec<_>>();
+                session.scopes = scopes;
+                session
+                    .expanded_variables
+                    .extend(references.iter().copied());
+                for reference in references {
+                    panel.fetch_variables(id, reference, cx);
+                }
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+
+        self.evaluate_watches(cx);
+        self.show_frame_line(&frame, cx);
+        cx.notify();
+    }
+
+    fn fetch_variables(
+        &mut self,
+        id: DebugAdapterClientId,
+        variables_reference: u64,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(session) = self.session_mut(id) else {
+            return;
+        };
+        let request = session
+            .client
+            .request::<requests::Variables>(VariablesArguments {
+                variables_reference,
+                ..Default::default()
+            });
+        cx.spawn(|panel, mut cx| async move {
+            let variables = request.await?.variables;
+            panel.update(&mut cx, |panel, cx| {
+                if let Some(session) = panel.session_mut(id) {
+                    session.variables.insert(variables_reference, variables);
+                    cx.notify();
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn toggle_variable(&mut self, variables_reference: u64, cx: &mut ViewContext<Self>) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        if !session.expanded_variables.remove(&variables_reference) {
+            session.expanded_variables.insert(variables_reference);
+            if !session.variables.contains_key(&variables_reference) {
+                let id = session.client.id();
+                self.fetch_variables(id, variables_reference, cx);
+            }
+        }
+        cx.notify();
+    }
+
+    /// Opens the file of the stack frame and highlights the line it is at.
+    fn show_frame_line(&mut self, frame: &StackFrame, cx: &mut ViewContext<Self>) {
+        let Some(path) = frame.source.as_ref().and_then(|source| source.path.clone()) else {
+            return;
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let row = (frame.line as u32).saturating_sub(1);
+        let open_task = workspace.update(cx, |workspace, cx| {
+            workspace.open_abs_path(path.into(), false, cx)
+        });
+        cx.spawn(|panel, mut cx| async move {
+            let item = open_task.await?;
+            panel.update(&mut cx, |panel, cx| {
+                let Some(editor) = item.downcast::<Editor>() else {
+                    return;
+                };
+                panel.clear_stopped_line(cx);
+                editor.update(cx, |editor, cx| {
+                    let point = Point::new(row, 0);
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let anchor = snapshot.anchor_before(point);
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([point..point])
+                    });
+                    editor.highlight_rows::<DebugStackFrameLine>(
+                        anchor..=anchor,
+                        Some(cx.theme().status().warning_background),
+                        false,
+                        cx,
+                    );
+                });
+                panel.stopped_editor = Some(editor.downgrade());
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn clear_stopped_line(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(editor) = self
+            .stopped_editor
+            .take()
+            .and_then(|editor| editor.upgrade())
+        {
+            editor.update(cx, |editor, cx| {
+                editor.clear_row_highlights::<DebugStackFrameLine>();
+                cx.notify();
+            });
+        }
+    }
+
+    /// Evaluates an expression in the active stack frame, if there is one.
+    fn evaluate(
+        &self,
+        expression: &str,
+        context: EvaluateContext,
+    ) -> Option<impl 'static + Send + std::future::Future<Output = anyhow::Result<String>>> {
+        let session = self.session.as_ref()?;
+        let request = session
+            .client
+            .request::<requests::Evaluate>(EvaluateArguments {
+                expression: expression.to_string(),
+                frame_id: session.active_frame_id,
+                context: Some(context),
+            });
+        Some(async move { Ok(request.await?.result) })
+    }
+
+    fn evaluate_watches(&mut self, cx: &mut ViewContext<Self>) {
+        for ix in 0..self.watches.len() {
+            self.evaluate_watch(ix, cx);
+        }
+    }
+
+    fn evaluate_watch(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let expression = self.watches[ix].expression.clone();
+        let Some(evaluation) = self.evaluate(&expression, EvaluateContext::Watch) else {
+            return;
+        };
+        cx.spawn(|panel, mut cx| async move {
+            let value = evaluation
+                .await
+                .map(SharedString::from)
+                .map_err(|error| SharedString::from(error.to_string()));
+            panel
+                .update(&mut cx, |panel, cx| {
+                    // The watches may have changed in the meantime.
+                    if let Some(watch) = panel
+                        .watches
+                        .iter_mut()
+                        .find(|watch| watch.expression == expression)
+                    {
+                        watch.value = Some(value);
+                        cx.notify();
+                    }
+                })
+                .ok();
+        })
+        .detach();
+    }
+
+    fn add_watch(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        let expression = self.watch_input.read(cx).text(cx);
+        let expression = expression.trim();
+        if expression.is_empty() {
+            return;
+        }
+        self.watches.push(Watch {
+            expression: expression.to_string().into(),
+            value: None,
+        });
+        self.watch_input.update(cx, |editor, cx| editor.clear(cx));
+        if self.session.as_ref().map_or(false, Session::is_stopped) {
+            self.evaluate_watch(self.watches.len() - 1, cx);
+        }
+        cx.notify();
+    }
+
+    fn remove_watch(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        self.watches.remove(ix);
+        cx.notify();
+    }
+
+    fn evaluate_console_input(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        let expression = self.console_input.read(cx).text(cx);
+        if expression.trim().is_empty() {
+            return;
+        }
+        self.console_input.update(cx, |editor, cx| editor.clear(cx));
+        self.push_console(ConsoleEntryKind::Input, format!("> {expression}"), cx);
+        let Some(evaluation) = self.evaluate(&expression, EvaluateContext::Repl) else {
+            self.push_console(ConsoleEntryKind::Error, "No debug session".to_string(), cx);
+            return;
+        };
+        cx.spawn(|panel, mut cx| async move {
+            let result = evaluation.await;
+            panel
+                .update(&mut cx, |panel, cx| match result {
+                    Ok(value) => panel.push_console(ConsoleEntryKind::Result, value, cx),
+                    Err(error) => {
+                        panel.push_console(ConsoleEntryKind::Error, format!("{error:#}"), cx)
+                    }
+                })
+                .ok();
+        })
+        .detach();
+    }
+
+    fn push_console(&mut self, kind: ConsoleEntryKind, text: String, cx: &mut ViewContext<Self>) {
+        self.console.push(ConsoleEntry {
+            kind,
+            text: text.into(),
+        });
+        cx.notify();
+    }
+
+    pub(crate) fn continue_or_start(&mut self, cx: &mut ViewContext<Self>) {
+        match self.session.as_ref() {
+            Some(session) if session.is_stopped() => self.continue_(cx),
+            Some(_) => {}
+            None => self.start(cx),
+        }
+    }
+
+    fn continue_(&mut self, cx: &mut ViewContext<Self>) {
+        let Some((session, thread_id)) = self.stopped_thread() else {
+            return;
+        };
+        let request = session
+            .client
+            .request::<requests::Continue>(ContinueArguments {
+                thread_id,
+                single_thread: None,
+            });
+        self.resumed(cx);
+        cx.background_executor().spawn(request.log_err()).detach();
+    }
+
+    pub(crate) fn pause(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+        if session.status != SessionStatus::Running {
+            return;
+        }
+        let Some(thread_id) = session
+            .active_thread_id
+            .or_else(|| session.threads.first().map(|thread| thread.id))
+        else {
+            return;
+        };
+        let request = session
+            .client
+            .request::<requests::Pause>(PauseArguments { thread_id });
+        cx.background_executor().spawn(request.log_err()).detach();
+    }
+
+    pub(crate) fn step_over(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some((session, thread_id)) = self.stopped_thread() {
+            let request = session.client.request::<requests::Next>(StepArguments {
+                thread_id,
+                single_thread: None,
+            });
+            self.resumed(cx);
+            cx.background_executor().spawn(request.log_err()).detach();
+        }
+    }
+
+    pub(crate) fn step_into(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some((session, thread_id)) = self.stopped_thread() {
+            let request = session.client.request::<requests::StepIn>(StepArguments {
+                thread_id,
+                single_thread: None,
+            });
+            self.resumed(cx);
+            cx.background_executor().spawn(request.log_err()).detach();
+        }
+    }
+
+    pub(crate) fn step_out(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some((session, thread_id)) = self.stopped_thread() {
+            let request = session.client.request::<requests::StepOut>(StepArguments {
+                thread_id,
+                single_thread: None,
+            });
+            self.resumed(cx);
+            cx.background_executor().spawn(request.log_err()).detach();
+        }
+    }
+
+    pub(crate) fn stop(&mut self, cx: &mut ViewContext<Self>) {
+        self.session_task = Task::ready(None);
+        self.end_session(cx);
+    }
+
+    /// The session along with its active thread, if the debuggee is stopped.
+    fn stopped_thread(&self) -> Option<(&Session, u64)> {
+        let session = self
+            .session
+            .as_ref()
+            .filter(|session| session.is_stopped())?;
+        Some((session, session.active_thread_id?))
+    }
+
+    /// Forgets about the stopped state, as adapters need not send a `continued` event after
+    /// a request resuming the debuggee.
+    fn resumed(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(session) = self.session.as_mut() {
+            session.status = SessionStatus::Running;
+            session.clear_stopped_state();
+        }
+        self.clear_stopped_line(cx);
+        cx.notify();
+    }
+
+    fn render_toolbar(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let status = self.session.as_ref().map(|session| session.status);
+        let is_stopped = status == Some(SessionStatus::Stopped);
+        let is_running = status == Some(SessionStatus::Running);
+        let title = match &self.session {
+            Some(session) => {
+                let status = match session.status {
+                    SessionStatus::Starting => "starting",
+                    SessionStatus::Running => "running",
+                    SessionStatus::Stopped => "paused",
+                    SessionStatus::Exited => "exited",
+                };
+                format!("{} ({status})", session.label)
+            }
+            None => "No debug session".to_string(),
+        };
+
+        h_flex()
+            .p_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                IconButton::new("debug-continue", IconName::Play)
+                    .icon_size(IconSize::Small)
+                    .disabled(!is_stopped && self.session.is_some())
+                    .tooltip(move |cx| Tooltip::for_action("Continue", &Continue, cx))
+                    .on_click(cx.listener(|panel, _, cx| panel.continue_or_start(cx))),
+            )
+            .child(
+                IconButton::new("debug-pause", IconName::DebugPause)
+                    .icon_size(IconSize::Small)
+                    .disabled(!is_running)
+                    .tooltip(move |cx| Tooltip::for_action("Pause", &Pause, cx))
+                    .on_click(cx.listener(|panel, _, cx| panel.pause(cx))),
+            )
+            .child(
+                IconButton::new("debug-step-over", IconName::DebugStepOver)
+                    .icon_size(IconSize::Small)
+                    .disabled(!is_stopped)
+                    .tooltip(move |cx| Tooltip::for_action("Step Over", &StepOver, cx))
+                    .on_click(cx.listener(|panel, _, cx| panel.step_over(cx))),
+            )
+            .child(
+                IconButton::new("debug-step-into", IconName::DebugStepInto)
+                    .icon_size(IconSize::Small)
+                    .disabled(!is_stopped)
+                    .tooltip(move |cx| Tooltip::for_action("Step Into", &StepInto, cx))
+                    .on_click(cx.listener(|panel, _, cx| panel.step_into(cx))),
+            )
+            .child(
+                IconButton::new("debug-step-out", IconName::DebugStepOut)
+                    .icon_size(IconSize::Small)
+                    .disabled(!is_stopped)
+                    .tooltip(move |cx| Tooltip::for_action("Step Out", &StepOut, cx))
+                    .on_click(cx.listener(|panel, _, cx| panel.step_out(cx))),
+            )
+            .child(
+                IconButton::new("debug-stop", IconName::Stop)
+                    .icon_size(IconSize::Small)
+                    .disabled(self.session.is_none())
+                    .tooltip(move |cx| Tooltip::for_action("Stop", &Stop, cx))
+                    .on_click(cx.listener(|panel, _, cx| panel.stop(cx))),
+            )
+            .child(Label::new(title).size(LabelSize::Small).color(Color::Muted))
+    }
+
+    fn render_configurations(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .p_2()
+            .gap_1()
+            .when(self.configurations.is_empty(), |this| {
+                let path = paths::LOCAL_DEBUG_RELATIVE_PATH.display();
+                this.child(
+                    Label::new(format!(
+                        "Add debug configurations to {path} to start debugging"
+                    ))
+                    .color(Color::Muted),
+                )
+            })
+            .children(
+                self.configurations
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, configuration)| {
+                        let configuration = configuration.clone();
+                        ListItem::new(ElementId::from(SharedString::from(format!(
+                            "debug_configuration_{ix}"
+                        ))))
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(Label::new(configuration.label.clone()))
+                                .child(
+                                    Label::new(configuration.adapter.clone())
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                ),
+                        )
+                        .on_click(cx.listener(move |panel, _, cx| {
+                            panel.start_session(configuration.clone(), cx)
+                        }))
+                    }),
+            )
+    }
+
+    fn render_section(
+        &self,
+        id: &'static str,
+        title: &'static str,
+        content: impl IntoElement,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        v_flex()
+            .flex_1()
+            .min_h_0()
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(title).size(LabelSize::Small)),
+            )
+            .child(
+                div()
+                    .id(id)
+                    .flex_1()
+                    .min_h_0()
+                    .overflow_y_scroll()
+                    .text_ui(cx)
+                    .child(content),
+            )
+    }
+
+    fn render_threads(&self, session: &Session, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().children(session.threads.iter().map(|thread| {
+            let thread_id = thread.id;
+            ListItem::new(ElementId::from(SharedString::from(format!(
+                "debug_thread_{thread_id}"
+            ))))
+            .selected(session.active_thread_id == Some(thread_id))
+            .child(Label::new(thread.name.clone()))
+            .on_click(cx.listener(move |panel, _, cx| panel.select_thread(thread_id, cx)))
+        }))
+    }
+
+    fn render_stack_frames(
+        &self,
+        session: &Session,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let id = session.client.id();
+        v_flex().children(session.stack_frames.iter().map(|frame| {
+            let location = frame
+                .source
+                .as_ref()
+                .and_then(|source| {
+                    let name = source.name.clone().or_else(|| {
+                        let path = Path::new(source.path.as_ref()?);
+                        Some(path.file_name()?.to_string_lossy().into_owned())
+                    })?;
+                    Some(format!("{name}:{}", frame.line))
+                })
+                .unwrap_or_default();
+            let frame_to_select = frame.clone();
+            ListItem::new(ElementId::from(SharedString::from(format!(
+                "debug_stack_frame_{}",
+                frame.id
+            ))))
+            .selected(session.active_frame_id == Some(frame.id))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(frame.name.clone()))
+                    .child(
+                        Label::new(location)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .on_click(
+                cx.listener(move |panel, _, cx| {
+                    panel.select_frame(id, frame_to_select.clone(), cx)
+                }),
+            )
+        }))
+    }
+
+    fn render_variables(&self, session: &Session, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let mut items = Vec::new();
+        for scope in &session.scopes {
+            let reference = scope.variables_reference;
+            items.push(
+                ListItem::new(ElementId::from(SharedString::from(format!(
+                    "debug_scope_{reference}"
+                ))))
+                .toggle(Some(session.expanded_variables.contains(&reference)))
+                .on_toggle(cx.listener(move |panel, _, cx| panel.toggle_variable(reference, cx)))
+                .child(Label::new(scope.name.clone()))
+                .into_any_element(),
+            );
+            self.render_variable_children(session, reference, 1, &mut items, cx);
+        }
+        v_flex().children(items)
+    }
+
+    fn render_variable_children(
+        &self,
+        session: &Session,
+        reference: u64,
+        depth: usize,
+        items: &mut Vec<gpui::AnyElement>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if !session.expanded_variables.contains(&reference) {
+            return;
+        }
+        let Some(variables) = session.variables.get(&reference) else {
+            return;
+        };
+        for (ix, variable) in variables.iter().enumerate() {
+            let child_reference = variable.variables_reference;
+            let toggle = (child_reference > 0)
+                .then(|| session.expanded_variables.contains(&child_reference));
+            items.push(
+                ListItem::new(ElementId::from(SharedString::from(format!(
+                    "debug_variable_{reference}_{ix}"
+                ))))
+                .indent_level(depth)
+                .indent_step_size(px(12.))
+                .toggle(toggle)
+                .on_toggle(
+                    cx.listener(move |panel, _, cx| panel.toggle_variable(child_reference, cx)),
+                )
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(Label::new(variable.name.clone()))
+                        .child(Label::new(variable.value.clone()).color(Color::Muted))
+                        .when_some(variable.type_.clone(), |this, type_| {
+                            this.child(
+                                Label::new(type_)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Disabled),
+                            )
+                        }),
+                )
+                .into_any_element(),
+            );
+            // Variables referring to their ancestors would expand forever.
+            if child_reference > 0 && child_reference != reference && depth < 16 {
+                self.render_variable_children(session, child_reference, depth + 1, items, cx);
+            }
+        }
+    }
+
+    fn render_watches(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .children(self.watches.iter().enumerate().map(|(ix, watch)| {
+                let (value, color) = match &watch.value {
+                    Some(Ok(value)) => (value.clone(), Color::Muted),
+                    Some(Err(error)) => (error.clone(), Color::Error),
+                    None => (SharedString::default(), Color::Muted),
+                };
+                ListItem::new(ElementId::from(SharedString::from(format!(
+                    "debug_watch_{ix}"
+                ))))
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(Label::new(watch.expression.clone()))
+                        .child(Label::new(value).color(color)),
+                )
+                .end_slot(
+                    IconButton::new(("remove-watch", ix), IconName::Close)
+                        .icon_size(IconSize::XSmall)
+                        .on_click(cx.listener(move |panel, _, cx| panel.remove_watch(ix, cx))),
+                )
+            }))
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .on_action(cx.listener(Self::add_watch))
+                    .child(self.watch_input.clone()),
+            )
+    }
+
+    fn render_console(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .flex_1()
+            .min_h_0()
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new("Console").size(LabelSize::Small)),
+            )
+            .child(
+                v_flex()
+                    .id("debug-console")
+                    .flex_1()
+                    .min_h_0()
+                    .px_2()
+                    .overflow_y_scroll()
+                    .text_ui(cx)
+                    .children(self.console.iter().map(|entry| {
+                        let color = match entry.kind {
+                            ConsoleEntryKind::Output => Color::Default,
+                            ConsoleEntryKind::Error => Color::Error,
+                            ConsoleEntryKind::Input => Color::Muted,
+                            ConsoleEntryKind::Result => Color::Accent,
+                        };
+                        Label::new(entry.text.clone())
+                            .size(LabelSize::Small)
+                            .color(color)
+                    })),
+            )
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .on_action(cx.listener(Self::evaluate_console_input))
+                    .child(self.console_input.clone()),
+            )
+    }
+}
+
+impl Panel for DebugPanel {
+    fn persistent_name() -> &'static str {
+        "Debug Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match DebugPanelSettings::get_global(cx).dock {
+            DebugPanelDockPosition::Left => DockPosition::Left,
+            DebugPanelDockPosition::Bottom => DockPosition::Bottom,
+            DebugPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<DebugPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings| {
+                let dock = match position {
+                    DockPosition::Left => DebugPanelDockPosition::Left,
+                    DockPosition::Bottom => DebugPanelDockPosition::Bottom,
+                    DockPosition::Right => DebugPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        let settings = DebugPanelSettings::get_global(cx);
+        match self.position(cx) {
+            DockPosition::Left | DockPosition::Right => {
+                self.width.unwrap_or(settings.default_width)
+            }
+            DockPosition::Bottom => self.height.unwrap_or(settings.default_height),
+        }
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        match self.position(cx) {
+            DockPosition::Left | DockPosition::Right => self.width = size,
+            DockPosition::Bottom => self.height = size,
+        }
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        DebugPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::Debug)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Debug Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+impl FocusableView for DebugPanel {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<Event> for DebugPanel {}
+
+impl EventEmitter<PanelEvent> for DebugPanel {}
+
+impl Render for DebugPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let body = match &self.session {
+            Some(session) => h_flex()
+                .flex_1()
+                .min_h_0()
+                .items_start()
+                .child(
+                    v_flex()
+                        .flex_1()
+                        .h_full()
+                        .border_r_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .child(self.render_section(
+                            "debug-threads",
+                            "Threads",
+                            self.render_threads(session, cx),
+                            cx,
+                        ))
+                        .child(self.render_section(
+                            "debug-call-stack",
+                            "Call Stack",
+                            self.render_stack_frames(session, cx),
+                            cx,
+                        )),
+                )
+                .child(
+                    v_flex()
+                        .flex_1()
+                        .h_full()
+                        .border_r_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .child(self.render_section(
+                            "debug-variables",
+                            "Variables",
+                            self.render_variables(session, cx),
+                            cx,
+                        ))
+                        .child(self.render_section(
+                            "debug-watches",
+                            "Watches",
+                            self.render_watches(cx),
+                            cx,
+                        )),
+                )
+                .child(v_flex().flex_1().h_full().child(self.render_console(cx)))
+                .into_any_element(),
+            None => h_flex()
+                .flex_1()
+                .min_h_0()
+                .items_start()
+                .child(
+                    v_flex()
+                        .flex_1()
+                        .h_full()
+                        .border_r_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .child(self.render_configurations(cx)),
+                )
+                .child(v_flex().flex_1().h_full().child(self.render_console(cx)))
+                .into_any_element(),
+        };
+
+        v_flex()
+            .id("debug-panel")
+            .size_full()
+            .key_context(self.dispatch_context(cx))
+            .track_focus(&self.focus_handle)
+            .child(self.render_toolbar(cx))
+            .child(body)
+    }
+}
@@ -0,0 +1,76 @@
+mod debug_panel;
+mod debug_panel_settings;
+mod session;
+
+use debug_panel_settings::DebugPanelSettings;
+use gpui::{actions, AppContext, ViewContext};
+use settings::Settings;
+use workspace::Workspace;
+
+pub use debug_panel::DebugPanel;
+
+actions!(
+    debugger_ui,
+    [
+        ToggleFocus,
+        Start,
+        Continue,
+        Pause,
+        StepOver,
+        StepInto,
+        StepOut,
+        Stop,
+    ]
+);
+
+pub fn init_settings(cx: &mut AppContext) {
+    DebugPanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut AppContext) {
+    init_settings(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, _: &ToggleFocus, cx| {
+                workspace.toggle_panel_focus::<DebugPanel>(cx);
+            })
+            .register_action(|workspace, _: &Start, cx| {
+                if let Some(panel) = workspace.focus_panel::<DebugPanel>(cx) {
+                    panel.update(cx, |panel, cx| panel.start(cx));
+                }
+            })
+            .register_action(|workspace, _: &Continue, cx| {
+                if let Some(panel) = workspace.focus_panel::<DebugPanel>(cx) {
+                    panel.update(cx, |panel, cx| panel.continue_or_start(cx));
+                }
+            })
+            .register_action(|workspace, _: &Pause, cx| {
+                update_panel(workspace, cx, DebugPanel::pause);
+            })
+            .register_action(|workspace, _: &StepOver, cx| {
+                update_panel(workspace, cx, DebugPanel::step_over);
+            })
+            .register_action(|workspace, _: &StepInto, cx| {
+                update_panel(workspace, cx, DebugPanel::step_into);
+            })
+            .register_action(|workspace, _: &StepOut, cx| {
+                update_panel(workspace, cx, DebugPanel::step_out);
+            })
+            .register_action(|workspace, _: &Stop, cx| {
+                update_panel(workspace, cx, DebugPanel::stop);
+            });
+    })
+    .detach();
+}
+
+/// Controls the debug session of the workspace's debug panel, if there is one.
+fn update_panel(
+    workspace: &mut Workspace,
+    cx: &mut ViewContext<Workspace>,
+    f: impl FnOnce(&mut DebugPanel, &mut ViewContext<DebugPanel>),
+) {
+    if let Some(panel) = workspace.panel::<DebugPanel>(cx) {
+        panel.update(cx, f);
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use collections::{HashMap, HashSet};
+use dap::{
+    events, requests, DebugAdapterBinary, DebugAdapterClient, DebugAdapterClientId,
+    DebugAdapterTransport, OutputCategory, Scope, SetBreakpointsArguments, Source,
+    SourceBreakpoint, StackFrame, Thread, Variable,
+};
+use futures::{
+    channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    Future,
+};
+use gpui::{AsyncAppContext, SharedString};
+use task::{DebugRequestKind, ResolvedDebugConfig};
+
+/// What the debug adapter reported about the debuggee, forwarded from its event handlers to
+/// the debug panel.
+pub(crate) enum SessionEvent {
+    Initialized,
+    Stopped {
+        thread_id: Option<u64>,
+        reason: String,
+    },
+    Continued,
+    Output {
+        category: Option<OutputCategory>,
+        output: String,
+    },
+    ThreadsChanged,
+    Exited {
+        exit_code: i64,
+    },
+    Terminated,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SessionStatus {
+    /// The debug adapter is being started and configured.
+    Starting,
+    Running,
+    Stopped,
+    /// The debuggee exited, while the debug adapter may still be around.
+    Exited,
+}
+
+/// A line of the debug console.
+pub(crate) struct ConsoleEntry {
+    pub kind: ConsoleEntryKind,
+    pub text: SharedString,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ConsoleEntryKind {
+    /// The output of the debuggee or of the debug adapter.
+    Output,
+    /// An error reported by the debug adapter.
+    Error,
+    /// An expression entered in the console.
+    Input,
+    /// The result of evaluating an expression entered in the console.
+    Result,
+}
+
+/// An expression evaluated whenever the debuggee stops.
+pub(crate) struct Watch {
+    pub expression: SharedString,
+    /// The value of the expression in the active stack frame, or the error evaluating it.
+    pub value: Option<Result<SharedString, SharedString>>,
+}
+
+/// A running debug adapter along with what is known about the debuggee it controls.
+pub(crate) struct Session {
+    pub label: SharedString,
+    pub client: Arc<DebugAdapterClient>,
+    pub status: SessionStatus,
+    pub threads: Vec<Thread>,
+    pub active_thread_id: Option<u64>,
+    pub stack_frames: Vec<StackFrame>,
+    pub active_frame_id: Option<u64>,
+    pub scopes: Vec<Scope>,
+    /// The fetched children of variables and scopes, keyed by their variables reference.
+    pub variables: HashMap<u64, Vec<Variable>>,
+    pub expanded_variables: HashSet<u64>,
+    _subscriptions: Vec<dap::Subscription>,
+}
+
+impl Session {
+    /// Starts the debug adapter of the configuration and initializes it, returning the session
+    /// along with the events of the adapter. The `launch` or `attach` request is left to the
+    /// caller, as adapters may only respond to it once they are configured.
+    pub(crate) async fn start(
+        id: DebugAdapterClientId,
+        config: &ResolvedDebugConfig,
+        default_cwd: &Path,
+        cx: AsyncAppContext,
+    ) -> Result<(Self, UnboundedReceiver<SessionEvent>)> {
+        let binary = DebugAdapterBinary {
+            path: PathBuf::from(&config.adapter),
+            arguments: config.adapter_args.iter().map(Into::into).collect(),
+            env: Some(config.env.clone()),
+            transport: match config.tcp_port {
+                Some(port) => DebugAdapterTransport::Tcp {
+                    host: [127, 0, 0, 1].into(),
+                    port,
+                },
+                None => DebugAdapterTransport::Stdio,
+            },
+        };
+        let cwd = config.cwd.as_deref().unwrap_or(default_cwd);
+        let client =
+            DebugAdapterClient::new(id, config.label.as_str().into(), binary, cwd, cx.clone())
+                .await?;
+
+        let (events_tx, events_rx) = mpsc::unbounded();
+        let subscriptions = subscribe_to_events(&client, events_tx);
+        let client = cx
+            .update(|cx| client.initialize(config.adapter_id.clone(), cx))?
+            .await?;
+
+        let session = Self {
+            label: config.label.clone().into(),
+            client,
+            status: SessionStatus::Starting,
+            threads: Vec::new(),
+            active_thread_id: None,
+            stack_frames: Vec::new(),
+            active_frame_id: None,
+            scopes: Vec::new(),
+            variables: HashMap::default(),
+            expanded_variables: HashSet::default(),
+            _subscriptions: subscriptions,
+        };
+        Ok((session, events_rx))
+    }
+
+    /// Sends the `launch` or `attach` request of the configuration.
+    pub(crate) fn launch(
+        &self,
+        config: &ResolvedDebugConfig,
+    ) -> impl 'static + Send + Future<Output = Result<()>> {
+        match config.request {
+            DebugRequestKind::Launch => futures::future::Either::Left(
+                self.client
+                    .request::<requests::Launch>(config.config.clone()),
+            ),
+            DebugRequestKind::Attach => futures::future::Either::Right(
+                self.client
+                    .request::<requests::Attach>(config.config.clone()),
+            ),
+        }
+    }
+
+    /// Replaces the breakpoints of the file at the given path with ones on the given zero-based
+    /// rows.
+    pub(crate) fn set_breakpoints(
+        &self,
+        abs_path: &Path,
+        rows: &[u32],
+    ) -> impl 'static + Send + Future<Output = Result<()>> {
+        let request = self
+            .client
+            .request::<requests::SetBreakpoints>(SetBreakpointsArguments {
+                source: Source {
+                    name: abs_path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned()),
+                    path: Some(abs_path.to_string_lossy().into_owned()),
+                    source_reference: None,
+                },
+                breakpoints: Some(
+                    rows.iter()
+                        .map(|row| SourceBreakpoint {
+                            line: *row as u64 + 1,
+                            ..Default::default()
+                        })
+                        .collect(),
+                ),
+                source_modified: Some(false),
+            });
+        async move {
+            request.await?;
+            Ok(())
+        }
+    }
+
+    /// Forgets everything about the state of a stopped debuggee, now that it runs again.
+    pub(crate) fn clear_stopped_state(&mut self) {
+        self.stack_frames.clear();
+        self.active_frame_id = None;
+        self.scopes.clear();
+        self.variables.clear();
+    }
+
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.status == SessionStatus::Stopped
+    }
+}
+
+fn subscribe_to_events(
+    client: &DebugAdapterClient,
+    events_tx: UnboundedSender<SessionEvent>,
+) -> Vec<dap::Subscription> {
+    vec![
+        client.on_event::<events::Initialized, _>({
+            let events_tx = events_tx.clone();
+            move |_, _| {
+                events_tx.unbounded_send(SessionEvent::Initialized).ok();
+            }
+        }),
+        client.on_event::<events::Stopped, _>({
+            let events_tx = events_tx.clone();
+            move |body, _| {
+                events_tx
+                    .unbounded_send(SessionEvent::Stopped {
+                        thread_id: body.thread_id,
+                        reason: body.reason,
+                    })
+                    .ok();
+            }
+        }),
+        client.on_event::<events::Continued, _>({
+            let events_tx = events_tx.clone();
+            move |_, _| {
+                events_tx.unbounded_send(SessionEvent::Continued).ok();
+            }
+        }),
+        client.on_event::<events::Output, _>({
+            let events_tx = events_tx.clone();
+            move |body, _| {
+                events_tx
+                    .unbounded_send(SessionEvent::Output {
+                        category: body.category,
+                        output: body.output,
+                    })
+                    .ok();
+            }
+        }),
+        client.on_event::<events::Thread, _>({
+            let events_tx = events_tx.clone();
+            move |_, _| {
+                events_tx.unbounded_send(SessionEvent::ThreadsChanged).ok();
+            }
+        }),
+        client.on_event::<events::Exited, _>({
+            let events_tx = events_tx.clone();
+            move |body, _| {
+                events_tx
+                    .unbounded_send(SessionEvent::Exited {
+                        exit_code: body.exit_code,
+                    })
+                    .ok();
+            }
+        }),
+        client.on_event::<events::Terminated, _>(move |_, _| {
+            events_tx.unbounded_send(SessionEvent::Terminated).ok();
+        }),
+    ]
+}
@@ -131,6 +131,24 @@ pub struct ShowCompletions {
     pub(super) trigger: Option<char>,
 }
 
+#[derive(PartialEq, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectTextObject {
+    pub text_object: TextObject,
+}
+
+#[derive(PartialEq, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveToNextTextObject {
+    pub text_object: TextObject,
+}
+
+#[derive(PartialEq, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveToPreviousTextObject {
+    pub text_object: TextObject,
+}
+
 impl_actions!(
     editor,
     [
@@ -145,10 +163,13 @@ impl_actions!(
         MovePageUp,
         MoveToBeginningOfLine,
         MoveToEndOfLine,
+        MoveToNextTextObject,
+        MoveToPreviousTextObject,
         MoveUpByLines,
         SelectDownByLines,
         SelectNext,
         SelectPrevious,
+        SelectTextObject,
         SelectToBeginningOfLine,
         SelectToEndOfLine,
         SelectUpByLines,
@@ -204,6 +225,8 @@ gpui::actions!(
         ExpandAllHunkDiffs,
         ExpandMacroRecursively,
         FindAllReferences,
+        FindSubtypes,
+        FindSupertypes,
         Fold,
         FoldSelectedRanges,
         Format,
@@ -282,12 +305,16 @@ gpui::actions!(
         SelectUp,
         ShowCharacterPalette,
         ShowInlineCompletion,
+        ShowSignatureHelp,
         ShuffleLines,
+        SignatureHelpNext,
+        SignatureHelpPrevious,
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
         SplitSelectionIntoLines,
         Tab,
         TabPrev,
+        ToggleBreakpoint,
         ToggleGitBlame,
         ToggleGitBlameInline,
         ToggleHunkDiff,
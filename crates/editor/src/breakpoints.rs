@@ -0,0 +1,90 @@
+use std::ops::Range;
+
+use collections::HashSet;
+use gpui::{AppContext, ViewContext};
+use language::{Point, ToPoint as _};
+use multi_buffer::{MultiBufferRow, ToPoint as _};
+
+use crate::{display_map::ToDisplayPoint, DisplayPoint, DisplayRow, Editor, EditorSnapshot};
+
+/// The display rows within the given range on which the project has a breakpoint, skipping
+/// the ones hidden in folds.
+pub(super) fn breakpoint_display_rows(
+    editor: &Editor,
+    snapshot: &EditorSnapshot,
+    visible_rows: Range<DisplayRow>,
+    cx: &AppContext,
+) -> HashSet<DisplayRow> {
+    let mut display_rows = HashSet::default();
+    let Some(project) = editor.project.as_ref() else {
+        return display_rows;
+    };
+    let project = project.read(cx);
+    let multi_buffer = editor.buffer.read(cx);
+    for (excerpt_id, buffer_snapshot, excerpt_range) in snapshot.buffer_snapshot.excerpts() {
+        let Some(buffer) = multi_buffer.buffer(buffer_snapshot.remote_id()) else {
+            continue;
+        };
+        let context = &excerpt_range.context;
+        let context_rows =
+            context.start.to_point(buffer_snapshot).row..=context.end.to_point(buffer_snapshot).row;
+        for row in project.breakpoint_rows(&buffer, cx) {
+            if !context_rows.contains(&row) {
+                continue;
+            }
+            let position = buffer_snapshot.anchor_before(Point::new(row, 0));
+            let Some(position) = snapshot
+                .buffer_snapshot
+                .anchor_in_excerpt(excerpt_id, position)
+            else {
+                continue;
+            };
+            let multibuffer_point = position.to_point(&snapshot.buffer_snapshot);
+            if snapshot.is_line_folded(MultiBufferRow(multibuffer_point.row)) {
+                continue;
+            }
+            let display_row = multibuffer_point.to_display_point(snapshot).row();
+            if visible_rows.contains(&display_row) {
+                display_rows.insert(display_row);
+            }
+        }
+    }
+    display_rows
+}
+
+/// Toggles a breakpoint on the buffer row shown on each of the given display rows.
+pub(super) fn toggle_breakpoints(
+    editor: &mut Editor,
+    display_rows: impl IntoIterator<Item = DisplayRow>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let snapshot = editor.snapshot(cx);
+    let multi_buffer = editor.buffer.read(cx);
+    let mut toggled = HashSet::default();
+    let mut to_toggle = Vec::new();
+    for display_row in display_rows {
+        let point = DisplayPoint::new(display_row, 0).to_point(&snapshot.display_snapshot);
+        let Some((buffer_snapshot, range)) = snapshot
+            .buffer_snapshot
+            .buffer_line_for_row(MultiBufferRow(point.row))
+        else {
+            continue;
+        };
+        let buffer_id = buffer_snapshot.remote_id();
+        let row = range.start.row;
+        if !toggled.insert((buffer_id, row)) {
+            continue;
+        }
+        if let Some(buffer) = multi_buffer.buffer(buffer_id) {
+            to_toggle.push((buffer, row));
+        }
+    }
+    project.update(cx, |project, cx| {
+        for (buffer, row) in to_toggle {
+            project.toggle_breakpoint(&buffer, row, cx);
+        }
+    });
+}
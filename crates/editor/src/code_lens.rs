@@ -0,0 +1,308 @@
+use std::{collections::BTreeMap, mem, time::Duration};
+
+use collections::{HashMap, HashSet};
+use gpui::{Model, Task, ViewContext, WeakView};
+use language::Buffer;
+use project::CodeLens;
+use settings::Settings;
+use text::{BufferId, Point, ToPoint};
+use ui::prelude::*;
+use util::ResultExt;
+
+use crate::{
+    BlockDisposition, BlockId, BlockProperties, BlockStyle, Editor, EditorMode, EditorSettings,
+    RenderBlock,
+};
+
+const CODE_LENS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
+
+#[derive(Default)]
+pub(super) struct CodeLensState {
+    block_ids: HashSet<BlockId>,
+    refresh_task: Option<Task<()>>,
+}
+
+/// Requests the code lenses of every buffer in the editor and shows them once they arrive,
+/// replacing the previously shown ones.
+pub(super) fn refresh_code_lenses(
+    editor: &mut Editor,
+    debounce: bool,
+    cx: &mut ViewContext<Editor>,
+) {
+    if editor.mode != EditorMode::Full || !EditorSettings::get_global(cx).code_lens {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let buffers = editor.buffer.read(cx).all_buffers();
+    editor.code_lens.refresh_task = Some(cx.spawn(|editor, mut cx| async move {
+        if debounce {
+            cx.background_executor()
+                .timer(CODE_LENS_DEBOUNCE_TIMEOUT)
+                .await;
+        }
+
+        let Some(tasks) = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let task = project.code_lens(&buffer, cx);
+                        (buffer, task)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .log_err()
+        else {
+            return;
+        };
+        let mut lenses = HashMap::default();
+        for (buffer, task) in tasks {
+            if let Some(buffer_lenses) = task.await.log_err() {
+                let buffer_id = buffer.update(&mut cx, |buffer, _| buffer.remote_id());
+                if let Ok(buffer_id) = buffer_id {
+                    lenses.insert(buffer_id, (buffer, buffer_lenses));
+                }
+            }
+        }
+
+        editor
+            .update(&mut cx, |editor, cx| show_code_lenses(editor, lenses, cx))
+            .ok();
+    }));
+}
+
+pub(super) fn code_lens_settings_changed(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if EditorSettings::get_global(cx).code_lens {
+        if editor.code_lens.block_ids.is_empty() {
+            refresh_code_lenses(editor, false, cx);
+        }
+    } else {
+        editor.code_lens.refresh_task = None;
+        let block_ids = mem::take(&mut editor.code_lens.block_ids);
+        if !block_ids.is_empty() {
+            editor.remove_blocks(block_ids, None, cx);
+        }
+    }
+}
+
+/// Shows the lenses of each excerpt in a block above the line they start on, grouping the
+/// lenses of the same line into a single block.
+fn show_code_lenses(
+    editor: &mut Editor,
+    lenses: HashMap<BufferId, (Model<Buffer>, Vec<CodeLens>)>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let editor_handle = cx.view().downgrade();
+    let mut blocks = Vec::new();
+    for (excerpt_id, buffer_snapshot, excerpt_range) in snapshot.excerpts() {
+        let Some((buffer, buffer_lenses)) = lenses.get(&buffer_snapshot.remote_id()) else {
+            continue;
+        };
+        let context_rows = excerpt_range.context.start.to_point(buffer_snapshot).row
+            ..=excerpt_range.context.end.to_point(buffer_snapshot).row;
+        let mut lenses_by_row = BTreeMap::<u32, Vec<CodeLens>>::new();
+        for lens in buffer_lenses {
+            // Lenses that could not be resolved have nothing to show.
+            if lens.lsp_lens.command.is_none() {
+                continue;
+            }
+            let row = lens.range.start.to_point(buffer_snapshot).row;
+            if context_rows.contains(&row) {
+                lenses_by_row.entry(row).or_default().push(lens.clone());
+            }
+        }
+
+        for (row, row_lenses) in lenses_by_row {
+            let indent = buffer_snapshot.indent_size_for_line(row);
+            let position = buffer_snapshot.anchor_after(Point::new(row, indent.len));
+            let Some(position) = snapshot.anchor_in_excerpt(excerpt_id, position) else {
+                continue;
+            };
+            blocks.push(BlockProperties {
+                position,
+                height: 1,
+                style: BlockStyle::Flex,
+                render: code_lens_block_renderer(row_lenses, buffer.clone(), editor_handle.clone()),
+                disposition: BlockDisposition::Above,
+            });
+        }
+    }
+
+    let old_block_ids = mem::take(&mut editor.code_lens.block_ids);
+    if !old_block_ids.is_empty() {
+        editor.remove_blocks(old_block_ids, None, cx);
+    }
+    editor.code_lens.block_ids = editor.insert_blocks(blocks, None, cx).into_iter().collect();
+}
+
+fn code_lens_block_renderer(
+    lenses: Vec<CodeLens>,
+    buffer: Model<Buffer>,
+    editor: WeakView<Editor>,
+) -> RenderBlock {
+    Box::new(move |cx| {
+        let mut buttons = h_flex().id(cx.block_id).pl(cx.anchor_x).gap_1();
+        for (ix, lens) in lenses.iter().enumerate() {
+            let Some(command) = lens.lsp_lens.command.as_ref() else {
+                continue;
+            };
+            if ix > 0 {
+                buttons = buttons.child(
+                    Label::new("|")
+                        .size(LabelSize::XSmall)
+                        .color(Color::Disabled),
+                );
+            }
+            buttons = buttons.child(
+                Button::new(("code-lens", ix), command.title.clone())
+                    .style(ButtonStyle::Transparent)
+                    .label_size(LabelSize::XSmall)
+                    .color(Color::Muted)
+                    .on_click({
+                        let lens = lens.clone();
+                        let buffer = buffer.clone();
+                        let editor = editor.clone();
+                        move |_, cx| {
+                            editor
+                                .update(cx, |editor, cx| {
+                                    run_code_lens(editor, &lens, buffer.clone(), cx)
+                                })
+                                .ok();
+                        }
+                    }),
+            );
+        }
+        buttons.into_any_element()
+    })
+}
+
+/// Runs the command of the lens on its language server, opening the edits it produced, if any.
+fn run_code_lens(
+    editor: &mut Editor,
+    lens: &CodeLens,
+    buffer: Model<Buffer>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let Some(action) = lens.to_code_action() else {
+        return;
+    };
+    let Some(workspace) = editor.workspace() else {
+        return;
+    };
+    let title = action.lsp_action.title.clone();
+    let apply_code_lens = workspace
+        .read(cx)
+        .project()
+        .clone()
+        .update(cx, |project, cx| {
+            project.apply_code_action(buffer, action, true, cx)
+        });
+    let workspace = workspace.downgrade();
+    cx.spawn(|editor, cx| async move {
+        let project_transaction = apply_code_lens.await?;
+        Editor::open_project_transaction(&editor, workspace, project_transaction, title, cx).await
+    })
+    .detach_and_log_err(cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_lsp_test_context::EditorLspTestContext};
+    use futures::StreamExt;
+    use indoc::indoc;
+
+    #[gpui::test]
+    async fn test_code_lenses_refresh_and_run(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(false),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            ˇfn a() {}
+
+            fn b() {}
+        "});
+
+        let lenses_available = Arc::new(AtomicBool::new(false));
+        let _lens_requests = cx.handle_request::<lsp::request::CodeLensRequest, _, _>({
+            let lenses_available = lenses_available.clone();
+            move |_, _, _| {
+                let lenses_available = lenses_available.load(Ordering::SeqCst);
+                async move {
+                    Ok(lenses_available.then(|| {
+                        vec![lsp::CodeLens {
+                            range: lsp::Range::new(
+                                lsp::Position::new(2, 3),
+                                lsp::Position::new(2, 4),
+                            ),
+                            command: Some(lsp::Command {
+                                title: "Run b".to_string(),
+                                command: "run".to_string(),
+                                arguments: None,
+                            }),
+                            data: None,
+                        }]
+                    }))
+                }
+            }
+        });
+        cx.executor().advance_clock(CODE_LENS_DEBOUNCE_TIMEOUT * 2);
+        cx.run_until_parked();
+        cx.update_editor(|editor, cx| {
+            assert!(editor.code_lens.block_ids.is_empty());
+            assert_eq!(editor.display_text(cx), "fn a() {}\n\nfn b() {}\n");
+        });
+
+        // The server tells the editor its lenses changed, and a lens block appears above `b`.
+        lenses_available.store(true, Ordering::SeqCst);
+        cx.lsp
+            .request::<lsp::request::CodeLensRefresh>(())
+            .await
+            .expect("code lens refresh request failed");
+        cx.executor().advance_clock(CODE_LENS_DEBOUNCE_TIMEOUT * 2);
+        cx.run_until_parked();
+        cx.update_editor(|editor, cx| {
+            assert_eq!(editor.code_lens.block_ids.len(), 1);
+            assert_eq!(editor.display_text(cx), "fn a() {}\n\n\nfn b() {}\n");
+        });
+
+        // Clicking the lens runs its command on the language server.
+        let mut execute_requests =
+            cx.handle_request::<lsp::request::ExecuteCommand, _, _>(|_, params, _| async move {
+                assert_eq!(params.command, "run");
+                Ok(None)
+            });
+        let buffer =
+            cx.update_editor(|editor, cx| editor.buffer().read(cx).as_singleton().unwrap());
+        let project = cx.update_editor(|editor, _| editor.project.clone().unwrap());
+        let lenses = cx
+            .update(|cx| project.update(cx, |project, cx| project.code_lens(&buffer, cx)))
+            .await
+            .unwrap();
+        assert_eq!(lenses.len(), 1);
+        cx.update_editor(|editor, cx| run_code_lens(editor, &lenses[0], buffer.clone(), cx));
+        execute_requests
+            .next()
+            .await
+            .expect("command was not executed");
+    }
+}
@@ -39,7 +39,7 @@ use fold_map::{FoldMap, FoldSnapshot};
 use gpui::{
     AnyElement, Font, HighlightStyle, LineLayout, Model, ModelContext, Pixels, UnderlineStyle,
 };
-pub(crate) use inlay_map::Inlay;
+pub(crate) use inlay_map::{Inlay, COLOR_SWATCH};
 use inlay_map::{InlayMap, InlaySnapshot};
 pub use inlay_map::{InlayOffset, InlayPoint};
 use language::{
@@ -62,7 +62,7 @@ use std::{
 };
 use sum_tree::{Bias, TreeMap};
 use tab_map::{TabMap, TabSnapshot};
-use text::LineIndent;
+use text::{BufferId, LineIndent};
 use ui::WindowContext;
 use wrap_map::{WrapMap, WrapSnapshot};
 
@@ -80,7 +80,16 @@ pub trait ToDisplayPoint {
     fn to_display_point(&self, map: &DisplaySnapshot) -> DisplayPoint;
 }
 
-type TextHighlights = TreeMap<Option<TypeId>, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>;
+/// Identifies a layer of text highlights. Semantic token layers sort first, so that the
+/// highlights set through [`DisplayMap::highlight_text`] are applied on top of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum HighlightKey {
+    SemanticToken(usize),
+    Type(TypeId),
+}
+
+type FoldingRanges = Arc<HashMap<BufferId, Arc<[Range<text::Anchor>]>>>;
+type TextHighlights = TreeMap<HighlightKey, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>;
 type InlayHighlights = TreeMap<TypeId, TreeMap<InlayId, (HighlightStyle, InlayHighlight)>>;
 
 /// Decides how text in a [`MultiBuffer`] should be displayed in a buffer, handling inlay hints,
@@ -107,6 +116,8 @@ pub struct DisplayMap {
     inlay_highlights: InlayHighlights,
     /// A container for explicitly foldable ranges, which supersede indentation based fold range suggestions.
     crease_map: CreaseMap,
+    /// Foldable line ranges reported by language servers, for buffers whose language has no `folds` query.
+    folding_ranges: FoldingRanges,
     fold_placeholder: FoldPlaceholder,
     pub clip_at_line_ends: bool,
 }
@@ -152,6 +163,7 @@ impl DisplayMap {
             wrap_map,
             block_map,
             crease_map,
+            folding_ranges: Default::default(),
             fold_placeholder,
             text_highlights: Default::default(),
             inlay_highlights: Default::default(),
@@ -179,6 +191,7 @@ impl DisplayMap {
             wrap_snapshot,
             block_snapshot,
             crease_snapshot: self.crease_map.snapshot(),
+            folding_ranges: self.folding_ranges.clone(),
             text_highlights: self.text_highlights.clone(),
             inlay_highlights: self.inlay_highlights.clone(),
             clip_at_line_ends: self.clip_at_line_ends,
@@ -256,6 +269,14 @@ impl DisplayMap {
         self.crease_map.insert(creases, &snapshot)
     }
 
+    /// Replaces the folding ranges reported by language servers, which must be sorted by their start.
+    pub fn set_folding_ranges(
+        &mut self,
+        folding_ranges: HashMap<BufferId, Arc<[Range<text::Anchor>]>>,
+    ) {
+        self.folding_ranges = Arc::new(folding_ranges);
+    }
+
     pub fn remove_creases(
         &mut self,
         crease_ids: impl IntoIterator<Item = CreaseId>,
@@ -355,7 +376,20 @@ impl DisplayMap {
         style: HighlightStyle,
     ) {
         self.text_highlights
-            .insert(Some(type_id), Arc::new((style, ranges)));
+            .insert(HighlightKey::Type(type_id), Arc::new((style, ranges)));
+    }
+
+    /// Replaces the semantic token highlights, given as one sorted list of ranges per style.
+    pub(crate) fn set_semantic_token_highlights(
+        &mut self,
+        highlights: Vec<(HighlightStyle, Vec<Range<Anchor>>)>,
+    ) {
+        self.text_highlights
+            .retain(|key, _| !matches!(key, HighlightKey::SemanticToken(_)));
+        for (ix, highlight) in highlights.into_iter().enumerate() {
+            self.text_highlights
+                .insert(HighlightKey::SemanticToken(ix), Arc::new(highlight));
+        }
     }
 
     pub(crate) fn highlight_inlays(
@@ -378,11 +412,14 @@ impl DisplayMap {
     }
 
     pub fn text_highlights(&self, type_id: TypeId) -> Option<(HighlightStyle, &[Range<Anchor>])> {
-        let highlights = self.text_highlights.get(&Some(type_id))?;
+        let highlights = self.text_highlights.get(&HighlightKey::Type(type_id))?;
         Some((highlights.0, &highlights.1))
     }
     pub fn clear_highlights(&mut self, type_id: TypeId) -> bool {
-        let mut cleared = self.text_highlights.remove(&Some(type_id)).is_some();
+        let mut cleared = self
+            .text_highlights
+            .remove(&HighlightKey::Type(type_id))
+            .is_some();
         cleared |= self.inlay_highlights.remove(&type_id).is_some();
         cleared
     }
@@ -473,6 +510,7 @@ pub struct DisplaySnapshot {
     pub buffer_snapshot: MultiBufferSnapshot,
     pub fold_snapshot: FoldSnapshot,
     pub crease_snapshot: CreaseSnapshot,
+    folding_ranges: FoldingRanges,
     inlay_snapshot: InlaySnapshot,
     tab_snapshot: TabSnapshot,
     wrap_snapshot: WrapSnapshot,
@@ -950,6 +988,62 @@ impl DisplaySnapshot {
         false
     }
 
+    /// Whether a fold can start on the given row, based on the syntax of the buffer when
+    /// it is known and on indentation otherwise.
+    pub fn starts_fold(&self, buffer_row: MultiBufferRow) -> bool {
+        match self.syntactic_fold_range(buffer_row) {
+            Some(range) => range.is_some(),
+            None => self.starts_indent(buffer_row),
+        }
+    }
+
+    /// Returns the range that can be folded on the given row according to the `folds` query of
+    /// the buffer's language or, failing that, to the folding ranges reported by its language
+    /// server. Returns `None` if neither is available for the buffer, in which case foldable
+    /// ranges are inferred from indentation.
+    fn syntactic_fold_range(&self, buffer_row: MultiBufferRow) -> Option<Option<Range<Point>>> {
+        let line_start = self
+            .buffer_snapshot
+            .point_to_offset(MultiBufferPoint::new(buffer_row.0, 0));
+        let excerpt = self
+            .buffer_snapshot
+            .excerpt_containing(line_start..line_start)?;
+        let buffer = excerpt.buffer();
+        let row = buffer
+            .offset_to_point(excerpt.map_offset_to_buffer(line_start))
+            .row;
+
+        let range = if buffer
+            .language()
+            .map_or(false, |language| language.has_folds_query())
+        {
+            buffer.fold_range_for_row(row)
+        } else if let Some(ranges) = self.folding_ranges.get(&buffer.remote_id()) {
+            let start_row =
+                |range: &Range<text::Anchor>| buffer.summary_for_anchor::<Point>(&range.start).row;
+            let start_ix = ranges.partition_point(|range| start_row(range) < row);
+            ranges[start_ix..]
+                .iter()
+                .take_while(|range| start_row(range) == row)
+                .map(|range| {
+                    buffer.summary_for_anchor::<Point>(&range.start)
+                        ..buffer.summary_for_anchor::<Point>(&range.end)
+                })
+                .max_by_key(|range| range.end)
+        } else {
+            return None;
+        };
+
+        Some(range.and_then(|range| {
+            let range = excerpt.map_range_from_buffer(
+                buffer.point_to_offset(range.start)..buffer.point_to_offset(range.end),
+            );
+            let range = self.buffer_snapshot.offset_to_point(range.start)
+                ..self.buffer_snapshot.offset_to_point(range.end);
+            (range.end.row > range.start.row).then_some(range)
+        }))
+    }
+
     pub fn foldable_range(
         &self,
         buffer_row: MultiBufferRow,
@@ -963,6 +1057,9 @@ impl DisplaySnapshot {
                 crease.range.to_point(&self.buffer_snapshot),
                 crease.placeholder.clone(),
             ))
+        } else if let Some(range) = self.syntactic_fold_range(buffer_row) {
+            let range = range.filter(|_| !self.is_line_folded(buffer_row))?;
+            Some((range, self.fold_placeholder.clone()))
         } else if self.starts_indent(MultiBufferRow(start.row))
             && !self.is_line_folded(MultiBufferRow(start.row))
         {
@@ -995,7 +1092,9 @@ impl DisplaySnapshot {
         &self,
     ) -> Option<Arc<(HighlightStyle, Vec<Range<Anchor>>)>> {
         let type_id = TypeId::of::<Tag>();
-        self.text_highlights.get(&Some(type_id)).cloned()
+        self.text_highlights
+            .get(&HighlightKey::Type(type_id))
+            .cloned()
     }
 
     #[allow(unused)]
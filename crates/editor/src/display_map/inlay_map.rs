@@ -1,22 +1,24 @@
 use crate::{HighlightStyles, InlayId};
 use collections::{BTreeMap, BTreeSet};
-use gpui::HighlightStyle;
+use gpui::{HighlightStyle, Hsla};
 use language::{Chunk, Edit, Point, TextSummary};
 use multi_buffer::{
     Anchor, MultiBufferChunks, MultiBufferRow, MultiBufferRows, MultiBufferSnapshot, ToOffset,
 };
 use std::{
-    any::TypeId,
     cmp,
     iter::Peekable,
     ops::{Add, AddAssign, Range, Sub, SubAssign},
     sync::Arc,
     vec,
 };
-use sum_tree::{Bias, Cursor, SumTree, TreeMap};
+use sum_tree::{Bias, Cursor, SumTree};
 use text::{Patch, Rope};
 
-use super::Highlights;
+use super::{HighlightKey, Highlights, TextHighlights};
+
+/// The text of the inlays showing the colors of color literals.
+pub(crate) const COLOR_SWATCH: &str = "■ ";
 
 /// Decides where the [`Inlay`]s should be displayed.
 ///
@@ -44,6 +46,8 @@ pub(crate) struct Inlay {
     pub(crate) id: InlayId,
     pub position: Anchor,
     pub text: text::Rope,
+    /// The color of the swatch shown by color inlays.
+    pub color: Option<Hsla>,
 }
 
 impl Inlay {
@@ -59,6 +63,7 @@ impl Inlay {
             id: InlayId::Hint(id),
             position,
             text: text.into(),
+            color: None,
         }
     }
 
@@ -67,6 +72,16 @@ impl Inlay {
             id: InlayId::Suggestion(id),
             position,
             text: text.into(),
+            color: None,
+        }
+    }
+
+    pub fn color(id: usize, position: Anchor, color: Hsla) -> Self {
+        Self {
+            id: InlayId::Color(id),
+            position,
+            text: COLOR_SWATCH.into(),
+            color: Some(color),
         }
     }
 }
@@ -191,7 +206,7 @@ pub struct InlayBufferRows<'a> {
 struct HighlightEndpoint {
     offset: InlayOffset,
     is_start: bool,
-    tag: Option<TypeId>,
+    tag: HighlightKey,
     style: HighlightStyle,
 }
 
@@ -219,7 +234,7 @@ pub struct InlayChunks<'a> {
     max_output_offset: InlayOffset,
     highlight_styles: HighlightStyles,
     highlight_endpoints: Peekable<vec::IntoIter<HighlightEndpoint>>,
-    active_highlights: BTreeMap<Option<TypeId>, HighlightStyle>,
+    active_highlights: BTreeMap<HighlightKey, HighlightStyle>,
     highlights: Highlights<'a>,
     snapshot: &'a InlaySnapshot,
 }
@@ -310,6 +325,10 @@ impl<'a> Iterator for InlayChunks<'a> {
                 let mut highlight_style = match inlay.id {
                     InlayId::Suggestion(_) => self.highlight_styles.suggestion,
                     InlayId::Hint(_) => self.highlight_styles.inlay_hint,
+                    InlayId::Color(_) => inlay.color.map(|color| HighlightStyle {
+                        color: Some(color),
+                        ..HighlightStyle::default()
+                    }),
                 };
                 let next_inlay_highlight_endpoint;
                 let offset_in_inlay = self.output_offset - self.transforms.start().0;
@@ -673,6 +692,7 @@ impl InlayMap {
                     id: inlay_id,
                     position: snapshot.buffer.anchor_at(position, bias),
                     text: text.into(),
+                    color: None,
                 });
             } else {
                 to_remove.push(
@@ -1065,7 +1085,7 @@ impl InlaySnapshot {
         &self,
         cursor: &mut Cursor<'_, Transform, (InlayOffset, usize)>,
         range: &Range<InlayOffset>,
-        text_highlights: &TreeMap<Option<TypeId>, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>,
+        text_highlights: &TextHighlights,
         highlight_endpoints: &mut Vec<HighlightEndpoint>,
     ) {
         while cursor.start().0 < range.end {
@@ -1178,7 +1198,8 @@ mod tests {
     use project::{InlayHint, InlayHintLabel, ResolveState};
     use rand::prelude::*;
     use settings::SettingsStore;
-    use std::{cmp::Reverse, env, sync::Arc};
+    use std::{any::TypeId, cmp::Reverse, env, sync::Arc};
+    use sum_tree::TreeMap;
     use text::Patch;
     use util::post_inc;
 
@@ -1279,6 +1300,7 @@ mod tests {
                 id: InlayId::Hint(post_inc(&mut next_inlay_id)),
                 position: buffer.read(cx).snapshot(cx).anchor_after(3),
                 text: "|123|".into(),
+                color: None,
             }],
         );
         assert_eq!(inlay_snapshot.text(), "abc|123|defghi");
@@ -1356,11 +1378,13 @@ mod tests {
                     id: InlayId::Hint(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_before(3),
                     text: "|123|".into(),
+                    color: None,
                 },
                 Inlay {
                     id: InlayId::Suggestion(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_after(3),
                     text: "|456|".into(),
+                    color: None,
                 },
             ],
         );
@@ -1567,16 +1591,19 @@ mod tests {
                     id: InlayId::Hint(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_before(0),
                     text: "|123|\n".into(),
+                    color: None,
                 },
                 Inlay {
                     id: InlayId::Hint(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_before(4),
                     text: "|456|".into(),
+                    color: None,
                 },
                 Inlay {
                     id: InlayId::Suggestion(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_before(7),
                     text: "\n|567|\n".into(),
+                    color: None,
                 },
             ],
         );
@@ -1677,7 +1704,7 @@ mod tests {
             text_highlight_ranges.sort_by_key(|range| (range.start, Reverse(range.end)));
             log::info!("highlighting text ranges {text_highlight_ranges:?}");
             text_highlights.insert(
-                Some(TypeId::of::<()>()),
+                HighlightKey::Type(TypeId::of::<()>()),
                 Arc::new((
                     HighlightStyle::default(),
                     text_highlight_ranges
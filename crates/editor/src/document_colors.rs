@@ -0,0 +1,487 @@
+use std::{mem, time::Duration};
+
+use collections::HashMap;
+use gpui::{rgb, Hsla, Model, Pixels, Point, Rgba, Task, ViewContext};
+use language::Buffer;
+use multi_buffer::{Anchor, ExcerptId};
+use project::{ColorPresentation, DocumentColor};
+use settings::Settings;
+use text::{Bias, BufferId};
+use ui::prelude::*;
+use util::{post_inc, ResultExt};
+
+use crate::{
+    display_map::{InlayOffset, COLOR_SWATCH},
+    mouse_context_menu::MouseContextMenu,
+    Editor, EditorMode, EditorSettings, EditorSnapshot, Inlay, InlayId, PointForPosition,
+};
+
+const DOCUMENT_COLORS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// The ways the color picker offers to edit the current color.
+const ADJUSTMENTS: [(&str, fn(Hsla) -> Hsla); 4] = [
+    ("Lighter", |color| Hsla {
+        l: (color.l + 0.1).min(1.),
+        ..color
+    }),
+    ("Darker", |color| Hsla {
+        l: (color.l - 0.1).max(0.),
+        ..color
+    }),
+    ("More Opaque", |color| Hsla {
+        a: (color.a + 0.1).min(1.),
+        ..color
+    }),
+    ("More Transparent", |color| Hsla {
+        a: (color.a - 0.1).max(0.),
+        ..color
+    }),
+];
+
+/// The colors offered by the color picker, which keep the opacity of the current color.
+const PALETTE: [(&str, u32); 12] = [
+    ("Black", 0x000000),
+    ("White", 0xffffff),
+    ("Gray", 0x808080),
+    ("Red", 0xff0000),
+    ("Orange", 0xffa500),
+    ("Yellow", 0xffff00),
+    ("Green", 0x008000),
+    ("Teal", 0x008080),
+    ("Blue", 0x0000ff),
+    ("Purple", 0x800080),
+    ("Pink", 0xffc0cb),
+    ("Brown", 0xa52a2a),
+];
+
+#[derive(Default)]
+pub(super) struct DocumentColorsState {
+    colors: HashMap<BufferId, (Model<Buffer>, Vec<DocumentColor>)>,
+    inlays: Vec<ColorInlay>,
+    refresh_task: Option<Task<()>>,
+}
+
+/// A swatch shown in front of a color literal of an excerpt.
+struct ColorInlay {
+    id: InlayId,
+    position: Anchor,
+    excerpt_id: ExcerptId,
+    buffer: Model<Buffer>,
+    color: DocumentColor,
+}
+
+/// Requests the color literals of every buffer in the editor and shows a swatch in front of
+/// each of them once they arrive.
+pub(super) fn refresh_document_colors(
+    editor: &mut Editor,
+    debounce: bool,
+    cx: &mut ViewContext<Editor>,
+) {
+    if editor.mode != EditorMode::Full || !EditorSettings::get_global(cx).document_colors {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let buffers = editor.buffer.read(cx).all_buffers();
+    editor.document_colors.refresh_task = Some(cx.spawn(|editor, mut cx| async move {
+        if debounce {
+            cx.background_executor()
+                .timer(DOCUMENT_COLORS_DEBOUNCE_TIMEOUT)
+                .await;
+        }
+
+        let Some(tasks) = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let task = project.document_colors(&buffer, cx);
+                        (buffer, task)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .log_err()
+        else {
+            return;
+        };
+        let mut colors = HashMap::default();
+        for (buffer, task) in tasks {
+            if let Some(buffer_colors) = task.await.log_err() {
+                let buffer_id = buffer.update(&mut cx, |buffer, _| buffer.remote_id());
+                if let Ok(buffer_id) = buffer_id {
+                    colors.insert(buffer_id, (buffer, buffer_colors));
+                }
+            }
+        }
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                editor.document_colors.colors = colors;
+                show_document_colors(editor, cx);
+            })
+            .ok();
+    }));
+}
+
+pub(super) fn document_colors_settings_changed(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if EditorSettings::get_global(cx).document_colors {
+        if editor.document_colors.colors.is_empty() {
+            refresh_document_colors(editor, false, cx);
+        }
+    } else if !editor.document_colors.colors.is_empty() {
+        editor.document_colors.refresh_task = None;
+        editor.document_colors.colors.clear();
+        show_document_colors(editor, cx);
+    }
+}
+
+/// Replaces the shown swatches with ones for the known colors of every excerpt.
+fn show_document_colors(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let mut color_inlays = Vec::new();
+    let mut to_insert = Vec::new();
+    for (excerpt_id, buffer_snapshot, excerpt_range) in snapshot.excerpts() {
+        let Some((buffer, colors)) = editor
+            .document_colors
+            .colors
+            .get(&buffer_snapshot.remote_id())
+        else {
+            continue;
+        };
+        let context = excerpt_range.context;
+        for color in colors {
+            let start = color.range.start;
+            if start.cmp(&context.start, buffer_snapshot).is_lt()
+                || start.cmp(&context.end, buffer_snapshot).is_gt()
+            {
+                continue;
+            }
+            let Some(position) = snapshot.anchor_in_excerpt(excerpt_id, start) else {
+                continue;
+            };
+            let inlay = Inlay::color(
+                post_inc(&mut editor.next_inlay_id),
+                position,
+                hsla_from_lsp(color.color),
+            );
+            color_inlays.push(ColorInlay {
+                id: inlay.id,
+                position,
+                excerpt_id,
+                buffer: buffer.clone(),
+                color: color.clone(),
+            });
+            to_insert.push(inlay);
+        }
+    }
+
+    let to_remove = mem::replace(&mut editor.document_colors.inlays, color_inlays)
+        .into_iter()
+        .map(|inlay| inlay.id)
+        .collect::<Vec<_>>();
+    if !to_remove.is_empty() || !to_insert.is_empty() {
+        editor.splice_inlays(to_remove, to_insert, cx);
+    }
+}
+
+/// Opens the color picker for the swatch at the given position, if there is one, returning
+/// whether it was opened.
+pub(crate) fn deploy_color_picker(
+    editor: &mut Editor,
+    snapshot: &EditorSnapshot,
+    point_for_position: &PointForPosition,
+    position: Point<Pixels>,
+    cx: &mut ViewContext<Editor>,
+) -> bool {
+    if point_for_position.column_overshoot_after_line_end != 0 {
+        return false;
+    }
+    let clicked_offset =
+        snapshot.display_point_to_inlay_offset(point_for_position.exact_unclipped, Bias::Left);
+    let Some(inlay) = editor.document_colors.inlays.iter().find(|inlay| {
+        let swatch_start = snapshot.anchor_to_inlay_offset(inlay.position);
+        let swatch_end = InlayOffset(swatch_start.0 + COLOR_SWATCH.len());
+        (swatch_start..swatch_end).contains(&clicked_offset)
+    }) else {
+        return false;
+    };
+    let Some(project) = editor.project.clone() else {
+        return false;
+    };
+
+    let excerpt_id = inlay.excerpt_id;
+    let buffer = inlay.buffer.clone();
+    let document_color = inlay.color.clone();
+    let presentations = project.update(cx, |project, cx| {
+        project.color_presentations(&buffer, &document_color, document_color.color, cx)
+    });
+    cx.spawn(|editor, mut cx| async move {
+        let presentations = presentations.await?;
+        editor.update(&mut cx, |editor, cx| {
+            // New colors are written the way the literal is written, if the server offers it.
+            let literal = buffer
+                .read(cx)
+                .text_for_range(document_color.range.clone())
+                .collect::<String>();
+            let format_ix = presentations
+                .iter()
+                .position(|presentation| {
+                    presentation
+                        .edits
+                        .first()
+                        .map_or(false, |(_, new_text)| *new_text == literal)
+                })
+                .unwrap_or(0);
+
+            let editor_handle = cx.view().downgrade();
+            let pick_color = {
+                let editor = editor_handle.clone();
+                let buffer = buffer.clone();
+                let document_color = document_color.clone();
+                move |new_color: lsp::Color, cx: &mut WindowContext| {
+                    editor
+                        .update(cx, |editor, cx| {
+                            change_color(
+                                editor,
+                                excerpt_id,
+                                buffer.clone(),
+                                &document_color,
+                                new_color,
+                                format_ix,
+                                cx,
+                            )
+                        })
+                        .ok();
+                }
+            };
+            let current_color = document_color.color;
+            let color_picker = ui::ContextMenu::build(cx, |mut menu, _| {
+                menu = menu.header(format!("Pick Color ({})", hex_label(current_color)));
+                for (name, adjust) in ADJUSTMENTS {
+                    let pick_color = pick_color.clone();
+                    menu = menu.entry(name, None, move |cx| {
+                        let new_color = adjust(hsla_from_lsp(current_color));
+                        pick_color(lsp_color_from_rgba(new_color.into()), cx)
+                    });
+                }
+
+                menu = menu.separator();
+                for (name, hex) in PALETTE {
+                    let color = palette_color(hex, current_color);
+                    menu = menu.custom_entry(
+                        move |cx| {
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    div()
+                                        .size_3()
+                                        .bg(hsla_from_lsp(color))
+                                        .border_1()
+                                        .border_color(cx.theme().colors().border),
+                                )
+                                .child(Label::new(name))
+                                .into_any_element()
+                        },
+                        {
+                            let pick_color = pick_color.clone();
+                            move |cx| pick_color(color, cx)
+                        },
+                    );
+                }
+
+                if presentations.len() > 1 {
+                    menu = menu.separator().header("Format");
+                    for (ix, presentation) in presentations.iter().enumerate() {
+                        let editor = editor_handle.clone();
+                        let presentation = presentation.clone();
+                        menu = menu.toggleable_entry(
+                            presentation.label.clone(),
+                            ix == format_ix,
+                            None,
+                            move |cx| {
+                                editor
+                                    .update(cx, |editor, cx| {
+                                        apply_color_presentation(
+                                            editor,
+                                            excerpt_id,
+                                            presentation.clone(),
+                                            cx,
+                                        )
+                                    })
+                                    .ok();
+                            },
+                        );
+                    }
+                }
+                menu
+            });
+            editor.mouse_context_menu = Some(MouseContextMenu::new(position, color_picker, cx));
+            cx.notify();
+        })
+    })
+    .detach_and_log_err(cx);
+    true
+}
+
+/// Rewrites the color literal as `new_color`, using the presentation at `format_ix` among the
+/// ones the language server offers for it.
+fn change_color(
+    editor: &mut Editor,
+    excerpt_id: ExcerptId,
+    buffer: Model<Buffer>,
+    document_color: &DocumentColor,
+    new_color: lsp::Color,
+    format_ix: usize,
+    cx: &mut ViewContext<Editor>,
+) {
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let presentations = project.update(cx, |project, cx| {
+        project.color_presentations(&buffer, document_color, new_color, cx)
+    });
+    cx.spawn(|editor, mut cx| async move {
+        let mut presentations = presentations.await?;
+        let presentation = if format_ix < presentations.len() {
+            presentations.swap_remove(format_ix)
+        } else if let Some(presentation) = presentations.pop() {
+            presentation
+        } else {
+            return Ok(());
+        };
+        editor.update(&mut cx, |editor, cx| {
+            apply_color_presentation(editor, excerpt_id, presentation, cx)
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+fn apply_color_presentation(
+    editor: &mut Editor,
+    excerpt_id: ExcerptId,
+    presentation: ColorPresentation,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let Some(edits) = presentation
+        .edits
+        .into_iter()
+        .map(|(range, new_text)| {
+            let start = snapshot.anchor_in_excerpt(excerpt_id, range.start)?;
+            let end = snapshot.anchor_in_excerpt(excerpt_id, range.end)?;
+            Some((start..end, new_text))
+        })
+        .collect::<Option<Vec<_>>>()
+    else {
+        log::warn!("color presentation edits outside of the excerpt");
+        return;
+    };
+    editor.transact(cx, |editor, cx| editor.edit(edits, cx));
+}
+
+fn hsla_from_lsp(color: lsp::Color) -> Hsla {
+    Rgba {
+        r: color.red,
+        g: color.green,
+        b: color.blue,
+        a: color.alpha,
+    }
+    .into()
+}
+
+fn lsp_color_from_rgba(color: Rgba) -> lsp::Color {
+    lsp::Color {
+        red: color.r,
+        green: color.g,
+        blue: color.b,
+        alpha: color.a,
+    }
+}
+
+/// The color of the palette with the given hex code, as opaque as the current color.
+fn palette_color(hex: u32, current_color: lsp::Color) -> lsp::Color {
+    lsp::Color {
+        alpha: current_color.alpha,
+        ..lsp_color_from_rgba(rgb(hex))
+    }
+}
+
+/// The color as `#rrggbb`, or as `#rrggbbaa` when it's translucent.
+fn hex_label(color: lsp::Color) -> String {
+    let channel = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+    let mut label = format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(color.red),
+        channel(color.green),
+        channel(color.blue)
+    );
+    if color.alpha < 1. {
+        label.push_str(&format!("{:02x}", channel(color.alpha)));
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_lsp_test_context::EditorLspTestContext};
+    use futures::StreamExt;
+
+    #[gpui::test]
+    async fn test_picked_colors_keep_alpha(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                color_provider: Some(lsp::ColorProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state("let color = ˇ\"#ff000080\";\n");
+        let color_range = cx.lsp_range("let color = «\"#ff000080\"»;\n");
+        let translucent_red = lsp::Color {
+            red: 1.,
+            green: 0.,
+            blue: 0.,
+            alpha: 128. / 255.,
+        };
+        cx.handle_request::<lsp::request::DocumentColor, _, _>(move |_, _, _| async move {
+            Ok(vec![lsp::ColorInformation {
+                range: color_range,
+                color: translucent_red,
+            }])
+        });
+        let mut presentations = cx.handle_request::<lsp::request::ColorPresentationRequest, _, _>(
+            move |_, params, _| async move {
+                Ok(vec![lsp::ColorPresentation {
+                    label: format!("\"{}\"", hex_label(params.color)),
+                    text_edit: None,
+                    additional_text_edits: None,
+                }])
+            },
+        );
+        cx.update_editor(|editor, cx| refresh_document_colors(editor, false, cx));
+        cx.run_until_parked();
+
+        cx.update_editor(|editor, cx| {
+            let inlay = &editor.document_colors.inlays[0];
+            assert_eq!(inlay.color.color, translucent_red);
+            assert_eq!(hex_label(inlay.color.color), "#ff000080");
+            let (excerpt_id, buffer, document_color) =
+                (inlay.excerpt_id, inlay.buffer.clone(), inlay.color.clone());
+            let blue = palette_color(0x0000ff, document_color.color);
+            change_color(editor, excerpt_id, buffer, &document_color, blue, 0, cx);
+        });
+        presentations.next().await;
+        cx.run_until_parked();
+        assert_eq!(
+            cx.update_editor(|editor, cx| editor.text(cx)),
+            "let color = \"#0000ff80\";\n"
+        );
+    }
+}
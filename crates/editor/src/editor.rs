@@ -15,10 +15,14 @@
 pub mod actions;
 mod blame_entry_tooltip;
 mod blink_manager;
+mod breakpoints;
+mod code_lens;
 mod debounced_delay;
 pub mod display_map;
+mod document_colors;
 mod editor_settings;
 mod element;
+mod folding_ranges;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -34,8 +38,12 @@ pub mod movement;
 mod persistence;
 mod rust_analyzer_ext;
 pub mod scroll;
+mod selection_ranges;
 mod selections_collection;
+mod semantic_tokens;
+mod signature_help;
 pub mod tasks;
+pub mod text_objects;
 
 #[cfg(test)]
 mod editor_tests;
@@ -49,11 +57,13 @@ use anyhow::{anyhow, Context as _, Result};
 use blink_manager::BlinkManager;
 use client::{Collaborator, ParticipantIndex};
 use clock::ReplicaId;
+use code_lens::CodeLensState;
 use collections::{BTreeMap, Bound, HashMap, HashSet, VecDeque};
 use convert_case::{Case, Casing};
 use debounced_delay::DebouncedDelay;
 use display_map::*;
 pub use display_map::{DisplayPoint, FoldPlaceholder};
+use document_colors::DocumentColorsState;
 pub use editor_settings::{CurrentLineHighlight, EditorSettings};
 use element::LineWithInvisibles;
 pub use element::{
@@ -87,10 +97,13 @@ use language::{
     language_settings::{self, all_language_settings, InlayHintSettings},
     markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, Capability, CharKind, CodeLabel,
     CursorShape, Diagnostic, Documentation, IndentKind, IndentSize, Language, OffsetRangeExt,
-    Point, Selection, SelectionGoal, TransactionId,
+    Point, Selection, SelectionGoal, TextObject, TransactionId,
 };
 use language::{BufferRow, Runnable, RunnableRange};
 use linked_editing_ranges::refresh_linked_ranges;
+use selection_ranges::SelectionRanges;
+use semantic_tokens::SemanticTokensState;
+use signature_help::SignatureHelpState;
 use task::{ResolvedTask, TaskTemplate, TaskVariables};
 
 use hover_links::{HoverLink, HoveredLinkState, InlayHighlight};
@@ -107,8 +120,9 @@ use ordered_float::OrderedFloat;
 use parking_lot::{Mutex, RwLock};
 use project::project_settings::{GitGutterSetting, ProjectSettings};
 use project::{
-    CodeAction, Completion, FormatTrigger, Item, Location, Project, ProjectPath,
-    ProjectTransaction, TaskSourceKind, WorktreeId,
+    CodeAction, Completion, DocumentLink, DocumentLinkTarget, FormatTrigger, Item, Location,
+    Project, ProjectPath, ProjectTransaction, TaskSourceKind, TestStatus, TypeHierarchyItem,
+    WorktreeId,
 };
 use rand::prelude::*;
 use rpc::{proto::*, ErrorExt};
@@ -126,7 +140,7 @@ use std::{
     mem,
     num::NonZeroU32,
     ops::{ControlFlow, Deref, DerefMut, Not as _, Range, RangeInclusive},
-    path::Path,
+    path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
     time::{Duration, Instant},
@@ -146,11 +160,12 @@ use util::{defer, maybe, post_inc, RangeExt, ResultExt, TryFutureExt};
 use workspace::item::{ItemHandle, PreviewTabsSettings};
 use workspace::notifications::{DetachAndPromptErr, NotificationId};
 use workspace::{
-    searchable::SearchEvent, ItemNavHistory, SplitDirection, ViewId, Workspace, WorkspaceId,
+    searchable::SearchEvent, ItemId, ItemNavHistory, SplitDirection, ViewId, Workspace, WorkspaceId,
 };
 use workspace::{OpenInTerminal, OpenTerminal, TabBarSettings, Toast};
 
 use crate::hover_links::find_url;
+use crate::persistence::DB;
 
 pub const FILE_HEADER_HEIGHT: u8 = 1;
 pub const MULTI_BUFFER_EXCERPT_HEADER_HEIGHT: u8 = 1;
@@ -160,6 +175,10 @@ const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
 const MAX_LINE_LEN: usize = 1024;
 const MIN_NAVIGATION_HISTORY_ROW_DELTA: i64 = 10;
 const MAX_SELECTION_HISTORY_LEN: usize = 1024;
+/// How many levels of subtypes or supertypes are walked from the type under the cursor.
+const MAX_TYPE_HIERARCHY_DEPTH: usize = 16;
+/// How many subtypes or supertypes are collected before the walk of the type hierarchy stops.
+const MAX_TYPE_HIERARCHY_ITEMS: usize = 500;
 pub(crate) const CURSORS_VISIBLE_FOR: Duration = Duration::from_millis(2000);
 #[doc(hidden)]
 pub const CODE_ACTIONS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
@@ -167,6 +186,7 @@ pub const CODE_ACTIONS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
 pub const DOCUMENT_HIGHLIGHTS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(75);
 
 pub(crate) const FORMAT_TIMEOUT: Duration = Duration::from_secs(2);
+const SERIALIZATION_THROTTLE_TIME: Duration = Duration::from_millis(100);
 
 pub fn render_parsed_markdown(
     element_id: impl Into<ElementId>,
@@ -235,6 +255,7 @@ pub fn render_parsed_markdown(
 pub(crate) enum InlayId {
     Suggestion(usize),
     Hint(usize),
+    Color(usize),
 }
 
 impl InlayId {
@@ -242,6 +263,7 @@ impl InlayId {
         match self {
             Self::Suggestion(id) => *id,
             Self::Hint(id) => *id,
+            Self::Color(id) => *id,
         }
     }
 }
@@ -465,6 +487,9 @@ pub struct Editor {
     autoclose_regions: Vec<AutocloseRegion>,
     snippet_stack: InvalidationStack<SnippetState>,
     select_larger_syntax_node_stack: Vec<Box<[Selection<usize>]>>,
+    selection_ranges_task: Option<Task<()>>,
+    /// How many times the selections are to be expanded once the pending selection ranges arrive.
+    pending_selection_expansions: usize,
     ime_transaction: Option<TransactionId>,
     active_diagnostics: Option<ActiveDiagnosticGroup>,
     soft_wrap_mode_override: Option<language_settings::SoftWrap>,
@@ -516,8 +541,18 @@ pub struct Editor {
     leader_peer_id: Option<PeerId>,
     remote_id: Option<ViewId>,
     hover_state: HoverState,
+    signature_help_state: SignatureHelpState,
+    semantic_tokens: SemanticTokensState,
+    code_lens: CodeLensState,
+    document_colors: DocumentColorsState,
+    folding_ranges_task: Option<Task<Option<()>>>,
+    serialize_selections: Task<()>,
+    serialize_folds: Task<()>,
     gutter_hovered: bool,
+    breakpoint_hint_row: Option<DisplayRow>,
     hovered_link_state: Option<HoveredLinkState>,
+    /// The document links of each buffer, with the version of the buffer they were fetched for.
+    document_links: HashMap<BufferId, (clock::Global, Vec<DocumentLink>)>,
     inline_completion_provider: Option<RegisteredInlineCompletionProvider>,
     active_inline_completion: Option<Inlay>,
     show_inline_completions: bool,
@@ -1707,6 +1742,14 @@ impl Editor {
                 project_subscriptions.push(cx.subscribe(project, |editor, _, event, cx| {
                     if let project::Event::RefreshInlayHints = event {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                    } else if let project::Event::RefreshSemanticTokens = event {
+                        semantic_tokens::refresh_semantic_tokens(editor, true, cx);
+                    } else if let project::Event::RefreshCodeLenses = event {
+                        code_lens::refresh_code_lenses(editor, true, cx);
+                    } else if let project::Event::BreakpointsChanged(_)
+                    | project::Event::TestStatusesChanged(_) = event
+                    {
+                        cx.notify();
                     } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                         if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                             let focus_handle = editor.focus_handle(cx);
@@ -1762,6 +1805,8 @@ impl Editor {
             autoclose_regions: Default::default(),
             snippet_stack: Default::default(),
             select_larger_syntax_node_stack: Vec::new(),
+            selection_ranges_task: None,
+            pending_selection_expansions: 0,
             ime_transaction: Default::default(),
             active_diagnostics: None,
             soft_wrap_mode_override,
@@ -1813,12 +1858,21 @@ impl Editor {
             leader_peer_id: None,
             remote_id: None,
             hover_state: Default::default(),
+            signature_help_state: Default::default(),
+            semantic_tokens: Default::default(),
+            code_lens: Default::default(),
+            document_colors: Default::default(),
+            folding_ranges_task: None,
+            serialize_selections: Task::ready(()),
+            serialize_folds: Task::ready(()),
             hovered_link_state: Default::default(),
+            document_links: Default::default(),
             inline_completion_provider: None,
             active_inline_completion: None,
             inlay_hint_cache: InlayHintCache::new(inlay_hint_settings),
             expanded_hunks: ExpandedHunks::default(),
             gutter_hovered: false,
+            breakpoint_hint_row: None,
             pixel_position_of_newest_cursor: None,
             last_bounds: None,
             expect_bounds_change: None,
@@ -1864,6 +1918,10 @@ impl Editor {
         };
         this.tasks_update_task = Some(this.refresh_runnables(cx));
         this._subscriptions.extend(project_subscriptions);
+        semantic_tokens::refresh_semantic_tokens(&mut this, false, cx);
+        code_lens::refresh_code_lenses(&mut this, false, cx);
+        document_colors::refresh_document_colors(&mut this, false, cx);
+        folding_ranges::refresh_folding_ranges(&mut this, false, cx);
 
         this.end_selection(cx);
         this.scroll_manager.show_scrollbar(cx);
@@ -1913,6 +1971,9 @@ impl Editor {
                 None => {}
             }
         }
+        if self.signature_help_state.has_multiple_signatures() {
+            key_context.add("showing_signature_help_overloads");
+        }
 
         for layer in self.keymap_context_layers.values() {
             key_context.extend(layer);
@@ -2261,6 +2322,10 @@ impl Editor {
             cx,
         );
 
+        if local {
+            self.serialize_selections(cx);
+        }
+
         if local {
             let new_cursor_position = self.selections.newest_anchor().head();
             let mut context_menu = self.context_menu.write();
@@ -2317,6 +2382,7 @@ impl Editor {
             }
 
             hide_hover(self, cx);
+            signature_help::refresh_signature_help(self, cx);
 
             if old_cursor_position.to_display_point(&display_map).row()
                 != new_cursor_position.to_display_point(&display_map).row()
@@ -2790,6 +2856,10 @@ impl Editor {
             return true;
         }
 
+        if signature_help::hide_signature_help(self, cx) {
+            return true;
+        }
+
         if self.hide_context_menu(cx).is_some() {
             return true;
         }
@@ -3142,6 +3212,7 @@ impl Editor {
 
             let trigger_in_words = !had_active_inline_completion;
             this.trigger_completion_on_input(&text, trigger_in_words, cx);
+            signature_help::signature_help_on_input(this, &text, cx);
             linked_editing_ranges::refresh_linked_ranges(this, cx);
             this.refresh_inline_completion(true, cx);
         });
@@ -4956,13 +5027,21 @@ impl Editor {
         &self,
         _style: &EditorStyle,
         is_active: bool,
+        test_status: Option<TestStatus>,
         row: DisplayRow,
         cx: &mut ViewContext<Self>,
     ) -> IconButton {
-        IconButton::new(("run_indicator", row.0 as usize), ui::IconName::Play)
+        let (icon, color) = match test_status {
+            None => (ui::IconName::Play, Color::Muted),
+            Some(TestStatus::Running) => (ui::IconName::Play, Color::Accent),
+            Some(TestStatus::Passed) => (ui::IconName::Check, Color::Success),
+            Some(TestStatus::Failed) => (ui::IconName::XCircle, Color::Error),
+            Some(TestStatus::Skipped) => (ui::IconName::Play, Color::Disabled),
+        };
+        IconButton::new(("run_indicator", row.0 as usize), icon)
             .shape(ui::IconButtonShape::Square)
             .icon_size(IconSize::XSmall)
-            .icon_color(Color::Muted)
+            .icon_color(color)
             .selected(is_active)
             .on_click(cx.listener(move |editor, _e, cx| {
                 editor.focus(cx);
@@ -4975,6 +5054,25 @@ impl Editor {
             }))
     }
 
+    fn render_breakpoint_indicator(
+        &self,
+        is_hint: bool,
+        row: DisplayRow,
+        cx: &mut ViewContext<Self>,
+    ) -> IconButton {
+        let color = if is_hint { Color::Muted } else { Color::Error };
+        IconButton::new(
+            ("breakpoint_indicator", row.0 as usize),
+            ui::IconName::DebugBreakpoint,
+        )
+        .shape(ui::IconButtonShape::Square)
+        .icon_size(IconSize::XSmall)
+        .icon_color(color)
+        .on_click(cx.listener(move |editor, _e, cx| {
+            breakpoints::toggle_breakpoints(editor, [row], cx);
+        }))
+    }
+
     pub fn context_menu_visible(&self) -> bool {
         self.context_menu
             .read()
@@ -8152,6 +8250,21 @@ impl Editor {
         &mut self,
         _: &SelectLargerSyntaxNode,
         cx: &mut ViewContext<Self>,
+    ) {
+        if EditorSettings::get_global(cx).lsp_selection_ranges
+            && selection_ranges::select_larger_selection_ranges(self, cx)
+        {
+            return;
+        }
+        self.select_larger_node(&SelectionRanges::default(), cx);
+    }
+
+    /// Expands every selection to the smallest of its language server's selection ranges that
+    /// contains it, or to its closest syntax ancestor if there is none.
+    fn select_larger_node(
+        &mut self,
+        selection_ranges: &SelectionRanges,
+        cx: &mut ViewContext<Self>,
     ) {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let buffer = self.buffer.read(cx).snapshot(cx);
@@ -8163,17 +8276,29 @@ impl Editor {
             .iter()
             .map(|selection| {
                 let old_range = selection.start..selection.end;
-                let mut new_range = old_range.clone();
-                while let Some(containing_range) =
-                    buffer.range_for_syntax_ancestor(new_range.clone())
-                {
-                    new_range = containing_range;
-                    if !display_map.intersects_fold(new_range.start)
-                        && !display_map.intersects_fold(new_range.end)
+                let new_range = selection_ranges::larger_selection_range(
+                    selection_ranges,
+                    selection.id,
+                    &old_range,
+                    |range| {
+                        !display_map.intersects_fold(range.start)
+                            && !display_map.intersects_fold(range.end)
+                    },
+                );
+                let new_range = new_range.unwrap_or_else(|| {
+                    let mut new_range = old_range.clone();
+                    while let Some(containing_range) =
+                        buffer.range_for_syntax_ancestor(new_range.clone())
                     {
-                        break;
+                        new_range = containing_range;
+                        if !display_map.intersects_fold(new_range.start)
+                            && !display_map.intersects_fold(new_range.end)
+                        {
+                            break;
+                        }
                     }
-                }
+                    new_range
+                });
 
                 selected_larger_node |= new_range != old_range;
                 Selection {
@@ -8200,6 +8325,8 @@ impl Editor {
         _: &SelectSmallerSyntaxNode,
         cx: &mut ViewContext<Self>,
     ) {
+        self.selection_ranges_task = None;
+        self.pending_selection_expansions = 0;
         let mut stack = mem::take(&mut self.select_larger_syntax_node_stack);
         if let Some(selections) = stack.pop() {
             self.change_selections(Some(Autoscroll::fit()), cx, |s| {
@@ -8209,6 +8336,83 @@ impl Editor {
         self.select_larger_syntax_node_stack = stack;
     }
 
+    /// Expands every selection to the text object of the given kind around it, such as the
+    /// function it is in. Like with the syntax nodes, the previous selections can be restored
+    /// with [`SelectSmallerSyntaxNode`].
+    pub fn select_text_object(&mut self, action: &SelectTextObject, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let old_selections = self.selections.all::<usize>(cx).into_boxed_slice();
+
+        let mut stack = mem::take(&mut self.select_larger_syntax_node_stack);
+        let mut selected_text_object = false;
+        let new_selections = old_selections
+            .iter()
+            .map(|selection| {
+                let old_range = selection.start..selection.end;
+                let new_range = text_objects::enclosing_text_object(
+                    &buffer,
+                    old_range.clone(),
+                    action.text_object,
+                )
+                .unwrap_or_else(|| old_range.clone());
+
+                selected_text_object |= new_range != old_range;
+                Selection {
+                    id: selection.id,
+                    start: new_range.start,
+                    end: new_range.end,
+                    goal: SelectionGoal::None,
+                    reversed: selection.reversed,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if selected_text_object {
+            stack.push(old_selections);
+            self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
+        }
+        self.select_larger_syntax_node_stack = stack;
+    }
+
+    pub fn move_to_next_text_object(
+        &mut self,
+        action: &MoveToNextTextObject,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.move_to_text_object(action.text_object, Direction::Next, cx);
+    }
+
+    pub fn move_to_previous_text_object(
+        &mut self,
+        action: &MoveToPreviousTextObject,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.move_to_text_object(action.text_object, Direction::Prev, cx);
+    }
+
+    fn move_to_text_object(
+        &mut self,
+        text_object: TextObject,
+        direction: Direction,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+            s.move_offsets_with(|snapshot, selection| {
+                if let Some(offset) = text_objects::text_object_boundary(
+                    snapshot,
+                    selection.head(),
+                    text_object,
+                    direction,
+                    false,
+                ) {
+                    selection.collapse_to(offset, SelectionGoal::None);
+                }
+            })
+        });
+    }
+
     fn refresh_runnables(&mut self, cx: &mut ViewContext<Self>) -> Task<()> {
         let project = self.project.clone();
         cx.spawn(|this, mut cx| async move {
@@ -8816,6 +9020,9 @@ impl Editor {
                     cx.open_url(&url);
                     Task::ready(Ok(None))
                 }
+                HoverLink::DocumentLink(buffer, link) => {
+                    self.compute_document_link_target(buffer, link, cx)
+                }
             };
             cx.spawn(|editor, mut cx| async move {
                 let target = target_task.await.context("target resolution task")?;
@@ -8909,6 +9116,7 @@ impl Editor {
                                 }),
                                 HoverLink::InlayHint(_, _) => None,
                                 HoverLink::Url(_) => None,
+                                HoverLink::DocumentLink(_, _) => None,
                             })
                             .unwrap_or(tab_kind.to_string());
                         let location_tasks = definitions
@@ -8919,6 +9127,9 @@ impl Editor {
                                     editor.compute_target_location(lsp_location, server_id, cx)
                                 }
                                 HoverLink::Url(_) => Task::ready(Ok(None)),
+                                HoverLink::DocumentLink(buffer, link) => {
+                                    editor.compute_document_link_target(buffer, link, cx)
+                                }
                             })
                             .collect::<Vec<_>>();
                         (title, location_tasks, editor.workspace().clone())
@@ -9001,6 +9212,32 @@ impl Editor {
         })
     }
 
+    /// Resolves the target of a document link, opening it right away when it is a URL.
+    fn compute_document_link_target(
+        &self,
+        buffer: Model<Buffer>,
+        link: DocumentLink,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<anyhow::Result<Option<Location>>> {
+        let Some(project) = self.project.clone() else {
+            return Task::ready(Ok(None));
+        };
+
+        let target_task = project.update(cx, |project, cx| {
+            project.open_document_link(&buffer, &link, cx)
+        });
+        cx.spawn(|editor, mut cx| async move {
+            match target_task.await.context("document link target")? {
+                Some(DocumentLinkTarget::Location(location)) => Ok(Some(location)),
+                Some(DocumentLinkTarget::Url(url)) => {
+                    editor.update(&mut cx, |_, cx| cx.open_url(&url))?;
+                    Ok(None)
+                }
+                None => Ok(None),
+            }
+        })
+    }
+
     pub fn find_all_references(
         &mut self,
         _: &FindAllReferences,
@@ -9084,6 +9321,103 @@ impl Editor {
         }))
     }
 
+    pub fn find_supertypes(
+        &mut self,
+        _: &FindSupertypes,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<()>>> {
+        self.find_type_hierarchy(false, cx)
+    }
+
+    pub fn find_subtypes(
+        &mut self,
+        _: &FindSubtypes,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<()>>> {
+        self.find_type_hierarchy(true, cx)
+    }
+
+    /// Walks the type hierarchy of the symbol under the newest selection, transitively
+    /// collecting its subtypes (or supertypes), and opens them in a multibuffer. The walk
+    /// stops at `MAX_TYPE_HIERARCHY_DEPTH` levels or `MAX_TYPE_HIERARCHY_ITEMS` types.
+    fn find_type_hierarchy(
+        &mut self,
+        subtypes: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<()>>> {
+        let head = self.selections.newest::<usize>(cx).head();
+        let (buffer, head) = self.buffer.read(cx).text_anchor_for_position(head, cx)?;
+        let replica_id = self.replica_id(cx);
+        let workspace = self.workspace()?;
+        let project = workspace.read(cx).project().clone();
+        let roots = project.update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, head, cx)
+        });
+        Some(cx.spawn(|_, mut cx| async move {
+            let roots = roots.await?;
+            let Some(root_name) = roots.first().map(|item| item.name.clone()) else {
+                return anyhow::Ok(());
+            };
+
+            let item_key = |item: &TypeHierarchyItem| {
+                let start = item.lsp_item.selection_range.start;
+                (item.lsp_item.uri.to_string(), start.line, start.character)
+            };
+            let mut visited = roots.iter().map(item_key).collect::<HashSet<_>>();
+            let mut level = roots;
+            let mut locations = Vec::new();
+            // The types of each level of the hierarchy are queried concurrently.
+            for _ in 0..MAX_TYPE_HIERARCHY_DEPTH {
+                if level.is_empty() || locations.len() >= MAX_TYPE_HIERARCHY_ITEMS {
+                    break;
+                }
+                let requests = project.update(&mut cx, |project, cx| {
+                    level
+                        .iter()
+                        .map(|item| {
+                            if subtypes {
+                                project.subtypes(item, cx)
+                            } else {
+                                project.supertypes(item, cx)
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                })?;
+                let mut next_level = Vec::new();
+                // A type that fails to be queried is left out, rather than failing the whole walk.
+                for related_item in futures::future::join_all(requests)
+                    .await
+                    .into_iter()
+                    .filter_map(|related| related.log_err())
+                    .flatten()
+                {
+                    if locations.len() >= MAX_TYPE_HIERARCHY_ITEMS {
+                        break;
+                    }
+                    if visited.insert(item_key(&related_item)) {
+                        locations.push(related_item.location.clone());
+                        next_level.push(related_item);
+                    }
+                }
+                level = next_level;
+            }
+            if locations.is_empty() {
+                return Ok(());
+            }
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let title = if subtypes {
+                    format!("Subtypes of `{root_name}`")
+                } else {
+                    format!("Supertypes of `{root_name}`")
+                };
+                Self::open_locations_in_multibuffer(
+                    workspace, locations, replica_id, title, false, cx,
+                );
+            })
+        }))
+    }
+
     /// Opens a multibuffer with the given project locations in it
     pub fn open_locations_in_multibuffer(
         workspace: &mut Workspace,
@@ -9653,7 +9987,7 @@ impl Editor {
         self.end_transaction_at(Instant::now(), cx)
     }
 
-    fn start_transaction_at(&mut self, now: Instant, cx: &mut ViewContext<Self>) {
+    pub fn start_transaction_at(&mut self, now: Instant, cx: &mut ViewContext<Self>) {
         self.end_selection(cx);
         if let Some(tx_id) = self
             .buffer
@@ -9667,7 +10001,7 @@ impl Editor {
         }
     }
 
-    fn end_transaction_at(
+    pub fn end_transaction_at(
         &mut self,
         now: Instant,
         cx: &mut ViewContext<Self>,
@@ -9836,6 +10170,7 @@ impl Editor {
             }
 
             self.scrollbar_marker_state.dirty = true;
+            self.serialize_folds(cx);
         }
     }
 
@@ -9871,6 +10206,102 @@ impl Editor {
             cx.notify();
             self.scrollbar_marker_state.dirty = true;
             self.active_indent_guides_state.dirty = true;
+            self.serialize_folds(cx);
+        }
+    }
+
+    /// Returns the key under which the state of this editor is persisted in the database, which is
+    /// only available for editors of a single local file that were added to a serialized workspace.
+    fn serialization_key(&self, cx: &ViewContext<Self>) -> Option<(ItemId, WorkspaceId, PathBuf)> {
+        let workspace_id = self.workspace.as_ref()?.1?;
+        let buffer = self.buffer.read(cx).as_singleton()?;
+        let path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+        Some((cx.view().item_id().as_u64() as ItemId, workspace_id, path))
+    }
+
+    fn serialize_selections(&mut self, cx: &mut ViewContext<Self>) {
+        let Some((item_id, workspace_id, path)) = self.serialization_key(cx) else {
+            return;
+        };
+        let selections = self
+            .selections
+            .all::<usize>(cx)
+            .into_iter()
+            .map(|selection| (selection.tail(), selection.head()))
+            .collect();
+        self.serialize_selections = cx.background_executor().spawn({
+            let executor = cx.background_executor().clone();
+            async move {
+                executor.timer(SERIALIZATION_THROTTLE_TIME).await;
+                DB.save_selections(item_id, workspace_id, path, selections)
+                    .await
+                    .log_err();
+            }
+        });
+    }
+
+    fn serialize_folds(&mut self, cx: &mut ViewContext<Self>) {
+        let Some((item_id, workspace_id, path)) = self.serialization_key(cx) else {
+            return;
+        };
+        let snapshot = self.snapshot(cx);
+        let buffer = &snapshot.display_snapshot.buffer_snapshot;
+        let folds = snapshot
+            .folds_in_range(0..buffer.len())
+            .map(|fold| {
+                (
+                    fold.range.start.to_offset(buffer),
+                    fold.range.end.to_offset(buffer),
+                )
+            })
+            .collect();
+        self.serialize_folds = cx.background_executor().spawn({
+            let executor = cx.background_executor().clone();
+            async move {
+                executor.timer(SERIALIZATION_THROTTLE_TIME).await;
+                DB.save_folds(item_id, workspace_id, path, folds)
+                    .await
+                    .log_err();
+            }
+        });
+    }
+
+    /// Restores the folds and selections that were persisted for the given file, unless the
+    /// editor shows a different file by now.
+    pub fn read_folds_and_selections_from_db(
+        &mut self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        path: PathBuf,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let clip_range = |(start, end): (usize, usize)| {
+            snapshot.clip_offset(start, Bias::Left)..snapshot.clip_offset(end, Bias::Right)
+        };
+
+        if let Some(folds) = DB
+            .get_folds(item_id, workspace_id, path.clone())
+            .log_err()
+            .filter(|folds| !folds.is_empty())
+        {
+            let placeholder = self.snapshot(cx).display_snapshot.fold_placeholder.clone();
+            let folds = folds
+                .into_iter()
+                .map(clip_range)
+                .filter(|range| !range.is_empty())
+                .map(|range| (range, placeholder.clone()))
+                .collect::<Vec<_>>();
+            self.fold_ranges(folds, false, cx);
+        }
+
+        if let Some(selections) = DB
+            .get_selections(item_id, workspace_id, path)
+            .log_err()
+            .filter(|selections| !selections.is_empty())
+        {
+            let selections = selections.into_iter().map(clip_range).collect::<Vec<_>>();
+            self.change_selections(None, cx, |s| s.select_ranges(selections));
         }
     }
 
@@ -9881,6 +10312,23 @@ impl Editor {
         }
     }
 
+    /// Sets the gutter row on which a breakpoint can be added with a click, if any.
+    fn set_breakpoint_hint_row(&mut self, row: Option<DisplayRow>, cx: &mut ViewContext<Self>) {
+        if row != self.breakpoint_hint_row {
+            self.breakpoint_hint_row = row;
+            cx.notify();
+        }
+    }
+
+    pub fn toggle_breakpoint(&mut self, _: &ToggleBreakpoint, cx: &mut ViewContext<Self>) {
+        let (_, selections) = self.selections.all_display(cx);
+        let rows = selections
+            .into_iter()
+            .map(|selection| selection.head().row())
+            .collect::<Vec<_>>();
+        breakpoints::toggle_breakpoints(self, rows, cx);
+    }
+
     pub fn insert_blocks(
         &mut self,
         blocks: impl IntoIterator<Item = BlockProperties<Anchor>>,
@@ -10895,6 +11343,11 @@ impl Editor {
                     }
                 }
 
+                semantic_tokens::refresh_semantic_tokens(self, true, cx);
+                code_lens::refresh_code_lenses(self, true, cx);
+                document_colors::refresh_document_colors(self, true, cx);
+                folding_ranges::refresh_folding_ranges(self, true, cx);
+                self.serialize_folds(cx);
                 let Some(project) = &self.project else { return };
                 let telemetry = project.read(cx).client().telemetry().clone();
                 refresh_linked_ranges(self, cx);
@@ -10912,9 +11365,14 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                semantic_tokens::refresh_semantic_tokens(self, true, cx);
+                code_lens::refresh_code_lenses(self, true, cx);
+                document_colors::refresh_document_colors(self, true, cx);
+                folding_ranges::refresh_folding_ranges(self, true, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
+                semantic_tokens::highlight_semantic_tokens(self, cx);
                 cx.emit(EditorEvent::ExcerptsRemoved { ids: ids.clone() })
             }
             multi_buffer::Event::ExcerptsEdited { ids } => {
@@ -10930,6 +11388,10 @@ impl Editor {
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, cx);
+                semantic_tokens::refresh_semantic_tokens(self, false, cx);
+                code_lens::refresh_code_lenses(self, false, cx);
+                document_colors::refresh_document_colors(self, false, cx);
+                folding_ranges::refresh_folding_ranges(self, false, cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
@@ -10971,6 +11433,9 @@ impl Editor {
             )),
             cx,
         );
+        semantic_tokens::semantic_tokens_settings_changed(self, cx);
+        code_lens::code_lens_settings_changed(self, cx);
+        document_colors::document_colors_settings_changed(self, cx);
         let editor_settings = EditorSettings::get_global(cx);
         self.scroll_manager.vertical_scroll_margin = editor_settings.vertical_scroll_margin;
         self.show_breadcrumbs = editor_settings.toolbar.breadcrumbs;
@@ -11380,6 +11845,7 @@ impl Editor {
         }
         self.hide_context_menu(cx);
         hide_hover(self, cx);
+        signature_help::hide_signature_help(self, cx);
         cx.emit(EditorEvent::Blurred);
         cx.notify();
     }
@@ -11796,7 +12262,7 @@ impl EditorSnapshot {
                 cx,
             ))
         } else if folded
-            || (self.starts_indent(buffer_row) && (row_contains_cursor || self.gutter_hovered))
+            || (self.starts_fold(buffer_row) && (row_contains_cursor || self.gutter_hovered))
         {
             Some(
                 Disclosure::new(("indent-fold-indicator", buffer_row.0), !folded)
@@ -12,6 +12,11 @@ pub struct EditorSettings {
     pub show_completion_documentation: bool,
     pub completion_documentation_secondary_query_debounce: u64,
     pub use_on_type_format: bool,
+    pub auto_signature_help: bool,
+    pub semantic_tokens: bool,
+    pub code_lens: bool,
+    pub document_colors: bool,
+    pub lsp_selection_ranges: bool,
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
     pub gutter: Gutter,
@@ -149,6 +154,31 @@ pub struct EditorSettingsContent {
     ///
     /// Default: true
     pub use_on_type_format: Option<bool>,
+    /// Whether to show the signature help popover automatically when typing
+    /// one of the language server's trigger characters, such as `(` or `,`.
+    ///
+    /// Default: true
+    pub auto_signature_help: Option<bool>,
+    /// Whether to highlight symbols with the semantic tokens reported by
+    /// language servers, on top of the syntax highlighting.
+    ///
+    /// Default: true
+    pub semantic_tokens: Option<bool>,
+    /// Whether to show the code lenses reported by language servers, such as
+    /// reference counts or run commands, above the symbols they apply to.
+    ///
+    /// Default: true
+    pub code_lens: Option<bool>,
+    /// Whether to show a swatch in front of the color literals reported by
+    /// language servers. Clicking a swatch opens a color picker.
+    ///
+    /// Default: true
+    pub document_colors: Option<bool>,
+    /// Whether to expand and shrink selections along the selection ranges
+    /// reported by language servers, rather than along syntax nodes only.
+    ///
+    /// Default: false
+    pub lsp_selection_ranges: Option<bool>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
     /// Scrollbar related settings
@@ -4622,6 +4622,216 @@ async fn test_select_larger_smaller_syntax_node(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_select_and_move_to_text_objects(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorLspTestContext::new_rust(Default::default(), cx).await;
+
+    cx.set_state(indoc! {"
+        fn outer(a: u8, bˇ: u16) {
+            let f = |x| { x + 1 };
+        }
+    "});
+    cx.update_editor(|editor, cx| {
+        editor.select_text_object(
+            &SelectTextObject {
+                text_object: TextObject::AroundParameter,
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state(indoc! {"
+        fn outer(a: u8«, b: u16ˇ») {
+            let f = |x| { x + 1 };
+        }
+    "});
+
+    // Selecting the same kind of text object again grows the selection.
+    cx.set_state(indoc! {"
+        fn outer(a: u8, b: u16) {
+            let f = |x| { xˇ + 1 };
+        }
+    "});
+    cx.update_editor(|editor, cx| {
+        editor.select_text_object(
+            &SelectTextObject {
+                text_object: TextObject::InsideFunction,
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state(indoc! {"
+        fn outer(a: u8, b: u16) {
+            let f = |x| {« x + 1 ˇ»};
+        }
+    "});
+    cx.update_editor(|editor, cx| {
+        editor.select_text_object(
+            &SelectTextObject {
+                text_object: TextObject::AroundFunction,
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state(indoc! {"
+        fn outer(a: u8, b: u16) {
+            let f = «|x| { x + 1 }ˇ»;
+        }
+    "});
+    cx.update_editor(|editor, cx| {
+        editor.select_text_object(
+            &SelectTextObject {
+                text_object: TextObject::AroundFunction,
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state(indoc! {"
+        «fn outer(a: u8, b: u16) {
+            let f = |x| { x + 1 };
+        }ˇ»
+    "});
+    cx.update_editor(|editor, cx| editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx));
+    cx.assert_editor_state(indoc! {"
+        fn outer(a: u8, b: u16) {
+            let f = «|x| { x + 1 }ˇ»;
+        }
+    "});
+
+    cx.set_state(indoc! {"
+        ˇfn outer(a: u8, b: u16) {
+            let f = |x| { x + 1 };
+        }
+    "});
+    cx.update_editor(|editor, cx| {
+        editor.move_to_next_text_object(
+            &MoveToNextTextObject {
+                text_object: TextObject::AroundFunction,
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state(indoc! {"
+        fn outer(a: u8, b: u16) {
+            let f = ˇ|x| { x + 1 };
+        }
+    "});
+    cx.update_editor(|editor, cx| {
+        editor.move_to_previous_text_object(
+            &MoveToPreviousTextObject {
+                text_object: TextObject::InsideParameter,
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state(indoc! {"
+        fn outer(a: u8, ˇb: u16) {
+            let f = |x| { x + 1 };
+        }
+    "});
+}
+
+#[gpui::test]
+async fn test_select_larger_smaller_lsp_selection_range(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    _ = cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                settings.lsp_selection_ranges = Some(true);
+            });
+        })
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            selection_range_provider: Some(lsp::SelectionRangeProviderCapability::Simple(true)),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    // The server knows about the words within string literals, which have no syntax nodes.
+    cx.lsp
+        .handle_request::<lsp::request::SelectionRangeRequest, _, _>(|params, _| async move {
+            Ok(Some(
+                params
+                    .positions
+                    .iter()
+                    .map(|position| {
+                        let line = position.line;
+                        let (word_start, string_end) = if line == 0 { (15, 20) } else { (14, 19) };
+                        [
+                            (word_start, string_end),
+                            (9, string_end),
+                            (8, string_end + 1),
+                            (0, string_end + 2),
+                        ]
+                        .into_iter()
+                        .rev()
+                        .fold(None, |parent, (start, end)| {
+                            Some(lsp::SelectionRange {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(line, start),
+                                    lsp::Position::new(line, end),
+                                ),
+                                parent: parent.map(Box::new),
+                            })
+                        })
+                        .unwrap()
+                    })
+                    .collect(),
+            ))
+        });
+
+    cx.set_state(indoc! {r#"
+        let a = "hello woˇrld";
+        let b = "good niˇght";
+    "#});
+
+    // Each cursor expands along the ranges reported for it.
+    cx.update_editor(|editor, cx| editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx));
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {r#"
+        let a = "hello «worldˇ»";
+        let b = "good «nightˇ»";
+    "#});
+
+    cx.update_editor(|editor, cx| editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx));
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {r#"
+        let a = "«hello worldˇ»";
+        let b = "«good nightˇ»";
+    "#});
+
+    cx.update_editor(|editor, cx| editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx));
+    cx.assert_editor_state(indoc! {r#"
+        let a = "hello «worldˇ»";
+        let b = "good «nightˇ»";
+    "#});
+
+    // Expanding again before the ranges arrive expands the selections as many times.
+    cx.set_state(indoc! {r#"
+        let a = "hello woˇrld";
+        let b = "good niˇght";
+    "#});
+    cx.update_editor(|editor, cx| {
+        editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx);
+        editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx);
+    });
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {r#"
+        let a = "«hello worldˇ»";
+        let b = "«good nightˇ»";
+    "#});
+
+    cx.update_editor(|editor, cx| editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx));
+    cx.assert_editor_state(indoc! {r#"
+        let a = "hello «worldˇ»";
+        let b = "good «nightˇ»";
+    "#});
+}
+
 #[gpui::test]
 async fn test_autoindent_selections(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -12110,6 +12320,108 @@ fn test_crease_insertion_and_rendering(cx: &mut TestAppContext) {
     assert!(!snapshot.is_line_folded(MultiBufferRow(1)));
 }
 
+#[gpui::test]
+async fn test_restoring_folds_and_selections(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/a",
+        json!({
+            "main.rs": sample_text(6, 3, 'a'),
+            "other.rs": sample_text(6, 3, 'a'),
+        }),
+    )
+    .await;
+    let project = Project::test(fs, ["/a".as_ref()], cx).await;
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+    let pane = workspace.update(cx, |workspace, _| workspace.active_pane().clone());
+
+    let workspace_id = DB
+        .write(|conn| {
+            conn.select_row::<WorkspaceId>(db::sqlez_macros::sql!(
+                INSERT INTO workspaces DEFAULT VALUES RETURNING workspace_id
+            ))
+            .unwrap()()
+            .unwrap()
+            .unwrap()
+        })
+        .await;
+    let main_path = PathBuf::from("/a/main.rs");
+    let restored_item_id: ItemId = 1;
+    DB.save_path(restored_item_id, workspace_id, main_path.clone())
+        .await
+        .unwrap();
+    DB.save_folds(
+        restored_item_id,
+        workspace_id,
+        main_path.clone(),
+        vec![(4, 11)],
+    )
+    .await
+    .unwrap();
+    DB.save_selections(
+        restored_item_id,
+        workspace_id,
+        main_path.clone(),
+        vec![(13, 14)],
+    )
+    .await
+    .unwrap();
+
+    // This editor was showing another file by the time it was serialized, so the folds and
+    // selections saved for its previous file don't apply anymore.
+    let reused_item_id: ItemId = 2;
+    DB.save_path(reused_item_id, workspace_id, PathBuf::from("/a/other.rs"))
+        .await
+        .unwrap();
+    DB.save_folds(
+        reused_item_id,
+        workspace_id,
+        main_path.clone(),
+        vec![(4, 11)],
+    )
+    .await
+    .unwrap();
+    DB.save_selections(reused_item_id, workspace_id, main_path, vec![(13, 14)])
+        .await
+        .unwrap();
+
+    let restored_editor = pane
+        .update(cx, |_, cx| {
+            Editor::deserialize(
+                project.clone(),
+                workspace.downgrade(),
+                workspace_id,
+                restored_item_id,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    restored_editor.update(cx, |editor, cx| {
+        assert_eq!(editor.display_text(cx), "aaa\n⋯\nddd\neee\nfff");
+        assert_eq!(editor.selections.ranges::<usize>(cx), vec![13..14]);
+    });
+
+    let reused_editor = pane
+        .update(cx, |_, cx| {
+            Editor::deserialize(
+                project.clone(),
+                workspace.downgrade(),
+                workspace_id,
+                reused_item_id,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    reused_editor.update(cx, |editor, cx| {
+        assert_eq!(editor.display_text(cx), sample_text(6, 3, 'a'));
+        assert_eq!(editor.selections.ranges::<usize>(cx), vec![0..0]);
+    });
+}
+
 fn empty_range(row: usize, column: usize) -> Range<DisplayPoint> {
     let point = DisplayPoint::new(DisplayRow(row as u32), column as u32);
     point..point
@@ -12211,6 +12523,78 @@ fn handle_resolve_completion_request(
     }
 }
 
+#[gpui::test]
+async fn test_find_subtypes(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorLspTestContext::new_rust(lsp::ServerCapabilities::default(), cx).await;
+    cx.set_state(indoc! {"
+        trait ˇShape {}
+        struct Circle;
+        struct Square;
+        struct Cube;
+    "});
+
+    fn type_item(url: &lsp::Uri, name: &str, line: u32) -> lsp::TypeHierarchyItem {
+        let name_start = if line == 0 { 6 } else { 7 };
+        let name_range = lsp::Range::new(
+            lsp::Position::new(line, name_start),
+            lsp::Position::new(line, name_start + name.len() as u32),
+        );
+        lsp::TypeHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::STRUCT,
+            tags: None,
+            detail: None,
+            uri: url.clone(),
+            range: name_range,
+            selection_range: name_range,
+            data: None,
+        }
+    }
+
+    cx.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>(|url, _, _| async move {
+        Ok(Some(vec![type_item(&url, "Shape", 0)]))
+    });
+    cx.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>(|url, params, _| async move {
+        match params.item.name.as_str() {
+            "Shape" => Ok(Some(vec![
+                type_item(&url, "Circle", 1),
+                type_item(&url, "Square", 2),
+            ])),
+            // The subtypes of other types are still collected when a request fails.
+            "Circle" => Err(anyhow::anyhow!("no subtypes of Circle")),
+            "Square" => Ok(Some(vec![type_item(&url, "Cube", 3)])),
+            _ => Ok(None),
+        }
+    });
+
+    cx.update_editor(|editor, cx| editor.find_subtypes(&FindSubtypes, cx))
+        .unwrap()
+        .await
+        .unwrap();
+    cx.run_until_parked();
+    let (title, highlighted_names) = cx.update_workspace(|workspace, cx| {
+        let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+        editor.update(cx, |editor, cx| {
+            let highlights = editor.all_text_background_highlights(cx);
+            let snapshot = editor.snapshot(cx);
+            let names = highlights
+                .into_iter()
+                .map(|(range, _)| {
+                    let range = range.start.to_point(&snapshot)..range.end.to_point(&snapshot);
+                    snapshot
+                        .buffer_snapshot
+                        .text_for_range(range)
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>();
+            (editor.buffer().read(cx).title(cx).to_string(), names)
+        })
+    });
+    assert_eq!(title, "Subtypes of `Shape`");
+    assert_eq!(highlighted_names, vec!["Circle", "Square", "Cube"]);
+}
+
 pub(crate) fn update_test_language_settings(
     cx: &mut TestAppContext,
     f: impl Fn(&mut AllLanguageSettingsContent),
@@ -1,8 +1,10 @@
 use crate::{
     blame_entry_tooltip::{blame_entry_relative_timestamp, BlameEntryTooltip},
+    breakpoints,
     display_map::{
         BlockContext, BlockStyle, DisplaySnapshot, HighlightedChunk, ToDisplayPoint, TransformBlock,
     },
+    document_colors,
     editor_settings::{
         CurrentLineHighlight, DoubleClickInMultibuffer, MultiCursorModifier, ShowScrollbar,
     },
@@ -17,14 +19,14 @@ use crate::{
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MouseContextMenu},
     scroll::scroll_amount::ScrollAmount,
-    CodeActionsMenu, CursorShape, DisplayPoint, DisplayRow, DocumentHighlightRead,
+    signature_help, CodeActionsMenu, CursorShape, DisplayPoint, DisplayRow, DocumentHighlightRead,
     DocumentHighlightWrite, Editor, EditorMode, EditorSettings, EditorSnapshot, EditorStyle,
     ExpandExcerpts, GutterDimensions, HalfPageDown, HalfPageUp, HoveredCursor, HunkToExpand,
     LineDown, LineUp, OpenExcerpts, PageDown, PageUp, Point, RowExt, RowRangeExt, SelectPhase,
     Selection, SoftWrap, ToPoint, CURSORS_VISIBLE_FOR, MAX_LINE_LEN,
 };
 use client::ParticipantIndex;
-use collections::{BTreeMap, HashMap};
+use collections::{BTreeMap, HashMap, HashSet};
 use git::{blame::BlameEntry, diff::DiffHunkStatus, Oid};
 use gpui::{
     anchored, deferred, div, fill, outline, point, px, quad, relative, size, svg,
@@ -273,6 +275,9 @@ impl EditorElement {
         register_action(view, cx, Editor::toggle_comments);
         register_action(view, cx, Editor::select_larger_syntax_node);
         register_action(view, cx, Editor::select_smaller_syntax_node);
+        register_action(view, cx, Editor::select_text_object);
+        register_action(view, cx, Editor::move_to_next_text_object);
+        register_action(view, cx, Editor::move_to_previous_text_object);
         register_action(view, cx, Editor::move_to_enclosing_bracket);
         register_action(view, cx, Editor::undo_selection);
         register_action(view, cx, Editor::redo_selection);
@@ -315,6 +320,7 @@ impl EditorElement {
         register_action(view, cx, Editor::fold_selected_ranges);
         register_action(view, cx, Editor::show_completions);
         register_action(view, cx, Editor::toggle_code_actions);
+        register_action(view, cx, Editor::toggle_breakpoint);
         register_action(view, cx, Editor::open_excerpts);
         register_action(view, cx, Editor::open_excerpts_in_split);
         register_action(view, cx, Editor::toggle_soft_wrap);
@@ -323,6 +329,9 @@ impl EditorElement {
         register_action(view, cx, Editor::toggle_indent_guides);
         register_action(view, cx, Editor::toggle_inlay_hints);
         register_action(view, cx, hover_popover::hover);
+        register_action(view, cx, signature_help::show_signature_help);
+        register_action(view, cx, signature_help::signature_help_next);
+        register_action(view, cx, signature_help::signature_help_previous);
         register_action(view, cx, Editor::reveal_in_finder);
         register_action(view, cx, Editor::copy_path);
         register_action(view, cx, Editor::copy_relative_path);
@@ -378,6 +387,20 @@ impl EditorElement {
                 cx.propagate();
             }
         });
+        register_action(view, cx, |editor, action, cx| {
+            if let Some(task) = editor.find_supertypes(action, cx) {
+                task.detach_and_log_err(cx);
+            } else {
+                cx.propagate();
+            }
+        });
+        register_action(view, cx, |editor, action, cx| {
+            if let Some(task) = editor.find_subtypes(action, cx) {
+                task.detach_and_log_err(cx);
+            } else {
+                cx.propagate();
+            }
+        });
         register_action(view, cx, Editor::next_inline_completion);
         register_action(view, cx, Editor::previous_inline_completion);
         register_action(view, cx, Editor::show_inline_completion);
@@ -478,6 +501,19 @@ impl EditorElement {
 
         let point_for_position =
             position_map.point_for_position(text_hitbox.bounds, event.position);
+        if click_count == 1
+            && !modifiers.modified()
+            && document_colors::deploy_color_picker(
+                editor,
+                &position_map.snapshot,
+                &point_for_position,
+                event.position,
+                cx,
+            )
+        {
+            cx.stop_propagation();
+            return;
+        }
         let position = point_for_position.previous_valid;
         if modifiers.shift && modifiers.alt {
             editor.select(
@@ -668,6 +704,13 @@ impl EditorElement {
         let modifiers = event.modifiers;
         let gutter_hovered = gutter_hitbox.is_hovered(cx);
         editor.set_gutter_hovered(gutter_hovered, cx);
+        let breakpoint_hint_row = (gutter_hovered && editor.project.is_some()).then(|| {
+            position_map
+                .point_for_position(text_hitbox.bounds, event.position)
+                .previous_valid
+                .row()
+        });
+        editor.set_breakpoint_hint_row(breakpoint_hint_row, cx);
 
         // Don't trigger hover popover if mouse is hovering over context menu
         if text_hitbox.is_hovered(cx) {
@@ -1524,6 +1567,7 @@ impl EditorElement {
         gutter_dimensions: &GutterDimensions,
         gutter_hitbox: &Hitbox,
         snapshot: &EditorSnapshot,
+        breakpoint_rows: &HashSet<DisplayRow>,
         cx: &mut WindowContext,
     ) -> Vec<AnyElement> {
         self.editor.update(cx, |editor, cx| {
@@ -1542,19 +1586,29 @@ impl EditorElement {
                 } else {
                     None
                 };
+            let project = editor.project.clone();
+            let multi_buffer = editor.buffer.clone();
             editor
                 .tasks
                 .iter()
-                .filter_map(|(_, tasks)| {
+                .filter_map(|((buffer_id, buffer_row), tasks)| {
                     let multibuffer_point = tasks.offset.0.to_point(&snapshot.buffer_snapshot);
                     let multibuffer_row = MultiBufferRow(multibuffer_point.row);
                     if snapshot.is_line_folded(multibuffer_row) {
                         return None;
                     }
                     let display_row = multibuffer_point.to_display_point(snapshot).row();
+                    if breakpoint_rows.contains(&display_row) {
+                        return None;
+                    }
+                    let test_status = project.as_ref().and_then(|project| {
+                        let buffer = multi_buffer.read(cx).buffer(*buffer_id)?;
+                        project.read(cx).test_status(&buffer, *buffer_row, cx)
+                    });
                     let button = editor.render_run_indicator(
                         &self.style,
                         Some(display_row) == active_task_indicator_row,
+                        test_status,
                         display_row,
                         cx,
                     );
@@ -1574,6 +1628,52 @@ impl EditorElement {
         })
     }
 
+    /// Lays out the indicators of the breakpoints on the visible rows, and the one hinting that
+    /// a breakpoint can be added on the hovered row of the gutter. Breakpoints take the place of
+    /// the run and code action indicators of their rows.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_breakpoint_indicators(
+        &self,
+        line_height: Pixels,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        snapshot: &EditorSnapshot,
+        visible_rows: Range<DisplayRow>,
+        breakpoint_rows: &HashSet<DisplayRow>,
+        code_actions_indicator_row: Option<DisplayRow>,
+        cx: &mut WindowContext,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            let hint_row = editor.breakpoint_hint_row.filter(|row| {
+                visible_rows.contains(row)
+                    && !breakpoint_rows.contains(row)
+                    && Some(*row) != code_actions_indicator_row
+                    && !editor.tasks.values().any(|tasks| {
+                        let multibuffer_point = tasks.offset.0.to_point(&snapshot.buffer_snapshot);
+                        multibuffer_point.to_display_point(snapshot).row() == *row
+                    })
+            });
+            breakpoint_rows
+                .iter()
+                .map(|row| (*row, false))
+                .chain(hint_row.map(|row| (row, true)))
+                .map(|(row, is_hint)| {
+                    let button = editor.render_breakpoint_indicator(is_hint, row, cx);
+                    prepaint_gutter_button(
+                        button,
+                        row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        cx,
+                    )
+                })
+                .collect_vec()
+        })
+    }
+
     fn layout_code_actions_indicator(
         &self,
         line_height: Pixels,
@@ -2462,6 +2562,66 @@ impl EditorElement {
         true
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_signature_help(
+        &self,
+        hitbox: &Hitbox,
+        text_hitbox: &Hitbox,
+        content_origin: gpui::Point<Pixels>,
+        start_row: DisplayRow,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        line_layouts: &[LineWithInvisibles],
+        newest_selection_head: DisplayPoint,
+        line_height: Pixels,
+        em_width: Pixels,
+        cx: &mut WindowContext,
+    ) {
+        let max_size = size(
+            (120. * em_width)
+                .min(hitbox.size.width / 2.)
+                .max(MIN_POPOVER_CHARACTER_WIDTH * em_width),
+            (8. * line_height)
+                .min(hitbox.size.height / 3.)
+                .max(MIN_POPOVER_LINE_HEIGHT * line_height),
+        );
+        let Some(mut popover) = self.editor.update(cx, |editor, cx| {
+            let workspace = editor.workspace.as_ref().map(|(w, _)| w.clone());
+            editor
+                .signature_help_state
+                .render(&self.style, max_size, workspace, cx)
+        }) else {
+            return;
+        };
+
+        let available_space = size(AvailableSpace::MinContent, AvailableSpace::MinContent);
+        let popover_size = popover.layout_as_root(available_space, cx);
+
+        let cursor_row_layout =
+            &line_layouts[newest_selection_head.row().minus(start_row) as usize];
+        let x = cursor_row_layout.x_for_index(newest_selection_head.column() as usize)
+            - scroll_pixel_position.x;
+        let y = newest_selection_head.row().as_f32() * line_height - scroll_pixel_position.y;
+        let cursor_point = content_origin + point(x, y);
+
+        let horizontal_offset =
+            (text_hitbox.upper_right().x - (cursor_point.x + popover_size.width)).min(Pixels::ZERO);
+        // Prefer the line above the cursor so that completions shown below it stay visible.
+        let popover_origin =
+            if cursor_point.y - text_hitbox.origin.y > popover_size.height + HOVER_POPOVER_GAP {
+                point(
+                    cursor_point.x + horizontal_offset,
+                    cursor_point.y - popover_size.height - HOVER_POPOVER_GAP,
+                )
+            } else {
+                point(
+                    cursor_point.x + horizontal_offset,
+                    cursor_point.y + line_height + HOVER_POPOVER_GAP,
+                )
+            };
+
+        cx.defer_draw(popover, popover_origin, 2);
+    }
+
     fn layout_mouse_context_menu(&self, cx: &mut WindowContext) -> Option<AnyElement> {
         let mouse_context_menu = self.editor.read(cx).mouse_context_menu.as_ref()?;
         let mut element = deferred(
@@ -2975,6 +3135,10 @@ impl EditorElement {
                 test_indicators.paint(cx);
             }
 
+            for breakpoint_indicator in layout.breakpoint_indicators.iter_mut() {
+                breakpoint_indicator.paint(cx);
+            }
+
             if let Some(indicator) = layout.code_actions_indicator.as_mut() {
                 indicator.paint(cx);
             }
@@ -4881,6 +5045,13 @@ impl Element for EditorElement {
 
                     let gutter_settings = EditorSettings::get_global(cx).gutter;
 
+                    let breakpoint_rows = breakpoints::breakpoint_display_rows(
+                        self.editor.read(cx),
+                        &snapshot,
+                        start_row..end_row,
+                        cx,
+                    );
+
                     let mut _context_menu_visible = false;
                     let mut code_actions_indicator = None;
                     if let Some(newest_selection_head) = newest_selection_head {
@@ -4898,6 +5069,19 @@ impl Element for EditorElement {
                                 cx,
                             );
 
+                            self.layout_signature_help(
+                                &hitbox,
+                                &text_hitbox,
+                                content_origin,
+                                start_row,
+                                scroll_pixel_position,
+                                &line_layouts,
+                                newest_selection_head,
+                                line_height,
+                                em_width,
+                                cx,
+                            );
+
                             let show_code_actions = snapshot
                                 .show_code_actions
                                 .unwrap_or_else(|| gutter_settings.code_actions);
@@ -4913,7 +5097,10 @@ impl Element for EditorElement {
                                     let has_test_indicator =
                                         self.editor.read(cx).tasks.contains_key(&(buffer_id, row));
 
-                                    if !has_test_indicator {
+                                    let has_breakpoint =
+                                        breakpoint_rows.contains(&newest_selection_head.row());
+
+                                    if !has_test_indicator && !has_breakpoint {
                                         code_actions_indicator = self
                                             .layout_code_actions_indicator(
                                                 line_height,
@@ -4935,6 +5122,22 @@ impl Element for EditorElement {
                         &gutter_dimensions,
                         &gutter_hitbox,
                         &snapshot,
+                        &breakpoint_rows,
+                        cx,
+                    );
+
+                    let breakpoint_indicators = self.layout_breakpoint_indicators(
+                        line_height,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &snapshot,
+                        start_row..end_row,
+                        &breakpoint_rows,
+                        code_actions_indicator
+                            .as_ref()
+                            .and(newest_selection_head)
+                            .map(|head| head.row()),
                         cx,
                     );
 
@@ -5036,6 +5239,7 @@ impl Element for EditorElement {
                         selections,
                         mouse_context_menu,
                         test_indicators,
+                        breakpoint_indicators,
                         code_actions_indicator,
                         gutter_fold_toggles,
                         crease_trailers,
@@ -5168,6 +5372,7 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     code_actions_indicator: Option<AnyElement>,
     test_indicators: Vec<AnyElement>,
+    breakpoint_indicators: Vec<AnyElement>,
     gutter_fold_toggles: Vec<Option<AnyElement>>,
     crease_trailers: Vec<Option<CreaseTrailerLayout>>,
     mouse_context_menu: Option<AnyElement>,
@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use collections::HashMap;
+use gpui::{Task, ViewContext};
+use util::ResultExt;
+
+use crate::{Editor, EditorMode};
+
+const FOLDING_RANGES_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Requests the folding ranges of the buffers whose language has no `folds` query from their
+/// language servers, which are then used to determine the foldable ranges of those buffers.
+pub(super) fn refresh_folding_ranges(
+    editor: &mut Editor,
+    debounce: bool,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.clone()?;
+
+    let buffers = editor
+        .buffer
+        .read(cx)
+        .all_buffers()
+        .into_iter()
+        .filter(|buffer| {
+            buffer
+                .read(cx)
+                .language()
+                .map_or(false, |language| !language.has_folds_query())
+        })
+        .collect::<Vec<_>>();
+    if buffers.is_empty() {
+        editor.folding_ranges_task = None;
+        return None;
+    }
+
+    editor.folding_ranges_task = Some(cx.spawn(|editor, mut cx| async move {
+        if debounce {
+            cx.background_executor()
+                .timer(FOLDING_RANGES_DEBOUNCE_TIMEOUT)
+                .await;
+        }
+
+        let tasks = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let buffer_id = buffer.read(cx).remote_id();
+                        (buffer_id, project.folding_ranges(&buffer, cx))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .log_err()?;
+        let mut folding_ranges = HashMap::default();
+        for (buffer_id, task) in tasks {
+            if let Some(ranges) = task.await.log_err() {
+                if !ranges.is_empty() {
+                    folding_ranges.insert(buffer_id, ranges.into());
+                }
+            }
+        }
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                editor.display_map.update(cx, |display_map, _| {
+                    display_map.set_folding_ranges(folding_ranges)
+                });
+                cx.notify();
+            })
+            .ok()
+    }));
+    None
+}
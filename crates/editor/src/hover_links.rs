@@ -4,13 +4,13 @@ use crate::{
     Anchor, Editor, EditorSnapshot, FindAllReferences, GoToDefinition, GoToTypeDefinition, InlayId,
     PointForPosition, SelectPhase,
 };
-use gpui::{px, AppContext, AsyncWindowContext, Model, Modifiers, Task, ViewContext};
-use language::{Bias, ToOffset};
+use gpui::{px, AppContext, AsyncWindowContext, Model, Modifiers, Task, ViewContext, WeakView};
+use language::{Bias, Buffer, ToOffset};
 use linkify::{LinkFinder, LinkKind};
 use lsp::LanguageServerId;
 use project::{
-    HoverBlock, HoverBlockKind, InlayHintLabelPartTooltip, InlayHintTooltip, LocationLink,
-    ResolveState,
+    DocumentLink, HoverBlock, HoverBlockKind, InlayHintLabelPartTooltip, InlayHintTooltip,
+    LocationLink, Project, ResolveState,
 };
 use std::ops::Range;
 use theme::ActiveTheme as _;
@@ -65,6 +65,7 @@ pub enum HoverLink {
     Url(String),
     Text(LocationLink),
     InlayHint(lsp::Location, LanguageServerId),
+    DocumentLink(Model<Buffer>, DocumentLink),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -521,21 +522,41 @@ pub fn show_link_definition(
                             (range, vec![HoverLink::Url(url)])
                         })
                         .ok()
-                    } else if let Some(project) = project {
-                        // query the LSP for definition info
-                        project
-                            .update(&mut cx, |project, cx| match preferred_kind {
-                                LinkDefinitionKind::Symbol => {
-                                    project.definition(&buffer, buffer_position, cx)
-                                }
+                    } else {
+                        // query the LSP for definition info, while looking for a document link,
+                        // which takes precedence
+                        let definitions = project
+                            .as_ref()
+                            .map(|project| {
+                                project.update(&mut cx, |project, cx| match preferred_kind {
+                                    LinkDefinitionKind::Symbol => {
+                                        project.definition(&buffer, buffer_position, cx)
+                                    }
 
-                                LinkDefinitionKind::Type => {
-                                    project.type_definition(&buffer, buffer_position, cx)
-                                }
-                            })?
-                            .await
-                            .ok()
-                            .map(|definition_result| {
+                                    LinkDefinitionKind::Type => {
+                                        project.type_definition(&buffer, buffer_position, cx)
+                                    }
+                                })
+                            })
+                            .transpose()?;
+                        let document_link = find_document_link(
+                            this.clone(),
+                            project.as_ref(),
+                            &buffer,
+                            buffer_position,
+                            cx.clone(),
+                        )
+                        .await;
+                        if let Some((link_range, link)) = document_link {
+                            let range = maybe!({
+                                let start =
+                                    snapshot.anchor_in_excerpt(excerpt_id, link_range.start)?;
+                                let end = snapshot.anchor_in_excerpt(excerpt_id, link_range.end)?;
+                                Some(RangeInEditor::Text(start..end))
+                            });
+                            Some((range, vec![HoverLink::DocumentLink(buffer.clone(), link)]))
+                        } else if let Some(definitions) = definitions {
+                            definitions.await.ok().map(|definition_result| {
                                 (
                                     definition_result.iter().find_map(|link| {
                                         link.origin.as_ref().and_then(|origin| {
@@ -551,8 +572,9 @@ pub fn show_link_definition(
                                     definition_result.into_iter().map(HoverLink::Text).collect(),
                                 )
                             })
-                    } else {
-                        None
+                        } else {
+                            None
+                        }
                     }
                 }
                 TriggerPoint::InlayHint(highlight, lsp_location, server_id) => Some((
@@ -624,6 +646,54 @@ pub fn show_link_definition(
     editor.hovered_link_state = Some(hovered_link_state);
 }
 
+/// Returns the document link reported by the language server at the given position, if any.
+/// The links of a buffer are only requested again once it has changed.
+pub(crate) async fn find_document_link(
+    editor: WeakView<Editor>,
+    project: Option<&Model<Project>>,
+    buffer: &Model<Buffer>,
+    position: text::Anchor,
+    mut cx: AsyncWindowContext,
+) -> Option<(Range<text::Anchor>, DocumentLink)> {
+    let project = project?;
+    let snapshot = buffer.update(&mut cx, |buffer, _| buffer.snapshot()).ok()?;
+    let buffer_id = snapshot.remote_id();
+    let cached_links = editor
+        .update(&mut cx, |editor, _| {
+            editor
+                .document_links
+                .get(&buffer_id)
+                .filter(|(version, _)| version == snapshot.version())
+                .map(|(_, links)| links.clone())
+        })
+        .ok()?;
+    let links = match cached_links {
+        Some(links) => links,
+        None => {
+            let links = project
+                .update(&mut cx, |project, cx| project.document_links(buffer, cx))
+                .ok()?
+                .await
+                .log_err()?;
+            editor
+                .update(&mut cx, |editor, _| {
+                    editor
+                        .document_links
+                        .insert(buffer_id, (snapshot.version().clone(), links.clone()))
+                })
+                .ok()?;
+            links
+        }
+    };
+    links
+        .into_iter()
+        .find(|link| {
+            link.range.start.cmp(&position, &snapshot).is_le()
+                && link.range.end.cmp(&position, &snapshot).is_gt()
+        })
+        .map(|link| (link.range.clone(), link))
+}
+
 pub(crate) fn find_url(
     buffer: &Model<language::Buffer>,
     position: text::Anchor,
@@ -1187,6 +1257,71 @@ mod tests {
             "});
     }
 
+    #[gpui::test]
+    async fn test_document_links(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(false),
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            // ˇSee README.md
+        "});
+        let link_point = cx.pixel_position(indoc! {"
+            // See READˇME.md
+        "});
+        let other_point = cx.pixel_position(indoc! {"
+            // Sˇee README.md
+        "});
+        let link_range = cx.lsp_range(indoc! {"
+            // See «README.md»
+        "});
+        let mut requests = cx.handle_request::<lsp::request::DocumentLinkRequest, _, _>(
+            move |_, _, _| async move {
+                Ok(Some(vec![lsp::DocumentLink {
+                    range: link_range,
+                    target: Some("file:///root/dir/README.md".parse().unwrap()),
+                    tooltip: None,
+                    data: None,
+                }]))
+            },
+        );
+
+        cx.simulate_mouse_move(link_point, None, Modifiers::secondary_key());
+        requests.next().await;
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            // See «README.md»
+        "});
+
+        // The links of the buffer are kept until it changes.
+        cx.simulate_mouse_move(other_point, None, Modifiers::secondary_key());
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            // See README.md
+        "});
+        cx.simulate_mouse_move(link_point, None, Modifiers::secondary_key());
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            // See «README.md»
+        "});
+        assert!(requests.try_next().is_err());
+
+        cx.update_editor(|editor, cx| editor.handle_input("x", cx));
+        cx.simulate_mouse_move(other_point, None, Modifiers::secondary_key());
+        requests.next().await;
+        cx.run_until_parked();
+    }
+
     #[gpui::test]
     async fn test_urls(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
@@ -937,23 +937,23 @@ impl Item for Editor {
     ) -> Task<Result<View<Self>>> {
         let project_item: Result<_> = project.update(cx, |project, cx| {
             // Look up the path with this key associated, create a self with that path
-            let path = DB
+            let abs_path = DB
                 .get_path(item_id, workspace_id)?
                 .context("No path stored for this editor")?;
 
             let (worktree, path) = project
-                .find_local_worktree(&path, cx)
-                .with_context(|| format!("No worktree for path: {path:?}"))?;
+                .find_local_worktree(&abs_path, cx)
+                .with_context(|| format!("No worktree for path: {abs_path:?}"))?;
             let project_path = ProjectPath {
                 worktree_id: worktree.read(cx).id(),
                 path: path.into(),
             };
 
-            Ok(project.open_path(project_path, cx))
+            Ok((project.open_path(project_path, cx), abs_path))
         });
 
         project_item
-            .map(|project_item| {
+            .map(|(project_item, abs_path)| {
                 cx.spawn(|pane, mut cx| async move {
                     let (_, project_item) = project_item.await?;
                     let buffer = project_item
@@ -963,6 +963,12 @@ impl Item for Editor {
                         cx.new_view(|cx| {
                             let mut editor = Editor::for_buffer(buffer, Some(project), cx);
 
+                            editor.read_folds_and_selections_from_db(
+                                item_id,
+                                workspace_id,
+                                abs_path,
+                                cx,
+                            );
                             editor.read_scroll_position_from_db(item_id, workspace_id, cx);
                             editor
                         })
@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use anyhow::{Context as _, Result};
+
 use db::sqlez_macros::sql;
 use db::{define_connection, query};
 
@@ -15,6 +17,22 @@ define_connection!(
     //   scroll_vertical_offset: f32,
     //   scroll_horizontal_offset: f32,
     // )
+    //
+    // editor_folds(
+    //   item_id: usize,
+    //   workspace_id: usize,
+    //   path: PathBuf,
+    //   start_offset: usize,
+    //   end_offset: usize,
+    // )
+    //
+    // editor_selections(
+    //   item_id: usize,
+    //   workspace_id: usize,
+    //   path: PathBuf,
+    //   start_offset: usize, // The tail of the selection, after its head when it is reversed
+    //   end_offset: usize,
+    // )
     pub static ref DB: EditorDb<WorkspaceDb> =
         &[sql! (
             CREATE TABLE editors(
@@ -31,6 +49,28 @@ define_connection!(
             ALTER TABLE editors ADD COLUMN scroll_top_row INTEGER NOT NULL DEFAULT 0;
             ALTER TABLE editors ADD COLUMN scroll_horizontal_offset REAL NOT NULL DEFAULT 0;
             ALTER TABLE editors ADD COLUMN scroll_vertical_offset REAL NOT NULL DEFAULT 0;
+        ),
+        sql! (
+            CREATE TABLE editor_folds(
+                item_id INTEGER NOT NULL,
+                workspace_id INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                start_offset INTEGER NOT NULL,
+                end_offset INTEGER NOT NULL,
+                FOREIGN KEY(item_id, workspace_id) REFERENCES editors(item_id, workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+            CREATE TABLE editor_selections(
+                item_id INTEGER NOT NULL,
+                workspace_id INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                start_offset INTEGER NOT NULL,
+                end_offset INTEGER NOT NULL,
+                FOREIGN KEY(item_id, workspace_id) REFERENCES editors(item_id, workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
         )];
 );
 
@@ -80,4 +120,182 @@ impl EditorDb {
             WHERE item_id = ?1 AND workspace_id = ?2
         }
     }
+
+    // Returns the folded offset ranges, provided the editor still shows the file at the given path
+    query! {
+        pub fn get_folds(item_id: ItemId, workspace_id: WorkspaceId, path: PathBuf) -> Result<Vec<(usize, usize)>> {
+            SELECT start_offset, end_offset
+            FROM editor_folds
+            WHERE item_id = ? AND workspace_id = ? AND path = ?
+            ORDER BY start_offset
+        }
+    }
+
+    pub async fn save_folds(
+        &self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        path: PathBuf,
+        folds: Vec<(usize, usize)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_editor_folds", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM editor_folds WHERE item_id = ? AND workspace_id = ?
+                ))?((item_id, workspace_id))
+                .context("clearing editor folds")?;
+                for (start_offset, end_offset) in folds {
+                    conn.exec_bound(sql!(
+                        INSERT INTO editor_folds
+                            (item_id, workspace_id, path, start_offset, end_offset)
+                        VALUES
+                            (?1, ?2, ?3, ?4, ?5)
+                    ))?((
+                        item_id,
+                        workspace_id,
+                        path.as_path(),
+                        start_offset,
+                        end_offset,
+                    ))
+                    .context("inserting editor fold")?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    // Returns the selected offset ranges in the order they were saved, provided the editor still
+    // shows the file at the given path
+    query! {
+        pub fn get_selections(item_id: ItemId, workspace_id: WorkspaceId, path: PathBuf) -> Result<Vec<(usize, usize)>> {
+            SELECT start_offset, end_offset
+            FROM editor_selections
+            WHERE item_id = ? AND workspace_id = ? AND path = ?
+            ORDER BY rowid
+        }
+    }
+
+    pub async fn save_selections(
+        &self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        path: PathBuf,
+        selections: Vec<(usize, usize)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_editor_selections", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM editor_selections WHERE item_id = ? AND workspace_id = ?
+                ))?((item_id, workspace_id))
+                .context("clearing editor selections")?;
+                for (start_offset, end_offset) in selections {
+                    conn.exec_bound(sql!(
+                        INSERT INTO editor_selections
+                            (item_id, workspace_id, path, start_offset, end_offset)
+                        VALUES
+                            (?1, ?2, ?3, ?4, ?5)
+                    ))?((
+                        item_id,
+                        workspace_id,
+                        path.as_path(),
+                        start_offset,
+                        end_offset,
+                    ))
+                    .context("inserting editor selection")?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::open_test_db;
+
+    #[gpui::test]
+    async fn test_saving_folds_and_selections() {
+        let db = EditorDb(open_test_db("test_saving_folds_and_selections").await);
+        let workspace_id = db
+            .write(|conn| {
+                conn.select_row::<WorkspaceId>(sql!(
+                    INSERT INTO workspaces DEFAULT VALUES RETURNING workspace_id
+                ))
+                .unwrap()()
+                .unwrap()
+                .unwrap()
+            })
+            .await;
+        let item_id = 1234;
+        let path = PathBuf::from("/root/main.rs");
+        db.save_path(item_id, workspace_id, path.clone())
+            .await
+            .unwrap();
+
+        db.save_folds(
+            item_id,
+            workspace_id,
+            path.clone(),
+            vec![(30, 40), (10, 20)],
+        )
+        .await
+        .unwrap();
+        db.save_selections(item_id, workspace_id, path.clone(), vec![(5, 5), (9, 2)])
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get_folds(item_id, workspace_id, path.clone()).unwrap(),
+            vec![(10, 20), (30, 40)]
+        );
+        assert_eq!(
+            db.get_selections(item_id, workspace_id, path.clone())
+                .unwrap(),
+            vec![(5, 5), (9, 2)]
+        );
+
+        // Selections come back in the order they were saved in, which need not be sorted.
+        db.save_selections(
+            item_id,
+            workspace_id,
+            path.clone(),
+            vec![(12, 12), (3, 7), (1, 1)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            db.get_selections(item_id, workspace_id, path.clone())
+                .unwrap(),
+            vec![(12, 12), (3, 7), (1, 1)]
+        );
+
+        // Saving again replaces the previous folds and selections of the editor.
+        db.save_folds(item_id, workspace_id, path.clone(), vec![(0, 3)])
+            .await
+            .unwrap();
+        db.save_selections(item_id, workspace_id, path.clone(), Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get_folds(item_id, workspace_id, path.clone()).unwrap(),
+            vec![(0, 3)]
+        );
+        assert!(db
+            .get_selections(item_id, workspace_id, path)
+            .unwrap()
+            .is_empty());
+
+        // Nothing is returned for a file the editor was not showing when they were saved.
+        let other_path = PathBuf::from("/root/other.rs");
+        assert!(db
+            .get_folds(item_id, workspace_id, other_path.clone())
+            .unwrap()
+            .is_empty());
+        assert!(db
+            .get_selections(item_id, workspace_id, other_path)
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -0,0 +1,128 @@
+use std::{mem, ops::Range};
+
+use collections::HashMap;
+use gpui::{Model, ViewContext};
+use language::Buffer;
+use multi_buffer::{ExcerptId, ToOffset};
+use text::BufferId;
+use util::ResultExt;
+
+use crate::Editor;
+
+/// The selection ranges a language server reported around each selection, from the innermost
+/// to the outermost, keyed by the selection's id and along with the range the selection had
+/// when they were requested.
+pub(super) type SelectionRanges = HashMap<usize, (Range<usize>, Vec<Range<usize>>)>;
+
+/// Requests the selection ranges around every selection from the language servers and expands
+/// each selection along its own ranges once they arrive. Returns whether anything was requested.
+/// Expanding again before they arrive expands the selections once more along the same ranges.
+pub(super) fn select_larger_selection_ranges(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> bool {
+    let Some(project) = editor.project.clone() else {
+        return false;
+    };
+    if editor.pending_selection_expansions > 0 {
+        editor.pending_selection_expansions += 1;
+        return true;
+    }
+
+    let multi_buffer = editor.buffer.read(cx);
+    let snapshot = multi_buffer.snapshot(cx);
+    let mut requests = HashMap::<BufferId, (Model<Buffer>, Vec<_>)>::default();
+    for selection in editor.selections.all::<usize>(cx) {
+        let anchor = snapshot.anchor_after(selection.start);
+        let Some(buffer) = anchor
+            .buffer_id
+            .and_then(|buffer_id| multi_buffer.buffer(buffer_id))
+        else {
+            continue;
+        };
+        requests
+            .entry(buffer.read(cx).remote_id())
+            .or_insert_with(|| (buffer, Vec::new()))
+            .1
+            .push((
+                selection.id,
+                selection.start..selection.end,
+                anchor.excerpt_id,
+                anchor.text_anchor,
+            ));
+    }
+    if requests.is_empty() {
+        return false;
+    }
+
+    let tasks = project.update(cx, |project, cx| {
+        requests
+            .into_values()
+            .map(|(buffer, selections)| {
+                let positions = selections
+                    .iter()
+                    .map(|(_, _, _, position)| *position)
+                    .collect();
+                (project.selection_ranges(&buffer, positions, cx), selections)
+            })
+            .collect::<Vec<_>>()
+    });
+    editor.pending_selection_expansions = 1;
+    editor.selection_ranges_task = Some(cx.spawn(|editor, mut cx| async move {
+        let mut responses = Vec::new();
+        for (task, selections) in tasks {
+            let ranges = task.await.log_err().unwrap_or_default();
+            responses.push((selections, ranges));
+        }
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let snapshot = editor.buffer.read(cx).snapshot(cx);
+                let mut selection_ranges = SelectionRanges::default();
+                for (selections, ranges) in responses {
+                    for ((selection_id, old_range, excerpt_id, _), ranges) in
+                        selections.into_iter().zip(ranges)
+                    {
+                        let ranges = ranges
+                            .into_iter()
+                            .map_while(|range| {
+                                let start = snapshot.anchor_in_excerpt(excerpt_id, range.start)?;
+                                let end = snapshot.anchor_in_excerpt(excerpt_id, range.end)?;
+                                Some(start.to_offset(&snapshot)..end.to_offset(&snapshot))
+                            })
+                            .collect();
+                        selection_ranges.insert(selection_id, (old_range, ranges));
+                    }
+                }
+                for _ in 0..mem::take(&mut editor.pending_selection_expansions) {
+                    editor.select_larger_node(&selection_ranges, cx);
+                }
+            })
+            .ok();
+    }));
+    true
+}
+
+/// Returns the smallest of the selection ranges reported for the selection that contains it,
+/// provided the selection still contains the range it had when they were requested, as it
+/// does after being expanded along them.
+pub(super) fn larger_selection_range(
+    selection_ranges: &SelectionRanges,
+    selection_id: usize,
+    old_range: &Range<usize>,
+    mut is_valid: impl FnMut(&Range<usize>) -> bool,
+) -> Option<Range<usize>> {
+    let (requested_range, ranges) = selection_ranges.get(&selection_id)?;
+    if requested_range.start < old_range.start || requested_range.end > old_range.end {
+        return None;
+    }
+    ranges
+        .iter()
+        .find(|range| {
+            range.start <= old_range.start
+                && range.end >= old_range.end
+                && *range != old_range
+                && is_valid(range)
+        })
+        .cloned()
+}
@@ -0,0 +1,177 @@
+use std::{ops::Range, time::Duration};
+
+use collections::HashMap;
+use gpui::{HighlightStyle, Task, ViewContext};
+use language::HighlightId;
+use multi_buffer::Anchor;
+use project::SemanticToken;
+use settings::Settings;
+use text::BufferId;
+use theme::{ActiveTheme, SyntaxTheme};
+use util::ResultExt;
+
+use crate::{Editor, EditorMode, EditorSettings};
+
+const SEMANTIC_TOKENS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(200);
+
+#[derive(Default)]
+pub(super) struct SemanticTokensState {
+    tokens: HashMap<BufferId, Vec<SemanticToken>>,
+    refresh_task: Option<Task<()>>,
+}
+
+/// Requests the semantic tokens of every buffer in the editor and highlights them once they arrive.
+pub(super) fn refresh_semantic_tokens(
+    editor: &mut Editor,
+    debounce: bool,
+    cx: &mut ViewContext<Editor>,
+) {
+    if editor.mode != EditorMode::Full || !EditorSettings::get_global(cx).semantic_tokens {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let buffers = editor.buffer.read(cx).all_buffers();
+    editor.semantic_tokens.refresh_task = Some(cx.spawn(|editor, mut cx| async move {
+        if debounce {
+            cx.background_executor()
+                .timer(SEMANTIC_TOKENS_DEBOUNCE_TIMEOUT)
+                .await;
+        }
+
+        let Some(tasks) = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let buffer_id = buffer.read(cx).remote_id();
+                        (buffer_id, project.semantic_tokens(&buffer, cx))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .log_err()
+        else {
+            return;
+        };
+        let mut tokens = HashMap::default();
+        for (buffer_id, task) in tasks {
+            if let Some(buffer_tokens) = task.await.log_err() {
+                tokens.insert(buffer_id, buffer_tokens);
+            }
+        }
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let buffer = editor.buffer.read(cx);
+                editor
+                    .semantic_tokens
+                    .tokens
+                    .retain(|buffer_id, _| buffer.buffer(*buffer_id).is_some());
+                editor.semantic_tokens.tokens.extend(tokens);
+                highlight_semantic_tokens(editor, cx);
+            })
+            .ok();
+    }));
+}
+
+/// Re-resolves the highlights of the known tokens after a settings or theme change.
+pub(super) fn semantic_tokens_settings_changed(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if EditorSettings::get_global(cx).semantic_tokens {
+        if editor.semantic_tokens.tokens.is_empty() {
+            refresh_semantic_tokens(editor, false, cx);
+        } else {
+            highlight_semantic_tokens(editor, cx);
+        }
+    } else if !editor.semantic_tokens.tokens.is_empty() {
+        editor.semantic_tokens = SemanticTokensState::default();
+        highlight_semantic_tokens(editor, cx);
+    }
+}
+
+/// Highlights the known tokens of every excerpt, grouping the tokens that resolve to the
+/// same syntax style into a single highlight layer.
+pub(super) fn highlight_semantic_tokens(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let highlights = if editor.semantic_tokens.tokens.is_empty() {
+        Vec::new()
+    } else {
+        semantic_token_highlights(editor, cx)
+    };
+    editor.display_map.update(cx, |display_map, _| {
+        display_map.set_semantic_token_highlights(highlights)
+    });
+    cx.notify();
+}
+
+fn semantic_token_highlights(
+    editor: &Editor,
+    cx: &ViewContext<Editor>,
+) -> Vec<(HighlightStyle, Vec<Range<Anchor>>)> {
+    let syntax_theme = cx.theme().syntax().clone();
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let mut ranges_by_highlight = HashMap::<u32, Vec<Range<Anchor>>>::default();
+    for (excerpt_id, buffer, excerpt_range) in snapshot.excerpts() {
+        let Some(tokens) = editor.semantic_tokens.tokens.get(&buffer.remote_id()) else {
+            continue;
+        };
+        let context = excerpt_range.context;
+        let start_ix =
+            tokens.partition_point(|token| token.range.end.cmp(&context.start, buffer).is_le());
+        for token in &tokens[start_ix..] {
+            if token.range.start.cmp(&context.end, buffer).is_ge() {
+                break;
+            }
+            let highlight_id = highlight_id_for_token(token, &syntax_theme);
+            if highlight_id.style(&syntax_theme).is_none() {
+                continue;
+            }
+            let Some(start) = snapshot.anchor_in_excerpt(excerpt_id, token.range.start) else {
+                continue;
+            };
+            let Some(end) = snapshot.anchor_in_excerpt(excerpt_id, token.range.end) else {
+                continue;
+            };
+            ranges_by_highlight
+                .entry(highlight_id.0)
+                .or_default()
+                .push(start..end);
+        }
+    }
+
+    ranges_by_highlight
+        .into_iter()
+        .filter_map(|(highlight_id, ranges)| {
+            Some((HighlightId(highlight_id).style(&syntax_theme)?, ranges))
+        })
+        .collect()
+}
+
+/// Resolves a token to a syntax style the same way tree-sitter captures are resolved, treating
+/// the token type and its modifiers as the components of a capture name (e.g. `variable.mutable`).
+fn highlight_id_for_token(token: &SemanticToken, syntax_theme: &SyntaxTheme) -> HighlightId {
+    let mut capture_name = capture_name_for_token_type(&token.token_type).to_string();
+    for modifier in &token.modifiers {
+        capture_name.push('.');
+        capture_name.push_str(modifier);
+    }
+    HighlightId::for_capture_name(&capture_name, syntax_theme)
+}
+
+/// Maps the token types of the LSP specification (and the common rust-analyzer extensions) onto
+/// the capture names used by the highlight queries, so that themes style both alike.
+fn capture_name_for_token_type(token_type: &str) -> &str {
+    match token_type {
+        "class" | "enum" | "struct" | "type" | "typeAlias" | "typeParameter" => "type",
+        "interface" => "type.interface",
+        "builtinType" => "type.builtin",
+        "enumMember" => "variant",
+        "parameter" => "variable.parameter",
+        "selfKeyword" => "variable.special",
+        "method" => "function.method",
+        "macro" => "function.special",
+        "decorator" => "attribute",
+        "regexp" => "string.regex",
+        token_type => token_type,
+    }
+}
@@ -0,0 +1,605 @@
+use crate::{
+    hover_popover::parse_blocks, Editor, EditorSettings, EditorStyle, ShowSignatureHelp,
+    SignatureHelpNext, SignatureHelpPrevious,
+};
+use gpui::{
+    AnyElement, FontWeight, HighlightStyle, MouseButton, Pixels, ScrollHandle, Size, StyledText,
+    Task, UnderlineStyle, ViewContext, WeakView,
+};
+use language::{char_kind, CharKind, ParsedMarkdown};
+use project::SignatureHelp;
+use settings::Settings;
+use ui::{prelude::*, Tooltip};
+use util::ResultExt;
+use workspace::Workspace;
+
+#[derive(Default, Debug)]
+pub struct SignatureHelpState {
+    task: Option<Task<Option<()>>>,
+    popover: Option<SignatureHelpPopover>,
+}
+
+impl SignatureHelpState {
+    pub fn visible(&self) -> bool {
+        self.popover.is_some()
+    }
+
+    pub fn popover(&self) -> Option<&SignatureHelpPopover> {
+        self.popover.as_ref()
+    }
+
+    pub fn has_multiple_signatures(&self) -> bool {
+        self.popover
+            .as_ref()
+            .map_or(false, |popover| popover.signature_help.signatures.len() > 1)
+    }
+
+    pub fn render(
+        &mut self,
+        style: &EditorStyle,
+        max_size: Size<Pixels>,
+        workspace: Option<WeakView<Workspace>>,
+        cx: &mut ViewContext<Editor>,
+    ) -> Option<AnyElement> {
+        let popover = self.popover.as_mut()?;
+        Some(popover.render(style, max_size, workspace, cx))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SignatureHelpPopover {
+    pub signature_help: SignatureHelp,
+    /// The signature shown in the popover, which the user may have cycled away
+    /// from the one the language server considers active.
+    pub active_signature: usize,
+    /// Documentation for the active parameter and the signature itself, one entry per signature.
+    pub parsed_documentation: Vec<Option<ParsedMarkdown>>,
+    pub scroll_handle: ScrollHandle,
+}
+
+impl SignatureHelpPopover {
+    pub fn render(
+        &mut self,
+        style: &EditorStyle,
+        max_size: Size<Pixels>,
+        workspace: Option<WeakView<Workspace>>,
+        cx: &mut ViewContext<Editor>,
+    ) -> AnyElement {
+        let signature_count = self.signature_help.signatures.len();
+        let signature = &self.signature_help.signatures[self.active_signature];
+        let highlights = signature.active_parameter().map(|parameter| {
+            (
+                parameter.label_range.clone(),
+                HighlightStyle {
+                    font_weight: Some(FontWeight::BOLD),
+                    underline: Some(UnderlineStyle {
+                        thickness: px(1.),
+                        color: None,
+                        wavy: false,
+                    }),
+                    ..Default::default()
+                },
+            )
+        });
+        let label =
+            StyledText::new(signature.label.clone()).with_highlights(&style.text, highlights);
+
+        let overloads = (signature_count > 1).then(|| {
+            h_flex()
+                .flex_none()
+                .gap_0p5()
+                .child(
+                    IconButton::new("signature_help_previous", IconName::ChevronUp)
+                        .icon_size(IconSize::Small)
+                        .tooltip(|cx| {
+                            Tooltip::for_action("Previous Signature", &SignatureHelpPrevious, cx)
+                        })
+                        .on_click(cx.listener(|editor, _, cx| {
+                            signature_help_previous(editor, &SignatureHelpPrevious, cx)
+                        })),
+                )
+                .child(
+                    Label::new(format!("{}/{}", self.active_signature + 1, signature_count))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    IconButton::new("signature_help_next", IconName::ChevronDown)
+                        .icon_size(IconSize::Small)
+                        .tooltip(|cx| Tooltip::for_action("Next Signature", &SignatureHelpNext, cx))
+                        .on_click(cx.listener(|editor, _, cx| {
+                            signature_help_next(editor, &SignatureHelpNext, cx)
+                        })),
+                )
+        });
+
+        let documentation = self
+            .parsed_documentation
+            .get(self.active_signature)
+            .and_then(Option::as_ref)
+            .map(|documentation| {
+                div()
+                    .pt_1()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(crate::render_parsed_markdown(
+                        "signature_help_documentation",
+                        documentation,
+                        style,
+                        workspace,
+                        cx,
+                    ))
+            });
+
+        div()
+            .id("signature_help_popover")
+            .elevation_2(cx)
+            .overflow_y_scroll()
+            .track_scroll(&self.scroll_handle)
+            .max_w(max_size.width)
+            .max_h(max_size.height)
+            // Prevent a mouse down/move on the popover from being propagated to the editor,
+            // because that would move the cursor and dismiss the popover.
+            .on_mouse_move(|_, cx| cx.stop_propagation())
+            .on_mouse_down(MouseButton::Left, |_, cx| cx.stop_propagation())
+            .child(
+                v_flex()
+                    .p_2()
+                    .gap_1()
+                    .child(h_flex().gap_2().children(overloads).child(label))
+                    .children(documentation),
+            )
+            .into_any_element()
+    }
+}
+
+/// Bindable action which requests signature help at the newest selection head.
+pub fn show_signature_help(
+    editor: &mut Editor,
+    _: &ShowSignatureHelp,
+    cx: &mut ViewContext<Editor>,
+) {
+    let is_retrigger = editor.signature_help_state.visible();
+    request_signature_help(
+        editor,
+        lsp::SignatureHelpContext {
+            trigger_kind: lsp::SignatureHelpTriggerKind::INVOKED,
+            trigger_character: None,
+            is_retrigger,
+            active_signature_help: None,
+        },
+        cx,
+    );
+}
+
+/// Requests signature help after text was typed into the editor.
+///
+/// The characters the language server registered as triggers open the popover. While the popover
+/// is visible, any other input re-requests the help so that the active parameter follows the cursor.
+pub(crate) fn signature_help_on_input(
+    editor: &mut Editor,
+    text: &str,
+    cx: &mut ViewContext<Editor>,
+) {
+    if !EditorSettings::get_global(cx).auto_signature_help {
+        return;
+    }
+
+    let is_retrigger = editor.signature_help_state.visible();
+    let mut chars = text.chars();
+    let trigger_character = match (chars.next(), chars.next()) {
+        (Some(character), None) => Some(character),
+        _ => None,
+    };
+    let is_trigger = trigger_character.map_or(false, |character| {
+        let position = editor.selections.newest_anchor().head();
+        let Some((project, (buffer, _))) = editor.project.as_ref().zip(
+            editor
+                .buffer
+                .read(cx)
+                .text_anchor_for_position(position, cx),
+        ) else {
+            return false;
+        };
+        let is_trigger = project.read(cx).is_signature_help_trigger(
+            &buffer,
+            &character.to_string(),
+            is_retrigger,
+            cx,
+        );
+        match is_trigger {
+            Some(is_trigger) => is_trigger,
+            // The host of a remote project drops the help for characters that aren't triggers,
+            // so while it's shown, the help is re-requested for the change instead.
+            None => {
+                let snapshot = editor.buffer.read(cx).snapshot(cx);
+                let scope = snapshot.language_scope_at(position);
+                !is_retrigger
+                    && !character.is_whitespace()
+                    && char_kind(&scope, character) != CharKind::Word
+            }
+        }
+    });
+
+    if is_trigger {
+        request_signature_help(
+            editor,
+            lsp::SignatureHelpContext {
+                trigger_kind: lsp::SignatureHelpTriggerKind::TRIGGER_CHARACTER,
+                trigger_character: trigger_character.map(|character| character.to_string()),
+                is_retrigger,
+                active_signature_help: None,
+            },
+            cx,
+        );
+    } else if is_retrigger {
+        refresh_signature_help(editor, cx);
+    }
+}
+
+/// Re-requests the visible signature help, e.g. after the cursor moved within the call.
+pub(crate) fn refresh_signature_help(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if !editor.signature_help_state.visible() {
+        return;
+    }
+
+    request_signature_help(
+        editor,
+        lsp::SignatureHelpContext {
+            trigger_kind: lsp::SignatureHelpTriggerKind::CONTENT_CHANGE,
+            trigger_character: None,
+            is_retrigger: true,
+            active_signature_help: None,
+        },
+        cx,
+    );
+}
+
+/// Hides the signature help popover, returning whether it was visible.
+pub fn hide_signature_help(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> bool {
+    editor.signature_help_state.task = None;
+    let did_hide = editor.signature_help_state.popover.take().is_some();
+    if did_hide {
+        cx.notify();
+    }
+    did_hide
+}
+
+pub fn signature_help_next(
+    editor: &mut Editor,
+    _: &SignatureHelpNext,
+    cx: &mut ViewContext<Editor>,
+) {
+    cycle_signature(editor, true, cx);
+}
+
+pub fn signature_help_previous(
+    editor: &mut Editor,
+    _: &SignatureHelpPrevious,
+    cx: &mut ViewContext<Editor>,
+) {
+    cycle_signature(editor, false, cx);
+}
+
+fn cycle_signature(editor: &mut Editor, forward: bool, cx: &mut ViewContext<Editor>) {
+    let Some(popover) = editor.signature_help_state.popover.as_mut() else {
+        return;
+    };
+    let signature_count = popover.signature_help.signatures.len();
+    popover.active_signature = if forward {
+        (popover.active_signature + 1) % signature_count
+    } else {
+        (popover.active_signature + signature_count - 1) % signature_count
+    };
+    cx.notify();
+}
+
+fn request_signature_help(
+    editor: &mut Editor,
+    context: lsp::SignatureHelpContext,
+    cx: &mut ViewContext<Editor>,
+) {
+    if editor.pending_rename.is_some() {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let position = editor.selections.newest_anchor().head();
+    let Some((buffer, buffer_position)) = editor
+        .buffer
+        .read(cx)
+        .text_anchor_for_position(position, cx)
+    else {
+        return;
+    };
+
+    let task = cx.spawn(|editor, mut cx| async move {
+        let signature_help = project
+            .update(&mut cx, |project, cx| {
+                project.signature_help(&buffer, buffer_position, context, cx)
+            })
+            .ok()?
+            .await
+            .log_err()
+            .flatten();
+        let Some(signature_help) = signature_help else {
+            editor
+                .update(&mut cx, |editor, cx| {
+                    editor.signature_help_state.popover = None;
+                    cx.notify();
+                })
+                .ok()?;
+            return None;
+        };
+
+        let language_registry = project
+            .update(&mut cx, |project, _| project.languages().clone())
+            .ok()?;
+        let language = buffer
+            .update(&mut cx, |buffer, _| buffer.language().cloned())
+            .ok()?;
+        let mut parsed_documentation = Vec::with_capacity(signature_help.signatures.len());
+        for signature in &signature_help.signatures {
+            let blocks = signature
+                .active_parameter()
+                .and_then(|parameter| parameter.documentation.clone())
+                .into_iter()
+                .chain(signature.documentation.clone())
+                .collect::<Vec<_>>();
+            parsed_documentation.push(if blocks.is_empty() {
+                None
+            } else {
+                Some(parse_blocks(&blocks, &language_registry, language.clone()).await)
+            });
+        }
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                // Keep the overload the user cycled to while the call being edited stays the same.
+                let active_signature = editor
+                    .signature_help_state
+                    .popover
+                    .as_ref()
+                    .filter(|popover| {
+                        popover
+                            .signature_help
+                            .signatures
+                            .iter()
+                            .map(|signature| &signature.label)
+                            .eq(signature_help
+                                .signatures
+                                .iter()
+                                .map(|signature| &signature.label))
+                    })
+                    .map_or(signature_help.active_signature, |popover| {
+                        popover.active_signature
+                    });
+                editor.signature_help_state.popover = Some(SignatureHelpPopover {
+                    signature_help,
+                    active_signature,
+                    parsed_documentation,
+                    scroll_handle: ScrollHandle::new(),
+                });
+                cx.notify();
+            })
+            .ok()
+    });
+    editor.signature_help_state.task = Some(task);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_lsp_test_context::EditorLspTestContext};
+    use futures::StreamExt;
+    use indoc::indoc;
+
+    fn signature_help_response() -> lsp::SignatureHelp {
+        lsp::SignatureHelp {
+            signatures: vec![
+                lsp::SignatureInformation {
+                    label: "fn add(a: u32, b: u32) -> u32".to_string(),
+                    documentation: Some(lsp::Documentation::String("Adds two numbers".to_string())),
+                    parameters: Some(vec![
+                        lsp::ParameterInformation {
+                            label: lsp::ParameterLabel::Simple("a: u32".to_string()),
+                            documentation: None,
+                        },
+                        lsp::ParameterInformation {
+                            label: lsp::ParameterLabel::Simple("b: u32".to_string()),
+                            documentation: None,
+                        },
+                    ]),
+                    active_parameter: None,
+                },
+                lsp::SignatureInformation {
+                    label: "fn add(a: f32, b: f32) -> f32".to_string(),
+                    documentation: None,
+                    parameters: Some(vec![
+                        lsp::ParameterInformation {
+                            label: lsp::ParameterLabel::LabelOffsets([7, 13]),
+                            documentation: None,
+                        },
+                        lsp::ParameterInformation {
+                            label: lsp::ParameterLabel::LabelOffsets([15, 21]),
+                            documentation: None,
+                        },
+                    ]),
+                    active_parameter: None,
+                },
+            ],
+            active_signature: Some(0),
+            active_parameter: Some(0),
+        }
+    }
+
+    #[gpui::test]
+    async fn test_signature_help_on_trigger_characters(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                signature_help_provider: Some(lsp::SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn main() { addˇ }
+        "});
+
+        let mut requests = cx.handle_request::<lsp::request::SignatureHelpRequest, _, _>(
+            move |_, params, _| async move {
+                let context = params.context.unwrap();
+                let mut response = signature_help_response();
+                if context.trigger_character.as_deref() == Some(",") {
+                    response.active_parameter = Some(1);
+                }
+                Ok(Some(response))
+            },
+        );
+
+        cx.simulate_keystroke("(");
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| {
+            let popover = editor.signature_help_state.popover().unwrap();
+            assert_eq!(popover.active_signature, 0);
+            let signature = &popover.signature_help.signatures[0];
+            assert_eq!(
+                &signature.label[signature.active_parameter().unwrap().label_range.clone()],
+                "a: u32"
+            );
+            assert!(popover.parsed_documentation[0].is_some());
+        });
+
+        cx.update_editor(|editor, cx| signature_help_next(editor, &SignatureHelpNext, cx));
+        cx.editor(|editor, _| {
+            let popover = editor.signature_help_state.popover().unwrap();
+            assert_eq!(popover.active_signature, 1);
+            let signature = &popover.signature_help.signatures[1];
+            assert_eq!(
+                &signature.label[signature.active_parameter().unwrap().label_range.clone()],
+                "a: f32"
+            );
+        });
+
+        // Moving to the next argument keeps the selected overload and updates the active parameter.
+        cx.simulate_keystrokes("1 ,");
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| {
+            let popover = editor.signature_help_state.popover().unwrap();
+            assert_eq!(popover.active_signature, 1);
+            let signature = &popover.signature_help.signatures[1];
+            assert_eq!(
+                &signature.label[signature.active_parameter().unwrap().label_range.clone()],
+                "b: f32"
+            );
+        });
+
+        cx.update_editor(|editor, cx| editor.cancel(&crate::Cancel, cx));
+        cx.editor(|editor, _| assert!(!editor.signature_help_state.visible()));
+    }
+
+    #[gpui::test]
+    async fn test_signature_help_on_other_characters(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                signature_help_provider: Some(lsp::SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn main() { ˇ }
+        "});
+
+        let mut requests = cx.handle_request::<lsp::request::SignatureHelpRequest, _, _>(
+            move |_, params, _| async move {
+                let context = params.context.unwrap();
+                // Servers don't answer for the characters they didn't register.
+                if context.trigger_kind == lsp::SignatureHelpTriggerKind::TRIGGER_CHARACTER {
+                    assert_eq!(context.trigger_character.as_deref(), Some("("));
+                }
+                Ok(Some(signature_help_response()))
+            },
+        );
+
+        // Characters that aren't triggers don't open the popover.
+        cx.simulate_keystroke(".");
+        cx.run_until_parked();
+        assert!(requests.try_next().is_err());
+        cx.editor(|editor, _| assert!(!editor.signature_help_state.visible()));
+
+        cx.simulate_keystrokes("a d d (");
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(editor.signature_help_state.visible()));
+
+        // Once it's open, they re-request the help as a change of the content.
+        cx.simulate_keystroke("x");
+        requests.next().await;
+        cx.run_until_parked();
+        cx.simulate_keystroke(".");
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(editor.signature_help_state.visible()));
+    }
+
+    #[gpui::test]
+    async fn test_signature_help_is_hidden_when_server_returns_nothing(
+        cx: &mut gpui::TestAppContext,
+    ) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                signature_help_provider: Some(lsp::SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn main() { add(ˇ) }
+        "});
+
+        let mut requests = cx.handle_request::<lsp::request::SignatureHelpRequest, _, _>(
+            move |_, _, _| async move { Ok(Some(signature_help_response())) },
+        );
+        cx.update_editor(|editor, cx| show_signature_help(editor, &ShowSignatureHelp, cx));
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(editor.signature_help_state.visible()));
+
+        // Leaving the call makes the server report no signatures, which dismisses the popover.
+        let mut requests = cx.handle_request::<lsp::request::SignatureHelpRequest, _, _>(
+            move |_, _, _| async move { Ok(None) },
+        );
+        cx.update_editor(|editor, cx| editor.move_right(&crate::MoveRight, cx));
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(!editor.signature_help_state.visible()));
+    }
+}
@@ -0,0 +1,195 @@
+use std::ops::Range;
+
+use language::TextObject;
+use multi_buffer::MultiBufferSnapshot;
+
+use crate::Direction;
+
+/// Returns the innermost text object of the given kind around `range`, as captured by the
+/// `textobjects` query of the language at that position.
+///
+/// An empty range selects the object the position is in, while a non-empty range only
+/// selects an object larger than itself, so that selecting text objects repeatedly grows
+/// the selection.
+pub fn enclosing_text_object(
+    snapshot: &MultiBufferSnapshot,
+    range: Range<usize>,
+    text_object: TextObject,
+) -> Option<Range<usize>> {
+    let around = text_object.around();
+    let inside = text_object.inside();
+
+    // Include the character after an empty range, so that objects starting at it are found.
+    let query_range = range.start..(range.end + 1).min(snapshot.len());
+    let mut around_ranges = Vec::new();
+    let mut inside_ranges = Vec::new();
+    for (object_range, object) in snapshot.text_object_ranges(query_range) {
+        // An empty range is on the character after it, so it is not in an object ending there.
+        let contains_range = object_range.start <= range.start
+            && (range.end < object_range.end
+                || (!range.is_empty() && range.end == object_range.end));
+        if !contains_range {
+            continue;
+        }
+        if object == around {
+            around_ranges.push(object_range);
+        } else if object == inside {
+            inside_ranges.push(object_range);
+        }
+    }
+
+    // Queries may capture only one of the two ranges of an object. Without an inside range,
+    // the inside of an object is the whole object; without an around range, the object only
+    // grows by the separator that follows a parameter.
+    let mut candidates = Vec::new();
+    for around_range in around_ranges {
+        let inside_range = snapshot
+            .text_object_ranges(around_range.clone())
+            .filter(|(object_range, object)| {
+                *object == inside
+                    && around_range.start <= object_range.start
+                    && object_range.end <= around_range.end
+            })
+            .map(|(object_range, _)| object_range)
+            .max_by_key(|object_range| object_range.len())
+            .unwrap_or_else(|| around_range.clone());
+        inside_ranges.retain(|object_range| *object_range != inside_range);
+        candidates.push((around_range, inside_range));
+    }
+    for inside_range in inside_ranges {
+        let around_range = if around == TextObject::AroundParameter {
+            with_separator(snapshot, inside_range.clone())
+        } else {
+            inside_range.clone()
+        };
+        candidates.push((around_range, inside_range));
+    }
+
+    candidates
+        .into_iter()
+        .filter_map(|(around_range, inside_range)| {
+            let object_range = if text_object == around {
+                around_range.clone()
+            } else {
+                without_delimiters(snapshot, inside_range, text_object)
+            };
+            let grows = object_range.start <= range.start
+                && range.end <= object_range.end
+                && object_range != range;
+            (range.is_empty() || grows).then_some((around_range, object_range))
+        })
+        .min_by_key(|(around_range, _)| around_range.len())
+        .map(|(_, object_range)| object_range)
+}
+
+/// Returns the start of the closest text object of the given kind before or after `offset`,
+/// or the offset of its last character when `end` is true.
+pub fn text_object_boundary(
+    snapshot: &MultiBufferSnapshot,
+    offset: usize,
+    text_object: TextObject,
+    direction: Direction,
+    end: bool,
+) -> Option<usize> {
+    let query_range = match direction {
+        Direction::Prev => 0..offset,
+        Direction::Next => offset..snapshot.len(),
+    };
+    let boundaries = snapshot
+        .text_object_ranges(query_range)
+        .filter(|(_, object)| *object == text_object)
+        .filter_map(|(object_range, _)| {
+            if end {
+                let last_char = snapshot.reversed_chars_at(object_range.end).next()?;
+                Some(object_range.end - last_char.len_utf8())
+            } else {
+                Some(object_range.start)
+            }
+        });
+    match direction {
+        Direction::Prev => boundaries.filter(|boundary| *boundary < offset).max(),
+        Direction::Next => boundaries.filter(|boundary| *boundary > offset).min(),
+    }
+}
+
+/// Strips the brackets that delimit the body of a function, class or block. When the
+/// brackets are on their own lines, the lines in between are selected, as with vim's `i{`.
+fn without_delimiters(
+    snapshot: &MultiBufferSnapshot,
+    range: Range<usize>,
+    text_object: TextObject,
+) -> Range<usize> {
+    if !matches!(
+        text_object,
+        TextObject::InsideFunction | TextObject::InsideClass | TextObject::InsideBlock
+    ) || range.len() < 2
+    {
+        return range;
+    }
+
+    let first = snapshot.chars_at(range.start).next();
+    let last = snapshot.reversed_chars_at(range.end).next();
+    if !matches!(
+        (first, last),
+        (Some('{'), Some('}')) | (Some('('), Some(')')) | (Some('['), Some(']'))
+    ) {
+        return range;
+    }
+
+    let mut start = range.start + 1;
+    let mut end = range.end - 1;
+    let opening_line_rest = snapshot
+        .chars_at(start)
+        .take_while(|c| *c != '\n')
+        .map(char::len_utf8)
+        .sum::<usize>();
+    let closing_line_start = snapshot
+        .reversed_chars_at(end)
+        .take_while(|c| *c != '\n')
+        .map(char::len_utf8)
+        .sum::<usize>();
+    let multiline = start + opening_line_rest < end
+        && snapshot
+            .text_for_range(start..start + opening_line_rest)
+            .all(|chunk| chunk.trim().is_empty())
+        && snapshot
+            .text_for_range(end - closing_line_start..end)
+            .all(|chunk| chunk.trim().is_empty());
+    if multiline {
+        start += opening_line_rest + 1;
+        end -= closing_line_start;
+        if start > end {
+            end = start;
+        }
+    }
+    start..end
+}
+
+/// Extends a parameter with the comma and whitespace that follow it or, for the last
+/// parameter, with the comma that precedes it.
+fn with_separator(snapshot: &MultiBufferSnapshot, range: Range<usize>) -> Range<usize> {
+    let mut following = snapshot.chars_at(range.end).peekable();
+    let mut end = range.end;
+    while following.next_if(|c| *c == ' ' || *c == '\t').is_some() {
+        end += 1;
+    }
+    if following.next() == Some(',') {
+        end += 1;
+        end += following
+            .take_while(|c| c.is_whitespace())
+            .map(char::len_utf8)
+            .sum::<usize>();
+        return range.start..end;
+    }
+
+    let mut preceding = snapshot.reversed_chars_at(range.start).peekable();
+    let mut start = range.start;
+    while let Some(c) = preceding.next_if(|c| c.is_whitespace()) {
+        start -= c.len_utf8();
+    }
+    if preceding.next() == Some(',') {
+        start - 1..range.end
+    } else {
+        range
+    }
+}
@@ -0,0 +1,567 @@
+//! Keyboard macros: the actions dispatched by keystrokes and the text typed into editors are
+//! recorded, to be replayed later or saved under a name.
+
+mod macro_picker;
+mod persistence;
+mod recording_indicator;
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    mem,
+    ops::Range,
+    rc::Rc,
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use editor::{Editor, EditorEvent};
+use gpui::{
+    actions, impl_actions, Action, AppContext, AsyncAppContext, Global, KeystrokeEvent, ReadGlobal,
+    UpdateGlobal, ViewContext, WeakView, WindowContext,
+};
+use macro_picker::{MacroPicker, MacroPickerMode};
+use persistence::KEYBOARD_MACROS_DB;
+pub use recording_indicator::RecordingIndicator;
+use serde::{Deserialize, Serialize};
+use util::ResultExt;
+use workspace::Workspace;
+
+/// Replays the macro recorded last.
+#[derive(Clone, Default, PartialEq, Deserialize)]
+pub struct ReplayLastMacro {
+    /// How many times to replay the macro, once if not set.
+    #[serde(default)]
+    pub times: Option<usize>,
+}
+
+/// Replays a saved macro, picking one to replay if no name is given.
+#[derive(Clone, Default, PartialEq, Deserialize)]
+pub struct ReplaySavedMacro {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// How many times to replay the macro, once if not set.
+    #[serde(default)]
+    pub times: Option<usize>,
+}
+
+actions!(
+    keyboard_macros,
+    [ToggleRecording, SaveLastMacro, DeleteSavedMacro]
+);
+
+impl_actions!(keyboard_macros, [ReplayLastMacro, ReplaySavedMacro]);
+
+/// The actions of this crate drive the recorder, so they are never recorded themselves.
+const NAMESPACE: &str = "keyboard_macros::";
+
+/// Stops a replay that keeps replaying itself, e.g. a macro that ends by replaying its own
+/// register, once this many macros have been replayed by one another.
+const MAX_REPLAY_DEPTH: usize = 1_000;
+
+pub fn init(cx: &mut AppContext) {
+    cx.set_global(MacroRecorder::default());
+    cx.observe_keystrokes(observe_keystrokes).detach();
+
+    cx.observe_new_views(|_, cx: &mut ViewContext<Editor>| {
+        let editor = cx.view().clone();
+        cx.subscribe(&editor, |_, editor, event: &EditorEvent, cx| {
+            let step = match event {
+                EditorEvent::Focused => {
+                    MacroRecorder::update_global(cx, |recorder, _| {
+                        recorder.active_editor = Some(editor.downgrade())
+                    });
+                    return;
+                }
+                EditorEvent::InputIgnored { text } => MacroStep::Insertion {
+                    text: text.clone(),
+                    utf16_range_to_replace: None,
+                },
+                EditorEvent::InputHandled {
+                    text,
+                    utf16_range_to_replace,
+                } => MacroStep::Insertion {
+                    text: text.clone(),
+                    utf16_range_to_replace: utf16_range_to_replace.clone(),
+                },
+                _ => return,
+            };
+            if MacroRecorder::global(cx).is_recording() {
+                MacroRecorder::update_global(cx, |recorder, _| recorder.record(step));
+            }
+        })
+        .detach();
+    })
+    .detach();
+
+    cx.observe_new_views(register).detach();
+    MacroRecorder::load_saved_macros(cx);
+}
+
+fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|_: &mut Workspace, _: &ToggleRecording, cx| {
+        MacroRecorder::update_global(cx, |recorder, _| {
+            if !recorder.stop_recording() {
+                recorder.start_recording(None, false);
+            }
+        });
+    });
+    workspace.register_action(|workspace: &mut Workspace, action: &ReplayLastMacro, cx| {
+        let Some(steps) = MacroRecorder::global(cx).last_macro().map(<[_]>::to_vec) else {
+            workspace.show_error(&anyhow!("No macro has been recorded yet"), cx);
+            return;
+        };
+        MacroRecorder::replay(&steps, action.times.unwrap_or(1), cx);
+    });
+    workspace.register_action(|workspace: &mut Workspace, action: &ReplaySavedMacro, cx| {
+        let Some(name) = action.name.as_ref() else {
+            MacroPicker::toggle(workspace, MacroPickerMode::Replay, cx);
+            return;
+        };
+        match MacroRecorder::global(cx)
+            .saved_macro(name)
+            .map(<[_]>::to_vec)
+        {
+            Some(steps) => MacroRecorder::replay(&steps, action.times.unwrap_or(1), cx),
+            None => workspace.show_error(&anyhow!("No macro is saved as `{name}`"), cx),
+        }
+    });
+    workspace.register_action(|workspace: &mut Workspace, _: &SaveLastMacro, cx| {
+        let recorder = MacroRecorder::global(cx);
+        let Some(steps) = recorder.last_macro() else {
+            workspace.show_error(&anyhow!("No macro has been recorded yet"), cx);
+            return;
+        };
+        // Check that the macro can be saved before asking for its name.
+        if let Err(error) = serialize_steps(steps, cx) {
+            workspace.show_error(&error, cx);
+            return;
+        }
+        MacroPicker::toggle(workspace, MacroPickerMode::Save, cx);
+    });
+    workspace.register_action(|workspace: &mut Workspace, _: &DeleteSavedMacro, cx| {
+        MacroPicker::toggle(workspace, MacroPickerMode::Delete, cx);
+    });
+}
+
+fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext) {
+    let Some(action) = keystroke_event.action.as_ref() else {
+        return;
+    };
+    if action.name().starts_with(NAMESPACE) || !MacroRecorder::global(cx).is_recording() {
+        return;
+    }
+    MacroRecorder::update_global(cx, |recorder, _| {
+        recorder.record(MacroStep::Action(action.boxed_clone()))
+    });
+}
+
+/// A step of a keyboard macro.
+#[derive(Debug)]
+pub enum MacroStep {
+    /// An action dispatched by a keystroke.
+    Action(Box<dyn Action>),
+    /// Text typed into an editor, replacing the given range relative to each cursor, if any.
+    Insertion {
+        text: Arc<str>,
+        utf16_range_to_replace: Option<Range<isize>>,
+    },
+}
+
+impl Clone for MacroStep {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Action(action) => Self::Action(action.boxed_clone()),
+            Self::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => Self::Insertion {
+                text: text.clone(),
+                utf16_range_to_replace: utf16_range_to_replace.clone(),
+            },
+        }
+    }
+}
+
+/// Records keyboard macros and replays them, keeping the last recorded macro, the macros recorded
+/// into registers and the macros saved under a name.
+#[derive(Default)]
+pub struct MacroRecorder {
+    recording: Option<Recording>,
+    last_macro: Option<Vec<MacroStep>>,
+    registers: HashMap<char, Vec<MacroStep>>,
+    saved_macros: BTreeMap<String, Vec<MacroStep>>,
+    /// The editor focused last, which the text of replayed macros is typed into.
+    active_editor: Option<WeakView<Editor>>,
+    replaying: bool,
+    /// The steps left to replay, along with the number of replayed macros that replayed them.
+    pending_steps: VecDeque<(MacroStep, usize)>,
+    /// The number of replayed macros that replayed the step being replayed.
+    replay_depth: usize,
+    /// Whether the replay was stopped for replaying more than `MAX_REPLAY_DEPTH` macros.
+    replay_depth_exceeded: bool,
+    replay_observers: Vec<Rc<dyn Fn(&dyn Action, &mut WindowContext)>>,
+}
+
+struct Recording {
+    register: Option<char>,
+    steps: Vec<MacroStep>,
+}
+
+impl Global for MacroRecorder {}
+
+impl MacroRecorder {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// The register that the macro being recorded is going to be stored in.
+    pub fn recording_register(&self) -> Option<char> {
+        self.recording.as_ref()?.register
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replaying
+    }
+
+    /// Starts recording a macro, dropping the one being recorded, if any. When `append` is set,
+    /// the recorded steps are added to the ones already in the register.
+    pub fn start_recording(&mut self, register: Option<char>, append: bool) {
+        let steps = match register {
+            Some(register) if append => self.registers.get(&register).cloned(),
+            _ => None,
+        };
+        self.recording = Some(Recording {
+            register,
+            steps: steps.unwrap_or_default(),
+        });
+    }
+
+    /// Stops recording, returning whether a macro was being recorded.
+    pub fn stop_recording(&mut self) -> bool {
+        let Some(recording) = self.recording.take() else {
+            return false;
+        };
+        if let Some(register) = recording.register {
+            self.registers.insert(register, recording.steps.clone());
+        }
+        self.last_macro = Some(recording.steps);
+        true
+    }
+
+    fn record(&mut self, step: MacroStep) {
+        // The steps of replayed macros are not recorded, the action that replayed them is.
+        if self.replaying {
+            return;
+        }
+        if let Some(recording) = self.recording.as_mut() {
+            recording.steps.push(step);
+        }
+    }
+
+    pub fn last_macro(&self) -> Option<&[MacroStep]> {
+        self.last_macro.as_deref()
+    }
+
+    pub fn register(&self, register: char) -> Option<&[MacroStep]> {
+        self.registers.get(&register).map(Vec::as_slice)
+    }
+
+    pub fn saved_macro(&self, name: &str) -> Option<&[MacroStep]> {
+        self.saved_macros.get(name).map(Vec::as_slice)
+    }
+
+    pub fn saved_macro_names(&self) -> impl Iterator<Item = &str> {
+        self.saved_macros.keys().map(String::as_str)
+    }
+
+    /// Calls the given callback after each action dispatched by a replayed macro, as such actions
+    /// are not reported to keystroke observers.
+    pub fn observe_replayed_actions(
+        &mut self,
+        callback: impl Fn(&dyn Action, &mut WindowContext) + 'static,
+    ) {
+        self.replay_observers.push(Rc::new(callback));
+    }
+
+    /// Saves the macro recorded last under the given name, replacing the macro saved under it.
+    pub fn save_last_macro(&mut self, name: String, cx: &mut AppContext) -> Result<()> {
+        let steps = self
+            .last_macro
+            .clone()
+            .ok_or_else(|| anyhow!("No macro has been recorded yet"))?;
+        let serialized_steps = serialize_steps(&steps, cx)?;
+        self.saved_macros.insert(name.clone(), steps);
+        cx.background_executor()
+            .spawn(KEYBOARD_MACROS_DB.save_macro(name, serialized_steps))
+            .detach_and_log_err(cx);
+        Ok(())
+    }
+
+    pub fn delete_saved_macro(&mut self, name: &str, cx: &mut AppContext) {
+        if self.saved_macros.remove(name).is_some() {
+            cx.background_executor()
+                .spawn(KEYBOARD_MACROS_DB.delete_macro(name.to_string()))
+                .detach_and_log_err(cx);
+        }
+    }
+
+    fn load_saved_macros(cx: &mut AppContext) {
+        let saved_macros = cx
+            .background_executor()
+            .spawn(async move { KEYBOARD_MACROS_DB.saved_macros() });
+        cx.spawn(|mut cx| async move {
+            let saved_macros = saved_macros.await?;
+            cx.update_global(|recorder: &mut MacroRecorder, cx| {
+                for (name, serialized_steps) in saved_macros {
+                    if let Some(steps) = deserialize_steps(&serialized_steps, cx)
+                        .map_err(|error| anyhow!("failed to load macro `{name}`: {error:#}"))
+                        .log_err()
+                    {
+                        recorder.saved_macros.insert(name, steps);
+                    }
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Replays the given steps the given number of times. The steps of a macro replayed by a
+    /// macro being replayed run before the rest of the latter, so macros can replay other macros.
+    pub fn replay(steps: &[MacroStep], times: usize, cx: &mut WindowContext) {
+        let already_replaying = Self::update_global(cx, |recorder, _| {
+            let depth = if recorder.replaying {
+                recorder.replay_depth + 1
+            } else {
+                0
+            };
+            // Like an error does in vim, a macro that keeps replaying itself stops the whole replay.
+            if depth > MAX_REPLAY_DEPTH {
+                recorder.pending_steps.clear();
+                recorder.replay_depth_exceeded = true;
+                return true;
+            }
+            let steps = steps
+                .iter()
+                .cycle()
+                .take(steps.len() * times)
+                .collect::<Vec<_>>();
+            for step in steps.into_iter().rev() {
+                recorder.pending_steps.push_front((step.clone(), depth));
+            }
+            mem::replace(&mut recorder.replaying, true)
+        });
+        if already_replaying {
+            return;
+        }
+
+        let window = cx.window_handle();
+        cx.spawn(|mut cx| async move {
+            loop {
+                let Ok(Some(step)) = cx.update_global(|recorder: &mut MacroRecorder, _| {
+                    let (step, depth) = recorder.pending_steps.pop_front()?;
+                    recorder.replay_depth = depth;
+                    Some(step)
+                }) else {
+                    break;
+                };
+                let replayed = match step {
+                    MacroStep::Action(action) => window
+                        .update(&mut cx, |_, cx| cx.dispatch_action(action.boxed_clone()))
+                        .and_then(|_| {
+                            // Observers run once the dispatched action has been handled.
+                            window.update(&mut cx, |_, cx| {
+                                let observers = Self::global(cx).replay_observers.clone();
+                                for observer in observers {
+                                    observer(action.as_ref(), cx);
+                                }
+                            })
+                        }),
+                    MacroStep::Insertion {
+                        text,
+                        utf16_range_to_replace,
+                    } => cx
+                        .update(|cx| {
+                            Self::global(cx)
+                                .active_editor
+                                .as_ref()
+                                .and_then(|editor| editor.upgrade())
+                        })
+                        .and_then(|editor| {
+                            let Some(editor) = editor else {
+                                return Ok(());
+                            };
+                            editor.update(&mut cx, |editor, cx| {
+                                editor.replay_insert_event(&text, utf16_range_to_replace, cx)
+                            })
+                        }),
+                };
+                if replayed.is_err() {
+                    break;
+                }
+            }
+
+            // The window may be gone, yet the recorder has to be ready to replay again.
+            let app_cx: &mut AsyncAppContext = &mut cx;
+            let depth_exceeded = app_cx
+                .update_global(|recorder: &mut MacroRecorder, _| {
+                    recorder.pending_steps.clear();
+                    recorder.replaying = false;
+                    recorder.replay_depth = 0;
+                    mem::take(&mut recorder.replay_depth_exceeded)
+                })
+                .log_err()
+                .unwrap_or(false);
+            if depth_exceeded {
+                window
+                    .update(&mut cx, |root, cx| {
+                        let Ok(workspace) = root.downcast::<Workspace>() else {
+                            return;
+                        };
+                        workspace.update(cx, |workspace, cx| {
+                            let error = anyhow!(
+                                "Stopped replaying after {MAX_REPLAY_DEPTH} macros replayed one another"
+                            );
+                            workspace.show_error(&error, cx);
+                        })
+                    })
+                    .log_err();
+            }
+        })
+        .detach();
+    }
+}
+
+/// A step of a saved macro. Actions are saved by name, as that's all an action can be rebuilt
+/// from.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SerializedMacroStep {
+    Action {
+        name: String,
+    },
+    Insertion {
+        text: String,
+        #[serde(default)]
+        utf16_range_to_replace: Option<Range<isize>>,
+    },
+}
+
+fn serialize_steps(steps: &[MacroStep], cx: &AppContext) -> Result<String> {
+    let steps = steps
+        .iter()
+        .map(|step| match step {
+            MacroStep::Action(action) => {
+                let name = action.name();
+                let rebuilt_action = cx.build_action(name, None).ok();
+                if !rebuilt_action.is_some_and(|rebuilt| rebuilt.partial_eq(action.as_ref())) {
+                    return Err(anyhow!(
+                        "The macro can't be saved, as the arguments of `{name}` would be lost"
+                    ));
+                }
+                Ok(SerializedMacroStep::Action {
+                    name: name.to_string(),
+                })
+            }
+            MacroStep::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => Ok(SerializedMacroStep::Insertion {
+                text: text.to_string(),
+                utf16_range_to_replace: utf16_range_to_replace.clone(),
+            }),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(serde_json::to_string(&steps)?)
+}
+
+fn deserialize_steps(serialized_steps: &str, cx: &AppContext) -> Result<Vec<MacroStep>> {
+    serde_json::from_str::<Vec<SerializedMacroStep>>(serialized_steps)?
+        .into_iter()
+        .map(|step| match step {
+            SerializedMacroStep::Action { name } => {
+                Ok(MacroStep::Action(cx.build_action(&name, None)?))
+            }
+            SerializedMacroStep::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => Ok(MacroStep::Insertion {
+                text: text.into(),
+                utf16_range_to_replace,
+            }),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use editor::actions::{MoveDown, SelectNext};
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    fn test_serialize_steps(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let steps = vec![
+                MacroStep::Action(MoveDown.boxed_clone()),
+                MacroStep::Insertion {
+                    text: "ab".into(),
+                    utf16_range_to_replace: Some(-1..0),
+                },
+            ];
+            let serialized_steps = serialize_steps(&steps, cx).unwrap();
+            let deserialized_steps = deserialize_steps(&serialized_steps, cx).unwrap();
+            assert_eq!(deserialized_steps.len(), 2);
+            assert!(
+                matches!(&deserialized_steps[0], MacroStep::Action(action) if action.partial_eq(&MoveDown))
+            );
+            assert!(matches!(
+                &deserialized_steps[1],
+                MacroStep::Insertion { text, utf16_range_to_replace: Some(range) }
+                    if text.as_ref() == "ab" && *range == (-1..0)
+            ));
+
+            // Arguments of actions can't be saved.
+            let steps = vec![MacroStep::Action(
+                SelectNext {
+                    replace_newest: true,
+                }
+                .boxed_clone(),
+            )];
+            assert!(serialize_steps(&steps, cx).is_err());
+        });
+    }
+
+    #[gpui::test]
+    fn test_recording_into_registers(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let mut recorder = MacroRecorder::default();
+            recorder.start_recording(Some('a'), false);
+            recorder.record(MacroStep::Action(MoveDown.boxed_clone()));
+            assert!(recorder.stop_recording());
+            assert!(!recorder.stop_recording());
+
+            recorder.start_recording(Some('a'), true);
+            assert_eq!(recorder.recording_register(), Some('a'));
+            recorder.record(MacroStep::Insertion {
+                text: "x".into(),
+                utf16_range_to_replace: None,
+            });
+            recorder.stop_recording();
+            assert_eq!(recorder.register('a').map(<[_]>::len), Some(2));
+            assert_eq!(recorder.last_macro().map(<[_]>::len), Some(2));
+
+            // Steps of replayed macros are not recorded.
+            recorder.start_recording(None, false);
+            recorder.replaying = true;
+            recorder.record(MacroStep::Action(MoveDown.boxed_clone()));
+            recorder.replaying = false;
+            recorder.stop_recording();
+            assert_eq!(recorder.last_macro().map(<[_]>::len), Some(0));
+            assert_eq!(recorder.register('a').map(<[_]>::len), Some(2));
+
+            assert!(recorder.save_last_macro("empty".into(), cx).is_ok());
+            assert_eq!(recorder.saved_macro_names().collect::<Vec<_>>(), ["empty"]);
+        });
+    }
+}
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{rems, DismissEvent, ReadGlobal, Task, UpdateGlobal, WeakView};
+use picker::{highlighted_match_with_paths::HighlightedText, Picker, PickerDelegate};
+use ui::{prelude::*, ListItem, ListItemSpacing, ViewContext, WindowContext};
+use util::ResultExt;
+use workspace::Workspace;
+
+use crate::MacroRecorder;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MacroPickerMode {
+    /// Saves the macro recorded last under the typed name, or the name of the picked macro.
+    Save,
+    Replay,
+    Delete,
+}
+
+/// Picks a saved macro, or the name to save a macro under.
+pub(crate) struct MacroPicker;
+
+impl MacroPicker {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        mode: MacroPickerMode,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let workspace_handle = cx.view().downgrade();
+        workspace.toggle_modal(cx, |cx| {
+            let names = MacroRecorder::global(cx)
+                .saved_macro_names()
+                .map(ToOwned::to_owned)
+                .collect();
+            let delegate = MacroPickerDelegate {
+                mode,
+                workspace: workspace_handle,
+                names,
+                query: String::new(),
+                matches: Vec::new(),
+                selected_index: 0,
+            };
+            Picker::uniform_list(delegate, cx).width(rems(34.))
+        });
+    }
+}
+
+pub(crate) struct MacroPickerDelegate {
+    mode: MacroPickerMode,
+    workspace: WeakView<Workspace>,
+    names: Vec<String>,
+    query: String,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for MacroPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        match self.mode {
+            MacroPickerMode::Save => "Name to save the last macro as…".into(),
+            MacroPickerMode::Replay => "Macro to replay…".into(),
+            MacroPickerMode::Delete => "Macro to delete…".into(),
+        }
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        match self.mode {
+            MacroPickerMode::Save if !self.query.is_empty() => {
+                format!("Press enter to save the last macro as `{}`", self.query).into()
+            }
+            MacroPickerMode::Save => "Type a name for the last macro".into(),
+            MacroPickerMode::Replay | MacroPickerMode::Delete => "No saved macros".into(),
+        }
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        self.query = query.clone();
+        let candidates = self
+            .names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| StringMatchCandidate::new(id, name.clone()))
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        score: 0.0,
+                        positions: Vec::new(),
+                        string: candidate.string,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    1000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.selected_index = 0;
+                    delegate.matches = matches;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let selected_name = self
+            .matches
+            .get(self.selected_index)
+            .map(|selected| selected.string.clone());
+        match self.mode {
+            MacroPickerMode::Save => {
+                // A typed name only picks an existing one to replace if it matches it exactly.
+                let name = if self.query.is_empty() || selected_name.as_ref() == Some(&self.query) {
+                    selected_name
+                } else {
+                    Some(self.query.clone())
+                };
+                let Some(name) = name.filter(|name| !name.trim().is_empty()) else {
+                    return;
+                };
+                let saved = MacroRecorder::update_global(cx, |recorder, cx| {
+                    recorder.save_last_macro(name.trim().to_string(), cx)
+                });
+                if let Err(error) = saved {
+                    self.workspace
+                        .update(cx, |workspace, cx| workspace.show_error(&error, cx))
+                        .log_err();
+                }
+            }
+            MacroPickerMode::Replay => {
+                let Some(steps) = selected_name
+                    .and_then(|name| Some(MacroRecorder::global(cx).saved_macro(&name)?.to_vec()))
+                else {
+                    return;
+                };
+                // The macro is replayed once the focus is back in the editor.
+                MacroRecorder::replay(&steps, 1, cx);
+            }
+            MacroPickerMode::Delete => {
+                let Some(name) = selected_name else {
+                    return;
+                };
+                MacroRecorder::update_global(cx, |recorder, cx| {
+                    recorder.delete_saved_macro(&name, cx)
+                });
+            }
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        let highlighted_name = HighlightedText {
+            text: hit.string.clone(),
+            highlight_positions: hit.positions.clone(),
+            char_count: hit.string.chars().count(),
+            color: Color::Default,
+        };
+        Some(
+            ListItem::new(SharedString::from(format!("keyboard-macro-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(highlighted_name.render(cx)),
+        )
+    }
+}
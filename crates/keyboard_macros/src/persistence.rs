@@ -0,0 +1,30 @@
+use db::{define_connection, query, sqlez_macros::sql};
+
+define_connection!(pub static ref KEYBOARD_MACROS_DB: KeyboardMacrosDb<()> =
+    &[sql!(
+        CREATE TABLE IF NOT EXISTS keyboard_macros(
+            name TEXT PRIMARY KEY,
+            steps TEXT NOT NULL
+        ) STRICT;
+    )];
+);
+
+impl KeyboardMacrosDb {
+    query! {
+        pub fn saved_macros() -> Result<Vec<(String, String)>> {
+            SELECT name, steps FROM keyboard_macros ORDER BY name
+        }
+    }
+
+    query! {
+        pub async fn save_macro(name: String, steps: String) -> Result<()> {
+            INSERT OR REPLACE INTO keyboard_macros(name, steps) VALUES ((?), (?))
+        }
+    }
+
+    query! {
+        pub async fn delete_macro(name: String) -> Result<()> {
+            DELETE FROM keyboard_macros WHERE name = (?)
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use gpui::{div, ReadGlobal, Render, Subscription, ViewContext};
+use workspace::{item::ItemHandle, ui::prelude::*, StatusItemView};
+
+use crate::MacroRecorder;
+
+/// Shows in the status bar that a keyboard macro is being recorded.
+pub struct RecordingIndicator {
+    recording: Option<Option<char>>,
+    _subscription: Subscription,
+}
+
+impl RecordingIndicator {
+    pub fn new(cx: &mut ViewContext<Self>) -> Self {
+        let _subscription = cx.observe_global::<MacroRecorder>(|this, cx| {
+            this.update_recording(cx);
+        });
+        let mut this = Self {
+            recording: None,
+            _subscription,
+        };
+        this.update_recording(cx);
+        this
+    }
+
+    fn update_recording(&mut self, cx: &mut ViewContext<Self>) {
+        let recorder = MacroRecorder::global(cx);
+        let recording = recorder
+            .is_recording()
+            .then(|| recorder.recording_register());
+        if recording != self.recording {
+            self.recording = recording;
+            cx.notify();
+        }
+    }
+}
+
+impl Render for RecordingIndicator {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        let Some(register) = self.recording else {
+            return div().into_any();
+        };
+
+        let label = match register {
+            Some(register) => format!("Recording @{register}"),
+            None => "Recording macro".to_string(),
+        };
+        Label::new(label)
+            .size(LabelSize::Small)
+            .color(Color::Accent)
+            .line_height_style(LineHeightStyle::UiLabel)
+            .into_any_element()
+    }
+}
+
+impl StatusItemView for RecordingIndicator {
+    fn set_active_pane_item(
+        &mut self,
+        _active_pane_item: Option<&dyn ItemHandle>,
+        _cx: &mut ViewContext<Self>,
+    ) {
+        // nothing to do.
+    }
+}
@@ -14,7 +14,7 @@ use crate::{
         SyntaxSnapshot, ToTreeSitterPoint,
     },
     task_context::RunnableRange,
-    LanguageScope, Outline, RunnableCapture, RunnableTag,
+    LanguageScope, Outline, RunnableCapture, RunnableTag, TextObject,
 };
 use anyhow::{anyhow, Context, Result};
 pub use clock::ReplicaId;
@@ -3014,6 +3014,118 @@ impl BufferSnapshot {
         })
     }
 
+    /// Returns the range that can be folded on the given row, as captured by the `folds`
+    /// query of the languages at that row. The range starts at the end of the row, and
+    /// leaves the closing delimiter of the captured syntax node (if any) unfolded.
+    ///
+    /// Nodes captured as `@fold.group` are folded together with the siblings of the same kind
+    /// on the rows that follow them, such as consecutive imports or line comments. When several
+    /// syntax nodes start on the row, the one spanning the most rows is used.
+    pub fn fold_range_for_row(&self, row: u32) -> Option<Range<Point>> {
+        let line_start = Point::new(row, 0);
+        let line_end = Point::new(row, self.line_len(row));
+        let mut syntax_matches = self.syntax.matches(
+            line_start.to_offset(self)..line_end.to_offset(self),
+            self,
+            |grammar| grammar.folds_config.as_ref().map(|config| &config.query),
+        );
+        let configs = syntax_matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.folds_config.as_ref())
+            .collect::<Vec<_>>();
+
+        let mut fold_end: Option<Point> = None;
+        while let Some(mat) = syntax_matches.peek() {
+            if let Some(config) = configs[mat.grammar_index] {
+                for capture in mat.captures {
+                    let end = if Some(capture.index) == config.fold_capture_ix {
+                        Some(capture.node.end_byte())
+                    } else if Some(capture.index) == config.fold_group_capture_ix {
+                        fold_group_end(capture.node)
+                    } else {
+                        None
+                    };
+                    let Some(end) = end.map(|end| end.to_point(self)) else {
+                        continue;
+                    };
+                    if capture.node.start_position().row as u32 == row
+                        && end.row > row
+                        && fold_end.map_or(true, |fold_end| end > fold_end)
+                    {
+                        fold_end = Some(end);
+                    }
+                }
+            }
+            syntax_matches.advance();
+        }
+
+        let mut fold_end = fold_end?;
+        // Keep the closing delimiters on the last row visible, e.g. `fn main() {⋯}`.
+        let last_row_start = Point::new(fold_end.row, self.indent_size_for_line(fold_end.row).len);
+        if last_row_start < fold_end
+            && self
+                .text_for_range(last_row_start..fold_end)
+                .flat_map(|chunk| chunk.chars())
+                .all(|c| !c.is_alphanumeric() && c != '_')
+        {
+            fold_end = last_row_start;
+        }
+        Some(line_end..fold_end)
+    }
+
+    /// Returns the ranges of the text objects that the `textobjects` query of the languages
+    /// captures in the given range, such as functions and their bodies. Nodes captured together
+    /// by a quantified capture of the same match, like the statements of a body, make up a
+    /// single range.
+    pub fn text_object_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> impl Iterator<Item = (Range<usize>, TextObject)> + '_ {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let mut syntax_matches = self.syntax.matches(range, self, |grammar| {
+            grammar
+                .text_object_config
+                .as_ref()
+                .map(|config| &config.query)
+        });
+        let configs = syntax_matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.text_object_config.as_ref())
+            .collect::<Vec<_>>();
+
+        let mut match_ranges = Vec::<(Range<usize>, TextObject)>::new();
+        iter::from_fn(move || loop {
+            if let Some(text_object) = match_ranges.pop() {
+                return Some(text_object);
+            }
+            let mat = syntax_matches.peek()?;
+            if let Some(config) = configs[mat.grammar_index] {
+                for capture in mat.captures {
+                    let Some(&(_, text_object)) = config
+                        .text_objects_by_capture_ix
+                        .iter()
+                        .find(|(ix, _)| *ix == capture.index)
+                    else {
+                        continue;
+                    };
+                    let node_range = capture.node.byte_range();
+                    if let Some((range, _)) = match_ranges
+                        .iter_mut()
+                        .find(|(_, existing)| *existing == text_object)
+                    {
+                        range.start = range.start.min(node_range.start);
+                        range.end = range.end.max(node_range.end);
+                    } else {
+                        match_ranges.push((node_range, text_object));
+                    }
+                }
+            }
+            syntax_matches.advance();
+        })
+    }
+
     pub fn runnable_ranges(
         &self,
         range: Range<Anchor>,
@@ -3602,6 +3714,30 @@ impl Clone for BufferSnapshot {
     }
 }
 
+/// Returns the end of the group of adjacent siblings of the same kind starting with the given
+/// node, or `None` if the node is not the first of its group.
+fn fold_group_end(node: tree_sitter::Node) -> Option<usize> {
+    let is_adjacent = |previous: &tree_sitter::Node, next: &tree_sitter::Node| {
+        previous.kind_id() == next.kind_id()
+            && next.start_position().row <= previous.end_position().row + 1
+    };
+    if node
+        .prev_named_sibling()
+        .map_or(false, |previous| is_adjacent(&previous, &node))
+    {
+        return None;
+    }
+
+    let mut last = node;
+    while let Some(next) = last.next_named_sibling() {
+        if !is_adjacent(&last, &next) {
+            break;
+        }
+        last = next;
+    }
+    Some(last.end_byte())
+}
+
 impl Deref for BufferSnapshot {
     type Target = text::BufferSnapshot;
 
@@ -1048,6 +1048,67 @@ fn test_range_for_syntax_ancestor(cx: &mut AppContext) {
     }
 }
 
+#[gpui::test]
+fn test_fold_range_for_row(cx: &mut AppContext) {
+    let language = rust_lang()
+        .with_folds_query(
+            r#"
+            (block) @fold
+            (arguments) @fold
+            (block_comment) @fold
+            (line_comment) @fold.group
+            (use_declaration) @fold.group
+            "#,
+        )
+        .unwrap();
+
+    cx.new_model(|cx| {
+        let text = indoc! {"
+            // a
+            // b
+            use a;
+            use b;
+
+            use c;
+            fn a() {
+                b(
+                    c,
+                    d);
+            }
+            /* e
+               f */
+        "};
+        let buffer = Buffer::local(text, cx).with_language(Arc::new(language), cx);
+        let snapshot = buffer.snapshot();
+        let folded_text = |row| {
+            snapshot.fold_range_for_row(row).map(|range| {
+                let mut text = snapshot
+                    .text_for_range(Point::new(row, 0)..range.start)
+                    .collect::<String>();
+                text.push('⋯');
+                text.extend(snapshot.text_for_range(
+                    range.end..Point::new(range.end.row, snapshot.line_len(range.end.row)),
+                ));
+                text
+            })
+        };
+
+        // Adjacent line comments and imports are folded together.
+        assert_eq!(folded_text(0), Some("// a⋯".to_string()));
+        assert_eq!(folded_text(1), None);
+        assert_eq!(folded_text(2), Some("use a;⋯".to_string()));
+        assert_eq!(folded_text(3), None);
+        assert_eq!(folded_text(5), None);
+        // The closing delimiter is kept visible, unless it follows other text.
+        assert_eq!(folded_text(6), Some("fn a() {⋯}".to_string()));
+        assert_eq!(folded_text(7), Some("    b(⋯".to_string()));
+        assert_eq!(folded_text(8), None);
+        assert_eq!(folded_text(11), Some("/* e⋯".to_string()));
+
+        buffer
+    });
+}
+
 #[gpui::test]
 fn test_autoindent_with_soft_tabs(cx: &mut AppContext) {
     init_settings(cx, |_| {});
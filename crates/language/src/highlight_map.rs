@@ -12,32 +12,10 @@ const DEFAULT_SYNTAX_HIGHLIGHT_ID: HighlightId = HighlightId(u32::MAX);
 
 impl HighlightMap {
     pub(crate) fn new(capture_names: &[&str], theme: &SyntaxTheme) -> Self {
-        // For each capture name in the highlight query, find the longest
-        // key in the theme's syntax styles that matches all of the
-        // dot-separated components of the capture name.
         HighlightMap(
             capture_names
                 .iter()
-                .map(|capture_name| {
-                    theme
-                        .highlights
-                        .iter()
-                        .enumerate()
-                        .filter_map(|(i, (key, _))| {
-                            let mut len = 0;
-                            let capture_parts = capture_name.split('.');
-                            for key_part in key.split('.') {
-                                if capture_parts.clone().any(|part| part == key_part) {
-                                    len += 1;
-                                } else {
-                                    return None;
-                                }
-                            }
-                            Some((i, len))
-                        })
-                        .max_by_key(|(_, len)| *len)
-                        .map_or(DEFAULT_SYNTAX_HIGHLIGHT_ID, |(i, _)| HighlightId(i as u32))
-                })
+                .map(|capture_name| HighlightId::for_capture_name(capture_name, theme))
                 .collect(),
         )
     }
@@ -51,6 +29,29 @@ impl HighlightMap {
 }
 
 impl HighlightId {
+    /// Finds the longest key in the theme's syntax styles that matches all of the
+    /// dot-separated components of the given capture name.
+    pub fn for_capture_name(capture_name: &str, theme: &SyntaxTheme) -> Self {
+        theme
+            .highlights
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (key, _))| {
+                let mut len = 0;
+                let capture_parts = capture_name.split('.');
+                for key_part in key.split('.') {
+                    if capture_parts.clone().any(|part| part == key_part) {
+                        len += 1;
+                    } else {
+                        return None;
+                    }
+                }
+                Some((i, len))
+            })
+            .max_by_key(|(_, len)| *len)
+            .map_or(DEFAULT_SYNTAX_HIGHLIGHT_ID, |(i, _)| HighlightId(i as u32))
+    }
+
     pub(crate) fn is_default(&self) -> bool {
         *self == DEFAULT_SYNTAX_HIGHLIGHT_ID
     }
@@ -841,6 +841,8 @@ pub struct Grammar {
     pub(crate) brackets_config: Option<BracketConfig>,
     pub(crate) redactions_config: Option<RedactionConfig>,
     pub(crate) runnable_config: Option<RunnableConfig>,
+    pub(crate) folds_config: Option<FoldsConfig>,
+    pub(crate) text_object_config: Option<TextObjectConfig>,
     pub(crate) indents_config: Option<IndentConfig>,
     pub outline_config: Option<OutlineConfig>,
     pub embedding_config: Option<EmbeddingConfig>,
@@ -899,6 +901,84 @@ struct RunnableConfig {
     pub extra_captures: Vec<RunnableCapture>,
 }
 
+struct FoldsConfig {
+    pub query: Query,
+    pub fold_capture_ix: Option<u32>,
+    pub fold_group_capture_ix: Option<u32>,
+}
+
+struct TextObjectConfig {
+    pub query: Query,
+    /// A mapping from capture indices to the text objects they capture
+    pub text_objects_by_capture_ix: Vec<(u32, TextObject)>,
+}
+
+/// A syntax node captured by a `textobjects` query, such as a function or its body,
+/// for editor actions and vim motions to select or move over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum TextObject {
+    /// `@function.inside`
+    InsideFunction,
+    /// `@function.around`
+    AroundFunction,
+    /// `@class.inside`
+    InsideClass,
+    /// `@class.around`
+    AroundClass,
+    /// `@parameter.inside`
+    InsideParameter,
+    /// `@parameter.around`
+    AroundParameter,
+    /// `@comment.inside`
+    InsideComment,
+    /// `@comment.around`
+    AroundComment,
+    /// `@block.inside`
+    InsideBlock,
+    /// `@block.around`
+    AroundBlock,
+}
+
+impl TextObject {
+    pub fn from_capture_name(name: &str) -> Option<Self> {
+        match name {
+            "function.inside" => Some(Self::InsideFunction),
+            "function.around" => Some(Self::AroundFunction),
+            "class.inside" => Some(Self::InsideClass),
+            "class.around" => Some(Self::AroundClass),
+            "parameter.inside" => Some(Self::InsideParameter),
+            "parameter.around" => Some(Self::AroundParameter),
+            "comment.inside" => Some(Self::InsideComment),
+            "comment.around" => Some(Self::AroundComment),
+            "block.inside" => Some(Self::InsideBlock),
+            "block.around" => Some(Self::AroundBlock),
+            _ => None,
+        }
+    }
+
+    /// The text object of the same kind, including what surrounds it.
+    pub fn around(self) -> Self {
+        match self {
+            Self::InsideFunction | Self::AroundFunction => Self::AroundFunction,
+            Self::InsideClass | Self::AroundClass => Self::AroundClass,
+            Self::InsideParameter | Self::AroundParameter => Self::AroundParameter,
+            Self::InsideComment | Self::AroundComment => Self::AroundComment,
+            Self::InsideBlock | Self::AroundBlock => Self::AroundBlock,
+        }
+    }
+
+    /// The text object of the same kind, without what surrounds it.
+    pub fn inside(self) -> Self {
+        match self {
+            Self::InsideFunction | Self::AroundFunction => Self::InsideFunction,
+            Self::InsideClass | Self::AroundClass => Self::InsideClass,
+            Self::InsideParameter | Self::AroundParameter => Self::InsideParameter,
+            Self::InsideComment | Self::AroundComment => Self::InsideComment,
+            Self::InsideBlock | Self::AroundBlock => Self::InsideBlock,
+        }
+    }
+}
+
 struct OverrideConfig {
     query: Query,
     values: HashMap<u32, (String, LanguageConfigOverride)>,
@@ -941,6 +1021,8 @@ impl Language {
                     override_config: None,
                     redactions_config: None,
                     runnable_config: None,
+                    folds_config: None,
+                    text_object_config: None,
                     error_query: Query::new(&ts_language, "(ERROR) @error").unwrap(),
                     ts_language,
                     highlight_map: Default::default(),
@@ -1001,6 +1083,16 @@ impl Language {
                 .with_runnable_query(query.as_ref())
                 .context("Error loading tests query")?;
         }
+        if let Some(query) = queries.folds {
+            self = self
+                .with_folds_query(query.as_ref())
+                .context("Error loading folds query")?;
+        }
+        if let Some(query) = queries.textobjects {
+            self = self
+                .with_text_object_query(query.as_ref())
+                .context("Error loading textobjects query")?;
+        }
         Ok(self)
     }
 
@@ -1297,6 +1389,56 @@ impl Language {
         Ok(self)
     }
 
+    pub fn with_folds_query(mut self, source: &str) -> Result<Self> {
+        let grammar = self
+            .grammar_mut()
+            .ok_or_else(|| anyhow!("cannot mutate grammar"))?;
+
+        let query = Query::new(&grammar.ts_language, source)?;
+        let mut fold_capture_ix = None;
+        let mut fold_group_capture_ix = None;
+        get_capture_indices(
+            &query,
+            &mut [
+                ("fold", &mut fold_capture_ix),
+                ("fold.group", &mut fold_group_capture_ix),
+            ],
+        );
+
+        if fold_capture_ix.is_some() || fold_group_capture_ix.is_some() {
+            grammar.folds_config = Some(FoldsConfig {
+                query,
+                fold_capture_ix,
+                fold_group_capture_ix,
+            });
+        }
+
+        Ok(self)
+    }
+
+    pub fn with_text_object_query(mut self, source: &str) -> Result<Self> {
+        let grammar = self
+            .grammar_mut()
+            .ok_or_else(|| anyhow!("cannot mutate grammar"))?;
+
+        let query = Query::new(&grammar.ts_language, source)?;
+        let text_objects_by_capture_ix = query
+            .capture_names()
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, name)| Some((ix as u32, TextObject::from_capture_name(name)?)))
+            .collect::<Vec<_>>();
+
+        if !text_objects_by_capture_ix.is_empty() {
+            grammar.text_object_config = Some(TextObjectConfig {
+                query,
+                text_objects_by_capture_ix,
+            });
+        }
+
+        Ok(self)
+    }
+
     fn grammar_mut(&mut self) -> Option<&mut Grammar> {
         Arc::get_mut(self.grammar.as_mut()?)
     }
@@ -1364,6 +1506,14 @@ impl Language {
         self.grammar.as_ref()
     }
 
+    /// Whether the language defines which of its syntax nodes can be folded,
+    /// in which case indentation is not used to determine foldable ranges.
+    pub fn has_folds_query(&self) -> bool {
+        self.grammar
+            .as_ref()
+            .map_or(false, |grammar| grammar.folds_config.is_some())
+    }
+
     pub fn default_scope(self: &Arc<Self>) -> LanguageScope {
         LanguageScope {
             language: self.clone(),
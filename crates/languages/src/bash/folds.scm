@@ -0,0 +1,11 @@
+[
+  (if_statement)
+  (for_statement)
+  (while_statement)
+  (case_statement)
+] @fold
+
+(_ "{" "}") @fold
+(_ "(" ")") @fold
+
+(comment) @fold.group
@@ -0,0 +1,6 @@
+(function_definition
+    body: (_) @function.inside) @function.around
+
+(comment) @comment.around
+
+(compound_statement) @block.around
@@ -0,0 +1,6 @@
+(_ "{" "}") @fold
+(_ "(" ")") @fold
+(_ "[" "]") @fold
+
+(comment) @fold.group
+(preproc_include) @fold.group
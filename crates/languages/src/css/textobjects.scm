@@ -0,0 +1,5 @@
+(arguments (_) @parameter.inside)
+
+(comment) @comment.around
+
+(block) @block.around
@@ -0,0 +1,7 @@
+(_ "{" "}") @fold
+(_ "(" ")") @fold
+(_ "[" "]") @fold
+(jsx_element) @fold
+
+(comment) @fold.group
+(import_statement) @fold.group
@@ -79,6 +79,7 @@ impl JsonLspAdapter {
             cx,
         );
         let tasks_schema = task::TaskTemplates::generate_json_schema();
+        let debug_schema = task::DebugTemplates::generate_json_schema();
         let tsconfig_schema = serde_json::Value::from_str(TSCONFIG_SCHEMA).unwrap();
         let package_json_schema = serde_json::Value::from_str(PACKAGE_JSON_SCHEMA).unwrap();
 
@@ -113,6 +114,13 @@ impl JsonLspAdapter {
                             &*paths::LOCAL_TASKS_RELATIVE_PATH,
                         ],
                         "schema": tasks_schema,
+                    },
+                    {
+                        "fileMatch": [
+                            schema_file_match(&paths::DEBUG),
+                            &*paths::LOCAL_DEBUG_RELATIVE_PATH,
+                        ],
+                        "schema": debug_schema,
                     }
 
                 ]
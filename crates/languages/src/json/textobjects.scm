@@ -0,0 +1,4 @@
+(comment) @comment.around
+
+(object) @block.around
+(array) @block.around
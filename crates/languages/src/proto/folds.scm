@@ -0,0 +1,3 @@
+(_ "{" "}") @fold
+
+(comment) @fold.group
@@ -0,0 +1,7 @@
+(message) @class.around
+(enum) @class.around
+(service) @class.around
+
+(rpc) @function.around
+
+(comment) @comment.around
@@ -0,0 +1,22 @@
+[
+  (function_definition)
+  (class_definition)
+  (if_statement)
+  (elif_clause)
+  (else_clause)
+  (for_statement)
+  (while_statement)
+  (with_statement)
+  (try_statement)
+  (except_clause)
+  (finally_clause)
+  (string)
+] @fold
+
+(_ "(" ")") @fold
+(_ "[" "]") @fold
+(_ "{" "}") @fold
+
+(comment) @fold.group
+(import_statement) @fold.group
+(import_from_statement) @fold.group
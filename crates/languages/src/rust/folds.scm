@@ -0,0 +1,8 @@
+(_ "{" "}") @fold
+(_ "(" ")") @fold
+(_ "[" "]") @fold
+
+(block_comment) @fold
+
+(line_comment) @fold.group
+(use_declaration) @fold.group
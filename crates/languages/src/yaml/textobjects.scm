@@ -0,0 +1,4 @@
+(comment) @comment.around
+
+(block_mapping) @block.around
+(block_sequence) @block.around
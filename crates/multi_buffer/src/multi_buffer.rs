@@ -14,7 +14,8 @@ use language::{
     AutoindentMode, Buffer, BufferChunks, BufferRow, BufferSnapshot, Capability, CharKind, Chunk,
     CursorShape, DiagnosticEntry, File, IndentGuide, IndentSize, Language, LanguageScope,
     OffsetRangeExt, OffsetUtf16, Outline, OutlineItem, Point, PointUtf16, Selection, TextDimension,
-    ToOffset as _, ToOffsetUtf16 as _, ToPoint as _, ToPointUtf16 as _, TransactionId, Unclipped,
+    TextObject, ToOffset as _, ToOffsetUtf16 as _, ToPoint as _, ToPointUtf16 as _, TransactionId,
+    Unclipped,
 };
 use smallvec::SmallVec;
 use std::{
@@ -3290,6 +3291,29 @@ impl MultiBufferSnapshot {
         )
     }
 
+    /// Returns the text objects around the given range, within the excerpt that contains it.
+    pub fn text_object_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> impl Iterator<Item = (Range<usize>, TextObject)> + '_ {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        self.excerpt_containing(range.clone())
+            .map(|excerpt| {
+                excerpt
+                    .buffer()
+                    .text_object_ranges(excerpt.map_range_to_buffer(range))
+                    .filter_map(move |(range, text_object)| {
+                        if excerpt.contains_buffer_range(range.clone()) {
+                            Some((excerpt.map_range_from_buffer(range), text_object))
+                        } else {
+                            None
+                        }
+                    })
+            })
+            .into_iter()
+            .flatten()
+    }
+
     pub fn redacted_ranges<'a, T: ToOffset>(
         &'a self,
         range: Range<T>,
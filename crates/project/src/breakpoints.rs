@@ -0,0 +1,145 @@
+use std::path::PathBuf;
+
+use collections::HashMap;
+use gpui::{AppContext, Model, ModelContext, WeakModel};
+use language::{Anchor, Bias, Buffer, Point, ToPoint as _};
+use util::ResultExt;
+
+use crate::{Event, File, Project, ProjectPath};
+
+/// The lines on which a debuggee should stop, for each file of the project.
+#[derive(Default)]
+pub(crate) struct Breakpoints {
+    files: HashMap<ProjectPath, FileBreakpoints>,
+}
+
+struct FileBreakpoints {
+    /// The buffer of the file, while it is open.
+    buffer: Option<WeakModel<Buffer>>,
+    /// The positions of the breakpoints in the open buffer, which follow its edits.
+    positions: Vec<Anchor>,
+    /// The rows of the breakpoints as of when the buffer was closed, used to place them again
+    /// once the file is reopened.
+    rows: Vec<u32>,
+}
+
+impl Breakpoints {
+    /// Anchors the breakpoints of the buffer's file to it, now that it is open.
+    pub(crate) fn buffer_opened(&mut self, buffer: &Model<Buffer>, cx: &AppContext) {
+        let Some(project_path) = project_path_for_buffer(buffer.read(cx), cx) else {
+            return;
+        };
+        if let Some(file_breakpoints) = self.files.get_mut(&project_path) {
+            let snapshot = buffer.read(cx).snapshot();
+            file_breakpoints.buffer = Some(buffer.downgrade());
+            file_breakpoints.positions = file_breakpoints
+                .rows
+                .drain(..)
+                .map(|row| {
+                    snapshot.anchor_before(snapshot.clip_point(Point::new(row, 0), Bias::Left))
+                })
+                .collect();
+        }
+    }
+
+    /// Remembers the rows of the buffer's breakpoints, as their anchors are about to go away.
+    pub(crate) fn buffer_closed(&mut self, buffer: &Buffer, cx: &AppContext) {
+        let Some(project_path) = project_path_for_buffer(buffer, cx) else {
+            return;
+        };
+        if let Some(file_breakpoints) = self.files.get_mut(&project_path) {
+            file_breakpoints.rows = rows(&file_breakpoints.positions, buffer);
+            file_breakpoints.positions.clear();
+            file_breakpoints.buffer = None;
+        }
+    }
+}
+
+fn project_path_for_buffer(buffer: &Buffer, cx: &AppContext) -> Option<ProjectPath> {
+    let file = File::from_dyn(buffer.file())?;
+    Some(ProjectPath {
+        worktree_id: file.worktree_id(cx),
+        path: file.path.clone(),
+    })
+}
+
+/// The sorted, deduplicated rows of the given positions.
+fn rows(positions: &[Anchor], buffer: &Buffer) -> Vec<u32> {
+    let mut rows = positions
+        .iter()
+        .map(|position| position.to_point(buffer).row)
+        .collect::<Vec<_>>();
+    rows.sort_unstable();
+    rows.dedup();
+    rows
+}
+
+impl Project {
+    /// Adds a breakpoint on the given row of the buffer, or removes the one that is there.
+    pub fn toggle_breakpoint(
+        &mut self,
+        buffer: &Model<Buffer>,
+        row: u32,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(project_path) = project_path_for_buffer(buffer.read(cx), cx) else {
+            return;
+        };
+        let weak_buffer = buffer.downgrade();
+        let buffer = buffer.read(cx);
+        let file_breakpoints = self
+            .breakpoints
+            .files
+            .entry(project_path.clone())
+            .or_insert_with(|| FileBreakpoints {
+                buffer: Some(weak_buffer),
+                positions: Vec::new(),
+                rows: Vec::new(),
+            });
+        let existing_breakpoint = file_breakpoints
+            .positions
+            .iter()
+            .position(|position| position.to_point(buffer).row == row);
+        if let Some(ix) = existing_breakpoint {
+            file_breakpoints.positions.remove(ix);
+        } else {
+            let point = buffer.clip_point(Point::new(row, 0), Bias::Left);
+            file_breakpoints.positions.push(buffer.anchor_before(point));
+        }
+        if file_breakpoints.positions.is_empty() {
+            self.breakpoints.files.remove(&project_path);
+        }
+        cx.emit(Event::BreakpointsChanged(project_path));
+    }
+
+    /// The rows of the breakpoints in the given buffer, in ascending order.
+    pub fn breakpoint_rows(&self, buffer: &Model<Buffer>, cx: &AppContext) -> Vec<u32> {
+        let buffer = buffer.read(cx);
+        project_path_for_buffer(buffer, cx)
+            .and_then(|project_path| self.breakpoints.files.get(&project_path))
+            .map(|file_breakpoints| rows(&file_breakpoints.positions, buffer))
+            .unwrap_or_default()
+    }
+
+    /// The zero-based rows of the breakpoints in every file of the project, keyed by the
+    /// absolute path of the file.
+    pub fn all_breakpoints(&self, cx: &AppContext) -> HashMap<PathBuf, Vec<u32>> {
+        self.breakpoints
+            .files
+            .iter()
+            .filter_map(|(project_path, file_breakpoints)| {
+                let worktree = self.worktree_for_id(project_path.worktree_id, cx)?;
+                let abs_path = worktree.read(cx).absolutize(&project_path.path).log_err()?;
+                let rows = match file_breakpoints
+                    .buffer
+                    .as_ref()
+                    .and_then(|buffer| buffer.upgrade())
+                {
+                    Some(buffer) => rows(&file_breakpoints.positions, buffer.read(cx)),
+                    None => file_breakpoints.rows.clone(),
+                };
+                Some((abs_path, rows))
+            })
+            .collect()
+    }
+}
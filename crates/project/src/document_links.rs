@@ -0,0 +1,220 @@
+use std::{ops::Range, sync::Arc};
+
+use anyhow::{anyhow, Context as _, Result};
+use client::{proto, Client, TypedEnvelope};
+use gpui::{AsyncAppContext, Model, ModelContext, Task};
+use language::{
+    proto::{deserialize_anchor, serialize_anchor},
+    Anchor, Bias, Buffer, Point,
+};
+use lsp::LanguageServerId;
+use text::BufferId;
+
+use crate::{Location, Project};
+
+/// A range of a buffer linking to a file or a URL, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct DocumentLink {
+    /// The id of the language server that reported this link.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer the link spans.
+    pub range: Range<Anchor>,
+    /// The raw link provided by the language server, whose target may be unresolved.
+    pub lsp_link: lsp::DocumentLink,
+}
+
+/// What a [`DocumentLink`] points at, once resolved.
+#[derive(Clone, Debug)]
+pub enum DocumentLinkTarget {
+    Url(String),
+    Location(Location),
+}
+
+impl Project {
+    /// Resolves the target of the given link, asking its language server for it when the link
+    /// was reported without one. Links to files open the target buffer, positioned at the line
+    /// and column in the link's fragment (e.g. `#L12,5`), if any.
+    pub fn open_document_link(
+        &mut self,
+        buffer: &Model<Buffer>,
+        link: &DocumentLink,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<DocumentLinkTarget>>> {
+        if self.is_local() {
+            let Some((adapter, server)) = self
+                .language_server_for_buffer(buffer.read(cx), link.server_id, cx)
+                .map(|(adapter, server)| (adapter.clone(), server.clone()))
+            else {
+                return Task::ready(Ok(None));
+            };
+            let lsp_link = link.lsp_link.clone();
+            cx.spawn(move |this, mut cx| async move {
+                let can_resolve = server
+                    .capabilities()
+                    .document_link_provider
+                    .as_ref()
+                    .and_then(|options| options.resolve_provider)
+                    .unwrap_or(false);
+                let lsp_link = if lsp_link.target.is_none() && can_resolve {
+                    server
+                        .request::<lsp::request::DocumentLinkResolve>(lsp_link)
+                        .await
+                        .context("document link resolve LSP request")?
+                } else {
+                    lsp_link
+                };
+                let Some(target) = lsp_link.target else {
+                    return Ok(None);
+                };
+                if !target.as_str().starts_with("file:") {
+                    return Ok(Some(DocumentLinkTarget::Url(target.as_str().to_string())));
+                }
+
+                let position = target_position(target.as_str());
+                let target_buffer = this
+                    .update(&mut cx, |this, cx| {
+                        this.open_local_buffer_via_lsp(
+                            target.into(),
+                            server.server_id(),
+                            adapter.name.clone(),
+                            cx,
+                        )
+                    })?
+                    .await?;
+                let range = target_buffer.update(&mut cx, |target_buffer, _| {
+                    let anchor =
+                        target_buffer.anchor_after(target_buffer.clip_point(position, Bias::Left));
+                    anchor..anchor
+                })?;
+                Ok(Some(DocumentLinkTarget::Location(Location {
+                    buffer: target_buffer,
+                    range,
+                })))
+            })
+        } else if let Some(project_id) = self.remote_id() {
+            let client = self.client.clone();
+            let request = proto::OpenDocumentLink {
+                project_id,
+                buffer_id: buffer.read(cx).remote_id().into(),
+                server_id: link.server_id.0 as u64,
+                lsp_link: serde_json::to_vec(&link.lsp_link).unwrap(),
+            };
+            cx.spawn(move |this, mut cx| async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("document link proto request")?;
+                if let Some(location) = response.location {
+                    let buffer_id = BufferId::new(location.buffer_id)?;
+                    let target_buffer = this
+                        .update(&mut cx, |this, cx| {
+                            this.wait_for_remote_buffer(buffer_id, cx)
+                        })?
+                        .await?;
+                    let start = location
+                        .start
+                        .and_then(deserialize_anchor)
+                        .ok_or_else(|| anyhow!("missing target start"))?;
+                    let end = location
+                        .end
+                        .and_then(deserialize_anchor)
+                        .ok_or_else(|| anyhow!("missing target end"))?;
+                    target_buffer
+                        .update(&mut cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+                        .await?;
+                    Ok(Some(DocumentLinkTarget::Location(Location {
+                        buffer: target_buffer,
+                        range: start..end,
+                    })))
+                } else {
+                    Ok(response.url.map(DocumentLinkTarget::Url))
+                }
+            })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    pub(crate) async fn handle_open_document_link(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::OpenDocumentLink>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::OpenDocumentLinkResponse> {
+        let peer_id = envelope.original_sender_id()?;
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, _| {
+            this.opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))
+        })??;
+        let link = DocumentLink {
+            server_id: LanguageServerId(envelope.payload.server_id as usize),
+            range: Anchor::MIN..Anchor::MIN,
+            lsp_link: serde_json::from_slice(&envelope.payload.lsp_link)?,
+        };
+
+        let target = this
+            .update(&mut cx, |this, cx| {
+                this.open_document_link(&buffer, &link, cx)
+            })?
+            .await?;
+        this.update(&mut cx, |this, cx| match target {
+            Some(DocumentLinkTarget::Url(url)) => proto::OpenDocumentLinkResponse {
+                url: Some(url),
+                location: None,
+            },
+            Some(DocumentLinkTarget::Location(location)) => proto::OpenDocumentLinkResponse {
+                url: None,
+                location: Some(proto::Location {
+                    buffer_id: this
+                        .create_buffer_for_peer(&location.buffer, peer_id, cx)
+                        .into(),
+                    start: Some(serialize_anchor(&location.range.start)),
+                    end: Some(serialize_anchor(&location.range.end)),
+                }),
+            },
+            None => proto::OpenDocumentLinkResponse {
+                url: None,
+                location: None,
+            },
+        })
+    }
+}
+
+/// Parses the position a link to a file points at from its fragment, following the common
+/// `#L<line>,<column>` convention where both are one-based and the `L` is optional.
+fn target_position(target: &str) -> Point {
+    let Some((_, fragment)) = target.split_once('#') else {
+        return Point::zero();
+    };
+    let mut components = fragment
+        .trim_start_matches('L')
+        .split(|c| c == ',' || c == ':')
+        .map(|component| component.parse::<u32>().ok());
+    match components.next().flatten() {
+        Some(line) => {
+            let column = components.next().flatten().unwrap_or(1);
+            Point::new(line.saturating_sub(1), column.saturating_sub(1))
+        }
+        None => Point::zero(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_position() {
+        assert_eq!(target_position("file:///a.rs"), Point::zero());
+        assert_eq!(target_position("file:///a.rs#L12"), Point::new(11, 0));
+        assert_eq!(target_position("file:///a.rs#L12,5"), Point::new(11, 4));
+        assert_eq!(target_position("file:///a.rs#12:5"), Point::new(11, 4));
+        assert_eq!(
+            target_position("file:///a.json#/definitions/a"),
+            Point::zero()
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Result;
+use futures::{future::join_all, select_biased, Future, FutureExt as _};
+use globset::GlobBuilder;
+use gpui::{AsyncAppContext, Model, ModelContext, Task};
+use language::CachedLspAdapter;
+use lsp::{LanguageServer, Uri};
+use util::ResultExt;
+
+use crate::{Project, ProjectEntryId, ProjectPath, WorktreeId};
+
+/// How long a rename, creation or deletion of files waits for the language servers to compute
+/// the edits that should accompany it.
+const WILL_FILE_OPERATION_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A change to the files of a local worktree, which language servers may want to react to,
+/// e.g. by updating the imports of a renamed module.
+#[derive(Clone, Debug)]
+pub(crate) struct FileOperation {
+    worktree_id: WorktreeId,
+    kind: FileOperationKind,
+    is_dir: bool,
+}
+
+#[derive(Clone, Debug)]
+enum FileOperationKind {
+    Create(PathBuf),
+    Rename { old: PathBuf, new: PathBuf },
+    Delete(PathBuf),
+}
+
+impl FileOperation {
+    /// The filters of the given server for this kind of operation, for the will- or
+    /// did-notification respectively.
+    fn filters<'a>(
+        &self,
+        server: &'a LanguageServer,
+        will: bool,
+    ) -> Option<&'a [lsp::FileOperationFilter]> {
+        let file_operations = server
+            .capabilities()
+            .workspace
+            .as_ref()?
+            .file_operations
+            .as_ref()?;
+        let options = match (&self.kind, will) {
+            (FileOperationKind::Create(_), true) => file_operations.will_create.as_ref(),
+            (FileOperationKind::Create(_), false) => file_operations.did_create.as_ref(),
+            (FileOperationKind::Rename { .. }, true) => file_operations.will_rename.as_ref(),
+            (FileOperationKind::Rename { .. }, false) => file_operations.did_rename.as_ref(),
+            (FileOperationKind::Delete(_), true) => file_operations.will_delete.as_ref(),
+            (FileOperationKind::Delete(_), false) => file_operations.did_delete.as_ref(),
+        }?;
+        Some(&options.filters)
+    }
+
+    /// Whether the server registered interest in this operation through one of its filters.
+    fn is_of_interest(&self, server: &LanguageServer, will: bool) -> bool {
+        let path = match &self.kind {
+            FileOperationKind::Create(path) | FileOperationKind::Delete(path) => path,
+            FileOperationKind::Rename { old, .. } => old,
+        };
+        self.filters(server, will)
+            .unwrap_or_default()
+            .iter()
+            .any(|filter| filter_matches(filter, path, self.is_dir))
+    }
+}
+
+fn filter_matches(filter: &lsp::FileOperationFilter, path: &Path, is_dir: bool) -> bool {
+    if filter
+        .scheme
+        .as_ref()
+        .map_or(false, |scheme| scheme != "file")
+    {
+        return false;
+    }
+    let pattern = &filter.pattern;
+    match pattern.matches {
+        Some(lsp::FileOperationPatternKind::File) if is_dir => return false,
+        Some(lsp::FileOperationPatternKind::Folder) if !is_dir => return false,
+        _ => {}
+    }
+    let ignore_case = pattern
+        .options
+        .as_ref()
+        .and_then(|options| options.ignore_case)
+        .unwrap_or(false);
+    GlobBuilder::new(&pattern.glob)
+        .case_insensitive(ignore_case)
+        .literal_separator(true)
+        .build()
+        .log_err()
+        .map_or(false, |glob| glob.compile_matcher().is_match(path))
+}
+
+fn file_uri(path: &Path) -> Option<String> {
+    let uri: lsp::RawUri = Uri::from_file_path(path).log_err()?.into();
+    Some(uri.as_str().to_string())
+}
+
+impl Project {
+    pub(crate) fn file_operation_for_create(
+        &self,
+        project_path: &ProjectPath,
+        is_dir: bool,
+        cx: &ModelContext<Self>,
+    ) -> Option<FileOperation> {
+        let worktree = self.worktree_for_id(project_path.worktree_id, cx)?;
+        let worktree = worktree.read(cx);
+        if !worktree.is_local() {
+            return None;
+        }
+        Some(FileOperation {
+            worktree_id: worktree.id(),
+            kind: FileOperationKind::Create(worktree.absolutize(&project_path.path).log_err()?),
+            is_dir,
+        })
+    }
+
+    pub(crate) fn file_operation_for_rename(
+        &self,
+        entry_id: ProjectEntryId,
+        new_path: &Path,
+        cx: &ModelContext<Self>,
+    ) -> Option<FileOperation> {
+        let worktree = self.worktree_for_entry(entry_id, cx)?;
+        let worktree = worktree.read(cx);
+        if !worktree.is_local() {
+            return None;
+        }
+        let entry = worktree.entry_for_id(entry_id)?;
+        Some(FileOperation {
+            worktree_id: worktree.id(),
+            kind: FileOperationKind::Rename {
+                old: worktree.absolutize(&entry.path).log_err()?,
+                new: worktree.absolutize(new_path).log_err()?,
+            },
+            is_dir: entry.is_dir(),
+        })
+    }
+
+    pub(crate) fn file_operation_for_delete(
+        &self,
+        entry_id: ProjectEntryId,
+        cx: &ModelContext<Self>,
+    ) -> Option<FileOperation> {
+        let worktree = self.worktree_for_entry(entry_id, cx)?;
+        let worktree = worktree.read(cx);
+        if !worktree.is_local() {
+            return None;
+        }
+        let entry = worktree.entry_for_id(entry_id)?;
+        Some(FileOperation {
+            worktree_id: worktree.id(),
+            kind: FileOperationKind::Delete(worktree.absolutize(&entry.path).log_err()?),
+            is_dir: entry.is_dir(),
+        })
+    }
+
+    /// Sends the `workspace/willCreateFiles`, `workspace/willRenameFiles` or
+    /// `workspace/willDeleteFiles` request for the operation to every language server of its
+    /// worktree that is interested in it, applying the workspace edits they respond with.
+    pub(crate) fn will_perform_file_operation(
+        &mut self,
+        operation: &FileOperation,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<()> {
+        let servers = self.file_operation_servers(operation, true);
+        if servers.is_empty() {
+            return Task::ready(());
+        }
+
+        let operation = operation.clone();
+        cx.spawn(move |this, mut cx| async move {
+            let requests = servers.into_iter().map(|(adapter, server)| {
+                let request = match &operation.kind {
+                    FileOperationKind::Create(path) => file_uri(path).map(|uri| {
+                        server
+                            .request::<lsp::request::WillCreateFiles>(lsp::CreateFilesParams {
+                                files: vec![lsp::FileCreate { uri }],
+                            })
+                            .boxed_local()
+                    }),
+                    FileOperationKind::Rename { old, new } => {
+                        file_uri(old).zip(file_uri(new)).map(|(old_uri, new_uri)| {
+                            server
+                                .request::<lsp::request::WillRenameFiles>(lsp::RenameFilesParams {
+                                    files: vec![lsp::FileRename { old_uri, new_uri }],
+                                })
+                                .boxed_local()
+                        })
+                    }
+                    FileOperationKind::Delete(path) => file_uri(path).map(|uri| {
+                        server
+                            .request::<lsp::request::WillDeleteFiles>(lsp::DeleteFilesParams {
+                                files: vec![lsp::FileDelete { uri }],
+                            })
+                            .boxed_local()
+                    }),
+                };
+                let mut timeout = cx
+                    .background_executor()
+                    .timer(WILL_FILE_OPERATION_TIMEOUT)
+                    .fuse();
+                async move {
+                    let mut request = request?.fuse();
+                    let edit = select_biased! {
+                        edit = request => edit.log_err().flatten(),
+                        _ = timeout => {
+                            log::warn!(
+                                "timed out waiting for {} to respond to a file operation",
+                                server.name()
+                            );
+                            None
+                        }
+                    };
+                    Some((adapter, server, edit?))
+                }
+            });
+            let edits = join_all(requests).await;
+
+            let Some(this) = this.upgrade() else {
+                return;
+            };
+            for (adapter, server, edit) in edits.into_iter().flatten() {
+                Self::deserialize_workspace_edit(
+                    this.clone(),
+                    edit,
+                    true,
+                    adapter,
+                    server,
+                    &mut cx,
+                )
+                .await
+                .log_err();
+            }
+        })
+    }
+
+    /// Notifies every language server of the operation's worktree that is interested in it
+    /// that it was performed.
+    pub(crate) fn did_perform_file_operation(&mut self, operation: &FileOperation) {
+        for (_, server) in self.file_operation_servers(operation, false) {
+            match &operation.kind {
+                FileOperationKind::Create(path) => {
+                    if let Some(uri) = file_uri(path) {
+                        server
+                            .notify::<lsp::notification::DidCreateFiles>(lsp::CreateFilesParams {
+                                files: vec![lsp::FileCreate { uri }],
+                            })
+                            .log_err();
+                    }
+                }
+                FileOperationKind::Rename { old, new } => {
+                    if let Some((old_uri, new_uri)) = file_uri(old).zip(file_uri(new)) {
+                        server
+                            .notify::<lsp::notification::DidRenameFiles>(lsp::RenameFilesParams {
+                                files: vec![lsp::FileRename { old_uri, new_uri }],
+                            })
+                            .log_err();
+                    }
+                }
+                FileOperationKind::Delete(path) => {
+                    if let Some(uri) = file_uri(path) {
+                        server
+                            .notify::<lsp::notification::DidDeleteFiles>(lsp::DeleteFilesParams {
+                                files: vec![lsp::FileDelete { uri }],
+                            })
+                            .log_err();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Performs an operation requested by a guest, letting the language servers react to it
+    /// like they do for the operations of the host.
+    pub(crate) async fn perform_file_operation<T>(
+        this: Model<Self>,
+        operation: Option<FileOperation>,
+        perform: impl Future<Output = Result<T>>,
+        mut cx: AsyncAppContext,
+    ) -> Result<T> {
+        let Some(operation) = operation else {
+            return perform.await;
+        };
+        this.update(&mut cx, |this, cx| {
+            this.will_perform_file_operation(&operation, cx)
+        })?
+        .await;
+        let result = perform.await?;
+        this.update(&mut cx, |this, _| {
+            this.did_perform_file_operation(&operation)
+        })?;
+        Ok(result)
+    }
+
+    fn file_operation_servers(
+        &self,
+        operation: &FileOperation,
+        will: bool,
+    ) -> Vec<(Arc<CachedLspAdapter>, Arc<LanguageServer>)> {
+        let mut servers = Vec::<(Arc<CachedLspAdapter>, Arc<LanguageServer>)>::new();
+        for (adapter, _, server) in self.language_servers_for_worktree(operation.worktree_id) {
+            if operation.is_of_interest(server, will)
+                && !servers
+                    .iter()
+                    .any(|(_, other)| other.server_id() == server.server_id())
+            {
+                servers.push((adapter.clone(), server.clone()));
+            }
+        }
+        servers
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{
-    CodeAction, CoreCompletion, DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint,
+    CallHierarchyCall, CallHierarchyItem, CodeAction, CodeLens, ColorPresentation, CoreCompletion,
+    DocumentColor, DocumentHighlight, DocumentLink, Hover, HoverBlock, HoverBlockKind, InlayHint,
     InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location,
-    LocationLink, MarkupContent, Project, ProjectTransaction, ResolveState,
+    LocationLink, MarkupContent, ParameterInformation, Project, ProjectTransaction, ResolveState,
+    SignatureHelp, SignatureInformation, TypeHierarchyItem,
 };
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -13,14 +15,14 @@ use language::{
     point_from_lsp, point_to_lsp,
     proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
     range_from_lsp, range_to_lsp, Anchor, Bias, Buffer, BufferSnapshot, CachedLspAdapter, CharKind,
-    OffsetRangeExt, PointUtf16, ToOffset, ToPointUtf16, Transaction, Unclipped,
+    OffsetRangeExt, Point, PointUtf16, ToOffset, ToPointUtf16, Transaction, Unclipped,
 };
 use lsp::{
     CompletionContext, CompletionListItemDefaultsEditRange, CompletionTriggerKind,
     DocumentHighlightKind, LanguageServer, LanguageServerId, LinkedEditingRangeServerCapabilities,
     OneOf, ServerCapabilities,
 };
-use std::{cmp::Reverse, ops::Range, path::Path, sync::Arc};
+use std::{cmp::Reverse, iter, mem, ops::Range, path::Path, sync::Arc};
 use text::{BufferId, LineEnding};
 
 pub fn lsp_formatting_options(tab_size: u32) -> lsp::FormattingOptions {
@@ -164,6 +166,52 @@ pub(crate) struct LinkedEditingRange {
     pub position: Anchor,
 }
 
+pub(crate) struct GetSignatureHelp {
+    pub position: PointUtf16,
+    pub context: lsp::SignatureHelpContext,
+}
+
+pub(crate) struct PrepareCallHierarchy {
+    pub position: PointUtf16,
+}
+
+pub(crate) struct GetIncomingCalls {
+    pub item: CallHierarchyItem,
+}
+
+pub(crate) struct GetOutgoingCalls {
+    pub item: CallHierarchyItem,
+}
+
+pub(crate) struct PrepareTypeHierarchy {
+    pub position: PointUtf16,
+}
+
+pub(crate) struct GetSupertypes {
+    pub item: TypeHierarchyItem,
+}
+
+pub(crate) struct GetSubtypes {
+    pub item: TypeHierarchyItem,
+}
+
+pub(crate) struct GetCodeLens;
+
+pub(crate) struct GetFoldingRanges;
+
+pub(crate) struct GetDocumentColors;
+
+pub(crate) struct GetDocumentLinks;
+
+pub(crate) struct GetSelectionRanges {
+    pub positions: Vec<Anchor>,
+}
+
+pub(crate) struct GetColorPresentations {
+    pub range: Range<Anchor>,
+    pub color: lsp::Color,
+}
+
 #[async_trait(?Send)]
 impl LspCommand for PrepareRename {
     type Response = Option<Range<Anchor>>;
@@ -2719,3 +2767,2116 @@ impl LspCommand for LinkedEditingRange {
         BufferId::new(message.buffer_id)
     }
 }
+
+#[async_trait(?Send)]
+impl LspCommand for GetSignatureHelp {
+    type Response = Option<SignatureHelp>;
+    type LspRequest = lsp::request::SignatureHelpRequest;
+    type ProtoRequest = proto::GetSignatureHelp;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        let Some(options) = &capabilities.signature_help_provider else {
+            return false;
+        };
+        // Servers only expect to be queried for the characters they have registered.
+        match &self.context.trigger_character {
+            Some(trigger)
+                if self.context.trigger_kind
+                    == lsp::SignatureHelpTriggerKind::TRIGGER_CHARACTER =>
+            {
+                let is_trigger = options
+                    .trigger_characters
+                    .iter()
+                    .flatten()
+                    .any(|character| character == trigger);
+                let is_retrigger = self.context.is_retrigger
+                    && options
+                        .retrigger_characters
+                        .iter()
+                        .flatten()
+                        .any(|character| character == trigger);
+                is_trigger || is_retrigger
+            }
+            _ => true,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::SignatureHelpParams {
+        lsp::SignatureHelpParams {
+            context: Some(self.context.clone()),
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Uri::from_file_path(path).unwrap().into(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::SignatureHelp>,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: LanguageServerId,
+        _: AsyncAppContext,
+    ) -> Result<Self::Response> {
+        let Some(help) = message else {
+            return Ok(None);
+        };
+
+        let signatures = help
+            .signatures
+            .into_iter()
+            .map(|signature| {
+                let mut search_start = signature.label.find('(').unwrap_or(0);
+                let parameters = signature
+                    .parameters
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|parameter| {
+                        let label_range = parameter_label_range(
+                            &signature.label,
+                            &parameter.label,
+                            search_start,
+                        )?;
+                        search_start = label_range.end;
+                        Some(ParameterInformation {
+                            label_range,
+                            documentation: parameter.documentation.and_then(hover_block_from_lsp),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let active_parameter = signature
+                    .active_parameter
+                    .or(help.active_parameter)
+                    .map(|index| index as usize)
+                    .filter(|index| *index < parameters.len());
+                SignatureInformation {
+                    label: signature.label,
+                    documentation: signature.documentation.and_then(hover_block_from_lsp),
+                    parameters,
+                    active_parameter,
+                }
+            })
+            .collect::<Vec<_>>();
+        if signatures.is_empty() {
+            return Ok(None);
+        }
+
+        let active_signature = help
+            .active_signature
+            .map(|index| index as usize)
+            .filter(|index| *index < signatures.len())
+            .unwrap_or(0);
+        Ok(Some(SignatureHelp {
+            signatures,
+            active_signature,
+        }))
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSignatureHelp {
+        let trigger_kind = match self.context.trigger_kind {
+            lsp::SignatureHelpTriggerKind::TRIGGER_CHARACTER => {
+                proto::SignatureHelpTriggerKind::TriggerCharacter
+            }
+            lsp::SignatureHelpTriggerKind::CONTENT_CHANGE => {
+                proto::SignatureHelpTriggerKind::ContentChange
+            }
+            _ => proto::SignatureHelpTriggerKind::Invoked,
+        };
+        proto::GetSignatureHelp {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            version: serialize_version(&buffer.version()),
+            trigger_kind: trigger_kind.into(),
+            trigger_character: self.context.trigger_character.clone(),
+            is_retrigger: self.context.is_retrigger,
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSignatureHelp,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let trigger_kind = match proto::SignatureHelpTriggerKind::from_i32(message.trigger_kind) {
+            Some(proto::SignatureHelpTriggerKind::TriggerCharacter) => {
+                lsp::SignatureHelpTriggerKind::TRIGGER_CHARACTER
+            }
+            Some(proto::SignatureHelpTriggerKind::ContentChange) => {
+                lsp::SignatureHelpTriggerKind::CONTENT_CHANGE
+            }
+            Some(proto::SignatureHelpTriggerKind::Invoked) | None => {
+                lsp::SignatureHelpTriggerKind::INVOKED
+            }
+        };
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+            context: lsp::SignatureHelpContext {
+                trigger_kind,
+                trigger_character: message.trigger_character,
+                is_retrigger: message.is_retrigger,
+                active_signature_help: None,
+            },
+        })
+    }
+
+    fn response_to_proto(
+        response: Option<SignatureHelp>,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetSignatureHelpResponse {
+        proto::GetSignatureHelpResponse {
+            signature_help: response.map(|help| proto::SignatureHelp {
+                signatures: help
+                    .signatures
+                    .into_iter()
+                    .map(|signature| proto::SignatureInformation {
+                        label: signature.label,
+                        documentation: signature.documentation.map(serialize_hover_block),
+                        parameters: signature
+                            .parameters
+                            .into_iter()
+                            .map(|parameter| proto::ParameterInformation {
+                                label_start: parameter.label_range.start as u32,
+                                label_end: parameter.label_range.end as u32,
+                                documentation: parameter.documentation.map(serialize_hover_block),
+                            })
+                            .collect(),
+                        active_parameter: signature.active_parameter.map(|index| index as u32),
+                    })
+                    .collect(),
+                active_signature: help.active_signature as u32,
+            }),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSignatureHelpResponse,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self::Response> {
+        let Some(help) = message.signature_help else {
+            return Ok(None);
+        };
+        let signatures = help
+            .signatures
+            .into_iter()
+            .map(|signature| {
+                let parameters = signature
+                    .parameters
+                    .into_iter()
+                    .filter_map(|parameter| {
+                        let label_range =
+                            parameter.label_start as usize..parameter.label_end as usize;
+                        // Ranges that do not point into the label would panic when rendered.
+                        signature.label.get(label_range.clone())?;
+                        Some(ParameterInformation {
+                            label_range,
+                            documentation: parameter.documentation.map(deserialize_hover_block),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let active_parameter = signature
+                    .active_parameter
+                    .map(|index| index as usize)
+                    .filter(|index| *index < parameters.len());
+                SignatureInformation {
+                    label: signature.label,
+                    documentation: signature.documentation.map(deserialize_hover_block),
+                    parameters,
+                    active_parameter,
+                }
+            })
+            .collect::<Vec<_>>();
+        if signatures.is_empty() {
+            return Ok(None);
+        }
+
+        let active_signature = (help.active_signature as usize).min(signatures.len() - 1);
+        Ok(Some(SignatureHelp {
+            signatures,
+            active_signature,
+        }))
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSignatureHelp) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+/// Converts an LSP parameter label into a byte range inside of the signature label.
+///
+/// Labels given as strings are searched for starting at `search_start`, so that parameters
+/// sharing a name with the function (or with each other) resolve to the right occurrence.
+fn parameter_label_range(
+    signature_label: &str,
+    parameter_label: &lsp::ParameterLabel,
+    search_start: usize,
+) -> Option<Range<usize>> {
+    match parameter_label {
+        lsp::ParameterLabel::Simple(label) => {
+            let start = signature_label
+                .get(search_start..)
+                .and_then(|rest| rest.find(label.as_str()))
+                .map(|offset| search_start + offset)
+                .or_else(|| signature_label.find(label.as_str()))?;
+            Some(start..start + label.len())
+        }
+        lsp::ParameterLabel::LabelOffsets([start, end]) => {
+            let mut utf16_offset = 0;
+            let mut start_offset = None;
+            let mut end_offset = None;
+            for (offset, character) in signature_label
+                .char_indices()
+                .chain(Some((signature_label.len(), '\0')))
+            {
+                if utf16_offset == *start as usize {
+                    start_offset = Some(offset);
+                }
+                if utf16_offset == *end as usize {
+                    end_offset = Some(offset);
+                    break;
+                }
+                utf16_offset += character.len_utf16();
+            }
+            let (start, end) = (start_offset?, end_offset?);
+            (start <= end).then_some(start..end)
+        }
+    }
+}
+
+fn hover_block_from_lsp(documentation: lsp::Documentation) -> Option<HoverBlock> {
+    let block = match documentation {
+        lsp::Documentation::String(text) => HoverBlock {
+            text,
+            kind: HoverBlockKind::PlainText,
+        },
+        lsp::Documentation::MarkupContent(markup_content) => HoverBlock {
+            text: markup_content.value,
+            kind: match markup_content.kind {
+                lsp::MarkupKind::Markdown => HoverBlockKind::Markdown,
+                lsp::MarkupKind::PlainText => HoverBlockKind::PlainText,
+            },
+        },
+    };
+    if block.text.trim().is_empty() {
+        None
+    } else {
+        Some(block)
+    }
+}
+
+fn serialize_hover_block(block: HoverBlock) -> proto::HoverBlock {
+    proto::HoverBlock {
+        text: block.text,
+        is_markdown: block.kind == HoverBlockKind::Markdown,
+        language: if let HoverBlockKind::Code { language } = block.kind {
+            Some(language)
+        } else {
+            None
+        },
+    }
+}
+
+fn deserialize_hover_block(block: proto::HoverBlock) -> HoverBlock {
+    HoverBlock {
+        text: block.text,
+        kind: if let Some(language) = block.language {
+            HoverBlockKind::Code { language }
+        } else if block.is_markdown {
+            HoverBlockKind::Markdown
+        } else {
+            HoverBlockKind::PlainText
+        },
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareCallHierarchy {
+    type Response = Vec<CallHierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyPrepare;
+    type ProtoRequest = proto::PrepareCallHierarchy;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        supports_call_hierarchy(capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyPrepareParams {
+        lsp::CallHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Uri::from_file_path(path).unwrap().into(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_items: Option<Vec<lsp::CallHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        let (lsp_adapter, language_server) =
+            language_server_for_buffer(&project, &buffer, server_id, &mut cx)?;
+        let mut items = Vec::new();
+        for lsp_item in lsp_items.unwrap_or_default() {
+            items.push(
+                call_hierarchy_item_from_lsp(
+                    lsp_item,
+                    &project,
+                    &lsp_adapter,
+                    &language_server,
+                    &mut cx,
+                )
+                .await?,
+            );
+        }
+        Ok(items)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareCallHierarchy {
+        proto::PrepareCallHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareCallHierarchy,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::PrepareCallHierarchyResponse {
+        let items = response
+            .iter()
+            .map(|item| {
+                let buffer_id = project.create_buffer_for_peer(&item.location.buffer, peer_id, cx);
+                serialize_call_hierarchy_item(item, buffer_id)
+            })
+            .collect();
+        proto::PrepareCallHierarchyResponse { items }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::PrepareCallHierarchyResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        let mut items = Vec::new();
+        for item in message.items {
+            items.push(deserialize_call_hierarchy_item(item, &project, &mut cx).await?);
+        }
+        Ok(items)
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareCallHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetIncomingCalls {
+    type Response = Vec<CallHierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyIncomingCalls;
+    type ProtoRequest = proto::GetIncomingCalls;
+
+    fn status(&self) -> Option<String> {
+        Some("Finding incoming calls...".to_owned())
+    }
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        supports_call_hierarchy(capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyIncomingCallsParams {
+        lsp::CallHierarchyIncomingCallsParams {
+            item: self.item.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_calls: Option<Vec<lsp::CallHierarchyIncomingCall>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        let (lsp_adapter, language_server) =
+            language_server_for_buffer(&project, &buffer, server_id, &mut cx)?;
+        let mut calls = Vec::new();
+        for lsp_call in lsp_calls.unwrap_or_default() {
+            let item = call_hierarchy_item_from_lsp(
+                lsp_call.from,
+                &project,
+                &lsp_adapter,
+                &language_server,
+                &mut cx,
+            )
+            .await?;
+            // The calls are made from within the caller.
+            let call_sites = item.location.buffer.update(&mut cx, |caller_buffer, _| {
+                lsp_call
+                    .from_ranges
+                    .into_iter()
+                    .map(|range| Location {
+                        buffer: item.location.buffer.clone(),
+                        range: anchor_range_from_lsp(caller_buffer, range),
+                    })
+                    .collect::<Vec<_>>()
+            })?;
+            calls.push(CallHierarchyCall { item, call_sites });
+        }
+        Ok(calls)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetIncomingCalls {
+        proto::GetIncomingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            item: Some(serialize_call_hierarchy_item(
+                &self.item,
+                buffer.remote_id(),
+            )),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetIncomingCalls,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let item = message.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(Self {
+            item: deserialize_call_hierarchy_item(item, &project, &mut cx).await?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyCall>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetIncomingCallsResponse {
+        proto::GetIncomingCallsResponse {
+            calls: serialize_call_hierarchy_calls(response, project, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetIncomingCallsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        deserialize_call_hierarchy_calls(message.calls, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetIncomingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetOutgoingCalls {
+    type Response = Vec<CallHierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyOutgoingCalls;
+    type ProtoRequest = proto::GetOutgoingCalls;
+
+    fn status(&self) -> Option<String> {
+        Some("Finding outgoing calls...".to_owned())
+    }
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        supports_call_hierarchy(capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyOutgoingCallsParams {
+        lsp::CallHierarchyOutgoingCallsParams {
+            item: self.item.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_calls: Option<Vec<lsp::CallHierarchyOutgoingCall>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        let (lsp_adapter, language_server) =
+            language_server_for_buffer(&project, &buffer, server_id, &mut cx)?;
+        let mut calls = Vec::new();
+        for lsp_call in lsp_calls.unwrap_or_default() {
+            // The calls are made from within the item the request was made for.
+            let call_sites = buffer.update(&mut cx, |caller_buffer, _| {
+                lsp_call
+                    .from_ranges
+                    .iter()
+                    .map(|range| Location {
+                        buffer: buffer.clone(),
+                        range: anchor_range_from_lsp(caller_buffer, *range),
+                    })
+                    .collect::<Vec<_>>()
+            })?;
+            let item = call_hierarchy_item_from_lsp(
+                lsp_call.to,
+                &project,
+                &lsp_adapter,
+                &language_server,
+                &mut cx,
+            )
+            .await?;
+            calls.push(CallHierarchyCall { item, call_sites });
+        }
+        Ok(calls)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetOutgoingCalls {
+        proto::GetOutgoingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            item: Some(serialize_call_hierarchy_item(
+                &self.item,
+                buffer.remote_id(),
+            )),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetOutgoingCalls,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let item = message.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(Self {
+            item: deserialize_call_hierarchy_item(item, &project, &mut cx).await?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyCall>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetOutgoingCallsResponse {
+        proto::GetOutgoingCallsResponse {
+            calls: serialize_call_hierarchy_calls(response, project, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetOutgoingCallsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        deserialize_call_hierarchy_calls(message.calls, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetOutgoingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+// `ServerCapabilities` has no `typeHierarchyProvider` field, so the type hierarchy
+// commands keep the default `check_capabilities` and are sent to every server they target.
+#[async_trait(?Send)]
+impl LspCommand for PrepareTypeHierarchy {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchyPrepare;
+    type ProtoRequest = proto::PrepareTypeHierarchy;
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchyPrepareParams {
+        lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Uri::from_file_path(path).unwrap().into(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_items: Option<Vec<lsp::TypeHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_lsp(lsp_items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareTypeHierarchy {
+        proto::PrepareTypeHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareTypeHierarchy,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::PrepareTypeHierarchyResponse {
+        proto::PrepareTypeHierarchyResponse {
+            items: serialize_type_hierarchy_items(response, project, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::PrepareTypeHierarchyResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        let mut items = Vec::new();
+        for item in message.items {
+            items.push(deserialize_type_hierarchy_item(item, &project, &mut cx).await?);
+        }
+        Ok(items)
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareTypeHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSupertypes {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySupertypes;
+    type ProtoRequest = proto::GetSupertypes;
+
+    fn status(&self) -> Option<String> {
+        Some("Finding supertypes...".to_owned())
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchySupertypesParams {
+        lsp::TypeHierarchySupertypesParams {
+            item: self.item.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_items: Option<Vec<lsp::TypeHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_lsp(lsp_items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSupertypes {
+        proto::GetSupertypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            item: Some(serialize_type_hierarchy_item(
+                &self.item,
+                buffer.remote_id(),
+            )),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSupertypes,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let item = message.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(Self {
+            item: deserialize_type_hierarchy_item(item, &project, &mut cx).await?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetSupertypesResponse {
+        proto::GetSupertypesResponse {
+            items: serialize_type_hierarchy_items(response, project, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSupertypesResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        let mut items = Vec::new();
+        for item in message.items {
+            items.push(deserialize_type_hierarchy_item(item, &project, &mut cx).await?);
+        }
+        Ok(items)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSupertypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSubtypes {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySubtypes;
+    type ProtoRequest = proto::GetSubtypes;
+
+    fn status(&self) -> Option<String> {
+        Some("Finding subtypes...".to_owned())
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchySubtypesParams {
+        lsp::TypeHierarchySubtypesParams {
+            item: self.item.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_items: Option<Vec<lsp::TypeHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_lsp(lsp_items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSubtypes {
+        proto::GetSubtypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            item: Some(serialize_type_hierarchy_item(
+                &self.item,
+                buffer.remote_id(),
+            )),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSubtypes,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let item = message.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(Self {
+            item: deserialize_type_hierarchy_item(item, &project, &mut cx).await?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetSubtypesResponse {
+        proto::GetSubtypesResponse {
+            items: serialize_type_hierarchy_items(response, project, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSubtypesResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        let mut items = Vec::new();
+        for item in message.items {
+            items.push(deserialize_type_hierarchy_item(item, &project, &mut cx).await?);
+        }
+        Ok(items)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSubtypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetCodeLens {
+    type Response = Vec<CodeLens>;
+    type LspRequest = lsp::request::CodeLensRequest;
+    type ProtoRequest = proto::GetCodeLens;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        capabilities.code_lens_provider.is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CodeLensParams {
+        lsp::CodeLensParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Uri::from_file_path(path).unwrap().into(),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_lenses: Option<Vec<lsp::CodeLens>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        let (_, language_server) =
+            language_server_for_buffer(&project, &buffer, server_id, &mut cx)?;
+        let can_resolve = GetCodeLens::can_resolve_lenses(language_server.capabilities());
+        // Servers may omit the commands of the lenses, which are cheap to list but
+        // expensive to compute (e.g. reference counts), until they are resolved.
+        let lsp_lenses =
+            future::join_all(lsp_lenses.unwrap_or_default().into_iter().map(|lsp_lens| {
+                let language_server = language_server.clone();
+                async move {
+                    if lsp_lens.command.is_some() || !can_resolve {
+                        return lsp_lens;
+                    }
+                    match language_server
+                        .request::<lsp::request::CodeLensResolve>(lsp_lens.clone())
+                        .await
+                    {
+                        Ok(resolved_lens) => resolved_lens,
+                        Err(error) => {
+                            log::warn!("failed to resolve code lens: {error:#}");
+                            lsp_lens
+                        }
+                    }
+                }
+            }))
+            .await;
+        buffer.update(&mut cx, |buffer, _| {
+            lsp_lenses
+                .into_iter()
+                .map(|lsp_lens| CodeLens {
+                    server_id,
+                    range: anchor_range_from_lsp(buffer, lsp_lens.range),
+                    lsp_lens,
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetCodeLens {
+        proto::GetCodeLens {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetCodeLens,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        lenses: Vec<CodeLens>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetCodeLensResponse {
+        proto::GetCodeLensResponse {
+            lenses: lenses
+                .iter()
+                .map(|lens| proto::CodeLens {
+                    server_id: lens.server_id.0 as u64,
+                    start: Some(serialize_anchor(&lens.range.start)),
+                    end: Some(serialize_anchor(&lens.range.end)),
+                    lsp_lens: serde_json::to_vec(&lens.lsp_lens).unwrap(),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCodeLensResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .lenses
+            .into_iter()
+            .map(|lens| {
+                let start = lens
+                    .start
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid start"))?;
+                let end = lens
+                    .end
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid end"))?;
+                Ok(CodeLens {
+                    server_id: LanguageServerId(lens.server_id as usize),
+                    range: start..end,
+                    lsp_lens: serde_json::from_slice(&lens.lsp_lens)?,
+                })
+            })
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetCodeLens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl GetCodeLens {
+    pub fn can_resolve_lenses(capabilities: &ServerCapabilities) -> bool {
+        capabilities
+            .code_lens_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetFoldingRanges {
+    type Response = Vec<Range<Anchor>>;
+    type LspRequest = lsp::request::FoldingRangeRequest;
+    type ProtoRequest = proto::GetFoldingRanges;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        match &capabilities.folding_range_provider {
+            Some(lsp::FoldingRangeProviderCapability::Simple(has_support)) => *has_support,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::FoldingRangeParams {
+        lsp::FoldingRangeParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Uri::from_file_path(path).unwrap().into(),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_ranges: Option<Vec<lsp::FoldingRange>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<Range<Anchor>>> {
+        // Only whole lines are folded (see `line_folding_only` in the client capabilities):
+        // a range spans from the end of its start line to the end of its end line.
+        buffer.read_with(&cx, |buffer, _| {
+            let max_row = buffer.max_point().row;
+            let mut ranges = lsp_ranges
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|range| range.start_line < range.end_line && range.end_line <= max_row)
+                .map(|range| {
+                    let start = Point::new(range.start_line, buffer.line_len(range.start_line));
+                    let end = Point::new(range.end_line, buffer.line_len(range.end_line));
+                    buffer.anchor_after(start)..buffer.anchor_before(end)
+                })
+                .collect::<Vec<_>>();
+            ranges.sort_by(|a, b| a.start.cmp(&b.start, buffer));
+            ranges
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetFoldingRanges {
+        proto::GetFoldingRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetFoldingRanges,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        ranges: Vec<Range<Anchor>>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetFoldingRangesResponse {
+        proto::GetFoldingRangesResponse {
+            ranges: ranges
+                .into_iter()
+                .map(|range| proto::AnchorRange {
+                    start: Some(serialize_anchor(&range.start)),
+                    end: Some(serialize_anchor(&range.end)),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetFoldingRangesResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<Range<Anchor>>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .ranges
+            .into_iter()
+            .map(|range| {
+                let start = range
+                    .start
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid start"))?;
+                let end = range
+                    .end
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid end"))?;
+                Ok(start..end)
+            })
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetFoldingRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentColors {
+    type Response = Vec<DocumentColor>;
+    type LspRequest = lsp::request::DocumentColor;
+    type ProtoRequest = proto::GetDocumentColors;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        match &capabilities.color_provider {
+            Some(lsp::ColorProviderCapability::Simple(has_support)) => *has_support,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentColorParams {
+        lsp::DocumentColorParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Uri::from_file_path(path).unwrap().into(),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_colors: Vec<lsp::ColorInformation>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentColor>> {
+        buffer.read_with(&cx, |buffer, _| {
+            let mut colors = lsp_colors
+                .into_iter()
+                .map(|lsp_color| DocumentColor {
+                    server_id,
+                    range: anchor_range_from_lsp(buffer, lsp_color.range),
+                    color: lsp_color.color,
+                })
+                .collect::<Vec<_>>();
+            colors.sort_by(|a, b| a.range.start.cmp(&b.range.start, buffer));
+            colors
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentColors {
+        proto::GetDocumentColors {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentColors,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        colors: Vec<DocumentColor>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentColorsResponse {
+        proto::GetDocumentColorsResponse {
+            colors: colors
+                .into_iter()
+                .map(|color| proto::DocumentColor {
+                    server_id: color.server_id.0 as u64,
+                    start: Some(serialize_anchor(&color.range.start)),
+                    end: Some(serialize_anchor(&color.range.end)),
+                    color: Some(serialize_color(color.color)),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentColorsResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentColor>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .colors
+            .into_iter()
+            .map(|color| {
+                let start = color
+                    .start
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid start"))?;
+                let end = color
+                    .end
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid end"))?;
+                Ok(DocumentColor {
+                    server_id: LanguageServerId(color.server_id as usize),
+                    range: start..end,
+                    color: deserialize_color(color.color.context("missing color")?),
+                })
+            })
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentColors) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetColorPresentations {
+    type Response = Vec<ColorPresentation>;
+    type LspRequest = lsp::request::ColorPresentationRequest;
+    type ProtoRequest = proto::GetColorPresentations;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        GetDocumentColors.check_capabilities(capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::ColorPresentationParams {
+        lsp::ColorPresentationParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Uri::from_file_path(path).unwrap().into(),
+            ),
+            color: self.color,
+            range: range_to_lsp(self.range.to_point_utf16(buffer)),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_presentations: Vec<lsp::ColorPresentation>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<ColorPresentation>> {
+        // A presentation without a text edit replaces the color's range with its label.
+        buffer.read_with(&cx, |buffer, _| {
+            lsp_presentations
+                .into_iter()
+                .map(|presentation| {
+                    let primary_edit = match presentation.text_edit {
+                        Some(edit) => (anchor_range_from_lsp(buffer, edit.range), edit.new_text),
+                        None => (self.range.clone(), presentation.label.clone()),
+                    };
+                    let edits = iter::once(primary_edit)
+                        .chain(
+                            presentation
+                                .additional_text_edits
+                                .into_iter()
+                                .flatten()
+                                .map(|edit| {
+                                    (anchor_range_from_lsp(buffer, edit.range), edit.new_text)
+                                }),
+                        )
+                        .collect();
+                    ColorPresentation {
+                        label: presentation.label,
+                        edits,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetColorPresentations {
+        proto::GetColorPresentations {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            start: Some(serialize_anchor(&self.range.start)),
+            end: Some(serialize_anchor(&self.range.end)),
+            color: Some(serialize_color(self.color)),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetColorPresentations,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let start = message
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = message
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            range: start..end,
+            color: deserialize_color(message.color.context("missing color")?),
+        })
+    }
+
+    fn response_to_proto(
+        presentations: Vec<ColorPresentation>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetColorPresentationsResponse {
+        proto::GetColorPresentationsResponse {
+            presentations: presentations
+                .into_iter()
+                .map(|presentation| proto::ColorPresentation {
+                    label: presentation.label,
+                    edits: presentation
+                        .edits
+                        .into_iter()
+                        .map(|(range, new_text)| proto::ColorPresentationEdit {
+                            start: Some(serialize_anchor(&range.start)),
+                            end: Some(serialize_anchor(&range.end)),
+                            new_text,
+                        })
+                        .collect(),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetColorPresentationsResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<ColorPresentation>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .presentations
+            .into_iter()
+            .map(|presentation| {
+                let edits = presentation
+                    .edits
+                    .into_iter()
+                    .map(|edit| {
+                        let start = edit
+                            .start
+                            .and_then(deserialize_anchor)
+                            .ok_or_else(|| anyhow!("invalid start"))?;
+                        let end = edit
+                            .end
+                            .and_then(deserialize_anchor)
+                            .ok_or_else(|| anyhow!("invalid end"))?;
+                        Ok((start..end, edit.new_text))
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(ColorPresentation {
+                    label: presentation.label,
+                    edits,
+                })
+            })
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetColorPresentations) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentLinks {
+    type Response = Vec<DocumentLink>;
+    type LspRequest = lsp::request::DocumentLinkRequest;
+    type ProtoRequest = proto::GetDocumentLinks;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        capabilities.document_link_provider.is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentLinkParams {
+        lsp::DocumentLinkParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Uri::from_file_path(path).unwrap().into(),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_links: Option<Vec<lsp::DocumentLink>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer.read_with(&cx, |buffer, _| {
+            let mut links = lsp_links
+                .unwrap_or_default()
+                .into_iter()
+                .map(|lsp_link| DocumentLink {
+                    server_id,
+                    range: anchor_range_from_lsp(buffer, lsp_link.range),
+                    lsp_link,
+                })
+                .collect::<Vec<_>>();
+            links.sort_by(|a, b| a.range.start.cmp(&b.range.start, buffer));
+            links
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentLinks {
+        proto::GetDocumentLinks {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentLinks,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        links: Vec<DocumentLink>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentLinksResponse {
+        proto::GetDocumentLinksResponse {
+            links: links
+                .iter()
+                .map(|link| proto::DocumentLink {
+                    server_id: link.server_id.0 as u64,
+                    start: Some(serialize_anchor(&link.range.start)),
+                    end: Some(serialize_anchor(&link.range.end)),
+                    lsp_link: serde_json::to_vec(&link.lsp_link).unwrap(),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentLinksResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .links
+            .into_iter()
+            .map(|link| {
+                let start = link
+                    .start
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid start"))?;
+                let end = link
+                    .end
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid end"))?;
+                Ok(DocumentLink {
+                    server_id: LanguageServerId(link.server_id as usize),
+                    range: start..end,
+                    lsp_link: serde_json::from_slice(&link.lsp_link)?,
+                })
+            })
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentLinks) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSelectionRanges {
+    type Response = Vec<Vec<Range<Anchor>>>;
+    type LspRequest = lsp::request::SelectionRangeRequest;
+    type ProtoRequest = proto::GetSelectionRanges;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        match &capabilities.selection_range_provider {
+            Some(lsp::SelectionRangeProviderCapability::Simple(has_support)) => *has_support,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::SelectionRangeParams {
+        lsp::SelectionRangeParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Uri::from_file_path(path).unwrap().into(),
+            ),
+            positions: self
+                .positions
+                .iter()
+                .map(|position| point_to_lsp(position.to_point_utf16(buffer)))
+                .collect(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_ranges: Option<Vec<lsp::SelectionRange>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<Vec<Range<Anchor>>>> {
+        // Each position gets a chain of ranges, from the innermost to the outermost one.
+        buffer.read_with(&cx, |buffer, _| {
+            let mut lsp_ranges = lsp_ranges.unwrap_or_default().into_iter();
+            self.positions
+                .iter()
+                .map(|_| {
+                    let mut ranges = Vec::new();
+                    let mut selection_range = lsp_ranges.next();
+                    while let Some(lsp::SelectionRange { range, parent }) = selection_range {
+                        ranges.push(anchor_range_from_lsp(buffer, range));
+                        selection_range = parent.map(|parent| *parent);
+                    }
+                    ranges
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSelectionRanges {
+        proto::GetSelectionRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            positions: self.positions.iter().map(serialize_anchor).collect(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSelectionRanges,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let positions = message
+            .positions
+            .into_iter()
+            .map(|position| deserialize_anchor(position).ok_or_else(|| anyhow!("invalid position")))
+            .collect::<Result<Vec<_>>>()?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self { positions })
+    }
+
+    fn response_to_proto(
+        selection_ranges: Vec<Vec<Range<Anchor>>>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetSelectionRangesResponse {
+        proto::GetSelectionRangesResponse {
+            selection_ranges: selection_ranges
+                .into_iter()
+                .map(|ranges| proto::SelectionRanges {
+                    ranges: ranges
+                        .into_iter()
+                        .map(|range| proto::AnchorRange {
+                            start: Some(serialize_anchor(&range.start)),
+                            end: Some(serialize_anchor(&range.end)),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSelectionRangesResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<Vec<Range<Anchor>>>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .selection_ranges
+            .into_iter()
+            .map(|selection_ranges| {
+                selection_ranges
+                    .ranges
+                    .into_iter()
+                    .map(|range| {
+                        let start = range
+                            .start
+                            .and_then(deserialize_anchor)
+                            .ok_or_else(|| anyhow!("invalid start"))?;
+                        let end = range
+                            .end
+                            .and_then(deserialize_anchor)
+                            .ok_or_else(|| anyhow!("invalid end"))?;
+                        Ok(start..end)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSelectionRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+fn serialize_color(color: lsp::Color) -> proto::Color {
+    proto::Color {
+        red: color.red,
+        green: color.green,
+        blue: color.blue,
+        alpha: color.alpha,
+    }
+}
+
+fn deserialize_color(color: proto::Color) -> lsp::Color {
+    lsp::Color {
+        red: color.red,
+        green: color.green,
+        blue: color.blue,
+        alpha: color.alpha,
+    }
+}
+
+fn supports_call_hierarchy(capabilities: &ServerCapabilities) -> bool {
+    match &capabilities.call_hierarchy_provider {
+        Some(lsp::CallHierarchyServerCapability::Simple(has_support)) => *has_support,
+        Some(lsp::CallHierarchyServerCapability::Options(_)) => true,
+        None => false,
+    }
+}
+
+fn anchor_range_from_lsp(buffer: &Buffer, range: lsp::Range) -> Range<Anchor> {
+    let start = buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
+    let end = buffer.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
+    buffer.anchor_after(start)..buffer.anchor_before(end)
+}
+
+async fn call_hierarchy_item_from_lsp(
+    lsp_item: lsp::CallHierarchyItem,
+    project: &Model<Project>,
+    lsp_adapter: &Arc<CachedLspAdapter>,
+    language_server: &Arc<LanguageServer>,
+    cx: &mut AsyncAppContext,
+) -> Result<CallHierarchyItem> {
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_via_lsp(
+                lsp_item.uri.clone().into(),
+                language_server.server_id(),
+                lsp_adapter.name.clone(),
+                cx,
+            )
+        })?
+        .await?;
+    let range = buffer.update(cx, |buffer, _| {
+        anchor_range_from_lsp(buffer, lsp_item.selection_range)
+    })?;
+    Ok(CallHierarchyItem {
+        server_id: language_server.server_id(),
+        name: lsp_item.name.clone(),
+        kind: lsp_item.kind,
+        detail: lsp_item.detail.clone(),
+        location: Location { buffer, range },
+        lsp_item,
+    })
+}
+
+fn serialize_call_hierarchy_item(
+    item: &CallHierarchyItem,
+    buffer_id: BufferId,
+) -> proto::CallHierarchyItem {
+    proto::CallHierarchyItem {
+        server_id: item.server_id.0 as u64,
+        name: item.name.clone(),
+        kind: unsafe { mem::transmute::<lsp::SymbolKind, i32>(item.kind) },
+        detail: item.detail.clone(),
+        location: Some(proto::Location {
+            buffer_id: buffer_id.into(),
+            start: Some(serialize_anchor(&item.location.range.start)),
+            end: Some(serialize_anchor(&item.location.range.end)),
+        }),
+        lsp_item: serde_json::to_vec(&item.lsp_item).unwrap(),
+    }
+}
+
+async fn deserialize_call_hierarchy_item(
+    item: proto::CallHierarchyItem,
+    project: &Model<Project>,
+    cx: &mut AsyncAppContext,
+) -> Result<CallHierarchyItem> {
+    let location = item.location.ok_or_else(|| anyhow!("missing location"))?;
+    Ok(CallHierarchyItem {
+        server_id: LanguageServerId(item.server_id as usize),
+        name: item.name,
+        kind: unsafe { mem::transmute::<i32, lsp::SymbolKind>(item.kind) },
+        detail: item.detail,
+        location: deserialize_location(location, project, cx).await?,
+        lsp_item: serde_json::from_slice(&item.lsp_item)?,
+    })
+}
+
+fn serialize_call_hierarchy_calls(
+    calls: Vec<CallHierarchyCall>,
+    project: &mut Project,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> Vec<proto::CallHierarchyCall> {
+    calls
+        .into_iter()
+        .map(|call| {
+            let buffer_id = project.create_buffer_for_peer(&call.item.location.buffer, peer_id, cx);
+            let call_sites = call
+                .call_sites
+                .into_iter()
+                .map(|call_site| {
+                    let buffer_id = project.create_buffer_for_peer(&call_site.buffer, peer_id, cx);
+                    proto::Location {
+                        buffer_id: buffer_id.into(),
+                        start: Some(serialize_anchor(&call_site.range.start)),
+                        end: Some(serialize_anchor(&call_site.range.end)),
+                    }
+                })
+                .collect();
+            proto::CallHierarchyCall {
+                item: Some(serialize_call_hierarchy_item(&call.item, buffer_id)),
+                call_sites,
+            }
+        })
+        .collect()
+}
+
+async fn deserialize_call_hierarchy_calls(
+    calls: Vec<proto::CallHierarchyCall>,
+    project: Model<Project>,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<CallHierarchyCall>> {
+    let mut result = Vec::new();
+    for call in calls {
+        let item = call.item.ok_or_else(|| anyhow!("missing item"))?;
+        let item = deserialize_call_hierarchy_item(item, &project, &mut cx).await?;
+        let mut call_sites = Vec::new();
+        for call_site in call.call_sites {
+            call_sites.push(deserialize_location(call_site, &project, &mut cx).await?);
+        }
+        result.push(CallHierarchyCall { item, call_sites });
+    }
+    Ok(result)
+}
+
+async fn deserialize_location(
+    location: proto::Location,
+    project: &Model<Project>,
+    cx: &mut AsyncAppContext,
+) -> Result<Location> {
+    let buffer_id = BufferId::new(location.buffer_id)?;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.wait_for_remote_buffer(buffer_id, cx)
+        })?
+        .await?;
+    let start = location
+        .start
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing location start"))?;
+    let end = location
+        .end
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing location end"))?;
+    buffer
+        .update(cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+        .await?;
+    Ok(Location {
+        buffer,
+        range: start..end,
+    })
+}
+
+async fn type_hierarchy_items_from_lsp(
+    lsp_items: Option<Vec<lsp::TypeHierarchyItem>>,
+    project: Model<Project>,
+    buffer: Model<Buffer>,
+    server_id: LanguageServerId,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<TypeHierarchyItem>> {
+    let (lsp_adapter, language_server) =
+        language_server_for_buffer(&project, &buffer, server_id, &mut cx)?;
+    let mut items = Vec::new();
+    for lsp_item in lsp_items.unwrap_or_default() {
+        let item_buffer = project
+            .update(&mut cx, |project, cx| {
+                project.open_local_buffer_via_lsp(
+                    lsp_item.uri.clone().into(),
+                    language_server.server_id(),
+                    lsp_adapter.name.clone(),
+                    cx,
+                )
+            })?
+            .await?;
+        let range = item_buffer.update(&mut cx, |item_buffer, _| {
+            anchor_range_from_lsp(item_buffer, lsp_item.selection_range)
+        })?;
+        items.push(TypeHierarchyItem {
+            server_id: language_server.server_id(),
+            name: lsp_item.name.clone(),
+            kind: lsp_item.kind,
+            detail: lsp_item.detail.clone(),
+            location: Location {
+                buffer: item_buffer,
+                range,
+            },
+            lsp_item,
+        });
+    }
+    Ok(items)
+}
+
+fn serialize_type_hierarchy_item(
+    item: &TypeHierarchyItem,
+    buffer_id: BufferId,
+) -> proto::TypeHierarchyItem {
+    proto::TypeHierarchyItem {
+        server_id: item.server_id.0 as u64,
+        name: item.name.clone(),
+        kind: unsafe { mem::transmute::<lsp::SymbolKind, i32>(item.kind) },
+        detail: item.detail.clone(),
+        location: Some(proto::Location {
+            buffer_id: buffer_id.into(),
+            start: Some(serialize_anchor(&item.location.range.start)),
+            end: Some(serialize_anchor(&item.location.range.end)),
+        }),
+        lsp_item: serde_json::to_vec(&item.lsp_item).unwrap(),
+    }
+}
+
+fn serialize_type_hierarchy_items(
+    items: Vec<TypeHierarchyItem>,
+    project: &mut Project,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> Vec<proto::TypeHierarchyItem> {
+    items
+        .iter()
+        .map(|item| {
+            let buffer_id = project.create_buffer_for_peer(&item.location.buffer, peer_id, cx);
+            serialize_type_hierarchy_item(item, buffer_id)
+        })
+        .collect()
+}
+
+async fn deserialize_type_hierarchy_item(
+    item: proto::TypeHierarchyItem,
+    project: &Model<Project>,
+    cx: &mut AsyncAppContext,
+) -> Result<TypeHierarchyItem> {
+    let location = item.location.ok_or_else(|| anyhow!("missing location"))?;
+    Ok(TypeHierarchyItem {
+        server_id: LanguageServerId(item.server_id as usize),
+        name: item.name,
+        kind: unsafe { mem::transmute::<i32, lsp::SymbolKind>(item.kind) },
+        detail: item.detail,
+        location: deserialize_location(location, project, cx).await?,
+        lsp_item: serde_json::from_slice(&item.lsp_item)?,
+    })
+}
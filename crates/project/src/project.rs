@@ -1,12 +1,19 @@
+mod breakpoints;
 pub mod connection_manager;
 pub mod debounced_delay;
+mod document_links;
+mod file_operations;
 pub mod lsp_command;
 pub mod lsp_ext_command;
 mod prettier_support;
 pub mod project_settings;
+mod pull_diagnostics;
 pub mod search;
+mod semantic_tokens;
 mod task_inventory;
+mod task_problems;
 pub mod terminals;
+mod test_statuses;
 
 #[cfg(test)]
 mod project_tests;
@@ -14,6 +21,7 @@ pub mod search_history;
 
 use anyhow::{anyhow, bail, Context as _, Result};
 use async_trait::async_trait;
+use breakpoints::Breakpoints;
 use client::{
     proto, Client, Collaborator, DevServerProjectId, PendingEntitySubscription, ProjectId,
     TypedEnvelope, UserStore,
@@ -73,10 +81,12 @@ use paths::{
 use postage::watch;
 use prettier_support::{DefaultPrettier, PrettierInstance};
 use project_settings::{LspSettings, ProjectSettings};
+use pull_diagnostics::PullDiagnosticsState;
 use rand::prelude::*;
 use rpc::{ErrorCode, ErrorExt as _};
 use search::SearchQuery;
 use search_history::SearchHistory;
+use semantic_tokens::CachedSemanticTokens;
 use serde::Serialize;
 use settings::{watch_config_file, Settings, SettingsLocation, SettingsStore};
 use sha2::{Digest, Sha256};
@@ -105,9 +115,13 @@ use std::{
 };
 use task::{
     static_source::{StaticSource, TrackedFile},
-    RevealStrategy, TaskContext, TaskTemplate, TaskVariables, VariableName,
+    DependsOrder, ProblemMatcherReference, RevealStrategy, TaskContext, TaskInput, TaskInputKind,
+    TaskManifest, TaskTemplate, TaskVariables, VariableName,
 };
+use task_problems::TaskProblems;
+use terminal::terminal_settings::TerminalSettings;
 use terminals::Terminals;
+use test_statuses::TestStatuses;
 use text::{Anchor, BufferId, LineEnding};
 use unicase::UniCase;
 use util::{
@@ -116,13 +130,16 @@ use util::{
 };
 use worktree::{CreatedEntry, RemoteWorktreeClient, Snapshot, Traversal};
 
+pub use document_links::{DocumentLink, DocumentLinkTarget};
 pub use fs::*;
 pub use language::Location;
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
+pub use semantic_tokens::SemanticToken;
 pub use task_inventory::{
     BasicContextProvider, ContextProviderWithTasks, Inventory, TaskSourceKind,
 };
+pub use test_statuses::TestStatus;
 pub use worktree::{
     Entry, EntryKind, File, LocalWorktree, PathChange, ProjectEntryId, RepositoryEntry,
     UpdatedEntriesSet, UpdatedGitRepositoriesSet, Worktree, WorktreeId, WorktreeSettings,
@@ -213,6 +230,11 @@ pub struct Project {
     local_buffer_ids_by_path: HashMap<ProjectPath, BufferId>,
     local_buffer_ids_by_entry_id: HashMap<ProjectEntryId, BufferId>,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
+    semantic_tokens: HashMap<BufferId, HashMap<LanguageServerId, CachedSemanticTokens>>,
+    pull_diagnostics: PullDiagnosticsState,
+    breakpoints: Breakpoints,
+    test_statuses: TestStatuses,
+    task_problems: TaskProblems,
     buffers_being_formatted: HashSet<BufferId>,
     buffers_needing_diff: HashSet<WeakModel<Buffer>>,
     git_diff_debouncer: DebouncedDelay,
@@ -333,6 +355,7 @@ pub enum Event {
     WorktreeRemoved(WorktreeId),
     WorktreeUpdatedEntries(WorktreeId, UpdatedEntriesSet),
     WorktreeUpdatedGitRepositories,
+    BufferSaved(ProjectPath),
     DiskBasedDiagnosticsStarted {
         language_server_id: LanguageServerId,
     },
@@ -354,8 +377,12 @@ pub enum Event {
     CollaboratorJoined(proto::PeerId),
     CollaboratorLeft(proto::PeerId),
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLenses,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
+    BreakpointsChanged(ProjectPath),
+    TestStatusesChanged(BufferId),
 }
 
 pub enum LanguageServerState {
@@ -475,6 +502,53 @@ pub struct CodeAction {
     pub lsp_action: lsp::CodeAction,
 }
 
+/// A command displayed above a range of a buffer, provided by a language server.
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    /// The id of the language server that produced this code lens.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer the code lens applies to.
+    pub range: Range<Anchor>,
+    /// The raw code lens provided by the language server.
+    pub lsp_lens: lsp::CodeLens,
+}
+
+/// A color literal of a buffer, as reported by a language server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentColor {
+    /// The id of the language server that reported this color.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer the color literal spans.
+    pub range: Range<Anchor>,
+    pub color: lsp::Color,
+}
+
+/// A way to write a color, offered by a language server to replace a color literal with.
+#[derive(Clone, Debug)]
+pub struct ColorPresentation {
+    /// The text shown when picking this presentation.
+    pub label: String,
+    /// The edits to apply to the buffer when this presentation is picked.
+    pub edits: Vec<(Range<Anchor>, String)>,
+}
+
+impl CodeLens {
+    /// Returns a code action running the command of this lens, to be applied
+    /// with [`Project::apply_code_action`].
+    pub fn to_code_action(&self) -> Option<CodeAction> {
+        let command = self.lsp_lens.command.clone()?;
+        Some(CodeAction {
+            server_id: self.server_id,
+            range: self.range.clone(),
+            lsp_action: lsp::CodeAction {
+                title: command.title.clone(),
+                command: Some(command),
+                ..lsp::CodeAction::default()
+            },
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolveState {
     Resolved,
@@ -528,6 +602,42 @@ pub struct LocationLink {
     pub target: Location,
 }
 
+/// A symbol in the call hierarchy, as reported by a language server.
+#[derive(Debug, Clone)]
+pub struct CallHierarchyItem {
+    pub server_id: LanguageServerId,
+    pub name: String,
+    pub kind: lsp::SymbolKind,
+    pub detail: Option<String>,
+    /// The range of the symbol's name, which is revealed when the item is opened.
+    pub location: Location,
+    /// The item as sent by the language server, which has to be passed back verbatim
+    /// when querying the item's incoming or outgoing calls.
+    pub lsp_item: lsp::CallHierarchyItem,
+}
+
+/// A type in the type hierarchy, as reported by a language server.
+#[derive(Debug, Clone)]
+pub struct TypeHierarchyItem {
+    pub server_id: LanguageServerId,
+    pub name: String,
+    pub kind: lsp::SymbolKind,
+    pub detail: Option<String>,
+    /// The range of the type's name, which is revealed when the item is opened.
+    pub location: Location,
+    /// The item as sent by the language server, which has to be passed back verbatim
+    /// when querying the item's supertypes or subtypes.
+    pub lsp_item: lsp::TypeHierarchyItem,
+}
+
+/// A caller (for incoming calls) or a callee (for outgoing calls) of a [`CallHierarchyItem`].
+#[derive(Debug, Clone)]
+pub struct CallHierarchyCall {
+    pub item: CallHierarchyItem,
+    /// The ranges of the calls, which are always located in the caller's buffer.
+    pub call_sites: Vec<Location>,
+}
+
 #[derive(Debug)]
 pub struct DocumentHighlight {
     pub range: Range<language::Anchor>,
@@ -583,6 +693,36 @@ impl Hover {
     }
 }
 
+/// Signatures of the callable surrounding a position, as reported by a language server.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignatureHelp {
+    pub signatures: Vec<SignatureInformation>,
+    /// The index of the signature the language server considers active.
+    pub active_signature: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignatureInformation {
+    pub label: String,
+    pub documentation: Option<HoverBlock>,
+    pub parameters: Vec<ParameterInformation>,
+    /// The index of the parameter the position is currently in, if any.
+    pub active_parameter: Option<usize>,
+}
+
+impl SignatureInformation {
+    pub fn active_parameter(&self) -> Option<&ParameterInformation> {
+        self.parameters.get(self.active_parameter?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterInformation {
+    /// The byte range of this parameter inside of its signature's label.
+    pub label_range: Range<usize>,
+    pub documentation: Option<HoverBlock>,
+}
+
 #[derive(Default)]
 pub struct ProjectTransaction(pub HashMap<Model<Buffer>, language::Transaction>);
 
@@ -644,6 +784,7 @@ impl Project {
     pub fn init_settings(cx: &mut AppContext) {
         WorktreeSettings::register(cx);
         ProjectSettings::register(cx);
+        TerminalSettings::register(cx);
     }
 
     pub fn init(client: &Arc<Client>, cx: &mut AppContext) {
@@ -677,6 +818,9 @@ impl Project {
         client.add_model_request_handler(Self::handle_inlay_hints);
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
         client.add_model_request_handler(Self::handle_refresh_inlay_hints);
+        client.add_model_request_handler(Self::handle_get_semantic_tokens);
+        client.add_model_request_handler(Self::handle_refresh_semantic_tokens);
+        client.add_model_request_handler(Self::handle_refresh_code_lenses);
         client.add_model_request_handler(Self::handle_reload_buffers);
         client.add_model_request_handler(Self::handle_synchronize_buffers);
         client.add_model_request_handler(Self::handle_format_buffers);
@@ -704,6 +848,20 @@ impl Project {
         client.add_model_request_handler(Self::handle_task_context_for_location);
         client.add_model_request_handler(Self::handle_task_templates);
         client.add_model_request_handler(Self::handle_lsp_command::<LinkedEditingRange>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSignatureHelp>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSubtypes>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetCodeLens>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentColors>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetColorPresentations>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentLinks>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSelectionRanges>);
+        client.add_model_request_handler(Self::handle_open_document_link);
     }
 
     pub fn local(
@@ -732,6 +890,11 @@ impl Project {
                 local_buffer_ids_by_path: Default::default(),
                 local_buffer_ids_by_entry_id: Default::default(),
                 buffer_snapshots: Default::default(),
+                semantic_tokens: Default::default(),
+                pull_diagnostics: Default::default(),
+                breakpoints: Default::default(),
+                test_statuses: Default::default(),
+                task_problems: Default::default(),
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
                 loading_buffers: HashMap::default(),
@@ -923,6 +1086,11 @@ impl Project {
                 buffers_needing_diff: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 buffer_snapshots: Default::default(),
+                semantic_tokens: Default::default(),
+                pull_diagnostics: Default::default(),
+                breakpoints: Default::default(),
+                test_statuses: Default::default(),
+                task_problems: Default::default(),
                 nonce: StdRng::from_entropy().gen(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
@@ -1444,8 +1612,25 @@ impl Project {
                 "No worktree for path {project_path:?}"
             ))));
         };
-        worktree.update(cx, |worktree, cx| {
-            worktree.create_entry(project_path.path, is_directory, cx)
+        let Some(operation) = self.file_operation_for_create(&project_path, is_directory, cx)
+        else {
+            return worktree.update(cx, |worktree, cx| {
+                worktree.create_entry(project_path.path, is_directory, cx)
+            });
+        };
+
+        let will_create = self.will_perform_file_operation(&operation, cx);
+        cx.spawn(move |this, mut cx| async move {
+            will_create.await;
+            let entry = worktree
+                .update(&mut cx, |worktree, cx| {
+                    worktree.create_entry(project_path.path, is_directory, cx)
+                })?
+                .await?;
+            this.update(&mut cx, |this, _| {
+                this.did_perform_file_operation(&operation)
+            })?;
+            Ok(entry)
         })
     }
 
@@ -1472,8 +1657,27 @@ impl Project {
         let Some(worktree) = self.worktree_for_entry(entry_id, cx) else {
             return Task::ready(Err(anyhow!(format!("No worktree for entry {entry_id:?}"))));
         };
-        worktree.update(cx, |worktree, cx| {
-            worktree.rename_entry(entry_id, new_path, cx)
+        let new_path: Arc<Path> = new_path.into();
+        let Some(operation) = self.file_operation_for_rename(entry_id, &new_path, cx) else {
+            return worktree.update(cx, |worktree, cx| {
+                worktree.rename_entry(entry_id, new_path, cx)
+            });
+        };
+
+        // Language servers compute the edits that accompany a rename, like updated imports,
+        // against the files as they are before it.
+        let will_rename = self.will_perform_file_operation(&operation, cx);
+        cx.spawn(move |this, mut cx| async move {
+            will_rename.await;
+            let entry = worktree
+                .update(&mut cx, |worktree, cx| {
+                    worktree.rename_entry(entry_id, new_path, cx)
+                })?
+                .await?;
+            this.update(&mut cx, |this, _| {
+                this.did_perform_file_operation(&operation)
+            })?;
+            Ok(entry)
         })
     }
 
@@ -1484,10 +1688,30 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) -> Option<Task<Result<()>>> {
         let worktree = self.worktree_for_entry(entry_id, cx)?;
-        cx.emit(Event::DeletedEntry(entry_id));
-        worktree.update(cx, |worktree, cx| {
-            worktree.delete_entry(entry_id, trash, cx)
-        })
+        let Some(operation) = self.file_operation_for_delete(entry_id, cx) else {
+            cx.emit(Event::DeletedEntry(entry_id));
+            return worktree.update(cx, |worktree, cx| {
+                worktree.delete_entry(entry_id, trash, cx)
+            });
+        };
+
+        let will_delete = self.will_perform_file_operation(&operation, cx);
+        Some(cx.spawn(move |this, mut cx| async move {
+            will_delete.await;
+            let delete = this.update(&mut cx, |_, cx| {
+                cx.emit(Event::DeletedEntry(entry_id));
+                worktree.update(cx, |worktree, cx| {
+                    worktree.delete_entry(entry_id, trash, cx)
+                })
+            })?;
+            if let Some(delete) = delete {
+                delete.await?;
+            }
+            this.update(&mut cx, |this, _| {
+                this.did_perform_file_operation(&operation)
+            })?;
+            Ok(())
+        }))
     }
 
     pub fn expand_entry(
@@ -2438,7 +2662,10 @@ impl Project {
 
         self.detect_language_for_buffer(buffer, cx);
         self.register_buffer_with_language_servers(buffer, cx);
+        self.breakpoints.buffer_opened(buffer, cx);
         cx.observe_release(buffer, |this, buffer, cx| {
+            this.breakpoints.buffer_closed(buffer, cx);
+            this.test_statuses.buffer_closed(buffer);
             if let Some(file) = File::from_dyn(buffer.file()) {
                 if file.is_local() {
                     let uri = lsp::Uri::from_file_path(file.abs_path(cx)).unwrap();
@@ -2551,6 +2778,8 @@ impl Project {
                 }
             }
         }
+
+        self.pull_diagnostics(buffer_handle, false, cx);
     }
 
     fn unregister_buffer_from_language_servers(
@@ -2577,6 +2806,8 @@ impl Project {
             }
 
             self.buffer_snapshots.remove(&buffer.remote_id());
+            self.semantic_tokens.remove(&buffer.remote_id());
+            self.pull_diagnostics.buffer_closed(buffer.remote_id());
             let file_url = lsp::Uri::from_file_path(old_path).unwrap();
             for (_, language_server) in self.language_servers_for_buffer(buffer, cx) {
                 language_server
@@ -2712,6 +2943,9 @@ impl Project {
         ) {
             self.request_buffer_diff_recalculation(&buffer, cx);
         }
+        if matches!(event, BufferEvent::Edited { .. }) {
+            self.pull_diagnostics(&buffer, true, cx);
+        }
 
         match event {
             BufferEvent::Operation(operation) => {
@@ -2835,6 +3069,9 @@ impl Project {
             }
 
             BufferEvent::Saved => {
+                if let Some(project_path) = buffer.read(cx).project_path(cx) {
+                    cx.emit(Event::BufferSaved(project_path));
+                }
                 let file = File::from_dyn(buffer.read(cx).file())?;
                 let worktree_id = file.worktree_id(cx);
                 let abs_path = file.as_local()?.abs_path(cx);
@@ -2854,8 +3091,10 @@ impl Project {
                         .log_err();
                 }
 
+                self.pull_diagnostics(&buffer, false, cx);
                 for language_server_id in self.language_server_ids_for_buffer(buffer.read(cx), cx) {
                     self.simulate_disk_based_diagnostics_events_if_needed(language_server_id, cx);
+                    self.pull_workspace_diagnostics(language_server_id, cx);
                 }
             }
             BufferEvent::FileHandleChanged => {
@@ -3658,6 +3897,61 @@ impl Project {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::SemanticTokensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |project, cx| {
+                            cx.emit(Event::RefreshSemanticTokens);
+                            project.remote_id().map(|project_id| {
+                                project
+                                    .client
+                                    .send(proto::RefreshSemanticTokens { project_id })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::CodeLensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |project, cx| {
+                            cx.emit(Event::RefreshCodeLenses);
+                            project.remote_id().map(|project_id| {
+                                project.client.send(proto::RefreshCodeLenses { project_id })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |project, cx| {
+                            project.refresh_pulled_diagnostics(server_id, cx)
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::ShowMessageRequest, _, _>({
                 let this = this.clone();
@@ -3837,6 +4131,7 @@ impl Project {
         }
 
         // Tell the language server about every open buffer in the worktree that matches the language.
+        let mut opened_buffers = Vec::new();
         for buffer in self.opened_buffers.values() {
             if let Some(buffer_handle) = buffer.upgrade() {
                 let buffer = buffer_handle.read(cx);
@@ -3902,9 +4197,15 @@ impl Project {
                         cx,
                     )
                 });
+                opened_buffers.push(buffer_handle);
             }
         }
 
+        for buffer in opened_buffers {
+            self.pull_diagnostics(&buffer, false, cx);
+        }
+        self.pull_workspace_diagnostics(server_id, cx);
+
         cx.notify();
         Ok(())
     }
@@ -3980,6 +4281,7 @@ impl Project {
 
             self.language_server_watched_paths.remove(&server_id);
             self.language_server_statuses.remove(&server_id);
+            self.pull_diagnostics.server_stopped(server_id);
             cx.notify();
 
             let server_state = self.language_servers.remove(&server_id);
@@ -4411,6 +4713,7 @@ impl Project {
             if let Some(work) = status.pending_work.remove(&token) {
                 if !work.is_disk_based_diagnostics_progress {
                     cx.emit(Event::RefreshInlayHints);
+                    cx.emit(Event::RefreshSemanticTokens);
                 }
             }
             cx.notify();
@@ -5876,6 +6179,311 @@ impl Project {
         self.linked_edit_impl(buffer, position, cx)
     }
 
+    fn signature_help_impl(
+        &self,
+        buffer: &Model<Buffer>,
+        position: PointUtf16,
+        context: lsp::SignatureHelpContext,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<SignatureHelp>>> {
+        let snapshot = buffer.read(cx).snapshot();
+        let scope = snapshot.language_scope_at(position);
+        let Some(server_id) = self
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .filter(|(_, server)| server.capabilities().signature_help_provider.is_some())
+            .filter(|(adapter, _)| {
+                scope
+                    .as_ref()
+                    .map(|scope| scope.language_allowed(&adapter.name))
+                    .unwrap_or(true)
+            })
+            .map(|(_, server)| LanguageServerToQuery::Other(server.server_id()))
+            .next()
+            .or_else(|| self.is_remote().then_some(LanguageServerToQuery::Primary))
+        else {
+            return Task::ready(Ok(None));
+        };
+
+        self.request_lsp(
+            buffer.clone(),
+            server_id,
+            GetSignatureHelp { position, context },
+            cx,
+        )
+    }
+
+    /// Whether typing the character should request signature help, as a trigger character
+    /// registered by a language server of the buffer, or as a retrigger character while the
+    /// help is shown. Returns `None` in remote projects, where only the host knows.
+    pub fn is_signature_help_trigger(
+        &self,
+        buffer: &Model<Buffer>,
+        character: &str,
+        is_retrigger: bool,
+        cx: &AppContext,
+    ) -> Option<bool> {
+        if self.is_remote() {
+            return None;
+        }
+        let is_trigger = self
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .filter_map(|(_, server)| server.capabilities().signature_help_provider.as_ref())
+            .any(|options| {
+                let lists = |characters: &Option<Vec<String>>| {
+                    characters
+                        .iter()
+                        .flatten()
+                        .any(|listed| listed == character)
+                };
+                lists(&options.trigger_characters)
+                    || is_retrigger && lists(&options.retrigger_characters)
+            });
+        Some(is_trigger)
+    }
+
+    pub fn signature_help<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        context: lsp::SignatureHelpContext,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<SignatureHelp>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.signature_help_impl(buffer, position, context, cx)
+    }
+
+    fn prepare_call_hierarchy_impl(
+        &self,
+        buffer: &Model<Buffer>,
+        position: PointUtf16,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyItem>>> {
+        let snapshot = buffer.read(cx).snapshot();
+        let scope = snapshot.language_scope_at(position);
+        let Some(server_id) = self
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .filter(|(_, server)| server.capabilities().call_hierarchy_provider.is_some())
+            .filter(|(adapter, _)| {
+                scope
+                    .as_ref()
+                    .map(|scope| scope.language_allowed(&adapter.name))
+                    .unwrap_or(true)
+            })
+            .map(|(_, server)| LanguageServerToQuery::Other(server.server_id()))
+            .next()
+            .or_else(|| self.is_remote().then_some(LanguageServerToQuery::Primary))
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        self.request_lsp(
+            buffer.clone(),
+            server_id,
+            PrepareCallHierarchy { position },
+            cx,
+        )
+    }
+
+    /// Resolves the call hierarchy items for the symbol at the given position,
+    /// which can then be used to query [`Self::incoming_calls`] and [`Self::outgoing_calls`].
+    pub fn prepare_call_hierarchy<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.prepare_call_hierarchy_impl(buffer, position, cx)
+    }
+
+    pub fn incoming_calls(
+        &self,
+        item: &CallHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetIncomingCalls { item: item.clone() },
+            cx,
+        )
+    }
+
+    pub fn outgoing_calls(
+        &self,
+        item: &CallHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetOutgoingCalls { item: item.clone() },
+            cx,
+        )
+    }
+
+    /// Resolves the type hierarchy items for the type at the given position,
+    /// which can then be used to query [`Self::supertypes`] and [`Self::subtypes`].
+    pub fn prepare_type_hierarchy<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareTypeHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn supertypes(
+        &self,
+        item: &TypeHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSupertypes { item: item.clone() },
+            cx,
+        )
+    }
+
+    pub fn subtypes(
+        &self,
+        item: &TypeHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSubtypes { item: item.clone() },
+            cx,
+        )
+    }
+
+    /// Returns the code lenses of the given buffer, as reported by the first of its
+    /// language servers that provides them.
+    pub fn code_lens(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        let Some(server_id) = self
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .find(|(_, server)| server.capabilities().code_lens_provider.is_some())
+            .map(|(_, server)| LanguageServerToQuery::Other(server.server_id()))
+            .or_else(|| self.is_remote().then_some(LanguageServerToQuery::Primary))
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        self.request_lsp(buffer.clone(), server_id, GetCodeLens, cx)
+    }
+
+    /// Returns the line ranges that can be folded in the given buffer, as reported by the
+    /// first of its language servers that provides them, sorted by their start.
+    pub fn folding_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<Range<Anchor>>>> {
+        let Some(server_id) = self
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .find(|(_, server)| GetFoldingRanges.check_capabilities(server.capabilities()))
+            .map(|(_, server)| LanguageServerToQuery::Other(server.server_id()))
+            .or_else(|| self.is_remote().then_some(LanguageServerToQuery::Primary))
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        self.request_lsp(buffer.clone(), server_id, GetFoldingRanges, cx)
+    }
+
+    /// Returns, for each of the given positions, the ranges around it that are worth selecting,
+    /// from the innermost to the outermost, as reported by the first of the buffer's language
+    /// servers that provides them.
+    pub fn selection_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        positions: Vec<Anchor>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<Vec<Range<Anchor>>>>> {
+        let request = GetSelectionRanges { positions };
+        let Some(server_id) = self
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .find(|(_, server)| request.check_capabilities(server.capabilities()))
+            .map(|(_, server)| LanguageServerToQuery::Other(server.server_id()))
+            .or_else(|| self.is_remote().then_some(LanguageServerToQuery::Primary))
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        self.request_lsp(buffer.clone(), server_id, request, cx)
+    }
+
+    /// Returns the color literals of the given buffer, as reported by the first of its
+    /// language servers that provides them, sorted by their start.
+    pub fn document_colors(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<DocumentColor>>> {
+        let Some(server_id) = self
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .find(|(_, server)| GetDocumentColors.check_capabilities(server.capabilities()))
+            .map(|(_, server)| LanguageServerToQuery::Other(server.server_id()))
+            .or_else(|| self.is_remote().then_some(LanguageServerToQuery::Primary))
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        self.request_lsp(buffer.clone(), server_id, GetDocumentColors, cx)
+    }
+
+    /// Returns the links of the given buffer, as reported by the first of its language servers
+    /// that provides them, sorted by their start. Their targets may have to be resolved with
+    /// [`Project::open_document_link`].
+    pub fn document_links(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<DocumentLink>>> {
+        let Some(server_id) = self
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .find(|(_, server)| server.capabilities().document_link_provider.is_some())
+            .map(|(_, server)| LanguageServerToQuery::Other(server.server_id()))
+            .or_else(|| self.is_remote().then_some(LanguageServerToQuery::Primary))
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        self.request_lsp(buffer.clone(), server_id, GetDocumentLinks, cx)
+    }
+
+    /// Returns the ways the language server that reported the given color literal offers
+    /// to write it as `new_color` instead.
+    pub fn color_presentations(
+        &self,
+        buffer: &Model<Buffer>,
+        document_color: &DocumentColor,
+        new_color: lsp::Color,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<ColorPresentation>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Other(document_color.server_id),
+            GetColorPresentations {
+                range: document_color.range.clone(),
+                color: new_color,
+            },
+            cx,
+        )
+    }
+
     #[inline(never)]
     fn completions_impl(
         &self,
@@ -8172,8 +8780,13 @@ impl Project {
         let remote_worktree_id = worktree.read(cx).id();
 
         let mut settings_contents = Vec::new();
+        let mut changed_manifests = HashSet::default();
+        let mut manifests_to_redetect = HashSet::default();
         for (path, _, change) in changes.iter() {
             let removed = change == &PathChange::Removed;
+            if matches!(change, PathChange::Added | PathChange::Removed) {
+                manifests_to_redetect.extend(TaskManifest::manifests_discovering_target_at(path));
+            }
             let abs_path = match worktree.read(cx).absolutize(path) {
                 Ok(abs_path) => abs_path,
                 Err(e) => {
@@ -8245,9 +8858,47 @@ impl Project {
                         );
                     }
                 })
+            } else if let Some(manifest) = TaskManifest::for_path(path) {
+                changed_manifests.insert(path.to_path_buf());
+                // Manifests of dependencies, such as the ones in `node_modules`, declare no tasks of the project.
+                let ignored = worktree
+                    .read(cx)
+                    .entry_for_path(path)
+                    .map_or(false, |entry| entry.is_ignored);
+                if removed {
+                    self.task_inventory().update(cx, |task_inventory, _| {
+                        task_inventory.remove_local_static_source(&abs_path);
+                    });
+                } else if !ignored {
+                    self.add_detected_task_source(worktree, manifest, path, abs_path, cx);
+                }
             }
         }
 
+        // The targets discovered by the manifests change along with the files of their packages.
+        for manifest_path in manifests_to_redetect {
+            if changed_manifests.contains(&manifest_path) {
+                continue;
+            }
+            let Some(manifest) = TaskManifest::for_path(&manifest_path) else {
+                continue;
+            };
+            let worktree_snapshot = worktree.read(cx);
+            let Some(entry) = worktree_snapshot.entry_for_path(&manifest_path) else {
+                continue;
+            };
+            if entry.is_ignored {
+                continue;
+            }
+            let Ok(abs_path) = worktree_snapshot.absolutize(&manifest_path) else {
+                continue;
+            };
+            self.task_inventory().update(cx, |task_inventory, _| {
+                task_inventory.remove_local_static_source(&abs_path);
+            });
+            self.add_detected_task_source(worktree, manifest, &manifest_path, abs_path, cx);
+        }
+
         if settings_contents.is_empty() {
             return;
         }
@@ -8286,6 +8937,74 @@ impl Project {
         .detach();
     }
 
+    /// Detects the tasks of the manifest at the given path of the worktree, as its contents change.
+    fn add_detected_task_source(
+        &mut self,
+        worktree: &Model<Worktree>,
+        manifest: TaskManifest,
+        path: &Path,
+        abs_path: PathBuf,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let worktree = worktree.read(cx);
+        let remote_worktree_id = worktree.id();
+        let package_dir = path.parent().unwrap_or(Path::new(""));
+        let mut target_files = Vec::new();
+        let mut dirs = vec![package_dir.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in worktree.child_entries(&dir) {
+                let Ok(relative_path) = entry.path.strip_prefix(package_dir) else {
+                    continue;
+                };
+                if entry.is_dir() {
+                    if manifest.discovers_targets_in(relative_path) {
+                        dirs.push(entry.path.to_path_buf());
+                    }
+                } else if manifest.discovers_target_at(relative_path) {
+                    target_files.push(relative_path.to_path_buf());
+                }
+            }
+        }
+
+        let fs = self.fs.clone();
+        let manifest_file_rx = watch_config_file(&cx.background_executor(), fs, abs_path.clone());
+        let cwd = abs_path
+            .parent()
+            .map(|dir| dir.to_string_lossy().into_owned());
+        // Tell apart the tasks of manifests in different directories of the worktree.
+        let label_suffix = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| format!(" ({})", dir.display()));
+        let detect_tasks = move |contents: &str| {
+            let mut tasks = manifest.detect_tasks(contents, &target_files)?;
+            for task in &mut tasks.0 {
+                task.cwd = cwd.clone();
+                if let Some(label_suffix) = &label_suffix {
+                    task.label.push_str(label_suffix);
+                }
+            }
+            Ok(tasks)
+        };
+        self.task_inventory().update(cx, |task_inventory, cx| {
+            task_inventory.add_source(
+                TaskSourceKind::Detected {
+                    id: remote_worktree_id,
+                    abs_path,
+                },
+                |tx, cx| {
+                    StaticSource::new(TrackedFile::new_parsed(
+                        manifest_file_rx,
+                        tx,
+                        detect_tasks,
+                        cx,
+                    ))
+                },
+                cx,
+            );
+        });
+    }
+
     pub fn set_active_path(&mut self, entry: Option<ProjectPath>, cx: &mut ModelContext<Self>) {
         let new_active_entry = entry.and_then(|project_path| {
             let worktree = self.worktree_for_id(project_path.worktree_id, cx)?;
@@ -8871,12 +9590,26 @@ impl Project {
         _: Arc<Client>,
         mut cx: AsyncAppContext,
     ) -> Result<proto::ProjectEntryResponse> {
-        let worktree = this.update(&mut cx, |this, cx| {
+        let (worktree, operation) = this.update(&mut cx, |this, cx| {
             let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
-            this.worktree_for_id(worktree_id, cx)
-                .ok_or_else(|| anyhow!("worktree not found"))
+            let worktree = this
+                .worktree_for_id(worktree_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))?;
+            let project_path = ProjectPath {
+                worktree_id,
+                path: Path::new(&envelope.payload.path).into(),
+            };
+            let operation =
+                this.file_operation_for_create(&project_path, envelope.payload.is_directory, cx);
+            anyhow::Ok((worktree, operation))
         })??;
-        Worktree::handle_create_entry(worktree, envelope.payload, cx).await
+        Self::perform_file_operation(
+            this,
+            operation,
+            Worktree::handle_create_entry(worktree, envelope.payload, cx.clone()),
+            cx,
+        )
+        .await
     }
 
     async fn handle_rename_project_entry(
@@ -8886,11 +9619,21 @@ impl Project {
         mut cx: AsyncAppContext,
     ) -> Result<proto::ProjectEntryResponse> {
         let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
-        let worktree = this.update(&mut cx, |this, cx| {
-            this.worktree_for_entry(entry_id, cx)
-                .ok_or_else(|| anyhow!("worktree not found"))
+        let (worktree, operation) = this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree_for_entry(entry_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))?;
+            let operation =
+                this.file_operation_for_rename(entry_id, Path::new(&envelope.payload.new_path), cx);
+            anyhow::Ok((worktree, operation))
         })??;
-        Worktree::handle_rename_entry(worktree, envelope.payload, cx).await
+        Self::perform_file_operation(
+            this,
+            operation,
+            Worktree::handle_rename_entry(worktree, envelope.payload, cx.clone()),
+            cx,
+        )
+        .await
     }
 
     async fn handle_copy_project_entry(
@@ -8914,12 +9657,21 @@ impl Project {
         mut cx: AsyncAppContext,
     ) -> Result<proto::ProjectEntryResponse> {
         let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
-        let worktree = this.update(&mut cx, |this, cx| {
-            this.worktree_for_entry(entry_id, cx)
-                .ok_or_else(|| anyhow!("worktree not found"))
+        let (worktree, operation) = this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree_for_entry(entry_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))?;
+            let operation = this.file_operation_for_delete(entry_id, cx);
+            anyhow::Ok((worktree, operation))
         })??;
         this.update(&mut cx, |_, cx| cx.emit(Event::DeletedEntry(entry_id)))?;
-        Worktree::handle_delete_entry(worktree, envelope.payload, cx).await
+        Self::perform_file_operation(
+            this,
+            operation,
+            Worktree::handle_delete_entry(worktree, envelope.payload, cx.clone()),
+            cx,
+        )
+        .await
     }
 
     async fn handle_expand_project_entry(
@@ -9805,6 +10557,12 @@ impl Project {
                             name: name.to_string(),
                         })
                     }
+                    TaskSourceKind::Detected { id, abs_path } => {
+                        proto::task_source_kind::Kind::Detected(proto::task_source_kind::Detected {
+                            id: id.to_proto(),
+                            abs_path: abs_path.to_string_lossy().to_string(),
+                        })
+                    }
                 });
                 let kind = Some(proto::TaskSourceKind { kind });
                 let template = Some(proto::TaskTemplate {
@@ -9820,6 +10578,49 @@ impl Project {
                         RevealStrategy::Never => proto::RevealStrategy::Never as i32,
                     },
                     tags: template.tags,
+                    problem_matchers: template
+                        .problem_matchers
+                        .into_iter()
+                        .map(|matcher| {
+                            let reference = match matcher {
+                                ProblemMatcherReference::Named(name) => {
+                                    proto::problem_matcher_reference::Reference::Named(name)
+                                }
+                                ProblemMatcherReference::Inline(matcher) => {
+                                    proto::problem_matcher_reference::Reference::Inline(
+                                        serde_json::to_vec(&matcher).unwrap(),
+                                    )
+                                }
+                            };
+                            proto::ProblemMatcherReference {
+                                reference: Some(reference),
+                            }
+                        })
+                        .collect(),
+                    depends_on: template.depends_on,
+                    depends_order: match template.depends_order {
+                        DependsOrder::Parallel => proto::DependsOrder::Parallel as i32,
+                        DependsOrder::Sequence => proto::DependsOrder::Sequence as i32,
+                    },
+                    inputs: template
+                        .inputs
+                        .into_iter()
+                        .map(|input| proto::TaskInput {
+                            id: input.id,
+                            kind: match input.kind {
+                                TaskInputKind::PromptString => {
+                                    proto::TaskInputKind::PromptString as i32
+                                }
+                                TaskInputKind::PickString => {
+                                    proto::TaskInputKind::PickString as i32
+                                }
+                            },
+                            description: input.description,
+                            default: input.default,
+                            options: input.options,
+                        })
+                        .collect(),
+                    watch: template.watch,
                 });
                 proto::TemplatePair { kind, template }
             })
@@ -9933,6 +10734,30 @@ impl Project {
         Ok(proto::Ack {})
     }
 
+    async fn handle_refresh_semantic_tokens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshSemanticTokens>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(Event::RefreshSemanticTokens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_refresh_code_lenses(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshCodeLenses>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(Event::RefreshCodeLenses);
+        })?;
+        Ok(proto::Ack {})
+    }
+
     async fn handle_lsp_command<T: LspCommand>(
         this: Model<Self>,
         envelope: TypedEnvelope<T::ProtoRequest>,
@@ -10954,6 +11779,12 @@ impl Project {
                                 name: language.name.into(),
                             }
                         }
+                        proto::task_source_kind::Kind::Detected(detected) => {
+                            TaskSourceKind::Detected {
+                                id: WorktreeId::from_proto(detected.id),
+                                abs_path: PathBuf::from(detected.abs_path),
+                            }
+                        }
                     };
 
                     let proto_template = template_pair.template?;
@@ -10963,6 +11794,43 @@ impl Project {
                         proto::RevealStrategy::Always => RevealStrategy::Always,
                         proto::RevealStrategy::Never => RevealStrategy::Never,
                     };
+                    let problem_matchers = proto_template
+                        .problem_matchers
+                        .into_iter()
+                        .filter_map(|matcher| match matcher.reference? {
+                            proto::problem_matcher_reference::Reference::Named(name) => {
+                                Some(ProblemMatcherReference::Named(name))
+                            }
+                            proto::problem_matcher_reference::Reference::Inline(matcher) => {
+                                serde_json::from_slice(&matcher)
+                                    .log_err()
+                                    .map(ProblemMatcherReference::Inline)
+                            }
+                        })
+                        .collect();
+                    let depends_order =
+                        match proto::DependsOrder::from_i32(proto_template.depends_order)
+                            .unwrap_or(proto::DependsOrder::Parallel)
+                        {
+                            proto::DependsOrder::Parallel => DependsOrder::Parallel,
+                            proto::DependsOrder::Sequence => DependsOrder::Sequence,
+                        };
+                    let inputs = proto_template
+                        .inputs
+                        .into_iter()
+                        .map(|input| TaskInput {
+                            kind: match proto::TaskInputKind::from_i32(input.kind)
+                                .unwrap_or(proto::TaskInputKind::PromptString)
+                            {
+                                proto::TaskInputKind::PromptString => TaskInputKind::PromptString,
+                                proto::TaskInputKind::PickString => TaskInputKind::PickString,
+                            },
+                            id: input.id,
+                            description: input.description,
+                            default: input.default,
+                            options: input.options,
+                        })
+                        .collect();
                     let task_template = TaskTemplate {
                         label: proto_template.label,
                         command: proto_template.command,
@@ -10973,6 +11841,11 @@ impl Project {
                         allow_concurrent_runs: proto_template.allow_concurrent_runs,
                         reveal,
                         tags: proto_template.tags,
+                        problem_matchers,
+                        depends_on: proto_template.depends_on,
+                        depends_order,
+                        inputs,
+                        watch: proto_template.watch,
                     };
                     Some((task_source_kind, task_template))
                 })
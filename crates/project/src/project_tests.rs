@@ -14,7 +14,7 @@ use serde_json::json;
 #[cfg(not(windows))]
 use std::os;
 use std::task::Poll;
-use task::{ResolvedTask, TaskContext, TaskTemplate, TaskTemplates};
+use task::{ResolvedTask, TaskContext, TaskTemplate, TaskTemplates, TerminalWorkDir};
 use unindent::Unindent as _;
 use util::{assert_set_eq, paths::PathMatcher, test::temp_tree};
 
@@ -294,6 +294,163 @@ async fn test_managing_project_specific_settings(cx: &mut gpui::TestAppContext)
     );
 }
 
+#[gpui::test]
+async fn test_detected_tasks(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            ".gitignore": "node_modules",
+            "Makefile": "build:\n\tcc main.c\n",
+            "web": {
+                "package.json": r#"{ "scripts": { "dev": "vite", "test": "vitest" } }"#,
+            },
+            "node_modules": {
+                "dep": {
+                    "package.json": r#"{ "scripts": { "prepare": "tsc" } }"#,
+                },
+            },
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    let worktree_id = cx.update(|cx| {
+        project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        })
+    });
+    let detected_tasks = |cx: &mut gpui::TestAppContext| {
+        cx.update(|cx| get_all_tasks(&project, Some(worktree_id), &TaskContext::default(), cx))
+    };
+    let makefile_kind = TaskSourceKind::Detected {
+        id: worktree_id,
+        abs_path: PathBuf::from("/the-root/Makefile"),
+    };
+    let package_json_kind = TaskSourceKind::Detected {
+        id: worktree_id,
+        abs_path: PathBuf::from("/the-root/web/package.json"),
+    };
+
+    let tasks = detected_tasks(cx)
+        .await
+        .into_iter()
+        .map(|(source_kind, task)| {
+            let resolved = task.resolved.unwrap();
+            (source_kind, task.resolved_label, resolved.cwd)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tasks,
+        vec![
+            (
+                makefile_kind.clone(),
+                "make build".to_string(),
+                Some(TerminalWorkDir::Local(PathBuf::from("/the-root"))),
+            ),
+            (
+                package_json_kind.clone(),
+                "npm run dev (web)".to_string(),
+                Some(TerminalWorkDir::Local(PathBuf::from("/the-root/web"))),
+            ),
+            (
+                package_json_kind.clone(),
+                "npm run test (web)".to_string(),
+                Some(TerminalWorkDir::Local(PathBuf::from("/the-root/web"))),
+            ),
+        ]
+    );
+
+    fs.insert_file(
+        "/the-root/Makefile",
+        "build:\n\tcc main.c\nclean:\n\trm -f a.out\n".into(),
+    )
+    .await;
+    fs.remove_file("/the-root/web/package.json".as_ref(), Default::default())
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let tasks = detected_tasks(cx)
+        .await
+        .into_iter()
+        .map(|(source_kind, task)| (source_kind, task.resolved_label))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tasks,
+        vec![
+            (makefile_kind.clone(), "make build".to_string()),
+            (makefile_kind.clone(), "make clean".to_string()),
+        ],
+        "Detected tasks should follow the changes of the manifests"
+    );
+}
+
+#[gpui::test]
+async fn test_detected_cargo_targets(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            "Cargo.toml": "[package]\nname = \"app\"\n",
+            "src": {
+                "main.rs": "fn main() {}",
+                "lib.rs": "",
+            },
+            "examples": {
+                "demo.rs": "fn main() {}",
+            },
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    let worktree_id = cx.update(|cx| {
+        project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        })
+    });
+    let detected_labels = |cx: &mut gpui::TestAppContext| {
+        cx.update(|cx| get_all_tasks(&project, Some(worktree_id), &TaskContext::default(), cx))
+    };
+
+    let labels = detected_labels(cx)
+        .await
+        .into_iter()
+        .map(|(_, task)| task.resolved_label)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec![
+            "cargo run --bin app".to_string(),
+            "cargo run --example demo".to_string(),
+        ]
+    );
+
+    fs.insert_file("/the-root/src/bin/tool.rs", "fn main() {}".into())
+        .await;
+    cx.executor().run_until_parked();
+    let labels = detected_labels(cx)
+        .await
+        .into_iter()
+        .map(|(_, task)| task.resolved_label)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec![
+            "cargo run --bin app".to_string(),
+            "cargo run --bin tool".to_string(),
+            "cargo run --example demo".to_string(),
+        ],
+        "Targets should be discovered as the files of the package are added"
+    );
+}
+
 #[gpui::test]
 async fn test_managing_language_servers(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4849,14 +5006,14 @@ async fn test_hovers_with_empty_parts(cx: &mut gpui::TestAppContext) {
 }
 
 #[gpui::test]
-async fn test_multiple_language_server_actions(cx: &mut gpui::TestAppContext) {
+async fn test_signature_help(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "a.tsx": "a",
+            "a.ts": "add(1, ",
         }),
     )
     .await;
@@ -4864,56 +5021,164 @@ async fn test_multiple_language_server_actions(cx: &mut gpui::TestAppContext) {
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
 
     let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-    language_registry.add(tsx_lang());
-    let language_server_names = [
-        "TypeScriptServer",
-        "TailwindServer",
-        "ESLintServer",
-        "NoActionsCapabilitiesServer",
-    ];
-    let mut fake_tsx_language_servers = language_registry.register_specific_fake_lsp_adapter(
-        "tsx",
-        true,
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
         FakeLspAdapter {
-            name: &language_server_names[0],
             capabilities: lsp::ServerCapabilities {
-                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
+                signature_help_provider: Some(lsp::SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
                 ..lsp::ServerCapabilities::default()
             },
             ..FakeLspAdapter::default()
         },
     );
-    let _a = language_registry.register_specific_fake_lsp_adapter(
-        "tsx",
-        false,
-        FakeLspAdapter {
-            name: &language_server_names[1],
-            capabilities: lsp::ServerCapabilities {
-                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
-                ..lsp::ServerCapabilities::default()
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    let mut request_handled = fake_server
+        .handle_request::<lsp::request::SignatureHelpRequest, _, _>(move |params, _| async move {
+            let context = params.context.unwrap();
+            assert_eq!(
+                context.trigger_kind,
+                lsp::SignatureHelpTriggerKind::TRIGGER_CHARACTER
+            );
+            assert_eq!(context.trigger_character.as_deref(), Some(","));
+            Ok(Some(lsp::SignatureHelp {
+                signatures: vec![
+                    lsp::SignatureInformation {
+                        label: "add(a: number, b: number): number".to_string(),
+                        documentation: None,
+                        parameters: Some(vec![
+                            lsp::ParameterInformation {
+                                label: lsp::ParameterLabel::Simple("a: number".to_string()),
+                                documentation: None,
+                            },
+                            lsp::ParameterInformation {
+                                label: lsp::ParameterLabel::Simple("b: number".to_string()),
+                                documentation: Some(lsp::Documentation::String(
+                                    "The second operand".to_string(),
+                                )),
+                            },
+                        ]),
+                        active_parameter: None,
+                    },
+                    lsp::SignatureInformation {
+                        label: "add(a: string): string".to_string(),
+                        documentation: None,
+                        parameters: Some(vec![lsp::ParameterInformation {
+                            label: lsp::ParameterLabel::LabelOffsets([4, 13]),
+                            documentation: None,
+                        }]),
+                        active_parameter: Some(0),
+                    },
+                ],
+                active_signature: Some(0),
+                active_parameter: Some(1),
+            }))
+        });
+
+    let signature_help_task = project.update(cx, |project, cx| {
+        project.signature_help(
+            &buffer,
+            Point::new(0, 7),
+            lsp::SignatureHelpContext {
+                trigger_kind: lsp::SignatureHelpTriggerKind::TRIGGER_CHARACTER,
+                trigger_character: Some(",".to_string()),
+                is_retrigger: false,
+                active_signature_help: None,
             },
-            ..FakeLspAdapter::default()
-        },
+            cx,
+        )
+    });
+    request_handled
+        .next()
+        .await
+        .expect("signature help request should have been triggered");
+    let signature_help = signature_help_task
+        .await
+        .unwrap()
+        .expect("signature help should be returned");
+    assert_eq!(signature_help.active_signature, 0);
+    let first_signature = &signature_help.signatures[0];
+    assert_eq!(
+        first_signature
+            .parameters
+            .iter()
+            .map(|parameter| &first_signature.label[parameter.label_range.clone()])
+            .collect::<Vec<_>>(),
+        vec!["a: number", "b: number"]
     );
-    let _b = language_registry.register_specific_fake_lsp_adapter(
-        "tsx",
-        false,
-        FakeLspAdapter {
-            name: &language_server_names[2],
-            capabilities: lsp::ServerCapabilities {
-                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
-                ..lsp::ServerCapabilities::default()
-            },
-            ..FakeLspAdapter::default()
-        },
+    assert_eq!(first_signature.active_parameter, Some(1));
+    assert_eq!(
+        first_signature
+            .active_parameter()
+            .and_then(|parameter| parameter.documentation.as_ref())
+            .map(|documentation| documentation.text.as_str()),
+        Some("The second operand")
     );
-    let _c = language_registry.register_specific_fake_lsp_adapter(
-        "tsx",
-        false,
+    let second_signature = &signature_help.signatures[1];
+    assert_eq!(
+        &second_signature.label[second_signature.parameters[0].label_range.clone()],
+        "a: string"
+    );
+    assert_eq!(second_signature.active_parameter, Some(0));
+
+    // Characters that the server did not register as triggers do not produce requests.
+    let signature_help = project
+        .update(cx, |project, cx| {
+            project.signature_help(
+                &buffer,
+                Point::new(0, 7),
+                lsp::SignatureHelpContext {
+                    trigger_kind: lsp::SignatureHelpTriggerKind::TRIGGER_CHARACTER,
+                    trigger_character: Some(";".to_string()),
+                    is_retrigger: false,
+                    active_signature_help: None,
+                },
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(signature_help, None);
+}
+
+#[gpui::test]
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "function main() { helper(); }",
+            "b.ts": "function helper() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
         FakeLspAdapter {
-            name: &language_server_names[3],
             capabilities: lsp::ServerCapabilities {
-                code_action_provider: None,
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
                 ..lsp::ServerCapabilities::default()
             },
             ..FakeLspAdapter::default()
@@ -4921,30 +5186,1186 @@ async fn test_multiple_language_server_actions(cx: &mut gpui::TestAppContext) {
     );
 
     let buffer = project
-        .update(cx, |p, cx| p.open_local_buffer("/dir/a.tsx", cx))
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
         .await
         .unwrap();
     cx.executor().run_until_parked();
 
-    let mut servers_with_actions_requests = HashMap::default();
-    for i in 0..language_server_names.len() {
-        let new_server = fake_tsx_language_servers.next().await.unwrap_or_else(|| {
-            panic!(
-                "Failed to get language server #{i} with name {}",
-                &language_server_names[i]
-            )
-        });
-        let new_server_name = new_server.server.name();
-        assert!(
-            !servers_with_actions_requests.contains_key(new_server_name),
-            "Unexpected: initialized server with the same name twice. Name: `{new_server_name}`"
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    fn lsp_item(name: &str, path: &str, line_len: u32) -> lsp::CallHierarchyItem {
+        lsp::CallHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            detail: Some(format!("function {name}()")),
+            uri: lsp::Uri::from_file_path(path).unwrap().into(),
+            range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, line_len)),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(0, 9),
+                lsp::Position::new(0, 9 + name.len() as u32),
+            ),
+            data: Some(json!({ "id": name })),
+        }
+    }
+
+    fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 10)
+            );
+            Ok(Some(vec![lsp_item("main", "/dir/a.ts", 29)]))
+        },
+    );
+    fake_server.handle_request::<lsp::request::CallHierarchyOutgoingCalls, _, _>(
+        |params, _| async move {
+            // The item is passed back to the server as it was received.
+            assert_eq!(params.item.data, Some(json!({ "id": "main" })));
+            Ok(Some(vec![lsp::CallHierarchyOutgoingCall {
+                to: lsp_item("helper", "/dir/b.ts", 20),
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(0, 18),
+                    lsp::Position::new(0, 24),
+                )],
+            }]))
+        },
+    );
+    fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "helper");
+            Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                from: lsp_item("main", "/dir/a.ts", 29),
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(0, 18),
+                    lsp::Position::new(0, 24),
+                )],
+            }]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, Point::new(0, 10), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let main_item = items[0].clone();
+    assert_eq!(main_item.name, "main");
+    assert_eq!(main_item.kind, lsp::SymbolKind::FUNCTION);
+    cx.update(|cx| {
+        let location = &main_item.location;
+        let buffer = location.buffer.read(cx);
+        assert_eq!(
+            buffer
+                .text_for_range(location.range.clone())
+                .collect::<String>(),
+            "main"
         );
-        let new_server_name = new_server_name.to_string();
-        match new_server_name.as_str() {
-            "TailwindServer" | "TypeScriptServer" => {
-                servers_with_actions_requests.insert(
-                    new_server_name.clone(),
-                    new_server.handle_request::<lsp::request::CodeActionRequest, _, _>(
+    });
+
+    let outgoing_calls = project
+        .update(cx, |project, cx| project.outgoing_calls(&main_item, cx))
+        .await
+        .unwrap();
+    assert_eq!(outgoing_calls.len(), 1);
+    let helper_item = outgoing_calls[0].item.clone();
+    cx.update(|cx| {
+        let helper_buffer = helper_item.location.buffer.read(cx);
+        assert_eq!(
+            helper_buffer.file().unwrap().path().as_ref(),
+            Path::new("b.ts")
+        );
+        assert_eq!(
+            helper_buffer
+                .text_for_range(helper_item.location.range.clone())
+                .collect::<String>(),
+            "helper"
+        );
+        // Outgoing calls are made from within the requested item.
+        let call_site = &outgoing_calls[0].call_sites[0];
+        assert_eq!(call_site.buffer, buffer);
+        assert_eq!(
+            call_site
+                .buffer
+                .read(cx)
+                .text_for_range(call_site.range.clone())
+                .collect::<String>(),
+            "helper"
+        );
+    });
+
+    let incoming_calls = project
+        .update(cx, |project, cx| project.incoming_calls(&helper_item, cx))
+        .await
+        .unwrap();
+    assert_eq!(incoming_calls.len(), 1);
+    assert_eq!(incoming_calls[0].item.name, "main");
+    assert_eq!(incoming_calls[0].item.location.buffer, buffer);
+    assert_eq!(incoming_calls[0].call_sites[0].buffer, buffer);
+}
+
+#[gpui::test]
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "interface Shape {}",
+            "b.ts": "class Circle implements Shape {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers =
+        language_registry.register_fake_lsp_adapter("TypeScript", FakeLspAdapter::default());
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    fn lsp_item(
+        name: &str,
+        kind: lsp::SymbolKind,
+        path: &str,
+        name_start: u32,
+        line_len: u32,
+    ) -> lsp::TypeHierarchyItem {
+        lsp::TypeHierarchyItem {
+            name: name.to_string(),
+            kind,
+            tags: None,
+            detail: None,
+            uri: lsp::Uri::from_file_path(path).unwrap().into(),
+            range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, line_len)),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(0, name_start),
+                lsp::Position::new(0, name_start + name.len() as u32),
+            ),
+            data: Some(json!({ "id": name })),
+        }
+    }
+
+    fake_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 12)
+            );
+            Ok(Some(vec![lsp_item(
+                "Shape",
+                lsp::SymbolKind::INTERFACE,
+                "/dir/a.ts",
+                10,
+                18,
+            )]))
+        },
+    );
+    fake_server.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>(
+        |params, _| async move {
+            // The item is passed back to the server as it was received.
+            assert_eq!(params.item.data, Some(json!({ "id": "Shape" })));
+            Ok(Some(vec![lsp_item(
+                "Circle",
+                lsp::SymbolKind::CLASS,
+                "/dir/b.ts",
+                6,
+                32,
+            )]))
+        },
+    );
+    fake_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "Circle");
+            Ok(Some(vec![lsp_item(
+                "Shape",
+                lsp::SymbolKind::INTERFACE,
+                "/dir/a.ts",
+                10,
+                18,
+            )]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, Point::new(0, 12), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let shape_item = items[0].clone();
+    assert_eq!(shape_item.name, "Shape");
+    assert_eq!(shape_item.kind, lsp::SymbolKind::INTERFACE);
+
+    let subtypes = project
+        .update(cx, |project, cx| project.subtypes(&shape_item, cx))
+        .await
+        .unwrap();
+    assert_eq!(subtypes.len(), 1);
+    let circle_item = subtypes[0].clone();
+    assert_eq!(circle_item.kind, lsp::SymbolKind::CLASS);
+    cx.update(|cx| {
+        let circle_buffer = circle_item.location.buffer.read(cx);
+        assert_eq!(
+            circle_buffer.file().unwrap().path().as_ref(),
+            Path::new("b.ts")
+        );
+        assert_eq!(
+            circle_buffer
+                .text_for_range(circle_item.location.range.clone())
+                .collect::<String>(),
+            "Circle"
+        );
+    });
+
+    let supertypes = project
+        .update(cx, |project, cx| project.supertypes(&circle_item, cx))
+        .await
+        .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    assert_eq!(supertypes[0].name, "Shape");
+    assert_eq!(supertypes[0].location.buffer, buffer);
+}
+
+#[gpui::test]
+async fn test_semantic_tokens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let mut a = b!();",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![
+                                    lsp::SemanticTokenType::VARIABLE,
+                                    lsp::SemanticTokenType::MACRO,
+                                ],
+                                token_modifiers: vec![
+                                    lsp::SemanticTokenModifier::DECLARATION,
+                                    lsp::SemanticTokenModifier::new("mutable"),
+                                ],
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    fake_server.handle_request::<lsp::request::SemanticTokensFullRequest, _, _>(
+        |_, _| async move {
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: Some("1".into()),
+                    data: vec![
+                        // `a`, a mutable variable declaration
+                        lsp::SemanticToken {
+                            delta_line: 0,
+                            delta_start: 8,
+                            length: 1,
+                            token_type: 0,
+                            token_modifiers_bitset: 0b11,
+                        },
+                        // `b`, a macro call
+                        lsp::SemanticToken {
+                            delta_line: 0,
+                            delta_start: 4,
+                            length: 1,
+                            token_type: 1,
+                            token_modifiers_bitset: 0,
+                        },
+                    ],
+                },
+            )))
+        },
+    );
+    fake_server.handle_request::<lsp::request::SemanticTokensFullDeltaRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(params.previous_result_id, "1");
+            // Drop the macro token.
+            Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+                lsp::SemanticTokensDelta {
+                    result_id: Some("2".into()),
+                    edits: vec![lsp::SemanticTokensEdit {
+                        start: 5,
+                        delete_count: 5,
+                        data: None,
+                    }],
+                },
+            )))
+        },
+    );
+
+    let token_texts = |tokens: &[SemanticToken], cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            tokens
+                .iter()
+                .map(|token| {
+                    (
+                        buffer
+                            .text_for_range(token.range.clone())
+                            .collect::<String>(),
+                        token.token_type.to_string(),
+                        token
+                            .modifiers
+                            .iter()
+                            .map(|modifier| modifier.to_string())
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+    };
+
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        token_texts(&tokens, cx),
+        vec![
+            (
+                "a".to_string(),
+                "variable".to_string(),
+                vec!["declaration".to_string(), "mutable".to_string()]
+            ),
+            ("b".to_string(), "macro".to_string(), vec![]),
+        ]
+    );
+
+    // The second request is sent as a delta against the first response.
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        token_texts(&tokens, cx),
+        vec![(
+            "a".to_string(),
+            "variable".to_string(),
+            vec!["declaration".to_string(), "mutable".to_string()]
+        )]
+    );
+}
+
+#[gpui::test]
+async fn test_code_lens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() {}\n\nfn b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    fake_server.handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _| async move {
+        Ok(Some(vec![
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4)),
+                command: Some(lsp::Command {
+                    title: "Run".into(),
+                    command: "run".into(),
+                    arguments: None,
+                }),
+                data: None,
+            },
+            // The command of this lens is only provided once it is resolved.
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(2, 3), lsp::Position::new(2, 4)),
+                command: None,
+                data: Some(json!({ "id": "b" })),
+            },
+        ]))
+    });
+    fake_server.handle_request::<lsp::request::CodeLensResolve, _, _>(|lens, _| async move {
+        assert_eq!(lens.data, Some(json!({ "id": "b" })));
+        Ok(lsp::CodeLens {
+            command: Some(lsp::Command {
+                title: "1 reference".into(),
+                command: "references".into(),
+                arguments: None,
+            }),
+            ..lens
+        })
+    });
+
+    let lenses = project
+        .update(cx, |project, cx| project.code_lens(&buffer, cx))
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            lenses
+                .iter()
+                .map(|lens| (
+                    buffer
+                        .text_for_range(lens.range.clone())
+                        .collect::<String>(),
+                    lens.lsp_lens.command.as_ref().unwrap().title.clone(),
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a".to_string(), "Run".to_string()),
+                ("b".to_string(), "1 reference".to_string()),
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_folding_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() {\n    b();\n}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    fake_server.handle_request::<lsp::request::FoldingRangeRequest, _, _>(|_, _| async move {
+        Ok(Some(vec![
+            lsp::FoldingRange {
+                start_line: 0,
+                start_character: Some(8),
+                end_line: 1,
+                end_character: Some(8),
+                kind: None,
+                collapsed_text: None,
+            },
+            // Ranges beyond the end of the buffer are ignored.
+            lsp::FoldingRange {
+                start_line: 1,
+                start_character: None,
+                end_line: 5,
+                end_character: None,
+                kind: None,
+                collapsed_text: None,
+            },
+        ]))
+    });
+
+    let ranges = project
+        .update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|range| range.start.to_point(buffer)..range.end.to_point(buffer))
+                .collect::<Vec<_>>(),
+            vec![Point::new(0, 8)..Point::new(1, 8)]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_document_colors(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "const RED: &str = \"#ff0000\";\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                color_provider: Some(lsp::ColorProviderCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    let red = lsp::Color {
+        red: 1.,
+        green: 0.,
+        blue: 0.,
+        alpha: 1.,
+    };
+    let blue = lsp::Color {
+        red: 0.,
+        green: 0.,
+        blue: 1.,
+        alpha: 1.,
+    };
+    let color_range = lsp::Range::new(lsp::Position::new(0, 19), lsp::Position::new(0, 26));
+    fake_server.handle_request::<lsp::request::DocumentColor, _, _>(move |_, _| async move {
+        Ok(vec![lsp::ColorInformation {
+            range: color_range,
+            color: red,
+        }])
+    });
+    fake_server.handle_request::<lsp::request::ColorPresentationRequest, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.range, color_range);
+            assert_eq!(params.color, blue);
+            Ok(vec![
+                lsp::ColorPresentation {
+                    label: "#0000ff".to_string(),
+                    text_edit: None,
+                    additional_text_edits: None,
+                },
+                lsp::ColorPresentation {
+                    label: "rgb(0, 0, 255)".to_string(),
+                    text_edit: Some(lsp::TextEdit {
+                        range: lsp::Range::new(
+                            lsp::Position::new(0, 18),
+                            lsp::Position::new(0, 27),
+                        ),
+                        new_text: "rgb(0, 0, 255)".to_string(),
+                    }),
+                    additional_text_edits: None,
+                },
+            ])
+        },
+    );
+
+    let colors = project
+        .update(cx, |project, cx| project.document_colors(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(colors.len(), 1);
+    assert_eq!(colors[0].color, red);
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            colors[0].range.to_point(buffer),
+            Point::new(0, 19)..Point::new(0, 26)
+        );
+    });
+
+    let presentations = project
+        .update(cx, |project, cx| {
+            project.color_presentations(&buffer, &colors[0], blue, cx)
+        })
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            presentations
+                .iter()
+                .map(|presentation| {
+                    (
+                        presentation.label.as_str(),
+                        presentation
+                            .edits
+                            .iter()
+                            .map(|(range, text)| (range.to_point(buffer), text.as_str()))
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "#0000ff",
+                    vec![(Point::new(0, 19)..Point::new(0, 26), "#0000ff")]
+                ),
+                (
+                    "rgb(0, 0, 255)",
+                    vec![(Point::new(0, 18)..Point::new(0, 27), "rgb(0, 0, 255)")]
+                ),
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_document_links(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "// https://zed.dev\nmod b;\n",
+            "b.rs": "const ONE: usize = 1;\nconst TWO: usize = 2;\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                }),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    fake_server.handle_request::<lsp::request::DocumentLinkRequest, _, _>(|_, _| async move {
+        Ok(Some(vec![
+            lsp::DocumentLink {
+                range: lsp::Range::new(lsp::Position::new(1, 4), lsp::Position::new(1, 5)),
+                target: None,
+                tooltip: None,
+                data: None,
+            },
+            lsp::DocumentLink {
+                range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 18)),
+                target: Some("https://zed.dev".parse().unwrap()),
+                tooltip: None,
+                data: None,
+            },
+        ]))
+    });
+    fake_server.handle_request::<lsp::request::DocumentLinkResolve, _, _>(
+        |mut link, _| async move {
+            link.target = Some("file:///dir/b.rs#L2,7".parse().unwrap());
+            Ok(link)
+        },
+    );
+
+    let links = project
+        .update(cx, |project, cx| project.document_links(&buffer, cx))
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            links
+                .iter()
+                .map(|link| link.range.to_point(buffer))
+                .collect::<Vec<_>>(),
+            vec![
+                Point::new(0, 3)..Point::new(0, 18),
+                Point::new(1, 4)..Point::new(1, 5)
+            ]
+        );
+    });
+
+    let target = project
+        .update(cx, |project, cx| {
+            project.open_document_link(&buffer, &links[0], cx)
+        })
+        .await
+        .unwrap();
+    assert!(
+        matches!(&target, Some(DocumentLinkTarget::Url(url)) if url.starts_with("https://zed.dev"))
+    );
+
+    let target = project
+        .update(cx, |project, cx| {
+            project.open_document_link(&buffer, &links[1], cx)
+        })
+        .await
+        .unwrap();
+    let Some(DocumentLinkTarget::Location(location)) = target else {
+        panic!("expected a location, got {target:?}");
+    };
+    location.buffer.read_with(cx, |target_buffer, _| {
+        assert_eq!(
+            target_buffer.file().unwrap().path().as_ref(),
+            Path::new("b.rs")
+        );
+        assert_eq!(
+            location.range.start.to_point(target_buffer),
+            Point::new(1, 6)
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_pull_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let a = 1;",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        identifier: None,
+                        inter_file_dependencies: false,
+                        workspace_diagnostics: false,
+                        work_done_progress_options: Default::default(),
+                    },
+                )),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    // The server only sends the diagnostics again when they changed since the last pull.
+    let previous_result_ids = Arc::new(Mutex::new(Vec::new()));
+    fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>({
+        let previous_result_ids = previous_result_ids.clone();
+        move |params, _| {
+            previous_result_ids
+                .lock()
+                .push(params.previous_result_id.clone());
+            async move {
+                let report = match params.previous_result_id {
+                    Some(result_id) => lsp::DocumentDiagnosticReport::Unchanged(
+                        lsp::RelatedUnchangedDocumentDiagnosticReport {
+                            related_documents: None,
+                            unchanged_document_diagnostic_report:
+                                lsp::UnchangedDocumentDiagnosticReport { result_id },
+                        },
+                    ),
+                    None => lsp::DocumentDiagnosticReport::Full(
+                        lsp::RelatedFullDocumentDiagnosticReport {
+                            related_documents: None,
+                            full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                                result_id: Some("1".to_string()),
+                                items: vec![lsp::Diagnostic {
+                                    range: lsp::Range::new(
+                                        lsp::Position::new(0, 4),
+                                        lsp::Position::new(0, 5),
+                                    ),
+                                    severity: Some(lsp::DiagnosticSeverity::ERROR),
+                                    message: "error 1".to_string(),
+                                    ..Default::default()
+                                }],
+                            },
+                        },
+                    ),
+                };
+                Ok(lsp::DocumentDiagnosticReportResult::Report(report))
+            }
+        }
+    });
+
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    cx.executor()
+        .advance_clock(pull_diagnostics::PULL_DIAGNOSTICS_DEBOUNCE_TIMEOUT);
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        let chunks = chunks_with_diagnostics(buffer, 0..buffer.len());
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|(s, d)| (s.as_str(), *d))
+                .collect::<Vec<_>>(),
+            &[
+                ("\nlet ", None),
+                ("a", Some(DiagnosticSeverity::ERROR)),
+                (" = 1;", None),
+            ]
+        );
+    });
+
+    // Edits are pulled for after a debounce, and unchanged reports keep the previous diagnostics.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(buffer.len()..buffer.len(), "\n")], None, cx)
+    });
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(buffer.len()..buffer.len(), "\n")], None, cx)
+    });
+    cx.executor()
+        .advance_clock(pull_diagnostics::PULL_DIAGNOSTICS_DEBOUNCE_TIMEOUT);
+    cx.executor().run_until_parked();
+    assert_eq!(
+        previous_result_ids.lock().last().cloned(),
+        Some(Some("1".to_string()))
+    );
+    buffer.update(cx, |buffer, _| {
+        let chunks = chunks_with_diagnostics(buffer, 0..buffer.len());
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|(s, d)| (s.as_str(), *d))
+                .collect::<Vec<_>>(),
+            &[
+                ("\nlet ", None),
+                ("a", Some(DiagnosticSeverity::ERROR)),
+                (" = 1;\n\n", None),
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_lsp_file_operations(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "",
+            "main.rs": "mod a;\n",
+            "notes.txt": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let rust_files = lsp::FileOperationRegistrationOptions {
+        filters: vec![lsp::FileOperationFilter {
+            scheme: Some("file".to_string()),
+            pattern: lsp::FileOperationPattern {
+                glob: "**/*.rs".to_string(),
+                matches: Some(lsp::FileOperationPatternKind::File),
+                options: None,
+            },
+        }],
+    };
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                workspace: Some(lsp::WorkspaceServerCapabilities {
+                    workspace_folders: None,
+                    file_operations: Some(lsp::WorkspaceFileOperationsServerCapabilities {
+                        will_rename: Some(rust_files.clone()),
+                        did_rename: Some(rust_files),
+                        ..Default::default()
+                    }),
+                }),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/main.rs", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let mut fake_server = fake_language_servers
+        .next()
+        .await
+        .expect("failed to get the language server");
+
+    fake_server.handle_request::<lsp::request::WillRenameFiles, _, _>(|params, _| async move {
+        assert_eq!(
+            params.files,
+            vec![lsp::FileRename {
+                old_uri: "file:///dir/a.rs".to_string(),
+                new_uri: "file:///dir/b.rs".to_string(),
+            }]
+        );
+        Ok(Some(lsp::WorkspaceEdit {
+            changes: Some(
+                [(
+                    Uri::from_file_path("/dir/main.rs").unwrap().into(),
+                    vec![lsp::TextEdit::new(
+                        lsp::Range::new(lsp::Position::new(0, 4), lsp::Position::new(0, 5)),
+                        "b".to_string(),
+                    )],
+                )]
+                .into_iter()
+                .collect(),
+            ),
+            ..Default::default()
+        }))
+    });
+
+    let entry_id = |path: &'static str, cx: &mut gpui::TestAppContext| {
+        project.update(cx, |project, cx| {
+            let worktree = project.worktrees().next().unwrap();
+            worktree.read(cx).entry_for_path(path).unwrap().id
+        })
+    };
+
+    // Renaming a module updates the imports of the files that use it.
+    let a_id = entry_id("a.rs", cx);
+    project
+        .update(cx, |project, cx| {
+            project.rename_entry(a_id, Path::new("b.rs"), cx)
+        })
+        .await
+        .unwrap();
+    let params = fake_server
+        .receive_notification::<lsp::notification::DidRenameFiles>()
+        .await;
+    assert_eq!(
+        params.files,
+        vec![lsp::FileRename {
+            old_uri: "file:///dir/a.rs".to_string(),
+            new_uri: "file:///dir/b.rs".to_string(),
+        }]
+    );
+    buffer.read_with(cx, |buffer, _| assert_eq!(buffer.text(), "mod b;\n"));
+
+    // Files the server did not register interest in are renamed without asking it.
+    let notes_id = entry_id("notes.txt", cx);
+    project
+        .update(cx, |project, cx| {
+            project.rename_entry(notes_id, Path::new("todo.txt"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    buffer.read_with(cx, |buffer, _| assert_eq!(buffer.text(), "mod b;\n"));
+}
+
+#[gpui::test]
+async fn test_multiple_language_server_actions(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.tsx": "a",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(tsx_lang());
+    let language_server_names = [
+        "TypeScriptServer",
+        "TailwindServer",
+        "ESLintServer",
+        "NoActionsCapabilitiesServer",
+    ];
+    let mut fake_tsx_language_servers = language_registry.register_specific_fake_lsp_adapter(
+        "tsx",
+        true,
+        FakeLspAdapter {
+            name: &language_server_names[0],
+            capabilities: lsp::ServerCapabilities {
+                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+    let _a = language_registry.register_specific_fake_lsp_adapter(
+        "tsx",
+        false,
+        FakeLspAdapter {
+            name: &language_server_names[1],
+            capabilities: lsp::ServerCapabilities {
+                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+    let _b = language_registry.register_specific_fake_lsp_adapter(
+        "tsx",
+        false,
+        FakeLspAdapter {
+            name: &language_server_names[2],
+            capabilities: lsp::ServerCapabilities {
+                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+    let _c = language_registry.register_specific_fake_lsp_adapter(
+        "tsx",
+        false,
+        FakeLspAdapter {
+            name: &language_server_names[3],
+            capabilities: lsp::ServerCapabilities {
+                code_action_provider: None,
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.tsx", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let mut servers_with_actions_requests = HashMap::default();
+    for i in 0..language_server_names.len() {
+        let new_server = fake_tsx_language_servers.next().await.unwrap_or_else(|| {
+            panic!(
+                "Failed to get language server #{i} with name {}",
+                &language_server_names[i]
+            )
+        });
+        let new_server_name = new_server.server.name();
+        assert!(
+            !servers_with_actions_requests.contains_key(new_server_name),
+            "Unexpected: initialized server with the same name twice. Name: `{new_server_name}`"
+        );
+        let new_server_name = new_server_name.to_string();
+        match new_server_name.as_str() {
+            "TailwindServer" | "TypeScriptServer" => {
+                servers_with_actions_requests.insert(
+                    new_server_name.clone(),
+                    new_server.handle_request::<lsp::request::CodeActionRequest, _, _>(
                         move |_, _| {
                             let name = new_server_name.clone();
                             async move {
@@ -5201,6 +6622,122 @@ async fn test_reordering_worktrees(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_breakpoints(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn one() {}\nfn two() {}\nfn three() {}\nfn four() {}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    project.update(cx, |project, cx| {
+        project.toggle_breakpoint(&buffer, 1, cx);
+        project.toggle_breakpoint(&buffer, 3, cx);
+    });
+    assert_eq!(
+        project.read_with(cx, |project, cx| project.breakpoint_rows(&buffer, cx)),
+        vec![1, 3]
+    );
+
+    // Breakpoints follow the lines they were set on.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "// one\n")], None, cx));
+    assert_eq!(
+        project.read_with(cx, |project, cx| project.breakpoint_rows(&buffer, cx)),
+        vec![2, 4]
+    );
+
+    // Toggling a breakpoint again removes it.
+    project.update(cx, |project, cx| project.toggle_breakpoint(&buffer, 2, cx));
+    assert_eq!(
+        project.read_with(cx, |project, cx| project.breakpoint_rows(&buffer, cx)),
+        vec![4]
+    );
+    assert_eq!(
+        project.read_with(cx, |project, cx| project.all_breakpoints(cx)),
+        [(PathBuf::from("/dir/a.rs"), vec![4])]
+            .into_iter()
+            .collect::<HashMap<_, _>>()
+    );
+
+    project.update(cx, |project, cx| project.toggle_breakpoint(&buffer, 4, cx));
+    assert!(project.read_with(cx, |project, cx| project.all_breakpoints(cx).is_empty()));
+}
+
+#[gpui::test]
+async fn test_test_statuses(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "#[test]\nfn one() {}\n#[test]\nfn two() {}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    project.update(cx, |project, cx| {
+        let snapshot = buffer.read(cx).snapshot();
+        let statuses = vec![
+            (snapshot.anchor_before(Point::new(1, 0)), TestStatus::Passed),
+            (snapshot.anchor_before(Point::new(3, 0)), TestStatus::Failed),
+        ];
+        project.set_test_statuses(&buffer, statuses, cx);
+    });
+    let statuses = |cx: &mut gpui::TestAppContext| {
+        project.read_with(cx, |project, cx| {
+            (0..5)
+                .map(|row| project.test_status(&buffer, row, cx))
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        statuses(cx),
+        [
+            None,
+            Some(TestStatus::Passed),
+            None,
+            Some(TestStatus::Failed),
+            None
+        ]
+    );
+
+    // Statuses follow the tests they were reported for.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "// one\n")], None, cx));
+    assert_eq!(
+        statuses(cx),
+        [
+            None,
+            None,
+            Some(TestStatus::Passed),
+            None,
+            Some(TestStatus::Failed)
+        ]
+    );
+
+    project.update(cx, |project, cx| {
+        project.set_test_statuses(&buffer, Vec::new(), cx)
+    });
+    assert_eq!(statuses(cx), [None; 5]);
+}
+
 async fn search(
     project: &Model<Project>,
     query: SearchQuery,
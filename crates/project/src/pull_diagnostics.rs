@@ -0,0 +1,347 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use collections::HashMap;
+use gpui::{Model, ModelContext, Task};
+use language::{Buffer, CachedLspAdapter, File as _};
+use lsp::{LanguageServerId, Uri};
+use text::BufferId;
+use util::ResultExt;
+
+use crate::{File, LanguageServerState, Project};
+
+pub(crate) const PULL_DIAGNOSTICS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// The state of the `textDocument/diagnostic` and `workspace/diagnostic` requests sent to the
+/// language servers that prefer diagnostics to be pulled rather than published.
+#[derive(Default)]
+pub(crate) struct PullDiagnosticsState {
+    /// The ids of the last reports each server sent for a file, which let it answer the next
+    /// pull for that file with an unchanged report.
+    result_ids: HashMap<LanguageServerId, HashMap<PathBuf, String>>,
+    document_tasks: HashMap<(BufferId, LanguageServerId), Task<()>>,
+    workspace_tasks: HashMap<LanguageServerId, Task<()>>,
+}
+
+impl PullDiagnosticsState {
+    pub(crate) fn buffer_closed(&mut self, buffer_id: BufferId) {
+        self.document_tasks
+            .retain(|(task_buffer_id, _), _| *task_buffer_id != buffer_id);
+    }
+
+    pub(crate) fn server_stopped(&mut self, server_id: LanguageServerId) {
+        self.result_ids.remove(&server_id);
+        self.document_tasks
+            .retain(|(_, task_server_id), _| *task_server_id != server_id);
+        self.workspace_tasks.remove(&server_id);
+    }
+}
+
+fn diagnostic_options(capabilities: &lsp::ServerCapabilities) -> Option<&lsp::DiagnosticOptions> {
+    match capabilities.diagnostic_provider.as_ref()? {
+        lsp::DiagnosticServerCapabilities::Options(options) => Some(options),
+        lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+            Some(&options.diagnostic_options)
+        }
+    }
+}
+
+impl Project {
+    /// Pulls the diagnostics of the given buffer from each of its language servers that
+    /// supports `textDocument/diagnostic`, replacing any pull of that buffer still in flight.
+    pub(crate) fn pull_diagnostics(
+        &mut self,
+        buffer: &Model<Buffer>,
+        debounce: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if !self.is_local() {
+            return;
+        }
+        let buffer_id = buffer.read(cx).remote_id();
+        let Some(abs_path) = File::from_dyn(buffer.read(cx).file())
+            .and_then(|file| Some(file.as_local()?.abs_path(cx)))
+        else {
+            return;
+        };
+        let servers = self
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .filter(|(_, server)| diagnostic_options(server.capabilities()).is_some())
+            .map(|(adapter, server)| (adapter.clone(), server.clone()))
+            .collect::<Vec<_>>();
+
+        for (adapter, server) in servers {
+            let server_id = server.server_id();
+            let abs_path = abs_path.clone();
+            let task = cx.spawn(move |this, mut cx| async move {
+                if debounce {
+                    cx.background_executor()
+                        .timer(PULL_DIAGNOSTICS_DEBOUNCE_TIMEOUT)
+                        .await;
+                }
+
+                let Some((version, previous_result_id)) = this
+                    .update(&mut cx, |this, _| {
+                        let version = this
+                            .buffer_snapshots
+                            .get(&buffer_id)?
+                            .get(&server_id)?
+                            .last()?
+                            .version;
+                        let previous_result_id = this
+                            .pull_diagnostics
+                            .result_ids
+                            .get(&server_id)
+                            .and_then(|result_ids| result_ids.get(&abs_path))
+                            .cloned();
+                        Some((version, previous_result_id))
+                    })
+                    .ok()
+                    .flatten()
+                else {
+                    return;
+                };
+                let Some(uri) = Uri::from_file_path(&abs_path).log_err() else {
+                    return;
+                };
+                let identifier = diagnostic_options(server.capabilities())
+                    .and_then(|options| options.identifier.clone());
+                let report = server
+                    .request::<lsp::request::DocumentDiagnosticRequest>(
+                        lsp::DocumentDiagnosticParams {
+                            text_document: lsp::TextDocumentIdentifier::new(uri.clone().into()),
+                            identifier,
+                            previous_result_id,
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    )
+                    .await
+                    .context("document diagnostic LSP request")
+                    .log_err();
+
+                this.update(&mut cx, |this, cx| {
+                    if let Some(report) = report {
+                        this.apply_document_diagnostic_report(
+                            &adapter, server_id, uri, version, report, cx,
+                        );
+                    }
+                    this.pull_diagnostics
+                        .document_tasks
+                        .remove(&(buffer_id, server_id));
+                })
+                .ok();
+            });
+            self.pull_diagnostics
+                .document_tasks
+                .insert((buffer_id, server_id), task);
+        }
+    }
+
+    /// Pulls the diagnostics of the whole workspace from the given language server, if it
+    /// supports `workspace/diagnostic`, replacing any such pull still in flight.
+    pub(crate) fn pull_workspace_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(LanguageServerState::Running {
+            adapter, server, ..
+        }) = self.language_servers.get(&server_id)
+        else {
+            return;
+        };
+        let Some(options) = diagnostic_options(server.capabilities()) else {
+            return;
+        };
+        if !options.workspace_diagnostics {
+            return;
+        }
+
+        let adapter = adapter.clone();
+        let server = server.clone();
+        let identifier = options.identifier.clone();
+        let previous_result_ids = self
+            .pull_diagnostics
+            .result_ids
+            .get(&server_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|(abs_path, result_id)| {
+                Some(lsp::PreviousResultId {
+                    uri: Uri::from_file_path(abs_path).log_err()?.into(),
+                    value: result_id.clone(),
+                })
+            })
+            .collect();
+        let task = cx.spawn(move |this, mut cx| async move {
+            let report = server
+                .request::<lsp::request::WorkspaceDiagnosticRequest>(
+                    lsp::WorkspaceDiagnosticParams {
+                        identifier,
+                        previous_result_ids,
+                        work_done_progress_params: Default::default(),
+                        partial_result_params: Default::default(),
+                    },
+                )
+                .await
+                .context("workspace diagnostic LSP request")
+                .log_err();
+
+            this.update(&mut cx, |this, cx| {
+                if let Some(report) = report {
+                    this.apply_workspace_diagnostic_report(&adapter, server_id, report, cx);
+                }
+                this.pull_diagnostics.workspace_tasks.remove(&server_id);
+            })
+            .ok();
+        });
+        self.pull_diagnostics
+            .workspace_tasks
+            .insert(server_id, task);
+    }
+
+    /// Pulls the diagnostics of every open buffer and of the workspace from the given language
+    /// server again, as requested by `workspace/diagnostic/refresh`.
+    pub(crate) fn refresh_pulled_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffers = self
+            .opened_buffers
+            .values()
+            .filter_map(|buffer| buffer.upgrade())
+            .filter(|buffer| {
+                self.language_server_ids_for_buffer(buffer.read(cx), cx)
+                    .contains(&server_id)
+            })
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.pull_diagnostics(&buffer, false, cx);
+        }
+        self.pull_workspace_diagnostics(server_id, cx);
+    }
+
+    fn apply_document_diagnostic_report(
+        &mut self,
+        adapter: &Arc<CachedLspAdapter>,
+        server_id: LanguageServerId,
+        uri: Uri,
+        version: i32,
+        report: lsp::DocumentDiagnosticReportResult,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let related_documents = match report {
+            lsp::DocumentDiagnosticReportResult::Report(lsp::DocumentDiagnosticReport::Full(
+                report,
+            )) => {
+                self.apply_diagnostic_report_kind(
+                    adapter,
+                    server_id,
+                    uri,
+                    Some(version),
+                    lsp::DocumentDiagnosticReportKind::Full(report.full_document_diagnostic_report),
+                    cx,
+                );
+                report.related_documents
+            }
+            lsp::DocumentDiagnosticReportResult::Report(
+                lsp::DocumentDiagnosticReport::Unchanged(report),
+            ) => {
+                self.apply_diagnostic_report_kind(
+                    adapter,
+                    server_id,
+                    uri,
+                    Some(version),
+                    lsp::DocumentDiagnosticReportKind::Unchanged(
+                        report.unchanged_document_diagnostic_report,
+                    ),
+                    cx,
+                );
+                report.related_documents
+            }
+            lsp::DocumentDiagnosticReportResult::Partial(report) => report.related_documents,
+        };
+
+        for (uri, report) in related_documents.into_iter().flatten() {
+            self.apply_diagnostic_report_kind(adapter, server_id, uri.into(), None, report, cx);
+        }
+    }
+
+    fn apply_workspace_diagnostic_report(
+        &mut self,
+        adapter: &Arc<CachedLspAdapter>,
+        server_id: LanguageServerId,
+        report: lsp::WorkspaceDiagnosticReportResult,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let items = match report {
+            lsp::WorkspaceDiagnosticReportResult::Report(report) => report.items,
+            lsp::WorkspaceDiagnosticReportResult::Partial(report) => report.items,
+        };
+        for item in items {
+            let (uri, version, report) = match item {
+                lsp::WorkspaceDocumentDiagnosticReport::Full(report) => (
+                    report.uri,
+                    report.version,
+                    lsp::DocumentDiagnosticReportKind::Full(report.full_document_diagnostic_report),
+                ),
+                lsp::WorkspaceDocumentDiagnosticReport::Unchanged(report) => (
+                    report.uri,
+                    report.version,
+                    lsp::DocumentDiagnosticReportKind::Unchanged(
+                        report.unchanged_document_diagnostic_report,
+                    ),
+                ),
+            };
+            let version = version.and_then(|version| i32::try_from(version).ok());
+            self.apply_diagnostic_report_kind(adapter, server_id, uri.into(), version, report, cx);
+        }
+    }
+
+    /// Records the result id of a report and, unless the server reported the diagnostics of the
+    /// file to be unchanged, replaces the server's diagnostics for that file with the new ones.
+    fn apply_diagnostic_report_kind(
+        &mut self,
+        adapter: &Arc<CachedLspAdapter>,
+        server_id: LanguageServerId,
+        uri: Uri,
+        version: Option<i32>,
+        report: lsp::DocumentDiagnosticReportKind,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(abs_path) = uri.clone().to_file_path().log_err() else {
+            return;
+        };
+        let result_ids = self
+            .pull_diagnostics
+            .result_ids
+            .entry(server_id)
+            .or_default();
+        match report {
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                match report.result_id {
+                    Some(result_id) => result_ids.insert(abs_path, result_id),
+                    None => result_ids.remove(&abs_path),
+                };
+                let mut params = lsp::PublishDiagnosticsParams {
+                    uri: uri.into(),
+                    diagnostics: report.items,
+                    version,
+                };
+                adapter.process_diagnostics(&mut params);
+                self.update_diagnostics(
+                    server_id,
+                    params,
+                    &adapter.disk_based_diagnostic_sources,
+                    cx,
+                )
+                .log_err();
+            }
+            lsp::DocumentDiagnosticReportKind::Unchanged(report) => {
+                result_ids.insert(abs_path, report.result_id);
+            }
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use std::{mem, ops::Range, sync::Arc};
+
+use anyhow::{anyhow, Context as _, Result};
+use client::{proto, Client, TypedEnvelope};
+use gpui::{AsyncAppContext, Model, ModelContext, Task, WeakModel};
+use language::{
+    proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
+    Anchor, Bias, Buffer, BufferSnapshot, PointUtf16, Unclipped,
+};
+use lsp::LanguageServer;
+use text::BufferId;
+
+use crate::Project;
+
+/// A range of a buffer classified by a language server's semantic tokens.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SemanticToken {
+    pub range: Range<Anchor>,
+    /// The token type, as named in the server's legend (e.g. `variable` or `macro`).
+    pub token_type: Arc<str>,
+    /// The token modifiers, as named in the server's legend (e.g. `mutable` or `unsafe`).
+    pub modifiers: Vec<Arc<str>>,
+}
+
+/// The last semantic tokens a language server sent for a buffer, kept in their raw form
+/// so that subsequent `textDocument/semanticTokens/full/delta` responses can be applied to them.
+#[derive(Debug, Default)]
+pub(crate) struct CachedSemanticTokens {
+    result_id: Option<String>,
+    data: Vec<lsp::SemanticToken>,
+}
+
+struct SemanticTokensLegend {
+    token_types: Vec<Arc<str>>,
+    token_modifiers: Vec<Arc<str>>,
+    supports_delta: bool,
+}
+
+impl SemanticTokensLegend {
+    fn new(capabilities: &lsp::ServerCapabilities) -> Option<Self> {
+        let options = match capabilities.semantic_tokens_provider.as_ref()? {
+            lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => options,
+            lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+                &options.semantic_tokens_options
+            }
+        };
+        let supports_delta = match options.full.as_ref()? {
+            lsp::SemanticTokensFullOptions::Bool(false) => return None,
+            lsp::SemanticTokensFullOptions::Bool(true) => false,
+            lsp::SemanticTokensFullOptions::Delta { delta } => delta.unwrap_or(false),
+        };
+        Some(Self {
+            token_types: options
+                .legend
+                .token_types
+                .iter()
+                .map(|token_type| Arc::from(token_type.as_str()))
+                .collect(),
+            token_modifiers: options
+                .legend
+                .token_modifiers
+                .iter()
+                .map(|modifier| Arc::from(modifier.as_str()))
+                .collect(),
+            supports_delta,
+        })
+    }
+
+    fn decode(&self, data: &[lsp::SemanticToken], snapshot: &BufferSnapshot) -> Vec<SemanticToken> {
+        let mut tokens = Vec::with_capacity(data.len());
+        let mut line = 0;
+        let mut start = 0;
+        for token in data {
+            if token.delta_line == 0 {
+                start += token.delta_start;
+            } else {
+                line += token.delta_line;
+                start = token.delta_start;
+            }
+            let Some(token_type) = self.token_types.get(token.token_type as usize) else {
+                continue;
+            };
+            let modifiers = self
+                .token_modifiers
+                .iter()
+                .enumerate()
+                .filter(|(ix, _)| *ix < 32 && token.token_modifiers_bitset & (1 << ix) != 0)
+                .map(|(_, modifier)| modifier.clone())
+                .collect();
+            let range_start =
+                snapshot.clip_point_utf16(Unclipped(PointUtf16::new(line, start)), Bias::Left);
+            let range_end = snapshot.clip_point_utf16(
+                Unclipped(PointUtf16::new(line, start + token.length)),
+                Bias::Left,
+            );
+            if range_start == range_end {
+                continue;
+            }
+            tokens.push(SemanticToken {
+                range: snapshot.anchor_after(range_start)..snapshot.anchor_before(range_end),
+                token_type: token_type.clone(),
+                modifiers,
+            });
+        }
+        tokens
+    }
+}
+
+/// Applies the edits of a `textDocument/semanticTokens/full/delta` response to the previously
+/// received tokens. Edit offsets are expressed in the flat integer encoding of the tokens.
+fn apply_semantic_token_edits(
+    data: &mut Vec<lsp::SemanticToken>,
+    mut edits: Vec<lsp::SemanticTokensEdit>,
+) {
+    const INTEGERS_PER_TOKEN: usize = 5;
+
+    edits.sort_by_key(|edit| edit.start);
+    for edit in edits.into_iter().rev() {
+        let start = (edit.start as usize / INTEGERS_PER_TOKEN).min(data.len());
+        let end = (start + edit.delete_count as usize / INTEGERS_PER_TOKEN).min(data.len());
+        data.splice(start..end, edit.data.unwrap_or_default());
+    }
+}
+
+impl Project {
+    /// Returns the semantic tokens of the given buffer, as reported by the first of its language
+    /// servers that provides them. Tokens are requested as a delta against the previous response
+    /// whenever the server supports it.
+    pub fn semantic_tokens(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<SemanticToken>>> {
+        if self.is_local() {
+            let buffer = buffer_handle.read(cx);
+            let Some((server, legend)) =
+                self.language_servers_for_buffer(buffer, cx)
+                    .find_map(|(_, server)| {
+                        let legend = SemanticTokensLegend::new(server.capabilities())?;
+                        Some((server.clone(), legend))
+                    })
+            else {
+                return Task::ready(Ok(Vec::new()));
+            };
+            let Some(abs_path) = buffer
+                .file()
+                .and_then(|file| file.as_local())
+                .map(|file| file.abs_path(cx))
+            else {
+                return Task::ready(Ok(Vec::new()));
+            };
+            let Ok(uri) = lsp::Uri::from_file_path(&abs_path) else {
+                return Task::ready(Err(anyhow!("invalid buffer path {abs_path:?}")));
+            };
+
+            let buffer_id = buffer.remote_id();
+            let server_id = server.server_id();
+            let snapshot = buffer.snapshot();
+            let previous_result_id = self
+                .semantic_tokens
+                .get(&buffer_id)
+                .and_then(|tokens_by_server| tokens_by_server.get(&server_id))
+                .and_then(|cached| cached.result_id.clone())
+                .filter(|_| legend.supports_delta);
+            cx.spawn(move |this, mut cx| async move {
+                let text_document = lsp::TextDocumentIdentifier::new(uri.into());
+                let mut response = None;
+                if let Some(previous_result_id) = previous_result_id {
+                    response = request_semantic_tokens_delta(
+                        &this,
+                        &server,
+                        buffer_id,
+                        text_document.clone(),
+                        previous_result_id,
+                        &mut cx,
+                    )
+                    .await;
+                }
+                let (result_id, data) = match response {
+                    Some(response) => response,
+                    None => match server
+                        .request::<lsp::request::SemanticTokensFullRequest>(
+                            lsp::SemanticTokensParams {
+                                text_document,
+                                work_done_progress_params: Default::default(),
+                                partial_result_params: Default::default(),
+                            },
+                        )
+                        .await
+                        .context("semantic tokens LSP request")?
+                    {
+                        Some(lsp::SemanticTokensResult::Tokens(tokens)) => {
+                            (tokens.result_id, tokens.data)
+                        }
+                        Some(lsp::SemanticTokensResult::Partial(tokens)) => (None, tokens.data),
+                        None => (None, Vec::new()),
+                    },
+                };
+
+                let tokens = legend.decode(&data, &snapshot);
+                this.update(&mut cx, |this, _| {
+                    this.semantic_tokens
+                        .entry(buffer_id)
+                        .or_default()
+                        .insert(server_id, CachedSemanticTokens { result_id, data });
+                })?;
+                Ok(tokens)
+            })
+        } else if let Some(project_id) = self.remote_id() {
+            let client = self.client.clone();
+            let request = proto::GetSemanticTokens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                version: serialize_version(&buffer_handle.read(cx).version()),
+            };
+            let buffer_handle = buffer_handle.clone();
+            cx.spawn(move |_, mut cx| async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("semantic tokens proto request")?;
+                buffer_handle
+                    .update(&mut cx, |buffer, _| {
+                        buffer.wait_for_version(deserialize_version(&response.version))
+                    })?
+                    .await?;
+                response
+                    .tokens
+                    .into_iter()
+                    .map(|token| {
+                        let start = token
+                            .start
+                            .and_then(deserialize_anchor)
+                            .context("missing token start")?;
+                        let end = token
+                            .end
+                            .and_then(deserialize_anchor)
+                            .context("missing token end")?;
+                        Ok(SemanticToken {
+                            range: start..end,
+                            token_type: token.token_type.into(),
+                            modifiers: token.modifiers.into_iter().map(Arc::from).collect(),
+                        })
+                    })
+                    .collect()
+            })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    pub(crate) async fn handle_get_semantic_tokens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetSemanticTokens>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetSemanticTokensResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, _| {
+            this.opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+            })?
+            .await
+            .with_context(|| format!("waiting for version for buffer {}", buffer.entity_id()))?;
+
+        let tokens = this
+            .update(&mut cx, |project, cx| project.semantic_tokens(&buffer, cx))?
+            .await
+            .context("semantic tokens fetch")?;
+        Ok(proto::GetSemanticTokensResponse {
+            tokens: tokens
+                .into_iter()
+                .map(|token| proto::SemanticToken {
+                    start: Some(serialize_anchor(&token.range.start)),
+                    end: Some(serialize_anchor(&token.range.end)),
+                    token_type: token.token_type.to_string(),
+                    modifiers: token
+                        .modifiers
+                        .iter()
+                        .map(|modifier| modifier.to_string())
+                        .collect(),
+                })
+                .collect(),
+            version: serialize_version(&buffer.update(&mut cx, |buffer, _| buffer.version())?),
+        })
+    }
+}
+
+/// Requests the tokens as a delta against the previous response, returning `None` when the
+/// server could not provide one and the full tokens have to be requested instead.
+async fn request_semantic_tokens_delta(
+    this: &WeakModel<Project>,
+    server: &Arc<LanguageServer>,
+    buffer_id: BufferId,
+    text_document: lsp::TextDocumentIdentifier,
+    previous_result_id: String,
+    cx: &mut AsyncAppContext,
+) -> Option<(Option<String>, Vec<lsp::SemanticToken>)> {
+    let server_id = server.server_id();
+    let response = server
+        .request::<lsp::request::SemanticTokensFullDeltaRequest>(lsp::SemanticTokensDeltaParams {
+            text_document,
+            previous_result_id,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await;
+    match response {
+        Ok(Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens))) => {
+            Some((tokens.result_id, tokens.data))
+        }
+        Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta))) => this
+            .update(cx, |this, _| {
+                let cached = this
+                    .semantic_tokens
+                    .get_mut(&buffer_id)?
+                    .get_mut(&server_id)?;
+                let mut data = mem::take(cached).data;
+                apply_semantic_token_edits(&mut data, delta.edits);
+                Some((delta.result_id, data))
+            })
+            .ok()
+            .flatten(),
+        Ok(Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { .. })) | Ok(None) => None,
+        Err(error) => {
+            log::warn!("semantic tokens delta request failed: {error:#}");
+            None
+        }
+    }
+}
@@ -7,13 +7,13 @@ use std::{
     sync::Arc,
 };
 
-use anyhow::Result;
-use collections::{btree_map, BTreeMap, VecDeque};
+use anyhow::{Context as _, Result};
+use collections::{btree_map, BTreeMap, HashMap, VecDeque};
 use futures::{
     channel::mpsc::{unbounded, UnboundedSender},
     StreamExt,
 };
-use gpui::{AppContext, Context, Model, ModelContext, Task};
+use gpui::{AppContext, Context, EntityId, Model, ModelContext, Task};
 use itertools::Itertools;
 use language::{ContextProvider, Language, Location};
 use task::{
@@ -21,7 +21,7 @@ use task::{
     TaskVariables, VariableName,
 };
 use text::{Point, ToPoint};
-use util::{post_inc, NumericPrefixWithSuffix, ResultExt};
+use util::{paths::PathMatcher, post_inc, NumericPrefixWithSuffix, ResultExt};
 use worktree::WorktreeId;
 
 use crate::Project;
@@ -30,10 +30,20 @@ use crate::Project;
 pub struct Inventory {
     sources: Vec<SourceInInventory>,
     last_scheduled_tasks: VecDeque<(TaskSourceKind, ResolvedTask)>,
+    watched_tasks: HashMap<TaskId, WatchedTask>,
+    /// The tasks whose runs are shown in each open terminal, by the id of the terminal.
+    task_terminals: HashMap<EntityId, TaskId>,
     update_sender: UnboundedSender<()>,
     _update_pooler: Task<anyhow::Result<()>>,
 }
 
+/// A task rerun whenever files matching its `watch` globs change.
+struct WatchedTask {
+    task_source_kind: TaskSourceKind,
+    resolved_task: ResolvedTask,
+    matchers: Vec<PathMatcher>,
+}
+
 struct SourceInInventory {
     source: StaticSource,
     kind: TaskSourceKind,
@@ -57,19 +67,23 @@ pub enum TaskSourceKind {
     },
     /// Languages-specific tasks coming from extensions.
     Language { name: Arc<str> },
+    /// Tasks detected in a build manifest of the worktree, such as `package.json` scripts or `Makefile` targets.
+    Detected { id: WorktreeId, abs_path: PathBuf },
 }
 
 impl TaskSourceKind {
     pub fn abs_path(&self) -> Option<&Path> {
         match self {
-            Self::AbsPath { abs_path, .. } | Self::Worktree { abs_path, .. } => Some(abs_path),
+            Self::AbsPath { abs_path, .. }
+            | Self::Worktree { abs_path, .. }
+            | Self::Detected { abs_path, .. } => Some(abs_path),
             Self::UserInput | Self::Language { .. } => None,
         }
     }
 
     pub fn worktree(&self) -> Option<WorktreeId> {
         match self {
-            Self::Worktree { id, .. } => Some(*id),
+            Self::Worktree { id, .. } | Self::Detected { id, .. } => Some(*id),
             _ => None,
         }
     }
@@ -88,6 +102,9 @@ impl TaskSourceKind {
                 format!("{id_base}_{id}_{}", abs_path.display())
             }
             TaskSourceKind::Language { name } => format!("language_{name}"),
+            TaskSourceKind::Detected { id, abs_path } => {
+                format!("detected_{id}_{}", abs_path.display())
+            }
         }
     }
 }
@@ -107,6 +124,8 @@ impl Inventory {
             Self {
                 sources: Vec::new(),
                 last_scheduled_tasks: VecDeque::new(),
+                watched_tasks: HashMap::default(),
+                task_terminals: HashMap::default(),
                 update_sender,
                 _update_pooler,
             }
@@ -382,6 +401,106 @@ impl Inventory {
     pub fn delete_previously_used(&mut self, id: &TaskId) {
         self.last_scheduled_tasks.retain(|(_, task)| &task.id != id);
     }
+
+    /// Starts watching the paths matching the `watch` globs of the task, to rerun the task when they change.
+    /// Watching a task again replaces the resolved task to rerun. Returns whether the task is watched.
+    pub fn watch_task(
+        &mut self,
+        task_source_kind: TaskSourceKind,
+        resolved_task: ResolvedTask,
+        cx: &mut ModelContext<Self>,
+    ) -> bool {
+        let matchers = resolved_task
+            .original_task()
+            .watch
+            .iter()
+            .filter_map(|glob| {
+                PathMatcher::new(glob)
+                    .with_context(|| format!("parsing watch glob {glob:?}"))
+                    .log_err()
+            })
+            .collect::<Vec<_>>();
+        if matchers.is_empty() {
+            return false;
+        }
+        self.watched_tasks.insert(
+            resolved_task.id.clone(),
+            WatchedTask {
+                task_source_kind,
+                resolved_task,
+                matchers,
+            },
+        );
+        cx.notify();
+        true
+    }
+
+    /// Stops rerunning the task on changes, returning whether it was watched.
+    pub fn unwatch_task(&mut self, id: &TaskId, cx: &mut ModelContext<Self>) -> bool {
+        let was_watched = self.watched_tasks.remove(id).is_some();
+        if was_watched {
+            cx.notify();
+        }
+        was_watched
+    }
+
+    pub fn is_task_watched(&self, id: &TaskId) -> bool {
+        self.watched_tasks.contains_key(id)
+    }
+
+    /// Records that the terminal shows a run of the task now, instead of whatever it showed before.
+    pub fn task_terminal_opened(&mut self, terminal_id: EntityId, task_id: TaskId) {
+        self.task_terminals.insert(terminal_id, task_id);
+    }
+
+    /// Forgets the closed terminal, and stops watching its task unless another terminal still shows a run of it,
+    /// as there is no run left to replace. Returns whether the task got unwatched.
+    pub fn task_terminal_closed(
+        &mut self,
+        terminal_id: EntityId,
+        cx: &mut ModelContext<Self>,
+    ) -> bool {
+        let Some(task_id) = self.task_terminals.remove(&terminal_id) else {
+            return false;
+        };
+        if self.task_terminals.values().any(|id| id == &task_id) {
+            return false;
+        }
+        self.unwatch_task(&task_id, cx)
+    }
+
+    /// The watched tasks to rerun after the given paths, relative to the worktree root, have changed.
+    pub fn watched_tasks_for_paths<'a>(
+        &self,
+        worktree_id: WorktreeId,
+        paths: impl IntoIterator<Item = &'a Path>,
+    ) -> Vec<(TaskSourceKind, ResolvedTask)> {
+        let paths = paths.into_iter().collect::<Vec<_>>();
+        self.watched_tasks
+            .values()
+            .filter(|watched| {
+                watched
+                    .task_source_kind
+                    .worktree()
+                    .map_or(true, |id| id == worktree_id)
+            })
+            .filter(|watched| {
+                paths.iter().any(|path| {
+                    watched
+                        .matchers
+                        .iter()
+                        .any(|matcher| matcher.is_match(path))
+                })
+            })
+            .map(|watched| {
+                (
+                    watched.task_source_kind.clone(),
+                    watched.resolved_task.clone(),
+                )
+            })
+            .sorted_by(|(_, task_a), (_, task_b)| task_a.resolved_label.cmp(&task_b.resolved_label))
+            .collect()
+    }
 }
 
 fn task_lru_comparator(
@@ -412,7 +531,8 @@ fn task_source_kind_preference(kind: &TaskSourceKind) -> u32 {
         TaskSourceKind::Language { .. } => 1,
         TaskSourceKind::UserInput => 2,
         TaskSourceKind::Worktree { .. } => 3,
-        TaskSourceKind::AbsPath { .. } => 4,
+        TaskSourceKind::Detected { .. } => 4,
+        TaskSourceKind::AbsPath { .. } => 5,
     }
 }
 
@@ -987,6 +1107,127 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_watched_tasks(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let worktree_1 = WorktreeId::from_usize(1);
+        let worktree_2 = WorktreeId::from_usize(2);
+        let resolve = |label: &str, watch: &[&str]| {
+            TaskTemplate {
+                label: label.to_string(),
+                command: "cargo check".to_string(),
+                watch: watch.iter().map(|glob| glob.to_string()).collect(),
+                ..TaskTemplate::default()
+            }
+            .resolve_task("test", &TaskContext::default())
+            .unwrap()
+        };
+        let worktree_kind = TaskSourceKind::Worktree {
+            id: worktree_1,
+            abs_path: PathBuf::from("/worktree_1/.zed/tasks.json"),
+            id_base: "local_tasks_for_worktree".into(),
+        };
+        let check = resolve("check", &["src/**/*.rs", "Cargo.toml"]);
+        let lint = resolve("lint", &["*.toml"]);
+        let unwatched = resolve("unwatched", &[]);
+
+        inventory.update(cx, |inventory, cx| {
+            assert!(inventory.watch_task(worktree_kind.clone(), check.clone(), cx));
+            assert!(inventory.watch_task(TaskSourceKind::UserInput, lint.clone(), cx));
+            assert!(
+                !inventory.watch_task(TaskSourceKind::UserInput, unwatched.clone(), cx),
+                "Tasks without watch globs should not be watched"
+            );
+            assert!(inventory.is_task_watched(&check.id));
+            assert!(!inventory.is_task_watched(&unwatched.id));
+        });
+
+        let watched_labels = |worktree_id: WorktreeId, paths: &[&str], cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, _| {
+                inventory
+                    .watched_tasks_for_paths(worktree_id, paths.iter().map(Path::new))
+                    .into_iter()
+                    .map(|(_, task)| task.original_task().label.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            watched_labels(worktree_1, &["src/foo/bar.rs"], cx),
+            vec!["check".to_string()]
+        );
+        assert_eq!(
+            watched_labels(worktree_1, &["README.md", "Cargo.toml"], cx),
+            vec!["check".to_string(), "lint".to_string()]
+        );
+        assert_eq!(
+            watched_labels(worktree_2, &["src/foo/bar.rs", "Cargo.toml"], cx),
+            vec!["lint".to_string()],
+            "Tasks of a worktree should only be rerun on changes in it"
+        );
+        assert!(watched_labels(worktree_1, &["README.md"], cx).is_empty());
+
+        inventory.update(cx, |inventory, cx| {
+            assert!(inventory.unwatch_task(&check.id, cx));
+            assert!(!inventory.unwatch_task(&check.id, cx));
+        });
+        assert_eq!(
+            watched_labels(worktree_1, &["src/foo/bar.rs", "Cargo.toml"], cx),
+            vec!["lint".to_string()]
+        );
+    }
+
+    #[gpui::test]
+    fn test_watched_task_terminals(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let resolve = |label: &str| {
+            TaskTemplate {
+                label: label.to_string(),
+                command: "cargo check".to_string(),
+                watch: vec!["**/*.rs".to_string()],
+                ..TaskTemplate::default()
+            }
+            .resolve_task("test", &TaskContext::default())
+            .unwrap()
+        };
+        let check = resolve("check");
+        let lint = resolve("lint");
+        let terminal_1 = EntityId::from(1u64);
+        let terminal_2 = EntityId::from(2u64);
+        let terminal_3 = EntityId::from(3u64);
+
+        inventory.update(cx, |inventory, cx| {
+            assert!(inventory.watch_task(TaskSourceKind::UserInput, check.clone(), cx));
+            assert!(inventory.watch_task(TaskSourceKind::UserInput, lint.clone(), cx));
+            inventory.task_terminal_opened(terminal_1, check.id.clone());
+            inventory.task_terminal_opened(terminal_2, check.id.clone());
+            inventory.task_terminal_opened(terminal_3, lint.id.clone());
+            // Rerunning the task in its terminal does not open another one.
+            inventory.task_terminal_opened(terminal_3, lint.id.clone());
+
+            assert!(
+                !inventory.task_terminal_closed(terminal_1, cx),
+                "Task should stay watched while another terminal shows it"
+            );
+            assert!(inventory.is_task_watched(&check.id));
+            assert!(inventory.task_terminal_closed(terminal_2, cx));
+            assert!(!inventory.is_task_watched(&check.id));
+            assert!(
+                !inventory.task_terminal_closed(terminal_2, cx),
+                "Closing an unknown terminal should do nothing"
+            );
+
+            // A terminal reused by another task unwatches that task once closed.
+            inventory.task_terminal_opened(terminal_3, check.id.clone());
+            assert!(inventory.watch_task(TaskSourceKind::UserInput, check.clone(), cx));
+            assert!(inventory.task_terminal_closed(terminal_3, cx));
+            assert!(!inventory.is_task_watched(&check.id));
+            assert!(
+                inventory.is_task_watched(&lint.id),
+                "Task should stay watched when its run was replaced by another task"
+            );
+        });
+    }
+
     pub(super) async fn resolved_task_names(
         inventory: &Model<Inventory>,
         worktree: Option<WorktreeId>,
@@ -0,0 +1,221 @@
+use std::{path::PathBuf, time::Duration};
+
+use collections::{HashMap, HashSet};
+use gpui::{EntityId, Model, ModelContext, Subscription};
+use language::{
+    Diagnostic, DiagnosticEntry, DiagnosticSeverity, LanguageServerId, PointUtf16, Unclipped,
+};
+use task::{Problem, ProblemMatcher, ProblemSeverity};
+use terminal::Terminal;
+
+use crate::Project;
+
+/// How long to wait for more output of a running task before matching problems in it again.
+const TASK_OUTPUT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// The diagnostics that problem matchers found in the output of tasks, keyed by the full label of
+/// the task, so that a rerun of a task replaces the diagnostics of its previous run.
+#[derive(Default)]
+pub(crate) struct TaskProblems {
+    tasks: HashMap<String, TaskDiagnostics>,
+}
+
+struct TaskDiagnostics {
+    /// The identifier under which the diagnostics are reported, as if they came from a language
+    /// server.
+    server_id: LanguageServerId,
+    matchers: Vec<ProblemMatcher>,
+    cwd: Option<PathBuf>,
+    /// The terminal running the latest run of the task.
+    terminal_id: EntityId,
+    /// The files that diagnostics were reported for.
+    paths: HashSet<PathBuf>,
+    scan_scheduled: bool,
+    next_scan_id: usize,
+    reported_scan_id: usize,
+    _subscription: Subscription,
+}
+
+impl Project {
+    /// Matches problems in the output of the task running in the given terminal as it is printed,
+    /// reporting them as diagnostics that replace the ones of the previous run of the task.
+    pub(crate) fn match_task_problems(
+        &mut self,
+        label: String,
+        matchers: Vec<ProblemMatcher>,
+        cwd: Option<PathBuf>,
+        terminal: &Model<Terminal>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let server_id = match self.task_problems.tasks.get(&label) {
+            Some(task) => {
+                let server_id = task.server_id;
+                self.report_task_problems(&label, None, Vec::new(), cx);
+                server_id
+            }
+            None => self.languages.next_language_server_id(),
+        };
+        if matchers.is_empty() {
+            self.task_problems.tasks.remove(&label);
+            return;
+        }
+
+        let subscription = cx.subscribe(terminal, {
+            let label = label.clone();
+            move |project, terminal, event, cx| match event {
+                terminal::Event::Wakeup => project.scan_task_output(&label, terminal, true, cx),
+                terminal::Event::TaskFinished => {
+                    project.scan_task_output(&label, terminal, false, cx)
+                }
+                _ => {}
+            }
+        });
+        self.task_problems.tasks.insert(
+            label,
+            TaskDiagnostics {
+                server_id,
+                matchers,
+                cwd,
+                terminal_id: terminal.entity_id(),
+                paths: HashSet::default(),
+                scan_scheduled: false,
+                next_scan_id: 0,
+                reported_scan_id: 0,
+                _subscription: subscription,
+            },
+        );
+    }
+
+    fn scan_task_output(
+        &mut self,
+        label: &str,
+        terminal: Model<Terminal>,
+        debounce: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(task) = self.task_problems.tasks.get_mut(label) else {
+            return;
+        };
+        if task.terminal_id != terminal.entity_id() || (debounce && task.scan_scheduled) {
+            return;
+        }
+        task.scan_scheduled |= debounce;
+        let matchers = task.matchers.clone();
+        let cwd = task.cwd.clone();
+        let label = label.to_string();
+        cx.spawn(|project, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(TASK_OUTPUT_DEBOUNCE).await;
+            }
+            let Some(scan_id) = project
+                .update(&mut cx, |project, _| {
+                    let task = project.task_problems.tasks.get_mut(&label)?;
+                    if task.terminal_id != terminal.entity_id() {
+                        return None;
+                    }
+                    task.scan_scheduled &= !debounce;
+                    task.next_scan_id += 1;
+                    Some(task.next_scan_id)
+                })
+                .ok()
+                .flatten()
+            else {
+                return;
+            };
+            let Ok(output) = terminal.update(&mut cx, |terminal, _| terminal.text()) else {
+                return;
+            };
+            let problems = cx
+                .background_executor()
+                .spawn(async move { task::find_problems(&matchers, &output, cwd.as_deref()) })
+                .await;
+            project
+                .update(&mut cx, |project, cx| {
+                    project.report_task_problems(&label, Some(scan_id), problems, cx)
+                })
+                .ok();
+        })
+        .detach();
+    }
+
+    /// Replaces the diagnostics of the task with the given problems, unless they were found by a
+    /// scan older than the one last reported.
+    fn report_task_problems(
+        &mut self,
+        label: &str,
+        scan_id: Option<usize>,
+        problems: Vec<Problem>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(task) = self.task_problems.tasks.get_mut(label) else {
+            return;
+        };
+        if let Some(scan_id) = scan_id {
+            if scan_id <= task.reported_scan_id {
+                return;
+            }
+            task.reported_scan_id = scan_id;
+        }
+        let server_id = task.server_id;
+
+        let mut diagnostics =
+            HashMap::<PathBuf, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>::default();
+        for problem in problems {
+            let start = PointUtf16::new(problem.line, problem.column.unwrap_or(0));
+            let end = match problem.end {
+                Some((line, column)) => PointUtf16::new(line, column.unwrap_or(u32::MAX)),
+                // Without a column, the whole line is reported.
+                None if problem.column.is_none() => PointUtf16::new(problem.line, u32::MAX),
+                None => start,
+            };
+            let entries = diagnostics.entry(problem.path).or_default();
+            let group_id = entries.len();
+            entries.push(DiagnosticEntry {
+                range: Unclipped(start)..Unclipped(end.max(start)),
+                diagnostic: Diagnostic {
+                    source: Some(problem.source.unwrap_or_else(|| label.to_string())),
+                    code: problem.code,
+                    severity: match problem.severity {
+                        ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                        ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                        ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                        ProblemSeverity::Hint => DiagnosticSeverity::HINT,
+                    },
+                    message: problem.message,
+                    group_id,
+                    is_primary: true,
+                    // The problems refer to the files as they were saved when the task ran.
+                    is_disk_based: true,
+                    ..Diagnostic::default()
+                },
+            });
+        }
+
+        let stale_paths = task
+            .paths
+            .iter()
+            .filter(|path| !diagnostics.contains_key(*path))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut reported_paths = HashSet::default();
+        for (path, entries) in stale_paths
+            .into_iter()
+            .map(|path| (path, Vec::new()))
+            .chain(diagnostics)
+        {
+            let has_entries = !entries.is_empty();
+            // Problems may be reported for files outside of the project, which have nowhere to
+            // be shown.
+            if self
+                .update_diagnostic_entries(server_id, path.clone(), None, entries, cx)
+                .is_ok()
+                && has_entries
+            {
+                reported_paths.insert(path);
+            }
+        }
+        if let Some(task) = self.task_problems.tasks.get_mut(label) {
+            task.paths = reported_paths;
+        }
+    }
+}
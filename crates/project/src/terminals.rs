@@ -1,12 +1,14 @@
 use crate::Project;
-use anyhow::Context as _;
+use anyhow::{anyhow, Context as _};
 use collections::HashMap;
+use futures::AsyncWriteExt;
 use gpui::{
-    AnyWindowHandle, AppContext, Context, Entity, Model, ModelContext, SharedString, WeakModel,
+    AnyWindowHandle, AppContext, Context, Entity, Model, ModelContext, SharedString, Task,
+    WeakModel,
 };
 use itertools::Itertools;
 use settings::{Settings, SettingsLocation};
-use smol::channel::bounded;
+use smol::{channel::bounded, process::Stdio};
 use std::{
     env,
     fs::File,
@@ -109,6 +111,32 @@ impl Project {
             .and_then(|cwd| cwd.local_path())
             .unwrap_or_else(|| Path::new(""));
 
+        // Problems are only matched in the output of local tasks, whose paths are those of the project.
+        let task_problems = spawn_task
+            .as_ref()
+            .filter(|_| {
+                working_directory
+                    .as_ref()
+                    .map_or(true, |cwd| cwd.is_local())
+            })
+            .map(|spawn_task| {
+                let cwd = spawn_task
+                    .cwd
+                    .as_ref()
+                    .or(working_directory.as_ref())
+                    .and_then(|cwd| cwd.local_path())
+                    .map(ToOwned::to_owned)
+                    .or_else(|| {
+                        let worktree = self.visible_worktrees(cx).next()?;
+                        Some(worktree.read(cx).abs_path().to_path_buf())
+                    });
+                (
+                    spawn_task.full_label.clone(),
+                    spawn_task.problem_matchers.clone(),
+                    cwd,
+                )
+            });
+
         let (spawn_task, shell) = match working_directory.as_ref() {
             Some(TerminalWorkDir::Ssh { ssh_command, path }) => {
                 log::debug!("Connecting to a remote server: {ssh_command:?}");
@@ -183,6 +211,9 @@ impl Project {
         )
         .map(|builder| {
             let terminal_handle = cx.new_model(|cx| builder.subscribe(cx));
+            if let Some((label, matchers, cwd)) = task_problems {
+                self.match_task_problems(label, matchers, cwd, &terminal_handle, cx);
+            }
 
             self.terminals
                 .local_handles
@@ -224,6 +255,87 @@ impl Project {
         terminal
     }
 
+    /// Runs the command line in the shell that tasks are spawned in, with the environment the
+    /// terminal settings give to tasks, and returns what it prints.
+    /// `input`, if any, is written to the standard input of the command.
+    pub fn run_shell_command(
+        &mut self,
+        command: String,
+        input: Option<String>,
+        working_directory: Option<PathBuf>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<anyhow::Result<String>> {
+        if !self.is_local() {
+            return Task::ready(Err(anyhow!(
+                "shell commands can only be run in local projects"
+            )));
+        }
+
+        let worktree = working_directory
+            .as_ref()
+            .and_then(|cwd| self.find_local_worktree(cwd, cx));
+        let settings_location = worktree.as_ref().map(|(worktree, path)| SettingsLocation {
+            worktree_id: worktree.read(cx).id().to_usize(),
+            path,
+        });
+        let settings = TerminalSettings::get(settings_location, cx);
+        let python_settings = settings.detect_venv.clone();
+        let mut env = settings.env.clone();
+        let Some((shell, mut args)) = (match settings.shell.clone() {
+            Shell::System => env::var("SHELL").ok().map(|shell| (shell, Vec::new())),
+            Shell::Program(shell) => Some((shell, Vec::new())),
+            Shell::WithArguments { program, args } => Some((program, args)),
+        }) else {
+            return Task::ready(Err(anyhow!("no shell to run the command in")));
+        };
+        args.extend(["-c".to_owned(), command.clone()]);
+
+        if let Some(python_settings) = &python_settings.as_option() {
+            let venv_base_directory = working_directory
+                .as_deref()
+                .unwrap_or_else(|| Path::new(""));
+            self.set_python_venv_path_for_tasks(python_settings, venv_base_directory, &mut env);
+        }
+
+        cx.background_executor().spawn(async move {
+            let mut child = smol::process::Command::new(&shell);
+            if let Some(working_directory) = working_directory {
+                child.current_dir(working_directory);
+            }
+            let mut child = child
+                .args(args)
+                .envs(env)
+                .stdin(if input.is_some() {
+                    Stdio::piped()
+                } else {
+                    Stdio::null()
+                })
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .with_context(|| format!("spawning `{command}` in {shell}"))?;
+
+            let stdin = child.stdin.take();
+            let write_input = async move {
+                if let (Some(mut stdin), Some(input)) = (stdin, input) {
+                    // Commands that don't read their input close it early, which isn't an error.
+                    stdin.write_all(input.as_bytes()).await.ok();
+                }
+            };
+            // The input is written while the output is read, so that neither pipe fills up.
+            let ((), output) = futures::join!(write_input, child.output());
+            let output = output?;
+            if !output.status.success() {
+                return Err(anyhow!(
+                    "`{command}` failed with exit code {:?}: {}",
+                    output.status.code(),
+                    String::from_utf8_lossy(&output.stderr).trim(),
+                ));
+            }
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        })
+    }
+
     pub fn find_activate_script_path(
         &mut self,
         settings: &VenvSettingsContent,
@@ -0,0 +1,64 @@
+use collections::HashMap;
+use gpui::{AppContext, Model, ModelContext};
+use language::{Anchor, Buffer, ToPoint as _};
+use text::BufferId;
+
+use crate::{Event, Project};
+
+/// The outcome of a test, as shown next to it in the gutter of the editor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestStatus {
+    Running,
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// The statuses of the tests in the open buffers of the project, positioned where the tests
+/// are declared so that they follow edits.
+#[derive(Default)]
+pub(crate) struct TestStatuses {
+    buffers: HashMap<BufferId, Vec<(Anchor, TestStatus)>>,
+}
+
+impl TestStatuses {
+    pub(crate) fn buffer_closed(&mut self, buffer: &Buffer) {
+        self.buffers.remove(&buffer.remote_id());
+    }
+}
+
+impl Project {
+    /// Replaces the statuses of the tests in the given buffer.
+    pub fn set_test_statuses(
+        &mut self,
+        buffer: &Model<Buffer>,
+        statuses: Vec<(Anchor, TestStatus)>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffer_id = buffer.read(cx).remote_id();
+        if statuses.is_empty() {
+            if self.test_statuses.buffers.remove(&buffer_id).is_none() {
+                return;
+            }
+        } else {
+            self.test_statuses.buffers.insert(buffer_id, statuses);
+        }
+        cx.emit(Event::TestStatusesChanged(buffer_id));
+    }
+
+    /// The status of the test declared on the given row of the buffer, if it has one.
+    pub fn test_status(
+        &self,
+        buffer: &Model<Buffer>,
+        row: u32,
+        cx: &AppContext,
+    ) -> Option<TestStatus> {
+        let buffer = buffer.read(cx);
+        self.test_statuses
+            .buffers
+            .get(&buffer.remote_id())?
+            .iter()
+            .find(|(position, _)| position.to_point(buffer).row == row)
+            .map(|(_, status)| *status)
+    }
+}
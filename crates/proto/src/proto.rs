@@ -204,6 +204,36 @@ messages!(
     (GetProjectSymbolsResponse, Background),
     (GetReferences, Background),
     (GetReferencesResponse, Background),
+    (GetSignatureHelp, Background),
+    (GetSignatureHelpResponse, Background),
+    (PrepareCallHierarchy, Background),
+    (PrepareCallHierarchyResponse, Background),
+    (GetIncomingCalls, Background),
+    (GetIncomingCallsResponse, Background),
+    (GetOutgoingCalls, Background),
+    (GetOutgoingCallsResponse, Background),
+    (PrepareTypeHierarchy, Background),
+    (PrepareTypeHierarchyResponse, Background),
+    (GetSupertypes, Background),
+    (GetSupertypesResponse, Background),
+    (GetSubtypes, Background),
+    (GetSubtypesResponse, Background),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetDocumentColors, Background),
+    (GetDocumentColorsResponse, Background),
+    (GetColorPresentations, Background),
+    (GetColorPresentationsResponse, Background),
+    (GetDocumentLinks, Background),
+    (GetDocumentLinksResponse, Background),
+    (OpenDocumentLink, Background),
+    (OpenDocumentLinkResponse, Background),
+    (GetSelectionRanges, Background),
+    (GetSelectionRangesResponse, Background),
     (GetSupermavenApiKey, Background),
     (GetSupermavenApiKeyResponse, Background),
     (GetTypeDefinition, Background),
@@ -246,7 +276,9 @@ messages!(
     (PrepareRename, Background),
     (PrepareRenameResponse, Background),
     (ProjectEntryResponse, Foreground),
+    (RefreshCodeLenses, Foreground),
     (RefreshInlayHints, Foreground),
+    (RefreshSemanticTokens, Foreground),
     (RejoinChannelBuffers, Foreground),
     (RejoinChannelBuffersResponse, Foreground),
     (RejoinRoom, Foreground),
@@ -376,6 +408,21 @@ request_messages!(
     (GetPrivateUserInfo, GetPrivateUserInfoResponse),
     (GetProjectSymbols, GetProjectSymbolsResponse),
     (GetReferences, GetReferencesResponse),
+    (GetSignatureHelp, GetSignatureHelpResponse),
+    (PrepareCallHierarchy, PrepareCallHierarchyResponse),
+    (GetIncomingCalls, GetIncomingCallsResponse),
+    (GetOutgoingCalls, GetOutgoingCallsResponse),
+    (PrepareTypeHierarchy, PrepareTypeHierarchyResponse),
+    (GetSupertypes, GetSupertypesResponse),
+    (GetSubtypes, GetSubtypesResponse),
+    (GetSemanticTokens, GetSemanticTokensResponse),
+    (GetCodeLens, GetCodeLensResponse),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetDocumentColors, GetDocumentColorsResponse),
+    (GetColorPresentations, GetColorPresentationsResponse),
+    (GetDocumentLinks, GetDocumentLinksResponse),
+    (OpenDocumentLink, OpenDocumentLinkResponse),
+    (GetSelectionRanges, GetSelectionRangesResponse),
     (GetSupermavenApiKey, GetSupermavenApiKeyResponse),
     (GetTypeDefinition, GetTypeDefinitionResponse),
     (LinkedEditingRange, LinkedEditingRangeResponse),
@@ -401,7 +448,9 @@ request_messages!(
     (PerformRename, PerformRenameResponse),
     (Ping, Ack),
     (PrepareRename, PrepareRenameResponse),
+    (RefreshCodeLenses, Ack),
     (RefreshInlayHints, Ack),
+    (RefreshSemanticTokens, Ack),
     (RejoinChannelBuffers, RejoinChannelBuffersResponse),
     (RejoinRoom, RejoinRoomResponse),
     (ReloadBuffers, ReloadBuffersResponse),
@@ -474,6 +523,21 @@ entity_messages!(
     GetHover,
     GetProjectSymbols,
     GetReferences,
+    GetSignatureHelp,
+    PrepareCallHierarchy,
+    GetIncomingCalls,
+    GetOutgoingCalls,
+    PrepareTypeHierarchy,
+    GetSupertypes,
+    GetSubtypes,
+    GetSemanticTokens,
+    GetCodeLens,
+    GetFoldingRanges,
+    GetDocumentColors,
+    GetColorPresentations,
+    GetDocumentLinks,
+    OpenDocumentLink,
+    GetSelectionRanges,
     GetTypeDefinition,
     InlayHints,
     JoinProject,
@@ -488,7 +552,9 @@ entity_messages!(
     OpenBufferForSymbol,
     PerformRename,
     PrepareRename,
+    RefreshCodeLenses,
     RefreshInlayHints,
+    RefreshSemanticTokens,
     ReloadBuffers,
     RemoveProjectCollaborator,
     RenameProjectEntry,
@@ -21,6 +21,7 @@ use rpc::{
     proto::{CreateDevServerResponse, DevServerStatus},
     ErrorCode, ErrorExt,
 };
+use task::DependsOrder;
 use task::RevealStrategy;
 use task::SpawnInTerminal;
 use task::TerminalWorkDir;
@@ -1178,6 +1179,9 @@ pub async fn spawn_ssh_task(
                     use_new_terminal: true,
                     allow_concurrent_runs: false,
                     reveal: RevealStrategy::Always,
+                    problem_matchers: Vec::new(),
+                    dependencies: Vec::new(),
+                    depends_order: DependsOrder::default(),
                 },
                 cx,
             )
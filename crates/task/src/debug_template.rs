@@ -0,0 +1,264 @@
+use std::path::PathBuf;
+
+use collections::{HashMap, HashSet};
+use schemars::{gen::SchemaSettings, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    task_template::{
+        substitute_all_template_variables_in_map, substitute_all_template_variables_in_str,
+        substitute_all_template_variables_in_vec,
+    },
+    TaskContext,
+};
+
+/// A template definition of a debug session to start, defined in `debug.json` files next to
+/// the `tasks.json` ones.
+/// May use the [`crate::VariableName`] to get the corresponding substitutions into its fields,
+/// including every string of its `config`.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DebugTemplate {
+    /// Human readable name of the debug session to display in the UI.
+    pub label: String,
+    /// Executable command that starts the debug adapter.
+    pub adapter: String,
+    /// Arguments to the debug adapter command.
+    #[serde(default)]
+    pub adapter_args: Vec<String>,
+    /// The identifier of the debug adapter sent to it on initialization, e.g. `lldb` or `debugpy`.
+    /// Defaults to the file name of the adapter command.
+    #[serde(default)]
+    pub adapter_id: Option<String>,
+    /// Env overrides for the debug adapter command.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Current working directory to start the debug adapter in, defaults to current project root.
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Whether the debug adapter launches the debuggee or attaches to a running one.
+    #[serde(default)]
+    pub request: DebugRequestKind,
+    /// The port to connect to the debug adapter on, when it listens on TCP rather than talking
+    /// over its standard input and output.
+    #[serde(default)]
+    pub tcp_port: Option<u16>,
+    /// The adapter-specific arguments of the `launch` or `attach` request,
+    /// e.g. the program to debug.
+    #[serde(default)]
+    pub config: serde_json::Value,
+}
+
+/// How a debug adapter gets hold of the debuggee.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugRequestKind {
+    /// Start the debuggee (default).
+    #[default]
+    Launch,
+    /// Attach to an already running debuggee.
+    Attach,
+}
+
+/// A group of debug templates defined in a JSON file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DebugTemplates(pub Vec<DebugTemplate>);
+
+impl DebugTemplates {
+    /// Generates JSON schema of the debug JSON template format.
+    pub fn generate_json_schema() -> serde_json_lenient::Value {
+        let schema = SchemaSettings::draft07()
+            .with(|settings| settings.option_add_null_type = false)
+            .into_generator()
+            .into_root_schema_for::<Self>();
+
+        serde_json_lenient::to_value(schema).unwrap()
+    }
+}
+
+/// A final form of the [`DebugTemplate`], resolved with a particular [`TaskContext`] and ready
+/// to start a debug session with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedDebugConfig {
+    /// Human readable name of the debug session.
+    pub label: String,
+    /// Executable command that starts the debug adapter.
+    pub adapter: String,
+    /// Arguments to the debug adapter command.
+    pub adapter_args: Vec<String>,
+    /// The identifier of the debug adapter sent to it on initialization.
+    pub adapter_id: String,
+    /// Env overrides for the debug adapter command, including the task variables.
+    pub env: HashMap<String, String>,
+    /// Current working directory to start the debug adapter in.
+    pub cwd: Option<PathBuf>,
+    /// Whether the debug adapter launches the debuggee or attaches to a running one.
+    pub request: DebugRequestKind,
+    /// The port to connect to the debug adapter on, if it listens on TCP.
+    pub tcp_port: Option<u16>,
+    /// The arguments of the `launch` or `attach` request, with all task variables substituted.
+    pub config: serde_json::Value,
+}
+
+impl DebugTemplate {
+    /// Replaces all `VariableName` task variables in the template string fields and in every
+    /// string of its `config`.
+    /// If any replacement fails, `None` is returned.
+    pub fn resolve(&self, cx: &TaskContext) -> Option<ResolvedDebugConfig> {
+        if self.label.trim().is_empty() || self.adapter.trim().is_empty() {
+            return None;
+        }
+
+        let mut variable_names = HashMap::default();
+        let mut substituted_variables = HashSet::default();
+        let task_variables = cx
+            .task_variables
+            .0
+            .iter()
+            .map(|(key, value)| {
+                let key_string = key.to_string();
+                if !variable_names.contains_key(&key_string) {
+                    variable_names.insert(key_string.clone(), key.clone());
+                }
+                (key_string, value.as_str())
+            })
+            .collect::<HashMap<_, _>>();
+        let mut substitute = |template: &str| {
+            substitute_all_template_variables_in_str(
+                template,
+                &task_variables,
+                &variable_names,
+                &mut substituted_variables,
+            )
+        };
+
+        let label = substitute(&self.label)?;
+        let adapter = substitute(&self.adapter)?;
+        let cwd = match self.cwd.as_deref() {
+            Some(cwd) => Some(PathBuf::from(substitute(cwd)?)),
+            None => cx.cwd.clone(),
+        };
+        let config = substitute_in_json(&self.config, &mut substitute)?;
+        let adapter_args = substitute_all_template_variables_in_vec(
+            &self.adapter_args,
+            &task_variables,
+            &variable_names,
+            &mut substituted_variables,
+        )?;
+        let mut env = substitute_all_template_variables_in_map(
+            &self.env,
+            &task_variables,
+            &variable_names,
+            &mut substituted_variables,
+        )?;
+        env.extend(
+            task_variables
+                .iter()
+                .map(|(key, value)| (key.clone(), value.to_string())),
+        );
+        let adapter_id = self.adapter_id.clone().unwrap_or_else(|| {
+            adapter
+                .rsplit(['/', '\\'])
+                .next()
+                .unwrap_or(&adapter)
+                .to_string()
+        });
+
+        Some(ResolvedDebugConfig {
+            label,
+            adapter,
+            adapter_args,
+            adapter_id,
+            env,
+            cwd,
+            request: self.request,
+            tcp_port: self.tcp_port,
+            config,
+        })
+    }
+}
+
+fn substitute_in_json(
+    value: &serde_json::Value,
+    substitute: &mut impl FnMut(&str) -> Option<String>,
+) -> Option<serde_json::Value> {
+    Some(match value {
+        serde_json::Value::String(string) => serde_json::Value::String(substitute(string)?),
+        serde_json::Value::Array(values) => serde_json::Value::Array(
+            values
+                .iter()
+                .map(|value| substitute_in_json(value, substitute))
+                .collect::<Option<_>>()?,
+        ),
+        serde_json::Value::Object(object) => serde_json::Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| Some((key.clone(), substitute_in_json(value, substitute)?)))
+                .collect::<Option<_>>()?,
+        ),
+        value => value.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use crate::{TaskVariables, VariableName};
+
+    use super::*;
+
+    #[test]
+    fn test_resolving_debug_templates() {
+        let template = serde_json_lenient::from_str::<DebugTemplates>(
+            r#"[
+                {
+                    "label": "Debug $ZED_STEM",
+                    "adapter": "/usr/bin/lldb-dap",
+                    "config": {
+                        "program": "$ZED_WORKTREE_ROOT/target/debug/$ZED_STEM",
+                        "args": ["--row", "$ZED_ROW"],
+                        "stopOnEntry": true
+                    }
+                }
+            ]"#,
+        )
+        .unwrap()
+        .0
+        .remove(0);
+
+        let cx = TaskContext {
+            cwd: Some(PathBuf::from("/project")),
+            task_variables: TaskVariables::from_iter([
+                (VariableName::WorktreeRoot, "/project".to_string()),
+                (VariableName::Stem, "main".to_string()),
+                (VariableName::Row, "12".to_string()),
+            ]),
+        };
+        let resolved = template.resolve(&cx).unwrap();
+        assert_eq!(resolved.label, "Debug main");
+        assert_eq!(resolved.adapter_id, "lldb-dap");
+        assert_eq!(resolved.request, DebugRequestKind::Launch);
+        assert_eq!(resolved.cwd.as_deref(), Some(Path::new("/project")));
+        assert_eq!(
+            resolved.config,
+            json!({
+                "program": "/project/target/debug/main",
+                "args": ["--row", "12"],
+                "stopOnEntry": true,
+            })
+        );
+        assert_eq!(
+            resolved.env.get("ZED_STEM").map(String::as_str),
+            Some("main")
+        );
+
+        let unknown_variable = DebugTemplate {
+            config: json!({ "program": "$ZED_SYMBOL" }),
+            ..template
+        };
+        assert_eq!(unknown_variable.resolve(&cx), None);
+    }
+}
@@ -0,0 +1,692 @@
+//! Detection of the tasks declared in build manifests, such as `package.json` scripts or `Makefile` targets.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use collections::HashSet;
+use serde::Deserialize;
+
+use crate::{TaskInput, TaskInputKind, TaskTemplate, TaskTemplates};
+
+/// A build manifest, the tasks declared in which are detected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TaskManifest {
+    /// Scripts of a `package.json`, run with the package manager of the package.
+    PackageJson,
+    /// Targets of a `Makefile`.
+    Makefile,
+    /// Recipes of a `justfile`.
+    Justfile,
+    /// Binaries and examples of the package of a `Cargo.toml`.
+    CargoToml,
+    /// Scripts declared in a `pyproject.toml`.
+    PyprojectToml,
+}
+
+impl TaskManifest {
+    /// The kind of the manifest at the given path, judging by its file name.
+    pub fn for_path(path: &Path) -> Option<Self> {
+        match path.file_name()?.to_str()? {
+            "package.json" => Some(Self::PackageJson),
+            "Makefile" | "makefile" | "GNUmakefile" => Some(Self::Makefile),
+            "justfile" | "Justfile" | ".justfile" => Some(Self::Justfile),
+            "Cargo.toml" => Some(Self::CargoToml),
+            "pyproject.toml" => Some(Self::PyprojectToml),
+            _ => None,
+        }
+    }
+
+    /// Whether targets of the manifest are discovered in the directory at the given path, relative to the directory of
+    /// the manifest, such as the `src/bin` directory of a Cargo package.
+    pub fn discovers_targets_in(self, relative_dir: &Path) -> bool {
+        match self {
+            Self::CargoToml => matches!(
+                path_components(relative_dir).as_deref(),
+                Some(
+                    [] | ["src"]
+                        | ["src", "bin"]
+                        | ["src", "bin", _]
+                        | ["examples"]
+                        | ["examples", _]
+                )
+            ),
+            _ => false,
+        }
+    }
+
+    /// Whether the file at the given path, relative to the directory of the manifest, is a target of the manifest by
+    /// its location, such as the `src/bin/*.rs` binaries of a Cargo package.
+    pub fn discovers_target_at(self, relative_path: &Path) -> bool {
+        match self {
+            Self::CargoToml => discovered_cargo_target(relative_path, "").is_some(),
+            _ => false,
+        }
+    }
+
+    /// The paths of the manifests which the file at the given path is a target of by its location, such as the
+    /// `Cargo.toml` of the package which `src/bin/tool.rs` is a binary of.
+    pub fn manifests_discovering_target_at(path: &Path) -> impl Iterator<Item = PathBuf> + '_ {
+        path.ancestors().skip(1).filter_map(move |dir| {
+            let relative_path = path.strip_prefix(dir).ok()?;
+            Self::CargoToml
+                .discovers_target_at(relative_path)
+                .then(|| dir.join("Cargo.toml"))
+        })
+    }
+
+    /// Detects the tasks declared in the manifest with the given contents, and the ones of the targets discovered
+    /// at the given paths of files, relative to the directory of the manifest.
+    /// The tasks are labeled after their commands, and run in the current directory.
+    pub fn detect_tasks(
+        self,
+        contents: &str,
+        target_files: &[PathBuf],
+    ) -> anyhow::Result<TaskTemplates> {
+        let tasks = match self {
+            Self::PackageJson => package_json_tasks(contents)?,
+            Self::Makefile => makefile_tasks(contents),
+            Self::Justfile => justfile_tasks(contents),
+            Self::CargoToml => cargo_toml_tasks(contents, target_files)?,
+            Self::PyprojectToml => pyproject_toml_tasks(contents)?,
+        };
+        Ok(TaskTemplates(tasks))
+    }
+}
+
+fn command_task(command: &str, args: Vec<String>) -> TaskTemplate {
+    let mut label = command.to_string();
+    for arg in &args {
+        label.push(' ');
+        label.push_str(arg);
+    }
+    TaskTemplate {
+        label,
+        command: command.to_string(),
+        args,
+        ..TaskTemplate::default()
+    }
+}
+
+#[derive(Deserialize)]
+struct PackageJson {
+    #[serde(default)]
+    scripts: BTreeMap<String, String>,
+    #[serde(default, rename = "packageManager")]
+    package_manager: Option<String>,
+}
+
+fn package_json_tasks(contents: &str) -> anyhow::Result<Vec<TaskTemplate>> {
+    let package_json =
+        serde_json_lenient::from_str::<PackageJson>(contents).context("parsing package.json")?;
+    // `packageManager` is formatted as `name@version`.
+    let package_manager = package_json
+        .package_manager
+        .as_deref()
+        .and_then(|package_manager| package_manager.split('@').next())
+        .filter(|package_manager| matches!(*package_manager, "pnpm" | "yarn" | "bun"))
+        .unwrap_or("npm");
+    Ok(package_json
+        .scripts
+        .into_keys()
+        .map(|script| command_task(package_manager, vec!["run".to_string(), script]))
+        .collect())
+}
+
+fn makefile_tasks(contents: &str) -> Vec<TaskTemplate> {
+    let mut seen_targets = HashSet::default();
+    let mut tasks = Vec::new();
+    for line in contents.lines() {
+        // Recipe lines start with a tab, and rules start at the beginning of a line.
+        if line.starts_with(|c: char| c.is_whitespace() || c == '#') {
+            continue;
+        }
+        let Some((targets, rest)) = line.split_once(':') else {
+            continue;
+        };
+        // Variable assignments, such as `CC := gcc`, `CC ::= gcc` or `FLAGS = -o a:b`.
+        if targets.contains('=') || rest.starts_with('=') || rest.starts_with(":=") {
+            continue;
+        }
+        for target in targets.split_whitespace() {
+            // Special targets, such as `.PHONY`, pattern rules and computed targets are not meant to be run by name.
+            let runnable = !target.starts_with('.') && !target.contains(['%', '$', '(', ')']);
+            if runnable && seen_targets.insert(target) {
+                tasks.push(command_task("make", vec![target.to_string()]));
+            }
+        }
+    }
+    tasks
+}
+
+fn justfile_tasks(contents: &str) -> Vec<TaskTemplate> {
+    let mut tasks = Vec::new();
+    let mut private = false;
+    for line in contents.lines() {
+        if line.starts_with(|c: char| c.is_whitespace() || c == '#') || line.is_empty() {
+            continue;
+        }
+        if let Some(attributes) = line.strip_prefix('[') {
+            private |= attributes.contains("private");
+            continue;
+        }
+        let recipe_is_private = std::mem::take(&mut private);
+        let Some(signature) = recipe_signature(line) else {
+            continue;
+        };
+        let mut words = signature.split_whitespace();
+        let Some(name) = words.next().map(|name| name.trim_start_matches('@')) else {
+            continue;
+        };
+        if recipe_is_private
+            || name.starts_with('_')
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            || matches!(
+                name,
+                "alias" | "export" | "import" | "mod" | "set" | "unexport"
+            )
+        {
+            continue;
+        }
+
+        // Parameters without defaults are asked for before running the recipe.
+        let mut args = vec![name.to_string()];
+        let mut inputs = Vec::new();
+        for parameter in words {
+            let parameter = parameter.trim_start_matches('$');
+            if parameter.contains('=') || parameter.starts_with('*') {
+                continue;
+            }
+            let parameter = parameter.trim_start_matches('+');
+            args.push(format!("${{input:{parameter}}}"));
+            inputs.push(TaskInput {
+                id: parameter.to_string(),
+                kind: TaskInputKind::PromptString,
+                description: Some(format!("Value of `{parameter}` for `just {name}`")),
+                default: None,
+                options: Vec::new(),
+            });
+        }
+        tasks.push(TaskTemplate {
+            label: format!("just {name}"),
+            command: "just".to_string(),
+            args,
+            inputs,
+            ..TaskTemplate::default()
+        });
+    }
+    tasks
+}
+
+/// The name and the parameters of the recipe declared by a line of a justfile, which precede the colon before its
+/// dependencies. Other lines, such as assignments (`version := "1.0"`), declare no recipe.
+fn recipe_signature(line: &str) -> Option<&str> {
+    let (signature, rest) = line.split_once(':')?;
+    (!rest.starts_with('=')).then_some(signature)
+}
+
+fn path_components(path: &Path) -> Option<Vec<&str>> {
+    path.components()
+        .map(|component| component.as_os_str().to_str())
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct CargoToml {
+    package: Option<CargoPackage>,
+    #[serde(default)]
+    bin: Vec<CargoTarget>,
+    #[serde(default)]
+    example: Vec<CargoTarget>,
+}
+
+#[derive(Deserialize)]
+struct CargoPackage {
+    name: String,
+    #[serde(default = "default_true")]
+    autobins: bool,
+    #[serde(default = "default_true")]
+    autoexamples: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct CargoTarget {
+    name: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum CargoTargetKind {
+    Bin,
+    Example,
+}
+
+/// The target which Cargo discovers at the given path of a file of the package, following its conventional layout:
+/// <https://doc.rust-lang.org/cargo/reference/cargo-targets.html#target-auto-discovery>
+fn discovered_cargo_target(
+    relative_path: &Path,
+    package_name: &str,
+) -> Option<(CargoTargetKind, String)> {
+    let (kind, name) = match path_components(relative_path)?.as_slice() {
+        ["src", "main.rs"] => (CargoTargetKind::Bin, package_name),
+        ["src", "bin", file] => (CargoTargetKind::Bin, file.strip_suffix(".rs")?),
+        ["src", "bin", dir, "main.rs"] => (CargoTargetKind::Bin, *dir),
+        ["examples", file] => (CargoTargetKind::Example, file.strip_suffix(".rs")?),
+        ["examples", dir, "main.rs"] => (CargoTargetKind::Example, *dir),
+        _ => return None,
+    };
+    Some((kind, name.to_string()))
+}
+
+fn cargo_toml_tasks(contents: &str, target_files: &[PathBuf]) -> anyhow::Result<Vec<TaskTemplate>> {
+    let cargo_toml = toml::from_str::<CargoToml>(contents).context("parsing Cargo.toml")?;
+    let mut bins = cargo_toml
+        .bin
+        .into_iter()
+        .filter_map(|target| target.name)
+        .collect::<Vec<_>>();
+    let mut examples = cargo_toml
+        .example
+        .into_iter()
+        .filter_map(|target| target.name)
+        .collect::<Vec<_>>();
+    // Manifests of virtual workspaces have no package to discover the targets of.
+    if let Some(package) = cargo_toml.package {
+        let mut discovered_targets = target_files
+            .iter()
+            .filter_map(|path| discovered_cargo_target(path, &package.name))
+            .collect::<Vec<_>>();
+        discovered_targets.sort();
+        for (kind, name) in discovered_targets {
+            let (targets, discovered) = match kind {
+                CargoTargetKind::Bin => (&mut bins, package.autobins),
+                CargoTargetKind::Example => (&mut examples, package.autoexamples),
+            };
+            // Targets declared explicitly may be located at the conventional paths too.
+            if discovered && !targets.contains(&name) {
+                targets.push(name);
+            }
+        }
+    }
+
+    let targets = |targets: Vec<String>, kind: &'static str| {
+        targets.into_iter().map(move |name| {
+            command_task("cargo", vec!["run".to_string(), format!("--{kind}"), name])
+        })
+    };
+    Ok(targets(bins, "bin")
+        .chain(targets(examples, "example"))
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct PyprojectToml {
+    #[serde(default)]
+    project: PyprojectProject,
+    #[serde(default)]
+    tool: PyprojectTools,
+}
+
+#[derive(Default, Deserialize)]
+struct PyprojectProject {
+    #[serde(default)]
+    scripts: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Default, Deserialize)]
+struct PyprojectTools {
+    poetry: Option<PyprojectScripts>,
+    pdm: Option<PyprojectScripts>,
+}
+
+#[derive(Default, Deserialize)]
+struct PyprojectScripts {
+    #[serde(default)]
+    scripts: BTreeMap<String, toml::Value>,
+}
+
+fn pyproject_toml_tasks(contents: &str) -> anyhow::Result<Vec<TaskTemplate>> {
+    let pyproject = toml::from_str::<PyprojectToml>(contents).context("parsing pyproject.toml")?;
+    let mut scripts = BTreeMap::new();
+    // The entry points of the package are only installed into the environment managed by the tool, if any.
+    let runner = if pyproject.tool.poetry.is_some() {
+        Some("poetry")
+    } else if pyproject.tool.pdm.is_some() {
+        Some("pdm")
+    } else {
+        None
+    };
+    for script in pyproject.project.scripts.into_keys() {
+        scripts.insert(script, runner);
+    }
+    for (tool, tool_scripts) in [
+        ("poetry", pyproject.tool.poetry),
+        ("pdm", pyproject.tool.pdm),
+    ] {
+        for script in tool_scripts
+            .into_iter()
+            .flat_map(|tool| tool.scripts.into_keys())
+        {
+            scripts.insert(script, Some(tool));
+        }
+    }
+    Ok(scripts
+        .into_iter()
+        .map(|(script, runner)| match runner {
+            Some(runner) => command_task(runner, vec!["run".to_string(), script]),
+            None => command_task(&script, Vec::new()),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detected(manifest: TaskManifest, contents: &str) -> Vec<(String, String, Vec<String>)> {
+        manifest
+            .detect_tasks(contents, &[])
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|task| (task.label, task.command, task.args))
+            .collect()
+    }
+
+    fn labels(manifest: TaskManifest, contents: &str) -> Vec<String> {
+        detected(manifest, contents)
+            .into_iter()
+            .map(|(label, _, _)| label)
+            .collect()
+    }
+
+    #[test]
+    fn test_manifest_for_path() {
+        let manifest = |path: &str| TaskManifest::for_path(Path::new(path));
+        assert_eq!(manifest("package.json"), Some(TaskManifest::PackageJson));
+        assert_eq!(
+            manifest("web/package.json"),
+            Some(TaskManifest::PackageJson)
+        );
+        assert_eq!(manifest("GNUmakefile"), Some(TaskManifest::Makefile));
+        assert_eq!(manifest("Justfile"), Some(TaskManifest::Justfile));
+        assert_eq!(
+            manifest("crates/task/Cargo.toml"),
+            Some(TaskManifest::CargoToml)
+        );
+        assert_eq!(
+            manifest("pyproject.toml"),
+            Some(TaskManifest::PyprojectToml)
+        );
+        assert_eq!(manifest("package-lock.json"), None);
+        assert_eq!(manifest("Makefile.am"), None);
+    }
+
+    #[test]
+    fn test_package_json_tasks() {
+        let package_json = r#"{
+            "name": "app",
+            "scripts": {
+                "test": "vitest",
+                "build": "vite build",
+            },
+        }"#;
+        assert_eq!(
+            detected(TaskManifest::PackageJson, package_json),
+            vec![
+                (
+                    "npm run build".to_string(),
+                    "npm".to_string(),
+                    vec!["run".to_string(), "build".to_string()]
+                ),
+                (
+                    "npm run test".to_string(),
+                    "npm".to_string(),
+                    vec!["run".to_string(), "test".to_string()]
+                ),
+            ]
+        );
+        assert_eq!(
+            labels(
+                TaskManifest::PackageJson,
+                r#"{"packageManager": "pnpm@9.1.0", "scripts": {"dev": "vite"}}"#
+            ),
+            vec!["pnpm run dev".to_string()]
+        );
+        assert!(labels(TaskManifest::PackageJson, r#"{"name": "app"}"#).is_empty());
+        assert!(TaskManifest::PackageJson.detect_tasks("{", &[]).is_err());
+    }
+
+    #[test]
+    fn test_makefile_tasks() {
+        let makefile = "\
+CC := gcc
+PREFIX ?= /usr/local
+FLAGS = -o a:b
+.PHONY: all test clean
+
+# Builds everything.
+all: build
+build test: deps
+\t$(CC) -c main.c: not a rule
+%.o: %.c
+\t$(CC) -c $<
+$(BUILD_DIR)/app: main.o
+clean::
+\trm -rf build
+test:
+";
+        assert_eq!(
+            labels(TaskManifest::Makefile, makefile),
+            vec![
+                "make all".to_string(),
+                "make build".to_string(),
+                "make test".to_string(),
+                "make clean".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_justfile_tasks() {
+        let justfile = "\
+set shell := [\"bash\", \"-c\"]
+version := \"1.0\"
+alias b := build
+
+# Builds the project.
+build:
+    cargo build
+
+@test filter=\"\" *args: build
+    cargo test {{filter}} {{args}}
+
+deploy env $TAG +hosts:
+    ./deploy.sh {{env}} $TAG {{hosts}}
+
+[private]
+helper:
+    echo private
+
+_hidden:
+    echo hidden
+";
+        let tasks = TaskManifest::Justfile
+            .detect_tasks(justfile, &[])
+            .unwrap()
+            .0;
+        assert_eq!(
+            tasks
+                .iter()
+                .map(|task| (task.label.as_str(), task.args.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("just build", vec!["build".to_string()]),
+                ("just test", vec!["test".to_string()]),
+                (
+                    "just deploy",
+                    vec![
+                        "deploy".to_string(),
+                        "${input:env}".to_string(),
+                        "${input:TAG}".to_string(),
+                        "${input:hosts}".to_string(),
+                    ]
+                ),
+            ]
+        );
+        assert_eq!(
+            tasks[2]
+                .inputs
+                .iter()
+                .map(|input| (input.id.as_str(), input.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                ("env", TaskInputKind::PromptString),
+                ("TAG", TaskInputKind::PromptString),
+                ("hosts", TaskInputKind::PromptString),
+            ]
+        );
+        assert!(tasks[0].inputs.is_empty());
+    }
+
+    #[test]
+    fn test_cargo_toml_tasks() {
+        let cargo_toml = r#"
+[package]
+name = "zed"
+
+[[bin]]
+name = "zed"
+path = "src/main.rs"
+
+[[bin]]
+path = "src/unnamed.rs"
+
+[[example]]
+name = "hello_world"
+"#;
+        assert_eq!(
+            labels(TaskManifest::CargoToml, cargo_toml),
+            vec![
+                "cargo run --bin zed".to_string(),
+                "cargo run --example hello_world".to_string(),
+            ]
+        );
+        assert!(labels(TaskManifest::CargoToml, "[workspace]\nmembers = []").is_empty());
+        assert!(TaskManifest::CargoToml.detect_tasks("[[bin]", &[]).is_err());
+    }
+
+    #[test]
+    fn test_cargo_toml_discovered_targets() {
+        let target_files = [
+            "src/main.rs",
+            "src/lib.rs",
+            "src/bin/tool.rs",
+            "src/bin/server/main.rs",
+            "src/bin/server/routes.rs",
+            "examples/demo.rs",
+            "examples/README.md",
+        ]
+        .map(PathBuf::from);
+        let cargo_toml_labels = |cargo_toml: &str| {
+            TaskManifest::CargoToml
+                .detect_tasks(cargo_toml, &target_files)
+                .unwrap()
+                .0
+                .into_iter()
+                .map(|task| task.label)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            cargo_toml_labels("[package]\nname = \"app\""),
+            vec![
+                "cargo run --bin app".to_string(),
+                "cargo run --bin server".to_string(),
+                "cargo run --bin tool".to_string(),
+                "cargo run --example demo".to_string(),
+            ]
+        );
+        assert_eq!(
+            cargo_toml_labels(
+                r#"
+[package]
+name = "app"
+autoexamples = false
+
+[[bin]]
+name = "tool"
+path = "src/bin/tool.rs"
+"#
+            ),
+            vec![
+                "cargo run --bin tool".to_string(),
+                "cargo run --bin app".to_string(),
+                "cargo run --bin server".to_string(),
+            ]
+        );
+        assert!(cargo_toml_labels("[workspace]\nmembers = []").is_empty());
+
+        let discovers = |path: &str| TaskManifest::CargoToml.discovers_target_at(Path::new(path));
+        assert!(discovers("src/bin/server/main.rs"));
+        assert!(!discovers("src/lib.rs"));
+        assert!(!discovers("tests/main.rs"));
+        assert!(!TaskManifest::Makefile.discovers_target_at(Path::new("src/main.rs")));
+        assert_eq!(
+            TaskManifest::manifests_discovering_target_at(Path::new("crates/app/src/bin/tool.rs"))
+                .collect::<Vec<_>>(),
+            vec![PathBuf::from("crates/app/Cargo.toml")]
+        );
+    }
+
+    #[test]
+    fn test_pyproject_toml_tasks() {
+        let pyproject = r#"
+[project]
+name = "app"
+
+[project.scripts]
+app-cli = "app.cli:main"
+"#;
+        assert_eq!(
+            detected(TaskManifest::PyprojectToml, pyproject),
+            vec![("app-cli".to_string(), "app-cli".to_string(), Vec::new())]
+        );
+
+        let poetry_pyproject = r#"
+[tool.poetry]
+name = "app"
+
+[tool.poetry.scripts]
+serve = "app.server:run"
+"#;
+        assert_eq!(
+            labels(TaskManifest::PyprojectToml, poetry_pyproject),
+            vec!["poetry run serve".to_string()]
+        );
+
+        let pdm_pyproject = r#"
+[project.scripts]
+app-cli = "app.cli:main"
+
+[tool.pdm.scripts]
+lint = "ruff check ."
+test = { cmd = "pytest" }
+"#;
+        assert_eq!(
+            labels(TaskManifest::PyprojectToml, pdm_pyproject),
+            vec![
+                "pdm run app-cli".to_string(),
+                "pdm run lint".to_string(),
+                "pdm run test".to_string(),
+            ]
+        );
+    }
+}
@@ -1,10 +1,14 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+mod debug_template;
+mod detected_tasks;
+mod problem_matcher;
 pub mod static_source;
 mod task_template;
 mod vscode_format;
 
+use anyhow::{bail, Context};
 use collections::{hash_map, HashMap, HashSet};
 use gpui::SharedString;
 use serde::{Deserialize, Serialize};
@@ -12,7 +16,15 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use std::{borrow::Cow, path::Path};
 
-pub use task_template::{RevealStrategy, TaskTemplate, TaskTemplates};
+pub use debug_template::{DebugRequestKind, DebugTemplate, DebugTemplates, ResolvedDebugConfig};
+pub use detected_tasks::TaskManifest;
+pub use problem_matcher::{
+    find_problems, BackgroundMatcher, FileLocation, Problem, ProblemMatcher,
+    ProblemMatcherReference, ProblemPattern, ProblemSeverity,
+};
+pub use task_template::{
+    DependsOrder, RevealStrategy, TaskInput, TaskInputKind, TaskTemplate, TaskTemplates,
+};
 pub use vscode_format::VsCodeTaskFile;
 
 /// Task identifier, unique within the application.
@@ -61,7 +73,7 @@ pub struct SpawnInTerminal {
     pub full_label: String,
     /// Human readable name of the terminal tab.
     pub label: String,
-    /// Executable command to spawn.
+    /// Executable command to spawn, empty for compound tasks that only run their dependencies.
     pub command: String,
     /// Arguments to the command, potentially unsubstituted,
     /// to let the shell that spawns the command to do the substitution, if needed.
@@ -78,6 +90,12 @@ pub struct SpawnInTerminal {
     pub allow_concurrent_runs: bool,
     /// What to do with the terminal pane and tab, after the command was started.
     pub reveal: RevealStrategy,
+    /// Problem matchers to find diagnostics in the output of the command with.
+    pub problem_matchers: Vec<ProblemMatcher>,
+    /// Tasks to run before this one, which is only spawned if all of them succeed.
+    pub dependencies: Vec<SpawnInTerminal>,
+    /// How to run the tasks this one depends on.
+    pub depends_order: DependsOrder,
 }
 
 /// A final form of the [`TaskTemplate`], that got resolved with a particualar [`TaskContext`] and now is ready to spawn the actual task.
@@ -91,6 +109,8 @@ pub struct ResolvedTask {
     pub id: TaskId,
     /// A template the task got resolved from.
     original_task: TaskTemplate,
+    /// A context the task got resolved with, to resolve the tasks it depends on with.
+    task_context: TaskContext,
     /// Full, unshortened label of the task after all resolutions are made.
     pub resolved_label: String,
     /// Variables that were substituted during the task template resolution.
@@ -118,6 +138,95 @@ impl ResolvedTask {
             .map(|resolved| resolved.label.as_str())
             .unwrap_or_else(|| self.resolved_label.as_str())
     }
+
+    /// Resolves the tasks this task depends on with the same context, along with the tasks those depend on.
+    /// `find_template` looks up the template of a task by its label, returning it with the id base of its source.
+    ///
+    /// Fails if any of the tasks is not found or cannot be resolved, or if the tasks depend on each other in a cycle.
+    pub fn resolve_dependencies(
+        &mut self,
+        find_template: impl Fn(&str) -> Option<(String, TaskTemplate)>,
+    ) -> anyhow::Result<()> {
+        let dependencies = resolve_dependencies(
+            &self.original_task,
+            &self.task_context,
+            &find_template,
+            &mut vec![self.original_task.label.clone()],
+        )?;
+        if let Some(resolved) = &mut self.resolved {
+            resolved.dependencies = dependencies;
+        }
+        Ok(())
+    }
+
+    /// Inputs of the task that were not asked for yet.
+    pub fn missing_inputs(&self) -> impl Iterator<Item = &TaskInput> + '_ {
+        self.original_task.inputs.iter().filter(|input| {
+            self.task_context
+                .task_variables
+                .get(&VariableName::Input(Cow::Owned(input.id.clone())))
+                .is_none()
+        })
+    }
+
+    /// Resolves the task again, with the given values of its inputs, keyed by the input ids.
+    pub fn resolve_inputs(
+        &self,
+        id_base: &str,
+        values: impl IntoIterator<Item = (String, String)>,
+    ) -> Option<ResolvedTask> {
+        let mut task_context = self.task_context.clone();
+        for (id, value) in values {
+            task_context
+                .task_variables
+                .insert(VariableName::Input(Cow::Owned(id)), value);
+        }
+        self.original_task.resolve_task(id_base, &task_context)
+    }
+
+    /// Values of the inputs of the task, to reuse when resolving the task again.
+    pub fn input_variables(&self) -> TaskVariables {
+        self.task_context
+            .task_variables
+            .0
+            .iter()
+            .filter(|(name, _)| matches!(name, VariableName::Input(_)))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+}
+
+fn resolve_dependencies(
+    template: &TaskTemplate,
+    cx: &TaskContext,
+    find_template: &dyn Fn(&str) -> Option<(String, TaskTemplate)>,
+    dependents: &mut Vec<String>,
+) -> anyhow::Result<Vec<SpawnInTerminal>> {
+    let mut dependencies = Vec::with_capacity(template.depends_on.len());
+    for label in &template.depends_on {
+        if dependents.contains(label) {
+            bail!(
+                "Task `{label}` depends on itself: {} -> {label}",
+                dependents.join(" -> ")
+            );
+        }
+        let (id_base, dependency_template) = find_template(label).with_context(|| {
+            format!(
+                "Task `{}` depends on unknown task `{label}`",
+                template.label
+            )
+        })?;
+        let mut dependency = dependency_template
+            .resolve_task(&id_base, cx)
+            .and_then(|task| task.resolved)
+            .with_context(|| format!("Failed to resolve task `{label}`"))?;
+        dependents.push(label.clone());
+        dependency.dependencies =
+            resolve_dependencies(&dependency_template, cx, find_template, dependents)?;
+        dependents.pop();
+        dependencies.push(dependency);
+    }
+    Ok(dependencies)
 }
 
 /// Variables, available for use in [`TaskContext`] when a Zed's [`TaskTemplate`] gets resolved into a [`ResolvedTask`].
@@ -151,6 +260,9 @@ pub enum VariableName {
     /// Custom variable, provided by the plugin or other external source.
     /// Will be printed with `CUSTOM_` prefix to avoid potential conflicts with other variables.
     Custom(Cow<'static, str>),
+    /// Value of an input of the task, asked for before spawning it.
+    /// Will be printed with `INPUT_` prefix, and may also be referenced as `${input:id}` in templates.
+    Input(Cow<'static, str>),
 }
 
 impl VariableName {
@@ -177,6 +289,10 @@ impl FromStr for VariableName {
                     without_prefix.strip_prefix(ZED_CUSTOM_VARIABLE_NAME_PREFIX)
                 {
                     Self::Custom(Cow::Owned(custom_name.to_owned()))
+                } else if let Some(input_id) =
+                    without_prefix.strip_prefix(ZED_INPUT_VARIABLE_NAME_PREFIX)
+                {
+                    Self::Input(Cow::Owned(input_id.to_owned()))
                 } else {
                     return Err(());
                 }
@@ -189,6 +305,9 @@ impl FromStr for VariableName {
 /// A prefix that all [`VariableName`] variants are prefixed with when used in environment variables and similar template contexts.
 pub const ZED_VARIABLE_NAME_PREFIX: &str = "ZED_";
 const ZED_CUSTOM_VARIABLE_NAME_PREFIX: &str = "CUSTOM_";
+const ZED_INPUT_VARIABLE_NAME_PREFIX: &str = "INPUT_";
+/// A prefix of the [`VariableName::Input`] variables, as used in templates.
+pub(crate) const ZED_INPUT_VARIABLE_PREFIX: &str = "ZED_INPUT_";
 
 impl std::fmt::Display for VariableName {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -208,6 +327,10 @@ impl std::fmt::Display for VariableName {
                 f,
                 "{ZED_VARIABLE_NAME_PREFIX}{ZED_CUSTOM_VARIABLE_NAME_PREFIX}{s}"
             ),
+            Self::Input(s) => write!(
+                f,
+                "{ZED_VARIABLE_NAME_PREFIX}{ZED_INPUT_VARIABLE_NAME_PREFIX}{s}"
+            ),
         }
     }
 }
@@ -0,0 +1,657 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::ResultExt;
+
+/// A problem matcher of a task, either referenced by the name of a built-in one or defined inline.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcherReference {
+    /// Name of a built-in problem matcher: `$rustc`, `$tsc`, `$tsc-watch`, `$gcc`, `$eslint-stylish` or `$eslint-compact`.
+    Named(String),
+    /// A problem matcher defined in place.
+    Inline(ProblemMatcher),
+}
+
+impl ProblemMatcherReference {
+    /// Looks up the referenced problem matcher, filling the fields of an inline one that are not set
+    /// from its `base` matcher.
+    pub fn resolve(&self) -> anyhow::Result<ProblemMatcher> {
+        match self {
+            Self::Named(name) => builtin_problem_matcher(name)
+                .with_context(|| format!("unknown problem matcher {name}")),
+            Self::Inline(matcher) => {
+                let Some(base) = matcher.base.as_deref() else {
+                    return Ok(matcher.clone());
+                };
+                let base = builtin_problem_matcher(base)
+                    .with_context(|| format!("unknown base problem matcher {base}"))?;
+                Ok(ProblemMatcher {
+                    base: None,
+                    source: matcher.source.clone().or(base.source),
+                    severity: matcher.severity.or(base.severity),
+                    file_location: matcher.file_location.or(base.file_location),
+                    base_directory: matcher.base_directory.clone().or(base.base_directory),
+                    pattern: if matcher.pattern.is_empty() {
+                        base.pattern
+                    } else {
+                        matcher.pattern.clone()
+                    },
+                    background: matcher.background.clone().or(base.background),
+                })
+            }
+        }
+    }
+}
+
+/// Describes how to find problems, such as compiler errors, in the output of a task, so that they
+/// are shown as diagnostics.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemMatcher {
+    /// Name of a built-in problem matcher to take the fields that are not set from.
+    #[serde(default)]
+    pub base: Option<String>,
+    /// Source of the diagnostics, defaults to the label of the task.
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Severity of the problems whose pattern does not capture one, defaults to `error`.
+    #[serde(default)]
+    pub severity: Option<ProblemSeverity>,
+    /// How to interpret the file paths of the problems, defaults to `auto_detect`.
+    #[serde(default)]
+    pub file_location: Option<FileLocation>,
+    /// Directory that relative file paths of the problems are relative to, defaults to the working directory of the task.
+    #[serde(default)]
+    pub base_directory: Option<String>,
+    /// Patterns matching consecutive lines of the output, that together describe a problem.
+    #[serde(default)]
+    pub pattern: Vec<ProblemPattern>,
+    /// Set for tasks that keep running and report problems in cycles, such as file watchers.
+    #[serde(default)]
+    pub background: Option<BackgroundMatcher>,
+}
+
+/// A regular expression matching a line of the task output, along with the indices of the capture
+/// groups holding each part of a problem.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match the line with.
+    pub regexp: String,
+    /// Capture group of the file path.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// Capture group of the whole location: `line`, `line,column` or `line,column,end_line,end_column`.
+    #[serde(default)]
+    pub location: Option<usize>,
+    /// Capture group of the one-based line.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// Capture group of the one-based column.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// Capture group of the one-based line the problem ends at.
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// Capture group of the one-based column the problem ends at.
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    /// Capture group of the severity, such as `error` or `warning`.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// Capture group of the code of the problem.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// Capture group of the message.
+    #[serde(default)]
+    pub message: Option<usize>,
+    /// Whether the last pattern of a multi-line matcher may match several lines in a row, each
+    /// reporting a problem.
+    #[serde(default, rename = "loop")]
+    pub loop_: bool,
+}
+
+/// Marks the start and the end of each cycle of a background task, the problems of a finished
+/// cycle replacing the ones of the previous one.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BackgroundMatcher {
+    /// Whether a cycle is already running when the task starts.
+    #[serde(default)]
+    pub active_on_start: bool,
+    /// The regular expression matching the line that starts a cycle.
+    pub begins_pattern: String,
+    /// The regular expression matching the line that ends a cycle.
+    pub ends_pattern: String,
+}
+
+/// How to interpret the file paths of the problems.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FileLocation {
+    /// The paths are absolute.
+    Absolute,
+    /// The paths are relative to the base directory.
+    Relative,
+    /// Absolute paths are used as they are, others are relative to the base directory.
+    AutoDetect,
+}
+
+/// The severity of a problem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    /// An error.
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message.
+    Info,
+    /// A hint, such as a note attached to an error.
+    Hint,
+}
+
+impl ProblemSeverity {
+    fn parse(text: &str) -> Option<Self> {
+        match text.to_ascii_lowercase().as_str() {
+            "error" | "fatal" | "e" => Some(Self::Error),
+            "warning" | "warn" | "w" => Some(Self::Warning),
+            "info" | "information" | "i" => Some(Self::Info),
+            "hint" | "note" | "help" => Some(Self::Hint),
+            _ => None,
+        }
+    }
+}
+
+/// A problem found in the output of a task.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// The file of the problem, absolute if it could be resolved.
+    pub path: PathBuf,
+    /// The zero-based line of the problem.
+    pub line: u32,
+    /// The zero-based column of the problem, if reported.
+    pub column: Option<u32>,
+    /// The zero-based line and column where the problem ends, if reported.
+    pub end: Option<(u32, Option<u32>)>,
+    /// How severe the problem is.
+    pub severity: ProblemSeverity,
+    /// The code of the problem, if reported.
+    pub code: Option<String>,
+    /// What the problem is about.
+    pub message: String,
+    /// The source of the problem, if its matcher sets one.
+    pub source: Option<String>,
+}
+
+/// Finds the problems the given matchers describe in the output of a task, with relative paths of
+/// the problems resolved against `cwd`, the working directory of the task.
+pub fn find_problems(
+    matchers: &[ProblemMatcher],
+    output: &str,
+    cwd: Option<&Path>,
+) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    for matcher in matchers {
+        if let Some(mut compiled) = CompiledMatcher::new(matcher).log_err() {
+            for line in output.lines() {
+                compiled.match_line(line.trim_end());
+            }
+            problems.extend(
+                compiled
+                    .into_problems()
+                    .into_iter()
+                    .filter_map(|problem| problem.into_problem(matcher, cwd)),
+            );
+        }
+    }
+    problems
+}
+
+struct CompiledMatcher<'a> {
+    patterns: Vec<(Regex, &'a ProblemPattern)>,
+    background: Option<(Regex, Regex)>,
+    /// Whether the lines are within a cycle of a background task, always set for other tasks.
+    active: bool,
+    /// The problem matched by the first patterns of a multi-line matcher, along with the index of
+    /// the pattern to match the next line with.
+    pending: Option<(ProblemCaptures, usize)>,
+    current_problems: Vec<ProblemCaptures>,
+    /// The problems of the last finished cycle of a background task.
+    finished_problems: Option<Vec<ProblemCaptures>>,
+}
+
+impl<'a> CompiledMatcher<'a> {
+    fn new(matcher: &'a ProblemMatcher) -> anyhow::Result<Self> {
+        if matcher.pattern.is_empty() {
+            return Err(anyhow!("problem matcher has no patterns"));
+        }
+        let patterns = matcher
+            .pattern
+            .iter()
+            .map(|pattern| {
+                let regex = Regex::new(&pattern.regexp)
+                    .with_context(|| format!("parsing problem pattern {:?}", pattern.regexp))?;
+                anyhow::Ok((regex, pattern))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let background = matcher
+            .background
+            .as_ref()
+            .map(|background| {
+                anyhow::Ok((
+                    Regex::new(&background.begins_pattern).context("parsing the begins pattern")?,
+                    Regex::new(&background.ends_pattern).context("parsing the ends pattern")?,
+                ))
+            })
+            .transpose()?;
+        Ok(Self {
+            patterns,
+            active: matcher
+                .background
+                .as_ref()
+                .map_or(true, |background| background.active_on_start),
+            background,
+            pending: None,
+            current_problems: Vec::new(),
+            finished_problems: None,
+        })
+    }
+
+    fn match_line(&mut self, line: &str) {
+        if let Some((begins_pattern, ends_pattern)) = &self.background {
+            if begins_pattern.is_match(line) {
+                self.active = true;
+                self.pending = None;
+                self.current_problems.clear();
+                return;
+            }
+            if ends_pattern.is_match(line) {
+                self.active = false;
+                self.pending = None;
+                self.finished_problems = Some(std::mem::take(&mut self.current_problems));
+                return;
+            }
+        }
+        if !self.active {
+            return;
+        }
+
+        if let Some((captures, pattern_ix)) = self.pending.take() {
+            let (regex, pattern) = &self.patterns[pattern_ix];
+            if let Some(line_captures) = regex.captures(line) {
+                let mut captures = captures;
+                captures.fill(pattern, &line_captures);
+                if pattern_ix + 1 < self.patterns.len() {
+                    self.pending = Some((captures, pattern_ix + 1));
+                } else {
+                    if pattern.loop_ {
+                        // Each line matching a looping pattern is a problem of its own, sharing
+                        // the parts captured by the previous patterns.
+                        let mut base = captures.clone();
+                        base.clear(pattern);
+                        self.pending = Some((base, pattern_ix));
+                    }
+                    self.current_problems.push(captures);
+                }
+                return;
+            }
+        }
+
+        let (regex, pattern) = &self.patterns[0];
+        if let Some(line_captures) = regex.captures(line) {
+            let mut captures = ProblemCaptures::default();
+            captures.fill(pattern, &line_captures);
+            if self.patterns.len() > 1 {
+                self.pending = Some((captures, 1));
+            } else {
+                self.current_problems.push(captures);
+            }
+        }
+    }
+
+    fn into_problems(self) -> Vec<ProblemCaptures> {
+        // Keep showing the problems of the last finished cycle while another one runs.
+        self.finished_problems.unwrap_or(self.current_problems)
+    }
+}
+
+/// The parts of a problem captured from the lines of the output.
+#[derive(Clone, Default)]
+struct ProblemCaptures {
+    file: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    end_line: Option<u32>,
+    end_column: Option<u32>,
+    severity: Option<String>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl ProblemCaptures {
+    fn fill(&mut self, pattern: &ProblemPattern, captures: &Captures) {
+        let group = |ix: Option<usize>| {
+            ix.and_then(|ix| captures.get(ix))
+                .map(|capture| capture.as_str())
+                .filter(|text| !text.is_empty())
+        };
+        let number = |ix: Option<usize>| group(ix).and_then(|text| text.trim().parse().ok());
+
+        if let Some(file) = group(pattern.file) {
+            self.file = Some(file.trim().to_string());
+        }
+        if let Some(location) = group(pattern.location) {
+            let mut numbers = location
+                .trim_matches(|c| c == '(' || c == ')')
+                .split(',')
+                .map(|number| number.trim().parse().ok());
+            self.line = numbers.next().flatten().or(self.line);
+            self.column = numbers.next().flatten().or(self.column);
+            self.end_line = numbers.next().flatten().or(self.end_line);
+            self.end_column = numbers.next().flatten().or(self.end_column);
+        }
+        self.line = number(pattern.line).or(self.line);
+        self.column = number(pattern.column).or(self.column);
+        self.end_line = number(pattern.end_line).or(self.end_line);
+        self.end_column = number(pattern.end_column).or(self.end_column);
+        if let Some(severity) = group(pattern.severity) {
+            self.severity = Some(severity.to_string());
+        }
+        if let Some(code) = group(pattern.code) {
+            self.code = Some(code.to_string());
+        }
+        if let Some(message) = group(pattern.message) {
+            self.message = Some(message.to_string());
+        }
+    }
+
+    /// Forgets the parts captured by the given pattern.
+    fn clear(&mut self, pattern: &ProblemPattern) {
+        if pattern.file.is_some() {
+            self.file = None;
+        }
+        if pattern.location.is_some() || pattern.line.is_some() {
+            self.line = None;
+            self.column = None;
+            self.end_line = None;
+            self.end_column = None;
+        }
+        if pattern.severity.is_some() {
+            self.severity = None;
+        }
+        if pattern.code.is_some() {
+            self.code = None;
+        }
+        if pattern.message.is_some() {
+            self.message = None;
+        }
+    }
+
+    fn into_problem(self, matcher: &ProblemMatcher, cwd: Option<&Path>) -> Option<Problem> {
+        let file = PathBuf::from(self.file?);
+        let base_directory = matcher.base_directory.as_deref().map(Path::new).or(cwd);
+        let path = match (matcher.file_location, base_directory) {
+            (Some(FileLocation::Absolute), _) | (_, None) => file,
+            (Some(FileLocation::Relative), Some(base_directory)) => base_directory.join(file),
+            (Some(FileLocation::AutoDetect) | None, Some(base_directory)) => {
+                if file.is_absolute() {
+                    file
+                } else {
+                    base_directory.join(file)
+                }
+            }
+        };
+        let line = self.line.unwrap_or(1).saturating_sub(1);
+        Some(Problem {
+            path,
+            line,
+            column: self.column.map(|column| column.saturating_sub(1)),
+            end: self.end_line.map(|end_line| {
+                (
+                    end_line.saturating_sub(1),
+                    self.end_column.map(|column| column.saturating_sub(1)),
+                )
+            }),
+            severity: self
+                .severity
+                .as_deref()
+                .and_then(ProblemSeverity::parse)
+                .or(matcher.severity)
+                .unwrap_or(ProblemSeverity::Error),
+            code: self.code,
+            message: self.message.unwrap_or_default(),
+            source: matcher.source.clone(),
+        })
+    }
+}
+
+/// The problem matchers that can be referenced by name.
+fn builtin_problem_matcher(name: &str) -> Option<ProblemMatcher> {
+    let pattern = |regexp: &str| ProblemPattern {
+        regexp: regexp.to_string(),
+        ..ProblemPattern::default()
+    };
+    let matcher = match name {
+        "$rustc" => ProblemMatcher {
+            pattern: vec![
+                ProblemPattern {
+                    severity: Some(1),
+                    code: Some(2),
+                    message: Some(3),
+                    ..pattern(r"^(warning|warn|error)(?:\[(.*?)\])?: (.*)$")
+                },
+                ProblemPattern {
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    ..pattern(r"^[\s\->=]*(.*?):(\d+):(\d+)\s*$")
+                },
+            ],
+            ..ProblemMatcher::default()
+        },
+        "$tsc" | "$tsc-watch" => ProblemMatcher {
+            file_location: Some(FileLocation::Relative),
+            pattern: vec![ProblemPattern {
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                code: Some(5),
+                message: Some(6),
+                ..pattern(
+                    r"^([^\s].*)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+TS(\d+)\s*:\s*(.*)$",
+                )
+            }],
+            background: (name == "$tsc-watch").then(|| BackgroundMatcher {
+                active_on_start: true,
+                begins_pattern: r"(Starting compilation in watch mode|File change detected\. Starting incremental compilation)\.\.\."
+                    .to_string(),
+                ends_pattern: r"(Compilation complete\.|Found \d+ errors?\.) Watching for file changes\."
+                    .to_string(),
+            }),
+            ..ProblemMatcher::default()
+        },
+        "$gcc" => ProblemMatcher {
+            pattern: vec![ProblemPattern {
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                ..pattern(r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error|note):\s+(.*)$")
+            }],
+            ..ProblemMatcher::default()
+        },
+        "$eslint-stylish" => ProblemMatcher {
+            file_location: Some(FileLocation::Absolute),
+            pattern: vec![
+                ProblemPattern {
+                    file: Some(1),
+                    ..pattern(r"^([^\s].*)$")
+                },
+                ProblemPattern {
+                    line: Some(1),
+                    column: Some(2),
+                    severity: Some(3),
+                    message: Some(4),
+                    code: Some(5),
+                    loop_: true,
+                    ..pattern(r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(.*))?$")
+                },
+            ],
+            ..ProblemMatcher::default()
+        },
+        "$eslint-compact" => ProblemMatcher {
+            pattern: vec![ProblemPattern {
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                code: Some(6),
+                ..pattern(r"^(.+):\sline\s(\d+),\scol\s(\d+),\s(Error|Warning|Info)\s-\s(.+)\s\((.+)\)$")
+            }],
+            ..ProblemMatcher::default()
+        },
+        _ => return None,
+    };
+    Some(matcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builtin_problems(name: &str, output: &str) -> Vec<Problem> {
+        let matcher = ProblemMatcherReference::Named(name.to_string())
+            .resolve()
+            .unwrap();
+        find_problems(&[matcher], output, Some(Path::new("/project")))
+    }
+
+    #[test]
+    fn test_rustc_problem_matcher() {
+        let output = "   Compiling app v0.1.0 (/project)
+error[E0425]: cannot find value `y` in this scope
+ --> src/main.rs:3:20
+  |
+3 |     println!(\"{}\", y);
+  |                    ^ help: a local variable with a similar name exists: `x`
+
+warning: unused variable: `x`
+ --> src/lib.rs:2:9
+  |
+error: could not compile `app` (bin \"app\") due to 1 previous error
+";
+        assert_eq!(
+            builtin_problems("$rustc", output),
+            vec![
+                Problem {
+                    path: PathBuf::from("/project/src/main.rs"),
+                    line: 2,
+                    column: Some(19),
+                    end: None,
+                    severity: ProblemSeverity::Error,
+                    code: Some("E0425".to_string()),
+                    message: "cannot find value `y` in this scope".to_string(),
+                    source: None,
+                },
+                Problem {
+                    path: PathBuf::from("/project/src/lib.rs"),
+                    line: 1,
+                    column: Some(8),
+                    end: None,
+                    severity: ProblemSeverity::Warning,
+                    code: None,
+                    message: "unused variable: `x`".to_string(),
+                    source: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_looping_problem_matcher() {
+        let output = "
+/project/src/app.js
+  1:10  error    'foo' is defined but never used  no-unused-vars
+  3:1   warning  Unexpected console statement     no-console
+
+/project/src/util.js
+  7:5  error  Missing semicolon  semi
+
+✖ 3 problems (2 errors, 1 warning)
+";
+        let problems = builtin_problems("$eslint-stylish", output)
+            .into_iter()
+            .map(|problem| {
+                (
+                    problem.path.to_string_lossy().into_owned(),
+                    problem.line,
+                    problem.severity,
+                    problem.code,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            vec![
+                (
+                    "/project/src/app.js".to_string(),
+                    0,
+                    ProblemSeverity::Error,
+                    Some("no-unused-vars".to_string())
+                ),
+                (
+                    "/project/src/app.js".to_string(),
+                    2,
+                    ProblemSeverity::Warning,
+                    Some("no-console".to_string())
+                ),
+                (
+                    "/project/src/util.js".to_string(),
+                    6,
+                    ProblemSeverity::Error,
+                    Some("semi".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_background_problem_matcher() {
+        let cycle = |errors: &str| {
+            format!(
+                "[12:00:00 PM] File change detected. Starting incremental compilation...\n{errors}[12:00:01 PM] Found 1 error. Watching for file changes.\n"
+            )
+        };
+        let first_cycle = cycle(
+            "src/a.ts(1,5): error TS2322: Type 'string' is not assignable to type 'number'.\n",
+        );
+        let second_cycle = cycle("src/b.ts:4:1 - error TS2304: Cannot find name 'foo'.\n");
+        let files = |output: &str| {
+            builtin_problems("$tsc-watch", output)
+                .into_iter()
+                .map(|problem| problem.path)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(files(&first_cycle), [PathBuf::from("/project/src/a.ts")]);
+        // A running cycle keeps the problems of the previous one until it finishes.
+        assert_eq!(
+            files(&format!(
+                "{first_cycle}[12:01:00 PM] File change detected. Starting incremental compilation...\n"
+            )),
+            [PathBuf::from("/project/src/a.ts")]
+        );
+        assert_eq!(
+            files(&format!("{first_cycle}{second_cycle}")),
+            [PathBuf::from("/project/src/b.ts")]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{borrow::Cow, path::PathBuf};
 
 use anyhow::{bail, Context};
 use collections::{HashMap, HashSet};
@@ -8,10 +8,13 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, SpawnInTerminal, TaskContext, TaskId, TerminalWorkDir, VariableName,
-    ZED_VARIABLE_NAME_PREFIX,
+    ProblemMatcherReference, ResolvedTask, SpawnInTerminal, TaskContext, TaskId, TerminalWorkDir,
+    VariableName, ZED_INPUT_VARIABLE_PREFIX, ZED_VARIABLE_NAME_PREFIX,
 };
 
+/// How the inputs of a task are referenced in its fields, as `${input:id}`.
+const INPUT_REFERENCE_PREFIX: &str = "input:";
+
 /// A template definition of a Zed task to run.
 /// May use the [`VariableName`] to get the corresponding substitutions into its fields.
 ///
@@ -24,6 +27,8 @@ pub struct TaskTemplate {
     /// Human readable name of the task to display in the UI.
     pub label: String,
     /// Executable command to spawn.
+    /// May be omitted by compound tasks, which only run the tasks they depend on.
+    #[serde(default)]
     pub command: String,
     /// Arguments to the command.
     #[serde(default)]
@@ -49,6 +54,36 @@ pub struct TaskTemplate {
     /// Represents the tags which this template attaches to. Adding this removes this task from other UI.
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Problem matchers to turn the output of the command into diagnostics with, either names of built-in ones
+    /// (`$rustc`, `$tsc`, `$tsc-watch`, `$gcc`, `$eslint-stylish`, `$eslint-compact`) or inline definitions.
+    #[serde(default)]
+    pub problem_matchers: Vec<ProblemMatcherReference>,
+    /// Labels of the tasks to run before this one, which is only run if all of them succeed.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// How to run the tasks this one depends on:
+    /// * `parallel` — start all of them at once (default)
+    /// * `sequence` — run them one after another, in the order listed
+    #[serde(default)]
+    pub depends_order: DependsOrder,
+    /// Values to ask for before spawning the task, referenced as `${input:id}` in its other fields.
+    #[serde(default)]
+    pub inputs: Vec<TaskInput>,
+    /// Globs of the paths, relative to the worktree root, whose changes rerun the task while it is watched, defaults to none.
+    /// A task with any is watched after it is spawned, and each rerun replaces the run before it.
+    #[serde(default)]
+    pub watch: Vec<String>,
+}
+
+/// How to run the tasks a task depends on.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Start all of the tasks at once.
+    #[default]
+    Parallel,
+    /// Run the tasks one after another, in the order they are listed, stopping at the first one that fails.
+    Sequence,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -62,6 +97,40 @@ pub enum RevealStrategy {
     Never,
 }
 
+/// A value to ask for before spawning a task.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TaskInput {
+    /// Identifier of the input, to reference its value with as `${input:id}`.
+    pub id: String,
+    /// How to ask for the value:
+    /// * `prompt_string` — let the value be typed in
+    /// * `pick_string` — let the value be picked out of the `options`
+    #[serde(rename = "type")]
+    pub kind: TaskInputKind,
+    /// Text to show when asking for the value.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Value to use when none is typed in, or to preselect among the options.
+    #[serde(default)]
+    pub default: Option<String>,
+    /// Values to pick from.
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+/// How to ask for the value of a [`TaskInput`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskInputKind {
+    /// Let the value be typed in.
+    #[serde(alias = "promptString")]
+    PromptString,
+    /// Let the value be picked out of the options of the input.
+    #[serde(alias = "pickString")]
+    PickString,
+}
+
 /// A group of Tasks defined in a JSON file.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TaskTemplates(pub Vec<TaskTemplate>);
@@ -85,8 +154,13 @@ impl TaskTemplate {
     ///
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
+    ///
+    /// A compound task, which has no command of its own, gets resolved too, as long as it depends on other tasks.
+    /// The tasks it depends on are not resolved here, see [`ResolvedTask::resolve_dependencies`].
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        if self.label.trim().is_empty()
+            || (self.command.trim().is_empty() && self.depends_on.is_empty())
+        {
             return None;
         }
 
@@ -169,11 +243,27 @@ impl TaskTemplate {
             &variable_names,
             &mut substituted_variables,
         )?;
+        let mut problem_matchers = Vec::with_capacity(self.problem_matchers.len());
+        for reference in &self.problem_matchers {
+            let Some(mut problem_matcher) = reference.resolve().log_err() else {
+                continue;
+            };
+            if let Some(base_directory) = problem_matcher.base_directory.as_deref() {
+                problem_matcher.base_directory = Some(substitute_all_template_variables_in_str(
+                    base_directory,
+                    &task_variables,
+                    &variable_names,
+                    &mut substituted_variables,
+                )?);
+            }
+            problem_matchers.push(problem_matcher);
+        }
         env.extend(task_variables.into_iter().map(|(k, v)| (k, v.to_owned())));
         Some(ResolvedTask {
             id: id.clone(),
             substituted_variables,
             original_task: self.clone(),
+            task_context: cx.clone(),
             resolved_label: full_label.clone(),
             resolved: Some(SpawnInTerminal {
                 id,
@@ -194,6 +284,9 @@ impl TaskTemplate {
                 use_new_terminal: self.use_new_terminal,
                 allow_concurrent_runs: self.allow_concurrent_runs,
                 reveal: self.reveal,
+                problem_matchers,
+                dependencies: Vec::new(),
+                depends_order: self.depends_order,
             }),
         })
     }
@@ -220,16 +313,23 @@ fn to_hex_hash(object: impl Serialize) -> anyhow::Result<String> {
     Ok(hex::encode(hasher.finalize()))
 }
 
-fn substitute_all_template_variables_in_str<A: AsRef<str>>(
+pub(crate) fn substitute_all_template_variables_in_str<A: AsRef<str>>(
     template_str: &str,
     task_variables: &HashMap<String, A>,
     variable_names: &HashMap<String, VariableName>,
     substituted_variables: &mut HashSet<VariableName>,
 ) -> Option<String> {
     let substituted_string = shellexpand::env_with_context(template_str, |var| {
-        // Colons denote a default value in case the variable is not set. We want to preserve that default, as otherwise shellexpand will substitute it for us.
-        let colon_position = var.find(':').unwrap_or(var.len());
-        let (variable_name, default) = var.split_at(colon_position);
+        let input_variable;
+        let (variable_name, default) = match var.strip_prefix(INPUT_REFERENCE_PREFIX) {
+            // Inputs are referenced as `${input:id}`, with no default value after the colon.
+            Some(input_id) => {
+                input_variable = VariableName::Input(Cow::Owned(input_id.to_owned())).to_string();
+                (input_variable.as_str(), "")
+            }
+            // Colons denote a default value in case the variable is not set. We want to preserve that default, as otherwise shellexpand will substitute it for us.
+            None => var.split_at(var.find(':').unwrap_or(var.len())),
+        };
         if let Some(name) = task_variables.get(variable_name) {
             if let Some(substituted_variable) = variable_names.get(variable_name) {
                 substituted_variables.insert(substituted_variable.clone());
@@ -241,6 +341,9 @@ fn substitute_all_template_variables_in_str<A: AsRef<str>>(
                 name.push_str(default);
             }
             return Ok(Some(name));
+        } else if variable_name.starts_with(ZED_INPUT_VARIABLE_PREFIX) {
+            // The value of the input was not asked for yet.
+            return Ok(Some(format!("${{{var}}}")));
         } else if variable_name.starts_with(ZED_VARIABLE_NAME_PREFIX) {
             bail!("Unknown variable name: {variable_name}");
         }
@@ -257,7 +360,7 @@ fn substitute_all_template_variables_in_str<A: AsRef<str>>(
     Some(substituted_string.into_owned())
 }
 
-fn substitute_all_template_variables_in_vec(
+pub(crate) fn substitute_all_template_variables_in_vec(
     template_strs: &[String],
     task_variables: &HashMap<String, &str>,
     variable_names: &HashMap<String, VariableName>,
@@ -276,7 +379,7 @@ fn substitute_all_template_variables_in_vec(
     Some(expanded)
 }
 
-fn substitute_all_template_variables_in_map(
+pub(crate) fn substitute_all_template_variables_in_map(
     keys_and_values: &HashMap<String, String>,
     task_variables: &HashMap<String, &str>,
     variable_names: &HashMap<String, VariableName>,
@@ -342,6 +445,17 @@ mod tests {
                 "should not resolve task with blank label and/or command: {task_with_blank_property:?}"
             );
         }
+
+        let compound_task = TaskTemplate {
+            command: "".to_string(),
+            args: Vec::new(),
+            depends_on: vec!["other_label".to_string()],
+            ..task_with_all_properties.clone()
+        };
+        let resolved_task = compound_task
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("should resolve task with blank command that depends on other tasks");
+        assert_eq!(resolved_task.resolved.unwrap().command, "");
     }
 
     #[test]
@@ -702,4 +816,182 @@ mod tests {
             .insert(VariableName::Symbol, "my-symbol".to_string());
         assert!(faulty_go_test.resolve_task("base", &context).is_some());
     }
+
+    #[test]
+    fn test_problem_matchers_resolution() {
+        let task = TaskTemplate {
+            label: "tsc".to_string(),
+            command: "tsc".to_string(),
+            problem_matchers: vec![
+                ProblemMatcherReference::Named("$rustc".to_string()),
+                ProblemMatcherReference::Named("$unknown".to_string()),
+                ProblemMatcherReference::Inline(crate::ProblemMatcher {
+                    base: Some("$tsc".to_string()),
+                    base_directory: Some(format!(
+                        "{}/web",
+                        VariableName::WorktreeRoot.template_value()
+                    )),
+                    ..Default::default()
+                }),
+            ],
+            ..TaskTemplate::default()
+        };
+        let mut context = TaskContext::default();
+        context
+            .task_variables
+            .insert(VariableName::WorktreeRoot, "/project".to_string());
+
+        let resolved_task = task.resolve_task(TEST_ID_BASE, &context).unwrap();
+        let problem_matchers = &resolved_task.resolved.unwrap().problem_matchers;
+        assert_eq!(
+            problem_matchers.len(),
+            2,
+            "Unknown problem matchers should be skipped"
+        );
+        assert_eq!(problem_matchers[0].pattern.len(), 2);
+        assert_eq!(problem_matchers[1].base, None);
+        assert_eq!(
+            problem_matchers[1].base_directory.as_deref(),
+            Some("/project/web")
+        );
+        assert_eq!(problem_matchers[1].pattern.len(), 1);
+    }
+
+    #[test]
+    fn test_dependencies_resolution() {
+        let template = |label: &str, command: &str, depends_on: &[&str]| TaskTemplate {
+            label: label.to_string(),
+            command: command.to_string(),
+            cwd: Some(VariableName::WorktreeRoot.template_value()),
+            depends_on: depends_on.iter().map(|label| label.to_string()).collect(),
+            ..TaskTemplate::default()
+        };
+        let templates = [
+            template("build", "cargo build", &[]),
+            template("lint", "cargo clippy", &[]),
+            template("test", "cargo test", &["build"]),
+            template("check", "", &["lint", "test"]),
+            template("cycle a", "echo a", &["cycle b"]),
+            template("cycle b", "echo b", &["cycle a"]),
+            template("broken", "echo broken", &["missing"]),
+        ];
+        let find_template = |label: &str| {
+            templates
+                .iter()
+                .find(|template| template.label == label)
+                .map(|template| (TEST_ID_BASE.to_string(), template.clone()))
+        };
+        let mut context = TaskContext::default();
+        context
+            .task_variables
+            .insert(VariableName::WorktreeRoot, "/project".to_string());
+
+        let mut resolved_task = find_template("check")
+            .unwrap()
+            .1
+            .resolve_task(TEST_ID_BASE, &context)
+            .unwrap();
+        resolved_task.resolve_dependencies(find_template).unwrap();
+        let spawn_in_terminal = resolved_task.resolved.unwrap();
+        let dependencies = &spawn_in_terminal.dependencies;
+        assert_eq!(
+            dependencies
+                .iter()
+                .map(|dependency| dependency.label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["lint", "test"]
+        );
+        assert_eq!(dependencies[1].dependencies.len(), 1);
+        let build = &dependencies[1].dependencies[0];
+        assert_eq!(build.command, "cargo build");
+        assert_eq!(
+            build.cwd,
+            Some(TerminalWorkDir::Local(PathBuf::from("/project"))),
+            "Dependencies should be resolved with the context of the task depending on them"
+        );
+
+        for label in ["cycle a", "broken"] {
+            let mut resolved_task = find_template(label)
+                .unwrap()
+                .1
+                .resolve_task(TEST_ID_BASE, &context)
+                .unwrap();
+            assert!(
+                resolved_task.resolve_dependencies(find_template).is_err(),
+                "Task `{label}` should fail to resolve its dependencies"
+            );
+        }
+    }
+
+    #[test]
+    fn test_inputs_resolution() {
+        let task = TaskTemplate {
+            label: "test ${input:filter}".to_string(),
+            command: "cargo test ${input:filter}".to_string(),
+            args: vec!["--".to_string(), "$ZED_INPUT_threads".to_string()],
+            inputs: vec![
+                TaskInput {
+                    id: "filter".to_string(),
+                    kind: TaskInputKind::PromptString,
+                    description: None,
+                    default: None,
+                    options: Vec::new(),
+                },
+                TaskInput {
+                    id: "threads".to_string(),
+                    kind: TaskInputKind::PickString,
+                    description: None,
+                    default: Some("1".to_string()),
+                    options: vec!["1".to_string(), "4".to_string()],
+                },
+            ],
+            ..TaskTemplate::default()
+        };
+
+        let unanswered_task = task
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("should resolve task with inputs not asked for yet");
+        assert_eq!(
+            unanswered_task
+                .missing_inputs()
+                .map(|input| input.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["filter", "threads"]
+        );
+        assert_eq!(
+            unanswered_task.resolved.as_ref().unwrap().command,
+            "cargo test ${input:filter}",
+            "Inputs not asked for yet should be left as they are"
+        );
+
+        let answered_task = unanswered_task
+            .resolve_inputs(
+                TEST_ID_BASE,
+                [
+                    ("filter".to_string(), "parser".to_string()),
+                    ("threads".to_string(), "4".to_string()),
+                ],
+            )
+            .unwrap();
+        assert_eq!(answered_task.missing_inputs().count(), 0);
+        assert_ne!(answered_task.id, unanswered_task.id);
+        assert_eq!(answered_task.resolved_label, "test parser");
+        let spawn_in_terminal = answered_task.resolved.as_ref().unwrap();
+        assert_eq!(spawn_in_terminal.command, "cargo test parser");
+        assert_eq!(spawn_in_terminal.command_label, "cargo test parser -- 4");
+        assert_eq!(
+            spawn_in_terminal
+                .env
+                .get("ZED_INPUT_threads")
+                .map(String::as_str),
+            Some("4")
+        );
+        assert_eq!(
+            answered_task.input_variables(),
+            TaskVariables::from_iter([
+                (VariableName::Input("filter".into()), "parser".to_string()),
+                (VariableName::Input("threads".into()), "4".to_string()),
+            ])
+        );
+    }
 }
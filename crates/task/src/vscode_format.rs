@@ -1,9 +1,13 @@
-use anyhow::bail;
+use anyhow::{bail, Context};
 use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    BackgroundMatcher, DependsOrder, FileLocation, ProblemMatcher, ProblemMatcherReference,
+    ProblemPattern, ProblemSeverity, TaskInput, TaskInputKind, TaskTemplate, TaskTemplates,
+    VariableName,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -41,6 +45,211 @@ enum Command {
     },
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeInput {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    description: Option<String>,
+    default: Option<String>,
+    #[serde(default)]
+    options: Vec<VsCodeInputOption>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeInputOption {
+    Value(String),
+    Labeled { value: String },
+}
+
+impl VsCodeInput {
+    /// Converts the input, unless it is of a type not supported by Zed, such as `command`.
+    fn to_zed_format(self) -> Option<TaskInput> {
+        let kind = match self.kind.as_str() {
+            "promptString" => TaskInputKind::PromptString,
+            "pickString" => TaskInputKind::PickString,
+            _ => return None,
+        };
+        Some(TaskInput {
+            id: self.id,
+            kind,
+            description: self.description,
+            default: self.default,
+            options: self
+                .options
+                .into_iter()
+                .map(|option| match option {
+                    VsCodeInputOption::Value(value) | VsCodeInputOption::Labeled { value } => value,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Whether any of the fields of the task reference the input with the given id.
+fn references_input(template: &TaskTemplate, id: &str) -> bool {
+    let reference = format!("${{input:{id}}}");
+    std::iter::once(&template.label)
+        .chain(Some(&template.command))
+        .chain(&template.args)
+        .chain(&template.cwd)
+        .chain(template.env.values())
+        .any(|field| field.contains(&reference))
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeDependsOn {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemMatchers {
+    Single(VsCodeProblemMatcher),
+    Multiple(Vec<VsCodeProblemMatcher>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemMatcher {
+    Named(String),
+    Inline(Box<VsCodeProblemMatcherDefinition>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemMatcherDefinition {
+    base: Option<String>,
+    owner: Option<String>,
+    source: Option<String>,
+    severity: Option<String>,
+    file_location: Option<VsCodeFileLocation>,
+    pattern: Option<VsCodeProblemPatterns>,
+    background: Option<VsCodeBackgroundMatcher>,
+}
+
+/// Either the kind of the file location, or the kind followed by the directory paths are relative to.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeFileLocation {
+    Kind(String),
+    WithDirectory(Vec<String>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemPatterns {
+    Single(VsCodeProblemPattern),
+    Multiple(Vec<VsCodeProblemPattern>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemPattern {
+    regexp: String,
+    file: Option<usize>,
+    location: Option<usize>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    severity: Option<usize>,
+    code: Option<usize>,
+    message: Option<usize>,
+    #[serde(default, rename = "loop")]
+    loop_: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeBackgroundMatcher {
+    #[serde(default)]
+    active_on_start: bool,
+    begins_pattern: VsCodeBackgroundPattern,
+    ends_pattern: VsCodeBackgroundPattern,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeBackgroundPattern {
+    Regexp(String),
+    Object { regexp: String },
+}
+
+impl VsCodeBackgroundPattern {
+    fn into_regexp(self) -> String {
+        match self {
+            Self::Regexp(regexp) | Self::Object { regexp } => regexp,
+        }
+    }
+}
+
+impl VsCodeProblemMatcher {
+    fn to_zed_format(self, replacer: &EnvVariableReplacer) -> ProblemMatcherReference {
+        let definition = match self {
+            Self::Named(name) => return ProblemMatcherReference::Named(name),
+            Self::Inline(definition) => *definition,
+        };
+        let (file_location, base_directory) = match definition.file_location {
+            Some(VsCodeFileLocation::Kind(kind)) => (Some(kind), None),
+            Some(VsCodeFileLocation::WithDirectory(kind_and_directory)) => {
+                let mut kind_and_directory = kind_and_directory.into_iter();
+                (kind_and_directory.next(), kind_and_directory.next())
+            }
+            None => (None, None),
+        };
+        let patterns = match definition.pattern {
+            Some(VsCodeProblemPatterns::Single(pattern)) => vec![pattern],
+            Some(VsCodeProblemPatterns::Multiple(patterns)) => patterns,
+            None => Vec::new(),
+        };
+        ProblemMatcherReference::Inline(ProblemMatcher {
+            base: definition.base,
+            source: definition.source.or(definition.owner),
+            severity: definition
+                .severity
+                .and_then(|severity| match severity.as_str() {
+                    "error" => Some(ProblemSeverity::Error),
+                    "warning" => Some(ProblemSeverity::Warning),
+                    "info" => Some(ProblemSeverity::Info),
+                    _ => None,
+                }),
+            file_location: file_location.and_then(|kind| match kind.as_str() {
+                "absolute" => Some(FileLocation::Absolute),
+                "relative" => Some(FileLocation::Relative),
+                "autoDetect" | "search" => Some(FileLocation::AutoDetect),
+                _ => None,
+            }),
+            base_directory: base_directory.map(|directory| replacer.replace(&directory)),
+            pattern: patterns
+                .into_iter()
+                .map(|pattern| ProblemPattern {
+                    regexp: pattern.regexp,
+                    file: pattern.file,
+                    location: pattern.location,
+                    line: pattern.line,
+                    column: pattern.column,
+                    end_line: pattern.end_line,
+                    end_column: pattern.end_column,
+                    severity: pattern.severity,
+                    code: pattern.code,
+                    message: pattern.message,
+                    loop_: pattern.loop_,
+                })
+                .collect(),
+            background: definition.background.map(|background| BackgroundMatcher {
+                active_on_start: background.active_on_start,
+                begins_pattern: background.begins_pattern.into_regexp(),
+                ends_pattern: background.ends_pattern.into_regexp(),
+            }),
+        })
+    }
+}
+
 type VsCodeEnvVariable = String;
 type ZedEnvVariable = String;
 
@@ -85,29 +294,61 @@ impl EnvVariableReplacer {
 
 impl VsCodeTaskDefinition {
     fn to_zed_format(self, replacer: &EnvVariableReplacer) -> anyhow::Result<TaskTemplate> {
-        if self.other_attributes.contains_key("dependsOn") {
-            bail!("Encountered unsupported `dependsOn` key during deserialization");
-        }
-        // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
-        // as that way we can provide more specific description of why deserialization failed.
-        // E.g. if the command is missing due to `dependsOn` presence, we can check other_attributes first before doing this (and provide nice error message)
-        // catch-all if on value.command presence.
-        let Some(command) = self.command else {
-            bail!("Missing `type` field in task");
+        let depends_on = self
+            .other_attributes
+            .get("dependsOn")
+            .and_then(|depends_on| {
+                serde_json_lenient::from_value::<VsCodeDependsOn>(depends_on.clone())
+                    .context("parsing `dependsOn`")
+                    .log_err()
+            })
+            .map_or_else(Vec::new, |depends_on| match depends_on {
+                VsCodeDependsOn::Single(label) => vec![label],
+                VsCodeDependsOn::Multiple(labels) => labels,
+            });
+        let depends_order = match self
+            .other_attributes
+            .get("dependsOrder")
+            .and_then(|depends_order| depends_order.as_str())
+        {
+            Some("sequence") => DependsOrder::Sequence,
+            _ => DependsOrder::Parallel,
         };
-
-        let (command, args) = match command {
-            Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
-            Command::Shell { command, args } => (command, args),
-            Command::Gulp { task } => ("gulp".to_owned(), vec![task]),
+        // `type` might not be set in tasks that use `dependsOn`, which are compound tasks then, running only the tasks they depend on.
+        // We still want to deserialize the whole object though (hence command is an Option), to tell those tasks apart from the invalid ones.
+        let (command, args) = match self.command {
+            Some(Command::Npm { script }) => ("npm".to_owned(), vec!["run".to_string(), script]),
+            Some(Command::Shell { command, args }) => (command, args),
+            Some(Command::Gulp { task }) => ("gulp".to_owned(), vec![task]),
+            None if !depends_on.is_empty() => (String::new(), Vec::new()),
+            None => bail!("Missing `type` field in task"),
         };
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
         let args = args.into_iter().map(|arg| replacer.replace(&arg)).collect();
+        // Problem matchers are parsed separately, so that a matcher in an unsupported format does not discard the whole task.
+        let problem_matchers = self
+            .other_attributes
+            .get("problemMatcher")
+            .and_then(|problem_matchers| {
+                serde_json_lenient::from_value::<VsCodeProblemMatchers>(problem_matchers.clone())
+                    .context("parsing `problemMatcher`")
+                    .log_err()
+            })
+            .map_or_else(Vec::new, |problem_matchers| match problem_matchers {
+                VsCodeProblemMatchers::Single(problem_matcher) => vec![problem_matcher],
+                VsCodeProblemMatchers::Multiple(problem_matchers) => problem_matchers,
+            })
+            .into_iter()
+            .map(|problem_matcher| problem_matcher.to_zed_format(replacer))
+            .collect();
         let mut ret = TaskTemplate {
             label: self.label,
             command,
             args,
+            problem_matchers,
+            depends_on,
+            depends_order,
             ..Default::default()
         };
         if let Some(options) = self.options {
@@ -122,6 +363,8 @@ impl VsCodeTaskDefinition {
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct VsCodeTaskFile {
     tasks: Vec<VsCodeTaskDefinition>,
+    #[serde(default)]
+    inputs: Vec<serde_json_lenient::Value>,
 }
 
 impl TryFrom<VsCodeTaskFile> for TaskTemplates {
@@ -140,10 +383,30 @@ impl TryFrom<VsCodeTaskFile> for TaskTemplates {
                 VariableName::SelectedText.to_string(),
             ),
         ]));
+        // Inputs are parsed separately, so that an input in an unsupported format does not discard all tasks.
+        let inputs = value
+            .inputs
+            .into_iter()
+            .filter_map(|input| {
+                serde_json_lenient::from_value::<VsCodeInput>(input)
+                    .context("parsing input")
+                    .log_err()?
+                    .to_zed_format()
+            })
+            .collect::<Vec<_>>();
         let templates = value
             .tasks
             .into_iter()
             .filter_map(|vscode_definition| vscode_definition.to_zed_format(&replacer).log_err())
+            .map(|mut template| {
+                // Inputs are declared for all tasks in VS Code, but only the ones referenced are asked for.
+                template.inputs = inputs
+                    .iter()
+                    .filter(|input| references_input(&template, &input.id))
+                    .cloned()
+                    .collect();
+                template
+            })
             .collect();
         Ok(Self(templates))
     }
@@ -155,7 +418,8 @@ mod tests {
 
     use crate::{
         vscode_format::{Command, VsCodeTaskDefinition},
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        DependsOrder, FileLocation, ProblemMatcher, ProblemMatcherReference, TaskInput,
+        TaskInputKind, TaskTemplate, TaskTemplates, VsCodeTaskFile,
     };
 
     use super::EnvVariableReplacer;
@@ -257,6 +521,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcherReference::Named("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
@@ -268,18 +533,21 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matchers: vec![ProblemMatcherReference::Named("$tsc-watch".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matchers: vec![ProblemMatcherReference::Named("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcherReference::Named("$tsc".to_string())],
                 ..Default::default()
             },
         ];
@@ -355,33 +623,152 @@ mod tests {
             .iter()
             .zip(expected)
             .for_each(|(lhs, rhs)| compare_without_other_attributes(lhs.clone(), rhs));
+        let tsc_problem_matcher = ProblemMatcherReference::Inline(ProblemMatcher {
+            base: Some("$tsc".to_string()),
+            file_location: Some(FileLocation::Relative),
+            base_directory: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+            ..Default::default()
+        });
         let expected = vec![
             TaskTemplate {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                problem_matchers: vec![ProblemMatcherReference::Inline(ProblemMatcher {
+                    base: Some("$tsc-watch".to_string()),
+                    file_location: Some(FileLocation::Relative),
+                    base_directory: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+                    ..Default::default()
+                })],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matchers: vec![tsc_problem_matcher.clone()],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcherReference::Named("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcherReference::Named("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matchers: vec![tsc_problem_matcher],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server and Extension".to_string(),
+                problem_matchers: vec![ProblemMatcherReference::Named("$rustc".to_string())],
+                depends_on: vec!["Build Server".to_string(), "Build Extension".to_string()],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server (Release) and Extension".to_string(),
+                problem_matchers: vec![ProblemMatcherReference::Named("$rustc".to_string())],
+                depends_on: vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ],
+                ..Default::default()
+            },
+        ];
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(tasks.0, expected);
+    }
+
+    #[test]
+    fn can_deserialize_compound_tasks() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    { "label": "Test", "type": "shell", "command": "cargo test", "dependsOn": "Build" },
+                    { "label": "CI", "dependsOn": ["Lint", "Test"], "dependsOrder": "sequence" },
+                    { "label": "Broken" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let expected = vec![
+            TaskTemplate {
+                label: "Test".to_string(),
+                command: "cargo test".to_string(),
+                depends_on: vec!["Build".to_string()],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "CI".to_string(),
+                depends_on: vec!["Lint".to_string(), "Test".to_string()],
+                depends_order: DependsOrder::Sequence,
+                ..Default::default()
+            },
+        ];
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(tasks.0, expected);
+    }
+
+    #[test]
+    fn can_deserialize_inputs() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    { "label": "Test", "type": "shell", "command": "cargo test ${input:filter}" },
+                    { "label": "Deploy", "type": "shell", "command": "deploy", "args": ["--env", "${input:env}"] },
+                    { "label": "Build", "type": "shell", "command": "cargo build" }
+                ],
+                "inputs": [
+                    { "id": "filter", "type": "promptString", "description": "Test filter" },
+                    {
+                        "id": "env",
+                        "type": "pickString",
+                        "options": ["staging", { "label": "Production", "value": "prod" }],
+                        "default": "staging"
+                    },
+                    { "id": "branch", "type": "command", "command": "git.branch" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let expected = vec![
+            TaskTemplate {
+                label: "Test".to_string(),
+                command: "cargo test ${input:filter}".to_string(),
+                inputs: vec![TaskInput {
+                    id: "filter".to_string(),
+                    kind: TaskInputKind::PromptString,
+                    description: Some("Test filter".to_string()),
+                    default: None,
+                    options: Vec::new(),
+                }],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Deploy".to_string(),
+                command: "deploy".to_string(),
+                args: vec!["--env".to_string(), "${input:env}".to_string()],
+                inputs: vec![TaskInput {
+                    id: "env".to_string(),
+                    kind: TaskInputKind::PickString,
+                    description: None,
+                    default: Some("staging".to_string()),
+                    options: vec!["staging".to_string(), "prod".to_string()],
+                }],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build".to_string(),
+                command: "cargo build".to_string(),
                 ..Default::default()
             },
         ];
@@ -0,0 +1,224 @@
+use std::sync::Arc;
+
+use futures::channel::oneshot;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{rems, DismissEvent, Task};
+use picker::{highlighted_match_with_paths::HighlightedText, Picker, PickerDelegate};
+use task::{TaskInput, TaskInputKind};
+use ui::{prelude::*, ListItem, ListItemSpacing, ViewContext, WindowContext};
+use util::ResultExt;
+use workspace::Workspace;
+
+/// Asks for the values of the inputs of a task, one picker at a time, before the task is spawned.
+pub(crate) struct TaskInputsPrompt;
+
+impl TaskInputsPrompt {
+    pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.set_prompt_for_task_inputs(Box::new(|_, inputs, cx| {
+            let (tx, rx) = oneshot::channel();
+            cx.spawn(|workspace, mut cx| async move {
+                let mut values = Vec::with_capacity(inputs.len());
+                for input in inputs {
+                    let Ok(value_rx) = workspace.update(&mut cx, |workspace, cx| {
+                        Self::prompt_for_input(workspace, input.clone(), cx)
+                    }) else {
+                        break;
+                    };
+                    match value_rx.await {
+                        Ok(Some(value)) => values.push((input.id, value)),
+                        _ => {
+                            tx.send(None).ok();
+                            return;
+                        }
+                    }
+                }
+                tx.send(Some(values)).ok();
+            })
+            .detach();
+            rx
+        }));
+    }
+
+    fn prompt_for_input(
+        workspace: &mut Workspace,
+        input: TaskInput,
+        cx: &mut ViewContext<Workspace>,
+    ) -> oneshot::Receiver<Option<String>> {
+        let (tx, rx) = oneshot::channel();
+        workspace.toggle_modal(cx, |cx| {
+            let delegate = TaskInputDelegate::new(input, tx);
+            Picker::uniform_list(delegate, cx).width(rems(34.))
+        });
+        rx
+    }
+}
+
+struct TaskInputDelegate {
+    input: TaskInput,
+    tx: Option<oneshot::Sender<Option<String>>>,
+    placeholder_text: Arc<str>,
+    query: String,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl TaskInputDelegate {
+    fn new(input: TaskInput, tx: oneshot::Sender<Option<String>>) -> Self {
+        let mut placeholder_text = input
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("Value of `{}`", input.id));
+        if let Some(default) = input.default.as_ref().filter(|default| !default.is_empty()) {
+            placeholder_text.push_str(&format!(" (default: {default})"));
+        }
+        Self {
+            input,
+            tx: Some(tx),
+            placeholder_text: Arc::from(placeholder_text),
+            query: String::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+
+    fn send(&mut self, value: Option<String>) {
+        if let Some(tx) = self.tx.take() {
+            tx.send(value).ok();
+        }
+    }
+}
+
+impl PickerDelegate for TaskInputDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        self.placeholder_text.clone()
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        match self.input.kind {
+            TaskInputKind::PickString => "No matching options".into(),
+            TaskInputKind::PromptString => match self.input.default.as_deref() {
+                Some(default) if self.query.is_empty() => {
+                    format!("Press enter to use `{default}`").into()
+                }
+                _ => "Press enter to use the typed value".into(),
+            },
+        }
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        self.query = query.clone();
+        if self.input.kind == TaskInputKind::PromptString {
+            return Task::ready(());
+        }
+
+        let candidates = self
+            .input
+            .options
+            .iter()
+            .enumerate()
+            .map(|(id, option)| StringMatchCandidate::new(id, option.clone()))
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        score: 0.0,
+                        positions: Vec::new(),
+                        string: candidate.string,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    1000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.selected_index = if query.is_empty() {
+                        // Preselect the default option, as confirming without typing anything
+                        // is expected to pick it.
+                        delegate
+                            .input
+                            .default
+                            .as_ref()
+                            .and_then(|default| matches.iter().position(|m| &m.string == default))
+                            .unwrap_or(0)
+                    } else {
+                        0
+                    };
+                    delegate.matches = matches;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let value = match self.input.kind {
+            TaskInputKind::PickString => {
+                let Some(selected) = self.matches.get(self.selected_index) else {
+                    return;
+                };
+                selected.string.clone()
+            }
+            TaskInputKind::PromptString => {
+                if self.query.is_empty() {
+                    self.input.default.clone().unwrap_or_default()
+                } else {
+                    self.query.clone()
+                }
+            }
+        };
+        self.send(Some(value));
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.send(None);
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        let highlighted_option = HighlightedText {
+            text: hit.string.clone(),
+            highlight_positions: hit.positions.clone(),
+            char_count: hit.string.chars().count(),
+            color: Color::Default,
+        };
+        Some(
+            ListItem::new(SharedString::from(format!("task-input-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(highlighted_option.render(cx)),
+        )
+    }
+}
@@ -1,20 +1,23 @@
 use ::settings::Settings;
 use editor::{tasks::task_context, Editor};
 use gpui::{AppContext, Task as AsyncTask, ViewContext, WindowContext};
+use inputs_prompt::TaskInputsPrompt;
 use modal::TasksModal;
 use project::{Location, WorktreeId};
 use workspace::tasks::schedule_task;
 use workspace::{tasks::schedule_resolved_task, Workspace};
 
+mod inputs_prompt;
 mod modal;
 mod settings;
 
-pub use modal::{Rerun, Spawn};
+pub use modal::{Rerun, Spawn, ToggleWatch};
 
 pub fn init(cx: &mut AppContext) {
     settings::TaskSettings::register(cx);
     cx.observe_new_views(
-        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            TaskInputsPrompt::register(workspace, cx);
             workspace
                 .register_action(spawn_task_or_modal)
                 .register_action(move |workspace, action: &modal::Rerun, cx| {
@@ -34,9 +37,12 @@ pub fn init(cx: &mut AppContext) {
                             if let Some(use_new_terminal) = action.use_new_terminal {
                                 original_task.use_new_terminal = use_new_terminal;
                             }
+                            // Reuse the answers given to the inputs of the task on its last run.
+                            let input_variables = last_scheduled_task.input_variables();
                             let context_task = task_context(workspace, cx);
                             cx.spawn(|workspace, mut cx| async move {
-                                let task_context = context_task.await;
+                                let mut task_context = context_task.await;
+                                task_context.task_variables.extend(input_variables);
                                 workspace
                                     .update(&mut cx, |workspace, cx| {
                                         schedule_task(
@@ -3,9 +3,9 @@ use std::sync::Arc;
 use crate::active_item_selection_properties;
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
-    impl_actions, rems, Action, AnyElement, AppContext, DismissEvent, EventEmitter, FocusableView,
-    InteractiveElement, Model, ParentElement, Render, SharedString, Styled, Subscription, Task,
-    View, ViewContext, VisualContext, WeakView,
+    actions, impl_actions, rems, Action, AnyElement, AppContext, DismissEvent, EventEmitter,
+    FocusableView, InteractiveElement, Model, ParentElement, Render, SharedString, Styled,
+    Subscription, Task, View, ViewContext, VisualContext, WeakView,
 };
 use picker::{highlighted_match_with_paths::HighlightedText, Picker, PickerDelegate};
 use project::{Project, TaskSourceKind};
@@ -61,6 +61,8 @@ pub struct Rerun {
 
 impl_actions!(task, [Rerun, Spawn]);
 
+actions!(task, [ToggleWatch]);
+
 /// A modal used to spawn new tasks.
 pub(crate) struct TasksModalDelegate {
     project: Model<Project>,
@@ -113,6 +115,44 @@ impl TasksModalDelegate {
         ))
     }
 
+    fn selected_candidate(&self) -> Option<&(TaskSourceKind, ResolvedTask)> {
+        let candidate_index = self.matches.get(self.selected_index)?.candidate_id;
+        self.candidates.as_ref()?.get(candidate_index)
+    }
+
+    fn is_task_watched(&self, task: &ResolvedTask, cx: &AppContext) -> bool {
+        self.project
+            .read(cx)
+            .task_inventory()
+            .read(cx)
+            .is_task_watched(&task.id)
+    }
+
+    /// Stops watching the selected task, or spawns it to watch it, if it has any `watch` globs.
+    fn toggle_watch(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        let Some((task_source_kind, task)) = self.selected_candidate().cloned() else {
+            return;
+        };
+        if task.original_task().watch.is_empty() {
+            return;
+        }
+        let unwatched = self.project.update(cx, |project, cx| {
+            project
+                .task_inventory()
+                .update(cx, |inventory, cx| inventory.unwatch_task(&task.id, cx))
+        });
+        if unwatched {
+            cx.notify();
+        } else {
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    schedule_resolved_task(workspace, task_source_kind, task, false, cx);
+                })
+                .ok();
+            cx.emit(DismissEvent);
+        }
+    }
+
     fn delete_previously_used(&mut self, ix: usize, cx: &mut AppContext) {
         let Some(candidates) = self.candidates.as_mut() else {
             return;
@@ -158,12 +198,18 @@ impl TasksModal {
             _subscription,
         }
     }
+
+    fn toggle_watch(&mut self, _: &ToggleWatch, cx: &mut ViewContext<Self>) {
+        self.picker
+            .update(cx, |picker, cx| picker.delegate.toggle_watch(cx));
+    }
 }
 
 impl Render for TasksModal {
-    fn render(&mut self, _: &mut ViewContext<Self>) -> impl gpui::prelude::IntoElement {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl gpui::prelude::IntoElement {
         v_flex()
             .key_context("TasksModal")
+            .on_action(cx.listener(Self::toggle_watch))
             .w(rems(34.))
             .child(self.picker.clone())
     }
@@ -387,12 +433,20 @@ impl PickerDelegate for TasksModalDelegate {
             TaskSourceKind::UserInput => Some(Icon::new(IconName::Terminal)),
             TaskSourceKind::AbsPath { .. } => Some(Icon::new(IconName::Settings)),
             TaskSourceKind::Worktree { .. } => Some(Icon::new(IconName::FileTree)),
+            TaskSourceKind::Detected { .. } => Some(Icon::new(IconName::MagicWand)),
             TaskSourceKind::Language { name } => file_icons::FileIcons::get(cx)
                 .get_type_icon(&name.to_lowercase())
                 .map(|icon_path| Icon::from_path(icon_path)),
         }
         .map(|icon| icon.color(Color::Muted).size(IconSize::Small));
-        let history_run_icon = if Some(ix) <= self.divider_index {
+        let history_run_icon = if self.is_task_watched(resolved_task, cx) {
+            Some(
+                Icon::new(IconName::RotateCw)
+                    .color(Color::Accent)
+                    .size(IconSize::Small)
+                    .into_any_element(),
+            )
+        } else if Some(ix) <= self.divider_index {
             Some(
                 Icon::new(IconName::HistoryRerun)
                     .color(Color::Muted)
@@ -507,6 +561,16 @@ impl PickerDelegate for TasksModalDelegate {
         } else {
             None
         };
+        let watch_button_label = self
+            .selected_candidate()
+            .filter(|(_, task)| !task.original_task().watch.is_empty())
+            .map(|(_, task)| {
+                if self.is_task_watched(task, cx) {
+                    "Stop watching"
+                } else {
+                    "Watch"
+                }
+            });
         Some(
             h_flex()
                 .w_full()
@@ -516,8 +580,9 @@ impl PickerDelegate for TasksModalDelegate {
                 .rounded_b_md()
                 .bg(cx.theme().colors().ghost_element_selected)
                 .child(
-                    left_button
-                        .map(|(label, action)| {
+                    h_flex()
+                        .gap_1()
+                        .children(left_button.map(|(label, action)| {
                             let keybind = KeyBinding::for_action(&*action, cx);
 
                             Button::new("edit-current-task", label)
@@ -526,9 +591,15 @@ impl PickerDelegate for TasksModalDelegate {
                                 .on_click(move |_, cx| {
                                     cx.dispatch_action(action.boxed_clone());
                                 })
-                                .into_any_element()
-                        })
-                        .unwrap_or_else(|| h_flex().into_any_element()),
+                        }))
+                        .children(watch_button_label.map(|label| {
+                            let keybind = KeyBinding::for_action(&ToggleWatch, cx);
+
+                            Button::new("toggle-task-watch", label)
+                                .label_size(LabelSize::Small)
+                                .when_some(keybind, |this, keybind| this.key_binding(keybind))
+                                .on_click(|_, cx| cx.dispatch_action(ToggleWatch.boxed_clone()))
+                        })),
                 )
                 .map(|this| {
                     if (current_modifiers.alt || self.matches.is_empty()) && !self.prompt.is_empty()
@@ -92,6 +92,8 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    /// The task of the terminal finished running, and its summary was appended to the output.
+    TaskFinished,
 }
 
 #[derive(Clone, Debug)]
@@ -969,6 +971,14 @@ impl Terminal {
         self.set_selection(Some((make_selection(&(start..=end)), end)));
     }
 
+    /// The text of the terminal, including its scrollback history.
+    pub fn text(&self) -> String {
+        let term = self.term.lock();
+        let start = AlacPoint::new(term.topmost_line(), Column(0));
+        let end = AlacPoint::new(term.bottommost_line(), term.last_column());
+        term.bounds_to_string(start, end)
+    }
+
     fn set_selection(&mut self, selection: Option<(Selection, AlacPoint)>) {
         self.events
             .push_back(InternalEvent::SetSelection(selection));
@@ -1467,6 +1477,7 @@ impl Terminal {
         // when Zed task finishes and no more output is made.
         // After the task summary is output once, no more text is appended to the terminal.
         unsafe { append_text_to_term(&mut self.term.lock(), &[&task_line, &command_line]) };
+        cx.emit(Event::TaskFinished);
     }
 }
 
@@ -3,21 +3,21 @@ use std::{ops::ControlFlow, path::PathBuf, sync::Arc};
 use crate::TerminalView;
 use collections::{HashMap, HashSet};
 use db::kvp::KEY_VALUE_STORE;
-use futures::future::join_all;
+use futures::{channel::oneshot, future::join_all};
 use gpui::{
     actions, Action, AppContext, AsyncWindowContext, DismissEvent, Entity, EventEmitter,
     ExternalPaths, FocusHandle, FocusableView, IntoElement, Model, ParentElement, Pixels, Render,
     Styled, Subscription, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
 };
 use itertools::Itertools;
-use project::{Fs, ProjectEntryId};
+use project::{Fs, Inventory, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
-use task::{RevealStrategy, SpawnInTerminal, TaskId, TerminalWorkDir};
+use task::{DependsOrder, RevealStrategy, SpawnInTerminal, TaskId, TerminalWorkDir};
 use terminal::{
     terminal_settings::{Shell, TerminalDockPosition, TerminalSettings},
-    Terminal,
+    TaskStatus, Terminal,
 };
 use ui::{
     h_flex, ButtonCommon, Clickable, ContextMenu, FluentBuilder, IconButton, IconSize, Selectable,
@@ -32,7 +32,7 @@ use workspace::{
     DraggedTab, NewTerminal, Pane, ToggleZoom, Workspace,
 };
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context as _, Result};
 
 const TERMINAL_PANEL_KEY: &str = "TerminalPanel";
 
@@ -309,7 +309,14 @@ impl TerminalPanel {
     ) {
         match event {
             pane::Event::ActivateItem { .. } => self.serialize(cx),
-            pane::Event::RemoveItem { .. } => self.serialize(cx),
+            pane::Event::RemoveItem { item_id } => {
+                if let Some(inventory) = self.task_inventory(cx) {
+                    inventory.update(cx, |inventory, cx| {
+                        inventory.task_terminal_closed(*item_id, cx);
+                    });
+                }
+                self.serialize(cx)
+            }
             pane::Event::Remove => cx.emit(PanelEvent::Close),
             pane::Event::ZoomIn => cx.emit(PanelEvent::ZoomIn),
             pane::Event::ZoomOut => cx.emit(PanelEvent::ZoomOut),
@@ -319,6 +326,16 @@ impl TerminalPanel {
                     let pane = self.pane.clone();
                     workspace.update(cx, |workspace, cx| item.added_to_pane(workspace, pane, cx))
                 }
+                let terminal = item
+                    .act_as::<TerminalView>(cx)
+                    .map(|terminal_view| terminal_view.read(cx).terminal().clone());
+                let task_id =
+                    terminal.and_then(|terminal| Some(terminal.read(cx).task()?.id.clone()));
+                if let Some((task_id, inventory)) = task_id.zip(self.task_inventory(cx)) {
+                    inventory.update(cx, |inventory, _| {
+                        inventory.task_terminal_opened(item.item_id(), task_id)
+                    });
+                }
             }
 
             _ => {}
@@ -347,6 +364,86 @@ impl TerminalPanel {
     }
 
     fn spawn_task(&mut self, spawn_in_terminal: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
+        if spawn_in_terminal.dependencies.is_empty() {
+            self.start_task(spawn_in_terminal.clone(), cx)
+                .detach_and_log_err(cx);
+        } else {
+            self.run_task(spawn_in_terminal.clone(), false, cx)
+                .detach_and_log_err(cx);
+        }
+    }
+
+    /// Runs the tasks the given task depends on and then the task itself, unless any of them fails.
+    /// With `wait_for_completion`, also waits for the task to finish, failing unless it succeeds.
+    fn run_task(
+        &mut self,
+        mut spawn_task: SpawnInTerminal,
+        wait_for_completion: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let dependencies = std::mem::take(&mut spawn_task.dependencies);
+        let run_dependencies: Task<Result<()>> = match spawn_task.depends_order {
+            DependsOrder::Parallel => {
+                let runs = dependencies
+                    .into_iter()
+                    .map(|dependency| self.run_task(dependency, true, cx))
+                    .collect::<Vec<_>>();
+                cx.background_executor().spawn(async move {
+                    join_all(runs)
+                        .await
+                        .into_iter()
+                        .collect::<Result<Vec<_>>>()?;
+                    Ok(())
+                })
+            }
+            DependsOrder::Sequence => cx.spawn(|terminal_panel, mut cx| async move {
+                for dependency in dependencies {
+                    terminal_panel
+                        .update(&mut cx, |terminal_panel, cx| {
+                            terminal_panel.run_task(dependency, true, cx)
+                        })?
+                        .await?;
+                }
+                Ok(())
+            }),
+        };
+
+        cx.spawn(|terminal_panel, mut cx| async move {
+            let label = spawn_task.full_label.clone();
+            run_dependencies
+                .await
+                .with_context(|| format!("Not running task `{label}`"))?;
+            // Compound tasks only run the tasks they depend on.
+            if spawn_task.command.trim().is_empty() {
+                return Ok(());
+            }
+            let terminal = terminal_panel
+                .update(&mut cx, |terminal_panel, cx| {
+                    terminal_panel.start_task(spawn_task, cx)
+                })?
+                .await?;
+            if wait_for_completion {
+                terminal
+                    .update(&mut cx, |terminal, cx| terminal.wait_for_completed_task(cx))?
+                    .await;
+                let status = terminal.update(&mut cx, |terminal, _| {
+                    terminal.task().map(|task| task.status)
+                })?;
+                if status != Some(TaskStatus::Completed { success: true }) {
+                    bail!("Task `{label}` did not succeed");
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Spawns the task in a terminal, reusing the terminal of its previous run if allowed,
+    /// after that run finishes, unless concurrent runs are allowed.
+    fn start_task(
+        &mut self,
+        spawn_in_terminal: SpawnInTerminal,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<Model<Terminal>>> {
         let mut spawn_task = spawn_in_terminal.clone();
         // Set up shell args unconditionally, as tasks are always spawned inside of a shell.
         let Some((shell, mut user_args)) = (match TerminalSettings::get_global(cx).shell.clone() {
@@ -354,7 +451,7 @@ impl TerminalPanel {
             Shell::Program(shell) => Some((shell, Vec::new())),
             Shell::WithArguments { program, args } => Some((program, args)),
         }) else {
-            return;
+            return Task::ready(Err(anyhow!("no shell to spawn the task in")));
         };
 
         spawn_task.command_label = format!("{shell} -i -c `{}`", spawn_task.command_label);
@@ -376,16 +473,12 @@ impl TerminalPanel {
         let use_new_terminal = spawn_in_terminal.use_new_terminal;
 
         if allow_concurrent_runs && use_new_terminal {
-            self.spawn_in_new_terminal(spawn_task, cx)
-                .detach_and_log_err(cx);
-            return;
+            return self.spawn_in_new_terminal(spawn_task, cx);
         }
 
         let terminals_for_task = self.terminals_for_task(&spawn_in_terminal.full_label, cx);
         if terminals_for_task.is_empty() {
-            self.spawn_in_new_terminal(spawn_task, cx)
-                .detach_and_log_err(cx);
-            return;
+            return self.spawn_in_new_terminal(spawn_task, cx);
         }
         let (existing_item_index, existing_terminal) = terminals_for_task
             .last()
@@ -396,28 +489,37 @@ impl TerminalPanel {
                 !use_new_terminal,
                 "Should have handled 'allow_concurrent_runs && use_new_terminal' case above"
             );
-            self.replace_terminal(spawn_task, existing_item_index, existing_terminal, cx);
+            Task::ready(
+                self.replace_terminal(spawn_task, existing_item_index, existing_terminal, cx)
+                    .context("replacing the task terminal"),
+            )
         } else {
+            let (terminal_tx, terminal_rx) = oneshot::channel();
             self.deferred_tasks.insert(
                 spawn_in_terminal.id.clone(),
                 cx.spawn(|terminal_panel, mut cx| async move {
                     wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
-                    terminal_panel
-                        .update(&mut cx, |terminal_panel, cx| {
-                            if use_new_terminal {
+                    let terminal = terminal_panel.update(&mut cx, |terminal_panel, cx| {
+                        if use_new_terminal {
+                            terminal_panel.spawn_in_new_terminal(spawn_task, cx)
+                        } else {
+                            Task::ready(
                                 terminal_panel
-                                    .spawn_in_new_terminal(spawn_task, cx)
-                                    .detach_and_log_err(cx);
-                            } else {
-                                terminal_panel.replace_terminal(
-                                    spawn_task,
-                                    existing_item_index,
-                                    existing_terminal,
-                                    cx,
-                                );
-                            }
-                        })
-                        .ok();
+                                    .replace_terminal(
+                                        spawn_task,
+                                        existing_item_index,
+                                        existing_terminal,
+                                        cx,
+                                    )
+                                    .context("replacing the task terminal"),
+                            )
+                        }
+                    });
+                    let terminal = match terminal {
+                        Ok(terminal) => terminal.await,
+                        Err(e) => Err(e),
+                    };
+                    terminal_tx.send(terminal).ok();
                 }),
             );
 
@@ -434,6 +536,12 @@ impl TerminalPanel {
                 }
                 RevealStrategy::Never => {}
             }
+
+            cx.background_executor().spawn(async move {
+                terminal_rx
+                    .await
+                    .context("the task got rescheduled before it started")?
+            })
         }
     }
 
@@ -604,13 +712,14 @@ impl TerminalPanel {
         terminal_item_index: usize,
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<'_, Self>,
-    ) -> Option<()> {
+    ) -> Option<Model<Terminal>> {
         let project = self
             .workspace
             .update(cx, |workspace, _| workspace.project().clone())
             .ok()?;
 
         let reveal = spawn_task.reveal;
+        let task_id = spawn_task.id.clone();
         let window = cx.window_handle();
         let new_terminal = project.update(cx, |project, cx| {
             project
@@ -618,8 +727,13 @@ impl TerminalPanel {
                 .log_err()
         })?;
         terminal_to_replace.update(cx, |terminal_to_replace, cx| {
-            terminal_to_replace.set_terminal(new_terminal, cx);
+            terminal_to_replace.set_terminal(new_terminal.clone(), cx);
         });
+        if let Some(inventory) = self.task_inventory(cx) {
+            inventory.update(cx, |inventory, _| {
+                inventory.task_terminal_opened(terminal_to_replace.entity_id(), task_id)
+            });
+        }
 
         match reveal {
             RevealStrategy::Always => {
@@ -635,13 +749,19 @@ impl TerminalPanel {
             RevealStrategy::Never => {}
         }
 
-        Some(())
+        Some(new_terminal)
     }
 
     pub fn pane(&self) -> &View<Pane> {
         &self.pane
     }
 
+    fn task_inventory(&self, cx: &AppContext) -> Option<Model<Inventory>> {
+        let workspace = self.workspace.upgrade()?;
+        let project = workspace.read(cx).project();
+        Some(project.read(cx).task_inventory().clone())
+    }
+
     fn has_no_terminals(&self, cx: &WindowContext) -> bool {
         self.pane.read(cx).items_len() == 0 && self.pending_terminals_to_add == 0
     }
@@ -591,6 +591,7 @@ fn subscribe_for_terminal_events(
             Event::BreadcrumbsChanged => cx.emit(ItemEvent::UpdateBreadcrumbs),
             Event::CloseTerminal => cx.emit(ItemEvent::CloseItem),
             Event::SelectionsChanged => cx.emit(SearchEvent::ActiveMatchChanged),
+            Event::TaskFinished => cx.emit(ItemEvent::UpdateTab),
         });
     vec![terminal_subscription, terminal_events_subscription]
 }
@@ -817,6 +818,17 @@ impl Item for TerminalView {
             },
             None => (IconName::Terminal, Color::Muted, None),
         };
+        let watched = terminal.task().map_or(false, |terminal_task| {
+            self.workspace.upgrade().map_or(false, |workspace| {
+                workspace
+                    .read(cx)
+                    .project()
+                    .read(cx)
+                    .task_inventory()
+                    .read(cx)
+                    .is_task_watched(&terminal_task.id)
+            })
+        });
 
         h_flex()
             .gap_2()
@@ -845,6 +857,13 @@ impl Item for TerminalView {
             } else {
                 Color::Muted
             }))
+            .when(watched, |this| {
+                this.child(
+                    Icon::new(IconName::RotateCw)
+                        .size(IconSize::XSmall)
+                        .color(Color::Accent),
+                )
+            })
             .into_any()
     }
 
@@ -0,0 +1,249 @@
+use std::path::Path;
+
+use gpui::SharedString;
+use language::{Anchor, BufferSnapshot};
+
+/// The test frameworks whose tests the panel discovers and runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum TestFramework {
+    Cargo,
+    Go,
+    Pytest,
+}
+
+impl TestFramework {
+    /// The framework running the tests matched by `runnables.scm` patterns with the given tag.
+    fn for_runnable_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "rust-test" => Some(Self::Cargo),
+            "go-test" => Some(Self::Go),
+            "python-unittest-method" => Some(Self::Pytest),
+            _ => None,
+        }
+    }
+
+    /// The framework whose tests the file at the given path may declare, judging by its name.
+    pub(crate) fn for_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?;
+        if file_name.ends_with(".rs") {
+            Some(Self::Cargo)
+        } else if file_name.ends_with("_test.go") {
+            Some(Self::Go)
+        } else if file_name.ends_with(".py")
+            && (file_name.starts_with("test_") || file_name.ends_with("_test.py"))
+        {
+            Some(Self::Pytest)
+        } else {
+            None
+        }
+    }
+
+    /// What separates the last segments of the full names the framework reports tests under.
+    fn name_separator(&self) -> Option<&'static str> {
+        match self {
+            Self::Cargo => Some("::"),
+            Self::Go => None,
+            Self::Pytest => Some("."),
+        }
+    }
+}
+
+/// A test declared in a file of the project.
+#[derive(Clone, Debug)]
+pub(crate) struct Test {
+    pub framework: TestFramework,
+    /// The name of the test within its file, such as `tests::it_works` for Rust or
+    /// `TestMath.test_add` for Python.
+    pub name: SharedString,
+    /// The argument selecting the test when passed to the framework.
+    pub filter: String,
+    /// The name the framework reports the test under, when it can be told from the path of
+    /// the file.
+    pub full_name: Option<String>,
+    /// Where the test is declared, which is also where its run indicator is shown.
+    pub position: Anchor,
+}
+
+impl Test {
+    /// Whether the framework reported a result for this test under the given name. Unless
+    /// `exact` is set, the name of the test may be the last segments of the reported one, as
+    /// the full name is not always known.
+    pub(crate) fn is_reported_as(&self, reported_name: &str, exact: bool) -> bool {
+        if self.full_name.as_deref() == Some(reported_name) {
+            return true;
+        }
+        if exact {
+            return false;
+        }
+        reported_name == self.name.as_ref()
+            || self.framework.name_separator().map_or(false, |separator| {
+                reported_name
+                    .strip_suffix(self.name.as_ref())
+                    .map_or(false, |prefix| prefix.ends_with(separator))
+            })
+    }
+}
+
+/// The tests declared in a buffer, as matched by the `runnables.scm` query of its language.
+pub(crate) fn tests_in_buffer(relative_path: &Path, snapshot: &BufferSnapshot) -> Vec<Test> {
+    let mut tests = Vec::new();
+    for runnable in snapshot.runnable_ranges(Anchor::MIN..Anchor::MAX) {
+        let Some(framework) = runnable
+            .runnable
+            .tags
+            .iter()
+            .find_map(|tag| TestFramework::for_runnable_tag(&tag.0))
+        else {
+            continue;
+        };
+        let run_text = snapshot
+            .text_for_range(runnable.run_range.clone())
+            .collect::<String>();
+        let position = snapshot.anchor_before(runnable.run_range.start);
+        let test = match framework {
+            TestFramework::Cargo => {
+                let mut segments = enclosing_rust_modules(snapshot, runnable.run_range.start);
+                segments.push(run_text);
+                let name = segments.join("::");
+                Test {
+                    framework,
+                    full_name: rust_module_path(relative_path).map(|mut module_path| {
+                        module_path.extend(segments);
+                        module_path.join("::")
+                    }),
+                    filter: name.clone(),
+                    name: name.into(),
+                    position,
+                }
+            }
+            TestFramework::Go => {
+                // `TestMain` sets up the other tests of the package rather than being one.
+                if run_text == "TestMain" {
+                    continue;
+                }
+                Test {
+                    framework,
+                    name: run_text.clone().into(),
+                    filter: run_text.clone(),
+                    full_name: Some(run_text),
+                    position,
+                }
+            }
+            TestFramework::Pytest => {
+                let Some(class_name) = runnable.extra_captures.get("_unittest_class_name") else {
+                    continue;
+                };
+                let module = relative_path
+                    .with_extension("")
+                    .iter()
+                    .map(|component| component.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                Test {
+                    framework,
+                    name: format!("{class_name}.{run_text}").into(),
+                    filter: format!(
+                        "{}::{class_name}::{run_text}",
+                        relative_path.to_string_lossy()
+                    ),
+                    full_name: Some(format!("{module}.{class_name}.{run_text}")),
+                    position,
+                }
+            }
+        };
+        tests.push(test);
+    }
+    tests
+}
+
+/// The names of the inline modules of a Rust file enclosing the given offset, outermost first.
+fn enclosing_rust_modules(snapshot: &BufferSnapshot, offset: usize) -> Vec<String> {
+    snapshot
+        .symbols_containing(offset, None)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|item| {
+            let name_range = item.name_ranges.first()?.clone();
+            item.text[..name_range.start]
+                .trim_end()
+                .ends_with("mod")
+                .then(|| item.text[name_range].to_string())
+        })
+        .collect()
+}
+
+/// The path of the module a Rust file declares within its crate, such as `foo::bar` for
+/// `src/foo/bar.rs`, assuming the conventional layout of a Cargo package.
+fn rust_module_path(relative_path: &Path) -> Option<Vec<String>> {
+    let mut components = relative_path
+        .with_extension("")
+        .iter()
+        .map(|component| component.to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    let root_ix = components.iter().rposition(|component| {
+        matches!(component.as_str(), "src" | "tests" | "benches" | "examples")
+    })?;
+    let mut modules = components.split_off(root_ix + 1);
+    // Apart from the library and main binary in `src`, every file or directory directly
+    // within these directories is the root of its own crate.
+    let mut in_crate_directory = components[root_ix] != "src";
+    if !in_crate_directory && modules.first().map(String::as_str) == Some("bin") {
+        modules.remove(0);
+        in_crate_directory = true;
+    }
+    if in_crate_directory && !modules.is_empty() {
+        modules.remove(0);
+    }
+    if matches!(
+        modules.last().map(String::as_str),
+        Some("lib" | "main" | "mod")
+    ) {
+        modules.pop();
+    }
+    Some(modules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_module_path() {
+        let module_path =
+            |path: &str| rust_module_path(Path::new(path)).map(|path| path.join("::"));
+        assert_eq!(module_path("src/lib.rs").as_deref(), Some(""));
+        assert_eq!(module_path("src/main.rs").as_deref(), Some(""));
+        assert_eq!(module_path("src/foo.rs").as_deref(), Some("foo"));
+        assert_eq!(module_path("src/foo/mod.rs").as_deref(), Some("foo"));
+        assert_eq!(
+            module_path("crates/editor/src/display_map/fold_map.rs").as_deref(),
+            Some("display_map::fold_map")
+        );
+        assert_eq!(module_path("src/bin/tool.rs").as_deref(), Some(""));
+        assert_eq!(
+            module_path("src/bin/tool/helpers.rs").as_deref(),
+            Some("helpers")
+        );
+        assert_eq!(module_path("tests/integration.rs").as_deref(), Some(""));
+        assert_eq!(
+            module_path("tests/integration/main.rs").as_deref(),
+            Some("")
+        );
+        assert_eq!(
+            module_path("tests/integration/support.rs").as_deref(),
+            Some("support")
+        );
+        assert_eq!(module_path("build.rs"), None);
+    }
+
+    #[test]
+    fn test_test_framework_for_path() {
+        let framework = |path: &str| TestFramework::for_path(Path::new(path));
+        assert_eq!(framework("src/lib.rs"), Some(TestFramework::Cargo));
+        assert_eq!(framework("calc/calc_test.go"), Some(TestFramework::Go));
+        assert_eq!(framework("calc/calc.go"), None);
+        assert_eq!(framework("tests/test_math.py"), Some(TestFramework::Pytest));
+        assert_eq!(framework("tests/math_test.py"), Some(TestFramework::Pytest));
+        assert_eq!(framework("math.py"), None);
+    }
+}
@@ -0,0 +1,501 @@
+use std::{path::PathBuf, time::Duration};
+
+use collections::HashMap;
+use serde::Deserialize;
+
+/// What a test framework reported about a single test.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TestResult {
+    /// The name of the test, as the framework reports it.
+    pub name: String,
+    pub outcome: TestOutcome,
+    pub duration: Option<Duration>,
+    /// The output of a failed test, or why a test was skipped.
+    pub message: Option<String>,
+    /// The file and the one-based line at which the test failed, relative to the directory
+    /// the framework ran in, when the output mentions it.
+    pub location: Option<(PathBuf, u32)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TestOutcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Deserialize)]
+struct CargoTestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: Option<String>,
+    exec_time: Option<f64>,
+    stdout: Option<String>,
+    message: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CargoArtifact {
+    reason: String,
+    profile: Option<CargoArtifactProfile>,
+    executable: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct CargoArtifactProfile {
+    test: bool,
+}
+
+/// Parses the output of `cargo test --no-run --message-format=json`, returning the paths of
+/// the test binaries that were built.
+pub(crate) fn parse_cargo_test_executables(output: &str) -> Vec<PathBuf> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<CargoArtifact>(line.trim()).ok())
+        .filter(|artifact| {
+            artifact.reason == "compiler-artifact"
+                && artifact
+                    .profile
+                    .as_ref()
+                    .is_some_and(|profile| profile.test)
+        })
+        .filter_map(|artifact| artifact.executable)
+        .collect()
+}
+
+/// Parses the output of a Rust test binary run with `-Z unstable-options --format json`,
+/// where each line is an event of the test harness.
+pub(crate) fn parse_cargo_test_json(output: &str) -> Vec<TestResult> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<CargoTestEvent>(line.trim()).ok())
+        .filter(|event| event.kind == "test")
+        .filter_map(|event| {
+            let outcome = match event.event.as_str() {
+                "ok" => TestOutcome::Passed,
+                "failed" | "timeout" => TestOutcome::Failed,
+                "ignored" => TestOutcome::Skipped,
+                _ => return None,
+            };
+            let message = event.stdout.or(event.message);
+            Some(TestResult {
+                name: event.name?,
+                outcome,
+                duration: event.exec_time.map(Duration::from_secs_f64),
+                location: message.as_deref().and_then(panic_location),
+                message: message.filter(|message| !message.trim().is_empty()),
+            })
+        })
+        .collect()
+}
+
+/// The location of a Rust panic, from a message such as
+/// `thread 'tests::it_works' panicked at src/lib.rs:10:9:`.
+fn panic_location(message: &str) -> Option<(PathBuf, u32)> {
+    let (_, rest) = message.split_once("panicked at ")?;
+    // Before Rust 1.73, the panic message came first: `panicked at 'message', src/lib.rs:10:9`.
+    let rest = match rest.strip_prefix('\'') {
+        Some(rest) => rest.split_once("', ")?.1,
+        None => rest,
+    };
+    let location = rest.lines().next()?.trim_end_matches(':');
+    let mut parts = location.rsplitn(3, ':');
+    let _column = parts.next()?;
+    let line = parts.next()?.parse().ok()?;
+    let path = parts.next()?;
+    Some((PathBuf::from(path), line))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GoTestEvent {
+    action: String,
+    package: Option<String>,
+    test: Option<String>,
+    elapsed: Option<f64>,
+    output: Option<String>,
+}
+
+/// Parses the output of `go test -json`, collecting the output of each test into the message
+/// of its result.
+pub(crate) fn parse_go_test_json(output: &str) -> Vec<TestResult> {
+    let mut outputs = HashMap::<(Option<String>, String), String>::default();
+    let mut results = Vec::new();
+    for event in output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<GoTestEvent>(line.trim()).ok())
+    {
+        let Some(test) = event.test else {
+            continue;
+        };
+        let key = (event.package, test);
+        let outcome = match event.action.as_str() {
+            "output" => {
+                let output = event.output.unwrap_or_default();
+                // Skip the lines `go test` prints around the output of every test.
+                if !output.starts_with("=== ") && !output.trim_start().starts_with("--- ") {
+                    outputs.entry(key).or_default().push_str(&output);
+                }
+                continue;
+            }
+            "pass" => TestOutcome::Passed,
+            "fail" => TestOutcome::Failed,
+            "skip" => TestOutcome::Skipped,
+            _ => continue,
+        };
+        let message = outputs
+            .remove(&key)
+            .filter(|message| !message.trim().is_empty());
+        results.push(TestResult {
+            name: key.1,
+            outcome,
+            duration: event.elapsed.map(Duration::from_secs_f64),
+            location: message
+                .as_deref()
+                .and_then(|message| source_location(message, ".go", false)),
+            message: message.filter(|_| outcome != TestOutcome::Passed),
+        });
+    }
+    results
+}
+
+/// Parses a JUnit XML report, as written by `pytest --junitxml`. Test names are the class
+/// name of each test case followed by a dot and the test case name.
+pub(crate) fn parse_junit_xml(xml: &str) -> Vec<TestResult> {
+    let mut results = Vec::new();
+    let mut current_case: Option<TestResult> = None;
+    // The text of the `failure` or `error` element being parsed.
+    let mut failure_text: Option<String> = None;
+    let mut rest = xml;
+    while let Some(tag_start) = rest.find('<') {
+        if let Some(text) = failure_text.as_mut() {
+            text.push_str(&unescape_xml(&rest[..tag_start]));
+        }
+        rest = &rest[tag_start..];
+
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            if let Some(text) = failure_text.as_mut() {
+                text.push_str(&cdata[..end]);
+            }
+            rest = cdata.get(end + 3..).unwrap_or_default();
+            continue;
+        }
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(tag_end) = tag_end(rest) else {
+            break;
+        };
+        let tag = &rest[1..tag_end];
+        rest = &rest[tag_end + 1..];
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        if let Some(name) = tag.strip_prefix('/') {
+            match name.trim() {
+                "testcase" => results.extend(current_case.take()),
+                "failure" | "error" => {
+                    if let (Some(case), Some(text)) = (current_case.as_mut(), failure_text.take()) {
+                        if !text.trim().is_empty() {
+                            case.location = source_location(&text, ".py", true);
+                            case.message = Some(text);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let is_self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let (name, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        let mut attributes = parse_xml_attributes(attributes);
+        match name {
+            "testcase" => {
+                let test_name = attributes.remove("name").unwrap_or_default();
+                let case = TestResult {
+                    name: match attributes.remove("classname") {
+                        Some(class_name) if !class_name.is_empty() => {
+                            format!("{class_name}.{test_name}")
+                        }
+                        _ => test_name,
+                    },
+                    outcome: TestOutcome::Passed,
+                    duration: attributes
+                        .get("time")
+                        .and_then(|time| time.parse().ok())
+                        .map(Duration::from_secs_f64),
+                    message: None,
+                    location: None,
+                };
+                if is_self_closing {
+                    results.push(case);
+                } else {
+                    current_case = Some(case);
+                }
+            }
+            "failure" | "error" | "skipped" => {
+                if let Some(case) = current_case.as_mut() {
+                    case.outcome = if name == "skipped" {
+                        TestOutcome::Skipped
+                    } else {
+                        TestOutcome::Failed
+                    };
+                    case.message = attributes.remove("message");
+                    if !is_self_closing && name != "skipped" {
+                        failure_text = Some(String::new());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    results
+}
+
+/// The index of the `>` closing the tag at the start of the text, skipping the ones in
+/// quoted attribute values.
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (ix, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, '>') => return Some(ix),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_xml_attributes(mut text: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::default();
+    while let Some((name, rest)) = text.split_once('=') {
+        let rest = rest.trim_start();
+        let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let Some(end) = rest[1..].find(quote) else {
+            break;
+        };
+        attributes.insert(name.trim().to_string(), unescape_xml(&rest[1..end + 1]));
+        text = &rest[end + 2..];
+    }
+    attributes
+}
+
+fn unescape_xml(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(ix) = rest.find('&') {
+        unescaped.push_str(&rest[..ix]);
+        rest = &rest[ix..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#')?.parse().ok())
+                .and_then(char::from_u32),
+        };
+        match c {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Finds a location such as `foo_test.go:12:` of a file with the given extension in the
+/// text, picking the last one when `last` is set.
+fn source_location(text: &str, extension: &str, last: bool) -> Option<(PathBuf, u32)> {
+    let mut locations = text.split_whitespace().filter_map(|word| {
+        let mut parts = word.split(':');
+        let path = parts.next()?;
+        let line = parts.next()?.parse().ok()?;
+        path.ends_with(extension)
+            .then(|| (PathBuf::from(path), line))
+    });
+    if last {
+        locations.next_back()
+    } else {
+        locations.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cargo_test_executables() {
+        let output = r#"
+{"reason":"compiler-artifact","target":{"kind":["lib"],"name":"calc"},"profile":{"test":false},"executable":null}
+{"reason":"compiler-artifact","target":{"kind":["bin"],"name":"calc"},"profile":{"test":false},"executable":"/calc/target/debug/calc"}
+{"reason":"compiler-artifact","target":{"kind":["lib"],"name":"calc"},"profile":{"test":true},"executable":"/calc/target/debug/deps/calc-1a2b"}
+{"reason":"compiler-artifact","target":{"kind":["test"],"name":"api"},"profile":{"test":true},"executable":"/calc/target/debug/deps/api-3c4d"}
+{"reason":"build-finished","success":true}
+"#;
+        assert_eq!(
+            parse_cargo_test_executables(output),
+            vec![
+                PathBuf::from("/calc/target/debug/deps/calc-1a2b"),
+                PathBuf::from("/calc/target/debug/deps/api-3c4d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_cargo_test_json() {
+        let output = r#"
+{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "tests::passes" }
+{ "type": "test", "name": "tests::passes", "event": "ok", "exec_time": 0.5 }
+{ "type": "test", "event": "started", "name": "tests::fails" }
+{ "type": "test", "name": "tests::fails", "event": "failed", "exec_time": 0.25, "stdout": "thread 'tests::fails' panicked at src/lib.rs:12:9:\nassertion failed: false\n" }
+{ "type": "test", "event": "ignored", "name": "tests::ignored" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "measured": 0, "filtered_out": 0, "exec_time": 0.75 }
+"#;
+        assert_eq!(
+            parse_cargo_test_json(output),
+            vec![
+                TestResult {
+                    name: "tests::passes".into(),
+                    outcome: TestOutcome::Passed,
+                    duration: Some(Duration::from_millis(500)),
+                    message: None,
+                    location: None,
+                },
+                TestResult {
+                    name: "tests::fails".into(),
+                    outcome: TestOutcome::Failed,
+                    duration: Some(Duration::from_millis(250)),
+                    message: Some(
+                        "thread 'tests::fails' panicked at src/lib.rs:12:9:\nassertion failed: false\n"
+                            .into()
+                    ),
+                    location: Some((PathBuf::from("src/lib.rs"), 12)),
+                },
+                TestResult {
+                    name: "tests::ignored".into(),
+                    outcome: TestOutcome::Skipped,
+                    duration: None,
+                    message: None,
+                    location: None,
+                },
+            ]
+        );
+
+        assert_eq!(
+            panic_location("thread 'main' panicked at 'boom', src/main.rs:3:5\nnote: ..."),
+            Some((PathBuf::from("src/main.rs"), 3))
+        );
+    }
+
+    #[test]
+    fn test_parse_go_test_json() {
+        let output = r#"
+{"Action":"start","Package":"example.com/calc"}
+{"Action":"run","Package":"example.com/calc","Test":"TestAdd"}
+{"Action":"output","Package":"example.com/calc","Test":"TestAdd","Output":"=== RUN   TestAdd\n"}
+{"Action":"output","Package":"example.com/calc","Test":"TestAdd","Output":"--- PASS: TestAdd (0.00s)\n"}
+{"Action":"pass","Package":"example.com/calc","Test":"TestAdd","Elapsed":0}
+{"Action":"run","Package":"example.com/calc","Test":"TestSub"}
+{"Action":"output","Package":"example.com/calc","Test":"TestSub","Output":"=== RUN   TestSub\n"}
+{"Action":"output","Package":"example.com/calc","Test":"TestSub","Output":"    calc_test.go:14: want 1, got 2\n"}
+{"Action":"output","Package":"example.com/calc","Test":"TestSub","Output":"--- FAIL: TestSub (0.01s)\n"}
+{"Action":"fail","Package":"example.com/calc","Test":"TestSub","Elapsed":0.01}
+{"Action":"output","Package":"example.com/calc","Output":"FAIL\n"}
+{"Action":"fail","Package":"example.com/calc","Elapsed":0.02}
+"#;
+        assert_eq!(
+            parse_go_test_json(output),
+            vec![
+                TestResult {
+                    name: "TestAdd".into(),
+                    outcome: TestOutcome::Passed,
+                    duration: Some(Duration::ZERO),
+                    message: None,
+                    location: None,
+                },
+                TestResult {
+                    name: "TestSub".into(),
+                    outcome: TestOutcome::Failed,
+                    duration: Some(Duration::from_millis(10)),
+                    message: Some("    calc_test.go:14: want 1, got 2\n".into()),
+                    location: Some((PathBuf::from("calc_test.go"), 14)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_junit_xml() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<testsuites>
+  <testsuite name="pytest" errors="0" failures="1" skipped="1" tests="3" time="0.050">
+    <testcase classname="tests.test_math.TestMath" name="test_add" time="0.001" />
+    <testcase classname="tests.test_math.TestMath" name="test_sub" time="0.002">
+      <failure message="AssertionError: 1 != 2">self = &lt;tests.test_math.TestMath testMethod=test_sub&gt;
+
+&gt;       self.assertEqual(1, 2)
+E       AssertionError: 1 != 2
+
+tests/test_math.py:8: AssertionError</failure>
+    </testcase>
+    <testcase classname="tests.test_math.TestMath" name="test_skip" time="0.000">
+      <skipped type="pytest.skip" message="not ready" />
+    </testcase>
+  </testsuite>
+</testsuites>"#;
+        assert_eq!(
+            parse_junit_xml(xml),
+            vec![
+                TestResult {
+                    name: "tests.test_math.TestMath.test_add".into(),
+                    outcome: TestOutcome::Passed,
+                    duration: Some(Duration::from_millis(1)),
+                    message: None,
+                    location: None,
+                },
+                TestResult {
+                    name: "tests.test_math.TestMath.test_sub".into(),
+                    outcome: TestOutcome::Failed,
+                    duration: Some(Duration::from_millis(2)),
+                    message: Some(
+                        "self = <tests.test_math.TestMath testMethod=test_sub>\n\n>       self.assertEqual(1, 2)\nE       AssertionError: 1 != 2\n\ntests/test_math.py:8: AssertionError"
+                            .into()
+                    ),
+                    location: Some((PathBuf::from("tests/test_math.py"), 8)),
+                },
+                TestResult {
+                    name: "tests.test_math.TestMath.test_skip".into(),
+                    outcome: TestOutcome::Skipped,
+                    duration: Some(Duration::ZERO),
+                    message: Some("not ready".into()),
+                    location: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+};
+
+use anyhow::{bail, Context as _, Result};
+use project::Worktree;
+
+use crate::{
+    discovery::TestFramework,
+    results::{
+        parse_cargo_test_executables, parse_cargo_test_json, parse_go_test_json, parse_junit_xml,
+        TestResult,
+    },
+};
+
+impl TestFramework {
+    /// The directory of the worktree to run the tests of the file at the given path in, so
+    /// that tests of several files may share a single run.
+    pub(crate) fn working_directory(&self, relative_path: &Path, worktree: &Worktree) -> Arc<Path> {
+        match self {
+            // The package of the file, as Cargo only builds the packages it is asked for.
+            Self::Cargo => relative_path
+                .ancestors()
+                .skip(1)
+                .find(|dir| worktree.entry_for_path(dir.join("Cargo.toml")).is_some())
+                .unwrap_or(Path::new(""))
+                .into(),
+            // Go tests are run per package, which is the directory of the file.
+            Self::Go => relative_path.parent().unwrap_or(Path::new("")).into(),
+            Self::Pytest => Path::new("").into(),
+        }
+    }
+}
+
+/// Runs the tests selected by the given filters, or all tests when there are none, in the
+/// given directory, returning the results reported by the framework.
+pub(crate) async fn run_tests(
+    framework: TestFramework,
+    cwd: &Path,
+    filters: Option<&[String]>,
+) -> Result<Vec<TestResult>> {
+    let mut report_file = None;
+    let (program, args) = match framework {
+        TestFramework::Cargo => return run_cargo_tests(cwd, filters).await,
+        TestFramework::Go => {
+            let mut args = vec!["test".to_string(), "-json".into()];
+            if let Some(filters) = filters {
+                args.push("-run".into());
+                args.push(format!("^({})$", filters.join("|")));
+            }
+            args.push(".".into());
+            ("go", args)
+        }
+        TestFramework::Pytest => {
+            let report = tempfile::Builder::new()
+                .prefix("zed-pytest-")
+                .suffix(".xml")
+                .tempfile()
+                .context("creating the pytest report file")?;
+            let mut args = vec![
+                "-m".to_string(),
+                "pytest".into(),
+                format!("--junitxml={}", report.path().display()),
+            ];
+            args.extend(filters.into_iter().flatten().cloned());
+            report_file = Some(report);
+            ("python3", args)
+        }
+    };
+
+    let output = smol::process::Command::new(program)
+        .args(&args)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .with_context(|| format!("running `{program}` in {cwd:?}"))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Go reports its results on stdout, while pytest writes them to the report file.
+    let results = match &report_file {
+        None => parse_go_test_json(&stdout),
+        Some(report) => {
+            let report = smol::fs::read_to_string(report.path())
+                .await
+                .unwrap_or_default();
+            parse_junit_xml(&report)
+        }
+    };
+    if results.is_empty() && !output.status.success() {
+        // No test ran at all, which usually means that the tests failed to build.
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        bail!("`{program} {}` failed: {message}", args.join(" "));
+    }
+    Ok(results)
+}
+
+/// Builds the test binaries of the package with Cargo, then runs each of them. The JSON
+/// output of the Rust test harness is unstable, so it's unlocked with `RUSTC_BOOTSTRAP` on
+/// stable toolchains, which is only set for the test binaries: set for the build, it would
+/// change how the crates are compiled and throw away what Cargo has built before.
+async fn run_cargo_tests(cwd: &Path, filters: Option<&[String]>) -> Result<Vec<TestResult>> {
+    let build_args = [
+        "test",
+        "--no-run",
+        "--message-format=json-render-diagnostics",
+    ];
+    let build = smol::process::Command::new("cargo")
+        .args(build_args)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .with_context(|| format!("running `cargo` in {cwd:?}"))?;
+    let stdout = String::from_utf8_lossy(&build.stdout);
+    if !build.status.success() {
+        let stderr = String::from_utf8_lossy(&build.stderr);
+        bail!("`cargo {}` failed: {}", build_args.join(" "), stderr.trim());
+    }
+
+    let mut args = Vec::new();
+    if let Some(filters) = filters {
+        args.extend(filters.iter().cloned());
+        // Otherwise, a filter also selects every test whose name contains it.
+        args.push("--exact".to_string());
+    }
+    args.extend(
+        [
+            "-Z",
+            "unstable-options",
+            "--format",
+            "json",
+            "--report-time",
+        ]
+        .map(String::from),
+    );
+    let mut results = Vec::new();
+    for executable in parse_cargo_test_executables(&stdout) {
+        let output = smol::process::Command::new(&executable)
+            .args(&args)
+            .current_dir(cwd)
+            .env("RUSTC_BOOTSTRAP", "1")
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .with_context(|| format!("running {executable:?} in {cwd:?}"))?;
+        results.extend(parse_cargo_test_json(&String::from_utf8_lossy(
+            &output.stdout,
+        )));
+    }
+    Ok(results)
+}
+
+/// The zero-based row of the file at the given absolute path at which a test failed, if the
+/// location reported for the failure is in that file.
+pub(crate) fn failure_row(location: &(PathBuf, u32), abs_path: &Path) -> Option<u32> {
+    let (path, line) = location;
+    (abs_path == path || abs_path.ends_with(path)).then(|| line.saturating_sub(1))
+}
@@ -0,0 +1,1084 @@
+mod discovery;
+mod results;
+mod runner;
+mod test_panel_settings;
+
+use std::{path::Path, sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use collections::{BTreeMap, HashMap, HashSet};
+use db::kvp::KEY_VALUE_STORE;
+use discovery::{tests_in_buffer, Test, TestFramework};
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{
+    actions, div, px, uniform_list, Action, AppContext, AsyncWindowContext, ElementId,
+    EventEmitter, FocusHandle, FocusableView, InteractiveElement, IntoElement, KeyContext, Model,
+    MouseButton, ParentElement, Pixels, Render, SharedString, Stateful, Styled, Subscription, Task,
+    UniformListScrollHandle, View, ViewContext, VisualContext, WeakView, WindowContext,
+};
+use language::{
+    Buffer, Diagnostic, DiagnosticEntry, DiagnosticSeverity, LanguageServerId, Point, PointUtf16,
+    ToPoint, Unclipped,
+};
+use project::{Fs, PathChange, Project, ProjectPath, TestStatus, WorktreeId};
+use results::{TestOutcome, TestResult};
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use test_panel_settings::{TestPanelDockPosition, TestPanelSettings};
+use util::{ResultExt, TryFutureExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    ui::{
+        h_flex, v_flex, ActiveTheme, ButtonCommon, Clickable, Color, Disableable, FluentBuilder,
+        Icon, IconButton, IconName, IconSize, Label, LabelCommon, LabelSize, ListItem,
+        StyledTypography, Tooltip,
+    },
+    Workspace,
+};
+
+actions!(
+    test_panel,
+    [
+        ToggleFocus,
+        RunAllTests,
+        RunFileTests,
+        StopTests,
+        RefreshTests
+    ]
+);
+
+const TEST_PANEL_KEY: &str = "TestPanel";
+
+/// How many lines of the output of a failed test are shown in its diagnostic.
+const MAX_DIAGNOSTIC_LINES: usize = 20;
+
+/// The tests to run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestSelection {
+    All,
+    File(ProjectPath),
+    Test(ProjectPath, SharedString),
+}
+
+/// A file of the project that may declare tests, kept open to follow its edits.
+struct TestFile {
+    buffer: Model<Buffer>,
+    tests: Vec<Test>,
+    _subscription: Subscription,
+}
+
+/// The outcome of the latest run of a test.
+#[derive(Clone, Debug)]
+struct TestState {
+    status: TestStatus,
+    duration: Option<Duration>,
+    message: Option<SharedString>,
+    /// The zero-based row of the file of the test at which it failed, when known.
+    failure_row: Option<u32>,
+}
+
+/// The tests of a framework run by a single command.
+struct TestGroup {
+    framework: TestFramework,
+    cwd: Arc<Path>,
+    /// The arguments selecting the tests to run, or `None` to run all tests in `cwd`.
+    filters: Option<Vec<String>>,
+    tests: Vec<(ProjectPath, Test)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ListEntry {
+    File(ProjectPath),
+    /// A test, given by the index of it among the tests of its file.
+    Test(ProjectPath, usize),
+}
+
+pub struct TestPanel {
+    fs: Arc<dyn Fs>,
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    width: Option<Pixels>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    pending_serialization: Task<Option<()>>,
+    files: BTreeMap<ProjectPath, TestFile>,
+    /// The outcomes of the tests that were run, keyed by file and by test name.
+    results: HashMap<ProjectPath, HashMap<SharedString, TestState>>,
+    collapsed_files: HashSet<ProjectPath>,
+    entries: Vec<ListEntry>,
+    run_task: Option<Task<()>>,
+    /// The identifier under which test failures are reported as diagnostics, as if they came
+    /// from a language server.
+    diagnostics_server_id: LanguageServerId,
+    files_with_diagnostics: HashSet<ProjectPath>,
+    _subscriptions: Vec<Subscription>,
+}
+
+#[derive(Debug)]
+pub enum Event {
+    Focus,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedTestPanel {
+    width: Option<Pixels>,
+}
+
+pub fn init_settings(cx: &mut AppContext) {
+    TestPanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut AppContext) {
+    init_settings(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, _: &ToggleFocus, cx| {
+                workspace.toggle_panel_focus::<TestPanel>(cx);
+            })
+            .register_action(|workspace, _: &RunAllTests, cx| {
+                if let Some(panel) = workspace.focus_panel::<TestPanel>(cx) {
+                    panel.update(cx, |panel, cx| panel.run_tests(TestSelection::All, cx));
+                }
+            })
+            .register_action(|workspace, _: &RunFileTests, cx| {
+                let Some(project_path) = workspace
+                    .active_item(cx)
+                    .and_then(|item| item.project_path(cx))
+                else {
+                    return;
+                };
+                if let Some(panel) = workspace.panel::<TestPanel>(cx) {
+                    panel.update(cx, |panel, cx| {
+                        panel.run_tests(TestSelection::File(project_path), cx)
+                    });
+                }
+            })
+            .register_action(|workspace, _: &StopTests, cx| {
+                if let Some(panel) = workspace.panel::<TestPanel>(cx) {
+                    panel.update(cx, |panel, cx| panel.stop_tests(cx));
+                }
+            })
+            .register_action(|workspace, _: &RefreshTests, cx| {
+                if let Some(panel) = workspace.panel::<TestPanel>(cx) {
+                    panel.update(cx, |panel, cx| panel.discover_tests(cx));
+                }
+            });
+    })
+    .detach();
+}
+
+impl TestPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(TEST_PANEL_KEY) })
+            .await
+            .context("loading test panel")
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedTestPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let panel = Self::new(workspace, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        let workspace_handle = workspace.weak_handle();
+        cx.new_view(|cx| {
+            let focus_handle = cx.focus_handle();
+            let focus_subscription = cx.on_focus(&focus_handle, Self::focus_in);
+
+            let mut test_panel_settings = *TestPanelSettings::get_global(cx);
+            let settings_subscription = cx.observe_global::<SettingsStore>(move |_, cx| {
+                let new_settings = *TestPanelSettings::get_global(cx);
+                if test_panel_settings != new_settings {
+                    test_panel_settings = new_settings;
+                    cx.notify();
+                }
+            });
+            let project_subscription = cx.subscribe(&project, |panel, _, event, cx| match event {
+                project::Event::WorktreeAdded | project::Event::WorktreeRemoved(_) => {
+                    panel.discover_tests(cx);
+                }
+                project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                    let changes = changes
+                        .iter()
+                        .map(|(path, _, change)| (path.clone(), *change))
+                        .collect::<Vec<_>>();
+                    panel.worktree_entries_updated(*worktree_id, changes, cx);
+                }
+                _ => {}
+            });
+            let diagnostics_server_id = project.read(cx).languages().next_language_server_id();
+
+            let mut panel = Self {
+                fs,
+                workspace: workspace_handle,
+                project,
+                width: None,
+                focus_handle,
+                scroll_handle: UniformListScrollHandle::new(),
+                pending_serialization: Task::ready(None),
+                files: BTreeMap::default(),
+                results: HashMap::default(),
+                collapsed_files: HashSet::default(),
+                entries: Vec::new(),
+                run_task: None,
+                diagnostics_server_id,
+                files_with_diagnostics: HashSet::default(),
+                _subscriptions: vec![
+                    settings_subscription,
+                    focus_subscription,
+                    project_subscription,
+                ],
+            };
+            panel.discover_tests(cx);
+            panel
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        TEST_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedTestPanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn dispatch_context(&self, _: &ViewContext<Self>) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("TestPanel");
+        dispatch_context
+    }
+
+    fn focus_in(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.focus_handle.contains_focused(cx) {
+            cx.emit(Event::Focus);
+        }
+    }
+
+    /// Looks for tests in every file of the local worktrees that may declare them.
+    fn discover_tests(&mut self, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        let mut candidates = Vec::new();
+        for worktree in project.visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            // Tests are run as local processes.
+            if !worktree.is_local() {
+                continue;
+            }
+            let worktree_id = worktree.id();
+            candidates.extend(
+                worktree
+                    .files(false, 0)
+                    .filter(|entry| TestFramework::for_path(&entry.path).is_some())
+                    .map(|entry| ProjectPath {
+                        worktree_id,
+                        path: entry.path.clone(),
+                    }),
+            );
+        }
+
+        let candidate_set = candidates.iter().cloned().collect::<HashSet<_>>();
+        self.files
+            .retain(|project_path, _| candidate_set.contains(project_path));
+        self.results
+            .retain(|project_path, _| candidate_set.contains(project_path));
+        self.update_entries(cx);
+        self.load_files(candidates, cx);
+    }
+
+    fn worktree_entries_updated(
+        &mut self,
+        worktree_id: WorktreeId,
+        changes: Vec<(Arc<Path>, PathChange)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let worktree = worktree.read(cx);
+        if !worktree.is_local() || !worktree.is_visible() {
+            return;
+        }
+
+        let mut changed_paths = Vec::new();
+        let mut removed_any = false;
+        for (path, change) in changes {
+            if TestFramework::for_path(&path).is_none() {
+                continue;
+            }
+            let project_path = ProjectPath { worktree_id, path };
+            if change == PathChange::Removed {
+                removed_any |= self.files.remove(&project_path).is_some();
+                self.results.remove(&project_path);
+            } else if worktree
+                .entry_for_path(&project_path.path)
+                .map_or(false, |entry| !entry.is_ignored)
+            {
+                changed_paths.push(project_path);
+            }
+        }
+        if removed_any {
+            self.update_entries(cx);
+            self.update_diagnostics(cx);
+        }
+        if !changed_paths.is_empty() {
+            self.load_files(changed_paths, cx);
+        }
+    }
+
+    /// Opens the files at the given paths that declare tests, unless they already are.
+    fn load_files(&mut self, paths: Vec<ProjectPath>, cx: &mut ViewContext<Self>) {
+        let project = self.project.clone();
+        let fs = self.fs.clone();
+        cx.spawn(|panel, mut cx| async move {
+            for project_path in paths {
+                let is_loaded =
+                    panel.update(&mut cx, |panel, _| panel.files.contains_key(&project_path))?;
+                if is_loaded {
+                    continue;
+                }
+                let Some(abs_path) = project
+                    .read_with(&cx, |project, cx| project.absolute_path(&project_path, cx))?
+                else {
+                    continue;
+                };
+                // Most source files declare no tests, so avoid opening buffers for them.
+                let Some(text) = fs.load(&abs_path).await.log_err() else {
+                    continue;
+                };
+                if !text.contains("test") && !text.contains("Test") {
+                    continue;
+                }
+                let buffer = project
+                    .update(&mut cx, |project, cx| {
+                        project.open_buffer(project_path.clone(), cx)
+                    })?
+                    .await;
+                if let Some(buffer) = buffer.log_err() {
+                    panel.update(&mut cx, |panel, cx| {
+                        panel.add_file(project_path, buffer, cx)
+                    })?;
+                }
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn add_file(
+        &mut self,
+        project_path: ProjectPath,
+        buffer: Model<Buffer>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let subscription = cx.subscribe(&buffer, {
+            let project_path = project_path.clone();
+            move |panel, _, event, cx| {
+                if let language::Event::Reparsed = event {
+                    panel.refresh_file(&project_path, cx);
+                }
+            }
+        });
+        self.files.insert(
+            project_path.clone(),
+            TestFile {
+                buffer,
+                tests: Vec::new(),
+                _subscription: subscription,
+            },
+        );
+        self.refresh_file(&project_path, cx);
+    }
+
+    /// Finds the tests declared in the file again, now that it was parsed.
+    fn refresh_file(&mut self, project_path: &ProjectPath, cx: &mut ViewContext<Self>) {
+        let Some(file) = self.files.get_mut(project_path) else {
+            return;
+        };
+        let snapshot = file.buffer.read(cx).snapshot();
+        file.tests = tests_in_buffer(&project_path.path, &snapshot);
+        self.update_test_statuses(project_path, cx);
+        self.update_entries(cx);
+    }
+
+    fn update_entries(&mut self, cx: &mut ViewContext<Self>) {
+        self.entries.clear();
+        for (project_path, file) in &self.files {
+            if file.tests.is_empty() {
+                continue;
+            }
+            self.entries.push(ListEntry::File(project_path.clone()));
+            if !self.collapsed_files.contains(project_path) {
+                self.entries.extend(
+                    (0..file.tests.len()).map(|ix| ListEntry::Test(project_path.clone(), ix)),
+                );
+            }
+        }
+        cx.notify();
+    }
+
+    fn toggle_collapsed(&mut self, project_path: &ProjectPath, cx: &mut ViewContext<Self>) {
+        if !self.collapsed_files.remove(project_path) {
+            self.collapsed_files.insert(project_path.clone());
+        }
+        self.update_entries(cx);
+    }
+
+    fn test_state(&self, project_path: &ProjectPath, name: &SharedString) -> Option<&TestState> {
+        self.results.get(project_path)?.get(name)
+    }
+
+    /// The status of a file, summarizing the statuses of its tests.
+    fn file_status(&self, project_path: &ProjectPath) -> Option<TestStatus> {
+        let states = self.results.get(project_path)?;
+        let file = self.files.get(project_path)?;
+        let statuses = file
+            .tests
+            .iter()
+            .filter_map(|test| Some(states.get(&test.name)?.status))
+            .collect::<Vec<_>>();
+        [TestStatus::Running, TestStatus::Failed, TestStatus::Passed]
+            .into_iter()
+            .find(|status| statuses.contains(status))
+            .or_else(|| statuses.first().copied())
+    }
+
+    /// Shows the statuses of the tests of the file in the gutter of its editors.
+    fn update_test_statuses(&mut self, project_path: &ProjectPath, cx: &mut ViewContext<Self>) {
+        let Some(file) = self.files.get(project_path) else {
+            return;
+        };
+        let states = self.results.get(project_path);
+        let statuses = file
+            .tests
+            .iter()
+            .filter_map(|test| Some((test.position, states?.get(&test.name)?.status)))
+            .collect();
+        let buffer = file.buffer.clone();
+        self.project.update(cx, |project, cx| {
+            project.set_test_statuses(&buffer, statuses, cx)
+        });
+    }
+
+    /// Reports the failed tests as diagnostics of their files.
+    fn update_diagnostics(&mut self, cx: &mut ViewContext<Self>) {
+        let mut diagnostics =
+            HashMap::<ProjectPath, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>::default();
+        for (project_path, file) in &self.files {
+            let Some(states) = self.results.get(project_path) else {
+                continue;
+            };
+            let snapshot = file.buffer.read(cx).snapshot();
+            for test in &file.tests {
+                let Some(state) = states
+                    .get(&test.name)
+                    .filter(|state| state.status == TestStatus::Failed)
+                else {
+                    continue;
+                };
+                let row = state
+                    .failure_row
+                    .unwrap_or_else(|| test.position.to_point(&snapshot).row)
+                    .min(snapshot.max_point().row);
+                let start = Point::new(row, snapshot.indent_size_for_line(row).len);
+                let end = Point::new(row, snapshot.line_len(row));
+                let mut message = format!("test {} failed", test.name);
+                if let Some(output) = &state.message {
+                    message.push_str(":\n");
+                    message.extend(
+                        output
+                            .lines()
+                            .take(MAX_DIAGNOSTIC_LINES)
+                            .flat_map(|line| [line, "\n"]),
+                    );
+                    message.truncate(message.trim_end().len());
+                }
+                let entries = diagnostics.entry(project_path.clone()).or_default();
+                let group_id = entries.len();
+                entries.push(DiagnosticEntry {
+                    range: Unclipped(snapshot.point_to_point_utf16(start))
+                        ..Unclipped(snapshot.point_to_point_utf16(end)),
+                    diagnostic: Diagnostic {
+                        source: Some("test".into()),
+                        severity: DiagnosticSeverity::ERROR,
+                        message,
+                        group_id,
+                        is_primary: true,
+                        ..Diagnostic::default()
+                    },
+                });
+            }
+        }
+
+        let stale_paths = self
+            .files_with_diagnostics
+            .iter()
+            .filter(|project_path| !diagnostics.contains_key(project_path))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.files_with_diagnostics = diagnostics.keys().cloned().collect();
+        let server_id = self.diagnostics_server_id;
+        self.project.update(cx, |project, cx| {
+            let updates = stale_paths
+                .into_iter()
+                .map(|project_path| (project_path, Vec::new()))
+                .chain(diagnostics);
+            for (project_path, entries) in updates {
+                if let Some(abs_path) = project.absolute_path(&project_path, cx) {
+                    project
+                        .update_diagnostic_entries(server_id, abs_path, None, entries, cx)
+                        .log_err();
+                }
+            }
+        });
+    }
+
+    /// Runs the selected tests, stopping the tests that are still running.
+    pub fn run_tests(&mut self, selection: TestSelection, cx: &mut ViewContext<Self>) {
+        self.stop_tests(cx);
+
+        let mut groups = BTreeMap::<(WorktreeId, TestFramework, Arc<Path>), TestGroup>::new();
+        let project = self.project.read(cx);
+        for (project_path, file) in &self.files {
+            if let TestSelection::File(path) | TestSelection::Test(path, _) = &selection {
+                if path != project_path {
+                    continue;
+                }
+            }
+            let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx) else {
+                continue;
+            };
+            let worktree = worktree.read(cx);
+            for test in &file.tests {
+                if let TestSelection::Test(_, name) = &selection {
+                    if &test.name != name {
+                        continue;
+                    }
+                }
+                let cwd = test
+                    .framework
+                    .working_directory(&project_path.path, worktree);
+                let group = groups
+                    .entry((project_path.worktree_id, test.framework, cwd.clone()))
+                    .or_insert_with(|| TestGroup {
+                        framework: test.framework,
+                        cwd: worktree.abs_path().join(&cwd).into(),
+                        filters: (selection != TestSelection::All).then(Vec::new),
+                        tests: Vec::new(),
+                    });
+                if let Some(filters) = group.filters.as_mut() {
+                    if !filters.contains(&test.filter) {
+                        filters.push(test.filter.clone());
+                    }
+                }
+                group.tests.push((project_path.clone(), test.clone()));
+            }
+        }
+        if groups.is_empty() {
+            return;
+        }
+
+        let mut running_paths = HashSet::default();
+        for (project_path, test) in groups.values().flat_map(|group| &group.tests) {
+            self.results
+                .entry(project_path.clone())
+                .or_default()
+                .insert(
+                    test.name.clone(),
+                    TestState {
+                        status: TestStatus::Running,
+                        duration: None,
+                        message: None,
+                        failure_row: None,
+                    },
+                );
+            running_paths.insert(project_path.clone());
+        }
+        for project_path in &running_paths {
+            self.update_test_statuses(project_path, cx);
+        }
+        cx.notify();
+
+        let groups = groups.into_values().collect::<Vec<_>>();
+        self.run_task = Some(cx.spawn(|panel, mut cx| async move {
+            for group in groups {
+                let results =
+                    runner::run_tests(group.framework, &group.cwd, group.filters.as_deref()).await;
+                panel
+                    .update(&mut cx, |panel, cx| {
+                        panel.apply_results(group.tests, results, cx)
+                    })
+                    .ok();
+            }
+            panel
+                .update(&mut cx, |panel, cx| {
+                    panel.run_task = None;
+                    cx.notify();
+                })
+                .ok();
+        }));
+    }
+
+    /// Stops the running tests, forgetting that they were started.
+    fn stop_tests(&mut self, cx: &mut ViewContext<Self>) {
+        if self.run_task.take().is_none() {
+            return;
+        }
+        let mut stopped_paths = Vec::new();
+        for (project_path, states) in &mut self.results {
+            let test_count = states.len();
+            states.retain(|_, state| state.status != TestStatus::Running);
+            if states.len() != test_count {
+                stopped_paths.push(project_path.clone());
+            }
+        }
+        for project_path in &stopped_paths {
+            self.update_test_statuses(project_path, cx);
+        }
+        cx.notify();
+    }
+
+    /// Records the results of a run of the given tests, which the framework reports under
+    /// names that do not always match the ones the tests were discovered with.
+    fn apply_results(
+        &mut self,
+        tests: Vec<(ProjectPath, Test)>,
+        results: Result<Vec<TestResult>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let states = match results {
+            Ok(results) => {
+                let mut matches = tests
+                    .iter()
+                    .map(|(_, test)| {
+                        results
+                            .iter()
+                            .position(|result| test.is_reported_as(&result.name, true))
+                    })
+                    .collect::<Vec<_>>();
+                let mut claimed_results = matches.iter().flatten().copied().collect::<HashSet<_>>();
+                for ((_, test), result_ix) in tests.iter().zip(&mut matches) {
+                    if result_ix.is_none() {
+                        *result_ix = (0..results.len()).find(|ix| {
+                            !claimed_results.contains(ix)
+                                && test.is_reported_as(&results[*ix].name, false)
+                        });
+                        claimed_results.extend(*result_ix);
+                    }
+                }
+
+                let project = self.project.read(cx);
+                tests
+                    .into_iter()
+                    .zip(matches)
+                    .map(|((project_path, test), result_ix)| {
+                        let state = result_ix.map(|ix| {
+                            let result = &results[ix];
+                            let failure_row = result.location.as_ref().and_then(|location| {
+                                let abs_path = project.absolute_path(&project_path, cx)?;
+                                runner::failure_row(location, &abs_path)
+                            });
+                            TestState {
+                                status: match result.outcome {
+                                    TestOutcome::Passed => TestStatus::Passed,
+                                    TestOutcome::Failed => TestStatus::Failed,
+                                    TestOutcome::Skipped => TestStatus::Skipped,
+                                },
+                                duration: result.duration,
+                                message: result.message.clone().map(SharedString::from),
+                                failure_row,
+                            }
+                        });
+                        (project_path, test.name, state)
+                    })
+                    .collect::<Vec<_>>()
+            }
+            Err(error) => {
+                let message = SharedString::from(format!("{error:#}"));
+                tests
+                    .into_iter()
+                    .map(|(project_path, test)| {
+                        let state = TestState {
+                            status: TestStatus::Failed,
+                            duration: None,
+                            message: Some(message.clone()),
+                            failure_row: None,
+                        };
+                        (project_path, test.name, Some(state))
+                    })
+                    .collect()
+            }
+        };
+
+        let mut updated_paths = HashSet::default();
+        for (project_path, name, state) in states {
+            let file_states = self.results.entry(project_path.clone()).or_default();
+            match state {
+                Some(state) => {
+                    file_states.insert(name, state);
+                }
+                // Tests the framework did not report on were not run, e.g. as the package
+                // failed to build.
+                None => {
+                    file_states.remove(&name);
+                }
+            }
+            updated_paths.insert(project_path);
+        }
+        for project_path in &updated_paths {
+            self.update_test_statuses(project_path, cx);
+        }
+        self.update_diagnostics(cx);
+        cx.notify();
+    }
+
+    fn open_test(&mut self, project_path: &ProjectPath, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let Some(file) = self.files.get(project_path) else {
+            return;
+        };
+        let Some(test) = file.tests.get(ix) else {
+            return;
+        };
+        let buffer = file.buffer.clone();
+        let position = test.position;
+        workspace.update(cx, |workspace, cx| {
+            let pane = workspace.active_pane().clone();
+            let editor = workspace.open_project_item::<Editor>(pane, buffer.clone(), cx);
+            editor.update(cx, |editor, cx| {
+                let point = position.to_point(&buffer.read(cx).snapshot());
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([point..point])
+                });
+            });
+        });
+    }
+
+    fn render_entry(&self, entry: &ListEntry, cx: &mut ViewContext<Self>) -> Stateful<gpui::Div> {
+        let settings = TestPanelSettings::get_global(cx);
+        let (item_id, list_item) = match entry {
+            ListEntry::File(project_path) => {
+                let item_id = ElementId::from(SharedString::from(format!(
+                    "test_file_{}_{}",
+                    project_path.worktree_id.to_usize(),
+                    project_path.path.display()
+                )));
+                let file_name = project_path
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let directory = project_path
+                    .path
+                    .parent()
+                    .map(|parent| parent.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let is_collapsed = self.collapsed_files.contains(project_path);
+                let list_item = ListItem::new(item_id.clone())
+                    .toggle(Some(!is_collapsed))
+                    .on_toggle(cx.listener({
+                        let project_path = project_path.clone();
+                        move |panel, _, cx| panel.toggle_collapsed(&project_path, cx)
+                    }))
+                    .child(
+                        h_flex()
+                            .h_6()
+                            .ml_1()
+                            .gap_2()
+                            .child(status_icon(self.file_status(project_path)))
+                            .child(Label::new(file_name))
+                            .child(
+                                Label::new(directory)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .end_slot(
+                        IconButton::new(item_id.clone(), IconName::Play)
+                            .icon_size(IconSize::XSmall)
+                            .tooltip(|cx| Tooltip::text("Run Tests in File", cx))
+                            .on_click(cx.listener({
+                                let project_path = project_path.clone();
+                                move |panel, _, cx| {
+                                    panel.run_tests(TestSelection::File(project_path.clone()), cx)
+                                }
+                            })),
+                    )
+                    .on_click(cx.listener({
+                        let project_path = project_path.clone();
+                        move |panel, event: &gpui::ClickEvent, cx| {
+                            if event.down.button == MouseButton::Right || event.down.first_mouse {
+                                return;
+                            }
+                            panel.toggle_collapsed(&project_path, cx);
+                        }
+                    }));
+                (item_id, list_item)
+            }
+            ListEntry::Test(project_path, ix) => {
+                let ix = *ix;
+                let test = &self.files[project_path].tests[ix];
+                let state = self.test_state(project_path, &test.name);
+                let item_id = ElementId::from(SharedString::from(format!(
+                    "test_{}_{}_{}",
+                    project_path.worktree_id.to_usize(),
+                    project_path.path.display(),
+                    test.name
+                )));
+                let duration = state
+                    .and_then(|state| state.duration)
+                    .map(|duration| format!("{}ms", duration.as_millis()));
+                let failure_message = state
+                    .filter(|state| state.status == TestStatus::Failed)
+                    .and_then(|state| state.message.clone());
+                let name = test.name.clone();
+                let list_item = ListItem::new(item_id.clone())
+                    .indent_level(1)
+                    .indent_step_size(px(settings.indent_size))
+                    .child(
+                        h_flex()
+                            .h_6()
+                            .ml_1()
+                            .gap_2()
+                            .child(status_icon(state.map(|state| state.status)))
+                            .child(Label::new(test.name.clone()))
+                            .when_some(duration, |this, duration| {
+                                this.child(
+                                    Label::new(duration)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                )
+                            }),
+                    )
+                    .end_slot(
+                        IconButton::new(item_id.clone(), IconName::Play)
+                            .icon_size(IconSize::XSmall)
+                            .tooltip(|cx| Tooltip::text("Run Test", cx))
+                            .on_click(cx.listener({
+                                let project_path = project_path.clone();
+                                move |panel, _, cx| {
+                                    panel.run_tests(
+                                        TestSelection::Test(project_path.clone(), name.clone()),
+                                        cx,
+                                    )
+                                }
+                            })),
+                    )
+                    .when_some(failure_message, |this, message| {
+                        this.tooltip(move |cx| Tooltip::text(message.clone(), cx))
+                    })
+                    .on_click(cx.listener({
+                        let project_path = project_path.clone();
+                        move |panel, event: &gpui::ClickEvent, cx| {
+                            if event.down.button == MouseButton::Right || event.down.first_mouse {
+                                return;
+                            }
+                            panel.open_test(&project_path, ix, cx);
+                        }
+                    }));
+                (item_id, list_item)
+            }
+        };
+
+        div()
+            .text_ui(cx)
+            .id(item_id)
+            .child(list_item)
+            .hover(|style| {
+                let hover_color = cx.theme().colors().ghost_element_hover;
+                style.bg(hover_color)
+            })
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let mut passed = 0;
+        let mut failed = 0;
+        for state in self.results.values().flat_map(|states| states.values()) {
+            match state.status {
+                TestStatus::Passed => passed += 1,
+                TestStatus::Failed => failed += 1,
+                TestStatus::Running | TestStatus::Skipped => {}
+            }
+        }
+        let is_running = self.run_task.is_some();
+
+        h_flex()
+            .p_1()
+            .gap_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(format!("{passed} passed")).color(Color::Success))
+                    .child(Label::new(format!("{failed} failed")).color(if failed > 0 {
+                        Color::Error
+                    } else {
+                        Color::Muted
+                    })),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        IconButton::new("run-all-tests", IconName::Play)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::for_action("Run All Tests", &RunAllTests, cx))
+                            .on_click(
+                                cx.listener(|panel, _, cx| panel.run_tests(TestSelection::All, cx)),
+                            ),
+                    )
+                    .child(
+                        IconButton::new("stop-tests", IconName::Stop)
+                            .icon_size(IconSize::Small)
+                            .disabled(!is_running)
+                            .tooltip(|cx| Tooltip::for_action("Stop Tests", &StopTests, cx))
+                            .on_click(cx.listener(|panel, _, cx| panel.stop_tests(cx))),
+                    )
+                    .child(
+                        IconButton::new("refresh-tests", IconName::RotateCw)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::for_action("Refresh Tests", &RefreshTests, cx))
+                            .on_click(cx.listener(|panel, _, cx| panel.discover_tests(cx))),
+                    ),
+            )
+    }
+}
+
+fn status_icon(status: Option<TestStatus>) -> Icon {
+    let (icon, color) = match status {
+        None => (IconName::Indicator, Color::Muted),
+        Some(TestStatus::Running) => (IconName::ArrowCircle, Color::Accent),
+        Some(TestStatus::Passed) => (IconName::Check, Color::Success),
+        Some(TestStatus::Failed) => (IconName::XCircle, Color::Error),
+        Some(TestStatus::Skipped) => (IconName::Dash, Color::Muted),
+    };
+    Icon::new(icon).size(IconSize::Small).color(color)
+}
+
+impl Panel for TestPanel {
+    fn persistent_name() -> &'static str {
+        "Test Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match TestPanelSettings::get_global(cx).dock {
+            TestPanelDockPosition::Left => DockPosition::Left,
+            TestPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<TestPanelSettings>(self.fs.clone(), cx, move |settings| {
+            let dock = match position {
+                DockPosition::Left | DockPosition::Bottom => TestPanelDockPosition::Left,
+                DockPosition::Right => TestPanelDockPosition::Right,
+            };
+            settings.dock = Some(dock);
+        });
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| TestPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        TestPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::TestTube)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Test Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+impl FocusableView for TestPanel {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<Event> for TestPanel {}
+
+impl EventEmitter<PanelEvent> for TestPanel {}
+
+impl Render for TestPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .id("test-panel")
+            .size_full()
+            .key_context(self.dispatch_context(cx))
+            .track_focus(&self.focus_handle)
+            .child(self.render_header(cx))
+            .map(|this| {
+                if self.entries.is_empty() {
+                    this.child(v_flex().p_4().child(
+                        Label::new("No tests were found in this project").color(Color::Muted),
+                    ))
+                } else {
+                    this.child(
+                        uniform_list(
+                            cx.view().clone(),
+                            "entries",
+                            self.entries.len(),
+                            |panel, range, cx| {
+                                panel.entries[range]
+                                    .to_vec()
+                                    .iter()
+                                    .map(|entry| panel.render_entry(entry, cx))
+                                    .collect()
+                            },
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+    }
+}
@@ -0,0 +1,52 @@
+use gpui::Pixels;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TestPanelDockPosition {
+    Left,
+    Right,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct TestPanelSettings {
+    pub button: bool,
+    pub default_width: Pixels,
+    pub dock: TestPanelDockPosition,
+    pub indent_size: f32,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct TestPanelSettingsContent {
+    /// Whether to show the test panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Customise default width (in pixels) taken by test panel
+    ///
+    /// Default: 300
+    pub default_width: Option<f32>,
+    /// The position of test panel
+    ///
+    /// Default: right
+    pub dock: Option<TestPanelDockPosition>,
+    /// Amount of indentation (in pixels) for the tests of a file.
+    ///
+    /// Default: 20
+    pub indent_size: Option<f32>,
+}
+
+impl Settings for TestPanelSettings {
+    const KEY: Option<&'static str> = Some("test_panel");
+
+    type FileContent = TestPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -115,6 +115,12 @@ pub enum IconName {
     Copy,
     CountdownTimer,
     Dash,
+    Debug,
+    DebugBreakpoint,
+    DebugPause,
+    DebugStepInto,
+    DebugStepOut,
+    DebugStepOver,
     Delete,
     Disconnected,
     Download,
@@ -198,6 +204,7 @@ pub enum IconName {
     SupermavenInit,
     Tab,
     Terminal,
+    TestTube,
     Trash,
     TriangleRight,
     Update,
@@ -247,6 +254,12 @@ impl IconName {
             IconName::Copy => "icons/copy.svg",
             IconName::CountdownTimer => "icons/countdown_timer.svg",
             IconName::Dash => "icons/dash.svg",
+            IconName::Debug => "icons/debug.svg",
+            IconName::DebugBreakpoint => "icons/debug_breakpoint.svg",
+            IconName::DebugPause => "icons/debug_pause.svg",
+            IconName::DebugStepInto => "icons/debug_step_into.svg",
+            IconName::DebugStepOut => "icons/debug_step_out.svg",
+            IconName::DebugStepOver => "icons/debug_step_over.svg",
             IconName::Delete => "icons/delete.svg",
             IconName::Disconnected => "icons/disconnected.svg",
             IconName::Download => "icons/download.svg",
@@ -330,6 +343,7 @@ impl IconName {
             IconName::SupermavenInit => "icons/supermaven_init.svg",
             IconName::Tab => "icons/tab.svg",
             IconName::Terminal => "icons/terminal.svg",
+            IconName::TestTube => "icons/test_tube.svg",
             IconName::Trash => "icons/trash.svg",
             IconName::TriangleRight => "icons/triangle_right.svg",
             IconName::Update => "icons/update.svg",
@@ -1,16 +1,39 @@
+use std::{
+    borrow::Cow,
+    ops::{Range, RangeInclusive},
+    path::PathBuf,
+    time::Instant,
+};
+
+use anyhow::{anyhow, Result};
+use command_palette::CommandPalette;
 use command_palette_hooks::CommandInterceptResult;
-use editor::actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive};
-use gpui::{impl_actions, Action, AppContext, ViewContext};
+use editor::{
+    actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
+    scroll::Autoscroll,
+    Anchor, Editor, MultiBufferSnapshot, ToPoint,
+};
+use gpui::{
+    actions, impl_actions, Action, AppContext, Keystroke, Modifiers, View, ViewContext,
+    WindowContext,
+};
+use language::Point;
+use multi_buffer::MultiBufferRow;
+use project::TaskSourceKind;
+use regex::{Regex, RegexBuilder};
 use serde_derive::Deserialize;
-use workspace::{SaveIntent, Workspace};
+use task::{TaskContext, TaskTemplate};
+use workspace::{tasks::schedule_task, SaveIntent, Workspace};
 
 use crate::{
     motion::{EndOfDocument, Motion, StartOfDocument},
     normal::{
         move_cursor,
-        search::{range_regex, FindCommand, ReplaceCommand},
-        JoinLines,
+        search::{parse_replace_all, replace_command, FindCommand, ReplaceCommand},
+        yank::{copy_selections_content, yank_selections_content},
     },
+    object::Object,
+    pickers::{ListJumps, ListMarks, ListRegisters},
     state::Mode,
     Vim,
 };
@@ -20,7 +43,95 @@ pub struct GoToLine {
     pub line: u32,
 }
 
-impl_actions!(vim, [GoToLine]);
+/// An ex command run over a range of lines, like `:5,20d` or `:'<,'>normal A;`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExCommand {
+    pub range: Option<CommandRange>,
+    pub command: LineCommand,
+}
+
+/// The line range an ex command applies to, like `%`, `5,20` or `'<,'>`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CommandRange {
+    pub start: Address,
+    pub end: Option<Address>,
+}
+
+/// A line address in a range, with the offset that follows it (as in `.+3` or `/foo/-1`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Address {
+    pub position: Position,
+    pub offset: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Position {
+    /// A 1-based line number. `0` stands for the position before the first line.
+    Line(u32),
+    CurrentLine,
+    LastLine,
+    Mark(char),
+    /// The next (or previous) line matching the pattern, as in `/foo/` or `?foo?`.
+    Pattern {
+        query: String,
+        backwards: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum LineCommand {
+    Delete {
+        register: Option<char>,
+    },
+    Yank {
+        register: Option<char>,
+    },
+    Join,
+    Indent {
+        times: usize,
+    },
+    Outdent {
+        times: usize,
+    },
+    Move {
+        destination: Address,
+    },
+    Copy {
+        destination: Address,
+    },
+    Sort {
+        case_sensitive: bool,
+    },
+    /// The `s/pattern/replacement/flags` part of a `:s` command.
+    Substitute {
+        query: String,
+    },
+    /// Runs the keystrokes in normal mode, once for each line.
+    Normal {
+        keys: String,
+    },
+    /// Runs the command on each line that matches (or, when inverted, doesn't match) the pattern.
+    Global {
+        pattern: String,
+        invert: bool,
+        command: Box<ExCommand>,
+    },
+    /// Runs the shell command in the terminal panel, as in `:!make`.
+    Shell {
+        command: String,
+    },
+    /// Replaces the lines with the output of the shell command they are piped through.
+    Filter {
+        command: String,
+    },
+    /// Inserts the output of the shell command below the line, as in `:r !date`.
+    ReadShell {
+        command: String,
+    },
+}
+
+impl_actions!(vim, [GoToLine, ExCommand]);
+actions!(vim, [VisualCommand, VisualFilter]);
 
 pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     workspace.register_action(|_: &mut Workspace, action: &GoToLine, cx| {
@@ -29,18 +140,25 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
             move_cursor(vim, Motion::StartOfDocument, Some(action.line as usize), cx);
         });
     });
+    workspace.register_action(ex_command);
+    workspace.register_action(|workspace: &mut Workspace, _: &VisualCommand, cx| {
+        // Leaving visual mode sets the `'<` and `'>` marks the range refers to.
+        Vim::update(cx, |vim, cx| vim.switch_mode(Mode::Normal, false, cx));
+        CommandPalette::toggle(workspace, "'<,'>", cx);
+    });
+    workspace.register_action(|workspace: &mut Workspace, _: &VisualFilter, cx| {
+        Vim::update(cx, |vim, cx| vim.switch_mode(Mode::Normal, false, cx));
+        CommandPalette::toggle(workspace, "'<,'>!", cx);
+    });
 }
 
 pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
     // Note: this is a very poor simulation of vim's command palette.
-    // In the future we should adjust it to handle parsing range syntax,
-    // and then calling the appropriate commands with/without ranges.
+    // Commands that work on lines are parsed along with their range (see
+    // `ExCommand::parse`), the rest are matched by name below.
     //
     // We also need to support passing arguments to commands like :w
     // (ideally with filename autocompletion).
-    //
-    // For now, you can only do a replace on the % range, and you can
-    // only use a specific line number range to "go to line"
     while query.starts_with(':') {
         query = &query[1..];
     }
@@ -255,15 +373,6 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
             ("lNext", editor::actions::GoToPrevDiagnostic.boxed_clone())
         }
 
-        // modify the buffer (should accept [range])
-        "j" | "jo" | "joi" | "join" => ("join", JoinLines.boxed_clone()),
-        "d" | "de" | "del" | "dele" | "delet" | "delete" | "dl" | "dell" | "delel" | "deletl"
-        | "deletel" | "dp" | "dep" | "delp" | "delep" | "deletp" | "deletep" => {
-            ("delete", editor::actions::DeleteLine.boxed_clone())
-        }
-        "sor" | "sor " | "sort" | "sort " => ("sort", SortLinesCaseSensitive.boxed_clone()),
-        "sor i" | "sort i" => ("sort i", SortLinesCaseInsensitive.boxed_clone()),
-
         // Explore, etc.
         "E" | "Ex" | "Exp" | "Expl" | "Explo" | "Explor" | "Explore" => (
             "Explore",
@@ -315,13 +424,25 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
             cx.build_action("assistant::ToggleFocus", None).unwrap(),
         ),
 
+        // marks, registers and the jump list
+        "marks" => ("marks", ListMarks.boxed_clone()),
+        "reg" | "regi" | "regis" | "regist" | "registe" | "register" | "registers" => {
+            ("registers", ListRegisters.boxed_clone())
+        }
+        "di" | "dis" | "disp" | "displ" | "displa" | "display" => {
+            ("display", ListRegisters.boxed_clone())
+        }
+        "ju" | "jum" | "jump" | "jumps" => ("jumps", ListJumps.boxed_clone()),
+
         // goto (other ranges handled under _ => )
         "$" => ("$", EndOfDocument.boxed_clone()),
         "%" => ("%", EndOfDocument.boxed_clone()),
         "0" => ("0", StartOfDocument.boxed_clone()),
 
         _ => {
-            if query.starts_with('/') || query.starts_with('?') {
+            if let Some(command) = ExCommand::parse(query) {
+                (query, command.boxed_clone())
+            } else if query.starts_with('/') || query.starts_with('?') {
                 (
                     query,
                     FindCommand {
@@ -330,24 +451,8 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
                     }
                     .boxed_clone(),
                 )
-            } else if query.starts_with('%') {
-                (
-                    query,
-                    ReplaceCommand {
-                        query: query.to_string(),
-                    }
-                    .boxed_clone(),
-                )
             } else if let Ok(line) = query.parse::<u32>() {
                 (query, GoToLine { line }.boxed_clone())
-            } else if range_regex().is_match(query) {
-                (
-                    query,
-                    ReplaceCommand {
-                        query: query.to_string(),
-                    }
-                    .boxed_clone(),
-                )
             } else {
                 return None;
             }
@@ -386,13 +491,1119 @@ fn generate_positions(string: &str, query: &str) -> Vec<usize> {
     positions
 }
 
+impl ExCommand {
+    /// Parses an ex command that works on a range of lines, like `5,20d`, `'<,'>normal A;`
+    /// or `g/TODO/d`. Returns `None` for anything else.
+    pub fn parse(query: &str) -> Option<Self> {
+        let mut input = query;
+        let range = CommandRange::parse(&mut input)?;
+        let command = match LineCommand::parse(input)? {
+            // Without a range, `:!` runs the command rather than filtering lines through it.
+            LineCommand::Filter { command } if range.is_none() => LineCommand::Shell { command },
+            command => command,
+        };
+        Some(Self { range, command })
+    }
+
+    /// Whether running the command types keys into the editor, which can only be done
+    /// once the command palette has handed the focus back to it.
+    fn dispatches_keystrokes(&self) -> bool {
+        match &self.command {
+            LineCommand::Normal { .. } => true,
+            LineCommand::Global { command, .. } => command.dispatches_keystrokes(),
+            _ => false,
+        }
+    }
+}
+
+impl CommandRange {
+    fn buffer() -> Self {
+        Self {
+            start: Address::new(Position::Line(1)),
+            end: Some(Address::new(Position::LastLine)),
+        }
+    }
+
+    fn current_line() -> Self {
+        Self {
+            start: Address::new(Position::CurrentLine),
+            end: None,
+        }
+    }
+
+    /// Parses the range at the start of `input`, advancing past it. Returns `Some(None)` when
+    /// there is no range, and `None` when the range is malformed.
+    fn parse(input: &mut &str) -> Option<Option<Self>> {
+        if let Some(rest) = input.strip_prefix('%') {
+            *input = rest;
+            return Some(Some(Self::buffer()));
+        }
+        let start = Address::parse(input)?;
+        let Some(rest) = input.strip_prefix(',') else {
+            return Some(start.map(|start| Self { start, end: None }));
+        };
+        *input = rest;
+        let end = Address::parse(input)?;
+        Some(Some(Self {
+            start: start.unwrap_or_else(|| Address::new(Position::CurrentLine)),
+            end: Some(end.unwrap_or_else(|| Address::new(Position::CurrentLine))),
+        }))
+    }
+
+    /// The 0-based rows of the range.
+    fn rows(
+        &self,
+        vim: &Vim,
+        snapshot: &MultiBufferSnapshot,
+        current_row: u32,
+    ) -> Result<RangeInclusive<u32>> {
+        let start = self.start.line_number(vim, snapshot, current_row)?;
+        let end = match &self.end {
+            Some(end) => end.line_number(vim, snapshot, current_row)?,
+            None => start,
+        };
+        let (start, end) = if start <= end {
+            (start, end)
+        } else {
+            (end, start)
+        };
+        Ok(start.saturating_sub(1)..=end.saturating_sub(1))
+    }
+}
+
+impl Address {
+    fn new(position: Position) -> Self {
+        Self {
+            position,
+            offset: 0,
+        }
+    }
+
+    /// Parses the address at the start of `input`, advancing past it. Returns `Some(None)` when
+    /// there is no address, and `None` when the address is malformed.
+    fn parse(input: &mut &str) -> Option<Option<Self>> {
+        let mut chars = input.chars();
+        let position = match chars.next() {
+            Some('.') => {
+                *input = chars.as_str();
+                Some(Position::CurrentLine)
+            }
+            Some('$') => {
+                *input = chars.as_str();
+                Some(Position::LastLine)
+            }
+            Some('\'' | '`') => {
+                let mark = chars.next()?;
+                *input = chars.as_str();
+                Some(Position::Mark(mark))
+            }
+            Some(delimiter @ ('/' | '?')) => {
+                let (query, rest) = split_at_delimiter(chars.as_str(), delimiter);
+                *input = rest.unwrap_or("");
+                Some(Position::Pattern {
+                    query,
+                    backwards: delimiter == '?',
+                })
+            }
+            Some(c) if c.is_ascii_digit() => Some(Position::Line(parse_number(input)?)),
+            _ => None,
+        };
+
+        let mut offset = None;
+        loop {
+            let sign = match input.chars().next() {
+                Some('+') => 1,
+                Some('-') => -1,
+                _ => break,
+            };
+            *input = &input[1..];
+            let amount = if input.starts_with(|c: char| c.is_ascii_digit()) {
+                i32::try_from(parse_number(input)?).ok()?
+            } else {
+                1
+            };
+            offset = Some(offset.unwrap_or(0) + sign * amount);
+        }
+
+        if position.is_none() && offset.is_none() {
+            return Some(None);
+        }
+        Some(Some(Self {
+            position: position.unwrap_or(Position::CurrentLine),
+            offset: offset.unwrap_or(0),
+        }))
+    }
+
+    /// Resolves the address to a 1-based line number, where `0` stands for the position
+    /// before the first line.
+    fn line_number(
+        &self,
+        vim: &Vim,
+        snapshot: &MultiBufferSnapshot,
+        current_row: u32,
+    ) -> Result<u32> {
+        let last_line = snapshot.max_point().row + 1;
+        let line = match &self.position {
+            Position::Line(line) => *line,
+            Position::CurrentLine => current_row + 1,
+            Position::LastLine => last_line,
+            Position::Mark(name) => {
+                let anchor = vim
+                    .state()
+                    .marks
+                    .get(&name.to_string())
+                    .and_then(|anchors| anchors.first())
+                    .ok_or_else(|| anyhow!("E20: Mark not set"))?;
+                anchor.to_point(snapshot).row + 1
+            }
+            Position::Pattern { query, backwards } => {
+                find_row(snapshot, query, *backwards, current_row)? + 1
+            }
+        };
+        let line = line as i64 + self.offset as i64;
+        if line < 0 || line > last_line as i64 {
+            return Err(anyhow!("E16: Invalid range"));
+        }
+        Ok(line as u32)
+    }
+}
+
+impl LineCommand {
+    /// Parses the command following the range of an ex command.
+    fn parse(input: &str) -> Option<Self> {
+        let input = input.trim_start();
+        if let Some(command) = input.strip_prefix('!') {
+            return Some(Self::Filter {
+                command: parse_shell_command(command)?,
+            });
+        }
+        if let Some(shift @ ('>' | '<')) = input.chars().next() {
+            let times = input.chars().take_while(|c| *c == shift).count();
+            if !input[times..].trim().is_empty() {
+                return None;
+            }
+            return Some(if shift == '>' {
+                Self::Indent { times }
+            } else {
+                Self::Outdent { times }
+            });
+        }
+
+        let name_len = input
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(input.len());
+        let (name, args) = input.split_at(name_len);
+        let (bang, args) = match args.strip_prefix('!') {
+            Some(args) => (true, args),
+            None => (false, args),
+        };
+
+        let command = match name {
+            "d" | "de" | "del" | "dele" | "delet" | "delete" | "dl" | "dell" | "delel"
+            | "deletl" | "deletel" | "dp" | "dep" | "delp" | "delep" | "deletp" | "deletep" => {
+                Self::Delete {
+                    register: parse_register(args)?,
+                }
+            }
+            "y" | "ya" | "yan" | "yank" => Self::Yank {
+                register: parse_register(args)?,
+            },
+            "j" | "jo" | "joi" | "join" if args.trim().is_empty() => Self::Join,
+            "m" | "mo" | "mov" | "move" => Self::Move {
+                destination: parse_destination(args)?,
+            },
+            "t" | "co" | "cop" | "copy" => Self::Copy {
+                destination: parse_destination(args)?,
+            },
+            "sor" | "sort" => match args.trim() {
+                "" => Self::Sort {
+                    case_sensitive: true,
+                },
+                "i" => Self::Sort {
+                    case_sensitive: false,
+                },
+                _ => return None,
+            },
+            "s" | "su" | "sub" | "subs" | "subst" | "substi" | "substit" | "substitu"
+            | "substitut" | "substitute" => {
+                let delimiter = args.chars().next()?;
+                if delimiter.is_alphanumeric() || delimiter.is_whitespace() || delimiter == '\\' {
+                    return None;
+                }
+                Self::Substitute {
+                    query: format!("s{args}"),
+                }
+            }
+            "norm" | "norma" | "normal" => {
+                let keys = args.trim_start();
+                if keys.is_empty() {
+                    return None;
+                }
+                return Some(Self::Normal {
+                    keys: keys.to_string(),
+                });
+            }
+            "r" | "re" | "rea" | "read" => {
+                // `:r!cmd` is the same as `:r !cmd`.
+                let command = if bang {
+                    args
+                } else {
+                    args.trim_start().strip_prefix('!')?
+                };
+                return Some(Self::ReadShell {
+                    command: parse_shell_command(command)?,
+                });
+            }
+            "g" | "gl" | "glo" | "glob" | "globa" | "global" => return parse_global(args, bang),
+            "v" | "vg" | "vgl" | "vglo" | "vglob" | "vgloba" | "vglobal" if !bang => {
+                return parse_global(args, true)
+            }
+            _ => return None,
+        };
+        (!bang).then_some(command)
+    }
+}
+
+fn parse_number(input: &mut &str) -> Option<u32> {
+    let len = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (digits, rest) = input.split_at(len);
+    *input = rest;
+    digits.parse().ok()
+}
+
+fn parse_register(args: &str) -> Option<Option<char>> {
+    let mut chars = args.trim().chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => Some(None),
+        (Some(register), None)
+            if register.is_ascii_alphabetic()
+                || matches!(register, '"' | '+' | '*' | '_' | '-') =>
+        {
+            Some(Some(register))
+        }
+        _ => None,
+    }
+}
+
+fn parse_shell_command(args: &str) -> Option<String> {
+    let command = args.trim();
+    (!command.is_empty()).then(|| command.to_string())
+}
+
+fn parse_destination(args: &str) -> Option<Address> {
+    let mut input = args.trim_start();
+    let destination = Address::parse(&mut input)??;
+    input.trim().is_empty().then_some(destination)
+}
+
+fn parse_global(args: &str, invert: bool) -> Option<LineCommand> {
+    let mut chars = args.chars();
+    let delimiter = chars.next()?;
+    if delimiter.is_alphanumeric() || delimiter.is_whitespace() || matches!(delimiter, '\\' | '"') {
+        return None;
+    }
+    let (pattern, rest) = split_at_delimiter(chars.as_str(), delimiter);
+    let command = ExCommand::parse(rest?)?;
+    // Shell commands run in the background, so they can't be run line by line.
+    if matches!(
+        command.command,
+        LineCommand::Global { .. }
+            | LineCommand::Shell { .. }
+            | LineCommand::Filter { .. }
+            | LineCommand::ReadShell { .. }
+    ) {
+        return None;
+    }
+    Some(LineCommand::Global {
+        pattern,
+        invert,
+        command: Box::new(command),
+    })
+}
+
+/// Splits a pattern from the text following its closing delimiter, if there is one.
+fn split_at_delimiter(input: &str, delimiter: char) -> (String, Option<&str>) {
+    let mut pattern = String::new();
+    let mut escaped = false;
+    for (ix, c) in input.char_indices() {
+        if escaped {
+            escaped = false;
+            if c != delimiter {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == delimiter {
+            return (pattern, Some(&input[ix + c.len_utf8()..]));
+        } else {
+            pattern.push(c);
+        }
+    }
+    if escaped {
+        pattern.push('\\');
+    }
+    (pattern, None)
+}
+
+// converts a vim pattern into a regex. As with `:s`, we don't attempt to fully convert
+// between the two syntaxes, but we do flip the meaning of escaped and unescaped grouping
+// characters so that common idioms work.
+fn vim_regex(pattern: &str) -> Result<Regex> {
+    if pattern.is_empty() {
+        return Err(anyhow!("E35: No previous regular expression"));
+    }
+    let mut regex = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c @ ('(' | ')' | '|' | '{' | '}' | '+' | '?')) => regex.push(c),
+                Some('<' | '>') => regex.push_str("\\b"),
+                Some(c) => {
+                    regex.push('\\');
+                    regex.push(c);
+                }
+                None => regex.push_str("\\\\"),
+            },
+            '(' | ')' | '|' | '{' | '}' | '+' | '?' => {
+                regex.push('\\');
+                regex.push(c);
+            }
+            c => regex.push(c),
+        }
+    }
+    Ok(Regex::new(&regex)?)
+}
+
+fn line_text(snapshot: &MultiBufferSnapshot, row: u32) -> String {
+    let end = Point::new(row, snapshot.line_len(MultiBufferRow(row)));
+    snapshot.text_for_range(Point::new(row, 0)..end).collect()
+}
+
+/// Finds the row of the next line matching the pattern, wrapping around the end of the buffer.
+fn find_row(
+    snapshot: &MultiBufferSnapshot,
+    query: &str,
+    backwards: bool,
+    current_row: u32,
+) -> Result<u32> {
+    let regex = vim_regex(query)?;
+    let max_row = snapshot.max_point().row;
+    let rows = if backwards {
+        (0..current_row)
+            .rev()
+            .chain((current_row..=max_row).rev())
+            .collect::<Vec<_>>()
+    } else {
+        (current_row + 1..=max_row)
+            .chain(0..=current_row)
+            .collect::<Vec<_>>()
+    };
+    rows.into_iter()
+        .find(|row| regex.is_match(&line_text(snapshot, *row)))
+        .ok_or_else(|| anyhow!("E486: Pattern not found: {query}"))
+}
+
+fn ex_command(workspace: &mut Workspace, action: &ExCommand, cx: &mut ViewContext<Workspace>) {
+    match &action.command {
+        LineCommand::Shell { command } => {
+            run_in_terminal(workspace, command, cx);
+            return;
+        }
+        LineCommand::Filter { .. } | LineCommand::ReadShell { .. } => {
+            if let Err(error) = run_shell_filter(workspace, action, cx) {
+                workspace.show_error(&error, cx);
+            }
+            return;
+        }
+        _ => {}
+    }
+    if let LineCommand::Substitute { query } = &action.command {
+        let rows = with_active_editor(cx, |vim, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let current_row = editor.selections.newest::<Point>(cx).head().row;
+            action
+                .range
+                .clone()
+                .unwrap_or_else(CommandRange::current_line)
+                .rows(vim, &snapshot, current_row)
+        });
+        match rows {
+            Ok(rows) => {
+                let query = format!("{},{}{}", rows.start() + 1, rows.end() + 1, query);
+                replace_command(workspace, &ReplaceCommand { query }, cx);
+            }
+            Err(error) => workspace.show_error(&error, cx),
+        }
+        return;
+    }
+
+    if action.dispatches_keystrokes() {
+        let action = action.clone();
+        let workspace = cx.view().downgrade();
+        cx.window_context()
+            .spawn(|mut cx| async move {
+                let result = cx.update(|cx| {
+                    // The input handler of the editor is only registered once it's drawn focused.
+                    cx.draw();
+                    run_ex_command(&action, cx)
+                })?;
+                if let Err(error) = result {
+                    workspace.update(&mut cx, |workspace, cx| workspace.show_error(&error, cx))?;
+                }
+                anyhow::Ok(())
+            })
+            .detach_and_log_err(cx);
+    } else if let Err(error) = run_ex_command(action, cx) {
+        workspace.show_error(&error, cx);
+    }
+}
+
+/// Runs the command as a single transaction, so that it can be undone in one go.
+fn run_ex_command(action: &ExCommand, cx: &mut WindowContext) -> Result<()> {
+    let Some(editor) = Vim::read(cx)
+        .active_editor
+        .as_ref()
+        .and_then(|editor| editor.upgrade())
+    else {
+        return Ok(());
+    };
+    Vim::update(cx, |vim, cx| vim.switch_mode(Mode::Normal, false, cx));
+    editor.update(cx, |editor, cx| {
+        editor.start_transaction_at(Instant::now(), cx)
+    });
+    let result = run_line_command(action.range.as_ref(), &action.command, cx);
+    editor.update(cx, |editor, cx| {
+        editor.end_transaction_at(Instant::now(), cx)
+    });
+    result
+}
+
+fn run_line_command(
+    range: Option<&CommandRange>,
+    command: &LineCommand,
+    cx: &mut WindowContext,
+) -> Result<()> {
+    match command {
+        LineCommand::Normal { keys } => {
+            let lines = with_active_editor(cx, |vim, editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let current_row = editor.selections.newest::<Point>(cx).head().row;
+                let rows = range
+                    .cloned()
+                    .unwrap_or_else(CommandRange::current_line)
+                    .rows(vim, &snapshot, current_row)?;
+                Ok(rows
+                    .map(|row| snapshot.anchor_before(Point::new(row, 0)))
+                    .collect::<Vec<_>>())
+            })?;
+            let keystrokes = keystrokes_for(keys);
+            for line in lines {
+                move_to_line(line, cx)?;
+                for keystroke in &keystrokes {
+                    let focused = cx.focused();
+                    cx.dispatch_keystroke(keystroke.clone());
+                    if cx.focused() != focused {
+                        cx.draw();
+                    }
+                }
+                // Incomplete commands are aborted, as if followed by escape.
+                let state = Vim::read(cx).state();
+                if state.mode != Mode::Normal || !state.operator_stack.is_empty() {
+                    cx.dispatch_keystroke(Keystroke::parse("escape")?);
+                }
+            }
+            Ok(())
+        }
+        LineCommand::Global {
+            pattern,
+            invert,
+            command,
+        } => {
+            // Actions bound in keymaps aren't parsed, so they can nest anything.
+            match &command.command {
+                LineCommand::Global { .. } => {
+                    return Err(anyhow!("E147: Cannot do :global recursive"))
+                }
+                LineCommand::Shell { .. }
+                | LineCommand::Filter { .. }
+                | LineCommand::ReadShell { .. } => {
+                    return Err(anyhow!("Shell commands can't be run by :global"))
+                }
+                _ => {}
+            }
+            let lines = with_active_editor(cx, |vim, editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let current_row = editor.selections.newest::<Point>(cx).head().row;
+                let rows = range.cloned().unwrap_or_else(CommandRange::buffer).rows(
+                    vim,
+                    &snapshot,
+                    current_row,
+                )?;
+                let regex = vim_regex(pattern)?;
+                Ok(rows
+                    .filter(|row| regex.is_match(&line_text(&snapshot, *row)) != *invert)
+                    .map(|row| MarkedLine::new(&snapshot, row))
+                    .collect::<Vec<_>>())
+            })?;
+            for line in lines {
+                let marked = with_active_editor(cx, |_, editor, cx| {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let Some(row) = line.row(&snapshot) else {
+                        return Ok(false);
+                    };
+                    select_point(editor, Point::new(row, 0), cx);
+                    Ok(true)
+                })?;
+                if marked {
+                    run_line_command(command.range.as_ref(), &command.command, cx)?;
+                }
+            }
+            Ok(())
+        }
+        command => with_active_editor(cx, |vim, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let current_row = editor.selections.newest::<Point>(cx).head().row;
+            let default_range = match command {
+                LineCommand::Sort { .. } => CommandRange::buffer(),
+                _ => CommandRange::current_line(),
+            };
+            let rows = range
+                .cloned()
+                .unwrap_or(default_range)
+                .rows(vim, &snapshot, current_row)?;
+            match command {
+                LineCommand::Delete { register } => delete_rows(vim, editor, rows, *register, cx),
+                LineCommand::Yank { register } => yank_rows(vim, editor, rows, *register, cx),
+                LineCommand::Join => join_rows(editor, rows, cx),
+                LineCommand::Indent { times } => indent_rows(editor, rows, *times, true, cx),
+                LineCommand::Outdent { times } => indent_rows(editor, rows, *times, false, cx),
+                LineCommand::Sort { case_sensitive } => {
+                    sort_rows(editor, rows, *case_sensitive, cx)
+                }
+                LineCommand::Move { destination } => {
+                    let destination = destination.line_number(vim, &snapshot, current_row)?;
+                    move_rows(editor, rows, destination, cx)?
+                }
+                LineCommand::Copy { destination } => {
+                    let destination = destination.line_number(vim, &snapshot, current_row)?;
+                    copy_rows(editor, rows, destination, cx)
+                }
+                // Only `:g` gets here: `:s` on its own goes through the search bar.
+                LineCommand::Substitute { query } => substitute_rows(editor, rows, query, cx)?,
+                LineCommand::Normal { .. }
+                | LineCommand::Global { .. }
+                | LineCommand::Shell { .. }
+                | LineCommand::Filter { .. }
+                | LineCommand::ReadShell { .. } => {
+                    return Err(anyhow!("E492: Not an editor command"));
+                }
+            }
+            Ok(())
+        }),
+    }
+}
+
+/// Runs `:s` on each of the rows at once, rather than through the search bar, for `:g`.
+fn substitute_rows(
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    query: &str,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let replacement = parse_replace_all(&format!("%{query}"));
+    if replacement.search.is_empty() {
+        return Err(anyhow!("E35: No previous regular expression"));
+    }
+    if !replacement.should_replace_all {
+        return Err(anyhow!("The c and n flags of :s can't be used in :global"));
+    }
+    let regex = RegexBuilder::new(&replacement.search)
+        .case_insensitive(!replacement.is_case_sensitive)
+        .build()?;
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let mut edits = Vec::new();
+    for row in rows {
+        let text = line_text(&snapshot, row);
+        // Lines without a match are left alone, as `:g` doesn't report them.
+        if let Cow::Owned(new_text) = regex.replace_all(&text, replacement.replacement.as_str()) {
+            let end = Point::new(row, snapshot.line_len(MultiBufferRow(row)));
+            edits.push((Point::new(row, 0)..end, new_text));
+        }
+    }
+    let Some(last_row) = edits.last().map(|(range, _)| range.start.row) else {
+        return Ok(());
+    };
+    editor.edit(edits, cx);
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    select_point(editor, first_non_whitespace(&snapshot, last_row), cx);
+    Ok(())
+}
+
+/// A line matched by `:g`, which is skipped once a command run on an earlier line has
+/// deleted it, as vim does.
+struct MarkedLine {
+    /// The first character of the line.
+    start: Anchor,
+    /// The newline ending the line above, which is gone once the line is joined to it.
+    newline_above: Option<Anchor>,
+}
+
+impl MarkedLine {
+    fn new(snapshot: &MultiBufferSnapshot, row: u32) -> Self {
+        Self {
+            start: snapshot.anchor_after(Point::new(row, 0)),
+            newline_above: (row > 0).then(|| snapshot.anchor_before(Point::new(row, 0))),
+        }
+    }
+
+    /// The row the line is on now, or `None` when it was deleted.
+    fn row(&self, snapshot: &MultiBufferSnapshot) -> Option<u32> {
+        if !self.start.is_valid(snapshot) {
+            return None;
+        }
+        let start = self.start.to_point(snapshot);
+        let joined = start.column > 0
+            && self
+                .newline_above
+                .as_ref()
+                .is_some_and(|newline| !newline.is_valid(snapshot));
+        (!joined).then_some(start.row)
+    }
+}
+
+fn run_in_terminal(workspace: &mut Workspace, command: &str, cx: &mut ViewContext<Workspace>) {
+    let task = TaskTemplate {
+        label: format!("!{command}"),
+        command: command.to_string(),
+        ..TaskTemplate::default()
+    };
+    let task_cx = TaskContext {
+        cwd: shell_working_directory(workspace, cx),
+        ..TaskContext::default()
+    };
+    schedule_task(
+        workspace,
+        TaskSourceKind::UserInput,
+        &task,
+        &task_cx,
+        false,
+        cx,
+    );
+}
+
+/// Where shell commands run: the root of the worktree of the active item, or of the first
+/// worktree when the active item has none.
+fn shell_working_directory(workspace: &Workspace, cx: &AppContext) -> Option<PathBuf> {
+    let project = workspace.project().read(cx);
+    let worktree = workspace
+        .active_item(cx)
+        .and_then(|item| item.project_path(cx))
+        .and_then(|path| project.worktree_for_id(path.worktree_id, cx))
+        .or_else(|| project.visible_worktrees(cx).next())?;
+    let worktree = worktree.read(cx);
+    let mut path = worktree.abs_path().to_path_buf();
+    if worktree.root_entry()?.is_file() {
+        path.pop();
+    }
+    Some(path)
+}
+
+/// Where the output of a shell command goes.
+enum ShellOutput {
+    /// Replaces the lines that were piped through the command.
+    Lines(Range<Anchor>),
+    /// Goes below the line, 0 being the top of the buffer.
+    BelowLine(u32),
+}
+
+/// Runs `:{range}!cmd` and `:r !cmd`, editing the buffer once the command is done, in a
+/// single transaction.
+fn run_shell_filter(
+    workspace: &mut Workspace,
+    action: &ExCommand,
+    cx: &mut ViewContext<Workspace>,
+) -> Result<()> {
+    let (command, filter) = match &action.command {
+        LineCommand::Filter { command } => (command.clone(), true),
+        LineCommand::ReadShell { command } => (command.clone(), false),
+        _ => return Ok(()),
+    };
+    Vim::update(cx, |vim, cx| vim.switch_mode(Mode::Normal, false, cx));
+    let (editor, input, output) = with_active_editor(cx, |vim, editor, cx| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let current_row = editor.selections.newest::<Point>(cx).head().row;
+        let range = action
+            .range
+            .clone()
+            .unwrap_or_else(CommandRange::current_line);
+        let editor = cx.view().clone();
+        if filter {
+            let rows = range.rows(vim, &snapshot, current_row)?;
+            let end = Point::new(*rows.end(), snapshot.line_len(MultiBufferRow(*rows.end())));
+            let lines =
+                snapshot.anchor_before(Point::new(*rows.start(), 0))..snapshot.anchor_after(end);
+            let input = format!("{}\n", lines_text(&snapshot, &rows));
+            Ok((editor, Some(input), ShellOutput::Lines(lines)))
+        } else {
+            let address = range.end.as_ref().unwrap_or(&range.start);
+            let line = address.line_number(vim, &snapshot, current_row)?;
+            Ok((editor, None, ShellOutput::BelowLine(line)))
+        }
+    })?;
+
+    let working_directory = shell_working_directory(workspace, cx);
+    let output_task = workspace.project().update(cx, |project, cx| {
+        project.run_shell_command(command, input, working_directory, cx)
+    });
+    cx.spawn(|workspace, mut cx| async move {
+        let text = match output_task.await {
+            Ok(text) => text,
+            Err(error) => {
+                return workspace.update(&mut cx, |workspace, cx| workspace.show_error(&error, cx));
+            }
+        };
+        let text = text.strip_suffix('\n').unwrap_or(&text);
+        editor.update(&mut cx, |editor, cx| {
+            editor.transact(cx, |editor, cx| {
+                insert_shell_output(editor, output, text, cx)
+            });
+        })
+    })
+    .detach_and_log_err(cx);
+    Ok(())
+}
+
+fn insert_shell_output(
+    editor: &mut Editor,
+    output: ShellOutput,
+    text: &str,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let first_row = match output {
+        ShellOutput::Lines(lines) => {
+            let lines = lines.start.to_point(&snapshot)..lines.end.to_point(&snapshot);
+            if text.is_empty() {
+                // Lines filtered out entirely are deleted, rather than left empty.
+                select_lines(editor, &(lines.start.row..=lines.end.row), cx);
+                editor.insert("", cx);
+            } else {
+                editor.edit([(lines.clone(), text)], cx);
+            }
+            lines.start.row
+        }
+        ShellOutput::BelowLine(line) => {
+            let line = line.min(snapshot.max_point().row + 1);
+            let (insertion_point, text) = insertion_below_line(&snapshot, line, text);
+            editor.edit([(insertion_point..insertion_point, text)], cx);
+            line
+        }
+    };
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let row = first_row.min(snapshot.max_point().row);
+    select_point(editor, first_non_whitespace(&snapshot, row), cx);
+}
+
+/// Runs `!{motion}`, which opens the command palette on `:{range}!` for the lines the motion
+/// spans, to type the command they are filtered through.
+pub(crate) fn filter_motion(
+    vim: &mut Vim,
+    motion: Motion,
+    times: Option<usize>,
+    cx: &mut WindowContext,
+) {
+    vim.stop_recording();
+    let prompt = vim.update_active_editor(cx, |_, editor, cx| {
+        let text_layout_details = editor.text_layout_details(cx);
+        editor.change_selections(None, cx, |s| {
+            s.move_with(|map, selection| {
+                motion.expand_selection(map, selection, times, false, &text_layout_details);
+            });
+        });
+        filter_prompt(editor, cx)
+    });
+    open_filter_prompt(prompt.flatten(), cx);
+}
+
+/// Runs `!{object}`, like [`filter_motion`].
+pub(crate) fn filter_object(vim: &mut Vim, object: Object, around: bool, cx: &mut WindowContext) {
+    vim.stop_recording();
+    let prompt = vim.update_active_editor(cx, |_, editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.move_with(|map, selection| {
+                object.expand_selection(map, selection, around);
+            });
+        });
+        filter_prompt(editor, cx)
+    });
+    open_filter_prompt(prompt.flatten(), cx);
+}
+
+/// Moves the cursor to the first of the selected lines, and returns the range that refers
+/// to them from there, as in `.,.+2!`.
+fn filter_prompt(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<(View<Workspace>, String)> {
+    let selection = editor.selections.newest::<Point>(cx);
+    let (start, mut end) = (selection.start, selection.end);
+    // Linewise motions end at the start of the line after the last one they span.
+    if end.column == 0 && end.row > start.row {
+        end.row -= 1;
+    }
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    select_point(editor, first_non_whitespace(&snapshot, start.row), cx);
+    let prompt = if end.row == start.row {
+        ".!".to_string()
+    } else {
+        format!(".,.+{}!", end.row - start.row)
+    };
+    Some((editor.workspace()?, prompt))
+}
+
+fn open_filter_prompt(prompt: Option<(View<Workspace>, String)>, cx: &mut WindowContext) {
+    let Some((workspace, prompt)) = prompt else {
+        return;
+    };
+    // The palette is opened once the operator is done, so that it takes the focus from an
+    // editor that's no longer being updated.
+    cx.defer(move |cx| {
+        workspace.update(cx, |workspace, cx| {
+            CommandPalette::toggle(workspace, &prompt, cx)
+        })
+    });
+}
+
+fn with_active_editor<T>(
+    cx: &mut WindowContext,
+    update: impl FnOnce(&mut Vim, &mut Editor, &mut ViewContext<Editor>) -> Result<T>,
+) -> Result<T> {
+    Vim::update(cx, |vim, cx| vim.update_active_editor(cx, update))
+        .ok_or_else(|| anyhow!("No active editor"))?
+}
+
+fn move_to_line(line: Anchor, cx: &mut WindowContext) -> Result<()> {
+    with_active_editor(cx, |_, editor, cx| {
+        editor.change_selections(None, cx, |s| s.select_anchor_ranges([line..line]));
+        Ok(())
+    })
+}
+
+/// Converts the argument of `:normal` into keystrokes, typing each character as is.
+fn keystrokes_for(keys: &str) -> Vec<Keystroke> {
+    keys.chars()
+        .map(|c| Keystroke {
+            modifiers: Modifiers {
+                shift: c.is_uppercase(),
+                ..Default::default()
+            },
+            key: match c {
+                ' ' => "space".to_string(),
+                c => c.to_lowercase().to_string(),
+            },
+            ime_key: Some(c.to_string()),
+        })
+        .collect()
+}
+
+fn select_point(editor: &mut Editor, point: Point, cx: &mut ViewContext<Editor>) {
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_ranges([point..point])
+    });
+}
+
+fn first_non_whitespace(snapshot: &MultiBufferSnapshot, row: u32) -> Point {
+    Point::new(row, snapshot.indent_size_for_line(MultiBufferRow(row)).len)
+}
+
+/// Selects whole lines the way a linewise delete expects: including the newline that
+/// follows them, or the one that precedes them on the last line.
+fn select_lines(editor: &mut Editor, rows: &RangeInclusive<u32>, cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let (start, end) = (*rows.start(), *rows.end());
+    let range = if end < snapshot.max_point().row {
+        Point::new(start, 0)..Point::new(end + 1, 0)
+    } else if start > 0 {
+        Point::new(start - 1, snapshot.line_len(MultiBufferRow(start - 1)))..snapshot.max_point()
+    } else {
+        Point::zero()..snapshot.max_point()
+    };
+    editor.change_selections(None, cx, |s| s.select_ranges([range]));
+}
+
+fn delete_rows(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    register: Option<char>,
+    cx: &mut ViewContext<Editor>,
+) {
+    editor.transact(cx, |editor, cx| {
+        select_lines(editor, &rows, cx);
+        vim.update_state(|state| state.selected_register = register);
+        copy_selections_content(vim, editor, true, cx);
+        editor.insert("", cx);
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let row = (*rows.start()).min(snapshot.max_point().row);
+        select_point(editor, first_non_whitespace(&snapshot, row), cx);
+    });
+}
+
+fn yank_rows(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    register: Option<char>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let cursor = editor.selections.newest::<Point>(cx).head();
+    select_lines(editor, &rows, cx);
+    vim.update_state(|state| state.selected_register = register);
+    yank_selections_content(vim, editor, true, cx);
+    editor.change_selections(None, cx, |s| s.select_ranges([cursor..cursor]));
+}
+
+fn join_rows(editor: &mut Editor, rows: RangeInclusive<u32>, cx: &mut ViewContext<Editor>) {
+    let (start, end) = (*rows.start(), *rows.end());
+    if end == editor.buffer().read(cx).snapshot(cx).max_point().row && start == end {
+        return;
+    }
+    // A single line is joined with the next one.
+    editor.change_selections(None, cx, |s| {
+        s.select_ranges([Point::new(start, 0)..Point::new(end, 0)])
+    });
+    editor.join_lines(&Default::default(), cx);
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    select_point(editor, first_non_whitespace(&snapshot, start), cx);
+}
+
+fn indent_rows(
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    times: usize,
+    indent: bool,
+    cx: &mut ViewContext<Editor>,
+) {
+    let (start, end) = (*rows.start(), *rows.end());
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let end_of_range = Point::new(end, snapshot.line_len(MultiBufferRow(end)));
+    editor.change_selections(None, cx, |s| {
+        s.select_ranges([Point::new(start, 0)..end_of_range])
+    });
+    for _ in 0..times {
+        if indent {
+            editor.indent(&Default::default(), cx);
+        } else {
+            editor.outdent(&Default::default(), cx);
+        }
+    }
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    select_point(editor, first_non_whitespace(&snapshot, end), cx);
+}
+
+fn sort_rows(
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    case_sensitive: bool,
+    cx: &mut ViewContext<Editor>,
+) {
+    let (start, end) = (*rows.start(), *rows.end());
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let end_of_range = Point::new(end, snapshot.line_len(MultiBufferRow(end)));
+    editor.change_selections(None, cx, |s| {
+        s.select_ranges([Point::new(start, 0)..end_of_range])
+    });
+    if case_sensitive {
+        editor.sort_lines_case_sensitive(&SortLinesCaseSensitive, cx);
+    } else {
+        editor.sort_lines_case_insensitive(&SortLinesCaseInsensitive, cx);
+    }
+    select_point(editor, Point::new(start, 0), cx);
+}
+
+/// Returns where to insert the lines so that they end up below `line` (1-based, 0 being the
+/// top of the buffer), and the text to insert there.
+fn insertion_below_line(
+    snapshot: &MultiBufferSnapshot,
+    line: u32,
+    lines: &str,
+) -> (Anchor, String) {
+    if line == 0 {
+        (snapshot.anchor_before(Point::zero()), format!("{lines}\n"))
+    } else {
+        let row = line - 1;
+        let end_of_line = Point::new(row, snapshot.line_len(MultiBufferRow(row)));
+        (snapshot.anchor_after(end_of_line), format!("\n{lines}"))
+    }
+}
+
+fn lines_text(snapshot: &MultiBufferSnapshot, rows: &RangeInclusive<u32>) -> String {
+    let end = Point::new(*rows.end(), snapshot.line_len(MultiBufferRow(*rows.end())));
+    snapshot
+        .text_for_range(Point::new(*rows.start(), 0)..end)
+        .collect()
+}
+
+fn move_rows(
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    destination: u32,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let (first_line, last_line) = (rows.start() + 1, rows.end() + 1);
+    if destination >= first_line && destination < last_line {
+        return Err(anyhow!("E134: Cannot move a range of lines into itself"));
+    }
+    let line_count = last_line - first_line + 1;
+    let new_last_line = if destination < first_line {
+        destination + line_count
+    } else {
+        destination
+    };
+    if destination != last_line && destination + 1 != first_line {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let lines = lines_text(&snapshot, &rows);
+        let (insertion_point, text) = insertion_below_line(&snapshot, destination, &lines);
+        select_lines(editor, &rows, cx);
+        editor.insert("", cx);
+        editor.edit([(insertion_point..insertion_point, text)], cx);
+    }
+    select_point(editor, Point::new(new_last_line - 1, 0), cx);
+    Ok(())
+}
+
+fn copy_rows(
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    destination: u32,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let lines = lines_text(&snapshot, &rows);
+    let (insertion_point, text) = insertion_below_line(&snapshot, destination, &lines);
+    editor.edit([(insertion_point..insertion_point, text)], cx);
+    let line_count = rows.end() - rows.start() + 1;
+    select_point(editor, Point::new(destination + line_count - 1, 0), cx);
+}
+
 #[cfg(test)]
 mod test {
     use std::path::Path;
 
-    use crate::test::{NeovimBackedTestContext, VimTestContext};
+    use crate::{
+        command::{Address, CommandRange, ExCommand, LineCommand, Position},
+        state::Mode,
+        test::{NeovimBackedTestContext, VimTestContext},
+    };
+    use command_palette::CommandPalette;
     use gpui::TestAppContext;
     use indoc::indoc;
+    use project::Fs;
+    use settings::SettingsStore;
 
     #[gpui::test]
     async fn test_command_basics(cx: &mut TestAppContext) {
@@ -516,4 +1727,452 @@ mod test {
         cx.simulate_keystrokes(": q a enter");
         cx.workspace(|workspace, cx| assert_eq!(workspace.items(cx).count(), 0));
     }
+
+    #[test]
+    fn test_parse_ex_command() {
+        let address = |position, offset| Address { position, offset };
+        assert_eq!(
+            ExCommand::parse("5,20d"),
+            Some(ExCommand {
+                range: Some(CommandRange {
+                    start: address(Position::Line(5), 0),
+                    end: Some(address(Position::Line(20), 0)),
+                }),
+                command: LineCommand::Delete { register: None },
+            })
+        );
+        assert_eq!(
+            ExCommand::parse("'<,'>normal A;"),
+            Some(ExCommand {
+                range: Some(CommandRange {
+                    start: address(Position::Mark('<'), 0),
+                    end: Some(address(Position::Mark('>'), 0)),
+                }),
+                command: LineCommand::Normal { keys: "A;".into() },
+            })
+        );
+        assert_eq!(
+            ExCommand::parse(r"/a\/b/+2,$-1 y x"),
+            Some(ExCommand {
+                range: Some(CommandRange {
+                    start: address(
+                        Position::Pattern {
+                            query: "a/b".into(),
+                            backwards: false,
+                        },
+                        2
+                    ),
+                    end: Some(address(Position::LastLine, -1)),
+                }),
+                command: LineCommand::Yank {
+                    register: Some('x')
+                },
+            })
+        );
+        assert_eq!(
+            ExCommand::parse("g!/TODO/-m0"),
+            Some(ExCommand {
+                range: None,
+                command: LineCommand::Global {
+                    pattern: "TODO".into(),
+                    invert: true,
+                    command: Box::new(ExCommand {
+                        range: Some(CommandRange {
+                            start: address(Position::CurrentLine, -1),
+                            end: None,
+                        }),
+                        command: LineCommand::Move {
+                            destination: address(Position::Line(0), 0),
+                        },
+                    }),
+                },
+            })
+        );
+        assert_eq!(
+            ExCommand::parse(">>").map(|command| command.command),
+            Some(LineCommand::Indent { times: 2 })
+        );
+        assert_eq!(
+            ExCommand::parse("%!sort -u"),
+            Some(ExCommand {
+                range: Some(CommandRange {
+                    start: address(Position::Line(1), 0),
+                    end: Some(address(Position::LastLine, 0)),
+                }),
+                command: LineCommand::Filter {
+                    command: "sort -u".into()
+                },
+            })
+        );
+        assert_eq!(
+            ExCommand::parse("!make test").map(|command| command.command),
+            Some(LineCommand::Shell {
+                command: "make test".into()
+            })
+        );
+        for query in ["r !date", "r!date", "read ! date"] {
+            assert_eq!(
+                ExCommand::parse(query).map(|command| command.command),
+                Some(LineCommand::ReadShell {
+                    command: "date".into()
+                })
+            );
+        }
+
+        // Not commands that work on lines, or incomplete ones.
+        assert_eq!(ExCommand::parse("5"), None);
+        assert_eq!(ExCommand::parse("/foo"), None);
+        assert_eq!(ExCommand::parse("g/TODO/"), None);
+        assert_eq!(ExCommand::parse("m"), None);
+        assert_eq!(ExCommand::parse("w"), None);
+        assert_eq!(ExCommand::parse("r file.txt"), None);
+        assert_eq!(ExCommand::parse("g/x/.!sort"), None);
+    }
+
+    #[test]
+    fn test_parse_global_substitute() {
+        assert_eq!(
+            ExCommand::parse("g/TODO/s/a/b/g").map(|command| command.command),
+            Some(LineCommand::Global {
+                pattern: "TODO".into(),
+                invert: false,
+                command: Box::new(ExCommand {
+                    range: None,
+                    command: LineCommand::Substitute {
+                        query: "s/a/b/g".into()
+                    },
+                }),
+            })
+        );
+    }
+
+    #[gpui::test]
+    async fn test_command_ranges(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d
+            e"})
+            .await;
+        cx.simulate_shared_keystrokes(": 2 , 3 d enter").await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            a
+            ˇd
+            e"});
+        cx.simulate_shared_keystrokes(": . , $ d enter").await;
+        cx.shared_state().await.assert_eq("ˇa");
+
+        cx.set_shared_state(indoc! {"
+            a
+            ˇb
+            c
+            d
+            e"})
+            .await;
+        cx.simulate_shared_keystrokes("m a j j m b : ' a , ' b d enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            a
+            ˇe"});
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            foo
+            d
+            e"})
+            .await;
+        cx.simulate_shared_keystrokes(": / f o o / + 1 , $ d enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            a
+            b
+            ˇfoo"});
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d
+            e"})
+            .await;
+        cx.simulate_shared_keystrokes(": . + 1 , . + 2 d enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            a
+            ˇd
+            e"});
+    }
+
+    #[gpui::test]
+    async fn test_command_move_copy(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d"})
+            .await;
+        cx.simulate_shared_keystrokes(": m $ enter").await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            b
+            c
+            d
+            ˇa"});
+        cx.simulate_shared_keystrokes(": 1 t . enter").await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            b
+            c
+            d
+            a
+            ˇb"});
+        cx.simulate_shared_keystrokes(": 2 , 3 m 0 enter").await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            c
+            ˇd
+            b
+            a
+            b"});
+    }
+
+    #[gpui::test]
+    async fn test_command_normal(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+              b
+            c"})
+            .await;
+        cx.simulate_shared_keystrokes(": % n o r m a l space shift-a ; enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            a;
+              b;
+            cˇ;"});
+
+        cx.set_shared_state(indoc! {"
+            ˇone
+            two
+            three"})
+            .await;
+        cx.simulate_shared_keystrokes("shift-v j : n o r m a l space x enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            ne
+            ˇwo
+            three"});
+    }
+
+    #[gpui::test]
+    async fn test_command_global(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            TODO b
+            c
+            TODO d
+            e"})
+            .await;
+        cx.simulate_shared_keystrokes(": g / T O D O / d enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            a
+            c
+            ˇe"});
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            pat b
+            c
+            pat d
+            e"})
+            .await;
+        cx.simulate_shared_keystrokes(": v / p a t / m $ enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            pat b
+            pat d
+            a
+            c
+            ˇe"});
+
+        cx.set_shared_state(indoc! {"
+            ˇx1
+            y
+            x2"})
+            .await;
+        cx.simulate_shared_keystrokes(": g / x / n o r m a l space shift-a z enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            x1z
+            y
+            x2ˇz"});
+    }
+
+    #[gpui::test]
+    async fn test_command_global_skips_deleted_lines(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        // The second line matches, but it's gone by the time its turn comes.
+        cx.set_state("ˇa\na\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes(": g / a / . , + 1 d enter");
+        cx.assert_editor_state("ˇb\nc");
+
+        // Lines joined to the one above are gone too.
+        cx.set_state("ˇa\na\na\na", Mode::Normal);
+        cx.simulate_keystrokes(": g / a / j enter");
+        assert_eq!(cx.update_editor(|editor, cx| editor.text(cx)), "a a\na a");
+    }
+
+    #[gpui::test]
+    async fn test_command_global_substitute(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇax x\nbx\nax", Mode::Normal);
+        cx.simulate_keystrokes(": g / a / s / x / y / enter");
+        cx.assert_editor_state("ay y\nbx\nˇay");
+        cx.simulate_keystrokes("u");
+        cx.assert_editor_state("ˇax x\nbx\nax");
+    }
+
+    #[gpui::test]
+    async fn test_command_global_with_shell_command(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        // Keymaps can bind commands that `:g` can't run, which are reported rather than run.
+        cx.set_state("ˇa\nb", Mode::Normal);
+        cx.dispatch_action(ExCommand {
+            range: None,
+            command: LineCommand::Global {
+                pattern: "a".into(),
+                invert: false,
+                command: Box::new(ExCommand {
+                    range: None,
+                    command: LineCommand::Shell {
+                        command: "make".into(),
+                    },
+                }),
+            },
+        });
+        cx.run_until_parked();
+        cx.assert_editor_state("ˇa\nb");
+        cx.workspace(|workspace, _| assert_eq!(workspace.notification_ids().len(), 1));
+    }
+
+    #[gpui::test]
+    async fn test_command_indent_and_undo(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\nc\nd", Mode::Normal);
+        cx.simulate_keystrokes(": 2 , 3 > > enter");
+        cx.assert_editor_state("a\n        b\n        ˇc\nd");
+        cx.simulate_keystrokes(": < enter");
+        cx.assert_editor_state("a\n        b\n    ˇc\nd");
+
+        // Commands run on many lines are undone in one go.
+        cx.set_state("ˇx1\ny\nx2", Mode::Normal);
+        cx.simulate_keystrokes(": g / x / n o r m a l space shift-a z enter");
+        cx.assert_editor_state("x1z\ny\nx2ˇz");
+        cx.simulate_keystrokes("u");
+        cx.assert_editor_state("ˇx1\ny\nx2");
+    }
+
+    /// Runs shell commands with `sh`, in a directory that exists on disk.
+    async fn init_shell(cx: &mut VimTestContext) {
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                let mut settings = store.raw_user_settings().clone();
+                settings["terminal"] = serde_json::json!({ "shell": { "program": "sh" } });
+                store.set_user_settings(&settings.to_string(), cx).unwrap();
+            })
+        });
+
+        let directory = std::env::temp_dir();
+        let project = cx.workspace(|workspace, _| workspace.project().clone());
+        let fs = cx.read(|cx| project.read(cx).fs().clone());
+        fs.create_dir(&directory).await.unwrap();
+        cx.update(|cx| {
+            project.update(cx, |project, cx| {
+                let worktree_ids = project
+                    .worktrees()
+                    .map(|worktree| worktree.read(cx).id())
+                    .collect::<Vec<_>>();
+                for worktree_id in worktree_ids {
+                    project.remove_worktree(worktree_id, cx);
+                }
+                project.find_or_create_local_worktree(&directory, true, cx)
+            })
+        })
+        .await
+        .unwrap();
+        cx.run_until_parked();
+        cx.executor().allow_parking();
+    }
+
+    /// Waits for the shell command that was started to edit the buffer.
+    fn wait_for_shell_output(cx: &mut VimTestContext) {
+        let text = cx.update_editor(|editor, cx| editor.text(cx));
+        for _ in 0..500 {
+            cx.run_until_parked();
+            if cx.update_editor(|editor, cx| editor.text(cx)) != text {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("the shell command didn't edit the buffer");
+    }
+
+    #[gpui::test]
+    async fn test_command_shell_filter(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        init_shell(&mut cx).await;
+
+        cx.set_state("ˇc\nb\na\nd", Mode::Normal);
+        cx.simulate_keystrokes(": 1 , 3 ! s o r t enter");
+        wait_for_shell_output(&mut cx);
+        cx.assert_editor_state("ˇa\nb\nc\nd");
+
+        // The filtered lines are restored in one go.
+        cx.simulate_keystrokes("u");
+        cx.assert_editor_state("ˇc\nb\na\nd");
+    }
+
+    #[gpui::test]
+    async fn test_command_read_shell(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        init_shell(&mut cx).await;
+
+        cx.set_state("ˇa\nb", Mode::Normal);
+        cx.simulate_keystrokes(": r space ! e c h o space h i enter");
+        wait_for_shell_output(&mut cx);
+        cx.assert_editor_state("a\nˇhi\nb");
+    }
+
+    #[gpui::test]
+    async fn test_filter_operator(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        init_shell(&mut cx).await;
+
+        cx.set_state("x\n\nc\nˇb\na\n\ny", Mode::Normal);
+        cx.simulate_keystrokes("! i p");
+        cx.assert_editor_state("x\n\nˇc\nb\na\n\ny");
+        let query = cx.workspace(|workspace, cx| {
+            workspace
+                .active_modal::<CommandPalette>(cx)
+                .unwrap()
+                .read(cx)
+                .query(cx)
+        });
+        assert_eq!(query, ".,.+2!");
+
+        cx.simulate_keystrokes("s o r t enter");
+        wait_for_shell_output(&mut cx);
+        cx.assert_editor_state("x\n\nˇa\nb\nc\n\ny");
+    }
 }
@@ -1,7 +1,7 @@
-use crate::{insert::NormalBefore, Vim, VimModeSetting};
+use crate::{enabled_in_settings, insert::NormalBefore, Vim};
 use editor::{Editor, EditorEvent};
 use gpui::{Action, AppContext, Entity, EntityId, UpdateGlobal, View, ViewContext, WindowContext};
-use settings::{Settings, SettingsStore};
+use settings::SettingsStore;
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(|_, cx: &mut ViewContext<Editor>| {
@@ -13,10 +13,10 @@ pub fn init(cx: &mut AppContext) {
         })
         .detach();
 
-        let mut enabled = VimModeSetting::get_global(cx).0;
+        let mut enabled = enabled_in_settings(cx);
         cx.observe_global::<SettingsStore>(move |editor, cx| {
-            if VimModeSetting::get_global(cx).0 != enabled {
-                enabled = VimModeSetting::get_global(cx).0;
+            if enabled_in_settings(cx) != enabled {
+                enabled = enabled_in_settings(cx);
                 if !enabled {
                     Vim::unhook_vim_settings(editor, cx);
                 }
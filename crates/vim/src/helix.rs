@@ -0,0 +1,760 @@
+//! Helix-style editing, where motions select text first and actions then apply to every
+//! selection.
+
+use std::{iter::Peekable, ops::Range};
+
+use editor::{
+    display_map::{DisplaySnapshot, ToDisplayPoint},
+    movement,
+    scroll::Autoscroll,
+    Anchor, Bias, DisplayPoint, Editor, ToOffset,
+};
+use gpui::{actions, impl_actions, ViewContext, WindowContext};
+use language::{char_kind, CharKind, Point, Selection, SelectionGoal};
+use multi_buffer::MultiBufferSnapshot;
+use regex::RegexBuilder;
+use search::{buffer_search, BufferSearchBar, SearchOptions};
+use serde::Deserialize;
+use workspace::{searchable::SearchableItem, Workspace};
+
+use crate::{
+    jump_list::push_to_jump_list,
+    motion::{coerce_punctuation, Motion},
+    normal::{
+        normal_object,
+        yank::{copy_selections_content, yank_selections_content},
+    },
+    object::Object,
+    state::{Mode, Operator, SearchState},
+    surrounds::SurroundsType,
+    Vim,
+};
+
+#[derive(Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct HelixNextWordStart {
+    #[serde(default)]
+    ignore_punctuation: bool,
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct HelixNextWordEnd {
+    #[serde(default)]
+    ignore_punctuation: bool,
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct HelixPreviousWordStart {
+    #[serde(default)]
+    ignore_punctuation: bool,
+}
+
+impl_actions!(
+    vim,
+    [HelixNextWordStart, HelixNextWordEnd, HelixPreviousWordStart]
+);
+
+actions!(
+    vim,
+    [
+        HelixSelectLine,
+        HelixCollapseSelection,
+        HelixFlipSelections,
+        HelixKeepPrimarySelection,
+        HelixSelectRegex,
+        HelixSurroundAdd,
+        HelixInsert,
+        HelixAppend,
+        HelixDelete,
+        HelixChange,
+        HelixYank
+    ]
+);
+
+#[derive(Clone, Copy, PartialEq)]
+enum WordTarget {
+    NextStart,
+    NextEnd,
+    PreviousStart,
+}
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(
+        |_: &mut Workspace, &HelixNextWordStart { ignore_punctuation }, cx| {
+            select_word(WordTarget::NextStart, ignore_punctuation, cx)
+        },
+    );
+    workspace.register_action(
+        |_: &mut Workspace, &HelixNextWordEnd { ignore_punctuation }, cx| {
+            select_word(WordTarget::NextEnd, ignore_punctuation, cx)
+        },
+    );
+    workspace.register_action(
+        |_: &mut Workspace, &HelixPreviousWordStart { ignore_punctuation }, cx| {
+            select_word(WordTarget::PreviousStart, ignore_punctuation, cx)
+        },
+    );
+    workspace.register_action(|_: &mut Workspace, _: &HelixSelectLine, cx| select_line(cx));
+    workspace.register_action(|_: &mut Workspace, _: &HelixCollapseSelection, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |_, editor, cx| {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.move_with(|map, selection| {
+                        let cursor = cursor_position(map, selection);
+                        selection.collapse_to(cursor, SelectionGoal::None)
+                    })
+                })
+            });
+        })
+    });
+    workspace.register_action(|_: &mut Workspace, _: &HelixFlipSelections, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |_, editor, cx| {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.move_with(|_, selection| {
+                        if !selection.is_empty() {
+                            selection.reversed = !selection.reversed;
+                        }
+                    })
+                })
+            });
+        })
+    });
+    workspace.register_action(|_: &mut Workspace, _: &HelixKeepPrimarySelection, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |_, editor, cx| {
+                let newest = editor.selections.newest_anchor().clone();
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_anchors(vec![newest])
+                })
+            });
+        })
+    });
+    workspace.register_action(|workspace, _: &HelixSelectRegex, cx| select_regex(workspace, cx));
+    workspace.register_action(|_: &mut Workspace, _: &HelixSurroundAdd, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.push_operator(
+                Operator::AddSurrounds {
+                    target: Some(SurroundsType::Selection),
+                },
+                cx,
+            )
+        })
+    });
+    workspace.register_action(|_: &mut Workspace, _: &HelixInsert, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |_, editor, cx| {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.move_with(|_, selection| {
+                        selection.collapse_to(selection.start, SelectionGoal::None)
+                    })
+                })
+            });
+            vim.switch_mode(Mode::Insert, false, cx);
+        })
+    });
+    workspace.register_action(|_: &mut Workspace, _: &HelixAppend, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |_, editor, cx| {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.move_with(|map, selection| {
+                        // An empty selection is a block cursor, so appending goes after the
+                        // character under it.
+                        let end = if selection.is_empty() {
+                            movement::saturating_right(map, selection.end)
+                        } else {
+                            selection.end
+                        };
+                        selection.collapse_to(end, SelectionGoal::None)
+                    })
+                })
+            });
+            vim.switch_mode(Mode::Insert, false, cx);
+        })
+    });
+    workspace.register_action(|_: &mut Workspace, _: &HelixDelete, cx| {
+        Vim::update(cx, |vim, cx| {
+            delete_selections(vim, cx);
+            if vim.state().mode == Mode::HelixSelect {
+                vim.switch_mode(Mode::HelixNormal, true, cx);
+            }
+        })
+    });
+    workspace.register_action(|_: &mut Workspace, _: &HelixChange, cx| {
+        Vim::update(cx, |vim, cx| {
+            delete_selections(vim, cx);
+            vim.switch_mode(Mode::Insert, false, cx);
+        })
+    });
+    workspace.register_action(|_: &mut Workspace, _: &HelixYank, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |vim, editor, cx| {
+                select_cursor_characters(editor, cx);
+                yank_selections_content(vim, editor, false, cx);
+            });
+        })
+    });
+}
+
+/// Returns the position of the block cursor of a selection: its head when the selection is
+/// empty or reversed, and the last selected character otherwise.
+pub(crate) fn cursor_position(
+    map: &DisplaySnapshot,
+    selection: &Selection<DisplayPoint>,
+) -> DisplayPoint {
+    if selection.reversed || selection.is_empty() {
+        selection.head()
+    } else {
+        movement::left(map, selection.end)
+    }
+}
+
+/// Moves the block cursor of the selection to the given point, keeping the character at its
+/// other end selected, as motions do in Helix's select mode.
+fn extend_selection(
+    map: &DisplaySnapshot,
+    selection: &mut Selection<DisplayPoint>,
+    cursor: DisplayPoint,
+    goal: SelectionGoal,
+) {
+    let anchor = if selection.reversed && !selection.is_empty() {
+        movement::left(map, selection.end)
+    } else {
+        selection.start
+    };
+    if cursor < anchor {
+        selection.start = cursor;
+        selection.end = movement::right(map, anchor);
+        selection.reversed = true;
+    } else {
+        selection.start = anchor;
+        selection.end = movement::right(map, cursor);
+        selection.reversed = false;
+    }
+    selection.goal = goal;
+}
+
+/// Moves each selection by the motion. Finding a character selects everything up to it, as
+/// Helix's `f` and `t` do, while other motions leave a cursor where they stop. In select mode,
+/// every motion extends the selections instead.
+pub(crate) fn helix_normal_motion(motion: Motion, times: Option<usize>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let extend = vim.state().mode == Mode::HelixSelect;
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            if motion.is_jump() {
+                push_to_jump_list(vim, editor, editor.selections.newest_anchor().head(), cx);
+            }
+            let text_layout_details = editor.text_layout_details(cx);
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = cursor_position(map, selection);
+                    let Some((point, goal)) =
+                        motion.move_point(map, cursor, selection.goal, times, &text_layout_details)
+                    else {
+                        return;
+                    };
+                    if extend {
+                        extend_selection(map, selection, point, goal);
+                        return;
+                    }
+                    match motion {
+                        Motion::FindForward { .. } => {
+                            selection.start = cursor;
+                            selection.end = movement::right(map, point);
+                            selection.reversed = false;
+                            selection.goal = goal;
+                        }
+                        Motion::FindBackward { .. } => {
+                            selection.start = point;
+                            selection.end = movement::right(map, cursor);
+                            selection.reversed = true;
+                            selection.goal = goal;
+                        }
+                        _ => selection.collapse_to(point, goal),
+                    }
+                })
+            });
+        });
+    });
+}
+
+/// Selects the text object around each cursor after `mi` or `ma`. Any other object is the
+/// surrounding pair to change after `mr`, which vim's `cs` handles already.
+pub(crate) fn helix_object(object: Object, cx: &mut WindowContext) {
+    let Some(Operator::Object { around }) = Vim::read(cx).active_operator() else {
+        normal_object(object, cx);
+        return;
+    };
+    Vim::update(cx, |vim, cx| {
+        vim.clear_operator(cx);
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let mut cursor = selection.clone();
+                    cursor.collapse_to(cursor_position(map, selection), SelectionGoal::None);
+                    if let Some(range) = object
+                        .range(map, cursor, around)
+                        .filter(|range| range.start != range.end)
+                    {
+                        selection.start = range.start;
+                        selection.end = range.end;
+                        selection.reversed = false;
+                        selection.goal = SelectionGoal::None;
+                    }
+                })
+            });
+        });
+    });
+}
+
+fn select_word(target: WordTarget, ignore_punctuation: bool, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let times = vim.take_count(cx).unwrap_or(1);
+        let extend = vim.state().mode == Mode::HelixSelect;
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let snapshot = &map.buffer_snapshot;
+                    let mut cursor = cursor_position(map, selection).to_offset(map, Bias::Left);
+                    let mut selected = None;
+                    for _ in 0..times {
+                        let Some(range) = word_range(snapshot, cursor, target, ignore_punctuation)
+                        else {
+                            break;
+                        };
+                        // The next word is looked for from the block cursor of this one.
+                        cursor = if target == WordTarget::PreviousStart {
+                            range.start
+                        } else {
+                            let last_char = snapshot.reversed_chars_at(range.end).next();
+                            range.end - last_char.map_or(0, char::len_utf8)
+                        };
+                        selected = Some(range);
+                    }
+                    let Some(range) = selected else {
+                        return;
+                    };
+                    if extend {
+                        extend_selection(
+                            map,
+                            selection,
+                            cursor.to_display_point(map),
+                            SelectionGoal::None,
+                        );
+                        return;
+                    }
+                    selection.start = range.start.to_display_point(map);
+                    selection.end = range.end.to_display_point(map);
+                    selection.reversed = target == WordTarget::PreviousStart;
+                    selection.goal = SelectionGoal::None;
+                })
+            });
+        });
+    });
+}
+
+/// Returns the range Helix's `w`, `e` or `b` selects from the block cursor at `cursor`. The
+/// cursor's own character is left out when it ends a word, so that repeating the motion selects
+/// the following word rather than the whitespace before it.
+fn word_range(
+    snapshot: &MultiBufferSnapshot,
+    cursor: usize,
+    target: WordTarget,
+    ignore_punctuation: bool,
+) -> Option<Range<usize>> {
+    let scope = snapshot.language_scope_at(cursor);
+    let kind = |c: char| coerce_punctuation(char_kind(&scope, c), ignore_punctuation);
+    let is_whitespace = |c: char| c != '\n' && kind(c) == CharKind::Whitespace;
+
+    if target == WordTarget::PreviousStart {
+        let mut end = cursor;
+        let mut preceding = snapshot.reversed_chars_at(cursor).peekable();
+        if let (Some(current), Some(&previous)) =
+            (snapshot.chars_at(cursor).next(), preceding.peek())
+        {
+            if current != '\n' && previous != '\n' && kind(current) == kind(previous) {
+                end += current.len_utf8();
+            }
+        }
+        let mut start = cursor;
+        start -= skip_while(&mut preceding, |c| c == '\n');
+        start -= skip_while(&mut preceding, is_whitespace);
+        if let Some(&c) = preceding.peek() {
+            let word_kind = kind(c);
+            start -= skip_while(&mut preceding, |c| c != '\n' && kind(c) == word_kind);
+        }
+        return (start < end).then_some(start..end);
+    }
+
+    let mut chars = snapshot.chars_at(cursor).peekable();
+    let current = *chars.peek()?;
+    let next = snapshot.chars_at(cursor).nth(1);
+    let mut start = cursor;
+    let ends_word = next.map_or(true, |next| {
+        current == '\n' || next == '\n' || kind(current) != kind(next)
+    });
+    if ends_word {
+        chars.next();
+        start += current.len_utf8();
+    }
+    start += skip_while(&mut chars, |c| c == '\n');
+
+    let mut end = start;
+    if target == WordTarget::NextEnd {
+        end += skip_while(&mut chars, is_whitespace);
+    }
+    if let Some(&c) = chars.peek() {
+        let word_kind = kind(c);
+        end += skip_while(&mut chars, |c| c != '\n' && kind(c) == word_kind);
+    }
+    if target == WordTarget::NextStart {
+        end += skip_while(&mut chars, is_whitespace);
+    }
+    (start < end).then_some(start..end)
+}
+
+/// Consumes the characters that match the predicate, returning their length in bytes.
+fn skip_while(
+    chars: &mut Peekable<impl Iterator<Item = char>>,
+    mut predicate: impl FnMut(char) -> bool,
+) -> usize {
+    let mut len = 0;
+    while let Some(c) = chars.next_if(|c| predicate(*c)) {
+        len += c.len_utf8();
+    }
+    len
+}
+
+/// Selects the lines of each selection, as Helix's `x` does. A selection of whole lines grows
+/// by the next lines instead.
+fn select_line(cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let times = vim.take_count(cx).unwrap_or(1) as u32;
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let max_point = map.buffer_snapshot.max_point();
+                    let start = selection.start.to_point(map);
+                    let end = selection.end.to_point(map);
+                    let whole_lines =
+                        start.column == 0 && end > start && (end.column == 0 || end == max_point);
+                    let end_row = if !whole_lines {
+                        end.row + times - 1
+                    } else if end.column == 0 {
+                        end.row - 1 + times
+                    } else {
+                        end.row + times
+                    };
+                    let end = if end_row < max_point.row {
+                        Point::new(end_row + 1, 0)
+                    } else {
+                        max_point
+                    };
+                    selection.start = Point::new(start.row, 0).to_display_point(map);
+                    selection.end = end.to_display_point(map);
+                    selection.reversed = false;
+                    selection.goal = SelectionGoal::None;
+                })
+            });
+        });
+    });
+}
+
+/// Extends each empty selection over the character under its block cursor, which Helix
+/// considers selected.
+fn select_cursor_characters(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    editor.change_selections(None, cx, |s| {
+        s.move_with(|map, selection| {
+            if selection.is_empty() {
+                selection.end = movement::right(map, selection.start);
+                selection.reversed = false;
+            }
+        })
+    });
+}
+
+fn delete_selections(vim: &mut Vim, cx: &mut WindowContext) {
+    vim.update_active_editor(cx, |vim, editor, cx| {
+        editor.transact(cx, |editor, cx| {
+            select_cursor_characters(editor, cx);
+            copy_selections_content(vim, editor, false, cx);
+            editor.insert("", cx);
+        });
+    });
+}
+
+/// Opens the search bar to type the pattern that `s` selects the matches of within the
+/// selections.
+fn select_regex(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let pane = workspace.active_pane().clone();
+    Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |_, editor, cx| {
+            select_cursor_characters(editor, cx);
+            editor.toggle_filtered_search_ranges(true, cx);
+        });
+        let prior_selections = vim.editor_selections(cx);
+        pane.update(cx, |pane, cx| {
+            if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
+                search_bar.update(cx, |search_bar, cx| {
+                    if !search_bar.show(cx) {
+                        return;
+                    }
+                    search_bar.select_query(cx);
+                    cx.focus_self();
+                    search_bar.set_search_options(SearchOptions::REGEX, cx);
+                    vim.workspace_state.search = SearchState {
+                        prior_selections,
+                        prior_mode: vim.state().mode,
+                        select_matches: true,
+                        ..Default::default()
+                    };
+                });
+            }
+        })
+    })
+}
+
+/// Replaces the selections from before `s` with the matches of the submitted pattern within
+/// them. The selections are kept when nothing matches.
+pub(crate) fn select_matches_submit(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let pane = workspace.active_pane().clone();
+    let Some(search_bar) = pane
+        .read(cx)
+        .toolbar()
+        .read(cx)
+        .item_of_type::<BufferSearchBar>()
+    else {
+        return;
+    };
+    let query = search_bar.update(cx, |search_bar, cx| {
+        let query = search_bar.query(cx);
+        search_bar.dismiss(&buffer_search::Dismiss, cx);
+        query
+    });
+    Vim::update(cx, |vim, cx| {
+        let search = std::mem::take(&mut vim.workspace_state.search);
+        vim.workspace_state
+            .registers
+            .insert('/', query.clone().into());
+        vim.update_active_editor(cx, |_, editor, cx| {
+            select_matches(editor, &query, &search.prior_selections, cx)
+        });
+    });
+}
+
+fn select_matches(
+    editor: &mut Editor,
+    query: &str,
+    selections: &[Range<Anchor>],
+    cx: &mut ViewContext<Editor>,
+) {
+    // Like Helix, the pattern ignores case unless it has an uppercase letter.
+    let Ok(regex) = RegexBuilder::new(query)
+        .case_insensitive(!query.chars().any(char::is_uppercase))
+        .build()
+    else {
+        return;
+    };
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let mut ranges = Vec::new();
+    for selection in selections {
+        let start = selection.start.to_offset(&snapshot);
+        let end = selection.end.to_offset(&snapshot);
+        let range = start.min(end)..start.max(end);
+        let text = snapshot.text_for_range(range.clone()).collect::<String>();
+        ranges.extend(
+            regex
+                .find_iter(&text)
+                .filter(|found| !found.as_str().is_empty())
+                .map(|found| range.start + found.start()..range.start + found.end()),
+        );
+    }
+    if ranges.is_empty() {
+        return;
+    }
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.select_ranges(ranges));
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_helix_word_selections(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state("ˇone two three", Mode::HelixNormal);
+        cx.simulate_keystrokes("w");
+        cx.assert_state("«one ˇ»two three", Mode::HelixNormal);
+        cx.simulate_keystrokes("w");
+        cx.assert_state("one «two ˇ»three", Mode::HelixNormal);
+        cx.simulate_keystrokes("b");
+        cx.assert_state("one «ˇtwo» three", Mode::HelixNormal);
+        cx.simulate_keystrokes("b");
+        cx.assert_state("«ˇone »two three", Mode::HelixNormal);
+
+        cx.set_state("ˇone two three", Mode::HelixNormal);
+        cx.simulate_keystrokes("e");
+        cx.assert_state("«oneˇ» two three", Mode::HelixNormal);
+        cx.simulate_keystrokes("e");
+        cx.assert_state("one« twoˇ» three", Mode::HelixNormal);
+
+        cx.set_state("ˇone two three", Mode::HelixNormal);
+        cx.simulate_keystrokes("2 w");
+        cx.assert_state("one «two ˇ»three", Mode::HelixNormal);
+
+        cx.set_state("one.ˇtwo three", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-b");
+        cx.assert_state("«ˇone.t»wo three", Mode::HelixNormal);
+
+        cx.set_state("ˇone two three", Mode::HelixNormal);
+        cx.simulate_keystrokes("f t");
+        cx.assert_state("«one tˇ»wo three", Mode::HelixNormal);
+        cx.simulate_keystrokes(";");
+        cx.assert_state("one ˇtwo three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_helix_select_mode(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state("ˇone two three", Mode::HelixNormal);
+        cx.simulate_keystrokes("v l");
+        cx.assert_state("«onˇ»e two three", Mode::HelixSelect);
+        cx.simulate_keystrokes("w");
+        cx.assert_state("«one ˇ»two three", Mode::HelixSelect);
+        cx.simulate_keystrokes("h");
+        cx.assert_state("«oneˇ» two three", Mode::HelixSelect);
+        cx.simulate_keystrokes("v l");
+        cx.assert_state("oneˇ two three", Mode::HelixNormal);
+
+        cx.set_state("one tˇwo three", Mode::HelixNormal);
+        cx.simulate_keystrokes("v h h");
+        cx.assert_state("one«ˇ tw»o three", Mode::HelixSelect);
+
+        cx.set_state(
+            indoc! {"
+            one
+            twˇo"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("v g g");
+        cx.assert_state(
+            indoc! {"
+            «ˇone
+            two»"},
+            Mode::HelixSelect,
+        );
+        cx.simulate_keystrokes("d");
+        cx.assert_state("ˇ", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_helix_select_line(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+            three"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «one
+            ˇ»two
+            three"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «one
+            two
+            ˇ»three"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «one
+            two
+            threeˇ»"},
+            Mode::HelixNormal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+            three"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x d");
+        cx.assert_state(
+            indoc! {"
+            ˇtwo
+            three"},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_helix_actions(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state("ˇone two", Mode::HelixNormal);
+        cx.simulate_keystrokes("w d");
+        cx.assert_state("ˇtwo", Mode::HelixNormal);
+
+        cx.set_state("ˇone two", Mode::HelixNormal);
+        cx.simulate_keystrokes("e c");
+        cx.assert_state("ˇ two", Mode::Insert);
+        cx.simulate_keystrokes("s i x escape");
+        cx.assert_state("sixˇ two", Mode::HelixNormal);
+
+        cx.set_state("ˇone two", Mode::HelixNormal);
+        cx.simulate_keystrokes("w y p");
+        cx.assert_state("one oneˇ two", Mode::HelixNormal);
+
+        cx.set_state("one tˇwo three", Mode::HelixNormal);
+        cx.simulate_keystrokes("m i w");
+        cx.assert_state("one «twoˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes("m s )");
+        cx.assert_state("one ˇ(two) three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_helix_select_regex(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state(
+            indoc! {"
+            ˇone two
+            one three
+            four one"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x x s o n e");
+        cx.run_until_parked();
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+            «oneˇ» two
+            «oneˇ» three
+            four one"},
+            Mode::HelixNormal,
+        );
+    }
+}
@@ -25,8 +25,12 @@ fn normal_before(_: &mut Workspace, action: &NormalBefore, cx: &mut ViewContext<
         vim.stop_recording_immediately(action.boxed_clone());
         if count <= 1 || vim.workspace_state.replaying {
             create_mark(vim, "^".into(), false, cx);
-            vim.update_active_editor(cx, |_, editor, cx| {
+            vim.update_active_editor(cx, |vim, editor, cx| {
                 editor.dismiss_menus_and_popups(false, cx);
+                // Helix leaves the cursor where the insertion ended.
+                if vim.helix_mode {
+                    return;
+                }
                 editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
                     s.move_cursors_with(|map, mut cursor, _| {
                         *cursor.column_mut() = cursor.column().saturating_sub(1);
@@ -0,0 +1,234 @@
+use editor::{Anchor, Direction, Editor};
+use gpui::{actions, AppContext};
+use ui::{ViewContext, WindowContext};
+use workspace::Workspace;
+
+use crate::{normal::mark, state::FileMark, Vim};
+
+actions!(vim, [JumpListOlder, JumpListNewer]);
+
+/// The number of positions the jump list remembers, as in vim.
+const JUMP_LIST_LENGTH: usize = 100;
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|_, _: &JumpListOlder, cx| {
+        let count = Vim::update(cx, |vim, cx| vim.take_count(cx)).unwrap_or(1);
+        move_in_jump_list(Direction::Prev, count, cx);
+    });
+    workspace.register_action(|_, _: &JumpListNewer, cx| {
+        let count = Vim::update(cx, |vim, cx| vim.take_count(cx)).unwrap_or(1);
+        move_in_jump_list(Direction::Next, count, cx);
+    });
+}
+
+fn move_in_jump_list(direction: Direction, count: usize, cx: &mut WindowContext) {
+    let target = Vim::update(cx, |vim, cx| {
+        if direction == Direction::Prev {
+            start_navigating(vim, cx);
+        }
+        let position = vim.workspace_state.jump_list_position?;
+        match direction {
+            Direction::Prev => position.checked_sub(count),
+            Direction::Next => Some(position + count),
+        }
+    });
+    if let Some(target) = target {
+        jump_to_entry(target, cx);
+    }
+}
+
+/// Moves to the jump list entry at the given index.
+pub(crate) fn jump_to_entry(index: usize, cx: &mut WindowContext) {
+    let Some(mark) = Vim::update(cx, |vim, _| {
+        let state = &mut vim.workspace_state;
+        let mark = state.jump_list.get(index)?.clone();
+        state.jump_list_position = Some(index);
+        Some(mark)
+    }) else {
+        return;
+    };
+    mark::jump_to_file_mark(mark, false, true, cx);
+}
+
+/// Adds the cursor position to the end of the jump list before moving back in it, so that
+/// moving forward again returns to it.
+pub(crate) fn start_navigating(vim: &mut Vim, cx: &mut WindowContext) {
+    if vim.workspace_state.jump_list_position.is_some() {
+        return;
+    }
+    vim.update_active_editor(cx, |vim, editor, cx| {
+        push_to_jump_list(vim, editor, editor.selections.newest_anchor().head(), cx)
+    });
+    vim.workspace_state.jump_list_position = vim.workspace_state.jump_list.len().checked_sub(1);
+}
+
+/// Remembers the given position of the editor before jumping away from it. Older entries on
+/// the same line are dropped, so that each line is in the jump list once.
+pub(crate) fn push_to_jump_list(vim: &mut Vim, editor: &Editor, position: Anchor, cx: &AppContext) {
+    let Some(mark) = FileMark::new(editor, position, cx) else {
+        return;
+    };
+    let state = &mut vim.workspace_state;
+    state.jump_list.retain(|entry| !entry.same_line(&mark, cx));
+    state.jump_list.push(mark);
+    if state.jump_list.len() > JUMP_LIST_LENGTH {
+        state.jump_list.remove(0);
+    }
+    state.jump_list_position = None;
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_jump_list(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+
+            three
+            four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("shift-g");
+        cx.simulate_keystrokes("g g j");
+        cx.simulate_keystrokes("}");
+        cx.assert_state(
+            indoc! {"
+            one
+            two
+            ˇ
+            three
+            four"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes("ctrl-o");
+        cx.assert_state(
+            indoc! {"
+            one
+            ˇtwo
+
+            three
+            four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("ctrl-o");
+        cx.assert_state(
+            indoc! {"
+            one
+            two
+
+            three
+            ˇfour"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("ctrl-o");
+        cx.assert_state(
+            indoc! {"
+            ˇone
+            two
+
+            three
+            four"},
+            Mode::Normal,
+        );
+        // There is nothing older to go back to.
+        cx.simulate_keystrokes("ctrl-o");
+        cx.assert_state(
+            indoc! {"
+            ˇone
+            two
+
+            three
+            four"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes("2 ctrl-i");
+        cx.assert_state(
+            indoc! {"
+            one
+            ˇtwo
+
+            three
+            four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("ctrl-i");
+        cx.assert_state(
+            indoc! {"
+            one
+            two
+            ˇ
+            three
+            four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("ctrl-i");
+        cx.assert_state(
+            indoc! {"
+            one
+            two
+            ˇ
+            three
+            four"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_jump_list_search_and_marks(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone two
+            three
+            two four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("m a j");
+        cx.simulate_keystrokes("/ f o u r");
+        cx.run_until_parked();
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+            one two
+            three
+            two ˇfour"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("` a");
+        cx.assert_state(
+            indoc! {"
+            ˇone two
+            three
+            two four"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes("ctrl-o");
+        cx.assert_state(
+            indoc! {"
+            one two
+            three
+            two ˇfour"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("ctrl-o");
+        cx.assert_state(
+            indoc! {"
+            one two
+            ˇthree
+            two four"},
+            Mode::Normal,
+        );
+    }
+}
@@ -4,16 +4,18 @@ use editor::{
         self, find_boundary, find_preceding_boundary_display_point, FindRange, TextLayoutDetails,
     },
     scroll::Autoscroll,
-    Anchor, Bias, DisplayPoint, RowExt, ToOffset,
+    text_objects, Anchor, Bias, Direction, DisplayPoint, RowExt, ToOffset,
 };
 use gpui::{actions, impl_actions, px, ViewContext, WindowContext};
-use language::{char_kind, CharKind, Point, Selection, SelectionGoal};
+use language::{char_kind, CharKind, Point, Selection, SelectionGoal, TextObject};
 use multi_buffer::MultiBufferRow;
 use serde::Deserialize;
 use std::ops::Range;
 use workspace::Workspace;
 
 use crate::{
+    helix::helix_normal_motion,
+    jump_list::push_to_jump_list,
     normal::{mark, normal_motion},
     state::{Mode, Operator},
     surrounds::SurroundsType,
@@ -97,6 +99,10 @@ pub enum Motion {
     WindowTop,
     WindowMiddle,
     WindowBottom,
+    NextMethodStart,
+    NextMethodEnd,
+    PreviousMethodStart,
+    PreviousMethodEnd,
 
     // we don't have a good way to run a search syncronously, so
     // we handle search motions by running the search async and then
@@ -243,6 +249,10 @@ actions!(
         WindowTop,
         WindowMiddle,
         WindowBottom,
+        NextMethodStart,
+        NextMethodEnd,
+        PreviousMethodStart,
+        PreviousMethodEnd,
     ]
 );
 
@@ -390,6 +400,18 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     workspace.register_action(|_: &mut Workspace, &WindowBottom, cx: _| {
         motion(Motion::WindowBottom, cx)
     });
+    workspace.register_action(|_: &mut Workspace, &NextMethodStart, cx: _| {
+        motion(Motion::NextMethodStart, cx)
+    });
+    workspace.register_action(|_: &mut Workspace, &NextMethodEnd, cx: _| {
+        motion(Motion::NextMethodEnd, cx)
+    });
+    workspace.register_action(|_: &mut Workspace, &PreviousMethodStart, cx: _| {
+        motion(Motion::PreviousMethodStart, cx)
+    });
+    workspace.register_action(|_: &mut Workspace, &PreviousMethodEnd, cx: _| {
+        motion(Motion::PreviousMethodEnd, cx)
+    });
 }
 
 pub(crate) fn search_motion(m: Motion, cx: &mut WindowContext) {
@@ -409,8 +431,16 @@ pub(crate) fn search_motion(m: Motion, cx: &mut WindowContext) {
                     });
                 }
             }
-            Mode::Normal | Mode::Replace | Mode::Insert => {
+            Mode::Normal | Mode::Replace | Mode::Insert | Mode::HelixNormal | Mode::HelixSelect => {
                 if Vim::read(cx).active_operator().is_none() {
+                    // The search has moved the cursor already, from where the jump is recorded.
+                    if let Some(prior_selection) = prior_selections.first() {
+                        Vim::update(cx, |vim, cx| {
+                            vim.update_active_editor(cx, |vim, editor, cx| {
+                                push_to_jump_list(vim, editor, prior_selection.start, cx)
+                            })
+                        });
+                    }
                     return;
                 }
             }
@@ -443,6 +473,7 @@ pub(crate) fn motion(motion: Motion, cx: &mut WindowContext) {
         Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
             visual_motion(motion.clone(), count, cx)
         }
+        Mode::HelixNormal | Mode::HelixSelect => helix_normal_motion(motion.clone(), count, cx),
         Mode::Insert => {
             // Shouldn't execute a motion in insert mode. Ignoring
         }
@@ -498,10 +529,62 @@ impl Motion {
             | RepeatFind { .. }
             | RepeatFindReversed { .. }
             | Jump { line: false, .. }
+            | NextMethodStart
+            | NextMethodEnd
+            | PreviousMethodStart
+            | PreviousMethodEnd
             | ZedSearchResult { .. } => false,
         }
     }
 
+    /// Whether moving the cursor with the motion records its previous position in the jump
+    /// list, as `G`, `%` or `{` do.
+    pub fn is_jump(&self) -> bool {
+        use Motion::*;
+        match self {
+            StartOfDocument
+            | EndOfDocument
+            | Matching
+            | StartOfParagraph
+            | EndOfParagraph
+            | WindowTop
+            | WindowMiddle
+            | WindowBottom
+            | ZedSearchResult { .. }
+            | Jump { .. } => true,
+            Down { .. }
+            | Up { .. }
+            | CurrentLine
+            | EndOfLine { .. }
+            | FindForward { .. }
+            | RepeatFind { .. }
+            | Left
+            | Backspace
+            | Right
+            | Space
+            | StartOfLine { .. }
+            | StartOfLineDownward
+            | EndOfLineDownward
+            | GoToColumn
+            | NextWordStart { .. }
+            | NextWordEnd { .. }
+            | PreviousWordStart { .. }
+            | PreviousWordEnd { .. }
+            | NextSubwordStart { .. }
+            | NextSubwordEnd { .. }
+            | PreviousSubwordStart { .. }
+            | PreviousSubwordEnd { .. }
+            | FirstNonWhitespace { .. }
+            | FindBackward { .. }
+            | RepeatFindReversed { .. }
+            | NextLineStart
+            | NextMethodStart
+            | NextMethodEnd
+            | PreviousMethodStart
+            | PreviousMethodEnd => false,
+        }
+    }
+
     pub fn infallible(&self) -> bool {
         use Motion::*;
         match self {
@@ -537,6 +620,10 @@ impl Motion {
             | WindowMiddle
             | WindowBottom
             | NextLineStart
+            | NextMethodStart
+            | NextMethodEnd
+            | PreviousMethodStart
+            | PreviousMethodEnd
             | ZedSearchResult { .. }
             | Jump { .. } => false,
         }
@@ -561,6 +648,8 @@ impl Motion {
             | PreviousWordEnd { .. }
             | NextSubwordEnd { .. }
             | PreviousSubwordEnd { .. }
+            | NextMethodEnd
+            | PreviousMethodEnd
             | NextLineStart => true,
             Left
             | Backspace
@@ -578,6 +667,8 @@ impl Motion {
             | FirstNonWhitespace { .. }
             | FindBackward { .. }
             | Jump { .. }
+            | NextMethodStart
+            | PreviousMethodStart
             | ZedSearchResult { .. } => false,
             RepeatFind { last_find: motion } | RepeatFindReversed { last_find: motion } => {
                 motion.inclusive()
@@ -770,6 +861,22 @@ impl Motion {
             WindowMiddle => window_middle(map, point, &text_layout_details),
             WindowBottom => window_bottom(map, point, &text_layout_details, times - 1),
             Jump { line, anchor } => mark::jump_motion(map, *anchor, *line),
+            NextMethodStart => (
+                method_boundary(map, point, times, Direction::Next, false),
+                SelectionGoal::None,
+            ),
+            NextMethodEnd => (
+                method_boundary(map, point, times, Direction::Next, true),
+                SelectionGoal::None,
+            ),
+            PreviousMethodStart => (
+                method_boundary(map, point, times, Direction::Prev, false),
+                SelectionGoal::None,
+            ),
+            PreviousMethodEnd => (
+                method_boundary(map, point, times, Direction::Prev, true),
+                SelectionGoal::None,
+            ),
             ZedSearchResult { new_selections, .. } => {
                 // There will be only one selection, as
                 // Search::SelectNextMatch selects a single match.
@@ -1765,6 +1872,31 @@ fn window_bottom(
     }
 }
 
+/// Moves to the start or the last character of the `times`th function before or after `point`,
+/// as captured by the `textobjects` query of the language.
+fn method_boundary(
+    map: &DisplaySnapshot,
+    point: DisplayPoint,
+    times: usize,
+    direction: Direction,
+    end: bool,
+) -> DisplayPoint {
+    let mut offset = point.to_offset(map, Bias::Left);
+    for _ in 0..times {
+        let Some(boundary) = text_objects::text_object_boundary(
+            &map.buffer_snapshot,
+            offset,
+            TextObject::AroundFunction,
+            direction,
+            end,
+        ) else {
+            break;
+        };
+        offset = boundary;
+    }
+    offset.to_display_point(map)
+}
+
 pub fn coerce_punctuation(kind: CharKind, treat_punctuation_as_word: bool) -> CharKind {
     if treat_punctuation_as_word && kind == CharKind::Punctuation {
         CharKind::Word
@@ -1776,7 +1908,10 @@ pub fn coerce_punctuation(kind: CharKind, treat_punctuation_as_word: bool) -> Ch
 #[cfg(test)]
 mod test {
 
-    use crate::test::NeovimBackedTestContext;
+    use crate::{
+        state::Mode,
+        test::{NeovimBackedTestContext, VimTestContext},
+    };
     use indoc::indoc;
 
     #[gpui::test]
@@ -2291,4 +2426,87 @@ mod test {
             }ˇ»
         "});
     }
+
+    #[gpui::test]
+    async fn test_method_motions(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                ˇfn a() {
+                    let f = |x| x;
+                }
+
+                fn b() {}
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("] m");
+        cx.assert_state(
+            indoc! {"
+                fn a() {
+                    let f = ˇ|x| x;
+                }
+
+                fn b() {}
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("] m");
+        cx.assert_state(
+            indoc! {"
+                fn a() {
+                    let f = |x| x;
+                }
+
+                ˇfn b() {}
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("[ shift-m");
+        cx.assert_state(
+            indoc! {"
+                fn a() {
+                    let f = |x| x;
+                ˇ}
+
+                fn b() {}
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("2 [ m");
+        cx.assert_state(
+            indoc! {"
+                ˇfn a() {
+                    let f = |x| x;
+                }
+
+                fn b() {}
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("] shift-m");
+        cx.assert_state(
+            indoc! {"
+                fn a() {
+                    let f = |x| ˇx;
+                }
+
+                fn b() {}
+            "},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes("d [ m");
+        cx.assert_state(
+            indoc! {"
+                fn a() {
+                    let f = ˇx;
+                }
+
+                fn b() {}
+            "},
+            Mode::Normal,
+        );
+    }
 }
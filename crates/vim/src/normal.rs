@@ -4,7 +4,7 @@ mod delete;
 mod increment;
 mod indent;
 pub(crate) mod mark;
-mod paste;
+pub(crate) mod paste;
 pub(crate) mod repeat;
 mod scroll;
 pub(crate) mod search;
@@ -15,6 +15,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::{
+    command::{filter_motion, filter_object},
+    jump_list::push_to_jump_list,
     motion::{self, first_non_whitespace, next_line_end, right, Motion},
     object::Object,
     state::{Mode, Operator},
@@ -210,6 +212,7 @@ pub fn normal_motion(
             Some(Operator::OppositeCase) => {
                 change_case_motion(vim, motion, times, CaseTarget::OppositeCase, cx)
             }
+            Some(Operator::Filter) => filter_motion(vim, motion, times, cx),
             Some(operator) => {
                 // Can't do anything for text objects, Ignoring
                 error!("Unexpected normal mode motion operator: {:?}", operator)
@@ -241,6 +244,7 @@ pub fn normal_object(object: Object, cx: &mut WindowContext) {
                 Some(Operator::OppositeCase) => {
                     change_case_object(vim, object, around, CaseTarget::OppositeCase, cx)
                 }
+                Some(Operator::Filter) => filter_object(vim, object, around, cx),
                 Some(Operator::AddSurrounds { target: None }) => {
                     waiting_operator = Some(Operator::AddSurrounds {
                         target: Some(SurroundsType::Object(object)),
@@ -277,7 +281,11 @@ pub(crate) fn move_cursor(
     times: Option<usize>,
     cx: &mut WindowContext,
 ) {
-    vim.update_active_editor(cx, |_, editor, cx| {
+    vim.update_active_editor(cx, |vim, editor, cx| {
+        if motion.is_jump() {
+            let position = editor.selections.newest_anchor().head();
+            push_to_jump_list(vim, editor, position, cx);
+        }
         let text_layout_details = editor.text_layout_details(cx);
         editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
             s.move_cursors_with(|map, cursor, goal| {
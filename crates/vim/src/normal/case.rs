@@ -151,7 +151,11 @@ where
                             cursor_positions.push(selection.start..selection.start);
                         }
                     }
-                    Mode::Insert | Mode::Normal | Mode::Replace => {
+                    Mode::Insert
+                    | Mode::Normal
+                    | Mode::Replace
+                    | Mode::HelixNormal
+                    | Mode::HelixSelect => {
                         let start = selection.start;
                         let mut end = start;
                         for _ in 0..count {
@@ -1,35 +1,93 @@
 use std::{ops::Range, sync::Arc};
 
+use anyhow::Context as _;
 use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     movement,
     scroll::Autoscroll,
-    Anchor, Bias, DisplayPoint,
+    Anchor, Bias, DisplayPoint, Editor,
 };
-use gpui::WindowContext;
-use language::SelectionGoal;
+use gpui::{AppContext, ViewContext, WindowContext};
+use language::{Point, SelectionGoal};
+use util::ResultExt;
 
 use crate::{
+    jump_list,
     motion::{self, Motion},
-    state::Mode,
+    persistence::VIM_DB,
+    state::{buffer_abs_path, FileMark, Mode},
     Vim,
 };
 
 pub fn create_mark(vim: &mut Vim, text: Arc<str>, tail: bool, cx: &mut WindowContext) {
-    let Some(anchors) = vim.update_active_editor(cx, |_, editor, _| {
-        editor
+    let Some((anchors, mark)) = vim.update_active_editor(cx, |_, editor, cx| {
+        let anchors = editor
             .selections
             .disjoint_anchors()
             .iter()
             .map(|s| if tail { s.tail() } else { s.head() })
-            .collect::<Vec<_>>()
+            .collect::<Vec<_>>();
+        let newest = editor.selections.newest_anchor();
+        let position = if tail { newest.tail() } else { newest.head() };
+        (anchors, FileMark::new(editor, position, cx))
     }) else {
         return;
     };
+
+    if is_global_mark(&text) {
+        if let Some(mark) = mark {
+            save_mark(&text, &mark, cx);
+            vim.workspace_state
+                .global_marks
+                .insert(text.to_string(), mark);
+        }
+        vim.clear_operator(cx);
+        return;
+    }
+
     vim.update_state(|state| state.marks.insert(text.to_string(), anchors));
+    if let Some(mark) = mark.filter(|_| is_file_mark(&text)) {
+        if let Some(path) = mark.path.clone() {
+            save_mark(&text, &mark, cx);
+            vim.workspace_state
+                .file_marks
+                .entry(path)
+                .or_default()
+                .insert(text.to_string(), mark.point);
+        }
+    }
     vim.clear_operator(cx);
 }
 
+/// Uppercase marks remember a file along with the position in it, and jump to it from any
+/// other file.
+pub(crate) fn is_global_mark(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_uppercase()) && chars.next().is_none()
+}
+
+/// Lowercase marks are local to a file, and kept for it once its editors are closed.
+fn is_file_mark(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_lowercase()) && chars.next().is_none()
+}
+
+/// Saves the mark, so that it is restored on the next start as with vim's viminfo.
+fn save_mark(name: &str, mark: &FileMark, cx: &AppContext) {
+    let Some(path) = mark.path.clone() else {
+        return;
+    };
+    cx.background_executor()
+        .spawn(VIM_DB.save_mark(
+            path.to_path_buf(),
+            name.to_string(),
+            mark.point.row,
+            mark.point.column,
+            is_global_mark(name),
+        ))
+        .detach_and_log_err(cx);
+}
+
 pub fn create_visual_marks(vim: &mut Vim, mode: Mode, cx: &mut WindowContext) {
     let mut starts = vec![];
     let mut ends = vec![];
@@ -60,32 +118,72 @@ pub fn create_visual_marks(vim: &mut Vim, mode: Mode, cx: &mut WindowContext) {
 }
 
 pub fn jump(text: Arc<str>, line: bool, cx: &mut WindowContext) {
-    let anchors = Vim::update(cx, |vim, cx| {
+    Vim::update(cx, |vim, cx| {
         vim.pop_operator(cx);
+    });
+    jump_to_mark(text, line, cx);
+}
 
-        match &*text {
-            "{" | "}" => vim.update_active_editor(cx, |_, editor, cx| {
-                let (map, selections) = editor.selections.all_display(cx);
-                selections
-                    .into_iter()
-                    .map(|selection| {
-                        let point = if &*text == "{" {
-                            movement::start_of_paragraph(&map, selection.head(), 1)
-                        } else {
-                            movement::end_of_paragraph(&map, selection.head(), 1)
-                        };
-                        map.buffer_snapshot
-                            .anchor_before(point.to_offset(&map, Bias::Left))
-                    })
-                    .collect::<Vec<Anchor>>()
-            }),
-            "." => vim.state().change_list.last().cloned(),
-            _ => vim.state().marks.get(&*text).cloned(),
+/// Moves to the mark, or applies the active operator up to it.
+pub(crate) fn jump_to_mark(text: Arc<str>, line: bool, cx: &mut WindowContext) {
+    if let Some(mark) = Vim::read(cx)
+        .workspace_state
+        .global_marks
+        .get(&*text)
+        .cloned()
+    {
+        let anchor = Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |_, editor, cx| anchor_in_editor(&mark, editor, cx))
+        })
+        .flatten();
+        match anchor {
+            Some(anchor) => jump_to_anchors(vec![anchor], line, cx),
+            // Operators only apply within a buffer.
+            None if Vim::read(cx).state().active_operator().is_none() => {
+                jump_to_file_mark(mark, line, false, cx)
+            }
+            None => {}
         }
+        return;
+    }
+
+    let anchors = Vim::update(cx, |vim, cx| match &*text {
+        "{" | "}" => vim.update_active_editor(cx, |_, editor, cx| {
+            let (map, selections) = editor.selections.all_display(cx);
+            selections
+                .into_iter()
+                .map(|selection| {
+                    let point = if &*text == "{" {
+                        movement::start_of_paragraph(&map, selection.head(), 1)
+                    } else {
+                        movement::end_of_paragraph(&map, selection.head(), 1)
+                    };
+                    map.buffer_snapshot
+                        .anchor_before(point.to_offset(&map, Bias::Left))
+                })
+                .collect::<Vec<Anchor>>()
+        }),
+        "." => vim.state().change_list.last().cloned(),
+        _ => vim.state().marks.get(&*text).cloned().or_else(|| {
+            vim.update_active_editor(cx, |vim, editor, cx| {
+                let buffer = editor.buffer().read(cx).as_singleton()?;
+                let path = buffer_abs_path(buffer.read(cx), cx)?;
+                let point = *vim.workspace_state.file_marks.get(&path)?.get(&*text)?;
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                Some(vec![
+                    snapshot.anchor_before(snapshot.clip_point(point, Bias::Left))
+                ])
+            })
+            .flatten()
+        }),
     });
 
-    let Some(anchors) = anchors else { return };
+    if let Some(anchors) = anchors {
+        jump_to_anchors(anchors, line, cx);
+    }
+}
 
+fn jump_to_anchors(anchors: Vec<Anchor>, line: bool, cx: &mut WindowContext) {
     let is_active_operator = Vim::read(cx).state().active_operator().is_some();
     if is_active_operator {
         if let Some(anchor) = anchors.last() {
@@ -100,7 +198,13 @@ pub fn jump(text: Arc<str>, line: bool, cx: &mut WindowContext) {
         return;
     } else {
         Vim::update(cx, |vim, cx| {
-            vim.update_active_editor(cx, |_, editor, cx| {
+            vim.update_active_editor(cx, |vim, editor, cx| {
+                jump_list::push_to_jump_list(
+                    vim,
+                    editor,
+                    editor.selections.newest_anchor().head(),
+                    cx,
+                );
                 let map = editor.snapshot(cx);
                 let mut ranges: Vec<Range<Anchor>> = Vec::new();
                 for mut anchor in anchors {
@@ -124,6 +228,115 @@ pub fn jump(text: Arc<str>, line: bool, cx: &mut WindowContext) {
     }
 }
 
+/// The position of the mark in the editor, when the editor shows the buffer of the mark.
+fn anchor_in_editor(mark: &FileMark, editor: &Editor, cx: &AppContext) -> Option<Anchor> {
+    let buffer = editor.buffer().read(cx).as_singleton()?;
+    if !mark.is_in(&buffer, cx) {
+        return None;
+    }
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    Some(snapshot.anchor_before(snapshot.clip_point(mark.point(cx), Bias::Left)))
+}
+
+/// Moves to the mark in whichever file it is in, opening the file if needed. Moves within the
+/// jump list don't add the position they leave to it.
+pub(crate) fn jump_to_file_mark(
+    mark: FileMark,
+    line: bool,
+    from_jump_list: bool,
+    cx: &mut WindowContext,
+) {
+    let Some(active_editor) = Vim::read(cx)
+        .active_editor
+        .clone()
+        .and_then(|editor| editor.upgrade())
+    else {
+        return;
+    };
+    if !from_jump_list {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |vim, editor, cx| {
+                jump_list::push_to_jump_list(
+                    vim,
+                    editor,
+                    editor.selections.newest_anchor().head(),
+                    cx,
+                )
+            })
+        });
+    }
+
+    let point = mark.point(cx);
+    let buffer = mark.buffer();
+    let active_buffer = active_editor.read(cx).buffer().read(cx).as_singleton();
+    if active_buffer.is_some_and(|active_buffer| mark.is_in(&active_buffer, cx)) {
+        active_editor.update(cx, |editor, cx| select_point(editor, point, line, cx));
+        return;
+    }
+
+    let Some(workspace) = active_editor.read(cx).workspace() else {
+        return;
+    };
+    Vim::update(cx, |vim, _| {
+        vim.workspace_state.navigating_jump_list = from_jump_list;
+    });
+    if let Some(buffer) = buffer {
+        let editor = workspace.update(cx, |workspace, cx| {
+            let existing_editor = workspace.items_of_type::<Editor>(cx).find(|editor| {
+                editor.read(cx).buffer().read(cx).as_singleton().as_ref() == Some(&buffer)
+            });
+            match existing_editor {
+                Some(editor) => {
+                    workspace.activate_item(&editor, cx);
+                    editor
+                }
+                None => workspace.open_project_item::<Editor>(
+                    workspace.active_pane().clone(),
+                    buffer,
+                    cx,
+                ),
+            }
+        });
+        editor.update(cx, |editor, cx| select_point(editor, point, line, cx));
+        return;
+    }
+
+    let Some(path) = mark.path else {
+        return;
+    };
+    let open_task = workspace.update(cx, |workspace, cx| {
+        workspace.open_abs_path(path.to_path_buf(), false, cx)
+    });
+    cx.spawn(|mut cx| async move {
+        let editor = open_task.await.and_then(|item| {
+            item.downcast::<Editor>()
+                .context("the marked file is not opened in an editor")
+        });
+        if editor.is_err() {
+            cx.update(|cx| {
+                Vim::update(cx, |vim, _| {
+                    vim.workspace_state.navigating_jump_list = false
+                })
+            })
+            .log_err();
+        }
+        editor?.update(&mut cx, |editor, cx| select_point(editor, point, line, cx))
+    })
+    .detach_and_log_err(cx);
+}
+
+fn select_point(editor: &mut Editor, point: Point, line: bool, cx: &mut ViewContext<Editor>) {
+    let map = editor.snapshot(cx).display_snapshot;
+    let point = map.buffer_snapshot.clip_point(point, Bias::Left);
+    let mut point = point.to_display_point(&map);
+    if line {
+        point = motion::first_non_whitespace(&map, false, point);
+    }
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_display_ranges([point..point])
+    });
+}
+
 pub fn jump_motion(
     map: &DisplaySnapshot,
     anchor: Anchor,
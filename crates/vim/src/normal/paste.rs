@@ -1,7 +1,7 @@
 use std::cmp;
 
 use editor::{display_map::ToDisplayPoint, movement, scroll::Autoscroll, DisplayPoint, RowExt};
-use gpui::{impl_actions, ViewContext};
+use gpui::{impl_actions, ViewContext, WindowContext};
 use language::{Bias, SelectionGoal};
 use serde::Deserialize;
 use workspace::Workspace;
@@ -12,9 +12,9 @@ use crate::{
     Vim,
 };
 
-#[derive(Clone, Deserialize, PartialEq)]
+#[derive(Clone, Default, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct Paste {
+pub(crate) struct Paste {
     #[serde(default)]
     before: bool,
     #[serde(default)]
@@ -24,10 +24,10 @@ struct Paste {
 impl_actions!(vim, [Paste]);
 
 pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
-    workspace.register_action(paste);
+    workspace.register_action(|_: &mut Workspace, action: &Paste, cx| paste(action, cx));
 }
 
-fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
+pub(crate) fn paste(action: &Paste, cx: &mut WindowContext) {
     Vim::update(cx, |vim, cx| {
         vim.record_current_action(cx);
         let count = vim.take_count(cx).unwrap_or(1);
@@ -54,6 +54,20 @@ fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
                     copy_selections_content(vim, editor, vim.state().mode == Mode::VisualLine, cx);
                 }
 
+                if vim.state().mode.is_helix() {
+                    // Helix pastes around its selections, where vim pastes around its cursor.
+                    editor.change_selections(None, cx, |s| {
+                        s.move_with(|map, selection| {
+                            let cursor = if action.before || selection.is_empty() {
+                                selection.start
+                            } else {
+                                movement::left(map, selection.end)
+                            };
+                            selection.collapse_to(cursor, SelectionGoal::None)
+                        })
+                    });
+                }
+
                 let (display_map, current_selections) = editor.selections.all_adjusted_display(cx);
 
                 // unlike zed, if you have a multi-cursor selection from vim block mode,
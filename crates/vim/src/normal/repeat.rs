@@ -1,14 +1,17 @@
+use std::sync::Arc;
+
 use crate::{
     insert::NormalBefore,
     motion::Motion,
-    state::{Mode, RecordedSelection, ReplayableAction},
+    state::{Mode, Operator, RecordedSelection, ReplayableAction},
     visual::visual_motion,
     Vim,
 };
-use gpui::{actions, Action, ViewContext, WindowContext};
+use gpui::{actions, Action, ReadGlobal, UpdateGlobal, ViewContext, WindowContext};
+use keyboard_macros::MacroRecorder;
 use workspace::Workspace;
 
-actions!(vim, [Repeat, EndRepeat]);
+actions!(vim, [Repeat, EndRepeat, ToggleRecord, ReplayLastRecording]);
 
 fn should_replay(action: &Box<dyn Action>) -> bool {
     // skip so that we don't leave the character palette open
@@ -48,6 +51,60 @@ pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>
     });
 
     workspace.register_action(|_: &mut Workspace, _: &Repeat, cx| repeat(cx, false));
+
+    workspace.register_action(|_: &mut Workspace, _: &ToggleRecord, cx| {
+        if !MacroRecorder::update_global(cx, |recorder, _| recorder.stop_recording()) {
+            Vim::update(cx, |vim, cx| {
+                vim.push_operator(Operator::RecordRegister, cx)
+            });
+        }
+    });
+
+    workspace.register_action(|_: &mut Workspace, _: &ReplayLastRecording, cx| {
+        let count = Vim::update(cx, |vim, cx| vim.take_count(cx)).unwrap_or(1);
+        if let Some(steps) = MacroRecorder::global(cx).last_macro().map(<[_]>::to_vec) {
+            MacroRecorder::replay(&steps, count, cx);
+        }
+    });
+}
+
+/// Starts recording a macro into the given register, appending to it for uppercase registers.
+pub(crate) fn record_register(vim: &mut Vim, text: Arc<str>, cx: &mut WindowContext) {
+    vim.clear_operator(cx);
+    let Some(register) = text
+        .chars()
+        .next()
+        .filter(|register| register.is_ascii_alphanumeric() || *register == '"')
+    else {
+        return;
+    };
+    MacroRecorder::update_global(cx, |recorder, _| {
+        recorder.start_recording(
+            Some(register.to_ascii_lowercase()),
+            register.is_ascii_uppercase(),
+        )
+    });
+}
+
+/// Replays the macro in the given register, or in the register replayed last for `@`.
+pub(crate) fn replay_register(vim: &mut Vim, text: Arc<str>, cx: &mut WindowContext) {
+    let count = vim.take_count(cx).unwrap_or(1);
+    vim.clear_operator(cx);
+    let register = match text.chars().next() {
+        Some('@') => vim.workspace_state.last_replayed_register,
+        register => register.map(|register| register.to_ascii_lowercase()),
+    };
+    let Some(register) = register else {
+        return;
+    };
+    let Some(steps) = MacroRecorder::global(cx)
+        .register(register)
+        .map(<[_]>::to_vec)
+    else {
+        return;
+    };
+    vim.workspace_state.last_replayed_register = Some(register);
+    MacroRecorder::replay(&steps, count, cx);
 }
 
 pub(crate) fn repeat(cx: &mut WindowContext, from_insert_mode: bool) {
@@ -497,4 +554,109 @@ mod test {
         cx.simulate_shared_keystrokes(".").await;
         cx.shared_state().await.assert_eq("ˇx hello\n");
     }
+
+    #[gpui::test]
+    async fn test_record_replay(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+            three
+            four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("q a shift-a ; escape j q");
+        cx.assert_state(
+            indoc! {"
+            one;
+            twˇo
+            three
+            four"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes("2 @ a");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            one;
+            two;
+            three;
+            fouˇr"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes("@ @");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            one;
+            two;
+            three;
+            fourˇ;"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_replay_many_times(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇ", Mode::Normal);
+        cx.simulate_keystrokes("q a shift-a x escape q");
+        cx.assert_state("ˇx", Mode::Normal);
+
+        // Long replays run to the end, however many steps they take.
+        cx.simulate_keystrokes("4 0 0 0 @ a");
+        cx.run_until_parked();
+        cx.assert_state(&format!("{}ˇx", "x".repeat(4000)), Mode::Normal);
+        cx.workspace(|workspace, _| assert!(workspace.notification_ids().is_empty()));
+    }
+
+    #[gpui::test]
+    async fn test_replay_recursive_macro(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇabc", Mode::Normal);
+        cx.simulate_keystrokes("q a x @ a q");
+        cx.assert_state("ˇbc", Mode::Normal);
+
+        // The macro keeps replaying itself once the line is empty, until it is stopped.
+        cx.simulate_keystrokes("@ a");
+        cx.run_until_parked();
+        cx.assert_state("ˇ", Mode::Normal);
+        cx.workspace(|workspace, _| assert_eq!(workspace.notification_ids().len(), 1));
+
+        cx.set_state("ˇabc", Mode::Normal);
+        cx.simulate_keystrokes("@ a");
+        cx.run_until_parked();
+        cx.assert_state("ˇ", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_record_append_and_repeat(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa b c d e", Mode::Normal);
+        cx.simulate_keystrokes("q b d w q");
+        cx.assert_state("ˇb c d e", Mode::Normal);
+        cx.simulate_keystrokes("q shift-b x q");
+        cx.assert_state("ˇ c d e", Mode::Normal);
+
+        cx.simulate_keystrokes("@ b");
+        cx.run_until_parked();
+        cx.assert_state("ˇ d e", Mode::Normal);
+
+        // The changes made by a macro are repeated by `.`.
+        cx.simulate_keystrokes(".");
+        cx.run_until_parked();
+        cx.assert_state("ˇd e", Mode::Normal);
+
+        // `shift-q` replays the macro recorded last.
+        cx.simulate_keystrokes("shift-q");
+        cx.run_until_parked();
+        cx.assert_state("ˇ", Mode::Normal);
+    }
 }
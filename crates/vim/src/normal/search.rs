@@ -8,6 +8,7 @@ use serde_derive::Deserialize;
 use workspace::{searchable::Direction, Workspace};
 
 use crate::{
+    helix,
     motion::{search_motion, Motion},
     normal::move_cursor,
     state::{Mode, SearchState},
@@ -46,11 +47,11 @@ pub struct ReplaceCommand {
 }
 
 #[derive(Debug, Default)]
-struct Replacement {
-    search: String,
-    replacement: String,
-    should_replace_all: bool,
-    is_case_sensitive: bool,
+pub(crate) struct Replacement {
+    pub(crate) search: String,
+    pub(crate) replacement: String,
+    pub(crate) should_replace_all: bool,
+    pub(crate) is_case_sensitive: bool,
     range: Option<Range<usize>>,
 }
 
@@ -134,6 +135,7 @@ fn search(workspace: &mut Workspace, action: &Search, cx: &mut ViewContext<Works
                         prior_selections,
                         prior_operator: vim.active_operator(),
                         prior_mode: vim.state().mode,
+                        select_matches: false,
                     };
                 });
             }
@@ -148,6 +150,10 @@ fn search_deploy(_: &mut Workspace, _: &buffer_search::Deploy, cx: &mut ViewCont
 }
 
 fn search_submit(workspace: &mut Workspace, _: &SearchSubmit, cx: &mut ViewContext<Workspace>) {
+    if Vim::read(cx).workspace_state.search.select_matches {
+        helix::select_matches_submit(workspace, cx);
+        return;
+    }
     let mut motion = None;
     Vim::update(cx, |vim, cx| {
         let pane = workspace.active_pane().clone();
@@ -338,7 +344,7 @@ fn find_command(workspace: &mut Workspace, action: &FindCommand, cx: &mut ViewCo
     })
 }
 
-fn replace_command(
+pub(crate) fn replace_command(
     workspace: &mut Workspace,
     action: &ReplaceCommand,
     cx: &mut ViewContext<Workspace>,
@@ -423,7 +429,7 @@ fn replace_command(
 // we don't attempt to fully convert between the two regex syntaxes,
 // but we do flip \( and \) to ( and ) (and vice-versa) in the pattern,
 // and convert \0..\9 to $0..$9 in the replacement so that common idioms work.
-fn parse_replace_all(query: &str) -> Replacement {
+pub(crate) fn parse_replace_all(query: &str) -> Replacement {
     let mut chars = query.chars();
     let mut range = None;
     let maybe_line_range_and_rest: Option<(Range<usize>, &str)> =
@@ -1,6 +1,7 @@
 use std::ops::Range;
 
 use crate::{
+    helix::helix_object,
     motion::{coerce_punctuation, right},
     normal::normal_object,
     state::Mode,
@@ -10,13 +11,13 @@ use crate::{
 use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     movement::{self, FindRange},
-    Bias, DisplayPoint,
+    text_objects, Bias, DisplayPoint, ToOffset, ToPoint,
 };
 
 use itertools::Itertools;
 
 use gpui::{actions, impl_actions, ViewContext, WindowContext};
-use language::{char_kind, BufferSnapshot, CharKind, Point, Selection};
+use language::{char_kind, BufferSnapshot, CharKind, Point, Selection, TextObject};
 use multi_buffer::MultiBufferRow;
 use serde::Deserialize;
 use workspace::Workspace;
@@ -36,6 +37,9 @@ pub enum Object {
     AngleBrackets,
     Argument,
     Tag,
+    Function,
+    Class,
+    Comment,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
@@ -61,7 +65,10 @@ actions!(
         CurlyBrackets,
         AngleBrackets,
         Argument,
-        Tag
+        Tag,
+        Function,
+        Class,
+        Comment
     ]
 );
 
@@ -99,11 +106,16 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     });
     workspace
         .register_action(|_: &mut Workspace, _: &Argument, cx: _| object(Object::Argument, cx));
+    workspace
+        .register_action(|_: &mut Workspace, _: &Function, cx: _| object(Object::Function, cx));
+    workspace.register_action(|_: &mut Workspace, _: &Class, cx: _| object(Object::Class, cx));
+    workspace.register_action(|_: &mut Workspace, _: &Comment, cx: _| object(Object::Comment, cx));
 }
 
 fn object(object: Object, cx: &mut WindowContext) {
     match Vim::read(cx).state().mode {
         Mode::Normal => normal_object(object, cx),
+        Mode::HelixNormal | Mode::HelixSelect => helix_object(object, cx),
         Mode::Visual | Mode::VisualLine | Mode::VisualBlock => visual_object(object, cx),
         Mode::Insert | Mode::Replace => {
             // Shouldn't execute a text object in insert mode. Ignoring
@@ -126,7 +138,10 @@ impl Object {
             | Object::AngleBrackets
             | Object::CurlyBrackets
             | Object::SquareBrackets
-            | Object::Argument => true,
+            | Object::Argument
+            | Object::Function
+            | Object::Class
+            | Object::Comment => true,
         }
     }
 
@@ -141,7 +156,10 @@ impl Object {
             | Object::SquareBrackets
             | Object::Tag
             | Object::CurlyBrackets
-            | Object::AngleBrackets => true,
+            | Object::AngleBrackets
+            | Object::Function
+            | Object::Class
+            | Object::Comment => true,
         }
     }
 
@@ -164,7 +182,10 @@ impl Object {
             | Object::AngleBrackets
             | Object::VerticalBars
             | Object::Tag
-            | Object::Argument => Mode::Visual,
+            | Object::Argument
+            | Object::Function
+            | Object::Class
+            | Object::Comment => Mode::Visual,
             Object::Paragraph => Mode::VisualLine,
         }
     }
@@ -212,6 +233,30 @@ impl Object {
                 surrounding_markers(map, relative_to, around, self.is_multiline(), '<', '>')
             }
             Object::Argument => argument(map, relative_to, around),
+            Object::Function => {
+                let text_object = if around {
+                    TextObject::AroundFunction
+                } else {
+                    TextObject::InsideFunction
+                };
+                text_object_range(map, relative_to, text_object)
+            }
+            Object::Class => {
+                let text_object = if around {
+                    TextObject::AroundClass
+                } else {
+                    TextObject::InsideClass
+                };
+                text_object_range(map, relative_to, text_object)
+            }
+            Object::Comment => {
+                let text_object = if around {
+                    TextObject::AroundComment
+                } else {
+                    TextObject::InsideComment
+                };
+                text_object_range(map, relative_to, text_object)
+            }
         }
     }
 
@@ -555,14 +600,54 @@ fn argument(
         Some(start..end)
     }
 
-    let result = comma_delimited_range_at(buffer, excerpt.map_offset_to_buffer(offset), around)?;
+    let result = comma_delimited_range_at(buffer, excerpt.map_offset_to_buffer(offset), around)
+        .filter(|result| excerpt.contains_buffer_range(result.clone()))
+        .map(|result| excerpt.map_range_from_buffer(result));
 
-    if excerpt.contains_buffer_range(result.clone()) {
-        let result = excerpt.map_range_from_buffer(result);
-        Some(result.start.to_display_point(map)..result.end.to_display_point(map))
+    // The `textobjects` query of the language can also capture parameters that are not
+    // delimited by brackets, such as the ones of Python lambdas.
+    let text_object = if around {
+        TextObject::AroundParameter
     } else {
-        None
+        TextObject::InsideParameter
+    };
+    let parameter = text_objects::enclosing_text_object(snapshot, offset..offset, text_object);
+    let result = match (result, parameter) {
+        (Some(result), Some(parameter)) if parameter.len() < result.len() => parameter,
+        (result, parameter) => result.or(parameter)?,
+    };
+    Some(result.start.to_display_point(map)..result.end.to_display_point(map))
+}
+
+/// Returns the range of the text object captured by the `textobjects` query of the language
+/// around `relative_to`. Functions, classes and comments that span whole lines are selected
+/// along with their indentation and trailing newline, so that deleting them leaves no blank
+/// line behind.
+fn text_object_range(
+    map: &DisplaySnapshot,
+    relative_to: DisplayPoint,
+    text_object: TextObject,
+) -> Option<Range<DisplayPoint>> {
+    let snapshot = &map.buffer_snapshot;
+    let offset = relative_to.to_offset(map, Bias::Left);
+    let mut range = text_objects::enclosing_text_object(snapshot, offset..offset, text_object)?;
+
+    if matches!(
+        text_object,
+        TextObject::AroundFunction | TextObject::AroundClass | TextObject::AroundComment
+    ) {
+        let start = range.start.to_point(snapshot);
+        let end = range.end.to_point(snapshot);
+        let starts_line =
+            start.column == snapshot.indent_size_for_line(MultiBufferRow(start.row)).len;
+        let ends_line = end.column == snapshot.line_len(MultiBufferRow(end.row));
+        if starts_line && ends_line && end.row < snapshot.max_point().row {
+            range = Point::new(start.row, 0).to_offset(snapshot)
+                ..Point::new(end.row + 1, 0).to_offset(snapshot);
+        }
     }
+
+    Some(range.start.to_display_point(map)..range.end.to_display_point(map))
 }
 
 fn sentence(
@@ -1437,6 +1522,109 @@ mod test {
         cx.assert_state("let a = [«test::call(first_arg)ˇ»]", Mode::Visual);
     }
 
+    #[gpui::test]
+    async fn test_function_object(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                fn a() {
+                    let x = 1;
+                }
+
+                fn b() {
+                    let ˇy = 2;
+                }
+                fn c() {}
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("d a f");
+        cx.assert_state(
+            indoc! {"
+                fn a() {
+                    let x = 1;
+                }
+
+                ˇfn c() {}
+            "},
+            Mode::Normal,
+        );
+
+        // The body is deleted from the signature too
+        cx.set_state(
+            indoc! {"
+                fn ˇb() {
+                    let y = 2;
+                }
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("d i f");
+        cx.assert_state(
+            indoc! {"
+                fn b() {
+                ˇ}
+            "},
+            Mode::Normal,
+        );
+
+        // Closures are functions too
+        cx.set_state("let f = |x| { x ˇ+ 1 };", Mode::Normal);
+        cx.simulate_keystrokes("c i f");
+        cx.assert_state("let f = |x| {ˇ};", Mode::Insert);
+
+        cx.set_state("let f = |x| { x ˇ+ 1 };", Mode::Normal);
+        cx.simulate_keystrokes("v a f");
+        cx.assert_state("let f = «|x| { x + 1 }ˇ»;", Mode::Visual);
+    }
+
+    #[gpui::test]
+    async fn test_class_and_comment_objects(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                impl Foo {
+                    fn a(&self) {
+                        ˇself.b();
+                    }
+                }
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("d i c");
+        cx.assert_state(
+            indoc! {"
+                impl Foo {
+                ˇ}
+            "},
+            Mode::Normal,
+        );
+
+        cx.set_state(
+            indoc! {"
+                struct Foo {
+                    a: ˇu8,
+                }
+                struct Bar;
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("d a c");
+        cx.assert_state("ˇstruct Bar;\n", Mode::Normal);
+
+        cx.set_state(
+            indoc! {"
+                // The first ˇfunction
+                fn a() {}
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("d a g c");
+        cx.assert_state("ˇfn a() {}\n", Mode::Normal);
+    }
+
     #[gpui::test]
     async fn test_delete_surrounding_character_objects(cx: &mut gpui::TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
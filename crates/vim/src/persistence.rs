@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result};
+use db::{define_connection, query, sqlez_macros::sql};
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // vim_registers(
+    //   name: String, // A single character, like `a` or `"`
+    //   text: String,
+    //   clipboard_selections: Option<String>, // JSON serialized `Vec<ClipboardSelection>`
+    // )
+    //
+    // vim_marks(
+    //   path: PathBuf,
+    //   name: String, // Uppercase marks are set in a single file at a time
+    //   row: u32,
+    //   column: u32,
+    // )
+    pub static ref VIM_DB: VimDb<()> =
+        &[sql!(
+            CREATE TABLE IF NOT EXISTS vim_registers(
+                name TEXT PRIMARY KEY,
+                text TEXT NOT NULL,
+                clipboard_selections TEXT
+            ) STRICT;
+            CREATE TABLE IF NOT EXISTS vim_marks(
+                path BLOB NOT NULL,
+                name TEXT NOT NULL,
+                row INTEGER NOT NULL,
+                column INTEGER NOT NULL,
+                PRIMARY KEY(path, name)
+            ) STRICT;
+        )];
+);
+
+impl VimDb {
+    query! {
+        pub fn saved_registers() -> Result<Vec<(String, String, Option<String>)>> {
+            SELECT name, text, clipboard_selections FROM vim_registers
+        }
+    }
+
+    pub async fn save_registers(
+        &self,
+        registers: Vec<(String, String, Option<String>)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_vim_registers", || {
+                for (name, text, clipboard_selections) in registers {
+                    conn.exec_bound(sql!(
+                        INSERT OR REPLACE INTO vim_registers
+                            (name, text, clipboard_selections)
+                        VALUES
+                            (?1, ?2, ?3)
+                    ))?((name, text, clipboard_selections))
+                    .context("saving vim register")?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn saved_marks() -> Result<Vec<(PathBuf, String, u32, u32)>> {
+            SELECT path, name, row, column FROM vim_marks
+        }
+    }
+
+    pub async fn save_mark(
+        &self,
+        path: PathBuf,
+        name: String,
+        row: u32,
+        column: u32,
+        global: bool,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_vim_mark", || {
+                if global {
+                    conn.exec_bound(sql!(
+                        DELETE FROM vim_marks WHERE name = ?
+                    ))?(name.as_str())
+                    .context("clearing global vim mark")?;
+                }
+                conn.exec_bound(sql!(
+                    INSERT OR REPLACE INTO vim_marks
+                        (path, name, row, column)
+                    VALUES
+                        (?1, ?2, ?3, ?4)
+                ))?((path.as_path(), name, row, column))
+                .context("saving vim mark")
+            })
+        })
+        .await
+    }
+}
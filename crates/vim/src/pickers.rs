@@ -0,0 +1,410 @@
+use std::sync::Arc;
+
+use editor::{Editor, ToPoint};
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{actions, rems, AppContext, DismissEvent, Task, View};
+use language::Point;
+use multi_buffer::MultiBufferRow;
+use picker::{highlighted_match_with_paths::HighlightedText, Picker, PickerDelegate};
+use ui::{prelude::*, ListItem, ListItemSpacing, ViewContext, WindowContext};
+use util::ResultExt;
+use workspace::Workspace;
+
+use crate::{
+    jump_list,
+    normal::{
+        mark,
+        paste::{self, Paste},
+    },
+    state::{buffer_abs_path, FileMark},
+    Vim,
+};
+
+actions!(vim, [ListMarks, ListRegisters, ListJumps]);
+
+/// The number of characters of a register shown in the list of registers.
+const REGISTER_PREVIEW_LENGTH: usize = 80;
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|workspace, _: &ListMarks, cx| {
+        let entries = Vim::update(cx, |vim, cx| mark_entries(vim, cx));
+        ListPicker::toggle(workspace, entries, "Mark to jump to…", cx);
+    });
+    workspace.register_action(|workspace, _: &ListRegisters, cx| {
+        let entries = register_entries(Vim::read(cx));
+        ListPicker::toggle(workspace, entries, "Register to paste…", cx);
+    });
+    workspace.register_action(|workspace, _: &ListJumps, cx| {
+        let entries = Vim::update(cx, |vim, cx| jump_entries(vim, cx));
+        ListPicker::toggle(workspace, entries, "Position to jump back to…", cx);
+    });
+}
+
+#[derive(Clone)]
+enum ListTarget {
+    Mark(Arc<str>),
+    Register(char),
+    JumpListEntry(usize),
+}
+
+struct ListEntry {
+    label: String,
+    target: ListTarget,
+}
+
+/// Lists the marks of the active editor, followed by the global marks, like `:marks`.
+fn mark_entries(vim: &Vim, cx: &AppContext) -> Vec<ListEntry> {
+    let active_editor = active_editor(vim);
+    let mut marks = Vec::new();
+    if let Some(editor) = active_editor.as_ref().map(|editor| editor.read(cx)) {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let mut names = vim.state().marks.keys().collect::<Vec<_>>();
+        names.sort();
+        for name in names {
+            let Some(anchor) = vim.state().marks[name].first() else {
+                continue;
+            };
+            let point = anchor.to_point(&snapshot);
+            marks.push((name.clone(), point, line_text(editor, point.row, cx)));
+        }
+
+        let path = editor
+            .buffer()
+            .read(cx)
+            .as_singleton()
+            .and_then(|buffer| buffer_abs_path(buffer.read(cx), cx));
+        if let Some(file_marks) = path.and_then(|path| vim.workspace_state.file_marks.get(&path)) {
+            let mut names = file_marks
+                .keys()
+                .filter(|name| !vim.state().marks.contains_key(*name))
+                .collect::<Vec<_>>();
+            names.sort();
+            for name in names {
+                let point = file_marks[name];
+                marks.push((name.clone(), point, line_text(editor, point.row, cx)));
+            }
+        }
+    }
+
+    let mut global_marks = vim.workspace_state.global_marks.iter().collect::<Vec<_>>();
+    global_marks.sort_by(|(name, _), (other_name, _)| name.cmp(other_name));
+    for (name, mark) in global_marks {
+        marks.push((
+            name.clone(),
+            mark.point(cx),
+            describe_file_mark(mark, active_editor.as_ref(), cx),
+        ));
+    }
+
+    marks
+        .into_iter()
+        .map(|(name, point, description)| ListEntry {
+            label: format!(
+                "{name} {:>5}:{:<4} {description}",
+                point.row + 1,
+                point.column + 1
+            ),
+            target: ListTarget::Mark(name.into()),
+        })
+        .collect()
+}
+
+/// Lists the registers that are set, like `:registers`.
+fn register_entries(vim: &Vim) -> Vec<ListEntry> {
+    let mut registers = vim.workspace_state.registers.iter().collect::<Vec<_>>();
+    registers.sort_by_key(|(name, _)| {
+        (
+            **name != '"',
+            !name.is_ascii_digit(),
+            !name.is_ascii_lowercase(),
+            **name,
+        )
+    });
+    registers
+        .into_iter()
+        .filter(|(_, register)| !register.text.is_empty())
+        .map(|(name, register)| {
+            let preview = register
+                .text
+                .chars()
+                .take(REGISTER_PREVIEW_LENGTH)
+                .collect::<String>()
+                .replace('\n', "^J");
+            ListEntry {
+                label: format!("\"{name}  {preview}"),
+                target: ListTarget::Register(*name),
+            }
+        })
+        .collect()
+}
+
+/// Lists the jump list from its newest entry, like `:jumps`, with the number of jumps back
+/// to each entry.
+fn jump_entries(vim: &mut Vim, cx: &mut WindowContext) -> Vec<ListEntry> {
+    // Once the cursor position is in the jump list, its entries don't move when jumping.
+    jump_list::start_navigating(vim, cx);
+    let position = vim.workspace_state.jump_list_position.unwrap_or_default();
+    let active_editor = active_editor(vim);
+    vim.workspace_state
+        .jump_list
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(index, mark)| {
+            let point = mark.point(cx);
+            ListEntry {
+                label: format!(
+                    "{:>3} {:>5}:{:<4} {}",
+                    position.abs_diff(index),
+                    point.row + 1,
+                    point.column + 1,
+                    describe_file_mark(mark, active_editor.as_ref(), cx)
+                ),
+                target: ListTarget::JumpListEntry(index),
+            }
+        })
+        .collect()
+}
+
+fn active_editor(vim: &Vim) -> Option<View<Editor>> {
+    vim.active_editor.as_ref()?.upgrade()
+}
+
+/// The text of the line a mark is on when it is in the active editor, as vim shows, and the
+/// path of its file otherwise.
+fn describe_file_mark(
+    mark: &FileMark,
+    active_editor: Option<&View<Editor>>,
+    cx: &AppContext,
+) -> String {
+    if let Some(editor) = active_editor.map(|editor| editor.read(cx)) {
+        let buffer = editor.buffer().read(cx).as_singleton();
+        if buffer.is_some_and(|buffer| mark.is_in(&buffer, cx)) {
+            return line_text(editor, mark.point(cx).row, cx);
+        }
+    }
+    match &mark.path {
+        Some(path) => path.to_string_lossy().into_owned(),
+        None => "[No Name]".to_string(),
+    }
+}
+
+fn line_text(editor: &Editor, row: u32, cx: &AppContext) -> String {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let row = row.min(snapshot.max_point().row);
+    let line_end = snapshot.line_len(MultiBufferRow(row));
+    snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, line_end))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Lists marks, registers or jump list entries, and picks one to jump to or paste.
+struct ListPicker;
+
+impl ListPicker {
+    fn toggle(
+        workspace: &mut Workspace,
+        entries: Vec<ListEntry>,
+        placeholder_text: &str,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let placeholder_text = Arc::from(placeholder_text);
+        workspace.toggle_modal(cx, |cx| {
+            let delegate = ListPickerDelegate {
+                entries,
+                placeholder_text,
+                matches: Vec::new(),
+                selected_index: 0,
+            };
+            Picker::uniform_list(delegate, cx).width(rems(34.))
+        });
+    }
+}
+
+struct ListPickerDelegate {
+    entries: Vec<ListEntry>,
+    placeholder_text: Arc<str>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for ListPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        self.placeholder_text.clone()
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        "Nothing to list".into()
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| StringMatchCandidate::new(id, entry.label.clone()))
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        score: 0.0,
+                        positions: Vec::new(),
+                        string: candidate.string,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    1000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.selected_index = 0;
+                    delegate.matches = matches;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(target) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|selected| self.entries.get(selected.candidate_id))
+            .map(|entry| entry.target.clone())
+        else {
+            return;
+        };
+        cx.emit(DismissEvent);
+        // Jump or paste once the focus is back in the editor.
+        cx.window_context().defer(move |cx| match target {
+            ListTarget::Mark(name) => mark::jump_to_mark(name, false, cx),
+            ListTarget::Register(name) => {
+                Vim::update(cx, |vim, cx| {
+                    vim.select_register(name.to_string().into(), cx)
+                });
+                paste::paste(&Paste::default(), cx);
+            }
+            ListTarget::JumpListEntry(index) => jump_list::jump_to_entry(index, cx),
+        });
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        let highlighted_label = HighlightedText {
+            text: hit.string.clone(),
+            highlight_positions: hit.positions.clone(),
+            char_count: hit.string.chars().count(),
+            color: Color::Default,
+        };
+        Some(
+            ListItem::new(SharedString::from(format!("vim-list-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(highlighted_label.render(cx)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use picker::Picker;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    use super::ListPickerDelegate;
+
+    fn listed_labels(cx: &mut VimTestContext) -> Vec<String> {
+        let picker = cx.workspace(|workspace, cx| {
+            workspace
+                .active_modal::<Picker<ListPickerDelegate>>(cx)
+                .expect("list picker is open")
+        });
+        cx.update_view(picker, |picker, _| {
+            picker
+                .delegate
+                .matches
+                .iter()
+                .map(|hit| hit.string.clone())
+                .collect()
+        })
+    }
+
+    #[gpui::test]
+    async fn test_list_marks_and_registers(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+            three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("j m a j y y");
+
+        cx.simulate_keystrokes(": m a r k s enter");
+        cx.run_until_parked();
+        assert_eq!(listed_labels(&mut cx), ["a     2:1    two"]);
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            one
+            ˇtwo
+            three"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(": r e g enter");
+        cx.run_until_parked();
+        assert_eq!(listed_labels(&mut cx), ["\"\"  three^J", "\"0  three^J"]);
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            one
+            two
+            ˇthree
+            three"},
+            Mode::Normal,
+        );
+    }
+}
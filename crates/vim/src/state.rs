@@ -1,11 +1,11 @@
-use std::{fmt::Display, ops::Range, sync::Arc};
+use std::{fmt::Display, ops::Range, path::Path, sync::Arc};
 
 use crate::surrounds::SurroundsType;
 use crate::{motion::Motion, object::Object};
 use collections::HashMap;
-use editor::{Anchor, ClipboardSelection};
-use gpui::{Action, ClipboardItem, KeyContext};
-use language::{CursorShape, Selection, TransactionId};
+use editor::{Anchor, ClipboardSelection, Editor};
+use gpui::{Action, AppContext, ClipboardItem, KeyContext, Model, WeakModel};
+use language::{Buffer, CursorShape, Point, Selection, TransactionId};
 use serde::{Deserialize, Serialize};
 use ui::SharedString;
 use workspace::searchable::Direction;
@@ -18,6 +18,10 @@ pub enum Mode {
     Visual,
     VisualLine,
     VisualBlock,
+    /// Helix's normal mode, where motions select text and actions apply to the selections.
+    HelixNormal,
+    /// Helix's select mode, where motions extend the selections rather than replacing them.
+    HelixSelect,
 }
 
 impl Display for Mode {
@@ -29,6 +33,8 @@ impl Display for Mode {
             Mode::Visual => write!(f, "VISUAL"),
             Mode::VisualLine => write!(f, "VISUAL LINE"),
             Mode::VisualBlock => write!(f, "VISUAL BLOCK"),
+            Mode::HelixNormal => write!(f, "HELIX NORMAL"),
+            Mode::HelixSelect => write!(f, "HELIX SELECT"),
         }
     }
 }
@@ -36,10 +42,16 @@ impl Display for Mode {
 impl Mode {
     pub fn is_visual(&self) -> bool {
         match self {
-            Mode::Normal | Mode::Insert | Mode::Replace => false,
+            Mode::Normal | Mode::Insert | Mode::Replace | Mode::HelixNormal | Mode::HelixSelect => {
+                false
+            }
             Mode::Visual | Mode::VisualLine | Mode::VisualBlock => true,
         }
     }
+
+    pub fn is_helix(&self) -> bool {
+        matches!(self, Mode::HelixNormal | Mode::HelixSelect)
+    }
 }
 
 impl Default for Mode {
@@ -67,7 +79,10 @@ pub enum Operator {
     Lowercase,
     Uppercase,
     OppositeCase,
+    Filter,
     Register,
+    RecordRegister,
+    ReplayRegister,
 }
 
 #[derive(Default, Clone)]
@@ -164,6 +179,84 @@ pub struct WorkspaceState {
 
     pub last_yank: Option<SharedString>,
     pub registers: HashMap<char, Register>,
+    /// The register of the macro replayed last, replayed again by `@@`.
+    pub last_replayed_register: Option<char>,
+
+    /// The uppercase marks, which are set in one file and jump to it from any other.
+    pub global_marks: HashMap<String, FileMark>,
+    /// The lowercase marks set in each file, kept once its editors are closed.
+    pub file_marks: HashMap<Arc<Path>, HashMap<String, Point>>,
+
+    pub jump_list: Vec<FileMark>,
+    pub jump_list_position: Option<usize>,
+    /// Set while the jump list switches to the editor of one of its entries, so that
+    /// leaving the previous editor isn't recorded as a jump.
+    pub navigating_jump_list: bool,
+}
+
+/// A position in a file, as remembered by global marks and the jump list.
+#[derive(Clone, Debug)]
+pub struct FileMark {
+    /// The buffer the position is in and where it is in it, following the edits made to it.
+    pub anchor: Option<(WeakModel<Buffer>, language::Anchor)>,
+    /// The absolute path of the file, to open it again once its buffer is closed.
+    pub path: Option<Arc<Path>>,
+    /// The position when the mark was set, used once the buffer is closed.
+    pub point: Point,
+}
+
+impl FileMark {
+    /// The mark at the given position of the editor, in the buffer of the excerpt there.
+    pub fn new(editor: &Editor, position: Anchor, cx: &AppContext) -> Option<Self> {
+        let (buffer, anchor) = editor
+            .buffer()
+            .read(cx)
+            .text_anchor_for_position(position, cx)?;
+        let point = buffer.read(cx).summary_for_anchor(&anchor);
+        let path = buffer_abs_path(buffer.read(cx), cx);
+        Some(Self {
+            anchor: Some((buffer.downgrade(), anchor)),
+            path,
+            point,
+        })
+    }
+
+    pub fn buffer(&self) -> Option<Model<Buffer>> {
+        self.anchor.as_ref()?.0.upgrade()
+    }
+
+    /// The current position of the mark.
+    pub fn point(&self, cx: &AppContext) -> Point {
+        match &self.anchor {
+            Some((buffer, anchor)) => buffer
+                .upgrade()
+                .map(|buffer| buffer.read(cx).summary_for_anchor(anchor))
+                .unwrap_or(self.point),
+            None => self.point,
+        }
+    }
+
+    /// Whether the mark is in the given buffer.
+    pub fn is_in(&self, buffer: &Model<Buffer>, cx: &AppContext) -> bool {
+        match self.buffer() {
+            Some(marked_buffer) => marked_buffer == *buffer,
+            None => self.path.is_some() && buffer_abs_path(buffer.read(cx), cx) == self.path,
+        }
+    }
+
+    /// Whether both marks are on the same line of the same file.
+    pub fn same_line(&self, other: &FileMark, cx: &AppContext) -> bool {
+        let same_file = match (self.buffer(), other.buffer()) {
+            (Some(buffer), Some(other_buffer)) => buffer == other_buffer,
+            _ => self.path.is_some() && self.path == other.path,
+        };
+        same_file && self.point(cx).row == other.point(cx).row
+    }
+}
+
+pub fn buffer_abs_path(buffer: &Buffer, cx: &AppContext) -> Option<Arc<Path>> {
+    let file = buffer.file()?.as_local()?;
+    Some(file.abs_path(cx).into())
 }
 
 #[derive(Debug)]
@@ -199,12 +292,15 @@ pub struct SearchState {
     pub prior_selections: Vec<Range<Anchor>>,
     pub prior_operator: Option<Operator>,
     pub prior_mode: Mode,
+    /// Whether submitting the search selects its matches within the prior selections, as
+    /// Helix's `s` does, rather than moving to the next match.
+    pub select_matches: bool,
 }
 
 impl EditorState {
     pub fn cursor_shape(&self) -> CursorShape {
         match self.mode {
-            Mode::Normal => {
+            Mode::Normal | Mode::HelixNormal | Mode::HelixSelect => {
                 if self.operator_stack.is_empty() {
                     CursorShape::Block
                 } else {
@@ -228,6 +324,8 @@ impl EditorState {
                 | Some(Operator::FindBackward { .. })
                 | Some(Operator::Mark)
                 | Some(Operator::Register)
+                | Some(Operator::RecordRegister)
+                | Some(Operator::ReplayRegister)
                 | Some(Operator::Jump { .. })
         )
     }
@@ -238,9 +336,13 @@ impl EditorState {
 
     pub fn clip_at_line_ends(&self) -> bool {
         match self.mode {
-            Mode::Insert | Mode::Visual | Mode::VisualLine | Mode::VisualBlock | Mode::Replace => {
-                false
-            }
+            Mode::Insert
+            | Mode::Visual
+            | Mode::VisualLine
+            | Mode::VisualBlock
+            | Mode::Replace
+            | Mode::HelixNormal
+            | Mode::HelixSelect => false,
             Mode::Normal => true,
         }
     }
@@ -258,10 +360,13 @@ impl EditorState {
                 Mode::Visual | Mode::VisualLine | Mode::VisualBlock => "visual",
                 Mode::Insert => "insert",
                 Mode::Replace => "replace",
+                Mode::HelixNormal => "helix_normal",
+                Mode::HelixSelect => "helix_select",
             },
         );
 
-        if self.vim_controlled() {
+        // Helix mode binds the keys that vim controls to its own actions.
+        if self.vim_controlled() && !self.mode.is_helix() {
             context.add("VimControl");
         }
 
@@ -318,7 +423,10 @@ impl Operator {
             Operator::Uppercase => "gU",
             Operator::Lowercase => "gu",
             Operator::OppositeCase => "g~",
+            Operator::Filter => "!",
             Operator::Register => "\"",
+            Operator::RecordRegister => "q",
+            Operator::ReplayRegister => "@",
         }
     }
 
@@ -330,6 +438,8 @@ impl Operator {
             | Operator::Jump { .. }
             | Operator::FindBackward { .. }
             | Operator::Register
+            | Operator::RecordRegister
+            | Operator::ReplayRegister
             | Operator::Replace
             | Operator::AddSurrounds { target: Some(_) }
             | Operator::ChangeSurrounds { .. }
@@ -13,6 +13,8 @@ use std::sync::Arc;
 pub enum SurroundsType {
     Motion(Motion),
     Object(Object),
+    /// The selections of Helix's normal mode.
+    Selection,
 }
 
 // This exists so that we can have Deserialize on Operators, but not on Motions.
@@ -83,6 +85,15 @@ pub fn add_surrounds(text: Arc<str>, target: SurroundsType, cx: &mut WindowConte
                                 });
                             range
                         }
+                        SurroundsType::Selection => {
+                            // An empty selection is a block cursor on the character after it.
+                            let end = if selection.is_empty() {
+                                movement::right(&display_map, selection.start)
+                            } else {
+                                selection.end
+                            };
+                            Some(selection.start..end)
+                        }
                     };
 
                     if let Some(range) = range {
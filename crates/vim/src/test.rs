@@ -1198,6 +1198,45 @@ async fn test_caret_mark(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_global_marks(cx: &mut TestAppContext) {
+    let mut cx = VimTestContext::new(cx, true).await;
+
+    cx.set_state(
+        indoc! {"
+        one
+        twˇo
+        three"},
+        Mode::Normal,
+    );
+    cx.simulate_keystrokes("m shift-a");
+    let marked_editor = cx.editor.entity_id();
+
+    cx.dispatch_action(workspace::NewFile);
+    cx.run_until_parked();
+    let new_editor = cx.workspace(|workspace, cx| workspace.active_item(cx).unwrap().item_id());
+    assert_ne!(new_editor, marked_editor);
+
+    // The mark moves the cursor back to the editor it was set in.
+    cx.simulate_keystrokes("' shift-a");
+    cx.run_until_parked();
+    let active_editor = cx.workspace(|workspace, cx| workspace.active_item(cx).unwrap().item_id());
+    assert_eq!(active_editor, marked_editor);
+    cx.assert_state(
+        indoc! {"
+        one
+        ˇtwo
+        three"},
+        Mode::Normal,
+    );
+
+    // Jumping back returns to the other editor.
+    cx.simulate_keystrokes("ctrl-o");
+    cx.run_until_parked();
+    let active_editor = cx.workspace(|workspace, cx| workspace.active_item(cx).unwrap().item_id());
+    assert_eq!(active_editor, new_editor);
+}
+
 #[cfg(target_os = "macos")]
 #[gpui::test]
 async fn test_dw_eol(cx: &mut gpui::TestAppContext) {
@@ -440,8 +440,11 @@ impl NeovimConnection {
                     Point::new(selection_row, selection_col)..Point::new(cursor_row, cursor_col),
                 )
             }
-            Mode::Insert | Mode::Normal | Mode::Replace => selections
-                .push(Point::new(selection_row, selection_col)..Point::new(cursor_row, cursor_col)),
+            Mode::Insert | Mode::Normal | Mode::Replace | Mode::HelixNormal | Mode::HelixSelect => {
+                selections.push(
+                    Point::new(selection_row, selection_col)..Point::new(cursor_row, cursor_col),
+                )
+            }
         }
 
         let ranges = encode_ranges(&text, &selections);
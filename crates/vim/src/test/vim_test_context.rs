@@ -21,6 +21,7 @@ impl VimTestContext {
             cx.set_global(settings);
             release_channel::init(SemanticVersion::default(), cx);
             command_palette::init(cx);
+            keyboard_macros::init(cx);
             crate::init(cx);
         });
     }
@@ -110,6 +111,14 @@ impl VimTestContext {
         })
     }
 
+    pub fn enable_helix(&mut self) {
+        self.cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<HelixModeSetting>(cx, |s| *s = Some(true));
+            });
+        })
+    }
+
     pub fn disable_vim(&mut self) {
         self.cx.update(|cx| {
             SettingsStore::update_global(cx, |store, cx| {
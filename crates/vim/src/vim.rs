@@ -6,11 +6,15 @@ mod test;
 mod change_list;
 mod command;
 mod editor_events;
+mod helix;
 mod insert;
+mod jump_list;
 mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod persistence;
+mod pickers;
 mod replace;
 mod state;
 mod surrounds;
@@ -28,19 +32,22 @@ use gpui::{
     actions, impl_actions, Action, AppContext, EntityId, FocusableView, Global, KeystrokeEvent,
     Subscription, UpdateGlobal, View, ViewContext, WeakView, WindowContext,
 };
+use keyboard_macros::MacroRecorder;
 use language::{CursorShape, Point, SelectionGoal, TransactionId};
 pub use mode_indicator::ModeIndicator;
 use motion::Motion;
 use normal::{mark::create_visual_marks, normal_replace};
+use persistence::VIM_DB;
 use replace::multi_replace;
 use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_derive::Serialize;
 use settings::{update_settings_file, Settings, SettingsSources, SettingsStore};
-use state::{EditorState, Mode, Operator, RecordedSelection, Register, WorkspaceState};
-use std::{ops::Range, sync::Arc};
+use state::{EditorState, FileMark, Mode, Operator, RecordedSelection, Register, WorkspaceState};
+use std::{ops::Range, path::Path, sync::Arc};
 use surrounds::{add_surrounds, change_surrounds, delete_surrounds};
 use ui::BorrowAppContext;
+use util::ResultExt;
 use visual::{visual_block_motion, visual_replace};
 use workspace::{self, Workspace};
 
@@ -51,6 +58,12 @@ use crate::state::ReplayableAction;
 /// Default: false
 pub struct VimModeSetting(pub bool);
 
+/// Whether or not to enable Helix-style editing, where motions select text and actions apply
+/// to the selections. Turns on the rest of Vim mode too.
+///
+/// Default: false
+pub struct HelixModeSetting(pub bool);
+
 /// An Action to Switch between modes
 #[derive(Clone, Deserialize, PartialEq)]
 pub struct SwitchMode(pub Mode);
@@ -91,9 +104,17 @@ impl_actions!(vim, [SwitchMode, PushOperator, Number, SelectRegister]);
 pub fn init(cx: &mut AppContext) {
     cx.set_global(Vim::default());
     VimModeSetting::register(cx);
+    HelixModeSetting::register(cx);
     VimSettings::register(cx);
 
     cx.observe_keystrokes(observe_keystrokes).detach();
+    // Actions replayed by keyboard macros don't come from keystrokes, yet `.` has to repeat the
+    // changes they make.
+    MacroRecorder::update_global(cx, |recorder, _| {
+        recorder.observe_replayed_actions(|action, cx| {
+            Vim::update(cx, |vim, _| vim.observe_action(action))
+        })
+    });
     editor_events::init(cx);
 
     cx.observe_new_views(|workspace: &mut Workspace, cx| register(workspace, cx))
@@ -105,15 +126,19 @@ pub fn init(cx: &mut AppContext) {
     CommandPaletteFilter::update_global(cx, |filter, _| {
         filter.hide_namespace(Vim::NAMESPACE);
     });
-    Vim::update_global(cx, |vim, cx| {
-        vim.set_enabled(VimModeSetting::get_global(cx).0, cx)
-    });
-    cx.observe_global::<SettingsStore>(|cx| {
-        Vim::update_global(cx, |vim, cx| {
-            vim.set_enabled(VimModeSetting::get_global(cx).0, cx)
-        });
-    })
-    .detach();
+    update_enabled(cx);
+    cx.observe_global::<SettingsStore>(update_enabled).detach();
+}
+
+fn update_enabled(cx: &mut AppContext) {
+    let enabled = enabled_in_settings(cx);
+    let helix_mode = HelixModeSetting::get_global(cx).0;
+    Vim::update_global(cx, |vim, cx| vim.set_enabled(enabled, helix_mode, cx));
+}
+
+/// Whether Vim mode is on, which it also is in Helix mode.
+fn enabled_in_settings(cx: &AppContext) -> bool {
+    VimModeSetting::get_global(cx).0 || HelixModeSetting::get_global(cx).0
 }
 
 fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
@@ -160,6 +185,9 @@ fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
     object::register(workspace, cx);
     visual::register(workspace, cx);
     change_list::register(workspace, cx);
+    jump_list::register(workspace, cx);
+    pickers::register(workspace, cx);
+    helix::register(workspace, cx);
 }
 
 /// Called whenever an keystroke is typed so vim can observe all actions
@@ -170,18 +198,7 @@ fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext)
         .as_ref()
         .map(|action| action.boxed_clone())
     {
-        Vim::update(cx, |vim, _| {
-            if vim.workspace_state.recording {
-                vim.workspace_state
-                    .recorded_actions
-                    .push(ReplayableAction::Action(action.boxed_clone()));
-
-                if vim.workspace_state.stop_recording_after_next_action {
-                    vim.workspace_state.recording = false;
-                    vim.workspace_state.stop_recording_after_next_action = false;
-                }
-            }
-        });
+        Vim::update(cx, |vim, _| vim.observe_action(action.as_ref()));
 
         // Keystroke is handled by the vim system, so continue forward
         if action.name().starts_with("vim::") {
@@ -201,7 +218,9 @@ fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext)
             | Operator::DeleteSurrounds
             | Operator::Mark
             | Operator::Jump { .. }
-            | Operator::Register,
+            | Operator::Register
+            | Operator::RecordRegister
+            | Operator::ReplayRegister,
         ) => {}
         Some(_) => {
             vim.clear_operator(cx);
@@ -216,6 +235,7 @@ struct Vim {
     active_editor: Option<WeakView<Editor>>,
     editor_subscription: Option<Subscription>,
     enabled: bool,
+    helix_mode: bool,
     editor_states: HashMap<EntityId, EditorState>,
     workspace_state: WorkspaceState,
     default_state: EditorState,
@@ -243,6 +263,20 @@ impl Vim {
             return;
         }
 
+        let previous_editor = self
+            .active_editor
+            .as_ref()
+            .and_then(|previous| previous.upgrade())
+            .filter(|previous| *previous != editor);
+        if let Some(previous_editor) = previous_editor {
+            // Switching to another editor is a jump, unless the jump list is what switched.
+            if !std::mem::take(&mut self.workspace_state.navigating_jump_list) {
+                let previous_editor = previous_editor.read(cx);
+                let position = previous_editor.selections.newest_anchor().head();
+                jump_list::push_to_jump_list(self, previous_editor, position, cx);
+            }
+        }
+
         self.active_editor = Some(editor.clone().downgrade());
         self.editor_subscription = Some(cx.subscribe(&editor, |editor, event, cx| match event {
             EditorEvent::SelectionsChanged { local: true } => {
@@ -288,6 +322,20 @@ impl Vim {
         self.sync_vim_settings(cx);
     }
 
+    /// Records the action for `.` to replay, if a change is being recorded.
+    fn observe_action(&mut self, action: &dyn Action) {
+        if self.workspace_state.recording {
+            self.workspace_state
+                .recorded_actions
+                .push(ReplayableAction::Action(action.boxed_clone()));
+
+            if self.workspace_state.stop_recording_after_next_action {
+                self.workspace_state.recording = false;
+                self.workspace_state.stop_recording_after_next_action = false;
+            }
+        }
+    }
+
     fn record_insertion(
         text: &Arc<str>,
         range_to_replace: Option<Range<isize>>,
@@ -410,6 +458,12 @@ impl Vim {
     }
 
     fn switch_mode(&mut self, mode: Mode, leave_selections: bool, cx: &mut WindowContext) {
+        // Helix's normal mode takes the place of vim's.
+        let mode = if mode == Mode::Normal {
+            self.default_mode()
+        } else {
+            mode
+        };
         let state = self.state();
         let last_mode = state.mode;
         let prior_mode = state.last_mode;
@@ -552,6 +606,8 @@ impl Vim {
         linewise: bool,
         cx: &mut ViewContext<Editor>,
     ) {
+        // Every write sets the unnamed register, and at most a few others besides.
+        let mut written = vec!['"'];
         if let Some(register) = register {
             let lower = register.to_lowercase().next().unwrap_or(register);
             if lower != register {
@@ -561,6 +617,7 @@ impl Vim {
                 current.clipboard_selections.take();
                 let yanked = current.clone();
                 self.workspace_state.registers.insert('"', yanked);
+                written.push(lower);
             } else {
                 self.workspace_state.registers.insert('"', content.clone());
                 match lower {
@@ -577,9 +634,11 @@ impl Vim {
                     '"' => {
                         self.workspace_state.registers.insert('0', content.clone());
                         self.workspace_state.registers.insert('"', content);
+                        written.push('0');
                     }
                     _ => {
                         self.workspace_state.registers.insert(lower, content);
+                        written.push(lower);
                     }
                 }
             }
@@ -599,14 +658,17 @@ impl Vim {
             self.workspace_state.registers.insert('"', content.clone());
             if is_yank {
                 self.workspace_state.registers.insert('0', content);
+                written.push('0');
             } else {
                 let contains_newline = content.text.contains('\n');
                 if !contains_newline {
                     self.workspace_state.registers.insert('-', content.clone());
+                    written.push('-');
                 }
                 if linewise || contains_newline {
                     let mut content = content;
                     for i in '1'..'8' {
+                        written.push(i);
                         if let Some(moved) = self.workspace_state.registers.insert(i, content) {
                             content = moved;
                         } else {
@@ -616,6 +678,80 @@ impl Vim {
                 }
             }
         }
+
+        let written_registers = written
+            .into_iter()
+            .filter_map(|name| self.persisted_register(name))
+            .collect::<Vec<_>>();
+        if !written_registers.is_empty() {
+            cx.background_executor()
+                .spawn(VIM_DB.save_registers(written_registers))
+                .detach_and_log_err(cx);
+        }
+    }
+
+    /// The register with the given name, if it's saved to be restored on the next start, as
+    /// with vim's viminfo, with its name, text and serialized clipboard selections.
+    fn persisted_register(&self, name: char) -> Option<(String, String, Option<String>)> {
+        if !name.is_ascii_alphanumeric() && !matches!(name, '"' | '-') {
+            return None;
+        }
+        let register = self.workspace_state.registers.get(&name)?;
+        let clipboard_selections = register
+            .clipboard_selections
+            .as_ref()
+            .and_then(|selections| serde_json::to_string(selections).log_err());
+        Some((
+            name.to_string(),
+            register.text.to_string(),
+            clipboard_selections,
+        ))
+    }
+
+    /// Restores the registers and marks saved by previous sessions, keeping those set since.
+    fn restore_persisted_state(cx: &mut AppContext) {
+        let registers = cx
+            .background_executor()
+            .spawn(async move { VIM_DB.saved_registers() });
+        let marks = cx
+            .background_executor()
+            .spawn(async move { VIM_DB.saved_marks() });
+        cx.spawn(|mut cx| async move {
+            let registers = registers.await?;
+            let marks = marks.await?;
+            cx.update_global(|vim: &mut Vim, _| {
+                let state = &mut vim.workspace_state;
+                for (name, text, clipboard_selections) in registers {
+                    let Some(name) = name.chars().next() else {
+                        continue;
+                    };
+                    state.registers.entry(name).or_insert_with(|| Register {
+                        text: text.into(),
+                        clipboard_selections: clipboard_selections
+                            .and_then(|selections| serde_json::from_str(&selections).log_err()),
+                    });
+                }
+                for (path, name, row, column) in marks {
+                    let path = Arc::<Path>::from(path);
+                    let point = Point::new(row, column);
+                    if normal::mark::is_global_mark(&name) {
+                        state.global_marks.entry(name).or_insert(FileMark {
+                            anchor: None,
+                            path: Some(path),
+                            point,
+                        });
+                    } else {
+                        state
+                            .file_marks
+                            .entry(path)
+                            .or_default()
+                            .entry(name)
+                            .or_insert(point);
+                    }
+                }
+            })
+        })
+        .detach_and_log_err(cx);
     }
 
     fn read_register(
@@ -855,7 +991,7 @@ impl Vim {
                 _ => Vim::update(cx, |vim, cx| vim.clear_operator(cx)),
             },
             Some(Operator::AddSurrounds { target }) => match Vim::read(cx).state().mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal | Mode::HelixSelect => {
                     if let Some(target) = target {
                         add_surrounds(text, target, cx);
                         Vim::update(cx, |vim, cx| vim.clear_operator(cx));
@@ -864,7 +1000,7 @@ impl Vim {
                 _ => Vim::update(cx, |vim, cx| vim.clear_operator(cx)),
             },
             Some(Operator::ChangeSurrounds { target }) => match Vim::read(cx).state().mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal | Mode::HelixSelect => {
                     if let Some(target) = target {
                         change_surrounds(text, target, cx);
                         Vim::update(cx, |vim, cx| vim.clear_operator(cx));
@@ -873,7 +1009,7 @@ impl Vim {
                 _ => Vim::update(cx, |vim, cx| vim.clear_operator(cx)),
             },
             Some(Operator::DeleteSurrounds) => match Vim::read(cx).state().mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal | Mode::HelixSelect => {
                     delete_surrounds(text, cx);
                     Vim::update(cx, |vim, cx| vim.clear_operator(cx));
                 }
@@ -903,6 +1039,12 @@ impl Vim {
                 }
             }),
             Some(Operator::Jump { line }) => normal::mark::jump(text, line, cx),
+            Some(Operator::RecordRegister) => {
+                Vim::update(cx, |vim, cx| normal::repeat::record_register(vim, text, cx))
+            }
+            Some(Operator::ReplayRegister) => {
+                Vim::update(cx, |vim, cx| normal::repeat::replay_register(vim, text, cx))
+            }
             _ => match Vim::read(cx).state().mode {
                 Mode::Replace => multi_replace(text, cx),
                 _ => {}
@@ -910,8 +1052,8 @@ impl Vim {
         }
     }
 
-    fn set_enabled(&mut self, enabled: bool, cx: &mut AppContext) {
-        if self.enabled == enabled {
+    fn set_enabled(&mut self, enabled: bool, helix_mode: bool, cx: &mut AppContext) {
+        if self.enabled == enabled && self.helix_mode == helix_mode {
             return;
         }
         if !enabled {
@@ -925,13 +1067,28 @@ impl Vim {
             return;
         }
 
-        self.enabled = true;
-        CommandPaletteFilter::update_global(cx, |filter, _| {
-            filter.show_namespace(Self::NAMESPACE);
-        });
-        CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
-            interceptor.set(Box::new(command::command_interceptor));
-        });
+        if !self.enabled {
+            self.enabled = true;
+            // Tests share a single in-memory database, so they start without the state of others.
+            if !cfg!(test) {
+                Self::restore_persisted_state(cx);
+            }
+            CommandPaletteFilter::update_global(cx, |filter, _| {
+                filter.show_namespace(Self::NAMESPACE);
+            });
+            CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
+                interceptor.set(Box::new(command::command_interceptor));
+            });
+        }
+
+        self.helix_mode = helix_mode;
+        let default_mode = self.default_mode();
+        self.default_state.mode = default_mode;
+        for state in self.editor_states.values_mut() {
+            if matches!(state.mode, Mode::Normal) || state.mode.is_helix() {
+                state.mode = default_mode;
+            }
+        }
 
         if let Some(active_window) = cx
             .active_window()
@@ -949,6 +1106,16 @@ impl Vim {
         }
     }
 
+    /// The mode that editors return to from other modes: Helix's normal mode when Helix mode is
+    /// enabled, and vim's otherwise.
+    fn default_mode(&self) -> Mode {
+        if self.helix_mode {
+            Mode::HelixNormal
+        } else {
+            Mode::Normal
+        }
+    }
+
     /// Returns the state of the active editor.
     pub fn state(&self) -> &EditorState {
         if let Some(active_editor) = self.active_editor.as_ref() {
@@ -977,7 +1144,8 @@ impl Vim {
             let state = vim.state();
             editor.set_cursor_shape(state.cursor_shape(), cx);
             editor.set_clip_at_line_ends(state.clip_at_line_ends(), cx);
-            editor.set_collapse_matches(true);
+            // Searching in Helix's modes selects the matches rather than moving to them.
+            editor.set_collapse_matches(!state.mode.is_helix());
             editor.set_input_enabled(!state.vim_controlled());
             editor.set_autoindent(state.should_autoindent());
             editor.selections.line_mode = matches!(state.mode, Mode::VisualLine);
@@ -1015,6 +1183,18 @@ impl Settings for VimModeSetting {
     }
 }
 
+impl Settings for HelixModeSetting {
+    const KEY: Option<&'static str> = Some("helix_mode");
+
+    type FileContent = Option<bool>;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        Ok(Self(sources.user.copied().flatten().unwrap_or(
+            sources.default.ok_or_else(Self::missing_default)?,
+        )))
+    }
+}
+
 /// Controls when to use system clipboard.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -1,11 +1,18 @@
-use project::TaskSourceKind;
-use task::{ResolvedTask, TaskContext, TaskTemplate};
+use std::{path::Path, time::Duration};
+
+use anyhow::anyhow;
+use project::{TaskSourceKind, WorktreeId};
+use task::{ResolvedTask, RevealStrategy, TaskContext, TaskTemplate};
 use ui::ViewContext;
 
 use crate::Workspace;
 
+/// How long to wait for more changes before rerunning a watched task, so that saving several files at once,
+/// or a save reported both by the editor and by the file system, reruns it only once.
+const WATCHED_TASK_RERUN_DEBOUNCE: Duration = Duration::from_millis(500);
+
 pub fn schedule_task(
-    workspace: &Workspace,
+    workspace: &mut Workspace,
     task_source_kind: TaskSourceKind,
     task_to_resolve: &TaskTemplate,
     task_cx: &TaskContext,
@@ -26,21 +33,132 @@ pub fn schedule_task(
 }
 
 pub fn schedule_resolved_task(
-    workspace: &Workspace,
+    workspace: &mut Workspace,
     task_source_kind: TaskSourceKind,
     mut resolved_task: ResolvedTask,
     omit_history: bool,
     cx: &mut ViewContext<'_, Workspace>,
 ) {
-    if let Some(spawn_in_terminal) = resolved_task.resolved.take() {
-        if !omit_history {
-            resolved_task.resolved = Some(spawn_in_terminal.clone());
+    let missing_inputs = resolved_task.missing_inputs().cloned().collect::<Vec<_>>();
+    if !missing_inputs.is_empty() {
+        cx.spawn(|workspace, mut cx| async move {
+            let Some(values) = workspace
+                .update(&mut cx, |workspace, cx| {
+                    workspace.prompt_for_task_inputs(missing_inputs, cx)
+                })?
+                .await?
+            else {
+                return Ok(());
+            };
+            workspace.update(&mut cx, |workspace, cx| {
+                match resolved_task.resolve_inputs(&task_source_kind.to_id_base(), values) {
+                    Some(resolved_task) => schedule_resolved_task(
+                        workspace,
+                        task_source_kind,
+                        resolved_task,
+                        omit_history,
+                        cx,
+                    ),
+                    None => workspace.show_error(
+                        &anyhow!(
+                            "Failed to resolve task '{}' with its inputs",
+                            resolved_task.original_task().label
+                        ),
+                        cx,
+                    ),
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+        return;
+    }
+    if !resolved_task.original_task().depends_on.is_empty() {
+        let templates = workspace
+            .project()
+            .read(cx)
+            .task_inventory()
+            .read(cx)
+            .list_tasks(None, task_source_kind.worktree());
+        // Prefer the tasks defined next to the one depending on them, when several tasks share a label.
+        let find_template = |label: &str| {
+            templates
+                .iter()
+                .filter(|(_, template)| template.label == label)
+                .min_by_key(|(kind, _)| kind != &task_source_kind)
+                .map(|(kind, template)| (kind.to_id_base(), template.clone()))
+        };
+        if let Err(error) = resolved_task.resolve_dependencies(find_template) {
+            workspace.show_error(&error, cx);
+            return;
+        }
+    }
+    if let Some(spawn_in_terminal) = resolved_task.resolved.clone() {
+        let watch = !resolved_task.original_task().watch.is_empty();
+        if watch || !omit_history {
             workspace.project().update(cx, |project, cx| {
-                project.task_inventory().update(cx, |inventory, _| {
-                    inventory.task_scheduled(task_source_kind, resolved_task);
+                project.task_inventory().update(cx, |inventory, cx| {
+                    if watch {
+                        inventory.watch_task(task_source_kind.clone(), resolved_task.clone(), cx);
+                    }
+                    if !omit_history {
+                        inventory.task_scheduled(task_source_kind, resolved_task);
+                    }
                 })
             });
         }
         cx.emit(crate::Event::SpawnTask(spawn_in_terminal));
     }
 }
+
+/// Reruns the watched tasks matching any of the changed paths of the worktree, once the changes settle.
+/// Each rerun replaces the previous run of the task in its terminal, without revealing it.
+pub(crate) fn rerun_watched_tasks<'a>(
+    workspace: &mut Workspace,
+    worktree_id: WorktreeId,
+    changed_paths: impl IntoIterator<Item = &'a Path>,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let watched_tasks = workspace
+        .project()
+        .read(cx)
+        .task_inventory()
+        .read(cx)
+        .watched_tasks_for_paths(worktree_id, changed_paths);
+    for (task_source_kind, mut resolved_task) in watched_tasks {
+        if let Some(resolved) = resolved_task.resolved.as_mut() {
+            resolved.allow_concurrent_runs = true;
+            resolved.use_new_terminal = false;
+            resolved.reveal = RevealStrategy::Never;
+        }
+        let task_id = resolved_task.id.clone();
+        // Replacing the pending rerun of the task drops it, restarting the debounce.
+        workspace.watched_task_reruns.insert(
+            task_id.clone(),
+            cx.spawn(|workspace, mut cx| async move {
+                cx.background_executor()
+                    .timer(WATCHED_TASK_RERUN_DEBOUNCE)
+                    .await;
+                workspace
+                    .update(&mut cx, |workspace, cx| {
+                        workspace.watched_task_reruns.remove(&task_id);
+                        let still_watched = workspace
+                            .project()
+                            .read(cx)
+                            .task_inventory()
+                            .read(cx)
+                            .is_task_watched(&task_id);
+                        if still_watched {
+                            schedule_resolved_task(
+                                workspace,
+                                task_source_kind,
+                                resolved_task,
+                                true,
+                                cx,
+                            );
+                        }
+                    })
+                    .ok();
+            }),
+        );
+    }
+}
@@ -51,7 +51,7 @@ pub use persistence::{
     WorkspaceDb, DB as WORKSPACE_DB,
 };
 use postage::stream::Stream;
-use project::{Project, ProjectEntryId, ProjectPath, Worktree, WorktreeId};
+use project::{PathChange, Project, ProjectEntryId, ProjectPath, Worktree, WorktreeId};
 use serde::Deserialize;
 use settings::Settings;
 use shared_screen::SharedScreen;
@@ -74,7 +74,7 @@ use std::{
     sync::{atomic::AtomicUsize, Arc, Weak},
     time::Duration,
 };
-use task::SpawnInTerminal;
+use task::{SpawnInTerminal, TaskId, TaskInput};
 use theme::{ActiveTheme, SystemAppearance, ThemeSettings};
 pub use toolbar::{Toolbar, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
 pub use ui;
@@ -557,6 +557,14 @@ type PromptForNewPath = Box<
     dyn Fn(&mut Workspace, &mut ViewContext<Workspace>) -> oneshot::Receiver<Option<ProjectPath>>,
 >;
 
+type PromptForTaskInputs = Box<
+    dyn Fn(
+        &mut Workspace,
+        Vec<TaskInput>,
+        &mut ViewContext<Workspace>,
+    ) -> oneshot::Receiver<Option<Vec<(String, String)>>>,
+>;
+
 /// Collects everything project-related for a certain window opened.
 /// In some way, is a counterpart of a window, as the [`WindowHandle`] could be downcast into `Workspace`.
 ///
@@ -599,6 +607,8 @@ pub struct Workspace {
     centered_layout: bool,
     bounds_save_task_queued: Option<Task<()>>,
     on_prompt_for_new_path: Option<PromptForNewPath>,
+    on_prompt_for_task_inputs: Option<PromptForTaskInputs>,
+    watched_task_reruns: HashMap<TaskId, Task<()>>,
     render_disconnected_overlay:
         Option<Box<dyn Fn(&mut Self, &mut ViewContext<Self>) -> AnyElement>>,
 }
@@ -657,6 +667,24 @@ impl Workspace {
                     cx.remove_window();
                 }
 
+                project::Event::BufferSaved(project_path) => {
+                    tasks::rerun_watched_tasks(
+                        this,
+                        project_path.worktree_id,
+                        [project_path.path.as_ref()],
+                        cx,
+                    );
+                }
+
+                project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                    // Entries found by the initial scan of a worktree were not changed.
+                    let changed_paths = changes
+                        .iter()
+                        .filter(|(_, _, change)| *change != PathChange::Loaded)
+                        .map(|(path, _, _)| path.as_ref());
+                    tasks::rerun_watched_tasks(this, *worktree_id, changed_paths, cx);
+                }
+
                 project::Event::DeletedEntry(entry_id) => {
                     for pane in this.panes.iter() {
                         pane.update(cx, |pane, cx| {
@@ -879,6 +907,8 @@ impl Workspace {
             centered_layout: false,
             bounds_save_task_queued: None,
             on_prompt_for_new_path: None,
+            on_prompt_for_task_inputs: None,
+            watched_task_reruns: HashMap::default(),
             render_disconnected_overlay: None,
         }
     }
@@ -1256,6 +1286,10 @@ impl Workspace {
         self.on_prompt_for_new_path = Some(prompt)
     }
 
+    pub fn set_prompt_for_task_inputs(&mut self, prompt: PromptForTaskInputs) {
+        self.on_prompt_for_task_inputs = Some(prompt)
+    }
+
     pub fn set_render_disconnected_overlay(
         &mut self,
         render: impl Fn(&mut Self, &mut ViewContext<Self>) -> AnyElement + 'static,
@@ -1312,6 +1346,44 @@ impl Workspace {
         }
     }
 
+    /// Asks for the values of the given task inputs, returning them along with the input ids,
+    /// or `None` if the user cancelled.
+    pub fn prompt_for_task_inputs(
+        &mut self,
+        inputs: Vec<TaskInput>,
+        cx: &mut ViewContext<Self>,
+    ) -> oneshot::Receiver<Option<Vec<(String, String)>>> {
+        if let Some(prompt) = self.on_prompt_for_task_inputs.take() {
+            let rx = prompt(self, inputs, cx);
+            self.on_prompt_for_task_inputs = Some(prompt);
+            rx
+        } else {
+            // With nowhere to ask, fall back to the default values of the inputs.
+            let mut values = Vec::new();
+            let mut inputs_without_value = Vec::new();
+            for input in inputs {
+                match input.default.or_else(|| input.options.first().cloned()) {
+                    Some(value) => values.push((input.id, value)),
+                    None => inputs_without_value.push(input.id),
+                }
+            }
+            let (tx, rx) = oneshot::channel();
+            if inputs_without_value.is_empty() {
+                tx.send(Some(values)).ok();
+            } else {
+                self.show_error(
+                    &anyhow!(
+                        "No value for the task inputs: {}",
+                        inputs_without_value.join(", ")
+                    ),
+                    cx,
+                );
+                tx.send(None).ok();
+            }
+            rx
+        }
+    }
+
     pub fn titlebar_item(&self) -> Option<AnyView> {
         self.titlebar_item.clone()
     }
@@ -6164,6 +6236,120 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_scheduling_task_with_unknown_dependency(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let spawned_labels = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let spawned_labels = spawned_labels.clone();
+            cx.subscribe(&workspace, move |_, event, _| {
+                if let Event::SpawnTask(spawn_in_terminal) = event {
+                    spawned_labels
+                        .borrow_mut()
+                        .push(spawn_in_terminal.label.clone());
+                }
+            })
+            .detach();
+        });
+
+        let template = task::TaskTemplate {
+            label: "build and run".to_string(),
+            command: "run".to_string(),
+            depends_on: vec!["build".to_string()],
+            ..task::TaskTemplate::default()
+        };
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.notification_ids().is_empty());
+            tasks::schedule_task(
+                workspace,
+                project::TaskSourceKind::UserInput,
+                &template,
+                &task::TaskContext::default(),
+                false,
+                cx,
+            );
+            assert_eq!(
+                workspace.notification_ids().len(),
+                1,
+                "The unknown dependency should be reported"
+            );
+        });
+        cx.run_until_parked();
+        assert!(
+            spawned_labels.borrow().is_empty(),
+            "A task whose dependencies cannot be resolved should not be spawned"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_scheduling_task_with_input_without_value(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let spawned_labels = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let spawned_labels = spawned_labels.clone();
+            cx.subscribe(&workspace, move |_, event, _| {
+                if let Event::SpawnTask(spawn_in_terminal) = event {
+                    spawned_labels
+                        .borrow_mut()
+                        .push(spawn_in_terminal.label.clone());
+                }
+            })
+            .detach();
+        });
+
+        let template = task::TaskTemplate {
+            label: "deploy".to_string(),
+            command: "deploy ${input:target} ${input:region}".to_string(),
+            inputs: vec![
+                TaskInput {
+                    id: "target".to_string(),
+                    kind: task::TaskInputKind::PromptString,
+                    description: None,
+                    default: None,
+                    options: Vec::new(),
+                },
+                TaskInput {
+                    id: "region".to_string(),
+                    kind: task::TaskInputKind::PickString,
+                    description: None,
+                    default: None,
+                    options: vec!["eu".to_string(), "us".to_string()],
+                },
+            ],
+            ..task::TaskTemplate::default()
+        };
+        workspace.update(cx, |workspace, cx| {
+            tasks::schedule_task(
+                workspace,
+                project::TaskSourceKind::UserInput,
+                &template,
+                &task::TaskContext::default(),
+                false,
+                cx,
+            );
+        });
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, _| {
+            assert_eq!(
+                workspace.notification_ids().len(),
+                1,
+                "The input without a value should be reported"
+            );
+        });
+        assert!(
+            spawned_labels.borrow().is_empty(),
+            "A task with an input without a value should not be spawned"
+        );
+    }
+
     mod register_project_item_tests {
         use ui::Context as _;
 
@@ -189,9 +189,13 @@ fn init_ui(app_state: Arc<AppState>, cx: &mut AppContext) -> Result<()> {
     project_symbols::init(cx);
     project_panel::init(Assets, cx);
     outline_panel::init(Assets, cx);
+    call_hierarchy_panel::init(cx);
+    debugger_ui::init(cx);
+    test_panel::init(cx);
     tasks_ui::init(cx);
     channel::init(&app_state.client.clone(), app_state.user_store.clone(), cx);
     search::init(cx);
+    keyboard_macros::init(cx);
     vim::init(cx);
     terminal_view::init(cx);
 
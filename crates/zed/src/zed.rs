@@ -8,8 +8,10 @@ mod open_listener;
 
 pub use app_menus::*;
 use breadcrumbs::Breadcrumbs;
+use call_hierarchy_panel::CallHierarchyPanel;
 use client::ZED_URL_SCHEME;
 use collections::VecDeque;
+use debugger_ui::DebugPanel;
 use editor::{scroll::Autoscroll, Editor, MultiBuffer};
 use gpui::{
     actions, point, px, AppContext, AsyncAppContext, Context, FocusableView, MenuItem, PromptLevel,
@@ -38,9 +40,10 @@ use workspace::notifications::NotificationId;
 
 use paths::{LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH};
 use terminal_view::terminal_panel::{self, TerminalPanel};
+use test_panel::TestPanel;
 use util::{asset_str, ResultExt};
 use uuid::Uuid;
-use vim::VimModeSetting;
+use vim::{HelixModeSetting, VimModeSetting};
 use welcome::BaseKeymap;
 use workspace::{
     create_and_open_local_file, notifications::simple_message_notification::MessageNotification,
@@ -145,6 +148,7 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
             activity_indicator::ActivityIndicator::new(workspace, app_state.languages.clone(), cx);
         let active_buffer_language =
             cx.new_view(|_| language_selector::ActiveBufferLanguage::new(workspace));
+        let macro_recording_indicator = cx.new_view(keyboard_macros::RecordingIndicator::new);
         let vim_mode_indicator = cx.new_view(|cx| vim::ModeIndicator::new(cx));
         let cursor_position =
             cx.new_view(|_| go_to_line::cursor_position::CursorPosition::new(workspace));
@@ -153,6 +157,7 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
             status_bar.add_left_item(activity_indicator, cx);
             status_bar.add_right_item(inline_completion_button, cx);
             status_bar.add_right_item(active_buffer_language, cx);
+            status_bar.add_right_item(macro_recording_indicator, cx);
             status_bar.add_right_item(vim_mode_indicator, cx);
             status_bar.add_right_item(cursor_position, cx);
         });
@@ -196,7 +201,11 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                 assistant::AssistantPanel::load(workspace_handle.clone(), cx.clone());
             let project_panel = ProjectPanel::load(workspace_handle.clone(), cx.clone());
             let outline_panel = OutlinePanel::load(workspace_handle.clone(), cx.clone());
+            let call_hierarchy_panel =
+                CallHierarchyPanel::load(workspace_handle.clone(), cx.clone());
             let terminal_panel = TerminalPanel::load(workspace_handle.clone(), cx.clone());
+            let debug_panel = DebugPanel::load(workspace_handle.clone(), cx.clone());
+            let test_panel = TestPanel::load(workspace_handle.clone(), cx.clone());
             let channels_panel =
                 collab_ui::collab_panel::CollabPanel::load(workspace_handle.clone(), cx.clone());
             let chat_panel =
@@ -209,7 +218,10 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
             let (
                 project_panel,
                 outline_panel,
+                call_hierarchy_panel,
                 terminal_panel,
+                debug_panel,
+                test_panel,
                 assistant_panel,
                 channels_panel,
                 chat_panel,
@@ -217,7 +229,10 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
             ) = futures::try_join!(
                 project_panel,
                 outline_panel,
+                call_hierarchy_panel,
                 terminal_panel,
+                debug_panel,
+                test_panel,
                 assistant_panel,
                 channels_panel,
                 chat_panel,
@@ -228,7 +243,10 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                 workspace.add_panel(assistant_panel, cx);
                 workspace.add_panel(project_panel, cx);
                 workspace.add_panel(outline_panel, cx);
+                workspace.add_panel(call_hierarchy_panel, cx);
                 workspace.add_panel(terminal_panel, cx);
+                workspace.add_panel(debug_panel, cx);
+                workspace.add_panel(test_panel, cx);
                 workspace.add_panel(channels_panel, cx);
                 workspace.add_panel(chat_panel, cx);
                 workspace.add_panel(notification_panel, cx);
@@ -645,13 +663,14 @@ pub fn handle_keymap_file_changes(
 ) {
     BaseKeymap::register(cx);
     VimModeSetting::register(cx);
+    HelixModeSetting::register(cx);
 
     let (base_keymap_tx, mut base_keymap_rx) = mpsc::unbounded();
     let mut old_base_keymap = *BaseKeymap::get_global(cx);
-    let mut old_vim_enabled = VimModeSetting::get_global(cx).0;
+    let mut old_vim_enabled = vim_keymap_enabled(cx);
     cx.observe_global::<SettingsStore>(move |cx| {
         let new_base_keymap = *BaseKeymap::get_global(cx);
-        let new_vim_enabled = VimModeSetting::get_global(cx).0;
+        let new_vim_enabled = vim_keymap_enabled(cx);
 
         if new_base_keymap != old_base_keymap || new_vim_enabled != old_vim_enabled {
             old_base_keymap = new_base_keymap;
@@ -699,7 +718,7 @@ pub fn load_default_keymap(cx: &mut AppContext) {
     }
 
     KeymapFile::load_asset(DEFAULT_KEYMAP_PATH, cx).unwrap();
-    if VimModeSetting::get_global(cx).0 {
+    if vim_keymap_enabled(cx) {
         KeymapFile::load_asset("keymaps/vim.json", cx).unwrap();
     }
 
@@ -708,6 +727,11 @@ pub fn load_default_keymap(cx: &mut AppContext) {
     }
 }
 
+/// Helix mode's bindings are part of the vim keymap.
+fn vim_keymap_enabled(cx: &AppContext) -> bool {
+    VimModeSetting::get_global(cx).0 || HelixModeSetting::get_global(cx).0
+}
+
 fn open_local_settings_file(
     workspace: &mut Workspace,
     _: &OpenLocalSettings,
@@ -3151,9 +3175,13 @@ mod tests {
             command_palette::init(cx);
             language::init(cx);
             editor::init(cx);
+            keyboard_macros::init(cx);
             collab_ui::init(&app_state, cx);
             project_panel::init((), cx);
             outline_panel::init((), cx);
+            call_hierarchy_panel::init(cx);
+            debugger_ui::init(cx);
+            test_panel::init(cx);
             terminal_view::init(cx);
             assistant::init(app_state.client.clone(), cx);
             tasks_ui::init(cx);
@@ -124,8 +124,11 @@ pub fn app_menus() -> Vec<Menu<'static>> {
                 MenuItem::separator(),
                 MenuItem::action("Project Panel", project_panel::ToggleFocus),
                 MenuItem::action("Outline Panel", outline_panel::ToggleFocus),
+                MenuItem::action("Call Hierarchy Panel", call_hierarchy_panel::ToggleFocus),
                 MenuItem::action("Collab Panel", collab_panel::ToggleFocus),
                 MenuItem::action("Terminal Panel", terminal_panel::ToggleFocus),
+                MenuItem::action("Debug Panel", debugger_ui::ToggleFocus),
+                MenuItem::action("Test Panel", test_panel::ToggleFocus),
                 MenuItem::separator(),
                 MenuItem::action("Diagnostics", diagnostics::Deploy),
                 MenuItem::separator(),
@@ -147,6 +150,8 @@ pub fn app_menus() -> Vec<Menu<'static>> {
                 MenuItem::action("Go to Definition", editor::actions::GoToDefinition),
                 MenuItem::action("Go to Type Definition", editor::actions::GoToTypeDefinition),
                 MenuItem::action("Find All References", editor::actions::FindAllReferences),
+                MenuItem::action("Find Supertypes", editor::actions::FindSupertypes),
+                MenuItem::action("Find Subtypes", editor::actions::FindSubtypes),
                 MenuItem::separator(),
                 MenuItem::action("Next Problem", editor::actions::GoToDiagnostic),
                 MenuItem::action("Previous Problem", editor::actions::GoToPrevDiagnostic),